@@ -0,0 +1,180 @@
+//! Node-level differences between two versions of a module.
+//!
+//! The nodes are matched by their AST IDs, which are kept in the module's metadata and stay the
+//! same when the node is edited. Therefore, the diff is meaningful only between versions of the
+//! same module file, e.g. between its snapshots in the project's history.
+
+use crate::prelude::*;
+
+use crate::definition::DefinitionInfo;
+use crate::definition::DefinitionProvider;
+use crate::graph;
+use crate::graph::GraphInfo;
+use crate::module;
+use crate::node;
+use crate::node::NodeInfo;
+
+
+
+// =================
+// === GraphDiff ===
+// =================
+
+/// A node present in both versions of the graph, but with a different code.
+#[allow(missing_docs)]
+#[derive(Clone, Debug)]
+pub struct ChangedNode {
+    pub old: NodeInfo,
+    pub new: NodeInfo,
+}
+
+/// The differences between two versions of a single graph.
+#[allow(missing_docs)]
+#[derive(Clone, Debug)]
+pub struct GraphDiff {
+    pub graph:   graph::Id,
+    pub added:   Vec<NodeInfo>,
+    pub removed: Vec<NodeInfo>,
+    pub changed: Vec<ChangedNode>,
+}
+
+impl GraphDiff {
+    /// Compare two versions of the graph. A missing version means the graph was added or removed.
+    ///
+    /// A node is changed if its code or its documentation comment is different. The nodes are
+    /// listed in the order of their lines.
+    pub fn new(graph: graph::Id, old: Option<&GraphInfo>, new: Option<&GraphInfo>) -> Self {
+        let nodes = |graph: Option<&GraphInfo>| graph.map(GraphInfo::nodes).unwrap_or_default();
+        let old_nodes = nodes(old);
+        let new_nodes = nodes(new);
+        let old_by_id: HashMap<node::Id, &NodeInfo> =
+            old_nodes.iter().map(|node| (node.id(), node)).collect();
+        let new_ids: HashSet<node::Id> = new_nodes.iter().map(|node| node.id()).collect();
+        let mut added = Vec::new();
+        let mut changed = Vec::new();
+        for node in &new_nodes {
+            match old_by_id.get(&node.id()) {
+                Some(old) if !same_code(old, node) =>
+                    changed.push(ChangedNode { old: (*old).clone(), new: node.clone() }),
+                Some(_) => {}
+                None => added.push(node.clone()),
+            }
+        }
+        let removed = old_nodes.iter().filter(|node| !new_ids.contains(&node.id())).cloned();
+        let removed = removed.collect();
+        Self { graph, added, removed, changed }
+    }
+
+    /// Check if there are no differences.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+fn same_code(old: &NodeInfo, new: &NodeInfo) -> bool {
+    old.ast().repr() == new.ast().repr() && old.documentation_text() == new.documentation_text()
+}
+
+
+
+// ==================
+// === ModuleDiff ===
+// ==================
+
+/// Compare the graphs of the module's top-level definitions in two versions of the module.
+///
+/// The definitions are matched by their names. Only the graphs with any differences are
+/// returned, in the order of definitions in the new version, followed by the removed ones.
+pub fn module_diff(old: &module::Info, new: &module::Info) -> Vec<GraphDiff> {
+    let definitions = |module: &module::Info| -> Vec<(graph::Id, DefinitionInfo)> {
+        let definitions = module.ast.def_iter().map(|definition| definition.item);
+        let definitions = definitions.map(|definition| {
+            (graph::Id::new_single_crumb(definition.name.item.clone()), definition)
+        });
+        definitions.collect()
+    };
+    let old_graphs: HashMap<_, _> = definitions(old)
+        .into_iter()
+        .map(|(id, definition)| (id, GraphInfo::from_definition(definition)))
+        .collect();
+    let new_definitions = definitions(new);
+    let new_ids: HashSet<_> = new_definitions.iter().map(|(id, _)| id.clone()).collect();
+    let mut diffs = Vec::new();
+    for (id, definition) in new_definitions {
+        let new_graph = GraphInfo::from_definition(definition);
+        let old_graph = old_graphs.get(&id);
+        diffs.push(GraphDiff::new(id, old_graph, Some(&new_graph)));
+    }
+    let removed = definitions(old).into_iter().filter(|(id, _)| !new_ids.contains(id));
+    for (id, definition) in removed {
+        let old_graph = GraphInfo::from_definition(definition);
+        diffs.push(GraphDiff::new(id, Some(&old_graph), None));
+    }
+    diffs.retain(|diff| !diff.is_empty());
+    diffs
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::graph::LocationHint;
+    use crate::module::Placement;
+
+    use parser::Parser;
+
+    fn repr(nodes: &[NodeInfo]) -> Vec<String> {
+        nodes.iter().map(|node| node.ast().repr()).collect()
+    }
+
+    #[test]
+    fn diff_of_module_versions() {
+        let parser = Parser::new();
+        let code = "foo = 1\n\nmain =\n    a = 1\n    b = a + 1\n    c = b\n\nbar = 2";
+        let old = module::Info::from(parser.parse_module(code, default()).unwrap());
+        let mut new = old.clone();
+        let main = graph::Id::new_plain_name("main");
+        new.update_definition(&main, |definition| {
+            let mut graph = GraphInfo::from_definition(definition);
+            let nodes = graph.nodes();
+            let edited = parser.parse_line_ast("a + 2").unwrap();
+            graph.edit_node(nodes[1].id(), edited)?;
+            graph.remove_node(nodes[2].id())?;
+            let added = parser.parse_line_ast("d = 4").unwrap();
+            let added = NodeInfo::from_main_line_ast(&added).unwrap();
+            graph.add_node(&added, LocationHint::End)?;
+            Ok(graph.source)
+        })
+        .unwrap();
+        let removed = graph::Id::new_plain_name("bar");
+        new.remove_line(new.ast.lines.len() - 1).unwrap();
+        let baz = parser.parse_line_ast("baz = 3").unwrap();
+        new.add_ast(baz, Placement::Begin).unwrap();
+
+        let diffs = module_diff(&old, &new);
+        assert_eq!(diffs.len(), 3);
+        let diff_of = |id: &graph::Id| diffs.iter().find(|diff| &diff.graph == id).unwrap();
+
+        let main_diff = diff_of(&main);
+        assert_eq!(repr(&main_diff.added), vec!["d = 4"]);
+        assert_eq!(repr(&main_diff.removed), vec!["c = b"]);
+        assert_eq!(main_diff.changed.len(), 1);
+        assert_eq!(main_diff.changed[0].old.ast().repr(), "b = a + 1");
+        assert_eq!(main_diff.changed[0].new.ast().repr(), "b = a + 2");
+
+        let baz_diff = diff_of(&graph::Id::new_plain_name("baz"));
+        assert_eq!(repr(&baz_diff.added), vec!["3"]);
+        assert!(baz_diff.removed.is_empty());
+        let bar_diff = diff_of(&removed);
+        assert_eq!(repr(&bar_diff.removed), vec!["2"]);
+        assert!(bar_diff.added.is_empty());
+
+        assert!(module_diff(&old, &old).is_empty());
+    }
+}
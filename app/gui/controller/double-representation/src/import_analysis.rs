@@ -0,0 +1,551 @@
+//! Project-wide analysis of the import declarations.
+//!
+//! The analysis builds the graph of imports between the project's modules, reporting the import
+//! cycles, and finds the imports in each module which are unused or redundant. The names are
+//! resolved using the [alias analysis](crate::alias_analysis) of the module's code and a
+//! [`NameResolver`] telling what names the imported modules define. The problems in each module may
+//! be fixed automatically with [`ModuleReport::clean_up`].
+//!
+//! The analysis is conservative: an import is reported as unused only if it can be told from the
+//! code. Notably, importing a module brings its extension methods into scope, and their usages
+//! cannot be recognized without type information, so imports of such modules are never reported as
+//! unused.
+
+use crate::prelude::*;
+
+use crate::alias_analysis;
+use crate::import;
+use crate::import::ImportedNames;
+use crate::module;
+use crate::name::QualifiedName;
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+
+
+// ==============
+// === Errors ===
+// ==============
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(display = "The import `{}` is no longer at line {} of the module.", import, line_index)]
+pub struct ImportMoved {
+    pub import:     String,
+    pub line_index: usize,
+}
+
+
+
+// ====================
+// === NameResolver ===
+// ====================
+
+/// Provides the information about the imported modules.
+pub trait NameResolver {
+    /// The names defined in the module or type of the given name, i.e. brought into scope by
+    /// `from <name> import all`. Returns [`None`] if the names are not known.
+    fn defined_names(&self, name: &QualifiedName) -> Option<BTreeSet<String>>;
+
+    /// Check if the module of the given name defines methods of types from other modules, which
+    /// are brought into scope by any import of the module. Should return `true` if not known.
+    fn defines_extension_methods(&self, module: &QualifiedName) -> bool;
+}
+
+
+
+// =============
+// === Issue ===
+// =============
+
+/// A problem with an import declaration.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Issue {
+    /// None of the names brought into scope by the import is used in the module.
+    Unused,
+    /// Only some of the listed names are used. The import may be narrowed to the used names.
+    PartiallyUnused {
+        /// The imported names not used in the module.
+        unused: BTreeSet<String>,
+    },
+    /// Everything imported by the declaration is already imported.
+    Redundant {
+        /// The line index of the import declaration importing the same names. [`None`] if the
+        /// module imports itself.
+        covered_by: Option<usize>,
+    },
+}
+
+/// An [`Issue`] found with an import declaration.
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ImportIssue {
+    /// The index of the module's line with the import declaration.
+    pub line_index: usize,
+    pub import:     import::Info,
+    pub issue:      Issue,
+}
+
+impl ImportIssue {
+    /// The import declaration which should replace the problematic one, or [`None`] if the
+    /// declaration should be removed.
+    pub fn fixed_import(&self) -> Option<import::Info> {
+        match (&self.issue, &self.import.imported) {
+            (Issue::PartiallyUnused { unused }, ImportedNames::List { names }) => {
+                let names = names.difference(unused).cloned().collect();
+                Some(import::Info {
+                    imported: ImportedNames::List { names },
+                    ..self.import.clone()
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+
+
+// ====================
+// === ModuleReport ===
+// ====================
+
+/// The problems with the import declarations of a single module.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ModuleReport {
+    /// The name of the analyzed module.
+    pub module: QualifiedName,
+    /// The found problems, ordered by the line index.
+    pub issues: Vec<ImportIssue>,
+}
+
+impl ModuleReport {
+    /// Analyze the imports of the module.
+    pub fn new(module: QualifiedName, info: &module::Info, resolver: &impl NameResolver) -> Self {
+        let imports = info.enumerate_imports().map(|(crumb, import)| (crumb.line_index, import));
+        let imports = imports.collect_vec();
+        let used_names = used_names(info);
+        let issues = imports.iter().filter_map(|(line_index, import)| {
+            let issue = redundancy(&module, *line_index, import, &imports)
+                .or_else(|| usage_issue(import, &used_names, resolver))?;
+            Some(ImportIssue { line_index: *line_index, import: import.clone(), issue })
+        });
+        let issues = issues.collect();
+        Self { module, issues }
+    }
+
+    /// Check if no problems were found.
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Fix the found problems in the module, removing or narrowing the problematic imports.
+    ///
+    /// The module must not be changed since the analysis; the import declarations are checked to
+    /// be still in the same lines.
+    pub fn clean_up(&self, info: &mut module::Info, parser: &parser::Parser) -> FallibleResult {
+        let current_imports: HashMap<_, _> =
+            info.enumerate_imports().map(|(crumb, import)| (crumb.line_index, import)).collect();
+        for issue in &self.issues {
+            if current_imports.get(&issue.line_index) != Some(&issue.import) {
+                let import = issue.import.to_string();
+                return Err(ImportMoved { import, line_index: issue.line_index }.into());
+            }
+        }
+        // Going from the last line, so the line indices of the remaining issues stay valid.
+        for issue in self.issues.iter().rev() {
+            info.remove_line(issue.line_index)?;
+            if let Some(fixed) = issue.fixed_import() {
+                let ast = parser.parse_line_ast(fixed.to_string())?;
+                info.add_line(issue.line_index, Some(ast));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The names used in the module's code, excluding the import declarations.
+fn used_names(info: &module::Info) -> HashSet<String> {
+    let lines = info.ast.shape().enumerate().map(|(_, ast)| ast);
+    let lines = lines.filter(|ast| import::Info::from_ast(ast).is_none());
+    let used = lines.flat_map(|ast| alias_analysis::analyze_ast(ast).used);
+    used.map(|name| name.item).collect()
+}
+
+/// Check if the names imported by `import` are all imported by `other` from the same module.
+fn covers(import: &import::Info, other: &import::Info) -> bool {
+    use ImportedNames::*;
+    import.module == other.module
+        && match (&import.imported, &other.imported) {
+            (Module { alias }, Module { alias: other_alias }) => alias == other_alias,
+            (List { .. } | AllExcept { .. } | All, All) => true,
+            (List { names }, AllExcept { not_imported }) => names.is_disjoint(not_imported),
+            (List { names }, List { names: other_names }) => names.is_subset(other_names),
+            (AllExcept { not_imported }, AllExcept { not_imported: other_not_imported }) =>
+                other_not_imported.is_subset(not_imported),
+            _ => false,
+        }
+}
+
+/// Check if the import is redundant: it is the module's self-import, or another import declaration
+/// brings the same names into scope. Of two equivalent declarations, the latter is redundant.
+fn redundancy(
+    module: &QualifiedName,
+    line_index: usize,
+    import: &import::Info,
+    all_imports: &[(usize, import::Info)],
+) -> Option<Issue> {
+    let is_module_import = matches!(import.imported, ImportedNames::Module { .. });
+    let imported = import.qualified_module_name().ok();
+    if !is_module_import && imported.as_ref() == Some(module) {
+        return Some(Issue::Redundant { covered_by: None });
+    }
+    let covering = all_imports.iter().find(|(other_line_index, other)| {
+        let equivalent = covers(other, import);
+        *other_line_index != line_index
+            && covers(import, other)
+            && (!equivalent || *other_line_index < line_index)
+    });
+    covering.map(|(covered_by, _)| Issue::Redundant { covered_by: Some(*covered_by) })
+}
+
+/// Check if the names brought into scope by the import are used.
+fn usage_issue(
+    import: &import::Info,
+    used_names: &HashSet<String>,
+    resolver: &impl NameResolver,
+) -> Option<Issue> {
+    let imported = import.qualified_module_name().ok()?;
+    let brings_extensions = || resolver.defines_extension_methods(&imported);
+    let all_unused = |names: &BTreeSet<String>| names.iter().all(|n| !used_names.contains(n));
+    match &import.imported {
+        ImportedNames::Module { alias } => {
+            let name = alias.as_deref().or_else(|| import.module.last().map(ImString::as_str))?;
+            (!used_names.contains(name) && !brings_extensions()).then_some(Issue::Unused)
+        }
+        ImportedNames::List { names } => {
+            let unused: BTreeSet<_> =
+                names.iter().filter(|name| !used_names.contains(*name)).cloned().collect();
+            if unused.is_empty() {
+                None
+            } else if unused.len() == names.len() {
+                // Even if none of the listed names is used, the extension methods may be.
+                (!brings_extensions()).then_some(Issue::Unused)
+            } else {
+                Some(Issue::PartiallyUnused { unused })
+            }
+        }
+        ImportedNames::All => {
+            let names = resolver.defined_names(&imported)?;
+            (all_unused(&names) && !brings_extensions()).then_some(Issue::Unused)
+        }
+        ImportedNames::AllExcept { not_imported } => {
+            let names = resolver.defined_names(&imported)?;
+            let names = names.difference(not_imported).cloned().collect();
+            (all_unused(&names) && !brings_extensions()).then_some(Issue::Unused)
+        }
+    }
+}
+
+
+
+// ===================
+// === ImportGraph ===
+// ===================
+
+/// The graph of imports between modules.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ImportGraph {
+    /// The modules imported by each module. Only the analyzed modules are included.
+    pub imports: BTreeMap<QualifiedName, BTreeSet<QualifiedName>>,
+}
+
+impl ImportGraph {
+    /// Build the graph of imports between the given modules. An import declaration referring to
+    /// an entity inside a module, like a type, counts as an import of that module.
+    pub fn new<'a>(
+        modules: impl IntoIterator<Item = (&'a QualifiedName, &'a module::Info)>,
+    ) -> Self {
+        let modules = modules.into_iter().collect_vec();
+        let known: HashSet<_> = modules.iter().map(|(name, _)| (*name).clone()).collect();
+        let imports = modules.iter().map(|(name, info)| {
+            let imported = info.iter_imports().filter_map(|import| {
+                let imported = import.qualified_module_name().ok()?;
+                let candidates = iter::once(imported.clone())
+                    .chain(imported.parents().map(|parent| parent.to_owned()).collect_vec());
+                candidates.into_iter().find(|candidate| known.contains(candidate))
+            });
+            ((*name).clone(), imported.collect())
+        });
+        Self { imports: imports.collect() }
+    }
+
+    /// The import cycles: groups of modules importing each other, directly or indirectly. A
+    /// module importing itself forms a cycle too. The modules in each cycle are sorted.
+    pub fn cycles(&self) -> Vec<Vec<QualifiedName>> {
+        let mut tarjan = Tarjan::new(self);
+        for module in self.imports.keys() {
+            if !tarjan.indices.contains_key(module) {
+                tarjan.visit(module);
+            }
+        }
+        let is_cycle = |component: &Vec<QualifiedName>| match component.as_slice() {
+            [single] =>
+                self.imports.get(single).map_or(false, |imported| imported.contains(single)),
+            _ => true,
+        };
+        let cycles = tarjan.components.into_iter().filter(is_cycle);
+        let cycles = cycles.map(|component| component.into_iter().sorted().collect_vec());
+        cycles.sorted().collect()
+    }
+}
+
+/// The state of the Tarjan's strongly connected components algorithm.
+#[derive(Debug)]
+struct Tarjan<'a> {
+    graph:      &'a ImportGraph,
+    next_index: usize,
+    indices:    HashMap<&'a QualifiedName, usize>,
+    low_links:  HashMap<&'a QualifiedName, usize>,
+    stack:      Vec<&'a QualifiedName>,
+    on_stack:   HashSet<&'a QualifiedName>,
+    components: Vec<Vec<QualifiedName>>,
+}
+
+impl<'a> Tarjan<'a> {
+    fn new(graph: &'a ImportGraph) -> Self {
+        Self {
+            graph,
+            next_index: 0,
+            indices: default(),
+            low_links: default(),
+            stack: default(),
+            on_stack: default(),
+            components: default(),
+        }
+    }
+
+    fn visit(&mut self, module: &'a QualifiedName) {
+        let index = self.next_index;
+        self.next_index += 1;
+        self.indices.insert(module, index);
+        self.low_links.insert(module, index);
+        self.stack.push(module);
+        self.on_stack.insert(module);
+        for imported in self.graph.imports.get(module).into_iter().flatten() {
+            if !self.indices.contains_key(imported) {
+                self.visit(imported);
+                let low_link = self.low_links[module].min(self.low_links[imported]);
+                self.low_links.insert(module, low_link);
+            } else if self.on_stack.contains(imported) {
+                let low_link = self.low_links[module].min(self.indices[imported]);
+                self.low_links.insert(module, low_link);
+            }
+        }
+        if self.low_links[module] == index {
+            let mut component = vec![];
+            while let Some(member) = self.stack.pop() {
+                self.on_stack.remove(member);
+                component.push(member.clone());
+                if member == module {
+                    break;
+                }
+            }
+            self.components.push(component);
+        }
+    }
+}
+
+
+
+// ==============
+// === Report ===
+// ==============
+
+/// The result of the project-wide analysis.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Report {
+    #[allow(missing_docs)]
+    pub graph:   ImportGraph,
+    /// The import cycles. See [`ImportGraph::cycles`].
+    pub cycles:  Vec<Vec<QualifiedName>>,
+    /// The problems with imports in each module, sorted by the module name. Only the modules with
+    /// any problems are included.
+    pub modules: Vec<ModuleReport>,
+}
+
+/// Analyze the imports of the given modules.
+pub fn analyze(modules: &[(QualifiedName, module::Info)], resolver: &impl NameResolver) -> Report {
+    let graph = ImportGraph::new(modules.iter().map(|(name, info)| (name, info)));
+    let cycles = graph.cycles();
+    let reports =
+        modules.iter().map(|(name, info)| ModuleReport::new(name.clone(), info, resolver));
+    let modules =
+        reports.filter(|report| !report.is_clean()).sorted_by(|a, b| a.module.cmp(&b.module));
+    Report { graph, cycles, modules: modules.collect() }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use parser::Parser;
+
+    #[derive(Debug, Default)]
+    struct MockResolver {
+        names:      HashMap<String, Vec<&'static str>>,
+        extensions: HashSet<String>,
+    }
+
+    impl NameResolver for MockResolver {
+        fn defined_names(&self, name: &QualifiedName) -> Option<BTreeSet<String>> {
+            let names = self.names.get(&name.to_string())?;
+            Some(names.iter().map(|name| name.to_string()).collect())
+        }
+
+        fn defines_extension_methods(&self, module: &QualifiedName) -> bool {
+            self.extensions.contains(&module.to_string())
+        }
+    }
+
+    fn resolver() -> MockResolver {
+        let names = [
+            ("Standard.Base.Data.Vector", vec!["Vector", "new"]),
+            ("Standard.Base.Data.Text", vec!["Text", "Case"]),
+            ("Standard.Base.Data.Table", vec!["Table", "Column"]),
+        ];
+        let names = names.into_iter().map(|(module, names)| (module.to_owned(), names)).collect();
+        let extensions = ["Standard.Base.Data.Text.Extensions".to_owned()].into();
+        MockResolver { names, extensions }
+    }
+
+    fn module(parser: &Parser, code: &str) -> module::Info {
+        module::Info { ast: parser.parse_module(code, default()).unwrap() }
+    }
+
+    fn name(name: &str) -> QualifiedName {
+        QualifiedName::from_text(name).unwrap()
+    }
+
+    #[test]
+    fn finding_unused_and_redundant_imports() {
+        let parser = Parser::new();
+        let code = r#"import Standard.Base.Data.Vector
+import Standard.Base.Data.Json as Js
+import Standard.Base.Data.Text.Extensions
+from Standard.Base.Data.Text import all
+from Standard.Base.Data.Table import all hiding Column
+from Standard.Base.Data.Unknown import all
+from Standard.Base.Data.Numbers import Integer, Decimal, Number
+from Standard.Base.Data.Vector import Vector
+import Standard.Base.Data.Vector
+from local.Project.Foo import all
+
+main =
+    x = Vector.new 10
+    y = Integer.parse "5"
+    z = Decimal.parse "5.0"
+    x.length"#;
+        let info = module(&parser, code);
+        let report = ModuleReport::new(name("local.Project.Foo"), &info, &resolver());
+        let issues = report.issues.iter().map(|i| (i.line_index, i.issue.clone())).collect_vec();
+        assert_eq!(issues, vec![
+            (1, Issue::Unused),
+            (3, Issue::Unused),
+            (4, Issue::Unused),
+            (6, Issue::PartiallyUnused { unused: ["Number".to_owned()].into() }),
+            (8, Issue::Redundant { covered_by: Some(0) }),
+            (9, Issue::Redundant { covered_by: None }),
+        ]);
+
+        let mut cleaned = info.clone();
+        report.clean_up(&mut cleaned, &parser).unwrap();
+        let expected = r#"import Standard.Base.Data.Vector
+import Standard.Base.Data.Text.Extensions
+from Standard.Base.Data.Unknown import all
+from Standard.Base.Data.Numbers import Decimal, Integer
+from Standard.Base.Data.Vector import Vector
+
+main =
+    x = Vector.new 10
+    y = Integer.parse "5"
+    z = Decimal.parse "5.0"
+    x.length"#;
+        cleaned.expect_code(expected);
+        let report = ModuleReport::new(name("local.Project.Foo"), &cleaned, &resolver());
+        assert!(report.is_clean(), "{report:?}");
+
+        // The cleanup is refused when the module has changed since the analysis.
+        let mut changed = module(&parser, &format!("import Standard.Base.Data.Table\n{code}"));
+        let old_report = ModuleReport::new(name("local.Project.Foo"), &info, &resolver());
+        assert!(old_report.clean_up(&mut changed, &parser).is_err());
+    }
+
+    #[test]
+    fn listed_names_from_extending_module() {
+        let parser = Parser::new();
+        let code = r#"from Standard.Base.Data.Text.Extensions import to_case, split
+from Standard.Base.Data.Text.Extensions import pad
+from Standard.Base.Data.Text import Case
+
+main = to_case "foo""#;
+        let info = module(&parser, code);
+        let report = ModuleReport::new(name("local.Project.Main"), &info, &resolver());
+        let issues = report.issues.iter().map(|i| (i.line_index, i.issue.clone())).collect_vec();
+        assert_eq!(issues, vec![
+            (0, Issue::PartiallyUnused { unused: ["split".to_owned()].into() }),
+            (2, Issue::Unused),
+        ]);
+    }
+
+    #[test]
+    fn covering_imports() {
+        let parser = Parser::new();
+        let code = r#"from Standard.Base.Data.Text import Text
+from Standard.Base.Data.Text import all hiding Case
+from Standard.Base.Data.Text import all
+import Standard.Base.Data.Text as T
+import Standard.Base.Data.Text as T
+
+main = T.Text Text Case"#;
+        let info = module(&parser, code);
+        let report = ModuleReport::new(name("local.Project.Main"), &info, &resolver());
+        let issues = report.issues.iter().map(|i| (i.line_index, i.issue.clone())).collect_vec();
+        assert_eq!(issues, vec![
+            (0, Issue::Redundant { covered_by: Some(1) }),
+            (1, Issue::Redundant { covered_by: Some(2) }),
+            (4, Issue::Redundant { covered_by: Some(3) }),
+        ]);
+    }
+
+    #[test]
+    fn finding_import_cycles() {
+        let parser = Parser::new();
+        let modules = [
+            ("local.Project.A", "import local.Project.B\nmain = B.foo"),
+            ("local.Project.B", "from local.Project.C.Type import all\nfoo = bar"),
+            ("local.Project.C", "import local.Project.A\nimport Standard.Base\nbar = A.main"),
+            ("local.Project.D", "import local.Project.A\nimport local.Project.D\nbaz = A.main"),
+            ("local.Project.E", "baz = 1"),
+        ];
+        let modules = modules.map(|(n, code)| (name(n), module(&parser, code)));
+        let report = analyze(&modules, &resolver());
+        assert_eq!(report.cycles, vec![
+            vec![name("local.Project.A"), name("local.Project.B"), name("local.Project.C")],
+            vec![name("local.Project.D")],
+        ]);
+        let imports_of_c = &report.graph.imports[&name("local.Project.C")];
+        assert_eq!(imports_of_c.iter().collect_vec(), vec![&name("local.Project.A")]);
+        let imports_of_b = &report.graph.imports[&name("local.Project.B")];
+        assert_eq!(imports_of_b.iter().collect_vec(), vec![&name("local.Project.C")]);
+
+        let reported_modules = report.modules.iter().map(|m| m.module.to_string()).collect_vec();
+        assert_eq!(reported_modules, vec!["local.Project.C", "local.Project.D"]);
+    }
+}
@@ -43,9 +43,11 @@ pub mod alias_analysis;
 pub mod connection;
 pub mod context_switch;
 pub mod definition;
+pub mod diff;
 pub mod graph;
 pub mod identifier;
 pub mod import;
+pub mod import_analysis;
 pub mod module;
 pub mod name;
 pub mod node;
@@ -6,5 +6,8 @@
 // ==============
 
 pub mod collapse;
+pub mod expand;
+pub mod rename;
 
 pub use collapse::collapse;
+pub use expand::expand;
@@ -0,0 +1,561 @@
+//! Module with logic for node expanding, the inverse of node collapsing.
+//!
+//! See the [`expand`] function for details.
+
+use crate::prelude::*;
+
+use crate::alias_analysis;
+use crate::definition;
+use crate::definition::DefinitionInfo;
+use crate::graph::GraphInfo;
+use crate::identifier;
+use crate::module;
+use crate::node;
+use crate::node::MainLine;
+
+use ast::known;
+use ast::opr::match_named_argument;
+use ast::BlockLine;
+use parser::Parser;
+
+
+
+// ==================
+// === Expand API ===
+// ==================
+
+// === Entry point ===
+
+/// Run the "expand node" refactoring, the inverse of [`collapse`](super::collapse()). Generates
+/// output describing how to apply the refactoring.
+///
+/// "Expanding a node" means replacing a node that calls a method defined in the same module with
+/// the nodes of that method's body. The method definition is left intact, as it may have other
+/// callers.
+///
+/// The method's parameters are replaced with the variables passed as the call's arguments. Other
+/// argument expressions are assigned to new nodes, placed before the method's nodes. Variables
+/// introduced in the method's body are renamed if their names are already used in the refactored
+/// definition. The value returned by the method is assigned to the call node's pattern, if there is
+/// one.
+pub fn expand(
+    graph: &GraphInfo,
+    node: node::Id,
+    module: &module::Info,
+    parser: &Parser,
+    module_name: &str,
+) -> FallibleResult<Expanded> {
+    let call_node = graph.locate_node(node)?;
+    let expression = call_node.expression();
+    let not_a_call = || NotAMethodCall(expression.repr());
+    let call = Call::new(&expression, module_name).ok_or_else(not_a_call)?;
+    let method_id = definition::Id::new_plain_name(&call.method);
+    let method = module::get_definition(&module.ast, &method_id).map_err(|_| not_a_call())?;
+    Expander::new(graph, call_node, call, method, parser)?.expand()
+}
+
+
+// === Expanded ===
+
+/// Result of running the node expanding algorithm. Describes update to the refactored definition.
+#[derive(Clone, Debug)]
+pub struct Expanded {
+    /// New contents of the refactored definition.
+    pub updated_definition: DefinitionInfo,
+    /// Nodes introduced in place of the expanded node, in the order of their lines.
+    pub introduced_nodes:   Vec<IntroducedNode>,
+}
+
+/// A node introduced in place of the expanded node.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct IntroducedNode {
+    /// Identifier of the node in the updated definition.
+    pub id:     node::Id,
+    /// Identifier of the node of the expanded method that this node is a copy of. `None` for nodes
+    /// assigning the call's arguments.
+    pub origin: Option<node::Id>,
+}
+
+
+// === Errors ===
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(display = "Node `{}` is not a call to a method defined in this module.", _0)]
+pub struct NotAMethodCall(String);
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(
+    display = "Cannot expand a call to a method with parameter `{}`. Only parameters being plain \
+variables without default values are supported.",
+    _0
+)]
+pub struct UnsupportedParameter(String);
+
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, Fail)]
+#[fail(
+    display = "Cannot expand a call with {} arguments to a method with {} parameters.",
+    found, expected
+)]
+pub struct ArityMismatch {
+    expected: usize,
+    found:    usize,
+}
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(display = "Cannot expand a call with argument named `{}`, which is not a parameter.", _0)]
+pub struct UnknownNamedArgument(String);
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(
+    display = "Cannot expand the call, as the method uses `{}`, which would refer to a variable \
+of the refactored definition.",
+    _0
+)]
+pub struct CapturedIdentifier(String);
+
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, Fail)]
+#[fail(display = "Cannot expand the call, as the last line of the method is an assignment, which \
+does not return a value.")]
+pub struct ReturnedAssignment;
+
+
+
+// ============
+// === Call ===
+// ============
+
+/// A call to a method defined in the refactored module, like `Main.foo a b` or `foo a b`.
+#[derive(Clone, Debug)]
+pub struct Call {
+    /// Name of the called method.
+    pub method:     String,
+    /// Arguments passed by position.
+    pub positional: Vec<Ast>,
+    /// Arguments passed by name.
+    pub named:      Vec<(String, Ast)>,
+}
+
+impl Call {
+    /// Interpret the expression as a call to a method of the module with the given name.
+    pub fn new(expression: &Ast, module_name: &str) -> Option<Self> {
+        let chain = ast::prefix::Chain::from_ast_non_strict(expression);
+        let method = match ast::opr::to_access(&chain.func) {
+            Some(access) => {
+                let qualifies = ast::identifier::name(&access.larg) == Some(module_name);
+                qualifies.then(|| ast::identifier::name(&access.rarg).map(ToOwned::to_owned))??
+            }
+            None => ast::identifier::name(&chain.func)?.to_owned(),
+        };
+        let is_method_name = method.starts_with(|c: char| c.is_lowercase());
+        let method = is_method_name.then_some(method)?;
+        let mut positional = vec![];
+        let mut named = vec![];
+        for arg in chain.args {
+            match match_named_argument(&arg.sast.wrapped) {
+                Some(named_arg) => named.push((named_arg.name.into(), named_arg.rarg.clone_ref())),
+                None => positional.push(arg.sast.wrapped),
+            }
+        }
+        Some(Self { method, positional, named })
+    }
+}
+
+
+
+// ================
+// === Expander ===
+// ================
+
+/// Helper type that stores the data used by the expanding algorithm and implements its logic.
+#[derive(Clone, Debug)]
+pub struct Expander<'a> {
+    /// The definition where the node expanding takes place.
+    graph:        &'a GraphInfo,
+    /// The expanded node.
+    call_node:    node::LocatedNode,
+    /// The lines of the expanded method's body, without empty lines.
+    method_lines: Vec<Ast>,
+    /// The new names of the variables of the expanded method, if they are to be renamed.
+    renames:      HashMap<String, String>,
+    /// The lines assigning argument expressions to the parameters they are passed to.
+    bindings:     Vec<Ast>,
+    /// Whether the method's last line only returns a variable assigned to the call's pattern.
+    /// Such a line is dropped, the variable being renamed instead.
+    drop_return:  bool,
+    parser:       &'a Parser,
+}
+
+impl<'a> Expander<'a> {
+    /// Resolve the names of the method's variables after expanding and the arguments passed to its
+    /// parameters.
+    pub fn new(
+        graph: &'a GraphInfo,
+        call_node: node::LocatedNode,
+        call: Call,
+        method: DefinitionInfo,
+        parser: &'a Parser,
+    ) -> FallibleResult<Self> {
+        let parameters = method
+            .args
+            .iter()
+            .map(|arg| match arg.item.shape() {
+                ast::Shape::Var(var) => Ok(var.name.clone()),
+                _ => Err(UnsupportedParameter(arg.item.repr())),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let arguments = Self::match_arguments(&parameters, call)?;
+        let method_lines = method.block_lines().into_iter().filter_map(|line| line.elem);
+        let method_lines = method_lines.collect_vec();
+
+        // Names of the refactored definition: the method's variables must not clash with any of
+        // them, and the method must not refer to the introduced ones from the module scope.
+        let mut graph_names = HashSet::<String>::new();
+        let mut graph_introduced = HashSet::<String>::new();
+        let graph_parameters = graph.source.args.iter();
+        let graph_parameters = graph_parameters.filter_map(|arg| ast::identifier::name(&arg.item));
+        graph_introduced.extend(graph_parameters.map(ToOwned::to_owned));
+        for line in graph.source.block_lines().into_iter().filter_map(|line| line.elem) {
+            let usage = alias_analysis::analyze_ast(&line);
+            graph_introduced.extend(usage.introduced.iter().map(|name| name.item.clone()));
+            graph_names.extend(usage.all_identifiers().into_iter().map(|name| name.item));
+        }
+        graph_names.extend(graph_introduced.iter().cloned());
+
+        // Names of the method's variables.
+        let mut method_names = parameters.iter().cloned().collect::<HashSet<_>>();
+        let mut introduced = vec![];
+        for line in &method_lines {
+            let usage = alias_analysis::analyze_ast(line);
+            for name in usage.used {
+                if !method_names.contains(&name.item) && graph_introduced.contains(&name.item) {
+                    return Err(CapturedIdentifier(name.item).into());
+                }
+            }
+            for name in usage.introduced {
+                if method_names.insert(name.item.clone()) {
+                    introduced.push(name.item);
+                }
+            }
+        }
+
+        let mut renames = HashMap::new();
+        let mut unavailable: HashSet<String> = graph_names.union(&method_names).cloned().collect();
+        let fresh_name = |name: &str, unavailable: &mut HashSet<String>| {
+            let unavailable_names = unavailable.iter().map(String::as_str);
+            let new_name = identifier::generate_name(name, unavailable_names)?.name().to_owned();
+            unavailable.insert(new_name.clone());
+            FallibleResult::Ok(new_name)
+        };
+
+        // Return value.
+        let last_line = method_lines.last();
+        let returned_var = last_line.and_then(|line| known::Var::try_from(line).ok());
+        let returned_var = returned_var.filter(|var| introduced.contains(&var.name));
+        let pattern = call_node.pattern().and_then(|pattern| known::Var::try_from(pattern).ok());
+        let drop_return =
+            returned_var.is_some() && (pattern.is_some() || call_node.pattern().is_none());
+        if let (Some(returned), Some(pattern)) = (&returned_var, &pattern) {
+            renames.insert(returned.name.clone(), pattern.name.clone());
+        }
+
+        // Parameters.
+        let mut bindings = vec![];
+        for (parameter, argument) in parameters.into_iter().zip(arguments) {
+            match known::Var::try_from(&argument) {
+                Ok(var) => {
+                    renames.insert(parameter, var.name.clone());
+                }
+                Err(_) => {
+                    let argument = ungroup(argument);
+                    let name = if graph_names.contains(&parameter) {
+                        fresh_name(&parameter, &mut unavailable)?
+                    } else {
+                        parameter.clone()
+                    };
+                    bindings.push(Ast::infix(Ast::var(&name), "=", argument));
+                    if name != parameter {
+                        renames.insert(parameter, name);
+                    }
+                }
+            }
+        }
+
+        // Variables introduced in the method.
+        for name in introduced {
+            let is_clashing = graph_names.contains(&name) || renames.values().contains(&name);
+            if is_clashing && !renames.contains_key(&name) {
+                let new_name = fresh_name(&name, &mut unavailable)?;
+                renames.insert(name, new_name);
+            }
+        }
+
+        Ok(Self { graph, call_node, method_lines, renames, bindings, drop_return, parser })
+    }
+
+    /// Assign the call's arguments to the method's parameters.
+    fn match_arguments(parameters: &[String], call: Call) -> FallibleResult<Vec<Ast>> {
+        let mut arguments = vec![None; parameters.len()];
+        for (name, argument) in call.named {
+            let index = parameters.iter().position(|parameter| parameter == &name);
+            let index = index.ok_or_else(|| UnknownNamedArgument(name.clone()))?;
+            arguments[index] = Some(argument);
+        }
+        let found = call.positional.len() + arguments.iter().flatten().count();
+        let mismatch = || ArityMismatch { expected: parameters.len(), found };
+        let mut positional = call.positional.into_iter();
+        for argument in arguments.iter_mut().filter(|argument| argument.is_none()) {
+            *argument = Some(positional.next().ok_or_else(mismatch)?);
+        }
+        if positional.next().is_some() {
+            return Err(mismatch().into());
+        }
+        Ok(arguments.into_iter().flatten().collect())
+    }
+
+    /// Rename the method's variables used or introduced in the line.
+    fn rename(&self, line: &Ast) -> FallibleResult<Ast> {
+        let usage = alias_analysis::analyze_ast(line);
+        usage.all_identifiers().into_iter().try_fold(line.clone(), |line, name| {
+            match self.renames.get(&name.item) {
+                Some(new_name) => line.set_traversing(&name.crumbs, Ast::var(new_name)),
+                None => Ok(line),
+            }
+        })
+    }
+
+    /// Generate the lines replacing the call node, paired with the method's lines they are copies
+    /// of. The lines get new IDs, as the method's lines remain in the module.
+    fn lines(&self) -> FallibleResult<Vec<(Ast, Option<&Ast>)>> {
+        let mut lines = self.bindings.iter().map(|binding| (binding.clone(), None)).collect_vec();
+        let (last, body) = self.method_lines.split_last().ok_or(ReturnedAssignment)?;
+        for line in body {
+            lines.push((self.rename(line)?, Some(line)));
+        }
+        if !self.drop_return {
+            let renamed = self.rename(last)?;
+            let returned = match self.call_node.pattern() {
+                Some(_) if ast::opr::is_assignment(last) => return Err(ReturnedAssignment.into()),
+                Some(pattern) => Ast::infix(pattern.clone(), "=", renamed),
+                None => renamed,
+            };
+            lines.push((returned, Some(last)));
+        }
+        lines
+            .into_iter()
+            .map(|(line, origin)| Ok((self.parser.parse_line_ast(line.repr())?, origin)))
+            .collect()
+    }
+
+    /// Run the expanding refactoring.
+    pub fn expand(&self) -> FallibleResult<Expanded> {
+        let lines = self.lines()?;
+        let node_id = |line: &Ast| MainLine::from_ast(line).map(|main_line| main_line.id());
+        let introduced_nodes = lines
+            .iter()
+            .filter_map(|(line, origin)| {
+                let origin = origin.and_then(node_id);
+                node_id(line).map(|id| IntroducedNode { id, origin })
+            })
+            .collect();
+        let mut block_lines = self.graph.source.block_lines();
+        let call_lines = self.call_node.index.first()..=self.call_node.index.last();
+        let new_lines = lines.into_iter().map(|(line, _)| BlockLine::new(Some(line)));
+        block_lines.splice(call_lines, new_lines);
+        let mut updated_definition = self.graph.source.clone();
+        updated_definition.set_block_lines(block_lines)?;
+        Ok(Expanded { updated_definition, introduced_nodes })
+    }
+}
+
+
+/// Strip the parentheses around the expression, if any.
+fn ungroup(expression: Ast) -> Ast {
+    if let ast::Shape::Tree(tree) = expression.shape() {
+        if tree.type_info == ast::TreeType::Group {
+            let mut children = tree.span_info.iter().filter_map(|seed| match seed {
+                ast::SpanSeed::Child(child) => Some(&child.node),
+                _ => None,
+            });
+            if let Some(body) = children.next() {
+                return body.clone_ref();
+            }
+        }
+    }
+    expression
+}
+
+
+
+// ============
+// === Test ===
+// ============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::definition::DefinitionName;
+    use crate::graph;
+
+    use ast::crumbs::Crumb;
+
+    const MODULE_NAME: &str = "Main";
+
+    /// Expand the node at the given line of the `main` method of the module, returning the code
+    /// of the updated `main` method.
+    fn run_case(parser: &Parser, code: &str, node_index: usize) -> FallibleResult<Expanded> {
+        let ast = parser.parse_module(code, default()).unwrap();
+        let module = module::Info { ast };
+        let main_name = DefinitionName::new_plain("main");
+        let main = module::locate_child(&module.ast, &main_name).unwrap();
+        let graph = graph::GraphInfo::from_definition(main.item.clone());
+        let node = graph.nodes()[node_index].id();
+        let expanded = expand(&graph, node, &module, parser, MODULE_NAME)?;
+        let main_crumb = Crumb::from(main.crumb());
+        let new_main = expanded.updated_definition.ast.clone();
+        let updated = module.ast.set(&main_crumb, new_main.into()).unwrap();
+        ast::test_utils::assert_unique_ids(updated.as_ref());
+        Ok(expanded)
+    }
+
+    fn expect_expanded(parser: &Parser, code: &str, node_index: usize, expected_main: &str) {
+        let expanded = run_case(parser, code, node_index).unwrap();
+        assert_eq!(expanded.updated_definition.ast.repr(), expected_main);
+    }
+
+    #[test]
+    fn expanding_collapsed_nodes() {
+        let parser = Parser::new();
+        let code = r"custom_new a =
+    b = 2
+    c = A + B
+    d = a + b
+    c
+
+main =
+    a = 1
+    c = Main.custom_new a
+    c + 7";
+        let expected = r"main =
+    a = 1
+    b = 2
+    c = A + B
+    d = a + b
+    c + 7";
+        expect_expanded(&parser, code, 1, expected);
+
+        let code = r"custom_new a b = a + b
+
+main =
+    a = 1
+    b = 2
+    custom_new a b";
+        let expected = r"main =
+    a = 1
+    b = 2
+    a + b";
+        expect_expanded(&parser, code, 2, expected);
+    }
+
+    #[test]
+    fn mapping_arguments() {
+        let parser = Parser::new();
+        let code = r"add x y =
+    sum = x + y
+    sum
+
+main =
+    a = 1
+    result = Main.add a (a * 2)
+    result";
+        let expected = r"main =
+    a = 1
+    y = a * 2
+    result = a + y
+    result";
+        expect_expanded(&parser, code, 1, expected);
+
+        // Named arguments, and a parameter name used in the refactored definition.
+        let code = r"add x y = x + y
+
+main =
+    y = 1
+    result = add y=10 y
+    result";
+        let expected = r"main =
+    y = 1
+    y1 = 10
+    result = y + y1
+    result";
+        expect_expanded(&parser, code, 1, expected);
+    }
+
+    #[test]
+    fn renaming_clashing_variables() {
+        let parser = Parser::new();
+        let code = r"compute a =
+    b = a + 1
+    c = b * 2
+    c + b
+
+main =
+    b = 5
+    c = 6
+    d = compute b
+    d + b + c";
+        let expected = r"main =
+    b = 5
+    c = 6
+    b1 = b + 1
+    c1 = b1 * 2
+    d = c1 + b1
+    d + b + c";
+        expect_expanded(&parser, code, 2, expected);
+    }
+
+    #[test]
+    fn introduced_nodes() {
+        let parser = Parser::new();
+        let code = r"add x y =
+    sum = x + y
+    sum
+
+main =
+    sum = Main.add 1 2";
+        let expanded = run_case(&parser, code, 0).unwrap();
+        let origins = expanded.introduced_nodes.iter().map(|node| node.origin.is_some());
+        assert_eq!(origins.collect_vec(), vec![false, false, true]);
+        let lines = expanded.updated_definition.block_lines();
+        let ids = lines.iter().filter_map(MainLine::from_block_line);
+        let ids = ids.map(|main_line| main_line.id()).collect_vec();
+        let introduced = expanded.introduced_nodes.iter().map(|node| node.id).collect_vec();
+        assert_eq!(ids, introduced);
+    }
+
+    #[test]
+    fn rejected_calls() {
+        let parser = Parser::new();
+        let cases = [
+            // Not a call to a method of this module.
+            "main =\n    Other.add 1 2",
+            // Too many arguments.
+            "add x y = x + y\n\nmain =\n    add 1 2 3",
+            // Too few arguments.
+            "add x y = x + y\n\nmain =\n    add 1",
+            // Unsupported parameter.
+            "add x (y = 1) = x + y\n\nmain =\n    add 1 2",
+            // The method's `z` would refer to the refactored definition's variable.
+            "add x y = x + y + z\n\nmain =\n    z = 1\n    add 1 2",
+        ];
+        for code in cases {
+            let node_index = code.split("main =").nth(1).unwrap().lines().count() - 2;
+            assert!(run_case(&parser, code, node_index).is_err(), "{code}");
+        }
+    }
+}
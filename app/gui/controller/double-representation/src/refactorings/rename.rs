@@ -0,0 +1,907 @@
+//! Module with logic for renaming methods, types and local variables.
+//!
+//! A method or a type may be referred to from any module of the project, so renaming it is done
+//! module by module: [`Rename::changes`] finds all occurrences of the renamed entity in a single
+//! module and describes how to replace them. See [`rename_local`] for renaming variables of a
+//! single definition.
+
+use crate::prelude::*;
+
+use crate::alias_analysis;
+use crate::definition::DefinitionInfo;
+use crate::definition::ScopeKind;
+use crate::import;
+use crate::module;
+use crate::name::QualifiedName;
+
+use ast::crumbs::Crumb;
+use ast::known;
+use ast::opr::match_named_argument;
+use ast::SpanSeed;
+use enso_text::index::*;
+use enso_text::unit::*;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+const EXPORT_KEYWORD: &str = "export";
+const TYPE_KEYWORD: &str = "type";
+const LIST_SEPARATOR: &str = ",";
+const SELF_ARGUMENT: &str = "self";
+
+
+
+// ==============
+// === Errors ===
+// ==============
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(display = "`{}` is not a valid name of a {}.", name, kind)]
+pub struct InvalidName {
+    name: String,
+    kind: &'static str,
+}
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(display = "`{}` cannot be renamed, as it is not a {}.", name, kind)]
+pub struct NotAMember {
+    name: String,
+    kind: &'static str,
+}
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(display = "The definition has no local variable named `{}`.", _0)]
+pub struct NoSuchVariable(String);
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(display = "The name `{}` is already used in the definition.", _0)]
+pub struct NameTaken(String);
+
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, Fail)]
+#[fail(display = "Internal refactoring error: renamed token not found in the AST.")]
+pub struct TokenNotFound;
+
+
+
+// ============
+// === Kind ===
+// ============
+
+/// The kind of the renamed entity.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Kind {
+    /// A method defined in a module, e.g. `main`.
+    ModuleMethod,
+    /// A type defined in a module.
+    Type,
+    /// A constructor of a type.
+    Constructor,
+    /// A method of a type, defined either in the type's body or as an extension method.
+    TypeMethod,
+}
+
+impl Kind {
+    /// Check if the entity is a member of a type, rather than of a module.
+    pub fn is_type_member(self) -> bool {
+        matches!(self, Kind::Constructor | Kind::TypeMethod)
+    }
+
+    /// Check if the names of entities of this kind start with an uppercase letter.
+    pub fn is_capitalized(self) -> bool {
+        matches!(self, Kind::Type | Kind::Constructor)
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Kind::ModuleMethod => "module method",
+            Kind::Type => "type",
+            Kind::Constructor => "constructor",
+            Kind::TypeMethod => "type method",
+        }
+    }
+}
+
+/// Check if the name is a valid identifier with the given capitalization.
+fn is_valid_name(name: &str, capitalized: bool) -> bool {
+    let first_char_valid = name.chars().next().map_or(false, |first| match capitalized {
+        true => first.is_uppercase(),
+        false => first.is_lowercase(),
+    });
+    first_char_valid && name.chars().all(|char| char.is_alphanumeric() || char == '_')
+}
+
+
+
+// ==============
+// === Rename ===
+// ==============
+
+/// Description of a method, type or constructor being renamed.
+#[derive(Clone, Debug)]
+pub struct Rename {
+    /// The fully qualified name of the renamed entity.
+    pub target:              QualifiedName,
+    /// The kind of the renamed entity.
+    pub kind:                Kind,
+    /// The new name of the renamed entity.
+    pub new_name:            String,
+    /// Whether method calls on arbitrary values (like `value.name`) should be renamed as well.
+    /// Applies only to type methods.
+    ///
+    /// The method called this way is known only at runtime, so such calls may be safely renamed
+    /// only if no other method has the same name. The caller should check it.
+    pub rename_method_calls: bool,
+    /// The module or type the renamed entity is defined in.
+    parent:                  QualifiedName,
+    /// The module the renamed entity is defined in.
+    defining_module:         QualifiedName,
+}
+
+impl Rename {
+    /// Constructor. Fails if the new name is not a valid name for the entity, or if the target name
+    /// does not describe an entity of the given kind.
+    pub fn new(
+        target: QualifiedName,
+        kind: Kind,
+        new_name: impl Into<String>,
+    ) -> FallibleResult<Self> {
+        let new_name = new_name.into();
+        if !is_valid_name(&new_name, kind.is_capitalized()) {
+            return Err(InvalidName { name: new_name, kind: kind.description() }.into());
+        }
+        let not_a_member = || NotAMember { name: target.to_string(), kind: kind.description() };
+        let parent = target.parent().ok_or_else(not_a_member)?.to_owned();
+        let defining_module = match kind.is_type_member() {
+            true => parent.parent().ok_or_else(not_a_member)?.to_owned(),
+            false => parent.clone(),
+        };
+        let rename_method_calls = false;
+        Ok(Self { target, kind, new_name, rename_method_calls, parent, defining_module })
+    }
+
+    /// The current name of the renamed entity.
+    pub fn old_name(&self) -> &str {
+        self.target.name()
+    }
+
+    /// Find all occurrences of the renamed entity in the given module: its definition, references
+    /// and the imports and exports mentioning it. Returns their ranges in the module's code,
+    /// ordered by their position.
+    pub fn occurrences(
+        &self,
+        module: &module::Info,
+        module_name: &QualifiedName,
+    ) -> FallibleResult<Vec<enso_text::Range<Byte>>> {
+        let mut finder = Finder::new(self, module, module_name);
+        finder.process_module(module.ast.ast());
+        let root = module.ast.ast();
+        let ranges = finder.found.iter().map(|occurrence| occurrence.range(root));
+        let mut ranges: Vec<_> = ranges.try_collect()?;
+        ranges.sort_by_key(|range| range.start);
+        ranges.dedup();
+        Ok(ranges)
+    }
+
+    /// Describe the changes renaming all occurrences of the renamed entity in the given module.
+    ///
+    /// The changes are ordered from the end of the module's code, so each of them remains valid
+    /// after applying the previous ones.
+    pub fn changes(
+        &self,
+        module: &module::Info,
+        module_name: &QualifiedName,
+    ) -> FallibleResult<Vec<enso_text::Change<Byte, String>>> {
+        let occurrences = self.occurrences(module, module_name)?;
+        let text = || self.new_name.clone();
+        Ok(occurrences
+            .into_iter()
+            .rev()
+            .map(|range| enso_text::Change { range, text: text() })
+            .collect())
+    }
+}
+
+
+
+// ==================
+// === Occurrence ===
+// ==================
+
+/// A place in the module's AST where the renamed entity's name occurs.
+#[derive(Clone, Debug)]
+enum Occurrence {
+    /// An identifier node.
+    Node(Vec<Crumb>),
+    /// A token of a tree node, like the type name in a type declaration.
+    TreeToken { tree: Vec<Crumb>, index: usize },
+}
+
+impl Occurrence {
+    /// The range of the occurrence in the code of the `root` AST.
+    fn range(&self, root: &Ast) -> FallibleResult<enso_text::Range<Byte>> {
+        match self {
+            Occurrence::Node(crumbs) => root.range_of_descendant_at(crumbs),
+            Occurrence::TreeToken { tree, index } => {
+                let tree_start = root.range_of_descendant_at(tree)?.start;
+                let ast::Shape::Tree(tree) = root.get_traversing(tree)?.shape() else {
+                    return Err(TokenNotFound.into());
+                };
+                let preceding = tree.span_info.iter().take(*index);
+                let offset: usize = preceding.map(seed_len).sum();
+                let token = match tree.span_info.get(*index) {
+                    Some(SpanSeed::Token(token)) => &token.token,
+                    _ => return Err(TokenNotFound.into()),
+                };
+                let start = tree_start + offset.bytes();
+                Ok(enso_text::Range::new(start, start + token.len().bytes()))
+            }
+        }
+    }
+}
+
+/// The length of the seed's code in bytes.
+fn seed_len(seed: &SpanSeed<Ast>) -> usize {
+    match seed {
+        SpanSeed::Space(space) => space.space,
+        SpanSeed::Token(token) => token.token.len(),
+        SpanSeed::Child(child) => child.node.repr_len().value,
+    }
+}
+
+/// The name of the identifier node, ignoring operators.
+fn identifier(ast: &Ast) -> Option<&str> {
+    match ast.shape() {
+        ast::Shape::Var(var) => Some(&var.name),
+        ast::Shape::Cons(cons) => Some(&cons.name),
+        _ => None,
+    }
+}
+
+/// The token the tree starts with, like the keyword of an import or type declaration.
+fn first_token(tree: &ast::Tree<Ast>) -> Option<&str> {
+    match tree.span_info.first()? {
+        SpanSeed::Token(token) => Some(&token.token),
+        _ => None,
+    }
+}
+
+/// Check if the import declaration makes the given name available without qualification.
+fn imports_name(imported: &import::ImportedNames, name: &str) -> bool {
+    match imported {
+        import::ImportedNames::Module { .. } => false,
+        import::ImportedNames::All => true,
+        import::ImportedNames::AllExcept { not_imported } => !not_imported.contains(name),
+        import::ImportedNames::List { names } => names.contains(name),
+    }
+}
+
+/// The names of variables introduced by the definition's parameters and body. They shadow the
+/// module's methods of the same name.
+fn local_names(definition: &DefinitionInfo) -> HashSet<String> {
+    let parameters = definition.args.iter().flat_map(|arg| arg.item.iter_recursive());
+    let parameters = parameters.filter_map(ast::identifier::as_var).map(ToOwned::to_owned);
+    let lines = definition.block_lines();
+    let lines = lines.iter().filter_map(|line| line.elem.as_ref());
+    let introduced = lines.flat_map(|line| alias_analysis::analyze_ast(line).introduced);
+    parameters.chain(introduced.map(|name| name.item)).collect()
+}
+
+
+
+// ==============
+// === Finder ===
+// ==============
+
+/// Traverses a module's AST looking for occurrences of the renamed entity.
+#[derive(Debug)]
+struct Finder<'a> {
+    rename:         &'a Rename,
+    module_name:    &'a QualifiedName,
+    /// Entities that are referred to with a single identifier, like the imported modules.
+    aliases:        HashMap<String, QualifiedName>,
+    /// Whether the renamed entity is referred to by its name alone, without qualification.
+    unqualified:    bool,
+    /// The location of the currently processed AST, relative to the module's root.
+    crumbs:         Vec<Crumb>,
+    /// Whether a local variable shadows the renamed entity in the currently processed AST.
+    shadowed:       bool,
+    /// Whether the currently processed AST is in the body of the renamed member's type.
+    in_parent_type: bool,
+    /// The type of `self` in the currently processed AST, if it is known.
+    self_type:      Option<QualifiedName>,
+    found:          Vec<Occurrence>,
+}
+
+impl<'a> Finder<'a> {
+    fn new(rename: &'a Rename, module: &module::Info, module_name: &'a QualifiedName) -> Self {
+        let mut aliases = HashMap::new();
+        aliases.insert(module_name.alias_name().to_string(), module_name.clone());
+        let in_defining_module = *module_name == rename.defining_module;
+        let mut unqualified = in_defining_module && !rename.kind.is_type_member();
+        let mut parent_type_visible = in_defining_module;
+        for import in module.iter_imports() {
+            let Ok(imported) = import.qualified_module_name() else { continue };
+            if let import::ImportedNames::Module { alias } = &import.imported {
+                let alias = alias.clone().unwrap_or_else(|| imported.alias_name().to_string());
+                aliases.insert(alias, imported);
+            } else if imported == rename.defining_module {
+                match rename.kind.is_type_member() {
+                    true =>
+                        parent_type_visible |= imports_name(&import.imported, rename.parent.name()),
+                    false => unqualified |= imports_name(&import.imported, rename.old_name()),
+                }
+            }
+        }
+        if rename.kind.is_type_member() && parent_type_visible {
+            aliases.insert(rename.parent.name().to_owned(), rename.parent.clone());
+        }
+        Self {
+            rename,
+            module_name,
+            aliases,
+            unqualified,
+            crumbs: default(),
+            shadowed: false,
+            in_parent_type: false,
+            self_type: None,
+            found: default(),
+        }
+    }
+
+    /// Enters a new location (relative to the current one), invokes `f`, leaves the location.
+    fn in_location<R>(
+        &mut self,
+        crumbs: impl IntoIterator<Item = Crumb>,
+        f: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        let depth = self.crumbs.len();
+        self.crumbs.extend(crumbs);
+        let result = f(self);
+        self.crumbs.truncate(depth);
+        result
+    }
+
+    /// Records the node at the given location (relative to the current one) as an occurrence.
+    fn record(&mut self, crumbs: impl IntoIterator<Item = Crumb>) {
+        let crumbs = self.crumbs.iter().cloned().chain(crumbs).collect();
+        self.found.push(Occurrence::Node(crumbs));
+    }
+
+    /// Records the token of the currently processed tree as an occurrence.
+    fn record_token(&mut self, index: usize) {
+        let tree = self.crumbs.clone();
+        self.found.push(Occurrence::TreeToken { tree, index });
+    }
+
+    /// Check if an identifier with the given name, not being part of an access chain, refers to
+    /// the renamed entity.
+    fn refers_to_target(&self, name: &str) -> bool {
+        let member_in_scope = self.in_parent_type && self.rename.kind == Kind::Constructor;
+        let visible = self.unqualified
+            || member_in_scope
+            || self.aliases.get(name).map_or(false, |entity| *entity == self.rename.target);
+        name == self.rename.old_name() && !self.shadowed && visible
+    }
+
+    /// Resolve the name path to the entity it refers to. If the path is `absolute`, it must be a
+    /// fully qualified name, as in import declarations.
+    fn resolve(&self, path: &[&str], absolute: bool) -> Option<QualifiedName> {
+        let (first, rest) = path.split_first()?;
+        let entity = match *first {
+            _ if absolute => None,
+            SELF_ARGUMENT => self.self_type.clone(),
+            first => self.aliases.get(first).cloned(),
+        };
+        match entity {
+            Some(entity) =>
+                Some(rest.iter().fold(entity, |name, segment| name.new_child(*segment))),
+            None => QualifiedName::from_all_segments(path.iter().copied()).ok(),
+        }
+    }
+
+    /// Check if the name path refers to the renamed entity's parent module or type.
+    fn refers_to_parent(&self, path: &[&str], absolute: bool) -> bool {
+        self.resolve(path, absolute).map_or(false, |entity| entity == self.rename.parent)
+    }
+
+    fn process_module(&mut self, module: &Ast) {
+        for (crumb, line) in module.enumerate() {
+            self.in_location(iter::once(crumb), |this| this.process_module_line(line))
+        }
+    }
+
+    fn process_module_line(&mut self, line: &Ast) {
+        let tree = match line.shape() {
+            ast::Shape::Tree(tree) => Some(tree),
+            _ => None,
+        };
+        match tree.and_then(first_token) {
+            Some(ast::macros::UNQUALIFIED_IMPORT_KEYWORD) =>
+                self.process_unqualified_import(tree.unwrap()),
+            Some(ast::macros::QUALIFIED_IMPORT_KEYWORD | EXPORT_KEYWORD) =>
+                self.process_qualified_import(tree.unwrap()),
+            Some(TYPE_KEYWORD) => self.process_type(tree.unwrap()),
+            _ =>
+                if let Some(definition) = DefinitionInfo::from_root_line_ast(line) {
+                    let defines_target = self.rename.kind == Kind::ModuleMethod
+                        && *self.module_name == self.rename.defining_module;
+                    self.process_definition(&definition, defines_target)
+                } else {
+                    self.process_ast(line)
+                },
+        }
+    }
+
+    /// Process the `import <module>` or `export <module>` declaration. The module may be the
+    /// renamed type.
+    fn process_qualified_import(&mut self, tree: &ast::Tree<Ast>) {
+        if let Some(crumb) = tree.iter_subcrumbs().next() {
+            let module = tree.get(&crumb).unwrap();
+            self.in_location(iter::once(crumb.into()), |this| this.process_path(module, true))
+        }
+    }
+
+    /// Process the `from <module> import <names>` or `from <module> export <names>` declaration.
+    fn process_unqualified_import(&mut self, tree: &ast::Tree<Ast>) {
+        let mut crumbs = tree.iter_subcrumbs();
+        let Some(module_crumb) = crumbs.next() else { return };
+        let module = tree.get(&module_crumb).unwrap();
+        self.in_location(iter::once(module_crumb.into()), |this| this.process_path(module, true));
+        let imports_from_parent = match ast::opr::as_access_chain(module) {
+            Some(chain) => {
+                let operands = chain.enumerate_operands();
+                let path: Option<Vec<_>> =
+                    operands.map(|operand| identifier(&operand?.arg)).collect();
+                path.map_or(false, |path| self.refers_to_parent(&path, true))
+            }
+            None => identifier(module).map_or(false, |name| self.refers_to_parent(&[name], true)),
+        };
+        if imports_from_parent {
+            for crumb in crumbs {
+                let names = tree.get(&crumb).unwrap();
+                self.in_location(iter::once(crumb.into()), |this| this.process_name_list(names))
+            }
+        }
+    }
+
+    /// Process the list of names imported from the renamed entity's parent.
+    fn process_name_list(&mut self, names: &Ast) {
+        let old_name = self.rename.old_name();
+        match ast::opr::Chain::try_new_of(names, LIST_SEPARATOR) {
+            Some(chain) =>
+                for operand in chain.enumerate_non_empty_operands() {
+                    if identifier(&operand.item.arg) == Some(old_name) {
+                        self.record(operand.crumbs)
+                    }
+                },
+            None =>
+                if identifier(names) == Some(old_name) {
+                    self.record(None)
+                },
+        }
+    }
+
+    /// Process the type declaration, with its constructors and methods.
+    fn process_type(&mut self, tree: &ast::Tree<Ast>) {
+        let name = tree.span_info.iter().enumerate().skip(1).find_map(|(index, seed)| match seed {
+            SpanSeed::Token(token) => Some((index, token.token.as_str())),
+            _ => None,
+        });
+        let declared = name.map(|(_, name)| self.module_name.clone().new_child(name));
+        let declares_target =
+            self.rename.kind == Kind::Type && declared.contains(&self.rename.target);
+        if let Some((index, _)) = name.filter(|_| declares_target) {
+            self.record_token(index)
+        }
+        let is_parent = self.rename.kind.is_type_member() && declared.contains(&self.rename.parent);
+        let outer_in_parent_type = mem::replace(&mut self.in_parent_type, is_parent);
+        let outer_self_type = mem::replace(&mut self.self_type, declared);
+        for crumb in tree.iter_subcrumbs() {
+            let member = tree.get(&crumb).unwrap();
+            self.in_location(iter::once(crumb.into()), |this| this.process_type_member(member))
+        }
+        self.in_parent_type = outer_in_parent_type;
+        self.self_type = outer_self_type;
+    }
+
+    fn process_type_member(&mut self, member: &Ast) {
+        let constructor = match member.shape() {
+            ast::Shape::Tree(tree) => first_token(tree),
+            _ => None,
+        };
+        let constructor = constructor.filter(|name| is_valid_name(name, true));
+        if let Some(name) = constructor {
+            let is_target = self.in_parent_type && self.rename.kind == Kind::Constructor;
+            if is_target && name == self.rename.old_name() {
+                self.record_token(0)
+            }
+            for (crumb, field) in member.enumerate() {
+                self.in_location(iter::once(crumb), |this| this.process_ast(field))
+            }
+        } else if let Some(definition) = DefinitionInfo::from_line_ast(member, ScopeKind::Root, 0) {
+            let defines_target = self.in_parent_type && self.rename.kind == Kind::TypeMethod;
+            self.process_definition(&definition, defines_target)
+        } else {
+            self.process_ast(member)
+        }
+    }
+
+    /// Process the definition. If `defines_target` is set, the definition with a plain name (not
+    /// an extension method) defines the renamed entity.
+    fn process_definition(&mut self, definition: &DefinitionInfo, defines_target: bool) {
+        let old_name = self.rename.old_name();
+        let name = &definition.name;
+        let extended_type = name.item.extended_target.iter().map(|segment| segment.as_str());
+        let extended_type = extended_type.collect_vec();
+        let extends_parent =
+            !extended_type.is_empty() && self.refers_to_parent(&extended_type, false);
+        self.in_location(name.crumbs.clone(), |this| {
+            for (index, segment) in name.item.extended_target.iter().enumerate() {
+                let is_target = match index {
+                    0 => this.refers_to_target(&segment.item),
+                    _ =>
+                        segment.item == old_name
+                            && this.refers_to_parent(&extended_type[..index], false),
+                };
+                if is_target {
+                    this.record(segment.crumbs.clone())
+                }
+            }
+            let defines_target =
+                if extended_type.is_empty() { defines_target } else { extends_parent };
+            if defines_target && name.item.name.item == old_name {
+                this.record(name.item.name.crumbs.clone())
+            }
+        });
+
+        let shadowed = self.shadowed || local_names(definition).contains(old_name);
+        let outer_shadowed = mem::replace(&mut self.shadowed, shadowed);
+        let self_type = extends_parent.then(|| self.rename.parent.clone());
+        let outer_self_type = match self_type {
+            Some(self_type) => Some(mem::replace(&mut self.self_type, Some(self_type))),
+            None => None,
+        };
+        for arg in &definition.args {
+            self.in_location(arg.crumbs.clone(), |this| this.process_ast(&arg.item))
+        }
+        let body = definition.body();
+        self.in_location(body.crumbs.clone(), |this| this.process_ast(body.item));
+        self.shadowed = outer_shadowed;
+        if let Some(outer_self_type) = outer_self_type {
+            self.self_type = outer_self_type;
+        }
+    }
+
+    /// Process the module path, either a single identifier or an access chain.
+    fn process_path(&mut self, path: &Ast, absolute: bool) {
+        if let Some(chain) = ast::opr::as_access_chain(path) {
+            self.process_access_chain(&chain, absolute)
+        }
+    }
+
+    /// Process the chain of access operators, like `Module.Type.method`. Apart from the first one,
+    /// its segments refer to the renamed entity if they follow a path referring to its parent.
+    fn process_access_chain(&mut self, chain: &ast::opr::Chain, absolute: bool) {
+        let old_name = self.rename.old_name();
+        let renames_method_calls =
+            self.rename.rename_method_calls && self.rename.kind == Kind::TypeMethod;
+        let mut path = Some(Vec::new());
+        for (index, operand) in chain.enumerate_operands().enumerate() {
+            let Some(operand) = operand else {
+                path = None;
+                continue;
+            };
+            match identifier(&operand.item.arg) {
+                Some(name) => {
+                    let is_target = match index {
+                        0 => !absolute && self.refers_to_target(name),
+                        _ => {
+                            let after_parent = path
+                                .as_ref()
+                                .map_or(false, |path| self.refers_to_parent(path, absolute));
+                            name == old_name && (after_parent || renames_method_calls)
+                        }
+                    };
+                    if is_target {
+                        self.record(operand.crumbs.clone())
+                    }
+                    if let Some(path) = &mut path {
+                        path.push(name)
+                    }
+                }
+                None => {
+                    path = None;
+                    let arg = &operand.item.arg;
+                    self.in_location(operand.crumbs.clone(), |this| this.process_ast(arg))
+                }
+            }
+        }
+    }
+
+    /// Process the expression, including the definitions it may contain.
+    fn process_ast(&mut self, ast: &Ast) {
+        if let Some(definition) = DefinitionInfo::from_line_ast(ast, ScopeKind::NonRoot, 0) {
+            self.process_definition(&definition, false)
+        } else if let Some(named_argument) = match_named_argument(ast) {
+            // The argument name refers to the parameter, not the renamed entity.
+            let crumb = ast::crumbs::InfixCrumb::RightOperand;
+            self.in_location(iter::once(crumb.into()), |this| this.process_ast(named_argument.rarg))
+        } else if let Some(chain) = ast::opr::as_access_chain(ast) {
+            self.process_access_chain(&chain, false)
+        } else if let Some(name) = identifier(ast) {
+            if self.refers_to_target(name) {
+                self.record(None)
+            }
+        } else {
+            for (crumb, child) in ast.enumerate() {
+                self.in_location(iter::once(crumb), |this| this.process_ast(child))
+            }
+        }
+    }
+}
+
+
+
+// =====================
+// === Local Renames ===
+// =====================
+
+/// Rename a local variable of the definition: either its parameter or a variable introduced in
+/// its body. Returns the updated definition.
+///
+/// Fails if the definition has no such variable, or if the new name is already used in it.
+pub fn rename_local(
+    definition: &DefinitionInfo,
+    old_name: &str,
+    new_name: &str,
+) -> FallibleResult<DefinitionInfo> {
+    if !is_valid_name(new_name, false) {
+        return Err(InvalidName { name: new_name.into(), kind: "variable" }.into());
+    }
+    let mut is_local = false;
+    let mut used_names = HashSet::new();
+    let mut occurrences = Vec::new();
+    for arg in &definition.args {
+        if let Some(name) = ast::identifier::as_var(&arg.item) {
+            is_local |= name == old_name;
+            if name == old_name {
+                occurrences.push(arg.crumbs.clone());
+            }
+            used_names.insert(name.to_owned());
+        }
+    }
+    let body = definition.body();
+    let lines = match known::Block::try_from(*body) {
+        Ok(_) => body.item.enumerate().map(|(crumb, line)| body.descendant(crumb, line)).collect(),
+        Err(_) => vec![body],
+    };
+    for line in lines {
+        let usage = alias_analysis::analyze_ast(line.item);
+        is_local |= usage.introduced.iter().any(|name| name.item == old_name);
+        for name in usage.all_identifiers() {
+            if name.item == old_name {
+                occurrences.push(line.crumbs.iter().cloned().chain(name.crumbs).collect());
+            }
+            used_names.insert(name.item);
+        }
+    }
+    if !is_local {
+        Err(NoSuchVariable(old_name.into()).into())
+    } else if used_names.contains(new_name) {
+        Err(NameTaken(new_name.into()).into())
+    } else {
+        let mut ast = definition.ast.clone();
+        for crumbs in occurrences {
+            let renamed =
+                ast.get_traversing(&crumbs)?.with_shape(ast::Var { name: new_name.into() });
+            ast = ast.set_traversing(&crumbs, renamed)?;
+        }
+        Ok(DefinitionInfo { ast, ..definition.clone() })
+    }
+}
+
+
+
+// ============
+// === Test ===
+// ============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::definition;
+
+    use parser::Parser;
+
+    const GEOMETRY: &str = "local.Proj.Geometry";
+
+    /// Run the rename on the module's code, returning the updated code.
+    fn run_rename(parser: &Parser, rename: &Rename, module_name: &str, code: &str) -> String {
+        let ast = parser.parse_module(code, default()).unwrap();
+        let module = module::Info { ast };
+        let module_name = QualifiedName::from_text(module_name).unwrap();
+        let mut code = code.to_owned();
+        for change in rename.changes(&module, &module_name).unwrap() {
+            code.replace_range(change.range.start.value..change.range.end.value, &change.text);
+        }
+        code
+    }
+
+    fn rename(target: &str, kind: Kind, new_name: &str) -> Rename {
+        let target = QualifiedName::from_text(target).unwrap();
+        Rename::new(target, kind, new_name).unwrap()
+    }
+
+    #[test]
+    fn renaming_module_method() {
+        let parser = Parser::new();
+        let rename = rename("local.Proj.Geometry.area", Kind::ModuleMethod, "surface");
+
+        let code = r"area : Number -> Number
+area r = r * r
+double_area r = 2 * (area r)
+shadowing area = area + 1
+main = Geometry.area 5";
+        let expected = r"surface : Number -> Number
+surface r = r * r
+double_area r = 2 * (surface r)
+shadowing area = area + 1
+main = Geometry.surface 5";
+        assert_eq!(run_rename(&parser, &rename, GEOMETRY, code), expected);
+
+        let code = r"import local.Proj.Geometry
+import local.Proj.Geometry as G
+from local.Proj.Geometry import area
+from local.Proj.Geometry export area
+
+main =
+    a = Geometry.area 1
+    b = G.area 2
+    c = local.Proj.Geometry.area 3
+    d = area 4
+    e = a.area
+    f = Other.area 5";
+        let expected = r"import local.Proj.Geometry
+import local.Proj.Geometry as G
+from local.Proj.Geometry import surface
+from local.Proj.Geometry export surface
+
+main =
+    a = Geometry.surface 1
+    b = G.surface 2
+    c = local.Proj.Geometry.surface 3
+    d = surface 4
+    e = a.area
+    f = Other.area 5";
+        assert_eq!(run_rename(&parser, &rename, "local.Proj.Main", code), expected);
+
+        // Without an unqualified import, `area` refers to something else.
+        let code = r"import local.Proj.Geometry
+
+main =
+    a = area 4";
+        assert_eq!(run_rename(&parser, &rename, "local.Proj.Main", code), code);
+    }
+
+    #[test]
+    fn renaming_type() {
+        let parser = Parser::new();
+        let rename = rename("local.Proj.Geometry.Shape", Kind::Type, "Figure");
+
+        let code = r"type Shape
+    Circle r
+    area self = 1
+Shape.perimeter self = 2
+make : Shape
+make = Shape.Circle 1";
+        let expected = r"type Figure
+    Circle r
+    area self = 1
+Figure.perimeter self = 2
+make : Figure
+make = Figure.Circle 1";
+        assert_eq!(run_rename(&parser, &rename, GEOMETRY, code), expected);
+
+        let code = r"import local.Proj.Geometry
+import local.Proj.Geometry.Shape
+from local.Proj.Geometry import Shape, make
+
+main = Shape.Circle (Geometry.Shape.Circle 2)";
+        let expected = r"import local.Proj.Geometry
+import local.Proj.Geometry.Figure
+from local.Proj.Geometry import Figure, make
+
+main = Figure.Circle (Geometry.Figure.Circle 2)";
+        assert_eq!(run_rename(&parser, &rename, "local.Proj.Main", code), expected);
+    }
+
+    #[test]
+    fn renaming_type_members() {
+        let parser = Parser::new();
+        let code = r"type Shape
+    Circle r
+    area self = self.r * self.r
+    unit = Circle 1
+Shape.double self = 2 * self.area
+
+main =
+    shape = Shape.Circle 2
+    shape.area";
+
+        let constructor = rename("local.Proj.Geometry.Shape.Circle", Kind::Constructor, "Disc");
+        let expected = r"type Shape
+    Disc r
+    area self = self.r * self.r
+    unit = Disc 1
+Shape.double self = 2 * self.area
+
+main =
+    shape = Shape.Disc 2
+    shape.area";
+        assert_eq!(run_rename(&parser, &constructor, GEOMETRY, code), expected);
+
+        let mut method = rename("local.Proj.Geometry.Shape.area", Kind::TypeMethod, "surface");
+        let expected = r"type Shape
+    Circle r
+    surface self = self.r * self.r
+    unit = Circle 1
+Shape.double self = 2 * self.surface
+
+main =
+    shape = Shape.Circle 2
+    shape.area";
+        assert_eq!(run_rename(&parser, &method, GEOMETRY, code), expected);
+
+        method.rename_method_calls = true;
+        let expected = expected.replace("shape.area", "shape.surface");
+        assert_eq!(run_rename(&parser, &method, GEOMETRY, code), expected);
+    }
+
+    #[test]
+    fn invalid_names() {
+        let target = QualifiedName::from_text("local.Proj.Geometry.Shape").unwrap();
+        assert!(Rename::new(target.clone(), Kind::Type, "shape").is_err());
+        assert!(Rename::new(target.clone(), Kind::Type, "Sha pe").is_err());
+        assert!(Rename::new(target.clone(), Kind::ModuleMethod, "Shape").is_err());
+        assert!(Rename::new(target, Kind::Constructor, "Figure").is_ok());
+        let module_method = QualifiedName::from_text("local.Proj.Main.main").unwrap();
+        assert!(Rename::new(module_method, Kind::TypeMethod, "run").is_err());
+    }
+
+    #[test]
+    fn renaming_local_variables() {
+        let parser = Parser::new();
+        let code = r"
+main input =
+    foo = input + 2
+    bar = foo + 1
+    baz x = x + foo
+    foo";
+        let ast = parser.parse_module(code, default()).unwrap();
+        let main = module::get_definition(&ast, &definition::Id::new_plain_name("main")).unwrap();
+
+        let renamed = rename_local(&main, "foo", "qux").unwrap();
+        let expected = r"main input =
+    qux = input + 2
+    bar = qux + 1
+    baz x = x + qux
+    qux";
+        assert_eq!(renamed.ast.repr(), expected);
+
+        let renamed = rename_local(&main, "input", "number").unwrap();
+        assert_eq!(renamed.ast.repr(), expected.replace("qux", "foo").replace("input", "number"));
+
+        assert!(rename_local(&main, "sum", "total").is_err());
+        assert!(rename_local(&main, "foo", "bar").is_err());
+        assert!(rename_local(&main, "foo", "Bar").is_err());
+    }
+}
@@ -925,6 +925,7 @@ pub struct SuggestionEntryArgument {
     pub default_value: Option<String>,
     /// Optional list of possible values that this argument takes.
     #[serde(default, deserialize_with = "enso_prelude::deserialize_null_as_default")]
+    #[serde(serialize_with = "enso_prelude::serialize_as_some")]
     pub tag_values:    Vec<String>,
 }
 
@@ -0,0 +1,28 @@
+// Searching the documented entries by name, using the `SEARCH_INDEX` defined in the search index
+// script.
+
+;(function () {
+    const MAX_RESULTS = 50
+    const input = document.getElementById('search-input')
+    const results = document.getElementById('search-results')
+    input.addEventListener('input', () => {
+        const query = input.value.trim().toLowerCase()
+        results.replaceChildren()
+        if (query === '') {
+            return
+        }
+        const lastSegment = name => name.slice(name.lastIndexOf('.') + 1).toLowerCase()
+        const matches = SEARCH_INDEX.filter(entry => entry.name.toLowerCase().includes(query))
+        // The entries whose own name starts with the query are the most relevant.
+        const rank = entry => (lastSegment(entry.name).startsWith(query) ? 0 : 1)
+        matches.sort((a, b) => rank(a) - rank(b))
+        for (const entry of matches.slice(0, MAX_RESULTS)) {
+            const item = document.createElement('li')
+            const link = document.createElement('a')
+            link.href = entry.page
+            link.textContent = entry.name
+            item.append(link, ` (${entry.kind})`)
+            results.append(item)
+        }
+    })
+})()
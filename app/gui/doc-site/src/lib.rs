@@ -0,0 +1,411 @@
+//! Generator of the static documentation site of whole libraries.
+//!
+//! The site is generated from the suggestion database, usually dumped by
+//! [`SuggestionDatabase::dump_as_json`]. It has a page for every module, type, constructor and
+//! method, rendered the same way as in the IDE's documentation panel (see
+//! [`ide_view_documentation::html`]), but with the pages linked to each other. The index page
+//! lists the modules of every library and allows searching the entries by name, using the search
+//! index generated along with the pages.
+
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+// === Non-Standard Linter Configuration ===
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+#![warn(unused_qualifications)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+
+use enso_prelude::*;
+use horrorshow::prelude::*;
+
+use double_representation::name::QualifiedName;
+use enso_doc_parser::DocSection;
+use enso_doc_parser::Tag;
+use enso_suggestion_database::documentation_ir::EntryDocumentation;
+use enso_suggestion_database::entry;
+use enso_suggestion_database::entry::Kind;
+use enso_suggestion_database::Entry;
+use enso_suggestion_database::SuggestionDatabase;
+use horrorshow::helper::doctype;
+use horrorshow::owned_html;
+use ide_view_documentation::html;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The path of the index page, listing the libraries' modules.
+pub const INDEX_PAGE: &str = "index.html";
+
+/// The path of the page listing the examples.
+pub const EXAMPLES_PAGE: &str = "examples.html";
+
+/// The path of the script defining the search index.
+pub const SEARCH_INDEX: &str = "search-index.js";
+
+/// The path of the stylesheet of all pages.
+pub const STYLESHEET: &str = "styles.css";
+
+const SEARCH_SCRIPT: &str = include_str!("../assets/search.js");
+const SITE_STYLESHEET: &str = include_str!("../assets/site.css");
+
+
+
+// =============
+// === Pages ===
+// =============
+
+/// The directory of the pages of the given entry kind. The kinds without a directory have no
+/// pages.
+fn page_directory(kind: Kind) -> Option<&'static str> {
+    match kind {
+        Kind::Module => Some("modules"),
+        Kind::Type => Some("types"),
+        Kind::Constructor => Some("constructors"),
+        Kind::Method => Some("methods"),
+        Kind::Function | Kind::Local => None,
+    }
+}
+
+/// Check if the entry is marked as private, so it should not be documented.
+fn is_private(entry: &Entry) -> bool {
+    let is_private_tag =
+        |section: &DocSection| matches!(section, DocSection::Tag { tag: Tag::Private, .. });
+    entry.documentation.iter().any(is_private_tag)
+}
+
+/// The name of the page file for the given name, without the extension.
+///
+/// The characters other than ASCII alphanumerics, `_`, `-` and `.` are escaped as `~` followed by
+/// the hex codes of their UTF-8 bytes, as operator names like `==` or `/` cannot be used in paths.
+fn file_name(name: &str) -> String {
+    let is_safe = |byte: &u8| byte.is_ascii_alphanumeric() || matches!(byte, b'_' | b'-' | b'.');
+    name.bytes()
+        .map(|byte| match is_safe(&byte) {
+            true => char::from(byte).to_string(),
+            false => format!("~{byte:02X}"),
+        })
+        .collect()
+}
+
+/// An entry documented on its own page.
+#[derive(Clone, Debug)]
+struct DocumentedEntry {
+    id:    entry::Id,
+    entry: Rc<Entry>,
+    name:  QualifiedName,
+    /// The path of the page, relative to the site's root.
+    page:  String,
+}
+
+/// All entries documented on their own pages.
+#[derive(Clone, Debug, Default)]
+struct Pages {
+    /// The entries sorted by their names.
+    entries: Vec<DocumentedEntry>,
+    by_name: HashMap<QualifiedName, String>,
+}
+
+impl Pages {
+    /// Collect the documented entries and assign them pages.
+    ///
+    /// The extension methods of the same type defined in different modules have the same
+    /// qualified name. Their pages are disambiguated by the defining module's name, placed after
+    /// `@`, and the links by the qualified name lead to the method of the first module by name.
+    fn new(database: &SuggestionDatabase) -> Self {
+        let entries = database.keys().into_iter().filter_map(|id| {
+            let entry = database.lookup(id).ok()?;
+            let directory = page_directory(entry.kind)?;
+            let name = entry.qualified_name();
+            let page = format!("{directory}/{}", file_name(&name.to_string()));
+            (!is_private(&entry)).then_some(DocumentedEntry { id, entry, name, page })
+        });
+        let entries = entries.sorted_by(|a, b| {
+            a.name.cmp(&b.name).then_with(|| a.entry.defined_in.cmp(&b.entry.defined_in))
+        });
+        let mut entries = entries.collect_vec();
+        let page_counts = entries.iter().counts_by(|documented| documented.page.clone());
+        for documented in &mut entries {
+            if page_counts[&documented.page] > 1 {
+                let defined_in = file_name(&documented.entry.defined_in.to_string());
+                documented.page = format!("{}@{defined_in}", documented.page);
+            }
+            documented.page.push_str(".html");
+        }
+        let mut by_name = HashMap::new();
+        for documented in &entries {
+            by_name.entry(documented.name.clone()).or_insert_with(|| documented.page.clone());
+        }
+        Self { entries, by_name }
+    }
+
+    /// The address of the entry's page, relative to a page placed in `depth` nested directories.
+    fn address(&self, name: &QualifiedName, depth: usize) -> Option<String> {
+        let page = self.by_name.get(name)?;
+        Some(format!("{}{page}", "../".repeat(depth)))
+    }
+}
+
+
+
+// ===================
+// === SearchIndex ===
+// ===================
+
+/// An entry of the search index.
+#[allow(missing_docs)]
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct SearchIndexEntry {
+    pub name: String,
+    pub kind: Kind,
+    /// The path of the entry's page, relative to the site's root.
+    pub page: String,
+}
+
+fn search_index(pages: &Pages) -> FallibleResult<String> {
+    let entries = pages.entries.iter().map(|documented| SearchIndexEntry {
+        name: documented.name.to_string(),
+        kind: documented.entry.kind,
+        page: documented.page.clone(),
+    });
+    let json = serde_json::to_string(&entries.collect_vec())?;
+    Ok(format!("const SEARCH_INDEX = {json}\n"))
+}
+
+
+
+// ============
+// === Site ===
+// ============
+
+/// The generated documentation site.
+#[derive(Clone, Debug, Default)]
+pub struct Site {
+    /// The content of the site's files by their paths, relative to the site's root.
+    pub files: BTreeMap<String, String>,
+}
+
+impl Site {
+    /// Generate the site documenting all entries of the database.
+    pub fn generate(database: &SuggestionDatabase) -> FallibleResult<Self> {
+        let pages = Pages::new(database);
+        let mut files = BTreeMap::new();
+        for documented in &pages.entries {
+            let docs = EntryDocumentation::new(database, &documented.id)?;
+            let address = |name: &QualifiedName| pages.address(name, 1);
+            let content = html::render_with_page_addresses(&docs, &address);
+            let title = documented.name.to_string();
+            let breadcrumbs = breadcrumbs(&pages, &documented.name);
+            let page = page(&title, 1, breadcrumbs, Raw(content));
+            files.insert(documented.page.clone(), page);
+        }
+        files.insert(INDEX_PAGE.into(), index_page(&pages));
+        files.insert(EXAMPLES_PAGE.into(), examples_page(database));
+        files.insert(SEARCH_INDEX.into(), search_index(&pages)?);
+        files.insert(STYLESHEET.into(), format!("{}\n{SITE_STYLESHEET}", html::STYLESHEET));
+        Ok(Self { files })
+    }
+
+    /// Write the site's files to the given directory.
+    pub fn write_to(&self, root: &Path) -> FallibleResult {
+        for (path, content) in &self.files {
+            let path = root.join(path);
+            if let Some(directory) = path.parent() {
+                std::fs::create_dir_all(directory)?;
+            }
+            std::fs::write(path, content)?;
+        }
+        Ok(())
+    }
+}
+
+/// A complete HTML page. `depth` is the number of directories the page is nested in.
+fn page(title: &str, depth: usize, navigation: impl Render, content: impl Render) -> String {
+    let root = "../".repeat(depth);
+    let page = owned_html! {
+        : doctype::HTML;
+        html {
+            head {
+                meta(charset="utf-8");
+                title { : title; }
+                link(rel="stylesheet", href=format!("{root}{STYLESHEET}"));
+            }
+            body {
+                nav(class="site-navigation") {
+                    a(href=format!("{root}{INDEX_PAGE}")) { : "Index"; }
+                    a(href=format!("{root}{EXAMPLES_PAGE}")) { : "Examples"; }
+                }
+                : &navigation;
+                h1(class="page-title") { : title; }
+                div(class="site-content") { : &content; }
+            }
+        }
+    };
+    page.into_string().unwrap()
+}
+
+/// The links to the pages of the entry's parents, e.g. the module and type of a method.
+fn breadcrumbs<'a>(pages: &'a Pages, name: &QualifiedName) -> impl Render + 'a {
+    let parents = name.parents().map(|parent| parent.to_owned()).collect_vec();
+    let parents = parents.into_iter().rev();
+    let parents = parents.filter_map(|parent| Some((pages.address(&parent, 1)?, parent)));
+    let parents = parents.collect_vec();
+    owned_html! {
+        div(class="breadcrumbs") {
+            @ for (address, parent) in &parents {
+                a(href=address) { : parent.to_string(); }
+                : " / ";
+            }
+        }
+    }
+}
+
+/// The index page, listing the modules of every library, with the search box.
+fn index_page(pages: &Pages) -> String {
+    let modules = pages.entries.iter().filter(|documented| documented.entry.kind == Kind::Module);
+    let libraries = modules.into_group_map_by(|documented| documented.name.project().clone());
+    let libraries = libraries.into_iter().sorted_by_key(|(library, _)| library.to_string());
+    let libraries = libraries.collect_vec();
+    let content = owned_html! {
+        input(id="search-input", type="search", placeholder="Search by name");
+        ul(id="search-results", class="unordered-list");
+        @ for (library, modules) in &libraries {
+            h2 { : library.to_string(); }
+            ul(class="unordered-list") {
+                @ for module in modules {
+                    li { a(href=&module.page) { : module.name.to_string(); } }
+                }
+            }
+        }
+        script(src=SEARCH_INDEX);
+        script { : Raw(SEARCH_SCRIPT); }
+    };
+    page("Libraries", 0, "", content)
+}
+
+/// The page listing the examples available in the IDE.
+fn examples_page(database: &SuggestionDatabase) -> String {
+    let examples = database.iterate_examples().sorted_by(|a, b| a.name.cmp(&b.name)).collect_vec();
+    let content = owned_html! {
+        div(class="enso-docs") {
+            @ for example in &examples {
+                h2 { : &example.name; }
+                : Raw(&example.documentation_html);
+                div(class="example-container") { pre { : &example.code; } }
+            }
+        }
+    };
+    page("Examples", 0, "", content)
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use enso_suggestion_database::mock;
+
+    #[test]
+    fn generating_site() {
+        let database = mock::standard_db_mock();
+        let site = Site::generate(&database).unwrap();
+        let paths = site.files.keys().map(String::as_str).collect_vec();
+        let expected_pages = [
+            "constructors/Standard.Base.Maybe.Some.html",
+            "methods/Standard.Base.Maybe.is_some.html",
+            "methods/local.Project.Submodule.module_method.html",
+            "modules/Standard.Base.html",
+            "modules/local.Project.Submodule.html",
+            "types/Standard.Base.Maybe.html",
+            "types/local.Project.Submodule.TestType.html",
+            INDEX_PAGE,
+            EXAMPLES_PAGE,
+            SEARCH_INDEX,
+            STYLESHEET,
+        ];
+        for page in expected_pages {
+            assert!(paths.contains(&page), "Missing {page} in {paths:?}.");
+        }
+
+        // The pages are linked to each other.
+        let type_page = &site.files["types/Standard.Base.Maybe.html"];
+        assert!(type_page.contains(r#"href="../constructors/Standard.Base.Maybe.Some.html""#));
+        assert!(type_page.contains(r#"href="../methods/Standard.Base.Maybe.is_some.html""#));
+        assert!(type_page.contains(r#"href="../modules/Standard.Base.html""#));
+        let module_page = &site.files["modules/local.Project.Submodule.html"];
+        assert!(module_page.contains(r#"href="../types/local.Project.Submodule.TestType.html""#));
+        let index = &site.files[INDEX_PAGE];
+        assert!(index.contains(r#"href="modules/local.Project.Submodule.html""#));
+
+        let search_index = &site.files[SEARCH_INDEX];
+        let json = search_index.trim().strip_prefix("const SEARCH_INDEX = ").unwrap();
+        let entries: Vec<serde_json::Value> = serde_json::from_str(json).unwrap();
+        let is_some = entries.iter().find(|e| e["name"] == "Standard.Base.Maybe.is_some").unwrap();
+        assert_eq!(is_some["kind"], "Method");
+        assert_eq!(is_some["page"], "methods/Standard.Base.Maybe.is_some.html");
+    }
+
+    #[test]
+    fn escaping_page_names() {
+        assert_eq!(file_name("Standard.Base.Maybe.is_some"), "Standard.Base.Maybe.is_some");
+        assert_eq!(file_name("Standard.Base.Number./"), "Standard.Base.Number.~2F");
+        assert_eq!(file_name("Standard.Base.Number.<="), "Standard.Base.Number.~3C~3D");
+        assert_eq!(file_name("Standard.Base.Any.=="), "Standard.Base.Any.~3D~3D");
+        assert_eq!(file_name("Standard.Base.Any.~x"), "Standard.Base.Any.~7Ex");
+
+        let database = mock::standard_db_mock();
+        let maybe = QualifiedName::from_text("Standard.Base.Maybe").unwrap();
+        let boolean = QualifiedName::from_text("Standard.Base.Boolean").unwrap();
+        let operator = Entry::new_nonextension_method(maybe, "*", boolean, false);
+        database.put_entry(100, operator);
+        let site = Site::generate(&database).unwrap();
+        assert!(site.files.contains_key("methods/Standard.Base.Maybe.~2A.html"));
+        let type_page = &site.files["types/Standard.Base.Maybe.html"];
+        assert!(type_page.contains(r#"href="../methods/Standard.Base.Maybe.~2A.html""#));
+    }
+
+    #[test]
+    fn disambiguating_extension_methods() {
+        let database = mock::standard_db_mock();
+        let maybe = QualifiedName::from_text("Standard.Base.Maybe").unwrap();
+        let boolean = QualifiedName::from_text("Standard.Base.Boolean").unwrap();
+        let extension = |module: &str| {
+            let module = QualifiedName::from_text(module).unwrap();
+            Entry::new_method(module, maybe.clone(), "is_empty", boolean.clone(), false)
+        };
+        database.put_entry(100, extension("local.Project.Submodule"));
+        database.put_entry(101, extension("local.Project"));
+        let site = Site::generate(&database).unwrap();
+        let first = "methods/Standard.Base.Maybe.is_empty@local.Project.html";
+        let second = "methods/Standard.Base.Maybe.is_empty@local.Project.Submodule.html";
+        assert!(site.files.contains_key(first));
+        assert!(site.files.contains_key(second));
+        assert!(!site.files.contains_key("methods/Standard.Base.Maybe.is_empty.html"));
+        // The methods without extensions of the same name keep the plain page names.
+        assert!(site.files.contains_key("methods/Standard.Base.Maybe.is_some.html"));
+    }
+
+    #[test]
+    fn generating_site_from_dump() {
+        let database = mock::standard_db_mock();
+        let dumped = SuggestionDatabase::new_from_json_dump(&database.dump_as_json()).unwrap();
+        let site = Site::generate(&database).unwrap();
+        let site_from_dump = Site::generate(&dumped).unwrap();
+        assert_eq!(site.files, site_from_dump.files);
+    }
+}
@@ -0,0 +1,57 @@
+//! Generate the static documentation site from the suggestion database dumped as JSON.
+//!
+//! ```text
+//! enso-doc-site <DATABASE_DUMP> <OUTPUT_DIRECTORY>
+//! ```
+
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+
+use enso_prelude::*;
+
+use clap::Parser;
+use enso_doc_site::Site;
+use enso_suggestion_database::SuggestionDatabase;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+
+
+// ============
+// === Args ===
+// ============
+
+/// Generate the static documentation site from the suggestion database dump.
+#[derive(Debug, Parser)]
+struct Args {
+    /// The suggestion database dumped as JSON by the IDE.
+    database_dump:    PathBuf,
+    /// The directory where the site is written.
+    output_directory: PathBuf,
+}
+
+
+
+// ============
+// === Main ===
+// ============
+
+fn generate(args: &Args) -> FallibleResult {
+    let dump = std::fs::read_to_string(&args.database_dump)?;
+    let database = SuggestionDatabase::new_from_json_dump(&dump)?;
+    Site::generate(&database)?.write_to(&args.output_directory)
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match generate(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("Failed to generate the documentation site: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,150 @@
+//! A native, headless front end of the IDE's controllers.
+//!
+//! Connects to a running Language Server of a project and executes graph editing scripts (see
+//! [`enso_gui::script`] for their syntax) using the same controllers as the IDE, without the GUI.
+//! It allows batch refactoring of many projects and end-to-end testing of the controllers.
+//!
+//! The IDE's code expects a single-threaded executor for its tasks; here it is a
+//! `tokio::task::LocalSet`, which also drives the WebSocket connections.
+
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+// === Non-Standard Linter Configuration ===
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+#![warn(unused_qualifications)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+
+
+
+mod transport;
+
+use enso_gui::prelude::*;
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+
+use clap::Parser;
+use clap::ValueHint;
+use double_representation::name::project;
+use enso_gui::engine_protocol::project_manager::ProjectName;
+use enso_gui::model::project::synchronized::Properties;
+use enso_gui::script;
+use futures::future::LocalFutureObj;
+use futures::task::LocalSpawn;
+use futures::task::SpawnError;
+use url::Url;
+
+
+
+// =====================
+// === CLI Arguments ===
+// =====================
+
+#[derive(Parser, Debug)]
+#[clap(version, about)]
+struct Args {
+    /// The Language Server's JSON-RPC endpoint.
+    #[clap(long, value_name = "URL", value_hint = ValueHint::Url)]
+    rpc_endpoint: Url,
+
+    /// The Language Server's binary protocol endpoint.
+    #[clap(long, value_name = "URL", value_hint = ValueHint::Url)]
+    binary_endpoint: Url,
+
+    /// The qualified name of the project served by the Language Server, e.g.
+    /// `local.New_Project_1`.
+    #[clap(long)]
+    project: String,
+
+    /// The version of the engine running the Language Server. The version required by the IDE is
+    /// assumed if not given.
+    #[clap(long)]
+    engine_version: Option<semver::Version>,
+
+    /// Time in seconds to wait for the pending changes to be sent to the Language Server, after
+    /// all the scripts are executed.
+    #[clap(long, value_name = "SECONDS", default_value = "30")]
+    shutdown_timeout: u64,
+
+    /// The scripts to execute, in the given order.
+    #[clap(value_name = "SCRIPT", required = true, value_hint = ValueHint::FilePath)]
+    scripts: Vec<PathBuf>,
+}
+
+
+
+// ===============
+// === Spawner ===
+// ===============
+
+/// Spawns the IDE's tasks on the current `tokio::task::LocalSet`.
+#[derive(Clone, Copy, Debug)]
+struct LocalSetSpawner;
+
+impl LocalSpawn for LocalSetSpawner {
+    fn spawn_local_obj(&self, future: LocalFutureObj<'static, ()>) -> Result<(), SpawnError> {
+        tokio::task::spawn_local(future);
+        Ok(())
+    }
+}
+
+
+
+// ============
+// === Main ===
+// ============
+
+/// Connect to the Language Server and create the project model.
+async fn open_project(args: &Args) -> FallibleResult<model::Project> {
+    let project_name = project::QualifiedName::from_text(&args.project)?;
+    let displayed_name = ProjectName::new_unchecked(project_name.project.as_str());
+    let engine_version = args.engine_version.clone();
+    let engine_version = engine_version.unwrap_or_else(enso_config::engine_version_required);
+    let properties = Properties { id: default(), project_name, displayed_name, engine_version };
+    let json = transport::WebSocket::new_opened(args.rpc_endpoint.as_str()).await?;
+    let binary = transport::WebSocket::new_opened(args.binary_endpoint.as_str()).await?;
+    model::project::Synchronized::new_with_transports(None, json, binary, properties).await
+}
+
+/// Execute all the scripts given in the arguments.
+async fn run(args: &Args) -> FallibleResult {
+    let project = open_project(args).await?;
+    let mut runner = script::Runner::new(project);
+    for path in &args.scripts {
+        info!("Executing script {}.", path.display());
+        let script = std::fs::read_to_string(path)?;
+        let result = runner.run(&script).await;
+        result.map_err(|error| failure::format_err!("{}: {error}", path.display()))?;
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build();
+    let runtime = runtime.expect("Failed to create the tokio runtime.");
+    let local_set = tokio::task::LocalSet::new();
+    executor::global::set_spawner(LocalSetSpawner);
+    let result = local_set.block_on(&runtime, run(&args));
+    // Dropping the project model closes its modules and connections. The spawned tasks finish
+    // once all the pending changes are sent and acknowledged.
+    let timeout = Duration::from_secs(args.shutdown_timeout);
+    if runtime.block_on(tokio::time::timeout(timeout, local_set)).is_err() {
+        warn!("Some pending changes may have not been sent to the Language Server.");
+    }
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("{error}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,168 @@
+//! Native WebSocket-based `Transport` implementation, used instead of the browser's WebSocket.
+
+use enso_prelude::*;
+
+use failure::Error;
+use failure::Fail;
+use futures::channel::mpsc;
+use futures::SinkExt;
+use futures::StreamExt;
+use json_rpc::Transport;
+use json_rpc::TransportEvent;
+use websocket_lite::ClientBuilder;
+use websocket_lite::Message;
+use websocket_lite::Opcode;
+
+
+
+// ==============
+// === Errors ===
+// ==============
+
+/// Failed to establish the connection.
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(display = "Failed to connect to {}: {}.", url, reason)]
+pub struct FailedToConnect {
+    url:    String,
+    reason: String,
+}
+
+/// The message could not be sent, because the connection is closed.
+#[derive(Clone, Copy, Debug, Fail)]
+#[fail(display = "The WebSocket connection is closed.")]
+pub struct ConnectionClosed;
+
+
+
+// =================
+// === WebSocket ===
+// =================
+
+/// A request to the task handling the connection.
+#[derive(Debug)]
+enum Request {
+    Send(Message),
+    SetEventTransmitter(mpsc::UnboundedSender<TransportEvent>),
+}
+
+/// A handle to the WebSocket connection.
+///
+/// The connection is handled by a task spawned on the current `tokio::task::LocalSet`, which
+/// sends the messages and emits the received ones as [`TransportEvent`]s. The task closes the
+/// connection and finishes once the handle is dropped and all the messages are sent.
+#[derive(Debug)]
+pub struct WebSocket {
+    requests: mpsc::UnboundedSender<Request>,
+}
+
+impl WebSocket {
+    /// Establish connection with endpoint defined by the given URL.
+    ///
+    /// Must be called within a `tokio::task::LocalSet`.
+    pub async fn new_opened(url: &str) -> Result<WebSocket, FailedToConnect> {
+        let failed = |reason: websocket_lite::Error| FailedToConnect {
+            url:    url.to_owned(),
+            reason: reason.to_string(),
+        };
+        let client = ClientBuilder::new(url).map_err(|error| failed(error.into()))?;
+        let client = client.async_connect().await.map_err(failed)?;
+        info!("Connection to {url} opened.");
+        let (requests, request_receiver) = mpsc::unbounded();
+        tokio::task::spawn_local(handle_connection(client, request_receiver));
+        Ok(WebSocket { requests })
+    }
+
+    fn request(&self, request: Request) -> Result<(), Error> {
+        self.requests.unbounded_send(request).map_err(|_| ConnectionClosed.into())
+    }
+}
+
+impl Transport for WebSocket {
+    fn send_text(&mut self, message: &str) -> Result<(), Error> {
+        debug!("Sending text message: {message}");
+        self.request(Request::Send(Message::text(message)))
+    }
+
+    fn send_binary(&mut self, message: &[u8]) -> Result<(), Error> {
+        debug!("Sending binary message of length {}.", message.len());
+        self.request(Request::Send(Message::binary(message.to_vec())))
+    }
+
+    fn set_event_transmitter(&mut self, transmitter: mpsc::UnboundedSender<TransportEvent>) {
+        if self.request(Request::SetEventTransmitter(transmitter)).is_err() {
+            warn!("Cannot set the event transmitter of a closed connection.");
+        }
+    }
+}
+
+/// Send the requested messages and emit the received ones, until the connection is closed by
+/// either side.
+async fn handle_connection<Client>(client: Client, mut requests: mpsc::UnboundedReceiver<Request>)
+where Client: futures::Stream<Item = websocket_lite::Result<Message>>
+        + futures::Sink<Message, Error = websocket_lite::Error> {
+    let (mut sink, mut stream) = client.split();
+    let mut transmitter = None;
+    // The events received before the transmitter was set.
+    let mut pending_events = Vec::new();
+    loop {
+        let event = tokio::select! {
+            request = requests.next() => match request {
+                Some(Request::Send(message)) => match sink.send(message).await {
+                    Ok(()) => None,
+                    Err(error) => {
+                        error!("Failed to send a message: {error}");
+                        Some(TransportEvent::Closed)
+                    }
+                },
+                Some(Request::SetEventTransmitter(new_transmitter)) => {
+                    for event in pending_events.drain(..) {
+                        channel::emit(&new_transmitter, event);
+                    }
+                    transmitter = Some(new_transmitter);
+                    None
+                }
+                None => {
+                    info!("Closing the connection.");
+                    if let Err(error) = sink.send(Message::close(None)).await {
+                        warn!("Failed to close the connection: {error}");
+                    }
+                    break;
+                }
+            },
+            message = stream.next() => match message {
+                Some(Ok(message)) => match message.opcode() {
+                    Opcode::Text => message.as_text().map(|text| {
+                        debug!("Received a text message: {text}");
+                        TransportEvent::TextMessage(text.to_owned())
+                    }),
+                    Opcode::Binary => Some(TransportEvent::BinaryMessage(message.data().to_vec())),
+                    Opcode::Ping => {
+                        if let Err(error) = sink.send(Message::pong(message.into_data())).await {
+                            warn!("Failed to respond to ping: {error}");
+                        }
+                        None
+                    }
+                    Opcode::Close => Some(TransportEvent::Closed),
+                    _ => None,
+                },
+                Some(Err(error)) => {
+                    error!("Failed to receive a message: {error}");
+                    Some(TransportEvent::Closed)
+                }
+                None => Some(TransportEvent::Closed),
+            },
+        };
+        if let Some(event) = event {
+            let closed = matches!(event, TransportEvent::Closed);
+            match &transmitter {
+                Some(transmitter) => channel::emit(transmitter, event),
+                None => pending_events.push(event),
+            }
+            if closed {
+                info!("Connection closed.");
+                break;
+            }
+        }
+    }
+}
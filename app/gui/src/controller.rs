@@ -9,6 +9,7 @@
 // === Export ===
 // ==============
 
+pub mod file_browser;
 pub mod graph;
 pub mod ide;
 pub mod module;
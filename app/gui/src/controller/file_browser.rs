@@ -0,0 +1,289 @@
+//! The content providers of the [File Browser](ensogl_component::file_browser::FileBrowser)
+//! component, listing the project's content roots and folders with the Language Server's file
+//! system API.
+
+use crate::prelude::*;
+
+use engine_protocol::language_server;
+use engine_protocol::language_server::ContentRoot;
+use engine_protocol::language_server::FileSystemObject;
+use engine_protocol::language_server::Path;
+use enso_frp as frp;
+use ensogl_component::file_browser::model::Entry;
+use ensogl_component::file_browser::model::EntryType;
+use ensogl_component::file_browser::model::FolderContent;
+use ensogl_component::file_browser::model::FolderType;
+use std::path::PathBuf;
+
+
+
+// ==============
+// === Errors ===
+// ==============
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(display = "The File Browser path {:?} does not start with a content root id.", path)]
+pub struct InvalidPath {
+    pub path: PathBuf,
+}
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(display = "The path {} is not a directory.", path)]
+pub struct NotADirectory {
+    pub path: Path,
+}
+
+
+
+// =============
+// === Paths ===
+// =============
+
+/// Convert the Language Server path to the path used by the File Browser. The first component of
+/// the File Browser path is the id of the content root, followed by the path segments.
+pub fn to_file_browser_path(path: &Path) -> PathBuf {
+    let root_id = path.root_id.to_string();
+    let segments = path.segments.iter().map(String::as_str);
+    std::iter::once(root_id.as_str()).chain(segments).collect()
+}
+
+/// Convert the File Browser path back to the Language Server path. It is the inverse of
+/// [`to_file_browser_path`].
+pub fn from_file_browser_path(path: &std::path::Path) -> FallibleResult<Path> {
+    let invalid_path = || InvalidPath { path: path.to_owned() };
+    let mut components = path.iter().map(|component| component.to_str());
+    let root_id = components.next().flatten().ok_or_else(invalid_path)?;
+    let root_id = Uuid::parse_str(root_id).map_err(|_| invalid_path())?;
+    let segments = components.map(|segment| segment.map(ToOwned::to_owned));
+    let segments = segments.collect::<Option<Vec<_>>>().ok_or_else(invalid_path)?;
+    Ok(Path { root_id, segments })
+}
+
+
+
+// ==============
+// === Folder ===
+// ==============
+
+/// The content of a folder, listed with the `file/list` Language Server method. The content of the
+/// subfolders is listed only when requested by the File Browser.
+#[derive(Clone, CloneRef, Debug)]
+pub struct Folder {
+    connection: Rc<language_server::Connection>,
+    path:       Rc<Path>,
+}
+
+impl Folder {
+    /// Constructor. The path is expected to be a directory.
+    pub fn new(connection: Rc<language_server::Connection>, path: Path) -> Self {
+        Self { connection, path: Rc::new(path) }
+    }
+
+    /// Open the folder at the given path, checking with the `file/info` Language Server method
+    /// that it is an existing directory.
+    pub async fn open(
+        connection: Rc<language_server::Connection>,
+        path: Path,
+    ) -> FallibleResult<Self> {
+        let info = connection.file_info(&path).await?;
+        match info.attributes.kind {
+            FileSystemObject::Directory { .. } | FileSystemObject::DirectoryTruncated { .. } =>
+                Ok(Self::new(connection, path)),
+            _ => Err(NotADirectory { path }.into()),
+        }
+    }
+
+    /// The File Browser entry of the listed object. Broken links and symlink loops are omitted, as
+    /// they cannot be opened.
+    fn entry(&self, object: &FileSystemObject) -> Option<Entry> {
+        let path = Path::from(object);
+        let type_ = match object {
+            FileSystemObject::Directory { .. } | FileSystemObject::DirectoryTruncated { .. } => {
+                let content = Folder::new(self.connection.clone_ref(), path.clone()).into();
+                EntryType::Folder { type_: FolderType::Standard, content }
+            }
+            FileSystemObject::File { .. } => EntryType::File,
+            FileSystemObject::Other { .. } | FileSystemObject::SymlinkLoop { .. } => return None,
+        };
+        let name = path.file_name()?.clone();
+        Some(Entry { type_, name, path: to_file_browser_path(&path) })
+    }
+}
+
+impl FolderContent for Folder {
+    fn request_entries(
+        &self,
+        entries_loaded: frp::Any<Rc<Vec<Entry>>>,
+        error_occurred: frp::Any<ImString>,
+    ) {
+        let this = self.clone_ref();
+        executor::global::spawn(async move {
+            match this.connection.file_list(&this.path).await {
+                Ok(response) => {
+                    let entries = response.paths.iter().filter_map(|object| this.entry(object));
+                    entries_loaded.emit(Rc::new(entries.collect()));
+                }
+                Err(error) => {
+                    let path = &this.path;
+                    error_occurred.emit(ImString::from(format!("Cannot list {path}: {error}")));
+                }
+            }
+        });
+    }
+}
+
+
+
+// ====================
+// === ContentRoots ===
+// ====================
+
+/// The list of the content roots of the project, presented as folders. It is the top-level
+/// content of the File Browser, which allows to browse the whole file system available to the
+/// project.
+#[derive(Clone, Debug)]
+pub struct ContentRoots {
+    connection: Rc<language_server::Connection>,
+    roots:      Vec<Rc<ContentRoot>>,
+}
+
+impl ContentRoots {
+    /// Constructor.
+    pub fn new(
+        connection: Rc<language_server::Connection>,
+        roots: impl IntoIterator<Item = Rc<ContentRoot>>,
+    ) -> Self {
+        Self { connection, roots: roots.into_iter().collect() }
+    }
+
+    /// The content roots of the given project.
+    pub fn for_project(project: &model::Project) -> Self {
+        Self::new(project.json_rpc(), project.content_roots())
+    }
+
+    fn entry(&self, root: &ContentRoot) -> Entry {
+        let (type_, name) = match root {
+            ContentRoot::Project { .. } => (FolderType::Project, "Project".to_owned()),
+            ContentRoot::FileSystemRoot { path, .. } => (FolderType::Root, path.clone()),
+            ContentRoot::Home { .. } => (FolderType::Home, "Home".to_owned()),
+            ContentRoot::Library { namespace, name, .. } =>
+                (FolderType::Library, format!("{namespace}.{name}")),
+            ContentRoot::Custom { id } => (FolderType::Custom, id.to_string()),
+        };
+        let path = Path::new_root(root.id());
+        let content = Folder::new(self.connection.clone_ref(), path.clone()).into();
+        let type_ = EntryType::Folder { type_, content };
+        Entry { type_, name, path: to_file_browser_path(&path) }
+    }
+}
+
+impl FolderContent for ContentRoots {
+    fn request_entries(
+        &self,
+        entries_loaded: frp::Any<Rc<Vec<Entry>>>,
+        _error_occurred: frp::Any<ImString>,
+    ) {
+        let entries = self.roots.iter().map(|root| self.entry(root));
+        entries_loaded.emit(Rc::new(entries.collect()));
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::executor::test_utils::TestWithLocalPoolExecutor;
+
+    use engine_protocol::language_server::response;
+    use engine_protocol::language_server::FileAttributes;
+    use engine_protocol::types::UTCDateTime;
+    use json_rpc::expect_call;
+
+    fn attributes(kind: FileSystemObject) -> FileAttributes {
+        let dummy_time = UTCDateTime::parse_from_rfc3339("1996-12-19T16:39:57-08:00").unwrap();
+        FileAttributes {
+            creation_time: dummy_time,
+            last_access_time: dummy_time,
+            last_modified_time: dummy_time,
+            kind,
+            byte_size: 0,
+        }
+    }
+
+    #[test]
+    fn converting_paths() {
+        let root_id = Uuid::new_v4();
+        let path = Path::new(root_id, &["data", "table.csv"]);
+        let file_browser_path = to_file_browser_path(&path);
+        assert_eq!(file_browser_path.iter().count(), 3);
+        assert_eq!(file_browser_path.file_name().unwrap(), "table.csv");
+        assert_eq!(from_file_browser_path(&file_browser_path).unwrap(), path);
+        let root = Path::new_root(root_id);
+        assert_eq!(from_file_browser_path(&to_file_browser_path(&root)).unwrap(), root);
+        assert!(from_file_browser_path(std::path::Path::new("data/table.csv")).is_err());
+    }
+
+    #[test]
+    fn listing_folders() {
+        let root_id = Uuid::new_v4();
+        let path = Path::new(root_id, &["data"]);
+        let mut client = language_server::MockClient::default();
+        let paths = vec![
+            FileSystemObject::new_file(Path::new(root_id, &["data", "table.csv"])).unwrap(),
+            FileSystemObject::new_directory(Path::new(root_id, &["data", "old"])).unwrap(),
+            FileSystemObject::SymlinkLoop {
+                name:   "loop".to_owned(),
+                path:   path.clone(),
+                target: Path::new_root(root_id),
+            },
+        ];
+        expect_call!(client.file_list(path=path.clone()) => Ok(response::FileList { paths }));
+        let connection = language_server::Connection::new_mock_rc(client);
+        let mut test = TestWithLocalPoolExecutor::set_up();
+        let network = frp::Network::new("test");
+        frp::extend! { network
+            entries_loaded <- any_mut::<Rc<Vec<Entry>>>();
+            error_occurred <- any_mut::<ImString>();
+            entries <- entries_loaded.sampler();
+        }
+        Folder::new(connection, path).request_entries(entries_loaded, error_occurred);
+        test.run_until_stalled();
+
+        let entries = entries.value();
+        let names = entries.iter().map(|entry| entry.name.as_str()).collect_vec();
+        assert_eq!(names, ["table.csv", "old"]);
+        assert!(matches!(entries[0].type_, EntryType::File));
+        assert!(matches!(entries[1].type_, EntryType::Folder { type_: FolderType::Standard, .. }));
+        let old_folder_path = Path::new(root_id, &["data", "old"]);
+        assert_eq!(from_file_browser_path(&entries[1].path).unwrap(), old_folder_path);
+    }
+
+    #[test]
+    fn opening_folders() {
+        let root_id = Uuid::new_v4();
+        let folder = Path::new(root_id, &["data"]);
+        let file = Path::new(root_id, &["data", "table.csv"]);
+        let mut client = language_server::MockClient::default();
+        let folder_kind = FileSystemObject::new_directory(folder.clone()).unwrap();
+        let folder_info = response::FileInfo { attributes: attributes(folder_kind) };
+        expect_call!(client.file_info(path=folder.clone()) => Ok(folder_info));
+        let file_kind = FileSystemObject::new_file(file.clone()).unwrap();
+        let file_info = response::FileInfo { attributes: attributes(file_kind) };
+        expect_call!(client.file_info(path=file.clone()) => Ok(file_info));
+        let connection = language_server::Connection::new_mock_rc(client);
+        let mut test = TestWithLocalPoolExecutor::set_up();
+
+        let opened = test.expect_completion(Folder::open(connection.clone_ref(), folder.clone()));
+        assert_eq!(*opened.unwrap().path, folder);
+        let opened = test.expect_completion(Folder::open(connection, file));
+        assert!(opened.is_err());
+    }
+}
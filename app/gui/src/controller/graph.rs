@@ -40,6 +40,7 @@ use span_tree::SpanTree;
 // ==============
 
 pub mod executed;
+pub mod layout;
 pub mod widget;
 
 pub use double_representation::graph::Id;
@@ -47,6 +48,7 @@ pub use double_representation::graph::LocationHint;
 
 
 
+mod annotation;
 mod clipboard;
 
 
@@ -1093,18 +1095,109 @@ impl Handle {
         let module_name = self.module.name().to_owned();
         let collapsed = collapse(&graph, node_ids, introduced_name, &self.parser, module_name)?;
         let Collapsed { new_method, updated_definition, collapsed_node } = collapsed;
+        let new_graph = Id::new_single_crumb(new_method.name.clone());
 
         let graph = self.graph_info()?;
         let my_name = graph.source.name.item;
         module.add_method(new_method, module::Placement::Before(my_name), &self.parser)?;
         module.update_definition(&self.id, |_| Ok(updated_definition))?;
         self.module.update_ast(module.ast)?;
+        // The collapsed node stays in the group of the nodes it replaces, if they share one.
+        let groups = nodes.iter().map(|node| node.metadata.as_ref().and_then(|m| m.group));
+        let groups = groups.collect_vec();
+        let shared_group = groups.first().copied().flatten();
+        let group = shared_group.filter(|group| groups.iter().all(|g| *g == Some(*group)));
         let position = Some(model::module::Position::mean(collapsed_positions));
-        let metadata = NodeMetadata { position, ..default() };
+        let metadata = NodeMetadata { position, group, ..default() };
         self.module.set_node_metadata(collapsed_node, metadata)?;
+        let extracted_nodes = nodes.iter().map(|node| node.info.id()).collect_vec();
+        self.copy_groups_to_graph(&extracted_nodes, &new_graph)?;
         Ok(collapsed_node)
     }
 
+    /// Expands the node calling a method defined in this module, the inverse of [`Self::collapse`].
+    ///
+    /// The node's line is replaced with the lines of the method's body. The introduced nodes are
+    /// placed around the expanded node's position, keeping their relative positions from the
+    /// method's graph. Returns the identifiers of the introduced nodes.
+    #[profile(Task)]
+    pub fn expand(&self, node: node::Id) -> FallibleResult<Vec<node::Id>> {
+        let _transaction_guard = self.get_or_open_transaction("Expand node");
+        analytics::remote_log_event("graph::expand");
+        use double_representation::refactorings::expand::expand;
+        use double_representation::refactorings::expand::Expanded;
+        info!("Expanding {node:?}.");
+        let module = module::Info { ast: self.module.ast() };
+        let graph = self.graph_info()?;
+        let module_name = self.module.name().to_owned();
+        let expanded = expand(&graph, node, &module, &self.parser, &module_name)?;
+        let Expanded { updated_definition, introduced_nodes } = expanded;
+
+        let position_of = |id| self.module.node_metadata(id).ok().and_then(|data| data.position);
+        let expanded_position = position_of(node).unwrap_or_default();
+        let group = self.module.node_metadata(node).ok().and_then(|data| data.group);
+        let origin_positions = introduced_nodes.iter().filter_map(|node| position_of(node.origin?));
+        let origin_center = model::module::Position::mean(origin_positions);
+        self.update_definition_ast(|_| Ok(updated_definition))?;
+        for introduced in &introduced_nodes {
+            let position = match introduced.origin.and_then(position_of) {
+                Some(origin) => expanded_position + (origin.vector - origin_center.vector).into(),
+                None => expanded_position,
+            };
+            let metadata = NodeMetadata { position: Some(position), group, ..default() };
+            self.module.set_node_metadata(introduced.id, metadata)?;
+        }
+        Ok(introduced_nodes.into_iter().map(|node| node.id).collect())
+    }
+
+    /// Arranges the nodes automatically, so the connections flow from top to bottom with as few
+    /// crossings as possible. See [`layout`] for details.
+    ///
+    /// If `selection` is given, only the selected nodes are arranged, in the area they occupied.
+    /// The `sizes` are the nodes' sizes as displayed; the nodes missing there are assumed to have
+    /// the default size.
+    #[profile(Task)]
+    pub fn auto_layout(
+        &self,
+        selection: Option<&[node::Id]>,
+        sizes: &HashMap<node::Id, Vector2>,
+        context: &impl SpanTreeContext,
+    ) -> FallibleResult {
+        let _transaction_guard = self.get_or_open_transaction("Auto layout");
+        analytics::remote_log_event("graph::auto_layout");
+        let is_selected = |id| selection.map_or(true, |selection| selection.contains(&id));
+        let nodes = self.nodes()?.into_iter().filter(|node| is_selected(node.id()));
+        let nodes = nodes.map(|node| {
+            let id = node.id();
+            let position = node.position().map_or_default(|position| position.vector);
+            layout::Node { id, size: sizes.get(&id).copied(), position }
+        });
+        let nodes = nodes.collect_vec();
+        let connections = self.connections(context)?.connections;
+        let connections =
+            connections.iter().map(|connection| (connection.source.node, connection.target.node));
+        let positions = layout::layout(&nodes, connections, &default());
+        for (id, position) in positions {
+            self.module.with_node_metadata(
+                id,
+                Box::new(move |metadata| metadata.position = Some(position.into())),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Renames a local variable of the graph's definition: a node's output or a parameter.
+    ///
+    /// Methods defined elsewhere are renamed with [`controller::Project::prepare_rename`].
+    #[profile(Task)]
+    pub fn rename_variable(&self, old_name: &str, new_name: &str) -> FallibleResult {
+        let _transaction_guard = self.get_or_open_transaction("Rename variable");
+        analytics::remote_log_event("graph::rename_variable");
+        use double_representation::refactorings::rename::rename_local;
+        info!("Renaming variable {old_name} to {new_name}.");
+        self.update_definition_ast(|definition| rename_local(&definition, old_name, new_name))
+    }
+
     /// Updates the given node in the definition.
     ///
     /// The function `F` is called with the information with the state of the node so far and
@@ -1154,6 +1247,7 @@ pub mod tests {
     use super::*;
 
     use crate::executor::test_utils::TestWithLocalPoolExecutor;
+    use crate::model::module::GroupMetadata;
     use crate::model::module::Position;
     use crate::model::module::TextChange;
     use crate::model::suggestion_database;
@@ -1470,6 +1564,287 @@ main =
         })
     }
 
+    #[test]
+    fn expanding_nodes() {
+        let mut test = Fixture::set_up();
+        let code = r"
+func1 =
+    a = 10
+    b = 20
+    a
+
+main =
+    a = Mock_Module.func1
+    a + c";
+
+        let expected_code = "
+func1 =
+    a = 10
+    b = 20
+    a
+
+main =
+    a = 10
+    b = 20
+    a + c";
+
+        test.data.code = code.to_owned();
+        test.run(move |graph| async move {
+            let nodes = graph.nodes().unwrap();
+            let method_graph = graph.module.ast();
+            let method_nodes = {
+                let id = definition::Id::new_plain_name("func1");
+                let method = module::get_definition(&method_graph, &id).unwrap();
+                GraphInfo::from_definition(method).nodes()
+            };
+            let set_position = |id, x, y| {
+                let position = Some(Position::new(x, y));
+                graph.module.set_node_metadata(id, NodeMetadata { position, ..default() }).unwrap();
+            };
+            set_position(nodes[0].info.id(), 100.0, 200.0);
+            set_position(method_nodes[0].id(), 0.0, 0.0);
+            set_position(method_nodes[1].id(), 50.0, 100.0);
+
+            let introduced = graph.expand(nodes[0].info.id()).unwrap();
+            model::module::test::expect_code(&*graph.module, expected_code);
+            assert_eq!(introduced.len(), 2);
+            let position_of = |id| graph.node(id).unwrap().metadata.and_then(|m| m.position);
+            assert_eq!(position_of(introduced[0]), Some(Position::new(75.0, 150.0)));
+            assert_eq!(position_of(introduced[1]), Some(Position::new(125.0, 250.0)));
+        })
+    }
+
+    #[test]
+    fn node_groups_and_comments() {
+        let mut test = Fixture::set_up();
+        test.data.code = r"
+main =
+    a = 10
+    b = a + 1
+    c = b + a"
+            .to_owned();
+        test.run(|graph| async move {
+            let ids = graph.nodes().unwrap().iter().map(|node| node.id()).collect_vec();
+            let color = Some("#4a9bd8".to_owned());
+            let group = graph.create_group("Inputs", color.clone(), ids[0..2].to_vec()).unwrap();
+            assert_eq!(graph.group_members(group).unwrap(), ids[0..2]);
+            let metadata = graph.group(group).unwrap();
+            assert_eq!((metadata.label.as_str(), metadata.color), ("Inputs", color));
+
+            graph.edit_group(group, "Sources", None).unwrap();
+            assert_eq!(graph.group(group).unwrap().label, "Sources");
+            // The group is found by its nodes after the graph's method is renamed.
+            let rename = Box::new(|group: &mut GroupMetadata| group.graph = "old_main".into());
+            graph.module.with_group_metadata(group, rename).unwrap();
+            assert_eq!(graph.group(group).unwrap().label, "Sources");
+            let other_group = graph.create_group("Result", None, Some(ids[1])).unwrap();
+            assert_eq!(graph.group_members(group).unwrap(), ids[0..1]);
+            // The group left without nodes is removed.
+            graph.remove_from_group(Some(ids[1])).unwrap();
+            assert!(graph.group(other_group).is_err());
+            assert_eq!(graph.groups().unwrap().len(), 1);
+            graph.remove_group(group).unwrap();
+            assert!(graph.groups().unwrap().is_empty());
+            assert!(graph.node(ids[0]).unwrap().metadata.unwrap().group.is_none());
+
+            let comment = graph.add_comment("Check the inputs.", Position::new(5.0, 10.0)).unwrap();
+            graph.edit_comment(comment, "Inputs checked.").unwrap();
+            graph.set_comment_position(comment, Position::new(-5.0, 0.0)).unwrap();
+            let metadata = graph.comment(comment).unwrap();
+            assert_eq!(metadata.text, "Inputs checked.");
+            assert_eq!(metadata.position, Position::new(-5.0, 0.0));
+            graph.remove_comment(comment).unwrap();
+            assert!(graph.comments().unwrap().is_empty());
+        })
+    }
+
+    #[test]
+    fn comments_follow_their_graph_definition() {
+        let mut test = Fixture::set_up();
+        test.data.code = "main =\n    a = 10\n    b = a + 1".to_owned();
+        test.run(|graph| async move {
+            let comment = graph.add_comment("Check the inputs.", Position::new(5.0, 10.0)).unwrap();
+            let change_code = |range: Range<usize>, text: &str| {
+                let code = graph.module.ast().repr();
+                let range = (range.start.byte()..range.end.byte()).into();
+                let change = TextChange { range, text: text.into() };
+                let mut id_map = graph.module.ast().id_map();
+                double_representation::text::apply_code_change_to_id_map(
+                    &mut id_map,
+                    &change,
+                    &code,
+                );
+                graph.module.apply_code_change(change, &graph.parser, id_map).unwrap();
+            };
+
+            // The comment stays in the graph when its method is renamed.
+            change_code(0..4, "entry");
+            let renamed = Handle::new(
+                graph.module.clone_ref(),
+                graph.suggestion_db.clone_ref(),
+                graph.parser.clone_ref(),
+                Id::new_plain_name("entry"),
+                graph.project_name.clone_ref(),
+            )
+            .unwrap();
+            assert_eq!(renamed.comment(comment).unwrap().text, "Check the inputs.");
+
+            // The comment is removed along with the graph's definition when the module is loaded.
+            let code_length = graph.module.ast().repr().len();
+            change_code(0..code_length, "main = 1");
+            let content = graph.module.serialized_content().unwrap().content;
+            let parsed = graph.parser.parse_with_metadata(content);
+            let path = graph.module.path().clone_ref();
+            let repository = Rc::new(model::undo_redo::Repository::new());
+            let loaded =
+                model::module::Plain::new(path, parsed.ast, parsed.metadata, repository, default());
+            assert!(loaded.all_comment_metadata().is_empty());
+        })
+    }
+
+    #[test]
+    fn collapsing_grouped_nodes() {
+        let mut test = Fixture::set_up();
+        test.data.code = r"
+main =
+    a = 10
+    b = a + 1
+    c = b + a"
+            .to_owned();
+        test.run(|graph| async move {
+            let ids = graph.nodes().unwrap().iter().map(|node| node.id()).collect_vec();
+            let group = graph.create_group("Group", None, ids.clone()).unwrap();
+            let collapsed = graph.collapse(ids[1..].to_vec(), "func").unwrap();
+            let collapsed_metadata = graph.node(collapsed).unwrap().metadata.unwrap();
+            assert_eq!(collapsed_metadata.group, Some(group));
+
+            // The collapsed nodes are in a copy of the group, displayed in the new method's graph.
+            let all_groups = graph.module.all_group_metadata();
+            let (copy, copy_metadata) = all_groups.iter().find(|(id, _)| *id != group).unwrap();
+            assert_eq!(copy_metadata.graph, "func1");
+            assert_eq!(copy_metadata.label, "Group");
+            let copy_of = |id| graph.module.node_metadata(id).unwrap().group;
+            assert_eq!(copy_of(ids[1]), Some(*copy));
+            assert_eq!(copy_of(ids[2]), Some(*copy));
+        })
+    }
+
+    #[test]
+    fn auto_layout_of_nodes() {
+        let mut test = Fixture::set_up();
+        test.data.code = r"
+main =
+    a = 10
+    b = a + 1
+    c = b + a
+    d = 20"
+            .to_owned();
+        test.run(|graph| async move {
+            let nodes = graph.nodes().unwrap();
+            let ids = nodes.iter().map(|node| node.id()).collect_vec();
+            for (index, id) in ids.iter().enumerate() {
+                graph.set_node_position(*id, Position::new(0.0, index as f32 * 100.0)).unwrap();
+            }
+            let context = span_tree::generate::context::Empty;
+            graph.auto_layout(None, &default(), &context).unwrap();
+            let position_of = |id| graph.node(id).unwrap().position().unwrap().vector;
+            let [a, b, c, d] = [0, 1, 2, 3].map(|index| position_of(ids[index]));
+            assert!(a.y > b.y);
+            assert!(b.y > c.y);
+            assert_eq!(a.y, d.y);
+            assert_ne!(a.x, d.x);
+
+            // Only the selected nodes are moved.
+            graph.set_node_position(ids[3], Position::new(500.0, 500.0)).unwrap();
+            graph.auto_layout(Some(&ids[0..2]), &default(), &context).unwrap();
+            assert_eq!(position_of(ids[3]), Vector2::new(500.0, 500.0));
+            assert!(position_of(ids[0]).y > position_of(ids[1]).y);
+        })
+    }
+
+    #[test]
+    fn auto_layout_of_nodes_with_sizes() {
+        let mut test = Fixture::set_up();
+        test.data.code = r"
+main =
+    a = 10
+    b = a + 1
+    c = b + a
+    d = 20
+    e = d + c"
+            .to_owned();
+        test.run(|graph| async move {
+            let nodes = graph.nodes().unwrap();
+            let ids = nodes.iter().map(|node| node.id()).collect_vec();
+            let sizes = [(300.0, 80.0), (60.0, 20.0), (200.0, 40.0), (400.0, 100.0), (90.0, 30.0)];
+            let sizes = sizes.map(|(width, height)| Vector2::new(width, height));
+            let sizes: HashMap<_, _> = ids.iter().copied().zip(sizes).collect();
+            let context = span_tree::generate::context::Empty;
+            graph.auto_layout(None, &sizes, &context).unwrap();
+            // The positions are the nodes' left edges and vertical centers.
+            let bounds_of = |id| {
+                let position = graph.node(id).unwrap().position().unwrap().vector;
+                let size = sizes[&id];
+                let min = Vector2::new(position.x, position.y - size.y / 2.0);
+                (min, min + size)
+            };
+            for (a, b) in ids.iter().tuple_combinations() {
+                let ((a_min, a_max), (b_min, b_max)) = (bounds_of(*a), bounds_of(*b));
+                let separate_horizontally = a_max.x <= b_min.x || b_max.x <= a_min.x;
+                let separate_vertically = a_max.y <= b_min.y || b_max.y <= a_min.y;
+                assert!(separate_horizontally || separate_vertically, "Nodes {a} and {b} overlap.");
+            }
+        })
+    }
+
+    #[test]
+    fn auto_layout_is_a_single_undo_transaction() {
+        let mut test = Fixture::set_up();
+        test.data.code = r"
+main =
+    a = 10
+    b = a + 1
+    c = b + a"
+            .to_owned();
+        test.run(|graph| async move {
+            let repository = graph.undo_redo_repository();
+            let content_before = graph.module.serialized_content().unwrap().content;
+            let frames_before = repository.len(model::undo_redo::Stack::Undo);
+            let context = span_tree::generate::context::Empty;
+            graph.auto_layout(None, &default(), &context).unwrap();
+            assert_ne!(graph.module.serialized_content().unwrap().content, content_before);
+            // All the moved nodes are restored by undoing a single frame.
+            assert_eq!(repository.len(model::undo_redo::Stack::Undo), frames_before + 1);
+            let frame = repository.last(model::undo_redo::Stack::Undo).unwrap();
+            assert_eq!(frame.name, "Auto layout");
+            let snapshot = &frame.snapshots[&graph.module.id()];
+            assert_eq!(snapshot.serialize().unwrap().content, content_before);
+        })
+    }
+
+    #[test]
+    fn renaming_variables() {
+        let mut test = Fixture::set_up();
+        let code = r"
+main =
+    sum = 10
+    foo = sum + 20
+    sum + foo";
+        let expected_code = "
+main =
+    total = 10
+    foo = total + 20
+    total + foo";
+        test.data.code = code.to_owned();
+        test.run(move |graph| async move {
+            graph.rename_variable("sum", "total").unwrap();
+            model::module::test::expect_code(&*graph.module, expected_code);
+            assert!(graph.rename_variable("total", "foo").is_err());
+            assert!(graph.rename_variable("sum", "other").is_err());
+            model::module::test::expect_code(&*graph.module, expected_code);
+        })
+    }
+
     #[test]
     fn graph_controller_doubly_nested_definition() {
         // Tests editing nested definition that requires transforming inline expression into
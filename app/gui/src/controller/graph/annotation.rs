@@ -0,0 +1,265 @@
+//! Node groups and comments: the annotations of a graph kept in the module's metadata.
+//!
+//! A group is a labelled frame drawn around its member nodes, while a comment is a note placed
+//! freely in the graph. A group is identified by the graph it is displayed in, see
+//! [`GroupMetadata::graph`], and a comment by its graph's definition, see
+//! [`CommentMetadata::definition`].
+//!
+//! The comments are not attached to any nodes, so they are not copied when the nodes are copied
+//! to the clipboard.
+
+use crate::model::traits::*;
+use crate::prelude::*;
+
+use crate::controller::graph::Handle;
+use crate::model::module::CommentId;
+use crate::model::module::CommentMetadata;
+use crate::model::module::CommentMetadataNotFound;
+use crate::model::module::GroupId;
+use crate::model::module::GroupMetadata;
+use crate::model::module::GroupMetadataNotFound;
+use crate::model::module::Position;
+
+use double_representation::node;
+
+
+
+// ==============
+// === Errors ===
+// ==============
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(display = "The definition of the graph {} has no ID.", _0)]
+pub struct DefinitionWithoutId(pub double_representation::graph::Id);
+
+
+
+// ==============
+// === Groups ===
+// ==============
+
+impl Handle {
+    /// The key identifying this graph in the annotations' metadata.
+    fn annotation_graph_key(&self) -> String {
+        self.id.to_string()
+    }
+
+    /// Create a new group of the given nodes. The nodes are moved out of their previous groups.
+    ///
+    /// The `color` is a CSS hex string; the default color is used if not given.
+    pub fn create_group(
+        &self,
+        label: impl Into<String>,
+        color: Option<String>,
+        nodes: impl IntoIterator<Item = node::Id>,
+    ) -> FallibleResult<GroupId> {
+        let _transaction_guard = self.get_or_open_transaction("Create group");
+        let id = GroupId::new_v4();
+        let graph = self.annotation_graph_key();
+        let metadata = GroupMetadata { graph, label: label.into(), color };
+        self.module.with_group_metadata(id, Box::new(|group| *group = metadata))?;
+        self.add_to_group(id, nodes)?;
+        Ok(id)
+    }
+
+    /// Returns the groups displayed in this graph.
+    ///
+    /// Besides the groups created in this graph, the groups of its nodes are included. This way
+    /// the groups are not lost when the graph's key changes, e.g. when its method is renamed.
+    pub fn groups(&self) -> FallibleResult<Vec<(GroupId, GroupMetadata)>> {
+        let graph = self.annotation_graph_key();
+        let nodes = self.nodes()?;
+        let node_groups: HashSet<GroupId> =
+            nodes.iter().filter_map(|node| node.metadata.as_ref()?.group).collect();
+        let groups = self.module.all_group_metadata().into_iter();
+        Ok(groups.filter(|(id, group)| group.graph == graph || node_groups.contains(id)).collect())
+    }
+
+    /// Returns the metadata of the group.
+    pub fn group(&self, id: GroupId) -> FallibleResult<GroupMetadata> {
+        let group = self.groups()?.into_iter().find(|(group_id, _)| *group_id == id);
+        group.map(|(_, group)| group).ok_or_else(|| GroupMetadataNotFound(id).into())
+    }
+
+    /// Returns the nodes belonging to the group.
+    pub fn group_members(&self, id: GroupId) -> FallibleResult<Vec<node::Id>> {
+        let is_member = |node: &controller::graph::Node| {
+            node.metadata.as_ref().map_or(false, |metadata| metadata.group == Some(id))
+        };
+        Ok(self.nodes()?.into_iter().filter(is_member).map(|node| node.id()).collect())
+    }
+
+    /// Set the group's label and color.
+    pub fn edit_group(
+        &self,
+        id: GroupId,
+        label: impl Into<String>,
+        color: Option<String>,
+    ) -> FallibleResult {
+        self.group(id)?;
+        let _transaction_guard = self.get_or_open_transaction("Edit group");
+        let label = label.into();
+        self.module.with_group_metadata(
+            id,
+            Box::new(|group| {
+                group.label = label;
+                group.color = color;
+            }),
+        )
+    }
+
+    /// Add the nodes to the group, moving them out of their previous groups.
+    pub fn add_to_group(
+        &self,
+        id: GroupId,
+        nodes: impl IntoIterator<Item = node::Id>,
+    ) -> FallibleResult {
+        self.group(id)?;
+        let _transaction_guard = self.get_or_open_transaction("Add nodes to group");
+        let previous_groups = self.set_group_of_nodes(nodes, Some(id))?;
+        self.remove_empty_groups(previous_groups)
+    }
+
+    /// Remove the nodes from their groups. The groups left without any nodes are removed.
+    pub fn remove_from_group(&self, nodes: impl IntoIterator<Item = node::Id>) -> FallibleResult {
+        let _transaction_guard = self.get_or_open_transaction("Remove nodes from group");
+        let previous_groups = self.set_group_of_nodes(nodes, None)?;
+        self.remove_empty_groups(previous_groups)
+    }
+
+    /// Remove the group. Its nodes are kept, but no longer grouped.
+    pub fn remove_group(&self, id: GroupId) -> FallibleResult {
+        let _transaction_guard = self.get_or_open_transaction("Remove group");
+        self.set_group_of_nodes(self.group_members(id)?, None)?;
+        self.module.remove_group_metadata(id)?;
+        Ok(())
+    }
+
+    /// Set the group of the nodes, returning the groups they belonged to before.
+    fn set_group_of_nodes(
+        &self,
+        nodes: impl IntoIterator<Item = node::Id>,
+        group: Option<GroupId>,
+    ) -> FallibleResult<HashSet<GroupId>> {
+        let mut previous_groups = HashSet::new();
+        for node in nodes {
+            let previous_groups = &mut previous_groups;
+            self.module.with_node_metadata(
+                node,
+                Box::new(move |metadata| {
+                    previous_groups.extend(mem::replace(&mut metadata.group, group));
+                }),
+            )?;
+        }
+        if let Some(group) = group {
+            previous_groups.remove(&group);
+        }
+        Ok(previous_groups)
+    }
+
+    fn remove_empty_groups(&self, groups: impl IntoIterator<Item = GroupId>) -> FallibleResult {
+        for group in groups {
+            if self.group_members(group)?.is_empty() {
+                self.module.remove_group_metadata(group)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Copy the groups of the given nodes to another graph of this module, moving the nodes to
+    /// the copies. Used when the nodes are moved to a new graph, e.g. by collapsing them.
+    pub(crate) fn copy_groups_to_graph(
+        &self,
+        nodes: &[node::Id],
+        graph: &double_representation::graph::Id,
+    ) -> FallibleResult {
+        let mut copies = HashMap::new();
+        for node in nodes {
+            let group = self.module.node_metadata(*node).ok().and_then(|metadata| metadata.group);
+            let Some(group) = group else { continue };
+            let copy = match copies.get(&group) {
+                Some(copy) => *copy,
+                None => {
+                    let copy = GroupId::new_v4();
+                    let mut metadata = self.group(group)?;
+                    metadata.graph = graph.to_string();
+                    self.module.with_group_metadata(copy, Box::new(|group| *group = metadata))?;
+                    copies.insert(group, copy);
+                    copy
+                }
+            };
+            self.module
+                .with_node_metadata(*node, Box::new(|metadata| metadata.group = Some(copy)))?;
+        }
+        Ok(())
+    }
+}
+
+
+
+// ================
+// === Comments ===
+// ================
+
+impl Handle {
+    /// The ID of this graph's definition, identifying the graph in the comments' metadata.
+    fn comment_graph_key(&self) -> FallibleResult<ast::Id> {
+        let definition = self.definition()?;
+        definition.item.ast.id().ok_or_else(|| DefinitionWithoutId(self.id.clone()).into())
+    }
+
+    /// Add a comment at the given position.
+    pub fn add_comment(
+        &self,
+        text: impl Into<String>,
+        position: impl Into<Position>,
+    ) -> FallibleResult<CommentId> {
+        let _transaction_guard = self.get_or_open_transaction("Add comment");
+        let id = CommentId::new_v4();
+        let definition = self.comment_graph_key()?;
+        let metadata = CommentMetadata { definition, text: text.into(), position: position.into() };
+        self.module.with_comment_metadata(id, Box::new(|comment| *comment = metadata))?;
+        Ok(id)
+    }
+
+    /// Returns the comments displayed in this graph.
+    pub fn comments(&self) -> FallibleResult<Vec<(CommentId, CommentMetadata)>> {
+        let definition = self.comment_graph_key()?;
+        let comments = self.module.all_comment_metadata().into_iter();
+        Ok(comments.filter(|(_, comment)| comment.definition == definition).collect())
+    }
+
+    /// Returns the metadata of the comment.
+    pub fn comment(&self, id: CommentId) -> FallibleResult<CommentMetadata> {
+        let comment = self.comments()?.into_iter().find(|(comment_id, _)| *comment_id == id);
+        comment.map(|(_, comment)| comment).ok_or_else(|| CommentMetadataNotFound(id).into())
+    }
+
+    /// Set the text of the comment.
+    pub fn edit_comment(&self, id: CommentId, text: impl Into<String>) -> FallibleResult {
+        self.comment(id)?;
+        let _transaction_guard = self.get_or_open_transaction("Edit comment");
+        let text = text.into();
+        self.module.with_comment_metadata(id, Box::new(|comment| comment.text = text))
+    }
+
+    /// Move the comment to the given position.
+    pub fn set_comment_position(
+        &self,
+        id: CommentId,
+        position: impl Into<Position>,
+    ) -> FallibleResult {
+        self.comment(id)?;
+        let _transaction_guard = self.get_or_open_transaction("Move comment");
+        let position = position.into();
+        self.module.with_comment_metadata(id, Box::new(|comment| comment.position = position))
+    }
+
+    /// Remove the comment.
+    pub fn remove_comment(&self, id: CommentId) -> FallibleResult {
+        let _transaction_guard = self.get_or_open_transaction("Remove comment");
+        self.module.remove_comment_metadata(id)?;
+        Ok(())
+    }
+}
@@ -12,6 +12,9 @@
 //!
 //! To copy the node as plain text, the user can enter the editing node, select the node expression,
 //! and copy it to the clipboard using the [`ensogl::Text`] functionality.
+//!
+//! The node's metadata, including its group, is copied along with it. The comments of the graph
+//! are not attached to any nodes, so they are not copied.
 
 use crate::prelude::*;
 
@@ -103,7 +106,13 @@ fn paste_node_from_custom_format(
             match content {
                 ClipboardContent::Node(node) => {
                     let expression = node.expression;
-                    let metadata = node.metadata;
+                    let mut metadata = node.metadata;
+                    if let Some(metadata) = &mut metadata {
+                        // The node may be pasted to a graph without the copied node's group.
+                        let groups = graph.groups()?;
+                        let group_exists = |group: &_| groups.iter().any(|(id, _)| id == group);
+                        metadata.group = metadata.group.filter(group_exists);
+                    }
                     graph.new_node_at_position(position, expression, metadata)?;
                     Ok(())
                 }
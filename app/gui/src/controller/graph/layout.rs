@@ -0,0 +1,526 @@
+//! Automatic layout of the graph's nodes.
+//!
+//! The nodes are arranged with the layered (Sugiyama) method, so the connections flow from the
+//! top to the bottom of the graph:
+//! 1. The connections forming cycles are reversed, as the method requires an acyclic graph.
+//! 2. The nodes are assigned to layers, so each connection goes from some layer to a lower one.
+//!    The connections spanning several layers are split with dummy vertices, one in each layer
+//!    they pass through.
+//! 3. The vertices in each layer are ordered to reduce the number of crossing connections, using
+//!    the barycenter heuristic.
+//! 4. The vertices are placed horizontally as close to their neighbours as the gaps between them
+//!    allow, and vertically in the rows of their layers.
+
+use crate::prelude::*;
+
+use double_representation::node;
+
+
+
+// ==============
+// === Config ===
+// ==============
+
+/// Parameters of the layout.
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    /// The minimal horizontal distance between two nodes in the same layer.
+    pub horizontal_gap:    f32,
+    /// The vertical distance between two layers.
+    pub vertical_gap:      f32,
+    /// The size assumed for nodes of unknown size.
+    pub default_node_size: Vector2,
+    /// The number of sweeps through all layers done when ordering and placing the nodes.
+    pub sweeps:            usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            horizontal_gap:    32.0,
+            vertical_gap:      48.0,
+            default_node_size: Vector2::new(120.0, 32.0),
+            sweeps:            8,
+        }
+    }
+}
+
+
+
+// ============
+// === Node ===
+// ============
+
+/// A node to be laid out.
+#[derive(Clone, Copy, Debug)]
+pub struct Node {
+    /// The node's identifier.
+    pub id:       node::Id,
+    /// The node's size, or [`None`] if unknown.
+    pub size:     Option<Vector2>,
+    /// The node's current position. The nodes keep their relative order where the layout allows.
+    pub position: Vector2,
+}
+
+
+
+// ==============
+// === Layout ===
+// ==============
+
+/// Compute the positions of the nodes connected by the given `connections` (pairs of the source
+/// and the target node).
+///
+/// The positions follow the convention of [`model::module::Position`]: the left edge and the
+/// vertical center of the node. The whole layout is placed with its top-left corner where the
+/// top-left corner of the nodes' current bounding box is, so the nodes stay in the same area.
+/// The connections to nodes outside the given set are ignored.
+pub fn layout(
+    nodes: &[Node],
+    connections: impl IntoIterator<Item = (node::Id, node::Id)>,
+    config: &Config,
+) -> HashMap<node::Id, Vector2> {
+    let mut layout = Layout::new(nodes, connections, config);
+    layout.order_layers();
+    layout.place_horizontally();
+    layout.positions(nodes)
+}
+
+/// A vertex of the layered graph: either a node or a dummy vertex of a long connection.
+#[derive(Clone, Debug)]
+struct Vertex {
+    node:  Option<node::Id>,
+    size:  Vector2,
+    layer: usize,
+    /// The horizontal position of the vertex's center.
+    x:     f32,
+}
+
+/// The layered graph being laid out.
+#[derive(Debug)]
+struct Layout<'a> {
+    config:   &'a Config,
+    vertices: Vec<Vertex>,
+    /// The vertices adjacent to each vertex in the layer above.
+    upper:    Vec<Vec<usize>>,
+    /// The vertices adjacent to each vertex in the layer below.
+    lower:    Vec<Vec<usize>>,
+    /// The vertices of each layer, ordered from left to right.
+    layers:   Vec<Vec<usize>>,
+}
+
+impl<'a> Layout<'a> {
+    fn new(
+        nodes: &[Node],
+        connections: impl IntoIterator<Item = (node::Id, node::Id)>,
+        config: &'a Config,
+    ) -> Self {
+        let index: HashMap<_, _> = nodes.iter().enumerate().map(|(i, node)| (node.id, i)).collect();
+        let edges = connections.into_iter().filter_map(|(source, target)| {
+            let source = *index.get(&source)?;
+            let target = *index.get(&target)?;
+            (source != target).then_some((source, target))
+        });
+        let edges = acyclic(nodes.len(), edges.unique().collect());
+        let layers = assign_layers(nodes.len(), &edges);
+        let vertices = nodes.iter().zip(layers).map(|(node, layer)| {
+            let size = node.size.unwrap_or(config.default_node_size);
+            Vertex { node: Some(node.id), size, layer, x: node.position.x + size.x / 2.0 }
+        });
+        let vertices = vertices.collect_vec();
+        let upper = vec![default(); nodes.len()];
+        let lower = vec![default(); nodes.len()];
+        let mut layout = Self { config, vertices, upper, lower, layers: default() };
+        for (source, target) in edges {
+            layout.add_edge(source, target);
+        }
+        let layer_count = layout.vertices.iter().map(|vertex| vertex.layer + 1).max().unwrap_or(0);
+        layout.layers = vec![default(); layer_count];
+        for (index, vertex) in layout.vertices.iter().enumerate() {
+            layout.layers[vertex.layer].push(index);
+        }
+        let vertices = &layout.vertices;
+        for layer in &mut layout.layers {
+            layer.sort_by(|a, b| vertices[*a].x.total_cmp(&vertices[*b].x));
+        }
+        layout
+    }
+
+    /// Add an edge going down from the `upper` vertex, splitting it with dummy vertices if it
+    /// spans more than one layer.
+    fn add_edge(&mut self, upper: usize, lower: usize) {
+        let (upper_vertex, lower_vertex) = (&self.vertices[upper], &self.vertices[lower]);
+        let x = (upper_vertex.x + lower_vertex.x) / 2.0;
+        let mut previous = upper;
+        for layer in upper_vertex.layer + 1..lower_vertex.layer {
+            let dummy = Vertex { node: None, size: Vector2::zeros(), layer, x };
+            self.vertices.push(dummy);
+            self.upper.push(vec![previous]);
+            self.lower.push(default());
+            let dummy = self.vertices.len() - 1;
+            self.lower[previous].push(dummy);
+            previous = dummy;
+        }
+        self.lower[previous].push(lower);
+        self.upper[lower].push(previous);
+    }
+
+    /// Order the vertices in layers, reducing the number of crossing edges.
+    ///
+    /// Each vertex is moved to the barycenter of its neighbours' positions in the adjacent layer,
+    /// alternately sweeping down and up the layers. The best ordering found is kept.
+    fn order_layers(&mut self) {
+        let mut best = self.layers.clone();
+        let mut best_crossings = self.crossings();
+        for sweep in 0..self.config.sweeps {
+            if best_crossings == 0 {
+                break;
+            }
+            let downwards = sweep % 2 == 0;
+            for index in self.sweep_order(downwards) {
+                let neighbours = if downwards { &self.upper } else { &self.lower };
+                let reference = if downwards { index - 1 } else { index + 1 };
+                let positions = position_in_layer(&self.layers[reference]);
+                let mut layer = mem::take(&mut self.layers[index]);
+                let keys = layer.iter().enumerate().map(|(current, vertex)| {
+                    let neighbours = neighbours[*vertex].iter().map(|n| positions[n] as f32);
+                    let barycenter = mean(neighbours).unwrap_or(current as f32);
+                    (*vertex, barycenter)
+                });
+                let keys: HashMap<_, _> = keys.collect();
+                layer.sort_by(|a, b| keys[a].total_cmp(&keys[b]));
+                self.layers[index] = layer;
+            }
+            let crossings = self.crossings();
+            if crossings < best_crossings {
+                best = self.layers.clone();
+                best_crossings = crossings;
+            }
+        }
+        self.layers = best;
+    }
+
+    /// The indices of the layers to process in a sweep, skipping the first processed one, whose
+    /// order is the reference for the rest.
+    fn sweep_order(&self, downwards: bool) -> Box<dyn Iterator<Item = usize>> {
+        let count = self.layers.len();
+        match downwards {
+            true => Box::new(1..count),
+            false => Box::new((0..count.saturating_sub(1)).rev()),
+        }
+    }
+
+    /// The number of crossing edges in the current ordering.
+    fn crossings(&self) -> usize {
+        let pairs = self.layers.iter().tuple_windows();
+        pairs.map(|(upper, lower)| self.crossings_between(upper, lower)).sum()
+    }
+
+    fn crossings_between(&self, upper: &[usize], lower: &[usize]) -> usize {
+        let lower_positions = &position_in_layer(lower);
+        let edges = upper.iter().enumerate().flat_map(|(upper_position, vertex)| {
+            let targets = self.lower[*vertex].iter();
+            targets.map(move |target| (upper_position, lower_positions[target]))
+        });
+        let edges = edges.collect_vec();
+        let crossing = |((a1, b1), (a2, b2)): (&(usize, usize), &(usize, usize))| {
+            (a1 < a2 && b1 > b2) || (a1 > a2 && b1 < b2)
+        };
+        edges.iter().tuple_combinations().filter(|pair| crossing(*pair)).count()
+    }
+
+    /// Place the vertices horizontally. Each vertex is moved as close to the mean position of its
+    /// neighbours in the adjacent layer as the ordering and the gaps in its layer allow.
+    fn place_horizontally(&mut self) {
+        for layer in 0..self.layers.len() {
+            let desired = self.layers[layer].iter().map(|v| self.vertices[*v].x).collect_vec();
+            self.place_layer(layer, &desired);
+        }
+        for sweep in 0..self.config.sweeps {
+            let downwards = sweep % 2 == 0;
+            for index in self.sweep_order(downwards) {
+                let neighbours = if downwards { &self.upper } else { &self.lower };
+                let desired = self.layers[index].iter().map(|vertex| {
+                    let neighbours = neighbours[*vertex].iter().map(|n| self.vertices[*n].x);
+                    mean(neighbours).unwrap_or(self.vertices[*vertex].x)
+                });
+                let desired = desired.collect_vec();
+                self.place_layer(index, &desired);
+            }
+        }
+    }
+
+    /// Place the vertices of the layer as close to the desired positions as possible, keeping
+    /// their order and the gaps between them.
+    ///
+    /// Subtracting the minimal offset of each vertex from the leftmost one turns the problem into
+    /// finding the closest non-decreasing sequence, solved with the pool adjacent violators
+    /// algorithm.
+    fn place_layer(&mut self, layer: usize, desired: &[f32]) {
+        let vertices = &self.layers[layer];
+        let widths = vertices.iter().map(|vertex| self.vertices[*vertex].size.x).collect_vec();
+        let offsets = widths.iter().tuple_windows().scan(0.0, |offset, (left, right)| {
+            *offset += (left + right) / 2.0 + self.config.horizontal_gap;
+            Some(*offset)
+        });
+        let offsets = iter::once(0.0).chain(offsets).collect_vec();
+        // Blocks of merged vertices: the sum of their shifted desired positions and their count.
+        let mut blocks: Vec<(f32, usize)> = Vec::new();
+        for (desired, offset) in desired.iter().zip(&offsets) {
+            blocks.push((desired - offset, 1));
+            while let [.., (left_sum, left_count), (right_sum, right_count)] = blocks[..] {
+                if left_sum / left_count as f32 <= right_sum / right_count as f32 {
+                    break;
+                }
+                blocks.pop();
+                *blocks.last_mut().unwrap() = (left_sum + right_sum, left_count + right_count);
+            }
+        }
+        let shifted =
+            blocks.iter().flat_map(|(sum, count)| iter::repeat(sum / *count as f32).take(*count));
+        for ((vertex, shifted), offset) in vertices.iter().zip(shifted).zip(&offsets) {
+            self.vertices[*vertex].x = shifted + offset;
+        }
+    }
+
+    /// The final positions of the nodes, in the convention of [`model::module::Position`].
+    fn positions(&self, nodes: &[Node]) -> HashMap<node::Id, Vector2> {
+        let config = self.config;
+        let size_of = |node: &Node| node.size.unwrap_or(config.default_node_size);
+        let current_left = nodes.iter().map(|node| node.position.x).reduce(f32::min);
+        let current_top = nodes.iter().map(|node| node.position.y + size_of(node).y / 2.0);
+        let current_top = current_top.reduce(f32::max);
+        let real_vertices = self.vertices.iter().filter(|vertex| vertex.node.is_some());
+        let left = real_vertices.map(|vertex| vertex.x - vertex.size.x / 2.0).reduce(f32::min);
+        let shift = current_left.unwrap_or_default() - left.unwrap_or_default();
+
+        let mut top = current_top.unwrap_or_default();
+        let mut positions = HashMap::new();
+        for layer in &self.layers {
+            let height = layer.iter().map(|v| self.vertices[*v].size.y).reduce(f32::max);
+            let height = height.unwrap_or_default();
+            for vertex in layer.iter().map(|vertex| &self.vertices[*vertex]) {
+                if let Some(id) = vertex.node {
+                    let x = vertex.x - vertex.size.x / 2.0 + shift;
+                    positions.insert(id, Vector2::new(x, top - height / 2.0));
+                }
+            }
+            top -= height + config.vertical_gap;
+        }
+        positions
+    }
+}
+
+fn mean(values: impl Iterator<Item = f32>) -> Option<f32> {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), value| (sum + value, count + 1));
+    (count > 0).then_some(sum / count as f32)
+}
+
+fn position_in_layer(layer: &[usize]) -> HashMap<usize, usize> {
+    layer.iter().enumerate().map(|(position, vertex)| (*vertex, position)).collect()
+}
+
+/// Reverse the edges closing cycles, found by a depth-first search.
+///
+/// The search keeps its path on an explicit stack, so it handles graphs of any depth.
+fn acyclic(vertex_count: usize, edges: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum State {
+        New,
+        Visiting,
+        Visited,
+    }
+    let mut successors = vec![Vec::new(); vertex_count];
+    for (source, target) in &edges {
+        successors[*source].push(*target);
+    }
+    let mut state = vec![State::New; vertex_count];
+    let mut back_edges = Vec::new();
+    // The vertices on the search path, with the number of their successors already visited.
+    let mut path: Vec<(usize, usize)> = Vec::new();
+    for root in 0..vertex_count {
+        if state[root] != State::New {
+            continue;
+        }
+        state[root] = State::Visiting;
+        path.push((root, 0));
+        while let Some((vertex, visited_successors)) = path.last_mut() {
+            let vertex = *vertex;
+            match successors[vertex].get(*visited_successors) {
+                Some(&successor) => {
+                    *visited_successors += 1;
+                    match state[successor] {
+                        State::New => {
+                            state[successor] = State::Visiting;
+                            path.push((successor, 0));
+                        }
+                        State::Visiting => back_edges.push((vertex, successor)),
+                        State::Visited => {}
+                    }
+                }
+                None => {
+                    state[vertex] = State::Visited;
+                    path.pop();
+                }
+            }
+        }
+    }
+    let reversed = |edge: (usize, usize)| match back_edges.contains(&edge) {
+        true => (edge.1, edge.0),
+        false => edge,
+    };
+    edges.into_iter().map(reversed).unique().collect()
+}
+
+/// Assign the vertices of an acyclic graph to layers, so each edge goes to a lower layer.
+///
+/// Each vertex is placed one layer below its lowest predecessor. The vertices without predecessors
+/// are then moved down to be just above their highest successor, to avoid long edges from the
+/// graph's inputs.
+fn assign_layers(vertex_count: usize, edges: &[(usize, usize)]) -> Vec<usize> {
+    let mut successors = vec![Vec::new(); vertex_count];
+    let mut predecessor_count = vec![0; vertex_count];
+    for (source, target) in edges {
+        successors[*source].push(*target);
+        predecessor_count[*target] += 1;
+    }
+    let sources = (0..vertex_count).filter(|vertex| predecessor_count[*vertex] == 0).collect_vec();
+    let mut layers = vec![0; vertex_count];
+    let mut remaining = predecessor_count;
+    let mut ready = sources.clone();
+    while let Some(vertex) = ready.pop() {
+        for &successor in &successors[vertex] {
+            layers[successor] = layers[successor].max(layers[vertex] + 1);
+            remaining[successor] -= 1;
+            if remaining[successor] == 0 {
+                ready.push(successor);
+            }
+        }
+    }
+    for source in sources {
+        let highest_successor = successors[source].iter().map(|successor| layers[*successor]).min();
+        if let Some(highest_successor) = highest_successor {
+            layers[source] = highest_successor - 1;
+        }
+    }
+    layers
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Case {
+        nodes:       Vec<Node>,
+        connections: Vec<(node::Id, node::Id)>,
+    }
+
+    impl Case {
+        /// Create nodes of default size, placed in a row in the given order.
+        fn new(node_count: usize, connections: &[(usize, usize)]) -> Self {
+            let ids = (0..node_count).map(|_| node::Id::new_v4()).collect_vec();
+            let nodes = ids.iter().enumerate().map(|(index, id)| {
+                let position = Vector2::new(index as f32 * 200.0, 0.0);
+                Node { id: *id, size: None, position }
+            });
+            let nodes = nodes.collect();
+            let connections = connections.iter().map(|(s, t)| (ids[*s], ids[*t])).collect();
+            Self { nodes, connections }
+        }
+
+        fn run(&self) -> Vec<Vector2> {
+            let config = default();
+            let positions = layout(&self.nodes, self.connections.iter().copied(), &config);
+            self.nodes.iter().map(|node| positions[&node.id]).collect()
+        }
+    }
+
+    #[test]
+    fn connections_flow_downwards() {
+        let case = Case::new(4, &[(3, 2), (2, 1), (1, 0)]);
+        let positions = case.run();
+        let config = Config::default();
+        let layer_distance = config.default_node_size.y + config.vertical_gap;
+        for (lower, upper) in positions.iter().tuple_windows() {
+            assert_eq!(upper.y - lower.y, layer_distance);
+            assert_eq!(upper.x, lower.x);
+        }
+        // The layout keeps the top-left corner of the nodes' bounding box.
+        assert_eq!(positions[3], Vector2::new(0.0, 0.0));
+    }
+
+    #[test]
+    fn inputs_are_placed_above_their_users() {
+        // Node 3 uses nodes 0 and 2, and node 2 uses node 1.
+        let case = Case::new(4, &[(0, 3), (1, 2), (2, 3)]);
+        let positions = case.run();
+        assert_eq!(positions[0].y, positions[2].y);
+        assert!(positions[1].y > positions[2].y);
+        assert!(positions[2].y > positions[3].y);
+    }
+
+    #[test]
+    fn crossings_are_removed() {
+        // Two independent chains, 0 -> 3 and 1 -> 2, cross when kept in the initial order.
+        let case = Case::new(4, &[(0, 3), (1, 2)]);
+        let positions = case.run();
+        let first_on_left = positions[0].x < positions[1].x;
+        assert_eq!(positions[3].x < positions[2].x, first_on_left);
+    }
+
+    #[test]
+    fn nodes_do_not_overlap() {
+        let connections = [(0, 4), (1, 4), (2, 4), (3, 4), (0, 5), (3, 5)];
+        let mut case = Case::new(6, &connections);
+        case.nodes[1].size = Some(Vector2::new(300.0, 64.0));
+        let positions = case.run();
+        let config = Config::default();
+        let size_of = |index: usize| case.nodes[index].size.unwrap_or(config.default_node_size);
+        for (a, b) in (0..6).tuple_combinations() {
+            let (a_left, b_left) = (positions[a].x, positions[b].x);
+            let (a_right, b_right) = (a_left + size_of(a).x, b_left + size_of(b).x);
+            let (a_top, b_top) =
+                (positions[a].y + size_of(a).y / 2.0, positions[b].y + size_of(b).y / 2.0);
+            let (a_bottom, b_bottom) = (a_top - size_of(a).y, b_top - size_of(b).y);
+            let separate_horizontally = a_right <= b_left || b_right <= a_left;
+            let separate_vertically = a_bottom >= b_top || b_bottom >= a_top;
+            assert!(separate_horizontally || separate_vertically, "Nodes {a} and {b} overlap.");
+        }
+    }
+
+    #[test]
+    fn cycles_are_laid_out() {
+        let case = Case::new(3, &[(0, 1), (1, 2), (2, 0)]);
+        let positions = case.run();
+        assert!(positions[0].y > positions[1].y);
+        assert!(positions[1].y > positions[2].y);
+    }
+
+    #[test]
+    fn deep_graphs_are_made_acyclic() {
+        // A chain deeper than a recursive search could handle, closed into a cycle.
+        let depth = 100_000;
+        let mut edges = (0..depth - 1).map(|vertex| (vertex, vertex + 1)).collect_vec();
+        edges.push((depth - 1, 0));
+        let edges = acyclic(depth, edges);
+        assert_eq!(edges.len(), depth);
+        assert!(edges.contains(&(0, depth - 1)));
+        assert!(edges.iter().all(|(source, target)| source < target));
+    }
+
+    #[test]
+    fn long_connections_do_not_cross_nodes() {
+        // The connection 0 -> 3 spans two layers, passing by nodes 1 and 2.
+        let case = Case::new(4, &[(0, 1), (1, 2), (2, 3), (0, 3), (0, 2)]);
+        let positions = case.run();
+        assert!(positions[0].y > positions[1].y);
+        assert!(positions[1].y > positions[2].y);
+        assert!(positions[2].y > positions[3].y);
+    }
+}
@@ -5,14 +5,34 @@ use crate::prelude::*;
 
 use crate::controller::ide::StatusNotificationPublisher;
 
+use ast::HasIdMap;
+use double_representation::diff::module_diff;
+use double_representation::diff::GraphDiff;
 use double_representation::import;
+use double_representation::import_analysis;
 use double_representation::name::project;
 use double_representation::name::QualifiedName;
+use double_representation::refactorings::rename;
+use double_representation::refactorings::rename::Rename;
+use double_representation::text::apply_code_change_to_id_map;
+use engine_protocol::language_server;
+use engine_protocol::language_server::response;
+use engine_protocol::language_server::FileEdit;
+use engine_protocol::language_server::FileSystemObject;
 use engine_protocol::language_server::MethodPointer;
 use engine_protocol::language_server::Path;
+use engine_protocol::language_server::TextEdit;
+use engine_protocol::language_server::TextRange;
+use engine_protocol::types::Sha3_224;
+use engine_protocol::types::UTCDateTime;
 use enso_frp::web::platform;
 use enso_frp::web::platform::Platform;
+use enso_text::index::*;
+use model::module::TextChange;
+use model::suggestion_database::entry::Kind as EntryKind;
 use parser::Parser;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 
 
 
@@ -28,6 +48,15 @@ pub const COMPILING_STDLIB_LABEL: &str = "Compiling standard library. It can tak
 /// This is the definition whose graph will be opened on IDE start.
 pub const MAIN_DEFINITION_NAME: &str = "main";
 
+/// Name of the file in the project's root directory which customizes the IDE theme. See
+/// [`ensogl::display::style::file`] for the description of its format.
+pub const THEME_FILE_NAME: &str = "theme.json";
+
+/// The directory storing the modules' content of the named snapshots, relative to the project's
+/// root directory. Each snapshot's content is stored in a file named after its commit ID. The
+/// directory is not reverted when restoring a snapshot.
+pub const SNAPSHOT_CONTENTS_DIRECTORY: [&str; 2] = [".enso", "snapshots"];
+
 /// The code with definition of the default `main` method.
 pub fn default_main_method_code() -> String {
     format!(r#"{MAIN_DEFINITION_NAME} = "Hello, World!""#)
@@ -49,6 +78,35 @@ pub fn main_method_ptr(
 
 
 
+// ==============
+// === Errors ===
+// ==============
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(display = "`{}` cannot be renamed: it is not a method, a type or a constructor.", _0)]
+pub struct CannotRename(QualifiedName);
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(display = "Cannot rename to `{}`, as it is already defined.", _0)]
+pub struct NameAlreadyDefined(QualifiedName);
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(display = "Module {} has changed since the rename was prepared.", _0)]
+pub struct OutdatedRenamePreview(model::module::Path);
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(display = "The content of module {} in snapshot {} is unknown.", module, commit_id)]
+pub struct SnapshotContentUnavailable {
+    module:    model::module::Path,
+    commit_id: String,
+}
+
+
+
 // =================
 // === Utilities ===
 // =================
@@ -91,12 +149,16 @@ pub struct InitializationResult {
 pub struct Project {
     pub model:                model::Project,
     pub status_notifications: StatusNotificationPublisher,
+    /// The modules' content of the snapshots saved or read by this controller, see
+    /// [`Self::save_named_snapshot`].
+    snapshot_contents:        SnapshotContents,
 }
 
 impl Project {
     /// Create a controller of given project.
     pub fn new(model: model::Project, status_notifications: StatusNotificationPublisher) -> Self {
-        Self { model, status_notifications }
+        let snapshot_contents = default();
+        Self { model, status_notifications, snapshot_contents }
     }
 
     /// Do the initial setup of opened project.
@@ -218,6 +280,29 @@ impl Project {
 }
 
 
+// === Theme File ===
+
+impl Project {
+    /// The path to the project's theme file.
+    pub fn theme_file_path(&self) -> Path {
+        Path::new(self.model.project_content_root_id(), [THEME_FILE_NAME])
+    }
+
+    /// Read the content of the project's theme file. Returns [`None`] if the project does not
+    /// have one.
+    #[profile(Detail)]
+    pub async fn read_theme_file(&self) -> FallibleResult<Option<String>> {
+        let path = self.theme_file_path();
+        let rpc = self.model.json_rpc();
+        if rpc.file_exists(&path).await?.exists {
+            Ok(Some(rpc.read_file(&path).await?.contents))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+
 // === Project Snapshotting ===
 
 impl Project {
@@ -238,13 +323,558 @@ impl Project {
     #[profile(Detail)]
     pub fn restore_project_snapshot(&self) -> impl Future<Output = FallibleResult> {
         let project_root_id = self.model.project_content_root_id();
-        let path_segments: [&str; 0] = [];
-        let root_path = Path::new(project_root_id, &path_segments);
         let language_server = self.model.json_rpc();
-        async move {
-            language_server.restore_vcs(&root_path, &None).await?;
-            Ok(())
+        restore_vcs_keeping_snapshot_contents(language_server, project_root_id, None)
+    }
+
+    /// Saves a snapshot of the current state of the project to the VCS under the given name.
+    ///
+    /// The content of the project's modules is stored in the project's directory alongside the
+    /// snapshot (see [`SNAPSHOT_CONTENTS_DIRECTORY`]), so the snapshot can be compared with other
+    /// ones by [`Self::snapshot_diff`], also in later sessions.
+    #[profile(Detail)]
+    pub async fn save_named_snapshot(&self, name: &str) -> FallibleResult<Snapshot> {
+        let root_path = Path::new_root(self.model.project_content_root_id());
+        let language_server = self.model.json_rpc();
+        // The content is read before saving, as the modules may be edited while the snapshot is
+        // being saved.
+        let mut contents = HashMap::new();
+        for path in self.module_paths() {
+            let file = language_server.read_file(path.file_path()).await?;
+            contents.insert(path, file.contents);
+        }
+        let timestamp = chrono::Utc::now().into();
+        let message = Snapshot::message(name, &timestamp);
+        let save = language_server.save_vcs(&root_path, &Some(message)).await?;
+        let snapshot = Snapshot::from_save(save);
+        let serialized = contents.iter().map(|(path, content)| (path.id().to_string(), content));
+        let serialized = serde_json::to_string(&serialized.collect::<BTreeMap<_, _>>())?;
+        let contents_path = self.snapshot_contents_path(&snapshot.commit_id);
+        language_server.write_file(&contents_path, &serialized).await?;
+        self.snapshot_contents.borrow_mut().insert(snapshot.commit_id.clone(), contents);
+        Ok(snapshot)
+    }
+
+    /// Lists the snapshots saved to the VCS, starting from the latest one. If `limit` is given,
+    /// only that many latest snapshots are listed.
+    #[profile(Detail)]
+    pub async fn snapshots(&self, limit: Option<usize>) -> FallibleResult<Vec<Snapshot>> {
+        let root_path = Path::new_root(self.model.project_content_root_id());
+        let list = self.model.json_rpc().list_vcs(&root_path, &limit).await?;
+        Ok(list.saves.into_iter().map(Snapshot::from_save).collect())
+    }
+
+    /// Restores the state of the project to the given snapshot. The stored content of all the
+    /// named snapshots is kept, so they can still be compared after the restore.
+    #[profile(Detail)]
+    pub async fn restore_snapshot(&self, snapshot: &Snapshot) -> FallibleResult {
+        let root_id = self.model.project_content_root_id();
+        let commit_id = Some(snapshot.commit_id.clone());
+        restore_vcs_keeping_snapshot_contents(self.model.json_rpc(), root_id, commit_id).await
+    }
+
+    /// Compares the graphs of the module in two snapshots, returning the added, removed and
+    /// changed nodes of each changed graph. If `new` is `None`, the current state of the module
+    /// is compared.
+    ///
+    /// Only the snapshots saved by [`Self::save_named_snapshot`] can be compared, as the Language
+    /// Server does not provide the content of past snapshots.
+    pub async fn snapshot_diff(
+        &self,
+        module: &model::module::Path,
+        old: &Snapshot,
+        new: Option<&Snapshot>,
+    ) -> FallibleResult<Vec<GraphDiff>> {
+        let old = self.module_info_in_snapshot(module, old).await?;
+        let new = match new {
+            Some(new) => self.module_info_in_snapshot(module, new).await?,
+            None => self.model.module(module.clone_ref()).await?.info(),
+        };
+        Ok(module_diff(&old, &new))
+    }
+
+    /// The path of the file with the modules' content of the snapshot.
+    fn snapshot_contents_path(&self, commit_id: &str) -> Path {
+        let file_name = format!("{commit_id}.json");
+        let segments = SNAPSHOT_CONTENTS_DIRECTORY.iter().copied().chain(iter::once(&*file_name));
+        Path::new(self.model.project_content_root_id(), segments)
+    }
+
+    /// Read the modules' content of the snapshot from the project's directory, unless it is
+    /// already known.
+    async fn load_snapshot_contents(&self, snapshot: &Snapshot) -> FallibleResult {
+        let commit_id = &snapshot.commit_id;
+        if self.snapshot_contents.borrow().contains_key(commit_id) {
+            return Ok(());
+        }
+        let path = self.snapshot_contents_path(commit_id);
+        let language_server = self.model.json_rpc();
+        if !language_server.file_exists(&path).await?.exists {
+            return Ok(());
+        }
+        let file = language_server.read_file(&path).await?;
+        let serialized: BTreeMap<String, String> = serde_json::from_str(&file.contents)?;
+        let root_id = self.model.project_content_root_id();
+        let contents = serialized.into_iter().filter_map(|(module, content)| {
+            let path = model::module::Path::from_name_segments(root_id, module.split('.')).ok()?;
+            Some((path, content))
+        });
+        self.snapshot_contents.borrow_mut().insert(commit_id.clone(), contents.collect());
+        Ok(())
+    }
+
+    async fn module_info_in_snapshot(
+        &self,
+        module: &model::module::Path,
+        snapshot: &Snapshot,
+    ) -> FallibleResult<double_representation::module::Info> {
+        self.load_snapshot_contents(snapshot).await?;
+        let contents = self.snapshot_contents.borrow();
+        let content = contents.get(&snapshot.commit_id).and_then(|modules| modules.get(module));
+        let content = content.ok_or_else(|| SnapshotContentUnavailable {
+            module:    module.clone_ref(),
+            commit_id: snapshot.commit_id.clone(),
+        })?;
+        let parsed = self.model.parser().parse_with_metadata::<serde_json::Value>(content);
+        Ok(parsed.ast.into())
+    }
+
+    /// The paths of all the modules defined in this project.
+    fn module_paths(&self) -> Vec<model::module::Path> {
+        let suggestion_db = self.model.suggestion_db();
+        let entries =
+            suggestion_db.keys().into_iter().filter_map(|id| suggestion_db.lookup(id).ok());
+        let modules = entries.filter(|entry| entry.kind == EntryKind::Module);
+        let project_name = self.model.qualified_name();
+        let modules = modules.map(|entry| entry.qualified_name());
+        let modules = modules.filter(|module| *module.project() == project_name);
+        let root_id = self.model.project_content_root_id();
+        modules.map(|module| model::module::Path::from_name(root_id, &module)).collect()
+    }
+}
+
+/// Restore the project to the given commit of the VCS, or to the last one if `commit_id` is `None`.
+///
+/// The restore reverts all the files of the project, so the files in
+/// [`SNAPSHOT_CONTENTS_DIRECTORY`] would be removed or reverted as well, losing the content of the
+/// snapshots saved after the restored one. Therefore, they are read before the restore and
+/// written back after it.
+async fn restore_vcs_keeping_snapshot_contents(
+    language_server: Rc<language_server::Connection>,
+    root_id: Uuid,
+    commit_id: Option<String>,
+) -> FallibleResult {
+    let directory = Path::new(root_id, SNAPSHOT_CONTENTS_DIRECTORY);
+    let mut files = Vec::new();
+    if language_server.file_exists(&directory).await?.exists {
+        for object in language_server.file_list(&directory).await?.paths {
+            if let FileSystemObject::File { .. } = object {
+                let path = Path::from(&object);
+                let contents = language_server.read_file(&path).await?.contents;
+                files.push((path, contents));
+            }
+        }
+    }
+    language_server.restore_vcs(&Path::new_root(root_id), &commit_id).await?;
+    for (path, contents) in files {
+        language_server.write_file(&path, &contents).await?;
+    }
+    Ok(())
+}
+
+/// The modules' content in the snapshots, by the snapshots' commit IDs.
+type SnapshotContents = Rc<RefCell<HashMap<String, HashMap<model::module::Path, String>>>>;
+
+/// A snapshot of the project saved to the VCS.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Snapshot {
+    /// The identifier of the snapshot in the VCS.
+    pub commit_id: String,
+    /// The name given to the snapshot when saving. The snapshots saved without a name, e.g. by
+    /// [`Project::save_project_snapshot`], have none.
+    pub name:      Option<String>,
+    /// The time of saving the snapshot, if known.
+    pub timestamp: Option<UTCDateTime>,
+}
+
+impl Snapshot {
+    /// The commit message of a named snapshot.
+    ///
+    /// The Language Server uses the saving time as the message of the snapshots saved without a
+    /// name, so the named ones are prefixed with it as well.
+    fn message(name: &str, timestamp: &UTCDateTime) -> String {
+        let timestamp = timestamp.to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+        format!("{timestamp} {name}")
+    }
+
+    fn from_save(save: response::SaveVcs) -> Self {
+        let response::SaveVcs { commit_id, message } = save;
+        let parse_timestamp = |text: &str| UTCDateTime::parse_from_rfc3339(text.trim()).ok();
+        let (name, timestamp) = match parse_timestamp(&message) {
+            Some(timestamp) => (None, Some(timestamp)),
+            None => match message.split_once(' ') {
+                Some((timestamp, name)) if parse_timestamp(timestamp).is_some() =>
+                    (Some(name.to_owned()), parse_timestamp(timestamp)),
+                _ => (Some(message), None),
+            },
+        };
+        Self { commit_id, name, timestamp }
+    }
+}
+
+
+// === Refactoring ===
+
+/// A line of a module's code changed by a rename refactoring.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RenamedLine {
+    /// The line number, counted from 0.
+    pub number: usize,
+    /// The line's content before the rename.
+    pub old:    String,
+    /// The line's content after the rename.
+    pub new:    String,
+}
+
+/// The changes a rename refactoring makes to a single module.
+#[derive(Clone, Debug)]
+pub struct ModuleRenamePreview {
+    /// The path of the changed module.
+    pub path:    model::module::Path,
+    /// The module's code the changes were computed for.
+    pub code:    String,
+    /// The changes of the module's code, ordered from the end of the code.
+    pub changes: Vec<TextChange>,
+    /// The changed lines, ordered by their numbers.
+    pub lines:   Vec<RenamedLine>,
+}
+
+impl ModuleRenamePreview {
+    fn new(path: model::module::Path, code: String, changes: Vec<TextChange>) -> Self {
+        let mut new_code = code.clone();
+        for change in &changes {
+            new_code.replace_range(change.range.start.value..change.range.end.value, &change.text);
+        }
+        let line_of = |change: &TextChange| code[..change.range.start.value].matches('\n').count();
+        let numbers = changes.iter().map(line_of).sorted().dedup().collect_vec();
+        // The renamed names never span multiple lines, so the line numbers are not affected.
+        let old_lines = code.split('\n').collect_vec();
+        let new_lines = new_code.split('\n').collect_vec();
+        let lines = numbers.into_iter().map(|number| RenamedLine {
+            number,
+            old: old_lines[number].to_owned(),
+            new: new_lines[number].to_owned(),
+        });
+        let lines = lines.collect();
+        Self { path, code, changes, lines }
+    }
+}
+
+/// The prepared rename refactoring, with all the changes it will make to the project's modules.
+///
+/// The preview should be shown to the user before applying it with [`Project::apply_rename`].
+#[derive(Clone, Debug)]
+pub struct RenamePreview {
+    /// The prepared rename of the entity.
+    pub rename:  Rename,
+    /// The modules to change, the ones without any occurrence of the renamed entity are skipped.
+    pub modules: Vec<ModuleRenamePreview>,
+}
+
+impl Display for RenamePreview {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let target = &self.rename.target;
+        writeln!(f, "Rename {target} to {}:", self.rename.new_name)?;
+        for module in &self.modules {
+            for line in &module.lines {
+                let path = &module.path;
+                let number = line.number + 1;
+                writeln!(f, "{path}:{number}: {} => {}", line.old.trim(), line.new.trim())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The kind of the renamed entity described by the suggestion database entry.
+fn rename_kind(entry: &model::suggestion_database::Entry) -> Option<rename::Kind> {
+    match entry.kind {
+        EntryKind::Type => Some(rename::Kind::Type),
+        EntryKind::Constructor => Some(rename::Kind::Constructor),
+        EntryKind::Method if entry.self_type.as_ref() == Some(&entry.defined_in) =>
+            Some(rename::Kind::ModuleMethod),
+        EntryKind::Method => Some(rename::Kind::TypeMethod),
+        EntryKind::Function | EntryKind::Local | EntryKind::Module => None,
+    }
+}
+
+impl Project {
+    /// Prepare renaming a method, a type or a constructor defined in this project. Finds all
+    /// occurrences of the renamed entity in the project's modules, including the imports and
+    /// qualified references.
+    ///
+    /// Method calls on arbitrary values (like `value.method`) are renamed only if no other
+    /// method in the suggestion database has the same name.
+    pub async fn prepare_rename(
+        &self,
+        target: &QualifiedName,
+        new_name: &str,
+    ) -> FallibleResult<RenamePreview> {
+        let suggestion_db = self.model.suggestion_db();
+        let (target_id, entry) = suggestion_db.lookup_by_qualified_name(target)?;
+        let kind = rename_kind(&entry).ok_or_else(|| CannotRename(target.clone()))?;
+        let mut rename = Rename::new(target.clone(), kind, new_name)?;
+        let parent = target.parent().ok_or_else(|| CannotRename(target.clone()))?.to_owned();
+        let new_target = parent.clone().new_child(new_name);
+        if suggestion_db.lookup_by_qualified_name(&new_target).is_ok() {
+            return Err(NameAlreadyDefined(new_target).into());
+        }
+        let entries =
+            suggestion_db.keys().into_iter().filter_map(|id| suggestion_db.lookup(id).ok());
+        let entries = entries.collect_vec();
+        let same_named_methods = entries
+            .iter()
+            .filter(|other| other.kind == EntryKind::Method && other.name == entry.name)
+            .count();
+        rename.rename_method_calls = kind == rename::Kind::TypeMethod && same_named_methods == 1;
+
+        // The renamed entity may be used in any of the project's modules. Besides, the methods
+        // of the renamed type may be defined as extension methods in other modules.
+        let modules = entries.iter().filter(|entry| entry.kind == EntryKind::Module);
+        let modules = modules.map(|entry| entry.qualified_name());
+        let mut members = suggestion_db.lookup_hierarchy(target_id).unwrap_or_default();
+        if let Ok((parent_id, _)) = suggestion_db.lookup_by_qualified_name(&parent) {
+            members.extend(suggestion_db.lookup_hierarchy(parent_id).unwrap_or_default());
+        }
+        let members = members.into_iter().filter_map(|id| suggestion_db.lookup(id).ok());
+        let extending_modules = members.map(|member| member.defined_in.clone());
+        let project_name = self.model.qualified_name();
+        let modules = modules.chain(extending_modules).unique();
+        let modules = modules.filter(|module| *module.project() == project_name).collect_vec();
+
+        let root_id = self.model.project_content_root_id();
+        let mut previews = Vec::new();
+        for module_name in modules {
+            let path = model::module::Path::from_name(root_id, &module_name);
+            let module = self.model.module(path.clone_ref()).await?;
+            let changes = rename.changes(&module.info(), &module_name)?;
+            if !changes.is_empty() {
+                previews.push(ModuleRenamePreview::new(path, module.ast().repr(), changes));
+            }
+        }
+        Ok(RenamePreview { rename, modules: previews })
+    }
+
+    /// Apply the prepared rename refactoring as a single undoable operation.
+    ///
+    /// The changes of each module are sent to the Language Server as a single versioned file
+    /// edit, and then the module models are reloaded from the Language Server. Fails without
+    /// changing anything if any of the modules changed since the rename was prepared. If the
+    /// Language Server rejects the edit of any module, the edits already applied to the other
+    /// modules are reverted, so the project is never left partially renamed.
+    pub async fn apply_rename(&self, preview: &RenamePreview) -> FallibleResult {
+        let parser = self.model.parser();
+        let mut edits = Vec::new();
+        for module_preview in &preview.modules {
+            let path = module_preview.path.clone_ref();
+            let module = self.model.module(path.clone_ref()).await?;
+            if module.ast().repr() != module_preview.code {
+                return Err(OutdatedRenamePreview(path).into());
+            }
+            edits.push(ModuleRenameEdit::new(module, &module_preview.changes, &parser)?);
+        }
+        let transaction_name = format!("Rename {}", preview.rename.old_name());
+        let _transaction = self.model.urm().get_or_open_transaction(&transaction_name);
+        let json_rpc = self.model.json_rpc();
+        let mut applied = Vec::new();
+        let mut failure = None;
+        for edit in &edits {
+            match json_rpc.apply_text_file_edit(&edit.edit, &true).await {
+                Ok(()) => applied.push(edit),
+                Err(error) => {
+                    failure = Some(error);
+                    break;
+                }
+            }
+        }
+        if failure.is_some() {
+            for edit in &applied {
+                if let Err(error) = json_rpc.apply_text_file_edit(&edit.revert(), &true).await {
+                    let path = edit.module.path();
+                    error!("Failed to revert the rename in {path}: {error}");
+                }
+            }
+        }
+        for edit in &applied {
+            if let Err(error) = edit.module.reopen_externally_changed_file().await {
+                let path = edit.module.path();
+                error!("Failed to reload {path} after the rename: {error}");
+            }
+        }
+        match failure {
+            Some(error) => Err(error.into()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The edit of a module's file applying a rename refactoring, see [`Project::apply_rename`].
+#[derive(Clone, Debug)]
+struct ModuleRenameEdit {
+    module:      model::Module,
+    edit:        FileEdit,
+    old_content: String,
+    new_content: String,
+}
+
+impl ModuleRenameEdit {
+    /// Create the edit of the module's file applying the code changes. The ids of the AST nodes
+    /// are preserved, so the node metadata stay assigned to the same nodes.
+    fn new(module: model::Module, changes: &[TextChange], parser: &Parser) -> FallibleResult<Self> {
+        let old_file = module.serialized_content()?;
+        let mut code = module.ast().repr();
+        let mut id_map = module.ast().id_map();
+        for change in changes {
+            apply_code_change_to_id_map(&mut id_map, change, &code);
+            code.replace_range(change.range.start.value..change.range.end.value, &change.text);
+        }
+        let ast = parser.parse(code, id_map).try_into()?;
+        let metadata = parser.parse_with_metadata(old_file.content.clone()).metadata;
+        let new_file = model::module::Content { ast, metadata }.serialize()?;
+
+        // The code is at the beginning of the file, so the changes' offsets are valid in the whole
+        // file. The id map and metadata sections are edited first, as the code changes may alter
+        // their position. The code changes are ordered from the end of the code, so each of them
+        // is not affected by the previous ones.
+        let old_text = enso_text::Rope::from(&old_file.content);
+        let position = |offset| {
+            let location = old_text.offset_to_location_snapped(offset);
+            old_text.utf16_code_unit_location_of_location(location).into()
+        };
+        let range = |start, end| TextRange { start: position(start), end: position(end) };
+        let new_tail = &new_file.content[new_file.code.end.value..];
+        let tail_edit = TextEdit {
+            range: range(old_file.code.end, old_file.content.len().byte()),
+            text:  new_tail.into(),
+        };
+        let code_edits = changes.iter().map(|change| TextEdit {
+            range: range(change.range.start, change.range.end),
+            text:  change.text.clone(),
+        });
+        let edit = FileEdit {
+            path:        module.path().file_path().clone(),
+            edits:       iter::once(tail_edit).chain(code_edits).collect(),
+            old_version: Sha3_224::new(old_file.content.as_bytes()),
+            new_version: Sha3_224::new(new_file.content.as_bytes()),
+        };
+        Ok(Self { module, edit, old_content: old_file.content, new_content: new_file.content })
+    }
+
+    /// The edit restoring the file's content from before this edit.
+    fn revert(&self) -> FileEdit {
+        let edit = TextEdit::from_prefix_postfix_differences(&self.new_content, &self.old_content);
+        FileEdit {
+            path:        self.edit.path.clone(),
+            edits:       vec![edit],
+            old_version: self.edit.new_version.clone(),
+            new_version: self.edit.old_version.clone(),
+        }
+    }
+}
+
+
+// === Import Analysis ===
+
+/// Resolves the names imported by the project's modules using the suggestion database.
+#[derive(Clone, Debug, Default)]
+struct ImportResolver {
+    /// The names brought into scope by importing all from the module or type.
+    defined_names:     HashMap<QualifiedName, BTreeSet<String>>,
+    /// The modules defining methods of types from other modules.
+    extending_modules: HashSet<QualifiedName>,
+    known_modules:     HashSet<QualifiedName>,
+}
+
+impl ImportResolver {
+    fn new(suggestion_db: &model::SuggestionDatabase) -> Self {
+        let mut resolver = Self::default();
+        let entries =
+            suggestion_db.keys().into_iter().filter_map(|id| suggestion_db.lookup(id).ok());
+        for entry in entries {
+            if matches!(entry.kind, EntryKind::Local | EntryKind::Function) {
+                continue;
+            }
+            let name = entry.qualified_name();
+            if entry.kind == EntryKind::Module {
+                resolver.known_modules.insert(name.clone());
+            }
+            if let Some(self_type) = &entry.self_type {
+                if !self_type.is_descendant_of(entry.defined_in.as_ref()) {
+                    resolver.extending_modules.insert(entry.defined_in.clone());
+                }
+            }
+            if let Some(parent) = name.parent() {
+                let names = resolver.defined_names.entry(parent.to_owned()).or_default();
+                names.insert(entry.name.to_string());
+            }
+            if let Some(reexporting_module) = &entry.reexported_in {
+                let names = resolver.defined_names.entry(reexporting_module.clone()).or_default();
+                names.insert(entry.name.to_string());
+            }
         }
+        resolver
+    }
+}
+
+impl import_analysis::NameResolver for ImportResolver {
+    fn defined_names(&self, name: &QualifiedName) -> Option<BTreeSet<String>> {
+        self.defined_names.get(name).cloned()
+    }
+
+    fn defines_extension_methods(&self, module: &QualifiedName) -> bool {
+        !self.known_modules.contains(module) || self.extending_modules.contains(module)
+    }
+}
+
+impl Project {
+    /// Analyze the imports of all the project's modules, finding the import cycles and the
+    /// unused or redundant imports.
+    ///
+    /// The temporary imports, added while the Component Browser is open, are not reported.
+    pub async fn analyze_imports(&self) -> FallibleResult<import_analysis::Report> {
+        let project_name = self.model.qualified_name();
+        let mut modules = Vec::new();
+        let mut temporary_imports = HashMap::new();
+        for path in self.module_paths() {
+            let module = self.model.module(path.clone_ref()).await?;
+            let temporary = module.all_import_metadata().into_iter();
+            let temporary = temporary.filter(|(_, metadata)| metadata.is_temporary);
+            let temporary: HashSet<_> = temporary.map(|(id, _)| id).collect();
+            let name = path.qualified_module_name(project_name.clone());
+            temporary_imports.insert(name.clone(), temporary);
+            modules.push((name, module.info()));
+        }
+        let resolver = ImportResolver::new(&self.model.suggestion_db());
+        let mut report = import_analysis::analyze(&modules, &resolver);
+        for module_report in &mut report.modules {
+            if let Some(temporary) = temporary_imports.get(&module_report.module) {
+                module_report.issues.retain(|issue| !temporary.contains(&issue.import.id()));
+            }
+        }
+        report.modules.retain(|module_report| !module_report.is_clean());
+        Ok(report)
+    }
+
+    /// Remove the unused and redundant imports of the module, as reported by
+    /// [`Self::analyze_imports`]. The change can be undone as a single operation.
+    pub async fn clean_up_imports(&self, report: &import_analysis::ModuleReport) -> FallibleResult {
+        let root_id = self.model.project_content_root_id();
+        let path = model::module::Path::from_name(root_id, &report.module);
+        let module = self.model.module(path).await?;
+        let mut info = module.info();
+        report.clean_up(&mut info, &self.model.parser())?;
+        let _transaction = self.model.urm().get_or_open_transaction("Clean up imports");
+        module.update_ast(info.ast)
     }
 }
 
@@ -260,6 +890,9 @@ mod tests {
 
     use crate::executor::test_utils::TestWithLocalPoolExecutor;
     use engine_protocol::language_server;
+    use engine_protocol::language_server::Position;
+    use enso_text::index::*;
+    use json_rpc::error::RpcError;
     use std::assert_matches::assert_matches;
 
     #[wasm_bindgen_test]
@@ -292,6 +925,309 @@ mod tests {
         expect_intact(&format!("{module_name}.main = 5"));
     }
 
+    #[wasm_bindgen_test]
+    fn rename_preview_lines() {
+        let code = "foo = 1\nbar = foo + 2\nmain = foo + foo";
+        let change = |start: usize| TextChange {
+            range: (start.byte()..(start + 3).byte()).into(),
+            text:  "baz".into(),
+        };
+        let changes = vec![change(35), change(29), change(14), change(0)];
+        let path = model::module::Path::from_mock_module_name("Test");
+        let preview = ModuleRenamePreview::new(path, code.into(), changes);
+        let line =
+            |number, old: &str, new: &str| RenamedLine { number, old: old.into(), new: new.into() };
+        let expected = vec![
+            line(0, "foo = 1", "baz = 1"),
+            line(1, "bar = foo + 2", "bar = baz + 2"),
+            line(2, "main = foo + foo", "main = baz + baz"),
+        ];
+        assert_eq!(preview.lines, expected);
+    }
+
+    fn mock_module(
+        parser: &parser::Parser,
+        urm: &model::undo_redo::Manager,
+        name: &str,
+        code: &str,
+        read_only: bool,
+    ) -> model::Module {
+        let path = model::module::Path::from_mock_module_name(name);
+        let ast = parser.parse_module(code, default()).unwrap();
+        let repository = urm.repository.clone_ref();
+        let read_only = Rc::new(Cell::new(read_only));
+        Rc::new(model::module::Plain::new(path, ast, default(), repository, read_only))
+    }
+
+    /// The files of a mock Language Server, changed by the text edits it receives.
+    #[derive(Clone, Debug, Default)]
+    struct MockFiles {
+        contents: Rc<RefCell<HashMap<Path, String>>>,
+        /// The files whose edits are rejected.
+        rejected: Rc<RefCell<HashSet<Path>>>,
+        /// The applied edits, in order.
+        applied:  Rc<RefCell<Vec<FileEdit>>>,
+    }
+
+    impl MockFiles {
+        fn insert(&self, module: &model::Module) {
+            let content = module.serialized_content().unwrap().content;
+            self.contents.borrow_mut().insert(module.path().file_path().clone(), content);
+        }
+
+        fn content(&self, path: &model::module::Path) -> String {
+            self.contents.borrow()[path.file_path()].clone()
+        }
+
+        /// Expect the given number of calls of each method opening, closing and editing the files.
+        fn expect_calls(&self, client: &language_server::MockClient, count: usize) {
+            for _ in 0..count {
+                let files = self.clone();
+                client.expect.open_text_file(move |path| {
+                    let content = files.contents.borrow()[path].clone();
+                    let current_version = Sha3_224::new(content.as_bytes());
+                    Ok(response::OpenTextFile { write_capability: None, content, current_version })
+                });
+                client.expect.close_text_file(|_| Ok(()));
+                let files = self.clone();
+                client.expect.apply_text_file_edit(move |edit, _| files.apply(edit));
+            }
+        }
+
+        fn apply(&self, edit: &FileEdit) -> json_rpc::Result<()> {
+            if self.rejected.borrow().contains(&edit.path) {
+                return Err(RpcError::LostConnection);
+            }
+            let mut contents = self.contents.borrow_mut();
+            let content = contents.get_mut(&edit.path).unwrap();
+            assert_eq!(edit.old_version, Sha3_224::new(content.as_bytes()));
+            for TextEdit { range, text } in &edit.edits {
+                let rope = enso_text::Rope::from(content.as_str());
+                let offset = |position: Position| {
+                    let location =
+                        rope.location_of_utf16_code_unit_location_snapped(position.into());
+                    rope.location_offset_snapped(location).value
+                };
+                content.replace_range(offset(range.start)..offset(range.end), text);
+            }
+            assert_eq!(edit.new_version, Sha3_224::new(content.as_bytes()));
+            self.applied.borrow_mut().push(edit.clone());
+            Ok(())
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn preparing_and_applying_rename() {
+        use model::suggestion_database::Entry;
+        let mut executor = TestWithLocalPoolExecutor::set_up();
+        let parser = parser::Parser::new();
+        let urm = Rc::new(model::undo_redo::Manager::new());
+        let name = |name: &str| QualifiedName::from_text(name).unwrap();
+        let number = name("Standard.Base.Data.Numbers.Number");
+        let entries = [
+            Entry::new_module(name("local.Project.Main")),
+            Entry::new_module(name("local.Project.Other")),
+            Entry::new_module_method(name("local.Project.Main"), "foo", number),
+        ];
+        let entries = entries.into_iter().enumerate().collect_vec();
+        let suggestion_db = model::SuggestionDatabase::new_from_entries(
+            entries.iter().map(|(id, entry)| (id, entry)),
+        );
+        let main_code = "foo = 1\nmain =\n    x = foo\n    x + 1";
+        let other_code = "import local.Project.Main\nbar = Main.foo";
+        let files = MockFiles::default();
+        for (name, code) in [("Main", main_code), ("Other", other_code)] {
+            let module = mock_module(&parser, &urm, name, code, false);
+            files.insert(&module);
+        }
+        let json_client = language_server::MockClient::default();
+        files.expect_calls(&json_client, 16);
+        let ls = language_server::Connection::new_mock_rc(json_client);
+        let open = |name: &str| {
+            let path = model::module::Path::from_mock_module_name(name);
+            let repository = urm.repository.clone_ref();
+            let module = model::module::Synchronized::open(
+                path,
+                ls.clone_ref(),
+                parser.clone_ref(),
+                repository,
+                default(),
+            );
+            let module: model::Module = executor.expect_completion(module).unwrap();
+            urm.module_opened(module.clone_ref());
+            module
+        };
+        let main = open("Main");
+        let other = open("Other");
+        executor.run_until_stalled();
+
+        let mut project = model::project::MockAPI::new();
+        for module in [&main, &other] {
+            model::project::test::expect_module(&mut project, module.clone_ref());
+        }
+        model::project::test::expect_parser(&mut project, &parser);
+        model::project::test::expect_root_id(&mut project, Uuid::default());
+        let project_name = project::QualifiedName::from_text("local.Project").unwrap();
+        model::project::test::expect_qualified_name(&mut project, &project_name);
+        model::project::test::expect_suggestion_db(&mut project, Rc::new(suggestion_db));
+        model::project::test::expect_json_rpc(&mut project, ls);
+        let project_urm = urm.clone_ref();
+        project.expect_urm().returning_st(move || project_urm.clone_ref());
+        let project_controller = controller::Project::new(Rc::new(project), default());
+
+        // The nodes which are not renamed keep their ids, and so their metadata.
+        let id_of = |module: &model::Module, expression: &str| {
+            let code = module.ast().repr();
+            let id_map = module.ast().id_map().vec.into_iter();
+            let mut ids =
+                id_map.filter(|(range, _)| &code[range.start.value..range.end.value] == expression);
+            ids.next().map(|(_, id)| id)
+        };
+        let node_id = id_of(&main, "x + 1").unwrap();
+        let target = name("local.Project.Main.foo");
+        let files_in_task = files.clone();
+        let edits_before = files.applied.borrow().len();
+        let (main_in_task, other_in_task) = (main.clone_ref(), other.clone_ref());
+        executor.run_task(async move {
+            let preview = project_controller.prepare_rename(&target, "total").await.unwrap();
+            let renamed_lines = preview.modules.iter().map(|module| module.lines.len());
+            assert_eq!(renamed_lines.collect_vec(), vec![2, 1]);
+            project_controller.apply_rename(&preview).await.unwrap();
+            let (main, other) = (main_in_task, other_in_task);
+            let expected_main = "total = 1\nmain =\n    x = total\n    x + 1";
+            let expected_other = "import local.Project.Main\nbar = Main.total";
+            assert_eq!(main.ast().repr(), expected_main);
+            assert_eq!(other.ast().repr(), expected_other);
+            // Each module is changed by a single edit.
+            let applied = files_in_task.applied.borrow();
+            let paths = applied[edits_before..].iter().map(|edit| &edit.path).take(2).collect_vec();
+            assert_eq!(paths, vec![main.path().file_path(), other.path().file_path()]);
+        });
+        executor.run_until_stalled();
+        assert_eq!(id_of(&main, "x + 1"), Some(node_id));
+        assert!(files.content(main.path()).starts_with("total = 1\n"));
+
+        // The rename is undone as a single operation.
+        urm.undo().unwrap();
+        executor.run_until_stalled();
+        assert_eq!(main.ast().repr(), main_code);
+        assert_eq!(other.ast().repr(), other_code);
+        assert!(files.content(other.path()).starts_with(other_code));
+    }
+
+    #[wasm_bindgen_test]
+    fn rename_is_reverted_when_a_module_fails() {
+        let parser = parser::Parser::new();
+        let urm = Rc::new(model::undo_redo::Manager::new());
+        let main_code = "foo = 1\nmain = foo";
+        let other_code = "bar = Main.foo";
+        let main = mock_module(&parser, &urm, "Main", main_code, false);
+        let other = mock_module(&parser, &urm, "Other", other_code, false);
+        let files = MockFiles::default();
+        files.insert(&main);
+        files.insert(&other);
+        let original_main_file = files.content(main.path());
+        // The Language Server rejects the edit of the other module.
+        files.rejected.borrow_mut().insert(other.path().file_path().clone());
+        let change = |start: usize| TextChange {
+            range: (start.byte()..(start + 3).byte()).into(),
+            text:  "baz".into(),
+        };
+        let main_preview =
+            ModuleRenamePreview::new(main.path().clone_ref(), main_code.into(), vec![
+                change(15),
+                change(0),
+            ]);
+        let other_preview =
+            ModuleRenamePreview::new(other.path().clone_ref(), other_code.into(), vec![change(11)]);
+        let target = QualifiedName::from_text("local.Project.Main.foo").unwrap();
+        let rename = Rename::new(target, rename::Kind::ModuleMethod, "baz").unwrap();
+        let preview = RenamePreview { rename, modules: vec![main_preview, other_preview] };
+
+        let mut project = model::project::MockAPI::new();
+        model::project::test::expect_module(&mut project, main.clone_ref());
+        model::project::test::expect_module(&mut project, other.clone_ref());
+        model::project::test::expect_parser(&mut project, &parser);
+        model::project::test::expect_qualified_name(&mut project, &default());
+        let json_client = language_server::MockClient::default();
+        files.expect_calls(&json_client, 3);
+        let ls = language_server::Connection::new_mock_rc(json_client);
+        model::project::test::expect_json_rpc(&mut project, ls);
+        project.expect_urm().returning_st(move || urm.clone_ref());
+        let project_controller = controller::Project::new(Rc::new(project), default());
+
+        TestWithLocalPoolExecutor::set_up().run_task(async move {
+            assert!(project_controller.apply_rename(&preview).await.is_err());
+            // The edit of the main module was applied and then reverted.
+            let applied = files.applied.borrow();
+            let main_file = main.path().file_path();
+            assert!(applied.iter().all(|edit| &edit.path == main_file));
+            assert_eq!(applied.len(), 2);
+            assert_eq!(files.content(main.path()), original_main_file);
+            assert_eq!(main.ast().repr(), main_code);
+            assert_eq!(other.ast().repr(), other_code);
+        });
+    }
+
+    #[wasm_bindgen_test]
+    fn analyzing_and_cleaning_up_imports() {
+        use model::suggestion_database::Entry;
+        let parser = parser::Parser::new();
+        let urm = Rc::new(model::undo_redo::Manager::new());
+        let name = |name: &str| QualifiedName::from_text(name).unwrap();
+        let number = name("Standard.Base.Data.Numbers.Number");
+        let entries = [
+            Entry::new_module(name("local.Project.Main")),
+            Entry::new_module(name("local.Project.Foo")),
+            Entry::new_module(name("local.Project.Extensions")),
+            Entry::new_module_method(name("local.Project.Foo"), "foo", number.clone()),
+            Entry::new_method(
+                name("local.Project.Extensions"),
+                name("Standard.Base.Data.Text.Text"),
+                "shout",
+                number,
+                false,
+            ),
+        ];
+        let entries = entries.into_iter().enumerate().collect_vec();
+        let suggestion_db = model::SuggestionDatabase::new_from_entries(
+            entries.iter().map(|(id, entry)| (id, entry)),
+        );
+        let main_code = "import local.Project.Foo\nfrom local.Project.Extensions import shout\n\
+                         main = \"a\".shout";
+        let main = mock_module(&parser, &urm, "Main", main_code, false);
+        let foo = mock_module(&parser, &urm, "Foo", "foo = 1", false);
+        let extensions = mock_module(&parser, &urm, "Extensions", "Text.shout self = self", false);
+
+        let mut project = model::project::MockAPI::new();
+        for module in [&main, &foo, &extensions] {
+            model::project::test::expect_module(&mut project, module.clone_ref());
+        }
+        model::project::test::expect_parser(&mut project, &parser);
+        model::project::test::expect_root_id(&mut project, Uuid::default());
+        let project_name = project::QualifiedName::from_text("local.Project").unwrap();
+        model::project::test::expect_qualified_name(&mut project, &project_name);
+        model::project::test::expect_suggestion_db(&mut project, Rc::new(suggestion_db));
+        project.expect_urm().returning_st(move || urm.clone_ref());
+        let project_controller = controller::Project::new(Rc::new(project), default());
+
+        TestWithLocalPoolExecutor::set_up().run_task(async move {
+            let report = project_controller.analyze_imports().await.unwrap();
+            assert!(report.cycles.is_empty());
+            // The unused import of the module defining extension methods is kept.
+            let reported_modules = report.modules.iter().map(|m| m.module.to_string());
+            assert_eq!(reported_modules.collect_vec(), vec!["local.Project.Main"]);
+            let issues = &report.modules[0].issues;
+            let issues = issues.iter().map(|i| (i.line_index, i.issue.clone())).collect_vec();
+            assert_eq!(issues, vec![(0, import_analysis::Issue::Unused)]);
+
+            project_controller.clean_up_imports(&report.modules[0]).await.unwrap();
+            let expected = "from local.Project.Extensions import shout\nmain = \"a\".shout";
+            assert_eq!(main.ast().repr(), expected);
+            let report = project_controller.analyze_imports().await.unwrap();
+            assert!(report.modules.is_empty());
+        });
+    }
 
     // === Project Snapshotting ===
 
@@ -333,4 +1269,158 @@ mod tests {
             assert_eq!(vcs.commit_count.get(), 3);
         });
     }
+
+    #[wasm_bindgen_test]
+    fn listing_snapshots() {
+        let timestamp = UTCDateTime::parse_from_rfc3339("2023-01-20T10:15:30.125Z").unwrap();
+        let save = |commit_id: &str, message: String| language_server::response::SaveVcs {
+            commit_id: commit_id.into(),
+            message,
+        };
+        let saves = vec![
+            save("c3", Snapshot::message("Before refactoring", &timestamp)),
+            save("c2", "2023-01-20T10:15:30.125Z".into()),
+            save("c1", "Initial commit".into()),
+        ];
+        let json_client = language_server::MockClient::default();
+        json_client.expect.list_vcs(move |path, limit| {
+            assert_eq!(path, &Path::new_root(Uuid::default()));
+            assert_eq!(limit, &Some(3));
+            Ok(language_server::response::ListVcs { saves })
+        });
+        let ls = language_server::Connection::new_mock_rc(json_client);
+        let mut project = model::project::MockAPI::new();
+        model::project::test::expect_root_id(&mut project, Uuid::default());
+        project.expect_json_rpc().returning_st(move || ls.clone_ref());
+        let project_controller = controller::Project::new(Rc::new(project), default());
+
+        TestWithLocalPoolExecutor::set_up().run_task(async move {
+            let snapshots = project_controller.snapshots(Some(3)).await.unwrap();
+            let snapshot = |commit_id: &str, name: Option<&str>, timestamp| Snapshot {
+                commit_id: commit_id.into(),
+                name: name.map(Into::into),
+                timestamp,
+            };
+            let expected = vec![
+                snapshot("c3", Some("Before refactoring"), Some(timestamp)),
+                snapshot("c2", None, Some(timestamp)),
+                snapshot("c1", Some("Initial commit"), None),
+            ];
+            assert_eq!(snapshots, expected);
+        });
+    }
+
+    #[wasm_bindgen_test]
+    fn saving_restoring_and_comparing_named_snapshots() {
+        let parser = parser::Parser::new();
+        let urm = Rc::new(model::undo_redo::Manager::new());
+        let main = mock_module(&parser, &urm, "Main", "main =\n    a = 1", false);
+        let saved_content = main.serialized_content().unwrap().content;
+        let main_path = main.path().clone_ref();
+        let contents_path = Path::new(Uuid::default(), [".enso", "snapshots", "c1.json"]);
+        let stored_contents: Rc<RefCell<Option<String>>> = default();
+        let project_name = project::QualifiedName::from_text("local.Project").unwrap();
+        let main_name = QualifiedName::from_text("local.Project.Main").unwrap();
+        let entry = model::suggestion_database::Entry::new_module(main_name);
+        let suggestion_db = Rc::new(model::SuggestionDatabase::new_from_entries([(&0, &entry)]));
+        let mock_project = |json_client: language_server::MockClient| {
+            let mut project = model::project::MockAPI::new();
+            model::project::test::expect_module(&mut project, main.clone_ref());
+            model::project::test::expect_parser(&mut project, &parser);
+            model::project::test::expect_root_id(&mut project, Uuid::default());
+            model::project::test::expect_qualified_name(&mut project, &project_name);
+            model::project::test::expect_suggestion_db(&mut project, suggestion_db.clone_ref());
+            let ls = language_server::Connection::new_mock_rc(json_client);
+            model::project::test::expect_json_rpc(&mut project, ls);
+            controller::Project::new(Rc::new(project), default())
+        };
+
+        // The modules' content is read before saving the snapshot, and stored alongside it.
+        let json_client = language_server::MockClient::default();
+        let file_path = main_path.file_path().clone();
+        json_client.expect.read_file(move |path| {
+            assert_eq!(path, &file_path);
+            Ok(response::Read { contents: saved_content })
+        });
+        json_client.expect.save_vcs(|_, message| {
+            let message = message.clone().unwrap();
+            assert!(message.ends_with(" Before edit"));
+            Ok(response::SaveVcs { commit_id: "c1".into(), message })
+        });
+        let expected_path = contents_path.clone();
+        let contents = stored_contents.clone_ref();
+        json_client.expect.write_file(move |path, written| {
+            assert_eq!(path, &expected_path);
+            *contents.borrow_mut() = Some(written.clone());
+            Ok(())
+        });
+        let project_controller = mock_project(json_client);
+        let snapshot = TestWithLocalPoolExecutor::set_up().expect_completion(async {
+            project_controller.save_named_snapshot("Before edit").await.unwrap()
+        });
+        assert_eq!(snapshot.name.as_deref(), Some("Before edit"));
+        let snapshot_to_restore = snapshot.clone();
+        let stored_contents = stored_contents.borrow().clone().unwrap();
+
+        // The stored content of the snapshots is not reverted by restoring the snapshot.
+        let json_client = language_server::MockClient::default();
+        let directory = Path::new(Uuid::default(), [".enso", "snapshots"]);
+        let expected_directory = directory.clone();
+        json_client.expect.file_exists(move |path| {
+            assert_eq!(path, &expected_directory);
+            Ok(response::FileExists { exists: true })
+        });
+        json_client.expect.file_list(move |path| {
+            let name = "c1.json".into();
+            Ok(response::FileList {
+                paths: vec![FileSystemObject::File { name, path: path.clone() }],
+            })
+        });
+        let (expected_path, contents) = (contents_path.clone(), stored_contents.clone());
+        json_client.expect.read_file(move |path| {
+            assert_eq!(path, &expected_path);
+            Ok(response::Read { contents })
+        });
+        json_client.expect.restore_vcs(|_, commit_id| {
+            assert_eq!(commit_id.as_deref(), Some("c1"));
+            Ok(response::RestoreVcs { changed: vec![] })
+        });
+        let (expected_path, expected_contents) = (contents_path.clone(), stored_contents.clone());
+        json_client.expect.write_file(move |path, written| {
+            assert_eq!(path, &expected_path);
+            assert_eq!(written, &expected_contents);
+            Ok(())
+        });
+        json_client.require_all_calls();
+        let project_controller = mock_project(json_client);
+        TestWithLocalPoolExecutor::set_up().run_task(async move {
+            project_controller.restore_snapshot(&snapshot_to_restore).await.unwrap();
+        });
+
+        let end = main.ast().repr().len().byte();
+        let change = TextChange { range: (end..end).into(), text: "\n    b = 2".into() };
+        main.apply_code_change(change, &parser, main.ast().id_map()).unwrap();
+
+        // The snapshot saved in another session is compared using the stored content.
+        let json_client = language_server::MockClient::default();
+        let expected_path = contents_path.clone();
+        json_client.expect.file_exists(move |path| {
+            assert_eq!(path, &expected_path);
+            Ok(response::FileExists { exists: true })
+        });
+        json_client.expect.read_file(move |path| {
+            assert_eq!(path, &contents_path);
+            Ok(response::Read { contents: stored_contents })
+        });
+        let project_controller = mock_project(json_client);
+        TestWithLocalPoolExecutor::set_up().run_task(async move {
+            let diffs =
+                project_controller.snapshot_diff(&main_path, &snapshot, None).await.unwrap();
+            assert_eq!(diffs.len(), 1);
+            assert_eq!(diffs[0].graph, double_representation::graph::Id::new_plain_name("main"));
+            assert_eq!(diffs[0].added.len(), 1);
+            assert!(diffs[0].removed.is_empty());
+            assert!(diffs[0].changed.is_empty());
+        });
+    }
 }
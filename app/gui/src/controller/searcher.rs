@@ -7,6 +7,7 @@ use crate::controller::graph::ImportType;
 use crate::controller::graph::RequiredImport;
 use crate::controller::searcher::breadcrumbs::BreadcrumbEntry;
 use crate::model::execution_context::GroupQualifiedName;
+use crate::model::module::usage;
 use crate::model::suggestion_database;
 use crate::presenter::searcher;
 
@@ -76,6 +77,11 @@ pub struct CannotCommitExpression {
     mode: Mode,
 }
 
+#[allow(missing_docs)]
+#[derive(Copy, Clone, Debug, Fail)]
+#[fail(display = "The project's main module is not loaded yet.")]
+pub struct MainModuleNotLoaded;
+
 
 
 // =====================
@@ -209,6 +215,9 @@ pub struct Data {
     /// All picked suggestions. If the user changes the generated code, it will be removed from
     /// this list.
     pub picked_suggestions: Vec<PickedSuggestion>,
+    /// The context of the current component list, in which the picked suggestions are recorded
+    /// as accepted.
+    pub usage_context:      usage::Context,
 }
 
 impl Data {
@@ -229,7 +238,8 @@ impl Data {
         let input = input::Input::new(input_ast, cursor_position);
         let components = default();
         let picked_suggestions = default();
-        Ok(Data { input, components, picked_suggestions })
+        let usage_context = default();
+        Ok(Data { input, components, picked_suggestions, usage_context })
     }
 }
 
@@ -266,6 +276,9 @@ pub struct Searcher {
     this_arg:         Rc<Option<ThisNode>>,
     position_in_code: Immutable<Location<Byte>>,
     project:          model::Project,
+    /// The project's main module, whose metadata keep the statistics of accepted components. It
+    /// is `None` until loaded.
+    main_module:      Rc<RefCell<Option<model::Module>>>,
 }
 
 impl Searcher {
@@ -302,20 +315,57 @@ impl Searcher {
             language_server: project.json_rpc(),
             position_in_code: Immutable(position_in_code),
             project,
+            main_module: default(),
         };
         Ok(ret.init())
     }
 
     fn init(self) -> Self {
+        self.load_main_module();
         self.reload_list();
         self
     }
 
+    /// Load the project's main module. If the edited graph is in the main module, it is available
+    /// immediately.
+    fn load_main_module(&self) {
+        let edited_module = self.graph.graph().module;
+        if edited_module.path() == &self.project.main_module_path() {
+            *self.main_module.borrow_mut() = Some(edited_module);
+        } else {
+            let project = self.project.clone_ref();
+            let main_module = self.main_module.clone_ref();
+            executor::global::spawn(async move {
+                match project.main_module_model().await {
+                    Ok(module) => *main_module.borrow_mut() = Some(module),
+                    Err(error) => error!("Failed to load the main module: {error}"),
+                }
+            });
+        }
+    }
+
+    /// The project's main module, if already loaded.
+    fn main_module(&self) -> FallibleResult<model::Module> {
+        self.main_module.borrow().clone().ok_or_else(|| MainModuleNotLoaded.into())
+    }
+
     /// Dump the suggestion database to the console in JSON format.
     pub fn dump_database_as_json(&self) {
         console_log!("{}", self.database.dump_as_json());
     }
 
+    /// Forget the statistics of accepted components, so the components are no longer ranked by
+    /// their usage.
+    pub fn reset_usage_statistics(&self) {
+        let result = self.main_module().and_then(|module| {
+            module.update_project_metadata(|metadata| metadata.component_usage.reset())
+        });
+        if let Err(error) = result {
+            error!("Failed to reset the component usage statistics: {error}");
+        }
+        self.reload_list();
+    }
+
     /// Abort editing and perform cleanup.
     pub fn abort_editing(&self) {
         self.clear_temporary_imports();
@@ -590,9 +640,42 @@ impl Searcher {
         if let Some(this) = self.this_arg.deref().as_ref() {
             this.introduce_pattern(graph.clone_ref())?;
         }
+        self.record_usage();
         Ok(node_id)
     }
 
+    /// Record the picked suggestions as accepted by the user. The statistics are updated in the
+    /// transaction of committing the node, so undoing the commit reverts them as well.
+    fn record_usage(&self) {
+        let (keys, context) = self.data.with_borrowed(|data| {
+            let keys = data.picked_suggestions.iter().map(|picked| picked.suggestion.usage_key());
+            (keys.collect_vec(), data.usage_context.clone())
+        });
+        if keys.is_empty() {
+            return;
+        }
+        let result = self.main_module().and_then(|module| {
+            module.update_project_metadata(|metadata| {
+                for key in keys {
+                    metadata.component_usage.record(key, &context);
+                }
+            })
+        });
+        if let Err(error) = result {
+            error!("Failed to record the component usage: {error}");
+        }
+    }
+
+    /// The statistics of accepted components. See [`usage`] module docs. The statistics are empty
+    /// until the main module is loaded.
+    fn usage_statistics(&self) -> usage::Statistics {
+        let module = self.main_module.borrow().clone();
+        let statistics = module.map(|module| {
+            module.with_project_metadata(|metadata| metadata.component_usage.clone())
+        });
+        statistics.unwrap_or_default()
+    }
+
     fn get_expression(&self, input: Ast) -> Ast {
         match self.this_var() {
             Some(this_var) => searcher::apply_this_argument(this_var, &input),
@@ -668,19 +751,21 @@ impl Searcher {
             let file = graph.module.path().file_path();
             let response =
                 ls.completion(file, &position, &this_type, &None, &tags, &is_static).await;
+            let usage_context = this.usage_context(&this_type);
             let new_list = match response {
                 Ok(response) => {
                     info!("Received suggestions from Language Server.");
                     let completions = response.results;
-                    this.make_component_list(completions, &this_type)
+                    this.make_component_list(completions, &this_type, &usage_context)
                 }
                 Err(err) => {
                     let msg = "Request for completions to the Language Server returned error";
                     error!("{msg}: {err}");
-                    this.make_component_list(this.database.keys(), &this_type)
+                    this.make_component_list(this.database.keys(), &this_type, &usage_context)
                 }
             };
             this.data.borrow_mut().components = new_list;
+            this.data.borrow_mut().usage_context = usage_context;
             this.notifier.publish(Notification::NewComponentList).await;
         });
     }
@@ -690,6 +775,7 @@ impl Searcher {
         &self,
         entry_ids: impl IntoIterator<Item = suggestion_database::entry::Id>,
         this_type: &Option<String>,
+        usage_context: &usage::Context,
     ) -> Rc<component::List> {
         let db = &*self.database;
         let groups = self.graph.component_groups();
@@ -703,6 +789,7 @@ impl Searcher {
             }
         };
 
+        builder.set_usage_scores(self.usage_statistics().scores(usage_context));
         builder.add_components_from_db(entry_ids);
         let mut list = builder.build();
         list.update_filtering(self.filter());
@@ -728,6 +815,11 @@ impl Searcher {
         self.location_to_utf16(location)
     }
 
+    fn usage_context(&self, this_type: &Option<String>) -> usage::Context {
+        let module = self.module_qualified_name().to_string();
+        usage::Context::new(this_type.clone(), Some(module))
+    }
+
     fn module_qualified_name(&self) -> QualifiedName {
         self.graph.module_qualified_name_with_project(&*self.project)
     }
@@ -885,6 +977,8 @@ pub mod test {
                 .returning_st(move || Err(ProjectOperationsNotSupported.into()));
             ide.expect_are_component_browser_private_entries_visible().returning_st(|| false);
             let breadcrumbs = Breadcrumbs::new();
+            // The edited module is the project's main module, unless a test replaces it.
+            let main_module = Rc::new(RefCell::new(Some(graph.graph().module)));
             let searcher = Searcher {
                 graph,
                 database: database.clone_ref(),
@@ -897,6 +991,7 @@ pub mod test {
                 this_arg: Rc::new(this),
                 position_in_code: Immutable(code.last_line_end_location()),
                 project: project.clone_ref(),
+                main_module,
             };
             Fixture { data, test, searcher, database }
         }
@@ -1346,6 +1441,12 @@ pub mod test {
         let expected_code =
             "import test.Test.Test\nmain =\n    2 + 2\n    operator1 = Test.test_method";
         assert_eq!(module.ast().repr(), expected_code);
+        // The picked suggestion is recorded as accepted.
+        let usage = || module.with_project_metadata(|m| m.component_usage.records().to_vec());
+        let records = usage();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].suggestion, "test.Test.Test.test_method3");
+        assert_eq!(records[0].count, 1);
 
         // Edit existing node.
         let mut tmp_node_info = NewNodeInfo::new_pushed_back("Nothing");
@@ -1360,6 +1461,50 @@ pub mod test {
         let expected_code =
             "import test.Test.Test\nmain =\n    Test.test_method\n    operator1 = Test.test_method";
         assert_eq!(module.ast().repr(), expected_code);
+        assert_eq!(usage()[0].count, 2);
+    }
+
+    #[test]
+    fn usage_is_recorded_in_main_module() {
+        let mut fixture =
+            Fixture::new_custom(suggestion_database_with_mock_entries, |data, client| {
+                data.change_main_body(&["2 + 2", "Nothing"]);
+                // Resetting the statistics reloads the component list.
+                data.expect_completion(client, None, &[]);
+            });
+        let test_method_3 = fixture.test_method_3_suggestion();
+        let searcher = &mut fixture.searcher;
+        let edited_module = searcher.graph.graph().module.clone_ref();
+        let main_module: model::Module = {
+            let path = model::module::Path::from_mock_module_name("Main");
+            let ast = Parser::new().parse_module("main = 1", default()).unwrap();
+            let repository = model::undo_redo::Repository::new();
+            Rc::new(model::module::Plain::new(path, ast, default(), Rc::new(repository), default()))
+        };
+        *searcher.main_module.borrow_mut() = Some(main_module.clone_ref());
+        let import = test_method_3.required_import();
+        let picked_method = PickedSuggestion {
+            suggestion: test_method_3,
+            inserted_code: "Test.test_method".into(),
+            import,
+        };
+        with(searcher.data.borrow_mut(), |mut data| {
+            data.picked_suggestions.push(picked_method);
+            data.input = input::Input::parse(&Parser::new(), "Test.test_method".into(), Byte(16));
+        });
+        searcher.commit_node().unwrap();
+
+        let usage = |module: &model::Module| {
+            module.with_project_metadata(|m| m.component_usage.records().to_vec())
+        };
+        let records = usage(&main_module);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].suggestion, "test.Test.Test.test_method3");
+        assert!(usage(&edited_module).is_empty());
+        assert_eq!(searcher.usage_statistics().records().len(), 1);
+
+        searcher.reset_usage_statistics();
+        assert!(searcher.usage_statistics().records().is_empty());
     }
 
     /// Test recognition of qualified names in the searcher's input.
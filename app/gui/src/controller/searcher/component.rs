@@ -9,6 +9,7 @@ use crate::controller::searcher::search;
 use crate::controller::searcher::search::search;
 use crate::controller::searcher::Filter;
 use crate::model::execution_context::GroupQualifiedName;
+use crate::model::module::usage;
 
 use enso_doc_parser::DocSection;
 use enso_doc_parser::Tag;
@@ -104,6 +105,15 @@ impl Suggestion {
             Self::Virtual { .. } => None,
         }
     }
+
+    /// The key identifying the suggestion in the [usage statistics](usage::Statistics): the
+    /// qualified name of the database entry, or the name of the virtual component.
+    pub fn usage_key(&self) -> String {
+        match self {
+            Self::FromDatabase { entry, .. } => entry.qualified_name().to_string(),
+            Self::Virtual { snippet } => snippet.name.to_string(),
+        }
+    }
 }
 
 
@@ -123,6 +133,8 @@ pub struct Component {
     aliases:        Rc<[ImString]>,
     /// Results of matching this component against the current filter, if any.
     match_info:     Option<MatchInfo>,
+    /// How often and how recently the component was accepted in the current context.
+    usage:          usage::Score,
 }
 
 impl Component {
@@ -136,7 +148,7 @@ impl Component {
         let aliases =
             entry.aliases().map(|alias| format!("{alias} ({label})").into()).collect_vec().into();
         let data = Suggestion::FromDatabase { id, entry };
-        Self { suggestion: data, label, aliases, group_id, match_info: default() }
+        Self { suggestion: data, label, aliases, group_id, match_info: default(), usage: default() }
     }
 
     /// Construct a new component without any associated [`suggestion_database`] entry.
@@ -147,6 +159,7 @@ impl Component {
             suggestion: Suggestion::Virtual { snippet },
             group_id:   Some(group_index),
             match_info: Default::default(),
+            usage:      default(),
         }
     }
 
@@ -175,6 +188,11 @@ impl Component {
         }
     }
 
+    /// The usage score of the component in the context the list was built for.
+    pub fn usage(&self) -> usage::Score {
+        self.usage
+    }
+
     /// Checks if component is filtered out.
     pub fn is_filtered_out(&self) -> bool {
         matches!(self.match_info, Some(MatchInfo::DoesNotMatch))
@@ -306,7 +324,8 @@ impl List {
     /// Update list filtering.
     ///
     /// If the filtering pattern is not empty, the components will be sorted by match score (best
-    /// match first), and [`Self::displayed`] will return only matched entries. Otherwise
+    /// match first, then the most used one), and [`Self::displayed`] will return only matched
+    /// entries. Otherwise
     /// [`Self::displayed`] will return a "default" view, which depend on the context - see
     /// [structure docs](List) for details.
     pub fn update_filtering(&mut self, filter: Filter) {
@@ -316,17 +335,18 @@ impl List {
             for component in &mut self.components {
                 component.update_matching_info(filter.clone_ref());
             }
-            self.components
-                .sort_by(|lhs, rhs| Self::entry_match_ordering(&lhs.match_info, &rhs.match_info));
+            self.components.sort_by(Self::entry_match_ordering);
             let first_non_matching =
                 self.components.lower_bound_by_key(&true, |entry| entry.is_filtered_out());
             self.filtered_in = Some(..first_non_matching);
         }
     }
 
-    /// Return the entry match ordering when sorting by match. See [`component::Order::ByMatch`].
-    fn entry_match_ordering(lhs: &Option<MatchInfo>, rhs: &Option<MatchInfo>) -> cmp::Ordering {
-        lhs.cmp(rhs).reverse()
+    /// Return the entry match ordering when sorting by match. The components matching equally
+    /// well are ordered by their usage score.
+    fn entry_match_ordering(lhs: &Component, rhs: &Component) -> cmp::Ordering {
+        let match_ordering = lhs.match_info.cmp(&rhs.match_info);
+        match_ordering.then_with(|| lhs.usage.cmp(&rhs.usage)).reverse()
     }
 }
 
@@ -446,6 +466,68 @@ pub(crate) mod tests {
         ]);
     }
 
+    #[test]
+    fn ordering_by_usage() {
+        let db = mock_suggestion_database! {
+            local.Project {
+                type Json {
+                    #[in_group("Data")]
+                    parse();
+                }
+                type Date {
+                    #[in_group("Data")]
+                    parse();
+                }
+                type Time {
+                    #[in_group("Data")]
+                    parse();
+                }
+                type DateTime {
+                    #[in_group("Data")]
+                    parse();
+                }
+            }
+        };
+        let project_name = project::QualifiedName::from_text("local.Project").unwrap();
+        let component_groups = &[ComponentGroup {
+            name:       GroupQualifiedName::new(project_name, "Data"),
+            color:      None,
+            components: default(),
+        }];
+        let context = usage::Context::new(None::<String>, Some("local.Project.Main"));
+        let mut statistics = usage::Statistics::default();
+        statistics.record("local.Project.Json.parse", &context);
+        statistics.record("local.Project.Time.parse", &context);
+
+        let mut builder = Builder::new(&db, component_groups);
+        builder.set_usage_scores(statistics.scores(&context));
+        builder.add_components_from_db(db.keys());
+        let mut list = builder.build();
+
+        // The most recently used goes first; the unused components keep their order.
+        let expected = vec!["Time.parse", "Json.parse", "Date.parse", "DateTime.parse"];
+        check_displayed_components(&list, expected.clone());
+        list.update_filtering(make_filter("parse"));
+        check_displayed_components(&list, expected);
+        list.update_filtering(make_filter("date"));
+        check_displayed_components(&list, vec!["Date.parse", "DateTime.parse"]);
+        // Usage decides only between equally good matches.
+        statistics.record("local.Project.DateTime.parse", &context);
+        let mut builder = Builder::new(&db, component_groups);
+        builder.set_usage_scores(statistics.scores(&context));
+        builder.add_components_from_db(db.keys());
+        let mut list = builder.build();
+        list.update_filtering(make_filter("date"));
+        check_displayed_components(&list, vec!["Date.parse", "DateTime.parse"]);
+        list.update_filtering(make_filter("parse"));
+        check_displayed_components(&list, vec![
+            "DateTime.parse",
+            "Time.parse",
+            "Json.parse",
+            "Date.parse",
+        ]);
+    }
+
     #[test]
     fn alias_matching() {
         let db = mock_suggestion_database! {
@@ -8,6 +8,7 @@ use crate::controller::searcher::component::hardcoded;
 use crate::controller::searcher::component::Component;
 use crate::model::execution_context;
 use crate::model::execution_context::GroupQualifiedName;
+use crate::model::module::usage;
 use crate::model::suggestion_database;
 
 use double_representation::name::project::STANDARD_NAMESPACE;
@@ -16,6 +17,7 @@ use double_representation::name::QualifiedNameRef;
 use enso_doc_parser::DocSection;
 use enso_doc_parser::Tag;
 use enso_suggestion_database::SuggestionDatabase;
+use std::cmp::Reverse;
 
 
 
@@ -49,28 +51,42 @@ impl<'a> InGroupComponentOrderingKey<'a> {
 ///
 /// Used in `sort_by_key` kind of methods, it allows expressing groups order in much simpler way
 /// than chains of `cmp` calls. The `Ord` and `PartialOrd` are derived from the variants/fields
-/// order.
+/// order. Inside a group or module, the most used components go first.
 #[allow(missing_docs)]
 #[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
 pub enum ComponentOrderingKey<'a> {
-    InGroup { group_index: usize, key: InGroupComponentOrderingKey<'a> },
-    ModuleContent { key: InGroupComponentOrderingKey<'a> },
-    Module { non_standard: bool, module: QualifiedNameRef<'a> },
+    InGroup {
+        group_index: usize,
+        usage:       Reverse<usage::Score>,
+        key:         InGroupComponentOrderingKey<'a>,
+    },
+    ModuleContent {
+        usage: Reverse<usage::Score>,
+        key:   InGroupComponentOrderingKey<'a>,
+    },
+    Module {
+        non_standard: bool,
+        module:       QualifiedNameRef<'a>,
+    },
 }
 
 impl<'a> ComponentOrderingKey<'a> {
     fn of(component: &'a Component) -> Self {
         use suggestion_database::entry::Kind;
+        let usage = Reverse(component.usage);
         match component.group_id {
-            Some(group_index) =>
-                Self::InGroup { group_index, key: InGroupComponentOrderingKey::of(component) },
+            Some(group_index) => Self::InGroup {
+                group_index,
+                usage,
+                key: InGroupComponentOrderingKey::of(component),
+            },
             None => match &component.suggestion {
                 component::Suggestion::FromDatabase { entry, .. } if entry.kind == Kind::Module =>
                     Self::Module {
                         non_standard: entry.defined_in.project().namespace != STANDARD_NAMESPACE,
                         module:       entry.defined_in.as_ref(),
                     },
-                _ => Self::ModuleContent { key: InGroupComponentOrderingKey::of(component) },
+                _ => Self::ModuleContent { usage, key: InGroupComponentOrderingKey::of(component) },
             },
         }
     }
@@ -106,7 +122,8 @@ struct EntryInGroup {
 ///   will be displayed, as the filtering out non-matching types is currently done on the Engine
 ///   side.
 ///
-/// The order of components, when not filtered, is specified by [`ComponentOrderingKey`].
+/// The order of components, when not filtered, is specified by [`ComponentOrderingKey`]. The usage
+/// scores of the components may be given with [`Self::set_usage_scores`].
 #[derive(Clone, Debug)]
 pub struct Builder<'a> {
     db:                 &'a SuggestionDatabase,
@@ -116,6 +133,7 @@ pub struct Builder<'a> {
     /// A mapping from entry id to group index and the cached suggestion database entry.
     entry_to_group_map: HashMap<suggestion_database::entry::Id, EntryInGroup>,
     group_name_to_id:   HashMap<GroupQualifiedName, usize>,
+    usage_scores:       usage::Scores,
 }
 
 impl<'a> Builder<'a> {
@@ -128,6 +146,7 @@ impl<'a> Builder<'a> {
             built_list: default(),
             entry_to_group_map: default(),
             group_name_to_id: default(),
+            usage_scores: default(),
         }
     }
 
@@ -150,6 +169,7 @@ impl<'a> Builder<'a> {
             built_list: component::List { groups: groups.collect(), ..default() },
             entry_to_group_map: entry_to_group_entries.collect(),
             group_name_to_id,
+            usage_scores: default(),
         }
     }
 
@@ -194,8 +214,18 @@ impl<'a> Builder<'a> {
         Self { inside_module: module_qn, ..Self::new(db, groups) }
     }
 
+    /// Set the usage scores of the components, affecting their order. See [`usage::Statistics`].
+    pub fn set_usage_scores(&mut self, scores: usage::Scores) {
+        self.usage_scores = scores;
+    }
+
     /// Return the built list.
     pub fn build(mut self) -> component::List {
+        let list = &mut self.built_list;
+        for component in list.components.iter_mut().chain(&mut list.displayed_by_default) {
+            let key = component.suggestion.usage_key();
+            component.usage = self.usage_scores.get(&key).copied().unwrap_or_default();
+        }
         self.built_list
             .displayed_by_default
             .sort_by(|lhs, rhs| ComponentOrderingKey::of(lhs).cmp(&ComponentOrderingKey::of(rhs)));
@@ -80,6 +80,7 @@ impl Initializer {
         let pixel_read_period = enso_config::ARGS.groups.debug.options.pixel_read_period.value;
         ensogl_app.display.set_pixel_read_period(pixel_read_period as usize);
         register_views(&ensogl_app);
+        ensogl_app.shortcuts.report_conflicts();
         let view = ensogl_app.new_view::<ide_view::root::View>();
 
         // IDE was opened with `project` argument, we should skip the Welcome Screen.
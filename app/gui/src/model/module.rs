@@ -27,6 +27,7 @@ use serde::Serialize;
 
 pub mod plain;
 pub mod synchronized;
+pub mod usage;
 
 pub use double_representation::module::Id;
 
@@ -46,6 +47,16 @@ pub struct NodeMetadataNotFound(pub ast::Id);
 #[fail(display = "Import with ID {} was not found in metadata.", _0)]
 pub struct ImportMetadataNotFound(pub import::Id);
 
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Copy, Fail)]
+#[fail(display = "Node group with ID {} was not found in metadata.", _0)]
+pub struct GroupMetadataNotFound(pub GroupId);
+
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Copy, Fail)]
+#[fail(display = "Comment with ID {} was not found in metadata.", _0)]
+pub struct CommentMetadataNotFound(pub CommentId);
+
 /// Failed attempt to tread a file path as a module path.
 #[derive(Clone, Debug, Fail)]
 #[fail(display = "The path `{}` is not a valid module path. {}", path, issue)]
@@ -362,7 +373,11 @@ impl Default for Metadata {
 pub struct ProjectMetadata {
     /// The execution context of the displayed graph editor.
     #[serde(default, deserialize_with = "enso_prelude::deserialize_or_default")]
-    pub call_stack: Vec<model::execution_context::LocalCall>,
+    pub call_stack:      Vec<model::execution_context::LocalCall>,
+    /// The statistics of the components accepted in the Component Browser, used for ranking.
+    #[serde(default, skip_serializing_if = "usage::Statistics::is_empty")]
+    #[serde(deserialize_with = "enso_prelude::deserialize_or_default")]
+    pub component_usage: usage::Statistics,
 }
 
 /// Metadata that belongs to ide.
@@ -376,12 +391,24 @@ pub struct IdeMetadata {
     /// The project metadata. This is stored only in the main module's metadata.
     #[serde(default, deserialize_with = "enso_prelude::deserialize_or_default")]
     project: Option<ProjectMetadata>,
+    /// Metadata of node groups in the module's graphs.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    #[serde(deserialize_with = "enso_prelude::deserialize_or_default")]
+    group:   HashMap<GroupId, GroupMetadata>,
+    /// Metadata of comments in the module's graphs.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    #[serde(deserialize_with = "enso_prelude::deserialize_or_default")]
+    comment: HashMap<CommentId, CommentMetadata>,
 }
 
 impl PruneUnusedIds for IdeMetadata {
     fn prune_unused_ids(&mut self, id_map: &ast::IdMap) {
         let used_ids: HashSet<_> = id_map.vec.iter().map(|(_, id)| *id).collect();
         self.node.retain(|id, _| used_ids.contains(id));
+        // A group exists as long as it has any member nodes.
+        let used_groups: HashSet<_> = self.node.values().filter_map(|node| node.group).collect();
+        self.group.retain(|id, _| used_groups.contains(id));
+        self.comment.retain(|_, comment| used_ids.contains(&comment.definition));
     }
 }
 
@@ -430,6 +457,10 @@ pub struct NodeMetadata {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default, deserialize_with = "enso_prelude::deserialize_or_default")]
     pub edit_status:     Option<NodeEditStatus>,
+    /// The group the node belongs to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, deserialize_with = "enso_prelude::deserialize_or_default")]
+    pub group:           Option<GroupId>,
 }
 
 /// Used for storing node position.
@@ -530,6 +561,49 @@ pub struct ImportMetadata {
     pub is_temporary: bool,
 }
 
+/// Identifier of a node group.
+pub type GroupId = Uuid;
+
+/// Identifier of a comment.
+pub type CommentId = Uuid;
+
+/// Metadata of a node group: a labelled frame drawn around its member nodes.
+///
+/// The nodes refer to their group in [`NodeMetadata::group`], so the frame follows them when they
+/// move, and the membership is copied along with the nodes' metadata.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct GroupMetadata {
+    /// The graph displaying the group, identified by the textual form of its
+    /// [`double_representation::graph::Id`]. The key is not updated when the graph's method is
+    /// renamed, so the groups are also found by their member nodes.
+    #[serde(default, deserialize_with = "enso_prelude::deserialize_or_default")]
+    pub graph: String,
+    /// The label displayed above the frame.
+    #[serde(default, deserialize_with = "enso_prelude::deserialize_or_default")]
+    pub label: String,
+    /// The frame's color as a CSS hex string, like `#4a9bd8`. The default color is used if not
+    /// set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, deserialize_with = "enso_prelude::deserialize_or_default")]
+    pub color: Option<String>,
+}
+
+/// Metadata of a comment: a note placed freely in a graph.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct CommentMetadata {
+    /// The graph displaying the comment, identified by the ID of its definition's AST. The ID is
+    /// kept when the definition is edited or its method is renamed, so the comment stays in the
+    /// graph. The comment is removed along with the definition.
+    #[serde(default, deserialize_with = "enso_prelude::deserialize_or_default")]
+    pub definition: ast::Id,
+    /// The comment's text.
+    #[serde(default, deserialize_with = "enso_prelude::deserialize_or_default")]
+    pub text:       String,
+    /// The comment's position in the graph's scene.
+    #[serde(default, deserialize_with = "enso_prelude::deserialize_or_default")]
+    pub position:   Position,
+}
+
 
 // ==============
 // === Module ===
@@ -618,6 +692,36 @@ pub trait API: Debug + model::undo_redo::Aware {
     /// Removes the import metadata of the import.
     fn remove_import_metadata(&self, id: import::Id) -> FallibleResult<ImportMetadata>;
 
+    /// Modify metadata of given node group.
+    ///
+    /// If the group doesn't have metadata, empty (default) metadata is inserted.
+    fn with_group_metadata(
+        &self,
+        id: GroupId,
+        fun: Box<dyn FnOnce(&mut GroupMetadata) + '_>,
+    ) -> FallibleResult;
+
+    /// Returns the metadata of all node groups in the module.
+    fn all_group_metadata(&self) -> Vec<(GroupId, GroupMetadata)>;
+
+    /// Removes the metadata of given node group. The member nodes are not modified.
+    fn remove_group_metadata(&self, id: GroupId) -> FallibleResult<GroupMetadata>;
+
+    /// Modify metadata of given comment.
+    ///
+    /// If the comment doesn't have metadata, empty (default) metadata is inserted.
+    fn with_comment_metadata(
+        &self,
+        id: CommentId,
+        fun: Box<dyn FnOnce(&mut CommentMetadata) + '_>,
+    ) -> FallibleResult;
+
+    /// Returns the metadata of all comments in the module.
+    fn all_comment_metadata(&self) -> Vec<(CommentId, CommentMetadata)>;
+
+    /// Removes the metadata of given comment.
+    fn remove_comment_metadata(&self, id: CommentId) -> FallibleResult<CommentMetadata>;
+
     /// This method exists as a monomorphication for [`with_project_metadata`]. Users are encouraged
     /// to use it rather then this method.
     ///
@@ -814,4 +918,54 @@ main = 5
         assert_eq!(node.position, Some(Position::new(-75.5, 52.0)));
         assert_eq!(file.metadata.rest, serde_json::Value::Object(default()));
     }
+
+    #[test]
+    fn group_and_comment_metadata() {
+        use enso_text::index::*;
+
+        let node = ast::Id::new_v4();
+        let removed_node = ast::Id::new_v4();
+        let group = GroupId::new_v4();
+        let removed_group = GroupId::new_v4();
+        let comment = CommentId::new_v4();
+        let removed_comment = CommentId::new_v4();
+        let definition = ast::Id::new_v4();
+        let graph = "main".to_owned();
+        let mut metadata = Metadata::default();
+        let label = "Inputs".to_owned();
+        let color = Some("#4a9bd8".to_owned());
+        let group_metadata = GroupMetadata { graph: graph.clone(), label, color };
+        let text = "Remember to validate the data.".to_owned();
+        let position = Position::new(10.0, -20.0);
+        let comment_metadata = CommentMetadata { definition, text, position };
+        metadata.ide.group.insert(group, group_metadata.clone());
+        metadata.ide.group.insert(removed_group, group_metadata);
+        metadata.ide.comment.insert(comment, comment_metadata.clone());
+        let removed_definition = ast::Id::new_v4();
+        let removed_comment_metadata =
+            CommentMetadata { definition: removed_definition, ..comment_metadata };
+        metadata.ide.comment.insert(removed_comment, removed_comment_metadata);
+        metadata.ide.node.insert(node, NodeMetadata { group: Some(group), ..default() });
+        let removed_node_metadata = NodeMetadata { group: Some(removed_group), ..default() };
+        metadata.ide.node.insert(removed_node, removed_node_metadata);
+
+        let serialized = serde_json::to_string(&metadata).unwrap();
+        let deserialized: Metadata = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, metadata);
+
+        // The groups without any member nodes are removed along with the nodes, and the comments
+        // are removed along with their graphs' definitions.
+        let mut id_map = ast::IdMap::default();
+        id_map.insert(0.byte()..1.byte(), node);
+        id_map.insert(0.byte()..2.byte(), definition);
+        metadata.prune_unused_ids(&id_map);
+        assert_eq!(metadata.ide.group.keys().collect_vec(), vec![&group]);
+        assert_eq!(metadata.ide.comment.keys().collect_vec(), vec![&comment]);
+
+        // Metadata written before groups and comments were introduced, or broken, is still read.
+        let old = r#"{"ide":{"node":{},"group":5}}"#;
+        let deserialized: Metadata = serde_json::from_str(old).unwrap();
+        assert!(deserialized.ide.group.is_empty());
+        assert!(deserialized.ide.comment.is_empty());
+    }
 }
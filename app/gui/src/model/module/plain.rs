@@ -2,7 +2,13 @@
 
 use crate::prelude::*;
 
+use crate::model::module::CommentId;
+use crate::model::module::CommentMetadata;
+use crate::model::module::CommentMetadataNotFound;
 use crate::model::module::Content;
+use crate::model::module::GroupId;
+use crate::model::module::GroupMetadata;
+use crate::model::module::GroupMetadataNotFound;
 use crate::model::module::ImportMetadata;
 use crate::model::module::ImportMetadataNotFound;
 use crate::model::module::Metadata;
@@ -267,6 +273,56 @@ impl model::module::API for Module {
         })
     }
 
+    fn with_group_metadata(
+        &self,
+        id: GroupId,
+        fun: Box<dyn FnOnce(&mut GroupMetadata) + '_>,
+    ) -> FallibleResult {
+        self.update_content(NotificationKind::MetadataChanged, |content| {
+            let lookup = content.metadata.ide.group.remove(&id);
+            let mut data = lookup.unwrap_or_default();
+            fun(&mut data);
+            content.metadata.ide.group.insert(id, data);
+        })
+    }
+
+    fn all_group_metadata(&self) -> Vec<(GroupId, GroupMetadata)> {
+        let content = self.content.borrow();
+        content.metadata.ide.group.clone().into_iter().collect()
+    }
+
+    fn remove_group_metadata(&self, id: GroupId) -> FallibleResult<GroupMetadata> {
+        self.try_updating_content(NotificationKind::MetadataChanged, |content| {
+            let lookup = content.metadata.ide.group.remove(&id);
+            lookup.ok_or_else(|| GroupMetadataNotFound(id).into())
+        })
+    }
+
+    fn with_comment_metadata(
+        &self,
+        id: CommentId,
+        fun: Box<dyn FnOnce(&mut CommentMetadata) + '_>,
+    ) -> FallibleResult {
+        self.update_content(NotificationKind::MetadataChanged, |content| {
+            let lookup = content.metadata.ide.comment.remove(&id);
+            let mut data = lookup.unwrap_or_default();
+            fun(&mut data);
+            content.metadata.ide.comment.insert(id, data);
+        })
+    }
+
+    fn all_comment_metadata(&self) -> Vec<(CommentId, CommentMetadata)> {
+        let content = self.content.borrow();
+        content.metadata.ide.comment.clone().into_iter().collect()
+    }
+
+    fn remove_comment_metadata(&self, id: CommentId) -> FallibleResult<CommentMetadata> {
+        self.try_updating_content(NotificationKind::MetadataChanged, |content| {
+            let lookup = content.metadata.ide.comment.remove(&id);
+            lookup.ok_or_else(|| CommentMetadataNotFound(id).into())
+        })
+    }
+
 
     fn boxed_with_project_metadata(&self, fun: Box<dyn FnOnce(&ProjectMetadata) + '_>) {
         let content = self.content.borrow();
@@ -3,7 +3,11 @@
 use crate::prelude::*;
 use enso_text::index::*;
 
+use crate::model::module::CommentId;
+use crate::model::module::CommentMetadata;
 use crate::model::module::Content;
+use crate::model::module::GroupId;
+use crate::model::module::GroupMetadata;
 use crate::model::module::ImportMetadata;
 use crate::model::module::NodeMetadata;
 use crate::model::module::Notification;
@@ -354,6 +358,38 @@ impl API for Module {
         self.model.remove_import_metadata(id)
     }
 
+    fn with_group_metadata(
+        &self,
+        id: GroupId,
+        fun: Box<dyn FnOnce(&mut GroupMetadata) + '_>,
+    ) -> FallibleResult {
+        self.model.with_group_metadata(id, fun)
+    }
+
+    fn all_group_metadata(&self) -> Vec<(GroupId, GroupMetadata)> {
+        self.model.all_group_metadata()
+    }
+
+    fn remove_group_metadata(&self, id: GroupId) -> FallibleResult<GroupMetadata> {
+        self.model.remove_group_metadata(id)
+    }
+
+    fn with_comment_metadata(
+        &self,
+        id: CommentId,
+        fun: Box<dyn FnOnce(&mut CommentMetadata) + '_>,
+    ) -> FallibleResult {
+        self.model.with_comment_metadata(id, fun)
+    }
+
+    fn all_comment_metadata(&self) -> Vec<(CommentId, CommentMetadata)> {
+        self.model.all_comment_metadata()
+    }
+
+    fn remove_comment_metadata(&self, id: CommentId) -> FallibleResult<CommentMetadata> {
+        self.model.remove_comment_metadata(id)
+    }
+
     fn boxed_with_project_metadata(&self, fun: Box<dyn FnOnce(&ProjectMetadata) + '_>) {
         self.model.boxed_with_project_metadata(fun)
     }
@@ -0,0 +1,294 @@
+//! Statistics of the components accepted by the user, used to rank the components in the Component
+//! Browser.
+//!
+//! Every time a node is committed, the suggestions used to create it are recorded along with the
+//! context they were picked in: the type of the `self` argument and the edited module. Each
+//! suggestion gets a [`Score`] blending how often and how recently it was accepted, with the
+//! acceptances in contexts similar to the current one weighted more. The statistics are stored in
+//! the project's metadata (see [`super::ProjectMetadata`]). The suggestions are identified by
+//! string keys: the qualified names of the database entries or the names of virtual components.
+
+use crate::prelude::*;
+
+use serde::Deserialize;
+use serde::Serialize;
+use std::cmp::Ordering;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The maximum number of stored records. When exceeded, the least recently used record is
+/// dropped.
+pub const MAX_RECORDS: usize = 1000;
+
+/// The number of acceptances after which the recency part of the record's score drops by half.
+const RECENCY_HALF_LIFE: f32 = 20.0;
+
+/// The weight of the frequency part of the score.
+const FREQUENCY_WEIGHT: f32 = 1.0;
+
+/// The weight of the recency part of the score.
+const RECENCY_WEIGHT: f32 = 2.0;
+
+/// The weight of the record's score when the `self` type matches the current context.
+const SAME_SELF_TYPE_WEIGHT: f32 = 0.5;
+
+/// The weight of the record's score when the module matches the current context.
+const SAME_MODULE_WEIGHT: f32 = 0.3;
+
+/// The weight of the record's score regardless of its context.
+const ANY_CONTEXT_WEIGHT: f32 = 0.2;
+
+
+
+// ===============
+// === Context ===
+// ===============
+
+/// The context in which a suggestion was accepted.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct Context {
+    /// The type of the `self` argument, if the Component Browser was opened for a source node.
+    #[serde(default)]
+    pub self_type: Option<String>,
+    /// The qualified name of the edited module.
+    #[serde(default)]
+    pub module:    Option<String>,
+}
+
+impl Context {
+    /// Constructor.
+    pub fn new(self_type: Option<impl Into<String>>, module: Option<impl Into<String>>) -> Self {
+        Self { self_type: self_type.map(Into::into), module: module.map(Into::into) }
+    }
+
+    /// How much the acceptances in the `other` context count when ranking in this context.
+    fn weight_of(&self, other: &Context) -> f32 {
+        let same_self_type =
+            if self.self_type == other.self_type { SAME_SELF_TYPE_WEIGHT } else { 0.0 };
+        let same_module = if self.module == other.module { SAME_MODULE_WEIGHT } else { 0.0 };
+        ANY_CONTEXT_WEIGHT + same_self_type + same_module
+    }
+}
+
+
+
+// =============
+// === Score ===
+// =============
+
+/// The usage score of a suggestion. The higher the score, the more likely the user will pick the
+/// suggestion. Suggestions which were never accepted have zero score.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Score(f32);
+
+impl Score {
+    /// The score value.
+    pub fn value(self) -> f32 {
+        self.0
+    }
+}
+
+impl Eq for Score {}
+
+impl PartialOrd for Score {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Score {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+/// The usage scores of suggestions in a specific context, keyed by the suggestion's key.
+pub type Scores = HashMap<String, Score>;
+
+
+
+// ==================
+// === Statistics ===
+// ==================
+
+/// The acceptances of a single suggestion in a single context.
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Record {
+    pub suggestion: String,
+    #[serde(flatten)]
+    pub context:    Context,
+    pub count:      u32,
+    /// The value of the [`Statistics`] clock at the last acceptance.
+    pub last_used:  u64,
+}
+
+/// The statistics of accepted suggestions.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Statistics {
+    /// The total number of recorded acceptances, used as a clock measuring the recency.
+    #[serde(default)]
+    clock:   u64,
+    #[serde(default)]
+    records: Vec<Record>,
+}
+
+impl Statistics {
+    /// Check if nothing was recorded.
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// All records, in the order of their first acceptance.
+    pub fn records(&self) -> &[Record] {
+        &self.records
+    }
+
+    /// Record the acceptance of the suggestion in the given context.
+    pub fn record(&mut self, suggestion: impl Into<String>, context: &Context) {
+        let suggestion = suggestion.into();
+        self.clock += 1;
+        let existing = self
+            .records
+            .iter_mut()
+            .find(|record| record.suggestion == suggestion && &record.context == context);
+        if let Some(record) = existing {
+            record.count = record.count.saturating_add(1);
+            record.last_used = self.clock;
+        } else {
+            let context = context.clone();
+            self.records.push(Record { suggestion, context, count: 1, last_used: self.clock });
+            if self.records.len() > MAX_RECORDS {
+                let least_recent = self.records.iter().position_min_by_key(|r| r.last_used);
+                if let Some(index) = least_recent {
+                    self.records.remove(index);
+                }
+            }
+        }
+    }
+
+    /// Forget all recorded acceptances.
+    pub fn reset(&mut self) {
+        *self = default();
+    }
+
+    /// The scores of all recorded suggestions when ranking in the given context.
+    pub fn scores(&self, context: &Context) -> Scores {
+        let mut scores = Scores::new();
+        for record in &self.records {
+            let frequency = (record.count as f32).ln_1p();
+            let age = self.clock.saturating_sub(record.last_used) as f32;
+            let recency = 0.5_f32.powf(age / RECENCY_HALF_LIFE);
+            let score = FREQUENCY_WEIGHT * frequency + RECENCY_WEIGHT * recency;
+            let weighted = context.weight_of(&record.context) * score;
+            scores.entry(record.suggestion.clone()).or_default().0 += weighted;
+        }
+        scores
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(self_type: Option<&str>, module: &str) -> Context {
+        Context::new(self_type, Some(module))
+    }
+
+    fn ranking(statistics: &Statistics, context: &Context) -> Vec<String> {
+        let scores = statistics.scores(context);
+        scores.into_iter().sorted_by(|(_, a), (_, b)| b.cmp(a)).map(|(key, _)| key).collect()
+    }
+
+    #[test]
+    fn frequency_and_recency() {
+        let ctx = context(None, "local.Project.Main");
+        let mut statistics = Statistics::default();
+        for _ in 0..5 {
+            statistics.record("Standard.Base.Data.read", &ctx);
+        }
+        statistics.record("Standard.Base.Data.Text.Text.length", &ctx);
+        // Frequently used suggestion wins over the one used once, even if more recently.
+        assert_eq!(ranking(&statistics, &ctx), vec![
+            "Standard.Base.Data.read",
+            "Standard.Base.Data.Text.Text.length",
+        ]);
+
+        // With equal frequency, the most recently used suggestion wins.
+        let mut statistics = Statistics::default();
+        statistics.record("Standard.Base.Data.read", &ctx);
+        statistics.record("Standard.Base.Data.Text.Text.length", &ctx);
+        assert_eq!(ranking(&statistics, &ctx), vec![
+            "Standard.Base.Data.Text.Text.length",
+            "Standard.Base.Data.read",
+        ]);
+    }
+
+    #[test]
+    fn context_weighting() {
+        let text_ctx = context(Some("Standard.Base.Data.Text.Text"), "local.Project.Main");
+        let table_ctx = context(Some("Standard.Table.Data.Table.Table"), "local.Project.Main");
+        let mut statistics = Statistics::default();
+        statistics.record("Standard.Base.Any.to_text", &table_ctx);
+        statistics.record("Standard.Base.Any.to_text", &table_ctx);
+        statistics.record("Standard.Base.Data.Text.Text.length", &text_ctx);
+        assert_eq!(ranking(&statistics, &text_ctx), vec![
+            "Standard.Base.Data.Text.Text.length",
+            "Standard.Base.Any.to_text",
+        ]);
+        assert_eq!(ranking(&statistics, &table_ctx), vec![
+            "Standard.Base.Any.to_text",
+            "Standard.Base.Data.Text.Text.length",
+        ]);
+        let scores = statistics.scores(&text_ctx);
+        assert!(scores.get("Standard.Base.Data.read").is_none());
+    }
+
+    #[test]
+    fn limiting_and_resetting() {
+        let ctx = Context::default();
+        let mut statistics = Statistics::default();
+        statistics.record("first", &ctx);
+        for i in 0..MAX_RECORDS {
+            statistics.record(format!("suggestion{i}"), &ctx);
+        }
+        assert_eq!(statistics.records().len(), MAX_RECORDS);
+        assert!(statistics.records().iter().all(|record| record.suggestion != "first"));
+
+        statistics.reset();
+        assert!(statistics.is_empty());
+        assert!(statistics.scores(&ctx).is_empty());
+    }
+
+    #[test]
+    fn serialization() {
+        let mut statistics = Statistics::default();
+        statistics.record("Standard.Base.Data.read", &context(None, "local.Project.Main"));
+        let json = serde_json::to_value(&statistics).unwrap();
+        let expected = serde_json::json!({
+            "clock": 1,
+            "records": [{
+                "suggestion": "Standard.Base.Data.read",
+                "self_type": null,
+                "module": "local.Project.Main",
+                "count": 1,
+                "last_used": 1,
+            }]
+        });
+        assert_eq!(json, expected);
+        let deserialized: Statistics = serde_json::from_value(json).unwrap();
+        assert_eq!(deserialized, statistics);
+        let empty: Statistics = serde_json::from_str("{}").unwrap();
+        assert!(empty.is_empty());
+    }
+}
@@ -170,7 +170,7 @@ pub type Synchronized = synchronized::Project;
 // ====================
 
 /// Notification emitted by the project model.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Notification {
     /// One of the backend connections has been lost.
     ConnectionLost(BackendConnection),
@@ -182,6 +182,8 @@ pub enum Notification {
     ExecutionFailed,
     /// Project has been renamed.
     Renamed,
+    /// A file in the project directory has been created or modified.
+    FileChanged(language_server::Path),
 }
 
 /// Denotes one of backend connections used by a project.
@@ -230,6 +232,11 @@ pub mod test {
             .returning_st(move |_path| ready(Ok(module.clone_ref())).boxed_local());
     }
 
+    /// Sets up main module path expectation on the mock project, returning a given path.
+    pub fn expect_main_module_path(project: &mut MockAPI, path: model::module::Path) {
+        project.expect_main_module_path().returning_st(move || path.clone_ref());
+    }
+
     /// Sets up execution context expectation on the mock project, returning a given context.
     pub fn expect_execution_ctx(project: &mut MockAPI, ctx: model::ExecutionContext) {
         let ctx2 = ctx.clone_ref();
@@ -20,14 +20,20 @@ use engine_protocol::language_server::CapabilityRegistration;
 use engine_protocol::language_server::ContentRoot;
 use engine_protocol::language_server::ExpressionUpdates;
 use engine_protocol::language_server::FileEditList;
+use engine_protocol::language_server::FileEvent;
+use engine_protocol::language_server::FileEventKind;
 use engine_protocol::language_server::MethodPointer;
+use engine_protocol::language_server::SuggestionsDatabaseVersion;
 use engine_protocol::language_server::TextFileModifiedOnDisk;
 use engine_protocol::project_manager;
 use engine_protocol::project_manager::MissingComponentAction;
 use engine_protocol::project_manager::ProjectName;
+use enso_suggestion_database::cache as suggestion_db_cache;
+use enso_web::sleep;
 use flo_stream::Subscriber;
 use json_rpc::error::RpcError;
 use parser::Parser;
+use std::time::Duration;
 
 
 
@@ -243,6 +249,114 @@ fn rename_project_on_notification(
 
 
 
+// ===============================
+// === SuggestionDatabaseCache ===
+// ===============================
+
+/// The path of the suggestion database cache, relative to the project's root directory.
+pub const SUGGESTION_DATABASE_CACHE_PATH: [&str; 2] = [".enso", "suggestion-database.cache"];
+
+/// The time between the database update and writing the cache. All updates received in that time
+/// are written at once, so a burst of updates (e.g. when the libraries are being loaded) does not
+/// cause re-encoding and re-writing the whole database for each of them.
+pub const SUGGESTION_DATABASE_CACHE_WRITE_DELAY: Duration = Duration::from_secs(1);
+
+/// The cache of the suggestion database stored in the project's directory, see
+/// [`enso_suggestion_database::cache`].
+///
+/// The cache is read when opening the project and written in the background shortly after the
+/// database is updated, see [`SUGGESTION_DATABASE_CACHE_WRITE_DELAY`]. The cache written in a
+/// different environment, i.e. with other engine or library versions, is ignored.
+#[derive(Clone, CloneRef, Debug)]
+pub struct SuggestionDatabaseCache {
+    language_server_bin: Rc<binary::Connection>,
+    path:                Rc<language_server::Path>,
+    /// The environment of the Language Server the project was opened with.
+    origin:              Rc<suggestion_db_cache::Origin>,
+    /// Whether the cache write is scheduled or in progress.
+    writing:             Rc<Cell<bool>>,
+    /// The version of the database in the cache file, if the file is known to be up to date.
+    cached_version:      Rc<Cell<Option<SuggestionsDatabaseVersion>>>,
+}
+
+impl SuggestionDatabaseCache {
+    /// Create a handle to the cache in the directory of the project with given content root.
+    pub fn new(
+        language_server_bin: Rc<binary::Connection>,
+        project_root_id: Uuid,
+        origin: suggestion_db_cache::Origin,
+    ) -> Self {
+        let path =
+            Rc::new(language_server::Path::new(project_root_id, &SUGGESTION_DATABASE_CACHE_PATH));
+        let origin = Rc::new(origin);
+        Self { language_server_bin, path, origin, writing: default(), cached_version: default() }
+    }
+
+    /// Read the cached database. Returns `None` if there is no valid cache.
+    #[profile(Detail)]
+    pub async fn read(&self) -> Option<SuggestionDatabase> {
+        let cache = match self.language_server_bin.read_file(&self.path).await {
+            Ok(cache) => cache,
+            Err(error) => {
+                info!("Cannot read the suggestion database cache: {error}");
+                return None;
+            }
+        };
+        match suggestion_db_cache::decode(&cache) {
+            Ok((origin, _)) if origin != *self.origin => {
+                info!("Ignoring the suggestion database cache written in another environment.");
+                None
+            }
+            Ok((_, database)) => {
+                self.cached_version.set(Some(database.version()));
+                Some(database)
+            }
+            Err(error) => {
+                warn!("Ignoring the invalid suggestion database cache: {error}");
+                None
+            }
+        }
+    }
+
+    /// Write the database to the cache in the background after the
+    /// [`SUGGESTION_DATABASE_CACHE_WRITE_DELAY`], unless the cache is up to date. The updates
+    /// made before the write starts are written together; if the database is updated while
+    /// writing, the cache is written again after another delay.
+    pub fn update(&self, database: &Rc<SuggestionDatabase>) {
+        if self.writing.replace(true) {
+            return;
+        }
+        let this = self.clone_ref();
+        let weak_database = Rc::downgrade(database);
+        executor::global::spawn(async move {
+            loop {
+                sleep(SUGGESTION_DATABASE_CACHE_WRITE_DELAY).await;
+                let Some(database) = weak_database.upgrade() else { break };
+                let version = database.version();
+                if this.cached_version.get() == Some(version) {
+                    break;
+                }
+                let cache = suggestion_db_cache::encode(&database, &this.origin);
+                drop(database);
+                let result = match cache {
+                    Ok(cache) => this.language_server_bin.write_file(&this.path, &cache).await,
+                    Err(error) => Err(error),
+                };
+                match result {
+                    Ok(()) => this.cached_version.set(Some(version)),
+                    Err(error) => {
+                        warn!("Failed to write the suggestion database cache: {error}");
+                        break;
+                    }
+                }
+            }
+            this.writing.set(false);
+        });
+    }
+}
+
+
+
 // =============
 // === Model ===
 // =============
@@ -341,6 +455,7 @@ pub struct Project {
     pub execution_contexts:  Rc<ExecutionContextsRegistry>,
     pub visualization:       controller::Visualization,
     pub suggestion_db:       Rc<SuggestionDatabase>,
+    pub suggestion_db_cache: SuggestionDatabaseCache,
     pub content_roots:       Rc<ContentRoots>,
     pub parser:              Parser,
     pub notifications:       notification::Publisher<model::project::Notification>,
@@ -369,8 +484,23 @@ impl Project {
         let visualization =
             controller::Visualization::new(language_server, embedded_visualizations);
         let language_server = &*language_server_rpc;
-        let suggestion_db = SuggestionDatabase::create_synchronized(language_server);
+        let project_root_id = language_server.project_root().id();
+        let cache_origin = suggestion_db_cache::Origin::new(
+            &properties.engine_version,
+            language_server.content_roots(),
+        );
+        let suggestion_db_cache = SuggestionDatabaseCache::new(
+            language_server_bin.clone_ref(),
+            project_root_id,
+            cache_origin,
+        );
+        let cached_suggestion_db = suggestion_db_cache.read().await;
+        let suggestion_db = SuggestionDatabase::create_synchronized_from_cache(
+            language_server,
+            cached_suggestion_db,
+        );
         let suggestion_db = Rc::new(suggestion_db.await.map_err(&wrap)?);
+        suggestion_db_cache.update(&suggestion_db);
         let content_roots = ContentRoots::new_from_connection(language_server);
         let content_roots = Rc::new(content_roots);
         let notifications = notification::Publisher::default();
@@ -386,6 +516,7 @@ impl Project {
             execution_contexts,
             visualization,
             suggestion_db,
+            suggestion_db_cache,
             content_roots,
             parser,
             notifications,
@@ -401,6 +532,11 @@ impl Project {
 
         ret.initialize_vcs().await.map_err(|err| wrap(err.into()))?;
         ret.acquire_suggestion_db_updates_capability().await.map_err(|err| wrap(err.into()))?;
+        if let Err(error) = ret.acquire_project_tree_updates_capability().await {
+            // Without the capability, the changed project files (like the theme file) are not
+            // reloaded, but the project is still usable.
+            warn!("Failed to acquire the project tree updates capability: {error}");
+        }
         Ok(ret)
     }
 
@@ -412,12 +548,27 @@ impl Project {
         language_server_bin: String,
         properties: Properties,
     ) -> FallibleResult<model::Project> {
-        let wrap = UnsupportedEngineVersion::error_wrapper(&properties);
-        let client_id = Uuid::new_v4();
         let json_ws = WebSocket::new_opened(&language_server_rpc).await?;
         let binary_ws = WebSocket::new_opened(&language_server_bin).await?;
-        let client_json = language_server::Client::new(json_ws);
-        let client_binary = binary::Client::new(binary_ws);
+        Self::new_with_transports(project_manager, json_ws, binary_ws, properties).await
+    }
+
+    /// Initializes the json and binary connection to Language Server over the given, already
+    /// opened transports, and creates a Project Model.
+    ///
+    /// Allows connecting with transports other than the browser's WebSocket, e.g. in native
+    /// applications.
+    #[profile(Detail)]
+    pub async fn new_with_transports(
+        project_manager: Option<Rc<dyn project_manager::API>>,
+        json_transport: impl json_rpc::Transport + 'static,
+        binary_transport: impl json_rpc::Transport + 'static,
+        properties: Properties,
+    ) -> FallibleResult<model::Project> {
+        let wrap = UnsupportedEngineVersion::error_wrapper(&properties);
+        let client_id = Uuid::new_v4();
+        let client_json = language_server::Client::new(json_transport);
+        let client_binary = binary::Client::new(binary_transport);
         crate::executor::global::spawn(client_json.runner());
         crate::executor::global::spawn(client_binary.runner());
         let connection_json =
@@ -541,6 +692,7 @@ impl Project {
         let properties = self.properties.clone_ref();
         let execution_contexts = self.execution_contexts.clone_ref();
         let weak_suggestion_db = Rc::downgrade(&self.suggestion_db);
+        let suggestion_db_cache = self.suggestion_db_cache.clone_ref();
         let weak_content_roots = Rc::downgrade(&self.content_roots);
         let weak_module_registry = Rc::downgrade(&self.module_registry);
         let execution_update_handler = self.execution_update_handler();
@@ -558,7 +710,10 @@ impl Project {
 
             // Event Handling
             match event {
-                Event::Notification(Notification::FileEvent(_)) => {}
+                Event::Notification(Notification::FileEvent(FileEvent { path, kind })) =>
+                    if kind != FileEventKind::Removed {
+                        publisher.notify(model::project::Notification::FileChanged(path));
+                    },
                 Event::Notification(Notification::TextAutoSave(_)) => {
                     let publisher = publisher.clone_ref();
                     let language_server = language_server.clone_ref();
@@ -616,6 +771,7 @@ impl Project {
                 Event::Notification(Notification::SuggestionDatabaseUpdates(update)) =>
                     if let Some(suggestion_db) = weak_suggestion_db.upgrade() {
                         suggestion_db.apply_update_event(update);
+                        suggestion_db_cache.update(&suggestion_db);
                     },
                 Event::Notification(Notification::ContentRootAdded { root }) => {
                     if let Some(content_roots) = weak_content_roots.upgrade() {
@@ -669,6 +825,17 @@ impl Project {
             .acquire_capability(&capability.method, &capability.register_options)
     }
 
+    /// Acquire the capability of receiving notifications about changes of files in the project
+    /// directory.
+    fn acquire_project_tree_updates_capability(
+        &self,
+    ) -> impl Future<Output = json_rpc::Result<()>> {
+        let root_path = language_server::Path::new_root(self.project_content_root_id());
+        let capability = CapabilityRegistration::create_receives_tree_updates(root_path);
+        self.language_server_rpc
+            .acquire_capability(&capability.method, &capability.register_options)
+    }
+
     /// Initialize the VCS if it was not already initialized.
     #[profile(Detail)]
     async fn initialize_vcs(&self) -> json_rpc::Result<()> {
@@ -812,6 +979,7 @@ mod test {
     use engine_protocol::language_server::response;
     use engine_protocol::language_server::Notification::ExpressionUpdates;
     use engine_protocol::types::Sha3_224;
+    use enso_web::clock::VirtualClock;
     use futures::SinkExt;
     use json_rpc::expect_call;
     use std::assert_matches::assert_matches;
@@ -823,12 +991,24 @@ mod test {
         project:              Project,
         binary_events_sender: futures::channel::mpsc::UnboundedSender<binary::client::Event>,
         json_events_sender:   futures::channel::mpsc::UnboundedSender<language_server::Event>,
+        /// The suggestion database caches written by the project model.
+        written_caches:       Rc<RefCell<Vec<Vec<u8>>>>,
     }
 
     impl Fixture {
         fn new(
             setup_mock_json: impl FnOnce(&mut language_server::MockClient),
             setup_mock_binary: impl FnOnce(&mut engine_protocol::binary::MockClient),
+        ) -> Self {
+            Self::new_with_tree_updates(Ok(()), setup_mock_json, setup_mock_binary)
+        }
+
+        /// Create the fixture, with the given result of acquiring the project tree updates
+        /// capability.
+        fn new_with_tree_updates(
+            tree_updates: json_rpc::Result<()>,
+            setup_mock_json: impl FnOnce(&mut language_server::MockClient),
+            setup_mock_binary: impl FnOnce(&mut engine_protocol::binary::MockClient),
         ) -> Self {
             let mut test = TestWithLocalPoolExecutor::set_up();
             let project_manager = project_manager::MockClient::default();
@@ -838,6 +1018,17 @@ mod test {
             let (binary_events_sender, binary_events) = futures::channel::mpsc::unbounded();
             binary_client.expect_event_stream().return_once(|| binary_events.boxed_local());
             let json_events_sender = json_client.setup_events();
+            binary_client.expect_read_file().returning_st(|_| {
+                futures::future::ready(Err(failure::format_err!("No such file."))).boxed_local()
+            });
+            let written_caches: Rc<RefCell<Vec<Vec<u8>>>> = default();
+            let caches = written_caches.clone_ref();
+            binary_client.expect_write_file().returning_st(move |path, contents| {
+                let cache_path = &SUGGESTION_DATABASE_CACHE_PATH;
+                assert_eq!(path, &language_server::Path::new(Uuid::default(), cache_path));
+                caches.borrow_mut().push(contents.to_vec());
+                futures::future::ready(Ok(())).boxed_local()
+            });
 
             let initial_suggestions_db =
                 response::GetSuggestionDatabase { entries: vec![], current_version: 0 };
@@ -849,6 +1040,11 @@ mod test {
             expect_call!(json_client.acquire_capability(method,options) => Ok(()));
             let path_segments: [&str; 0] = [];
             let root_path = language_server::Path::new(Uuid::default(), &path_segments);
+            let capability_reg =
+                CapabilityRegistration::create_receives_tree_updates(root_path.clone());
+            let method = capability_reg.method;
+            let options = capability_reg.register_options;
+            expect_call!(json_client.acquire_capability(method,options) => tree_updates);
             expect_call!(json_client.init_vcs(root_path) => Ok(()));
 
             setup_mock_json(&mut json_client);
@@ -866,7 +1062,7 @@ mod test {
                 Project::new(Some(project_manager), json_connection, binary_connection, properties)
                     .boxed_local();
             let project = test.expect_completion(project_fut).unwrap();
-            Fixture { test, project, binary_events_sender, json_events_sender }
+            Fixture { test, project, binary_events_sender, json_events_sender, written_caches }
         }
     }
 
@@ -895,6 +1091,13 @@ mod test {
         });
     }
 
+    #[wasm_bindgen_test]
+    fn project_without_tree_updates_capability() {
+        // The project is created even if the changes of its files cannot be observed.
+        let f = Fixture::new_with_tree_updates(Err(RpcError::LostConnection), |_| {}, |_| {});
+        assert_eq!(f.project.project_content_root_id(), Uuid::default());
+    }
+
     #[wasm_bindgen_test]
     fn obtain_module_controller() {
         let path = module::Path::from_mock_module_name("TestModule");
@@ -922,6 +1125,47 @@ mod test {
         });
     }
 
+    #[wasm_bindgen_test]
+    fn writing_suggestion_database_cache() {
+        let clock = VirtualClock::new();
+        clock.install();
+        let Fixture { mut test, project, json_events_sender, written_caches, .. } =
+            Fixture::new(|_| {}, |_| {});
+        let wait_for_write = |test: &mut TestWithLocalPoolExecutor| {
+            test.run_until_stalled();
+            clock.advance(SUGGESTION_DATABASE_CACHE_WRITE_DELAY);
+            test.run_until_stalled();
+        };
+        let cached_database =
+            |index: usize| suggestion_db_cache::decode(&written_caches.borrow()[index]).unwrap().1;
+        wait_for_write(&mut test);
+        assert_eq!(written_caches.borrow().len(), 1);
+        assert_eq!(cached_database(0).version(), 0);
+
+        // The updates received before the write are written at once.
+        for current_version in [1, 2] {
+            let update = language_server::SuggestionDatabaseUpdatesEvent {
+                updates: vec![],
+                current_version,
+            };
+            let notification = language_server::Notification::SuggestionDatabaseUpdates(update);
+            json_events_sender
+                .unbounded_send(language_server::Event::Notification(notification))
+                .unwrap();
+        }
+        test.run_until_stalled();
+        assert_eq!(project.suggestion_db.version(), 2);
+        assert_eq!(written_caches.borrow().len(), 1);
+        wait_for_write(&mut test);
+        assert_eq!(written_caches.borrow().len(), 2);
+        assert_eq!(cached_database(1).version(), 2);
+
+        // The up-to-date cache is not written again.
+        wait_for_write(&mut test);
+        assert_eq!(written_caches.borrow().len(), 2);
+        VirtualClock::uninstall();
+    }
+
     fn mock_calls_for_opening_text_file(
         client: &language_server::MockClient,
         path: language_server::Path,
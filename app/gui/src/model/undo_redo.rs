@@ -608,6 +608,27 @@ main =
         });
     }
 
+    // Expand a node calling a method of the module.
+    #[test]
+    fn expand_node_atomic() {
+        let code = r#"
+extracted foo =
+    bar = foo + 6
+    baz = 2 + foo + bar
+    baz
+
+main =
+    foo = 2
+    baz = Mock_Module.extracted foo
+    caz = baz / 2 * baz
+"#;
+        check_atomic_graph_action(code, |graph| {
+            let nodes = graph.nodes().unwrap();
+            assert_eq!(nodes.len(), 3);
+            graph.expand(nodes[1].id()).unwrap();
+        });
+    }
+
     // A complex operation: involves introducing variable name, reordering lines and
     // replacing an argument.
     #[test]
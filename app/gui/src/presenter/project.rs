@@ -11,6 +11,7 @@ use crate::presenter::ComponentBrowserSearcher;
 use crate::EXECUTION_FAILED_MESSAGE;
 
 use engine_protocol::language_server::ExecutionEnvironment;
+use engine_protocol::language_server::Path;
 use enso_frp as frp;
 use ensogl::system::js;
 use ide_view as view;
@@ -38,6 +39,9 @@ const LOST_CONTEXT_SPINNER_PROGRESS: f32 = 0.0;
 /// the spinner near completion. Context restoration is much faster than initial loading; the only
 /// time-consuming operation required is shader recompilation.
 const RESTORING_CONTEXT_SPINNER_PROGRESS: f32 = 0.9;
+/// The name under which the project's theme file is registered in the theme manager. The theme is
+/// applied as an overlay over the builtin ones, so it overrides their values.
+const PROJECT_THEME_NAME: &str = "project";
 
 
 
@@ -251,6 +255,24 @@ impl Model {
         self.execution_failed_notification.show();
     }
 
+    /// Load the project's theme file, if it exists, and apply it over the builtin themes.
+    fn load_theme_file(&self) {
+        let controller = self.controller.clone_ref();
+        executor::global::spawn(async move {
+            match controller.read_theme_file().await {
+                Ok(Some(source)) => apply_theme_file(&source),
+                Ok(None) => {}
+                Err(err) => error!("Error while reading the project's theme file: {err}"),
+            }
+        })
+    }
+
+    fn file_changed(&self, path: &Path) {
+        if *path == self.controller.theme_file_path() {
+            self.load_theme_file();
+        }
+    }
+
     fn execution_context_interrupt(&self) {
         let controller = self.graph_controller.clone_ref();
         executor::global::spawn(async move {
@@ -394,6 +416,21 @@ impl Model {
     }
 }
 
+/// Register the theme file content in the theme manager and apply it as an overlay, so it stays
+/// applied when switching between the builtin themes. If the file contains errors, they are
+/// reported to the user, and the previously loaded version stays in use.
+fn apply_theme_file(source: &str) {
+    let manager = ensogl::display::world::with_context(|t| t.theme_manager.clone_ref());
+    match manager.load_file(PROJECT_THEME_NAME, source, &ensogl_hardcoded_theme::schema()) {
+        Ok(()) => manager.add_overlay(PROJECT_THEME_NAME),
+        Err(errors) => {
+            let details = errors.iter().map(|error| format!("\n{error}")).collect::<String>();
+            let message = format!("The project's theme file contains errors:{details}");
+            notification::warning(message, &None);
+        }
+    }
+}
+
 
 // ===============
 // === Project ===
@@ -497,6 +534,7 @@ impl Project {
 
         self.init_analytics()
             .init_execution_environments()
+            .init_theme_file()
             .setup_notification_handler()
             .attach_frp_to_values_computed_notifications(graph_controller, values_computed)
     }
@@ -509,6 +547,11 @@ impl Project {
         self
     }
 
+    fn init_theme_file(self) -> Self {
+        self.model.load_theme_file();
+        self
+    }
+
     fn init_analytics(self) -> Self {
         let network = &self.network;
         let project = &self.model.view;
@@ -558,6 +601,9 @@ impl Project {
                 Notification::Renamed => {
                     model.project_renamed();
                 }
+                Notification::FileChanged(path) => {
+                    model.file_changed(&path);
+                }
             };
             std::future::ready(())
         });
@@ -371,6 +371,8 @@ impl ComponentBrowserSearcher {
             action_list_changed <+ model.project.searcher_input_changed.constant(());
 
             eval_ model.project.request_dump_suggestion_database(model.controller.dump_database_as_json());
+            eval_ model.project.request_reset_component_usage_statistics(
+                model.controller.reset_usage_statistics());
             eval_ model.project.toggle_component_browser_private_entries_visibility (
                 model.controller.reload_list());
         }
@@ -123,6 +123,8 @@ where
 pub mod tests {
     use super::*;
 
+    use crate::executor::test_utils::TestWithVirtualClock;
+
     #[test]
     fn successful_operation() {
         let operation = || async { Ok(4) };
@@ -148,12 +150,15 @@ pub mod tests {
             }
         };
         let retry_times = [10, 20, 30, 40, 50].into_iter().map(Duration::from_millis);
+        let mut fixture = TestWithVirtualClock::set_up();
         let mut future =
             retry_operation(operation, retry_times, "Test operation failed.").boxed_local();
         future.expect_pending();
-        std::thread::sleep(Duration::from_millis(10));
+        fixture.advance(Duration::from_millis(10));
+        future.expect_pending();
+        fixture.advance(Duration::from_millis(19));
         future.expect_pending();
-        std::thread::sleep(Duration::from_millis(30));
+        fixture.advance(Duration::from_millis(1));
         let result = future.expect_ready();
         assert_eq!(result, RetryResult::OkAfterRetries(3, NonEmptyVec::new(1, vec![2])));
     }
@@ -166,19 +171,20 @@ pub mod tests {
             async move { Err(call_index) }
         };
         let retry_times = [10, 20, 30, 40, 50].into_iter().map(Duration::from_millis);
+        let mut fixture = TestWithVirtualClock::set_up();
         let mut future =
             retry_operation(operation, retry_times, "One does not simply walk into Mordor.")
                 .boxed_local();
         future.expect_pending();
-        std::thread::sleep(Duration::from_millis(10));
+        fixture.advance(Duration::from_millis(10));
         future.expect_pending();
-        std::thread::sleep(Duration::from_millis(20));
+        fixture.advance(Duration::from_millis(20));
         future.expect_pending();
-        std::thread::sleep(Duration::from_millis(30));
+        fixture.advance(Duration::from_millis(30));
         future.expect_pending();
-        std::thread::sleep(Duration::from_millis(40));
+        fixture.advance(Duration::from_millis(40));
         future.expect_pending();
-        std::thread::sleep(Duration::from_millis(60));
+        fixture.advance(Duration::from_millis(50));
         let result: RetryResult<usize, _> = future.expect_ready();
         assert_eq!(result, RetryResult::Err(NonEmptyVec::new(1, vec![2, 3, 4, 5, 6])));
     }
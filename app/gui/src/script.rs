@@ -0,0 +1,357 @@
+//! Scripting of the graph controllers without the GUI.
+//!
+//! A script is a sequence of graph operations, see [`command`] for its syntax. The [`Runner`]
+//! executes them against the controllers of a project, so the same editing logic as in the IDE
+//! can be used for batch refactorings of many projects and for end-to-end controller tests.
+
+use crate::prelude::*;
+
+use crate::controller::graph::Connection;
+use crate::controller::graph::Endpoint;
+use crate::controller::graph::NewNodeInfo;
+use crate::controller::graph::NodeTrees;
+
+use double_representation::name::QualifiedName;
+use double_representation::node;
+use span_tree::generate::Context as SpanTreeContext;
+use span_tree::PortId;
+
+
+// ==============
+// === Export ===
+// ==============
+
+pub mod command;
+
+pub use command::Command;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// Separates the segments of a qualified name. The renamed names containing it are renamed in
+/// the whole project.
+const QUALIFIED_NAME_SEPARATOR: char = '.';
+
+
+
+// ==============
+// === Errors ===
+// ==============
+
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, Fail)]
+#[fail(display = "No graph is opened; the script should start with the `open` command.")]
+pub struct NoGraphOpened;
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(display = "No node named `{}` in the graph.", _0)]
+pub struct NoSuchNode(String);
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(display = "The node `{}` has no argument `{:?}`.", node, argument)]
+pub struct NoSuchArgument {
+    node:     String,
+    argument: command::ArgumentRef,
+}
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(display = "Expected the node `{}` to be `{}`, but it is `{}`.", node, expected, actual)]
+pub struct UnexpectedExpression {
+    node:     String,
+    expected: String,
+    actual:   String,
+}
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(display = "The command in line {} failed: {}", line, message)]
+pub struct CommandFailed {
+    pub line:    usize,
+    pub message: String,
+}
+
+
+
+// ==============
+// === Runner ===
+// ==============
+
+/// Executes the scripts against the controllers of the project.
+///
+/// The commands operate on the currently opened graph, which is set by [`Command::Open`].
+#[derive(Debug)]
+pub struct Runner {
+    project: model::Project,
+    graph:   Option<controller::ExecutedGraph>,
+}
+
+impl Runner {
+    /// Create a runner without any graph opened.
+    pub fn new(project: model::Project) -> Self {
+        Self { project, graph: None }
+    }
+
+    /// Create a runner with the given graph opened.
+    pub fn new_with_graph(project: model::Project, graph: controller::ExecutedGraph) -> Self {
+        Self { project, graph: Some(graph) }
+    }
+
+    /// The currently opened graph.
+    pub fn graph(&self) -> FallibleResult<&controller::ExecutedGraph> {
+        self.graph.as_ref().ok_or_else(|| NoGraphOpened.into())
+    }
+
+    /// Parse and execute the script, stopping at the first failed command.
+    ///
+    /// The script is parsed as a whole before executing anything, so a script with syntax errors
+    /// does not leave the project partially modified.
+    pub async fn run(&mut self, script: &str) -> FallibleResult {
+        for line in command::parse(script)? {
+            info!("Executing line {}: {:?}.", line.number, line.command);
+            let number = line.number;
+            let result = self.execute(line.command).await;
+            result
+                .map_err(|error| CommandFailed { line: number, message: error.to_string() })?;
+        }
+        Ok(())
+    }
+
+    /// Execute a single command.
+    pub async fn execute(&mut self, command: Command) -> FallibleResult {
+        match command {
+            Command::Open { module, method } => return self.open(module, method).await,
+            Command::Rename { name, new_name } if name.contains(QUALIFIED_NAME_SEPARATOR) =>
+                return self.rename_in_project(&name, &new_name).await,
+            _ => {}
+        }
+        let executed = self.graph()?;
+        let graph = executed.graph();
+        match command {
+            Command::Open { .. } => unreachable!("Opening graph is handled above."),
+            Command::AddNode { node, expression } => {
+                let introduce_pattern = node.is_none();
+                let info =
+                    NewNodeInfo { introduce_pattern, ..NewNodeInfo::new_pushed_back(expression) };
+                let id = graph.add_node(info)?;
+                match node {
+                    Some(name) => graph.set_pattern_on(id, Ast::var(name)),
+                    None => Ok(()),
+                }
+            }
+            Command::SetExpression { node, expression } =>
+                graph.set_expression(node_id(&graph, &node)?, expression),
+            Command::RemoveNode { node } => graph.remove_node(node_id(&graph, &node)?),
+            Command::Connect { source, target } =>
+                executed.connect(&connection(executed, &source, &target)?),
+            Command::Disconnect { source, target } =>
+                executed.disconnect(&connection(executed, &source, &target)?),
+            Command::MoveNode { node, position } =>
+                graph.set_node_position(node_id(&graph, &node)?, position),
+            Command::Collapse { nodes, method } => {
+                let nodes = nodes.iter().map(|node| node_id(&graph, node));
+                let nodes = nodes.collect::<FallibleResult<Vec<_>>>()?;
+                graph.collapse(nodes, &method).map(drop)
+            }
+            Command::Expand { node } => graph.expand(node_id(&graph, &node)?).map(drop),
+            Command::Rename { name, new_name } => graph.rename_variable(&name, &new_name),
+            Command::Layout => graph.auto_layout(None, &default(), executed),
+            Command::ExpectExpression { node, expression } => {
+                let actual = graph.node(node_id(&graph, &node)?)?.info.expression().repr();
+                let expected = graph.parse_node_expression(&expression)?.repr();
+                if actual == expected {
+                    Ok(())
+                } else {
+                    Err(UnexpectedExpression { node, expected, actual }.into())
+                }
+            }
+        }
+    }
+
+    /// Rename the method, type or constructor given by its qualified name in all the project's
+    /// modules.
+    async fn rename_in_project(&self, name: &str, new_name: &str) -> FallibleResult {
+        let target = QualifiedName::from_text(name)?;
+        let project = controller::Project::new(self.project.clone_ref(), default());
+        let preview = project.prepare_rename(&target, new_name).await?;
+        info!("{preview}");
+        project.apply_rename(&preview).await
+    }
+
+    /// Open the graph of the method defined in the module. The project's main module and its
+    /// `main` method are opened by default.
+    async fn open(
+        &mut self,
+        module: Option<Vec<String>>,
+        method: Option<String>,
+    ) -> FallibleResult {
+        let module_path = match module {
+            Some(segments) => {
+                let root_id = self.project.project_content_root_id();
+                model::module::Path::from_name_segments(root_id, segments)?
+            }
+            None => self.project.main_module_path(),
+        };
+        let method = method.unwrap_or_else(|| controller::project::MAIN_DEFINITION_NAME.into());
+        let method = module_path.method_pointer(self.project.qualified_name(), method);
+        info!("Opening graph of {method:?}.");
+        let graph = controller::ExecutedGraph::new(self.project.clone_ref(), method).await?;
+        self.graph = Some(graph);
+        Ok(())
+    }
+}
+
+/// Find the node assigned to the variable of the given name.
+fn node_id(graph: &controller::Graph, name: &str) -> FallibleResult<node::Id> {
+    let nodes = graph.nodes()?;
+    let node = nodes.iter().find(|node| node.variable_name().ok().flatten() == Some(name));
+    node.map(|node| node.id()).ok_or_else(|| NoSuchNode(name.into()).into())
+}
+
+/// Describe the connection from the whole output of the `source` node to the `target` port.
+fn connection(
+    executed: &controller::ExecutedGraph,
+    source: &str,
+    target: &command::TargetPort,
+) -> FallibleResult<Connection> {
+    let graph = executed.graph();
+    let source = Endpoint::root(node_id(&graph, source)?);
+    let target_node = graph.node(node_id(&graph, &target.node)?)?;
+    let port = match &target.argument {
+        Some(argument) => argument_port(&target_node, argument, executed).ok_or_else(|| {
+            let node = target.node.clone();
+            NoSuchArgument { node, argument: argument.clone() }
+        })?,
+        None => PortId::Root,
+    };
+    Ok(Connection { source, target: Endpoint::new(target_node.id(), port) })
+}
+
+/// Find the port of the node's argument. Both the present arguments and the placeholders of the
+/// expected ones are considered.
+fn argument_port(
+    node: &controller::graph::Node,
+    argument: &command::ArgumentRef,
+    context: &impl SpanTreeContext,
+) -> Option<PortId> {
+    let trees = NodeTrees::new(&node.info, context)?;
+    let mut arguments = Vec::new();
+    trees.inputs.root_ref().dfs(|port| {
+        if port.kind.is_argument() || port.kind.is_expected_argument() {
+            arguments.push((port.kind.argument_name().map(ToOwned::to_owned), port.port_id));
+        }
+    });
+    let found = match argument {
+        command::ArgumentRef::Name(name) =>
+            arguments.into_iter().find(|(argument, _)| argument.as_ref() == Some(name)),
+        command::ArgumentRef::Position(position) => arguments.into_iter().nth(*position),
+    };
+    found.and_then(|(_, port)| port)
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test::mock;
+
+    fn run_script(code: &str, script: &str) -> (mock::Fixture, FallibleResult) {
+        let mut data = mock::Unified::new();
+        data.set_code(code);
+        run_script_with_fixture(data.fixture(), script)
+    }
+
+    fn run_script_with_fixture(
+        mut fixture: mock::Fixture,
+        script: &str,
+    ) -> (mock::Fixture, FallibleResult) {
+        let project = fixture.project.clone_ref();
+        let mut runner = Runner::new_with_graph(project, fixture.executed_graph.clone_ref());
+        let script = script.to_owned();
+        let result = Rc::new(RefCell::new(None));
+        let result_cell = result.clone_ref();
+        fixture.executor.run_task(async move {
+            *result_cell.borrow_mut() = Some(runner.run(&script).await);
+        });
+        fixture.run_until_stalled();
+        let result = result.borrow_mut().take().expect("The script did not finish.");
+        (fixture, result)
+    }
+
+    #[test]
+    fn editing_graph_with_script() {
+        let code = "main =\n    foo = 2\n    bar = 3";
+        let script = r#"
+            # Build the sum of both nodes.
+            add sum = 0 + 0
+            connect foo -> sum.0
+            connect bar -> sum.1
+            expect sum = foo + bar
+            set bar = 4
+            remove foo
+            rename sum total
+            move total 10 20
+        "#;
+        let (fixture, result) = run_script(code, script);
+        result.unwrap();
+        let expected = "main =\n    bar = 4\n    total = foo + bar";
+        assert_eq!(fixture.module.ast().repr(), expected);
+        let graph = fixture.executed_graph.graph();
+        let total = node_id(&graph, "total").unwrap();
+        let position = graph.node(total).unwrap().position().unwrap();
+        assert_eq!(position.vector, Vector2::new(10.0, 20.0));
+    }
+
+    #[test]
+    fn failing_script() {
+        let code = "main =\n    foo = 2";
+        let script = "set foo = 3\nexpect foo = 4\nset foo = 5";
+        let (fixture, result) = run_script(code, script);
+        let error = result.unwrap_err();
+        let failed = error.downcast::<CommandFailed>().unwrap();
+        assert_eq!(failed.line, 2);
+        // The commands after the failed one are not executed.
+        assert_eq!(fixture.module.ast().repr(), "main =\n    foo = 3");
+
+        let (_, result) = run_script(code, "remove bar");
+        assert!(result.unwrap_err().to_string().contains("bar"));
+    }
+
+    #[test]
+    fn renaming_method_in_project() {
+        use model::suggestion_database::Entry;
+        let mut data = mock::Unified::new();
+        data.set_code("helper = 1\nmain =\n    foo = helper");
+        let module_name = data.module_qualified_name();
+        let number = QualifiedName::from_text("Standard.Base.Data.Numbers.Number").unwrap();
+        data.suggestions.insert(3, Entry::new_module(module_name.clone()));
+        data.suggestions.insert(4, Entry::new_module_method(module_name.clone(), "helper", number));
+        let sent_edits = Rc::new(RefCell::new(Vec::new()));
+        let fixture = data.fixture_customize(|_, client, _| {
+            let sent_edits = sent_edits.clone_ref();
+            client.expect.apply_text_file_edit(move |edit, _| {
+                sent_edits.borrow_mut().push(edit.clone());
+                Ok(())
+            });
+        });
+        let script = format!("rename {module_name}.helper util");
+        let (_, result) = run_script_with_fixture(fixture, &script);
+        result.unwrap();
+        // The whole module is renamed with a single edit sent to the Language Server.
+        let sent_edits = sent_edits.borrow();
+        assert_eq!(sent_edits.len(), 1);
+        let texts = sent_edits[0].edits.iter().map(|edit| edit.text.as_str()).collect_vec();
+        assert_eq!(texts.iter().filter(|text| **text == "util").count(), 2);
+    }
+}
@@ -0,0 +1,376 @@
+//! The commands of the graph editing scripts and their parser.
+//!
+//! A script consists of lines, each being a single command. Empty lines and lines starting with
+//! [`COMMENT_MARKER`] are ignored. The nodes are referred to by the names of the variables they
+//! are assigned to. The available commands are:
+//!
+//! ```text
+//! open [<module> [<method>]]            # Open the graph of the method, `Main` `main` by default.
+//! add [<node> =] <expression>           # Add a node; a name is generated if not given.
+//! set <node> = <expression>             # Set the node's expression.
+//! remove <node>                         # Remove the node.
+//! connect <node> -> <node>[.<argument>] # Connect the node's output to the argument, given by
+//!                                       # its name or position, or to the whole expression.
+//! disconnect <node> -> <node>[.<argument>]
+//! move <node> <x> <y>                   # Set the node's position.
+//! collapse <node>... into <method>      # Collapse the nodes to a new method.
+//! expand <node>                         # Expand the method called by the node.
+//! rename <name> <new name>              # Rename a node's variable or the method's parameter.
+//!                                       # A qualified name of a method, type or constructor
+//!                                       # is renamed in the whole project.
+//! layout                                # Arrange all nodes automatically.
+//! expect <node> = <expression>          # Fail if the node's expression is different.
+//! ```
+
+use crate::prelude::*;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The lines starting with this marker are ignored.
+pub const COMMENT_MARKER: &str = "#";
+
+/// Separates the source and the target of a connection.
+pub const CONNECTION_ARROW: &str = "->";
+
+/// Separates the node name from its expression.
+pub const ASSIGNMENT: &str = "=";
+
+/// Separates the collapsed nodes from the name of the introduced method.
+pub const COLLAPSE_INTO_KEYWORD: &str = "into";
+
+
+
+// ==============
+// === Errors ===
+// ==============
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(display = "Invalid command in line {}: {}", line, message)]
+pub struct ParseError {
+    pub line:    usize,
+    pub message: String,
+}
+
+
+
+// ===============
+// === Command ===
+// ===============
+
+/// A port being a target of a connection.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TargetPort {
+    /// The name of the node.
+    pub node:     String,
+    /// The argument of the node's expression, given by its name or position. If `None`, the
+    /// whole expression is the target.
+    pub argument: Option<ArgumentRef>,
+}
+
+/// Identifies an argument of the node's expression.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ArgumentRef {
+    /// The argument with the given name.
+    Name(String),
+    /// The n-th argument of the expression, counting from 0.
+    Position(usize),
+}
+
+/// A single operation on the graph.
+#[allow(missing_docs)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    /// Open the graph of the method. The module is given by its name segments without the
+    /// project's name; `None` means the project's main module.
+    Open {
+        module: Option<Vec<String>>,
+        method: Option<String>,
+    },
+    AddNode {
+        node:       Option<String>,
+        expression: String,
+    },
+    SetExpression {
+        node:       String,
+        expression: String,
+    },
+    RemoveNode {
+        node: String,
+    },
+    Connect {
+        source: String,
+        target: TargetPort,
+    },
+    Disconnect {
+        source: String,
+        target: TargetPort,
+    },
+    MoveNode {
+        node:     String,
+        position: Vector2,
+    },
+    Collapse {
+        nodes:  Vec<String>,
+        method: String,
+    },
+    Expand {
+        node: String,
+    },
+    Rename {
+        name:     String,
+        new_name: String,
+    },
+    Layout,
+    ExpectExpression {
+        node:       String,
+        expression: String,
+    },
+}
+
+/// A command together with the number of the script's line it was given in, counting from 1.
+#[allow(missing_docs)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Line {
+    pub number:  usize,
+    pub command: Command,
+}
+
+
+
+// ==============
+// === Parser ===
+// ==============
+
+/// Parse the whole script.
+pub fn parse(script: &str) -> Result<Vec<Line>, ParseError> {
+    let lines = script.lines().enumerate().map(|(index, line)| (index + 1, line.trim()));
+    let lines = lines.filter(|(_, line)| !line.is_empty() && !line.starts_with(COMMENT_MARKER));
+    let lines = lines.map(|(number, line)| {
+        let command = parse_command(line).map_err(|message| ParseError { line: number, message });
+        command.map(|command| Line { number, command })
+    });
+    lines.collect()
+}
+
+/// Parse a single command. Returns the error message on failure.
+pub fn parse_command(line: &str) -> Result<Command, String> {
+    let (keyword, rest) = split_word(line);
+    match keyword {
+        "open" => {
+            let mut words = rest.split_whitespace();
+            let module = words.next().map(|module| module.split('.').map(Into::into).collect());
+            let method = words.next().map(Into::into);
+            expect_end(words)?;
+            Ok(Command::Open { module, method })
+        }
+        "add" => match split_assignment(rest) {
+            Some((node, expression)) =>
+                Ok(Command::AddNode { node: Some(node), expression: non_empty(&expression)? }),
+            None => Ok(Command::AddNode { node: None, expression: non_empty(rest)? }),
+        },
+        "set" => {
+            let (node, expression) = assignment(rest)?;
+            Ok(Command::SetExpression { node, expression })
+        }
+        "remove" => Ok(Command::RemoveNode { node: single_name(rest)? }),
+        "connect" => {
+            let (source, target) = connection(rest)?;
+            Ok(Command::Connect { source, target })
+        }
+        "disconnect" => {
+            let (source, target) = connection(rest)?;
+            Ok(Command::Disconnect { source, target })
+        }
+        "move" => {
+            let mut words = rest.split_whitespace();
+            let node = words.next().ok_or("Missing node name.")?.into();
+            let mut coordinate = || -> Result<f32, String> {
+                let word = words.next().ok_or("Missing node position.")?;
+                word.parse().map_err(|_| format!("Invalid coordinate `{word}`."))
+            };
+            let position = Vector2::new(coordinate()?, coordinate()?);
+            expect_end(words)?;
+            Ok(Command::MoveNode { node, position })
+        }
+        "collapse" => {
+            let mut words = rest.split_whitespace().collect_vec();
+            let method = words.pop().ok_or("Missing the method name.")?.into();
+            if words.pop() != Some(COLLAPSE_INTO_KEYWORD) {
+                return Err(format!("Expected `{COLLAPSE_INTO_KEYWORD}` before the method name."));
+            }
+            if words.is_empty() {
+                return Err("No nodes to collapse.".into());
+            }
+            let nodes = words.into_iter().map(Into::into).collect();
+            Ok(Command::Collapse { nodes, method })
+        }
+        "expand" => Ok(Command::Expand { node: single_name(rest)? }),
+        "rename" => {
+            let mut words = rest.split_whitespace();
+            let name = words.next().ok_or("Missing the renamed name.")?.into();
+            let new_name = words.next().ok_or("Missing the new name.")?.into();
+            expect_end(words)?;
+            Ok(Command::Rename { name, new_name })
+        }
+        "layout" => {
+            expect_end(rest.split_whitespace())?;
+            Ok(Command::Layout)
+        }
+        "expect" => {
+            let (node, expression) = assignment(rest)?;
+            Ok(Command::ExpectExpression { node, expression })
+        }
+        _ => Err(format!("Unknown command `{keyword}`.")),
+    }
+}
+
+/// Split the first word of the text from the rest.
+fn split_word(text: &str) -> (&str, &str) {
+    let text = text.trim();
+    match text.split_once(char::is_whitespace) {
+        Some((word, rest)) => (word, rest.trim()),
+        None => (text, ""),
+    }
+}
+
+/// Split `<name> = <expression>` text, if the part before the assignment is a single word.
+fn split_assignment(text: &str) -> Option<(String, String)> {
+    let (name, expression) = text.split_once(ASSIGNMENT)?;
+    let name = name.trim();
+    let is_name = !name.is_empty() && !name.contains(char::is_whitespace);
+    // The `==` operator is not an assignment.
+    let is_assignment = !expression.starts_with(ASSIGNMENT);
+    (is_name && is_assignment).then(|| (name.into(), expression.trim().into()))
+}
+
+fn assignment(text: &str) -> Result<(String, String), String> {
+    let (name, expression) = split_assignment(text).ok_or("Expected `<node> = <expression>`.")?;
+    Ok((name, non_empty(&expression)?))
+}
+
+fn connection(text: &str) -> Result<(String, TargetPort), String> {
+    let (source, target) =
+        text.split_once(CONNECTION_ARROW).ok_or("Expected `<node> -> <node>[.<argument>]`.")?;
+    let source = single_name(source)?;
+    let target = single_name(target)?;
+    let target = match target.split_once('.') {
+        Some((node, argument)) => {
+            let argument = match argument.parse() {
+                Ok(position) => ArgumentRef::Position(position),
+                Err(_) => ArgumentRef::Name(argument.into()),
+            };
+            TargetPort { node: node.into(), argument: Some(argument) }
+        }
+        None => TargetPort { node: target, argument: None },
+    };
+    Ok((source, target))
+}
+
+fn single_name(text: &str) -> Result<String, String> {
+    let mut words = text.split_whitespace();
+    let name = words.next().ok_or("Missing node name.")?;
+    expect_end(words)?;
+    Ok(name.into())
+}
+
+fn non_empty(expression: &str) -> Result<String, String> {
+    let expression = expression.trim();
+    if expression.is_empty() {
+        Err("Missing expression.".into())
+    } else {
+        Ok(expression.into())
+    }
+}
+
+fn expect_end<'a>(mut words: impl Iterator<Item = &'a str>) -> Result<(), String> {
+    match words.next() {
+        Some(word) => Err(format!("Unexpected `{word}`.")),
+        None => Ok(()),
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing_commands() {
+        let script = r#"
+            # A comment.
+            open Geometry.Shapes area
+            add sum = 2 + 2
+            add foo bar
+            set sum = 3 == 3
+            connect sum -> foo.1
+            disconnect sum -> foo.arg
+            move sum 10 -20.5
+            collapse sum foo into helper
+            expect helper = Main.helper
+            rename local.Project.Main.helper util
+            layout
+        "#;
+        let lines = parse(script).unwrap();
+        let numbers = lines.iter().map(|line| line.number).collect_vec();
+        assert_eq!(numbers, vec![3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13]);
+        let commands = lines.into_iter().map(|line| line.command).collect_vec();
+        let target = |argument| TargetPort { node: "foo".into(), argument: Some(argument) };
+        let expected = vec![
+            Command::Open {
+                module: Some(vec!["Geometry".into(), "Shapes".into()]),
+                method: Some("area".into()),
+            },
+            Command::AddNode { node: Some("sum".into()), expression: "2 + 2".into() },
+            Command::AddNode { node: None, expression: "foo bar".into() },
+            Command::SetExpression { node: "sum".into(), expression: "3 == 3".into() },
+            Command::Connect { source: "sum".into(), target: target(ArgumentRef::Position(1)) },
+            Command::Disconnect {
+                source: "sum".into(),
+                target: target(ArgumentRef::Name("arg".into())),
+            },
+            Command::MoveNode { node: "sum".into(), position: Vector2::new(10.0, -20.5) },
+            Command::Collapse { nodes: vec!["sum".into(), "foo".into()], method: "helper".into() },
+            Command::ExpectExpression {
+                node:       "helper".into(),
+                expression: "Main.helper".into(),
+            },
+            Command::Rename {
+                name:     "local.Project.Main.helper".into(),
+                new_name: "util".into(),
+            },
+            Command::Layout,
+        ];
+        assert_eq!(commands, expected);
+    }
+
+    #[test]
+    fn parsing_invalid_commands() {
+        let cases = [
+            "frobnicate foo",
+            "set foo",
+            "remove",
+            "remove foo bar",
+            "connect foo bar",
+            "move foo 1",
+            "move foo x 1",
+            "collapse into bar",
+            "collapse foo bar",
+            "layout now",
+        ];
+        for case in cases {
+            let script = format!("open\n{case}");
+            let error = parse(&script).expect_err(case);
+            assert_eq!(error.line, 2, "{case}");
+        }
+    }
+}
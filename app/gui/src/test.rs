@@ -246,6 +246,9 @@ pub mod mock {
             model::project::test::expect_qualified_name(&mut project, &self.project_name);
             model::project::test::expect_qualified_module_name(&mut project);
             model::project::test::expect_parser(&mut project, &self.parser);
+            // The mock module is the project's main module.
+            let main_module_path = module.path().clone_ref();
+            model::project::test::expect_main_module_path(&mut project, main_module_path);
             model::project::test::expect_module(&mut project, module);
             model::project::test::expect_execution_ctx(&mut project, execution_context);
             // Root ID is needed to generate module path used to get the module.
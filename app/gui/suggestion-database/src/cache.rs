@@ -0,0 +1,312 @@
+//! The on-disk cache of the suggestion database.
+//!
+//! Fetching the whole database from the Language Server takes a significant time when opening a
+//! project. Instead, the database may be stored in the project's directory and loaded on the next
+//! startup, and then reconciled with the Language Server by
+//! [`SuggestionDatabase::create_synchronized_from_cache`].
+//!
+//! The database's version is counted separately by each Language Server's database, so it does not
+//! identify the database's content alone. The cache is therefore valid only if it was written with
+//! the same engine and libraries, as described by its [`Origin`].
+//!
+//! The cache starts with the [`MAGIC`] bytes and the [`FORMAT_VERSION`] (a little-endian `u32`),
+//! followed by the origin, the database's version and entries encoded with [`bincode`], which is
+//! much more compact and faster to decode than JSON.
+
+use crate::prelude::*;
+use enso_profiler::prelude::*;
+
+use crate::entry;
+use crate::Entry;
+use crate::SuggestionDatabase;
+
+use bincode::Options;
+use engine_protocol::language_server::ContentRoot;
+use engine_protocol::language_server::SuggestionsDatabaseVersion;
+use enso_profiler as profiler;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The bytes identifying the suggestion database cache.
+pub const MAGIC: &[u8; 4] = b"ESDB";
+
+/// The version of the cache format. It must be increased on every change of the encoded data,
+/// including the changes of the [`Entry`] structure, so the outdated caches are not loaded.
+pub const FORMAT_VERSION: u32 = 3;
+
+/// The options of encoding the cache content.
+fn bincode_options() -> impl Options {
+    bincode::DefaultOptions::new().with_fixint_encoding()
+}
+
+
+
+// ==============
+// === Errors ===
+// ==============
+
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, Fail)]
+#[fail(display = "The data is not a suggestion database cache.")]
+pub struct NotACache;
+
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, Fail)]
+#[fail(display = "The suggestion database cache has unsupported format version {}.", _0)]
+pub struct UnsupportedFormatVersion(pub u32);
+
+
+
+// ==============
+// === Origin ===
+// ==============
+
+/// The environment of the Language Server the cached database was synchronized with.
+#[derive(Clone, Debug, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Origin {
+    /// The version of the engine.
+    pub engine_version: String,
+    /// The libraries available to the project, as `namespace.name:version`, sorted.
+    pub libraries:      Vec<String>,
+}
+
+impl Origin {
+    /// Describe the environment with the given engine version and the Language Server's content
+    /// roots, which include the libraries.
+    pub fn new<'a>(
+        engine_version: impl Display,
+        content_roots: impl IntoIterator<Item = &'a ContentRoot>,
+    ) -> Self {
+        let libraries = content_roots.into_iter().filter_map(|root| match root {
+            ContentRoot::Library { namespace, name, version, .. } =>
+                Some(format!("{namespace}.{name}:{version}")),
+            _ => None,
+        });
+        Self {
+            engine_version: engine_version.to_string(),
+            libraries:      libraries.sorted().collect(),
+        }
+    }
+}
+
+
+
+// ==============
+// === Encode ===
+// ==============
+
+/// The encoded part of the cache.
+#[derive(Debug, serde::Serialize)]
+struct CacheContent<'a> {
+    origin:  &'a Origin,
+    version: SuggestionsDatabaseVersion,
+    entries: Vec<(entry::Id, &'a Entry)>,
+}
+
+/// The decoded part of the cache.
+#[derive(Debug, serde::Deserialize)]
+struct DecodedCacheContent {
+    origin:  Origin,
+    version: SuggestionsDatabaseVersion,
+    entries: Vec<(entry::Id, Entry)>,
+}
+
+/// Encode the database synchronized with the Language Server of the given origin into the cache.
+#[profile(Detail)]
+pub fn encode(database: &SuggestionDatabase, origin: &Origin) -> FallibleResult<Vec<u8>> {
+    let entries = database.entries.borrow();
+    let entries = entries.iter().map(|(id, entry)| (*id, entry.as_ref())).sorted_by_key(|e| e.0);
+    let version = database.version();
+    let content = CacheContent { origin, version, entries: entries.collect() };
+    let mut cache = MAGIC.to_vec();
+    cache.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    bincode_options().serialize_into(&mut cache, &content)?;
+    Ok(cache)
+}
+
+/// Decode the database and its origin from the cache.
+#[profile(Detail)]
+pub fn decode(cache: &[u8]) -> FallibleResult<(Origin, SuggestionDatabase)> {
+    let cache = cache.strip_prefix(MAGIC.as_slice()).ok_or(NotACache)?;
+    let format_version = cache.get(..mem::size_of::<u32>()).ok_or(NotACache)?;
+    let content = &cache[format_version.len()..];
+    let format_version = u32::from_le_bytes(format_version.try_into()?);
+    if format_version != FORMAT_VERSION {
+        return Err(UnsupportedFormatVersion(format_version).into());
+    }
+    let DecodedCacheContent { origin, version, entries } =
+        bincode_options().deserialize(content)?;
+    Ok((origin, SuggestionDatabase::from_entries_with_version(entries, version)))
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::mock;
+
+    use double_representation::name::QualifiedName;
+    use engine_protocol::language_server;
+    use engine_protocol::language_server::response;
+    use engine_protocol::language_server::SuggestionDatabaseUpdatesEvent;
+    use engine_protocol::language_server::SuggestionEntry;
+    use engine_protocol::language_server::SuggestionsDatabaseEntry;
+    use enso_executor::test_utils::TestWithLocalPoolExecutor;
+
+    /// Check if both databases have the same version, entries and indices.
+    fn assert_same_databases(database: &SuggestionDatabase, expected: &SuggestionDatabase) {
+        assert_eq!(database.version(), expected.version());
+        let entries = |db: &SuggestionDatabase| db.keys().into_iter().sorted().collect_vec();
+        assert_eq!(entries(database), entries(expected));
+        for id in expected.keys() {
+            let entry = expected.lookup(id).unwrap();
+            assert_eq!(database.lookup(id).unwrap(), entry);
+            let by_name = database.lookup_by_qualified_name(&entry.qualified_name());
+            assert_eq!(by_name.map(|(id, _)| id).ok(), Some(id));
+            assert_eq!(database.lookup_hierarchy(id), expected.lookup_hierarchy(id));
+        }
+    }
+
+    fn ls_response(version: SuggestionsDatabaseVersion) -> response::GetSuggestionDatabase {
+        let module = "local.Project.Main";
+        let method = |id, name: &str| SuggestionsDatabaseEntry {
+            id,
+            suggestion: SuggestionEntry::Method {
+                external_id:   None,
+                name:          name.into(),
+                module:        module.into(),
+                arguments:     vec![language_server::SuggestionEntryArgument::new("x", "Any")],
+                self_type:     module.into(),
+                return_type:   "Standard.Base.Number".into(),
+                is_static:     true,
+                documentation: Some("ALIAS Foo\n\nThe method.".into()),
+                reexport:      None,
+            },
+        };
+        let module_entry = SuggestionsDatabaseEntry {
+            id:         1,
+            suggestion: SuggestionEntry::Module {
+                module:        module.into(),
+                documentation: None,
+                reexport:      None,
+            },
+        };
+        let entries = vec![module_entry, method(2, "foo"), method(3, "bar")];
+        response::GetSuggestionDatabase { entries, current_version: version }
+    }
+
+    #[test]
+    fn encoding_and_decoding() {
+        let database = mock::standard_db_mock();
+        let number = QualifiedName::from_text("Standard.Base.Number").unwrap();
+        let (id, _) = database.lookup_by_qualified_name(&number).unwrap();
+        let remove = entry::Update::Remove { id };
+        database.apply_update_event(SuggestionDatabaseUpdatesEvent {
+            updates:         vec![remove],
+            current_version: 7,
+        });
+        let origin = Origin {
+            engine_version: "0.0.0-dev".into(),
+            libraries:      vec!["Standard.Base:0.0.0-dev".into()],
+        };
+        let cache = encode(&database, &origin).unwrap();
+        let (decoded_origin, decoded) = decode(&cache).unwrap();
+        assert_eq!(decoded_origin, origin);
+        assert_same_databases(&decoded, &database);
+
+        let database = SuggestionDatabase::from_ls_response(ls_response(3));
+        let (_, decoded) = decode(&encode(&database, &origin).unwrap()).unwrap();
+        assert_same_databases(&decoded, &database);
+
+        assert!(decode(b"ESD").is_err());
+        assert!(decode(&cache[1..]).is_err());
+        let mut outdated = cache.clone();
+        outdated[MAGIC.len()] += 1;
+        assert!(decode(&outdated).unwrap_err().downcast::<UnsupportedFormatVersion>().is_ok());
+        let truncated = &cache[..cache.len() - 1];
+        assert!(decode(truncated).is_err());
+    }
+
+    /// Reconcile the database decoded from the cache with the Language Server's database of
+    /// the given version, and check if the result is the same as the freshly fetched database.
+    fn reconcile(cached: &SuggestionDatabase, current_version: SuggestionsDatabaseVersion) {
+        let fresh = SuggestionDatabase::from_ls_response(ls_response(current_version));
+        let client = language_server::MockClient::default();
+        client.expect.get_suggestions_database_version(move || {
+            Ok(response::GetSuggestionDatabaseVersion { current_version })
+        });
+        if cached.version() != current_version {
+            client.expect.get_suggestions_database(move || Ok(ls_response(current_version)));
+        }
+        let language_server = language_server::Connection::new_mock(client);
+        let (_, cached) = decode(&encode(cached, &default()).unwrap()).unwrap();
+        let mut fixture = TestWithLocalPoolExecutor::set_up();
+        fixture.run_task(async move {
+            let database =
+                SuggestionDatabase::create_synchronized_from_cache(&language_server, Some(cached));
+            assert_same_databases(&database.await.unwrap(), &fresh);
+        });
+        fixture.run_until_stalled();
+    }
+
+    #[test]
+    fn origin_of_cache() {
+        let library = |name: &str| ContentRoot::Library {
+            id:        default(),
+            namespace: "Standard".into(),
+            name:      name.into(),
+            version:   "0.0.0-dev".into(),
+        };
+        let roots = [ContentRoot::Project { id: default() }, library("Table"), library("Base")];
+        let origin = Origin::new("2023.1.1", &roots);
+        assert_eq!(origin.engine_version, "2023.1.1");
+        assert_eq!(origin.libraries, vec!["Standard.Base:0.0.0-dev", "Standard.Table:0.0.0-dev"]);
+    }
+
+    #[test]
+    fn reconciling_cached_database() {
+        // The Language Server's database did not change since the cache was written.
+        let cached = SuggestionDatabase::from_ls_response(ls_response(5));
+        reconcile(&cached, 5);
+
+        // The Language Server's database was updated since; the cached database catches up with
+        // the whole database fetched again.
+        let mut outdated = ls_response(4);
+        outdated.entries.truncate(2);
+        let cached = SuggestionDatabase::from_ls_response(outdated);
+        reconcile(&cached, 5);
+
+        // The entries were changed and renumbered.
+        let mut outdated = ls_response(4);
+        if let SuggestionEntry::Module { documentation, .. } = &mut outdated.entries[0].suggestion {
+            *documentation = Some("Outdated documentation.".into());
+        }
+        outdated.entries[1].id = 3;
+        outdated.entries[2].id = 2;
+        let mut removed_method = SuggestionsDatabaseEntry { id: 4, ..outdated.entries[1].clone() };
+        if let SuggestionEntry::Method { name, .. } = &mut removed_method.suggestion {
+            *name = "baz".into();
+        }
+        outdated.entries.push(removed_method);
+        let cached = SuggestionDatabase::from_ls_response(outdated);
+        reconcile(&cached, 5);
+
+        // The database updated with events is cached with the version of the last event.
+        let cached = SuggestionDatabase::from_ls_response(ls_response(4));
+        cached.apply_update_event(SuggestionDatabaseUpdatesEvent {
+            updates:         vec![],
+            current_version: 5,
+        });
+        reconcile(&cached, 5);
+    }
+}
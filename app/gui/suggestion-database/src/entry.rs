@@ -88,7 +88,7 @@ pub struct ModuleSpan {
 /// In order to make icon definitions more readable for non-programmer users, the builtin icon name
 /// is allowed to be formatted in arbitrary casing. Either `SNAKE_case`,`camelCase`, `Pascal_Case`
 /// etc. is allowed.
-#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct IconName {
     /// The name is kept in `PascalCase` to allow easy conversion into builtin icon ID.
     pascal_cased: ImString,
@@ -176,7 +176,7 @@ impl From<Import> for import::Info {
 // === Kind ===
 
 /// A type of suggestion entry.
-#[derive(Copy, Clone, Debug, Eq, PartialEq, ForEachVariant, serde::Serialize)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ForEachVariant, serde::Serialize, serde::Deserialize)]
 #[allow(missing_docs)]
 pub enum Kind {
     Type,
@@ -195,7 +195,7 @@ pub enum Kind {
 /// Methods are visible "Everywhere", as they are imported on a module level, so they are not
 /// specific to any particular span in the module file.
 /// However local variables and local function have limited visibility.
-#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize)]
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum Scope {
     /// The entry is visible in the whole module where it was defined. It can be also brought to
     /// other modules by import declarations.
@@ -214,7 +214,7 @@ pub enum Scope {
 // === Entry ===
 
 /// The Suggestion Database Entry.
-#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize)]
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct Entry {
     /// A type of suggestion.
     pub kind:          Kind,
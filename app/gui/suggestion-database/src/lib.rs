@@ -55,6 +55,7 @@ use language_server::types::SuggestionsDatabaseVersion;
 // === Export ===
 // ==============
 
+pub mod cache;
 pub mod documentation_ir;
 pub mod entry;
 pub mod example;
@@ -345,6 +346,13 @@ impl SuggestionDatabase {
         serde_json::to_string(&all_entries).unwrap()
     }
 
+    /// Create a database from the entries dumped by [`Self::dump_as_json`]. As the dump does not
+    /// contain the entries' IDs, the entries are numbered in the order of the dump.
+    pub fn new_from_json_dump(json: &str) -> FallibleResult<Self> {
+        let entries: Vec<Entry> = serde_json::from_str(json)?;
+        Ok(Self::from_entries_with_version(entries.into_iter().enumerate(), default()))
+    }
+
     /// Create a database filled with entries provided by the given iterator.
     pub fn new_from_entries<'a>(
         entries: impl IntoIterator<Item = (&'a SuggestionId, &'a Entry)>,
@@ -366,15 +374,99 @@ impl SuggestionDatabase {
         Ok(Self::from_ls_response(response))
     }
 
+    /// Create a new database synchronized with the Language Server, taking its initial content
+    /// from the `cached` database.
+    ///
+    /// The cached database is used as is if its version is the same as the current version of the
+    /// Language Server's database. Otherwise, the whole database is fetched, as the Language Server
+    /// does not provide the updates made since a given version, and the cached database catches up
+    /// with it by applying the differences as update events (see [`Self::catch_up`]). Once
+    /// created, the database is kept up to date by applying the update events, as with
+    /// [`Self::create_synchronized`].
+    ///
+    /// The caller is responsible for checking that the cache was written for the same Language
+    /// Server environment, see [`cache::Origin`].
+    pub async fn create_synchronized_from_cache(
+        language_server: &language_server::Connection,
+        cached: Option<Self>,
+    ) -> FallibleResult<Self> {
+        if let Some(cached) = cached {
+            let response = language_server.client.get_suggestions_database_version().await?;
+            let current_version = response.current_version;
+            if cached.version() == current_version {
+                info!("Using the cached suggestion database of version {current_version}.");
+                return Ok(cached);
+            }
+            info!(
+                "The cached suggestion database has version {}, while the current version is \
+                {current_version}. Catching up with the current database.",
+                cached.version()
+            );
+            let response = language_server.client.get_suggestions_database().await?;
+            cached.catch_up(response);
+            return Ok(cached);
+        }
+        Self::create_synchronized(language_server).await
+    }
+
+    /// Update the database to the content received from the Language Server. The differences are
+    /// applied as a single update event, so the subscribers are notified about the changed
+    /// entries only.
+    ///
+    /// The changed entries are removed and added again. So are all their descendants in the
+    /// hierarchy, so they are attached to the re-added entries.
+    fn catch_up(&self, response: language_server::response::GetSuggestionDatabase) {
+        let fresh: HashMap<_, _> =
+            response.entries.into_iter().map(|entry| (entry.id, entry.suggestion)).collect();
+        let mut removed = HashSet::new();
+        for id in self.keys() {
+            let cached = self.lookup(id).ok();
+            let fresh = fresh.get(&id).map(|entry| Entry::from_ls_entry(entry.clone()));
+            if cached.as_deref() != fresh.as_ref() {
+                removed.insert(id);
+            }
+        }
+        let mut to_visit = removed.iter().copied().collect_vec();
+        while let Some(id) = to_visit.pop() {
+            for child in self.lookup_hierarchy(id).unwrap_or_default() {
+                if removed.insert(child) {
+                    to_visit.push(child);
+                }
+            }
+        }
+        let is_new = |id: &entry::Id| self.lookup(*id).is_err();
+        let added = fresh.into_iter().filter(|(id, _)| removed.contains(id) || is_new(id));
+        let added = added.sorted_by_key(|(id, _)| *id);
+        let removed = removed.into_iter().sorted();
+        let removed = removed.map(|id| entry::Update::Remove { id });
+        let added = added
+            .map(|(id, suggestion)| entry::Update::Add { id, suggestion: Box::new(suggestion) });
+        let updates = removed.chain(added).collect();
+        self.apply_update_event(SuggestionDatabaseUpdatesEvent {
+            updates,
+            current_version: response.current_version,
+        });
+    }
+
     /// Create a new database model from response received from the Language Server.
     fn from_ls_response(response: language_server::response::GetSuggestionDatabase) -> Self {
+        let entries = response.entries.into_iter();
+        let entries =
+            entries.map(|ls_entry| (ls_entry.id, Entry::from_ls_entry(ls_entry.suggestion)));
+        Self::from_entries_with_version(entries, response.current_version)
+    }
+
+    /// Create a new database model with the given entries, being synchronized with the given
+    /// version of the Language Server's database.
+    fn from_entries_with_version(
+        new_entries: impl IntoIterator<Item = (entry::Id, Entry)>,
+        version: SuggestionsDatabaseVersion,
+    ) -> Self {
         let mut entries = HashMap::new();
         let mut qualified_name_to_id_map = QualifiedNameToIdMap::default();
         let mut method_pointer_to_id_map = MethodPointerToIdMap::default();
         let mut hierarchy_index = HierarchyIndex::default();
-        for ls_entry in response.entries {
-            let id = ls_entry.id;
-            let entry = Entry::from_ls_entry(ls_entry.suggestion);
+        for (id, entry) in new_entries {
             qualified_name_to_id_map.set_and_warn_if_existed(&entry.qualified_name(), id);
             method_pointer_to_id_map.set(&entry, id);
             entries.insert(id, Rc::new(entry));
@@ -391,11 +483,16 @@ impl SuggestionDatabase {
             method_pointer_to_id_map: RefCell::new(method_pointer_to_id_map),
             hierarchy_index:          RefCell::new(hierarchy_index),
             examples:                 RefCell::new(examples),
-            version:                  Cell::new(response.current_version),
+            version:                  Cell::new(version),
             notifications:            default(),
         }
     }
 
+    /// The version of the Language Server's database this database is synchronized with.
+    pub fn version(&self) -> SuggestionsDatabaseVersion {
+        self.version.get()
+    }
+
     /// Subscribe for notifications about changes in the database.
     pub fn subscribe(&self) -> Subscriber<Notification> {
         self.notifications.subscribe()
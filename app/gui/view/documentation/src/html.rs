@@ -26,6 +26,15 @@ use horrorshow::owned_html;
 
 
 
+// ==================
+// === Stylesheet ===
+// ==================
+
+/// The stylesheet of the rendered documentation.
+pub const STYLESHEET: &str = include_str!("../assets/styles.css");
+
+
+
 // =============
 // === Icons ===
 // =============
@@ -48,6 +57,37 @@ fn svg_icon(content: &'static str, class: &'static str) -> impl Render {
 
 
 
+// =============
+// === Links ===
+// =============
+
+/// Gives the address of the documentation page of the entry with given name, if there is one.
+///
+/// In the IDE, the links between documentation pages are handled by the documentation panel,
+/// which recognizes them by the `id` attribute (see [`anchor_name`]), so no address is given.
+/// The static documentation pages link to each other using the addresses.
+pub type PageAddress<'a> = &'a dyn Fn(&QualifiedName) -> Option<String>;
+
+/// A link to the documentation of the entry with given name.
+fn link<'a>(
+    name: &QualifiedName,
+    class: &'static str,
+    page_address: PageAddress,
+    content: impl Render + 'a,
+) -> impl Render + 'a {
+    let id = anchor_name(name);
+    let href = page_address(name);
+    owned_html! {
+        @ if let Some(href) = &href {
+            a(id=&id, class=class, href=href) { : &content; }
+        } else {
+            a(id=&id, class=class) { : &content; }
+        }
+    }
+}
+
+
+
 // ==============
 // === Render ===
 // ==============
@@ -55,9 +95,16 @@ fn svg_icon(content: &'static str, class: &'static str) -> impl Render {
 /// Render entry documentation to HTML code.
 #[profile(Detail)]
 pub fn render(docs: &EntryDocumentation) -> String {
+    render_with_page_addresses(docs, &|_| None)
+}
+
+/// Render entry documentation to HTML code, with the links to other entries pointing to the
+/// addresses of their documentation pages.
+#[profile(Detail)]
+pub fn render_with_page_addresses(docs: &EntryDocumentation, page_address: PageAddress) -> String {
     let html = match docs {
         EntryDocumentation::Placeholder => String::from("No documentation available."),
-        EntryDocumentation::Docs(docs) => render_documentation(docs.clone_ref()),
+        EntryDocumentation::Docs(docs) => render_documentation(docs.clone_ref(), page_address),
     };
     match validate_utf8(&html) {
         Ok(_) => html,
@@ -74,10 +121,11 @@ fn validate_utf8(s: &str) -> Result<&str, std::str::Utf8Error> {
     std::str::from_utf8(bytes)
 }
 
-fn render_documentation(docs: Documentation) -> String {
+fn render_documentation(docs: Documentation, page_address: PageAddress) -> String {
     match docs {
-        Documentation::Module(module_docs) => render_module_documentation(&module_docs),
-        Documentation::Type { docs, .. } => render_type_documentation(&docs),
+        Documentation::Module(module_docs) =>
+            render_module_documentation(&module_docs, page_address),
+        Documentation::Type { docs, .. } => render_type_documentation(&docs, page_address),
         Documentation::Function(docs) => render_function_documentation(&docs),
         Documentation::Local(docs) => render_local_documentation(&docs),
         Documentation::Constructor { docs, .. } => render_function_documentation(&docs),
@@ -97,15 +145,15 @@ fn render_documentation(docs: Documentation) -> String {
 /// - Synopsis and a list of constructors.
 /// - Methods.
 /// - Examples.
-fn render_type_documentation(docs: &TypeDocumentation) -> String {
+fn render_type_documentation(docs: &TypeDocumentation, page_address: PageAddress) -> String {
     let constructors_exist = !docs.constructors.is_empty();
     let methods_exist = !docs.methods.is_empty();
     let examples_exist = !docs.examples.is_empty();
     let synopsis = &docs.synopsis;
     let constructors = &docs.constructors;
     let synopsis = section_content(type_synopsis(synopsis));
-    let constructors = section_content(list_of_functions(constructors));
-    let methods = section_content(list_of_functions(&docs.methods));
+    let constructors = section_content(list_of_functions(constructors, page_address));
+    let methods = section_content(list_of_functions(&docs.methods, page_address));
     let examples = section_content(list_of_examples(&docs.examples));
     let tags = section_content(list_of_tags(&docs.tags));
 
@@ -154,11 +202,14 @@ fn type_synopsis<'a>(synopsis: &'a Synopsis) -> Box<dyn Render + 'a> {
 }
 
 /// A list of methods defined for the type.
-fn list_of_functions<'a>(functions: &'a [Function]) -> Box<dyn Render + 'a> {
+fn list_of_functions<'a>(
+    functions: &'a [Function],
+    page_address: PageAddress<'a>,
+) -> Box<dyn Render + 'a> {
     box_html! {
         ul(class="unordered-list") {
             @ for f in functions.iter() {
-                : single_function(f);
+                : single_function(f, page_address);
             }
         }
     }
@@ -167,17 +218,20 @@ fn list_of_functions<'a>(functions: &'a [Function]) -> Box<dyn Render + 'a> {
 /// A documentation for a single method in the list.
 /// If the first [`DocSection`] is of type [`DocSection::Paragraph`], it is rendered on the first
 /// line, after the list of arguments.
-fn single_function<'a>(function: &'a Function) -> Box<dyn Render + 'a> {
+fn single_function<'a>(
+    function: &'a Function,
+    page_address: PageAddress<'a>,
+) -> Box<dyn Render + 'a> {
     let first = match &function.synopsis.as_ref()[..] {
         [DocSection::Paragraph { body }, ..] => Some(body),
         _ => None,
     };
     box_html! {
         li(class="method-item") {
-            a(id=anchor_name(&function.name), class="link method") {
+            : link(&function.name, "link method", page_address, owned_html! {
                 span(class="entry-name") { : function.name.name(); }
                 span(class="arguments") { : arguments_list(&function.arguments); }
-            }
+            });
             @ if let Some(first) = first {
                 : ": "; : Raw(first);
             }
@@ -196,13 +250,13 @@ fn single_function<'a>(function: &'a Function) -> Box<dyn Render + 'a> {
 /// - Types.
 /// - Functions.
 /// - Examples.
-fn render_module_documentation(docs: &ModuleDocumentation) -> String {
+fn render_module_documentation(docs: &ModuleDocumentation, page_address: PageAddress) -> String {
     let types_exist = !docs.types.is_empty();
     let methods_exist = !docs.methods.is_empty();
     let examples_exist = !docs.examples.is_empty();
     let synopsis = section_content(module_synopsis(&docs.synopsis));
-    let types = section_content(list_of_types(&docs.types));
-    let methods = section_content(list_of_functions(&docs.methods));
+    let types = section_content(list_of_types(&docs.types, page_address));
+    let methods = section_content(list_of_functions(&docs.methods, page_address));
     let examples = section_content(list_of_examples(&docs.examples));
     let tags = section_content(list_of_tags(&docs.tags));
     let content = owned_html! {
@@ -225,24 +279,27 @@ fn render_module_documentation(docs: &ModuleDocumentation) -> String {
 }
 
 /// A list of types defined in the module.
-fn list_of_types<'a>(types: &'a Types) -> Box<dyn Render + 'a> {
+fn list_of_types<'a>(types: &'a Types, page_address: PageAddress<'a>) -> Box<dyn Render + 'a> {
     box_html! {
         ul(class="unordered-list") {
             @ for type_ in types.iter() {
-                : single_type(type_);
+                : single_type(type_, page_address);
             }
         }
     }
 }
 
 /// A single type in the list.
-fn single_type<'a>(type_: &'a TypeDocumentation) -> Box<dyn Render + 'a> {
+fn single_type<'a>(
+    type_: &'a TypeDocumentation,
+    page_address: PageAddress<'a>,
+) -> Box<dyn Render + 'a> {
     box_html! {
         li(class="type-item") {
-            a(id=anchor_name(&type_.name), class="link type") {
+            : link(&type_.name, "link type", page_address, owned_html! {
                 span(class="entry-name") { : type_.name.name(); }
                 span(class="arguments") { : arguments_list(&type_.arguments); }
-            }
+            });
         }
     }
 }
@@ -148,9 +148,8 @@ impl Model {
 
     /// Add `<style>` tag with the stylesheet to the `style_container`.
     fn load_css_stylesheet(&self) {
-        let stylesheet = include_str!("../assets/styles.css");
         let element = web::document.create_element_or_panic("style");
-        element.set_inner_html(stylesheet);
+        element.set_inner_html(html::STYLESHEET);
         self.style_container.append_or_warn(&element);
     }
 
@@ -151,6 +151,8 @@ ensogl::define_endpoints! {
         accept_searcher_input(),
         /// Dump the suggestion database in JSON to the console.
         dump_suggestion_database(),
+        /// Forget the statistics of components accepted in the Component Browser.
+        reset_component_usage_statistics(),
         /// Start the language server profiling
         start_language_server_profiling(),
         /// Stop the language server profiling
@@ -184,6 +186,8 @@ ensogl::define_endpoints! {
         current_shortcut               (Option<ImString>),
         /// Request the controller to dump the suggestion database in JSON to the console.
         request_dump_suggestion_database(),
+        /// Request the controller to forget the statistics of accepted components.
+        request_reset_component_usage_statistics(),
     }
 }
 
@@ -710,6 +714,8 @@ impl View {
             frp.source.debug_mode <+ debug_mode;
             popup.is_enabled <+ debug_mode;
             frp.source.request_dump_suggestion_database <+ frp.dump_suggestion_database;
+            frp.source.request_reset_component_usage_statistics <+
+                frp.reset_component_usage_statistics;
         }
         self
     }
@@ -816,6 +822,7 @@ impl application::View for View {
             (Press, "debug_mode", "ctrl shift enter", "debug_push_breadcrumb"),
             (Press, "debug_mode", "ctrl shift b", "debug_pop_breadcrumb"),
             (Press, "debug_mode", "ctrl shift u", "dump_suggestion_database"),
+            (Press, "debug_mode", "ctrl shift k", "reset_component_usage_statistics"),
             (Press, "", "cmd alt ,", "start_language_server_profiling"),
             (Press, "", "cmd alt .", "stop_language_server_profiling"),
         ]
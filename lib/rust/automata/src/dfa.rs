@@ -165,6 +165,180 @@ impl From<&Nfa> for Dfa {
 
 
 
+// ====================
+// === Minimization ===
+// ====================
+
+impl Dfa {
+    /// Construct an equivalent DFA with the minimal number of states, for an automaton matching
+    /// a set of rules. The `rules` are the NFA states accepting the rules, like the states returned
+    /// by [`Nfa::new_pattern`], in the order of their priority. The rule accepted in a DFA state is
+    /// the first of the `rules` it was constructed from. Two states are equivalent if they accept
+    /// the same rule and their transitions lead to equivalent states. See
+    /// [`Dfa::minimize_by_key`] to learn more.
+    pub fn minimize(&self, rules: &[nfa::State]) -> Dfa {
+        self.minimize_by_key(|sources| rules.iter().find(|rule| sources.contains(rule)).copied())
+    }
+
+    /// Construct an equivalent DFA with the minimal number of states, using the
+    /// [Hopcroft's algorithm](https://en.wikipedia.org/wiki/DFA_minimization#Hopcroft's_algorithm).
+    /// Blocks of states are refined with the smaller half of every split block, but splitting a
+    /// block takes time proportional to its size, so the worst-case complexity is `O(m * n^2)`,
+    /// where `n` is the number of states and `m` is the number of alphabet divisions.
+    ///
+    /// The `key` function is called with the NFA states each DFA state was constructed from.
+    /// States with different keys are never merged, so the key should describe everything that
+    /// distinguishes the states for the user of the automaton, like the rule accepted in the
+    /// state. The sources of merged states are joined.
+    ///
+    /// The invalid state is treated as a state without sources. All states equivalent to it are
+    /// removed, and transitions to them are replaced with transitions to the invalid state.
+    pub fn minimize_by_key<K: Eq + Hash>(&self, key: impl Fn(&[nfa::State]) -> K) -> Dfa {
+        let partition = Partition::new(self, key);
+        let sink = partition.block_of[self.links.rows];
+        let start = partition.block_of[Self::START_STATE.id()];
+
+        // Blocks are numbered in the order of the breadth-first traversal from the start block,
+        // so the result does not depend on the order of the refinement.
+        let mut new_ids = vec![State::INVALID; partition.blocks.len()];
+        let mut order = vec![start];
+        new_ids[start] = Self::START_STATE;
+        let mut i = 0;
+        while i < order.len() {
+            let representative = partition.blocks[order[i]][0];
+            for column in 0..self.links.columns {
+                let target = partition.block_of[self.target(representative, column)];
+                if target != sink && new_ids[target].is_invalid() {
+                    new_ids[target] = State::new(order.len());
+                    order.push(target);
+                }
+            }
+            i += 1;
+        }
+
+        let mut links = Matrix::new(order.len(), self.links.columns);
+        let mut sources = Vec::with_capacity(order.len());
+        for (row, &block) in order.iter().enumerate() {
+            let states = &partition.blocks[block];
+            if block != sink {
+                for column in 0..self.links.columns {
+                    let target = partition.block_of[self.target(states[0], column)];
+                    links[(row, column)] = new_ids[target];
+                }
+            }
+            let block_sources = states.iter().filter_map(|&state| self.sources.get(state));
+            let mut block_sources = block_sources.flatten().copied().collect_vec();
+            block_sources.sort();
+            block_sources.dedup();
+            sources.push(block_sources);
+        }
+        let alphabet = self.alphabet.clone();
+        Dfa { alphabet, links, sources }
+    }
+
+    /// The target of the transition, with the invalid state represented as the row index past the
+    /// last state.
+    fn target(&self, state: usize, column: usize) -> usize {
+        match self.links.safe_index(state, column) {
+            Some(target) if !target.is_invalid() => target.id(),
+            _ => self.links.rows,
+        }
+    }
+}
+
+
+// === Partition ===
+
+/// A partition of DFA states into blocks of equivalent states, used by [`Dfa::minimize_by_key`].
+/// The invalid state is represented as the state with the index equal to the number of rows of
+/// the transition matrix.
+#[derive(Clone, Debug)]
+struct Partition {
+    blocks:   Vec<Vec<usize>>,
+    block_of: Vec<usize>,
+}
+
+impl Partition {
+    /// Compute the coarsest partition of the DFA states consistent with the `key` function and
+    /// the transitions.
+    fn new<K: Eq + Hash>(dfa: &Dfa, key: impl Fn(&[nfa::State]) -> K) -> Self {
+        let state_count = dfa.links.rows + 1;
+        let columns = dfa.links.columns;
+        let no_sources: &[nfa::State] = &[];
+
+        let mut blocks: Vec<Vec<usize>> = vec![];
+        let mut block_of = Vec::with_capacity(state_count);
+        let mut block_by_key = HashMap::<K, usize>::new();
+        for state in 0..state_count {
+            let sources = dfa.sources.get(state).map_or(no_sources, |sources| &sources[..]);
+            let block = *block_by_key.entry(key(sources)).or_insert_with(|| {
+                blocks.push(vec![]);
+                blocks.len() - 1
+            });
+            blocks[block].push(state);
+            block_of.push(block);
+        }
+
+        // For every symbol division and every state, the states transitioning to it.
+        let mut inverse = vec![vec![vec![]; state_count]; columns];
+        for state in 0..state_count {
+            for (column, inverse) in inverse.iter_mut().enumerate() {
+                inverse[dfa.target(state, column)].push(state);
+            }
+        }
+
+        let mut worklist = (0..blocks.len()).collect_vec();
+        let mut in_worklist = vec![true; blocks.len()];
+        let mut in_preimage = vec![false; state_count];
+        while let Some(splitter) = worklist.pop() {
+            in_worklist[splitter] = false;
+            // The splitter may be split itself while processing it, so its states are copied.
+            let splitter = blocks[splitter].clone();
+            for inverse in &inverse {
+                let preimage = splitter.iter().flat_map(|&state| &inverse[state]);
+                let preimage = preimage.copied().collect_vec();
+                let mut touched = vec![];
+                for &state in &preimage {
+                    if !in_preimage[state] {
+                        in_preimage[state] = true;
+                        touched.push(block_of[state]);
+                    }
+                }
+                touched.sort_unstable();
+                touched.dedup();
+                for block in touched {
+                    let (inside, outside): (Vec<_>, Vec<_>) =
+                        blocks[block].iter().partition(|&&state| in_preimage[state]);
+                    if outside.is_empty() {
+                        continue;
+                    }
+                    let new_block = blocks.len();
+                    for &state in &inside {
+                        block_of[state] = new_block;
+                    }
+                    let smaller = if inside.len() <= outside.len() { new_block } else { block };
+                    blocks[block] = outside;
+                    blocks.push(inside);
+                    in_worklist.push(false);
+                    if in_worklist[block] {
+                        worklist.push(new_block);
+                        in_worklist[new_block] = true;
+                    } else {
+                        worklist.push(smaller);
+                        in_worklist[smaller] = true;
+                    }
+                }
+                for state in preimage {
+                    in_preimage[state] = false;
+                }
+            }
+        }
+        Self { blocks, block_of }
+    }
+}
+
+
+
 // =============
 // === Tests ===
 // =============
@@ -323,6 +497,72 @@ pub mod tests {
         assert_eq!(get_name(&nfa, &dfa, make_state(4)), Some(&String::from("rule_2")));
     }
 
+    #[test]
+    fn dfa_minimize_already_minimal() {
+        let nfa = nfa::tests::simple_rules();
+        let dfa = Dfa::from(&nfa.nfa);
+        assert_eq!(dfa.minimize(&nfa.pattern_state_ids), dfa);
+    }
+
+    #[test]
+    fn dfa_minimize_merges_states_accepting_the_same_rule() {
+        let nfa = nfa::tests::complex_rules();
+        let dfa = Dfa::from(&nfa.nfa);
+        let minimal = dfa.minimize(&nfa.pattern_state_ids);
+        // The states after reading the first and the following letters of a word are merged.
+        let expected = Matrix::from(vec![
+            vec![1, 2, 1, 1, 1, 1, 3],
+            vec![invalid(), invalid(), invalid(), invalid(), invalid(), invalid(), invalid()],
+            vec![invalid(), invalid(), invalid(), 4, 5, invalid(), invalid()],
+            vec![invalid(), invalid(), invalid(), invalid(), invalid(), invalid(), invalid()],
+            vec![invalid(), invalid(), invalid(), 4, invalid(), invalid(), invalid()],
+            vec![invalid(), invalid(), invalid(), invalid(), 5, invalid(), invalid()],
+        ]);
+        assert_same_matrix(&minimal, &expected);
+        let accepted_rule = |dfa: &Dfa, state: usize| {
+            let rules = &nfa.pattern_state_ids;
+            rules.iter().position(|rule| dfa.sources[state].contains(rule))
+        };
+        let accepted_rules = (0..6).map(|state| accepted_rule(&minimal, state)).collect_vec();
+        assert_eq!(accepted_rules, vec![None, Some(3), Some(3), Some(2), Some(0), Some(1)]);
+        assert_eq!(accepted_rule(&dfa, 6), Some(0));
+        assert_eq!(accepted_rule(&dfa, 7), Some(1));
+    }
+
+    #[test]
+    fn dfa_minimize_merges_equivalent_states() {
+        let nfa = nfa::tests::pattern_or();
+        let dfa = Dfa::from(&nfa.nfa);
+        let minimal = dfa.minimize_by_key(|sources| sources.contains(&nfa.end_state_id));
+        let expected = Matrix::from(vec![vec![invalid(), 1, invalid(), 1, invalid()], vec![
+            invalid(),
+            invalid(),
+            invalid(),
+            invalid(),
+            invalid(),
+        ]]);
+        assert_same_matrix(&minimal, &expected);
+        let mut merged_sources = [dfa.sources[1].clone(), dfa.sources[2].clone()].concat();
+        merged_sources.sort();
+        merged_sources.dedup();
+        assert_eq!(minimal.sources[1], merged_sources);
+    }
+
+    #[test]
+    fn dfa_minimize_removes_dead_states() {
+        let nfa = nfa::tests::complex_rules();
+        let dfa = Dfa::from(&nfa.nfa);
+        // Only words of `b` are accepted, so all other states are equivalent to the invalid one.
+        let b_word = nfa.pattern_state_ids[1];
+        let minimal = dfa.minimize_by_key(|sources| sources.contains(&b_word));
+        let expected = Matrix::from(vec![
+            vec![invalid(), 1, invalid(), invalid(), invalid(), invalid(), invalid()],
+            vec![invalid(), invalid(), invalid(), invalid(), 2, invalid(), invalid()],
+            vec![invalid(), invalid(), invalid(), invalid(), 2, invalid(), invalid()],
+        ]);
+        assert_same_matrix(&minimal, &expected);
+    }
+
     // === The Benchmarks ===
 
     #[bench]
@@ -364,4 +604,11 @@ pub mod tests {
     fn bench_to_dfa_complex_rules(bencher: &mut Bencher) {
         bencher.iter(|| Dfa::from(&nfa::tests::complex_rules().nfa))
     }
+
+    #[bench]
+    fn bench_minimize_complex_rules(bencher: &mut Bencher) {
+        let nfa = nfa::tests::complex_rules();
+        let dfa = Dfa::from(&nfa.nfa);
+        bencher.iter(|| dfa.minimize(&nfa.pattern_state_ids))
+    }
 }
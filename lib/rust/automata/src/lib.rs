@@ -27,6 +27,7 @@ pub mod data;
 pub mod dfa;
 pub mod nfa;
 pub mod pattern;
+pub mod regex;
 pub mod state;
 pub mod symbol;
 
@@ -34,4 +35,5 @@ pub use dfa::Dfa;
 pub use enso_prelude as prelude;
 pub use nfa::Nfa;
 pub use pattern::*;
+pub use regex::Regex;
 pub use symbol::*;
@@ -0,0 +1,650 @@
+//! A parser of a subset of the regular expression syntax, and a matcher built from the parsed
+//! expressions.
+//!
+//! The supported syntax is:
+//! - Literal characters, including any Unicode characters.
+//! - Escapes of the special characters (like `\*` or `\[`), the `\n`, `\r`, `\t` and `\0` control
+//!   characters, and Unicode code points written as `\u{1F600}`.
+//! - The `\d`, `\w` and `\s` ASCII classes, and their negations `\D`, `\W` and `\S`.
+//! - The `.` class, matching any character except the new line.
+//! - Character classes, like `[abc]`, `[a-z0-9_]`, `[\u{391}-\u{3A9}]` or the negated `[^"\\]`.
+//! - Alternation `a|b`, groups `(ab)` and non-capturing groups `(?:ab)`. Groups do not capture
+//!   anything, as automata do not support capturing.
+//! - Repetitions `*`, `+`, `?`, `{n}`, `{n,}` and `{n,m}`.
+//!
+//! Anchors, backreferences, lookarounds, and Unicode properties are not supported.
+
+use crate::prelude::*;
+
+use crate::dfa;
+use crate::dfa::Dfa;
+use crate::nfa::Nfa;
+use crate::pattern::Pattern;
+use crate::symbol::Symbol;
+
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The maximal valid Unicode code point.
+const MAX_CHAR: u32 = char::MAX as u32;
+
+
+
+// =============
+// === Error ===
+// =============
+
+/// An error of parsing a regular expression. Positions are byte offsets in the parsed expression.
+#[derive(Clone, Debug, Eq, Fail, PartialEq)]
+#[allow(missing_docs)]
+pub enum ParseError {
+    #[fail(display = "Unexpected end of the regular expression.")]
+    UnexpectedEnd,
+    #[fail(display = "Unexpected character {:?} at position {}.", character, position)]
+    UnexpectedChar { character: char, position: usize },
+    #[fail(display = "Nothing to repeat at position {}.", position)]
+    NothingToRepeat { position: usize },
+    #[fail(display = "Unsupported escape sequence at position {}.", position)]
+    InvalidEscape { position: usize },
+    #[fail(display = "Invalid character range at position {}.", position)]
+    InvalidRange { position: usize },
+    #[fail(display = "Invalid repetition bounds at position {}.", position)]
+    InvalidRepetition { position: usize },
+}
+
+
+
+// ======================
+// === CharacterClass ===
+// ======================
+
+/// A set of characters, represented as sorted, disjoint and non-adjacent ranges of code points.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct CharacterClass {
+    ranges: Vec<(u32, u32)>,
+}
+
+impl CharacterClass {
+    fn new(ranges: impl IntoIterator<Item = (u32, u32)>) -> Self {
+        let mut ranges = ranges.into_iter().collect_vec();
+        ranges.sort_unstable();
+        let mut normalized: Vec<(u32, u32)> = vec![];
+        for (start, end) in ranges {
+            match normalized.last_mut() {
+                Some(last) if start <= last.1.saturating_add(1) => last.1 = last.1.max(end),
+                _ => normalized.push((start, end)),
+            }
+        }
+        Self { ranges: normalized }
+    }
+
+    fn char(char: char) -> Self {
+        Self::new([(char as u32, char as u32)])
+    }
+
+    fn digit() -> Self {
+        Self::new([('0' as u32, '9' as u32)])
+    }
+
+    fn word() -> Self {
+        let ranges = [('0', '9'), ('A', 'Z'), ('_', '_'), ('a', 'z')];
+        Self::new(ranges.iter().map(|(start, end)| (*start as u32, *end as u32)))
+    }
+
+    fn space() -> Self {
+        Self::new([('\t' as u32, '\r' as u32), (' ' as u32, ' ' as u32)])
+    }
+
+    fn any_but_newline() -> Self {
+        Self::char('\n').negated()
+    }
+
+    fn extend(&mut self, other: &Self) {
+        *self = Self::new(self.ranges.iter().chain(&other.ranges).copied());
+    }
+
+    fn negated(&self) -> Self {
+        let mut ranges = vec![];
+        let mut next = 0;
+        for &(start, end) in &self.ranges {
+            if start > next {
+                ranges.push((next, start - 1));
+            }
+            next = end + 1;
+        }
+        if next <= MAX_CHAR {
+            ranges.push((next, MAX_CHAR));
+        }
+        Self { ranges }
+    }
+
+    fn to_pattern(&self) -> Pattern {
+        let ranges = self
+            .ranges
+            .iter()
+            .map(|&(start, end)| Pattern::symbols(Symbol::from(start)..=Symbol::from(end)));
+        let mut ranges = ranges.collect_vec();
+        match ranges.len() {
+            0 => Pattern::never(),
+            1 => ranges.remove(0),
+            _ => Pattern::Or(ranges),
+        }
+    }
+}
+
+
+
+// ==============
+// === Parser ===
+// ==============
+
+/// Parse the regular expression into a [`Pattern`]. See the module docs to learn about the
+/// supported syntax.
+pub fn parse(regex: &str) -> Result<Pattern, ParseError> {
+    let mut parser = Parser { chars: regex.char_indices().peekable(), len: regex.len() };
+    let pattern = parser.alternation()?;
+    match parser.chars.next() {
+        None => Ok(pattern),
+        Some((position, character)) => Err(ParseError::UnexpectedChar { character, position }),
+    }
+}
+
+/// A recursive descent parser of regular expressions.
+#[derive(Debug)]
+struct Parser<'a> {
+    chars: Peekable<CharIndices<'a>>,
+    len:   usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().map(|(_, char)| *char)
+    }
+
+    fn position(&mut self) -> usize {
+        let len = self.len;
+        self.chars.peek().map_or(len, |(position, _)| *position)
+    }
+
+    fn next(&mut self) -> Result<char, ParseError> {
+        self.chars.next().map(|(_, char)| char).ok_or(ParseError::UnexpectedEnd)
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), ParseError> {
+        let position = self.position();
+        match self.next()? {
+            character if character == expected => Ok(()),
+            character => Err(ParseError::UnexpectedChar { character, position }),
+        }
+    }
+
+    fn consume(&mut self, expected: char) -> bool {
+        let found = self.peek() == Some(expected);
+        if found {
+            self.chars.next();
+        }
+        found
+    }
+
+    /// `alternation := sequence ('|' sequence)*`
+    fn alternation(&mut self) -> Result<Pattern, ParseError> {
+        let mut alternatives = vec![self.sequence()?];
+        while self.consume('|') {
+            alternatives.push(self.sequence()?);
+        }
+        Ok(if alternatives.len() == 1 { alternatives.remove(0) } else { Pattern::Or(alternatives) })
+    }
+
+    /// `sequence := repetition*`
+    fn sequence(&mut self) -> Result<Pattern, ParseError> {
+        let mut items = vec![];
+        while !matches!(self.peek(), None | Some('|') | Some(')')) {
+            items.push(self.repetition()?);
+        }
+        Ok(match items.len() {
+            0 => Pattern::always(),
+            1 => items.remove(0),
+            _ => Pattern::Seq(items),
+        })
+    }
+
+    /// `repetition := atom ('*' | '+' | '?' | '{' bounds '}')*`
+    fn repetition(&mut self) -> Result<Pattern, ParseError> {
+        let mut pattern = self.atom()?;
+        loop {
+            let position = self.position();
+            pattern = match self.peek() {
+                Some('*') => pattern.many(),
+                Some('+') => pattern.many1(),
+                Some('?') => pattern.opt(),
+                Some('{') => {
+                    self.chars.next();
+                    let (min, max) = self.repetition_bounds(position)?;
+                    match max {
+                        None => Pattern::repeat(&pattern, min) >> pattern.many(),
+                        Some(max) if max == min => Pattern::repeat(&pattern, min),
+                        Some(max) => Pattern::repeat_between(&pattern, min, max + 1),
+                    }
+                }
+                _ => break Ok(pattern),
+            };
+            if position == self.position() {
+                self.chars.next();
+            }
+        }
+    }
+
+    /// Parse the `n}`, `n,}` or `n,m}` part of a repetition.
+    fn repetition_bounds(&mut self, position: usize) -> Result<(usize, Option<usize>), ParseError> {
+        let invalid = ParseError::InvalidRepetition { position };
+        let min = self.number().ok_or_else(|| invalid.clone())?;
+        let max = if self.consume(',') {
+            if self.peek() == Some('}') {
+                None
+            } else {
+                Some(self.number().ok_or_else(|| invalid.clone())?)
+            }
+        } else {
+            Some(min)
+        };
+        self.expect('}')?;
+        match max {
+            Some(max) if max < min => Err(invalid),
+            _ => Ok((min, max)),
+        }
+    }
+
+    fn number(&mut self) -> Option<usize> {
+        let mut digits = String::new();
+        while let Some(digit) = self.peek().filter(|char| char.is_ascii_digit()) {
+            digits.push(digit);
+            self.chars.next();
+        }
+        digits.parse().ok()
+    }
+
+    /// `atom := '(' ('?:')? alternation ')' | '[' class ']' | '.' | escape | character`
+    fn atom(&mut self) -> Result<Pattern, ParseError> {
+        let position = self.position();
+        match self.next()? {
+            '(' => {
+                if self.consume('?') {
+                    self.expect(':')?;
+                }
+                let pattern = self.alternation()?;
+                self.expect(')')?;
+                Ok(pattern)
+            }
+            '[' => Ok(self.class()?.to_pattern()),
+            '.' => Ok(CharacterClass::any_but_newline().to_pattern()),
+            '\\' => Ok(self.escape(position)?.to_pattern()),
+            '*' | '+' | '?' | '{' => Err(ParseError::NothingToRepeat { position }),
+            character @ (']' | '}') => Err(ParseError::UnexpectedChar { character, position }),
+            character => Ok(Pattern::char(character)),
+        }
+    }
+
+    /// Parse a character class, assuming that the opening bracket was already consumed.
+    fn class(&mut self) -> Result<CharacterClass, ParseError> {
+        let negated = self.consume('^');
+        let mut class = CharacterClass::default();
+        let mut first = true;
+        while first || self.peek() != Some(']') {
+            first = false;
+            let position = self.position();
+            let start = self.class_item()?;
+            let is_range = self.peek() == Some('-') && {
+                let mut lookahead = self.chars.clone();
+                lookahead.next();
+                !matches!(lookahead.peek(), Some((_, ']')) | None)
+            };
+            if is_range {
+                self.chars.next();
+                let end = self.class_item()?;
+                match (single_char(&start), single_char(&end)) {
+                    (Some(start), Some(end)) if start <= end =>
+                        class.extend(&CharacterClass::new([(start, end)])),
+                    _ => return Err(ParseError::InvalidRange { position }),
+                }
+            } else {
+                class.extend(&start);
+            }
+        }
+        self.expect(']')?;
+        Ok(if negated { class.negated() } else { class })
+    }
+
+    /// Parse a single character or an escape sequence within a character class.
+    fn class_item(&mut self) -> Result<CharacterClass, ParseError> {
+        let position = self.position();
+        match self.next()? {
+            '\\' => self.escape(position),
+            character => Ok(CharacterClass::char(character)),
+        }
+    }
+
+    /// Parse an escape sequence, assuming that the backslash was already consumed.
+    fn escape(&mut self, position: usize) -> Result<CharacterClass, ParseError> {
+        let invalid = ParseError::InvalidEscape { position };
+        Ok(match self.next()? {
+            'n' => CharacterClass::char('\n'),
+            'r' => CharacterClass::char('\r'),
+            't' => CharacterClass::char('\t'),
+            '0' => CharacterClass::char('\0'),
+            'd' => CharacterClass::digit(),
+            'D' => CharacterClass::digit().negated(),
+            'w' => CharacterClass::word(),
+            'W' => CharacterClass::word().negated(),
+            's' => CharacterClass::space(),
+            'S' => CharacterClass::space().negated(),
+            'u' => {
+                self.expect('{')?;
+                let mut digits = String::new();
+                while let Some(digit) = self.peek().filter(|char| char.is_ascii_hexdigit()) {
+                    digits.push(digit);
+                    self.chars.next();
+                }
+                self.expect('}')?;
+                let code = u32::from_str_radix(&digits, 16).map_err(|_| invalid.clone())?;
+                CharacterClass::char(char::from_u32(code).ok_or(invalid)?)
+            }
+            character if character.is_ascii_punctuation() => CharacterClass::char(character),
+            _ => return Err(invalid),
+        })
+    }
+}
+
+/// The code point of a class consisting of a single character.
+fn single_char(class: &CharacterClass) -> Option<u32> {
+    match class.ranges[..] {
+        [(start, end)] if start == end => Some(start),
+        _ => None,
+    }
+}
+
+
+
+// =============
+// === Regex ===
+// =============
+
+/// A regular expression compiled to a minimal [`Dfa`].
+#[derive(Clone, Debug)]
+pub struct Regex {
+    /// The minimal automaton matching the expression.
+    pub dfa:   Dfa,
+    accepting: Vec<bool>,
+}
+
+impl Regex {
+    /// Parse and compile the regular expression. See the module docs to learn about the supported
+    /// syntax.
+    pub fn new(regex: &str) -> Result<Self, ParseError> {
+        Ok(Self::from_pattern(&parse(regex)?))
+    }
+
+    /// Compile the pattern.
+    pub fn from_pattern(pattern: &Pattern) -> Self {
+        let mut nfa = Nfa::new();
+        let end = nfa.new_pattern(nfa.start, pattern);
+        let dfa = Dfa::from(&nfa).minimize_by_key(|sources| sources.contains(&end));
+        let accepting = dfa.sources.iter().map(|sources| sources.contains(&end)).collect();
+        Self { dfa, accepting }
+    }
+
+    /// Check whether the state is an accepting one.
+    pub fn is_accepting(&self, state: dfa::State) -> bool {
+        !state.is_invalid() && self.accepting[state.id()]
+    }
+
+    /// Check whether the whole `input` matches the expression.
+    pub fn is_match(&self, input: &str) -> bool {
+        let state = input.chars().try_fold(Dfa::START_STATE, |state, char| {
+            let next = self.dfa.next_state(state, &Symbol::from(char));
+            (!next.is_invalid()).as_some(next)
+        });
+        state.map_or(false, |state| self.is_accepting(state))
+    }
+
+    /// The length in bytes of the longest prefix of the `input` matching the expression, if any.
+    pub fn longest_match(&self, input: &str) -> Option<usize> {
+        let mut state = Dfa::START_STATE;
+        let mut longest = self.is_accepting(state).as_some(0);
+        for (offset, char) in input.char_indices() {
+            state = self.dfa.next_state(state, &Symbol::from(char));
+            if state.is_invalid() {
+                break;
+            }
+            if self.is_accepting(state) {
+                longest = Some(offset + char.len_utf8());
+            }
+        }
+        longest
+    }
+
+    /// Generate the source code of a standalone Rust module named `name`, containing the
+    /// transition table of the automaton and functions matching strings against it. The
+    /// generated code has no dependencies, so it can be included in crates which should not depend
+    /// on this library, like the generated lexers.
+    pub fn as_rust_code(&self, name: &str) -> String {
+        let states = self.dfa.links.rows;
+        let columns = self.dfa.links.columns;
+        let divisions = self.dfa.alphabet.keys().map(|symbol| symbol.index).collect_vec();
+        let mut code = String::new();
+        let mut out = |line: &str| {
+            code.push_str(line);
+            code.push('\n');
+        };
+        out("/// A matcher generated by the `enso-automata` crate. Do not edit manually.");
+        out("#[allow(dead_code)]");
+        out(&format!("pub mod {name} {{"));
+        out("    /// The state denoting that the input does not match.");
+        out("    pub const INVALID: u32 = u32::MAX;");
+        out("    /// The first code point of every alphabet division, in ascending order.");
+        out(&format!("    pub const DIVISIONS: [u64; {columns}] = {divisions:?};"));
+        out("    /// The next state for every state and alphabet division.");
+        out(&format!("    pub const TRANSITIONS: [[u32; {columns}]; {states}] = ["));
+        for row in 0..states {
+            let targets = (0..columns).map(|column| {
+                let target = self.dfa.links[(row, column)];
+                if target.is_invalid() {
+                    "INVALID".to_string()
+                } else {
+                    target.id().to_string()
+                }
+            });
+            out(&format!("        [{}],", targets.collect_vec().join(", ")));
+        }
+        out("    ];");
+        out("    /// Whether the state is an accepting one.");
+        out(&format!("    pub const ACCEPTING: [bool; {states}] = {:?};", self.accepting));
+        out("");
+        out("    /// The next state after reading the character in the given state.");
+        out("    pub fn next_state(state: u32, char: char) -> u32 {");
+        out("        let division = match DIVISIONS.binary_search(&(char as u64)) {");
+        out("            Ok(index) => index,");
+        out("            Err(index) => index - 1,");
+        out("        };");
+        out("        TRANSITIONS[state as usize][division]");
+        out("    }");
+        out("");
+        out("    /// The length in bytes of the longest prefix of the input which matches.");
+        out("    pub fn longest_match(input: &str) -> Option<usize> {");
+        out("        let mut state = 0;");
+        out("        let mut longest = ACCEPTING[0].then_some(0);");
+        out("        for (offset, char) in input.char_indices() {");
+        out("            state = next_state(state, char);");
+        out("            if state == INVALID {");
+        out("                break;");
+        out("            }");
+        out("            if ACCEPTING[state as usize] {");
+        out("                longest = Some(offset + char.len_utf8());");
+        out("            }");
+        out("        }");
+        out("        longest");
+        out("    }");
+        out("");
+        out("    /// Check whether the whole input matches.");
+        out("    pub fn is_match(input: &str) -> bool {");
+        out("        let mut state = 0;");
+        out("        for char in input.chars() {");
+        out("            state = next_state(state, char);");
+        out("            if state == INVALID {");
+        out("                return false;");
+        out("            }");
+        out("        }");
+        out("        ACCEPTING[state as usize]");
+        out("    }");
+        out("}");
+        code
+    }
+}
+
+impl FromStr for Regex {
+    type Err = ParseError;
+    fn from_str(regex: &str) -> Result<Self, Self::Err> {
+        Self::new(regex)
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(start: char, end: char) -> Pattern {
+        Pattern::range(start..=end)
+    }
+
+    fn assert_matches(regex: &str, matching: &[&str], not_matching: &[&str]) {
+        let compiled = Regex::new(regex).unwrap();
+        for input in matching {
+            assert!(compiled.is_match(input), "{regex:?} should match {input:?}.");
+        }
+        for input in not_matching {
+            assert!(!compiled.is_match(input), "{regex:?} should not match {input:?}.");
+        }
+    }
+
+    #[test]
+    fn parse_literals_and_operators() {
+        assert_eq!(parse("a").unwrap(), Pattern::char('a'));
+        assert_eq!(parse("ab").unwrap(), Pattern::char('a') >> Pattern::char('b'));
+        assert_eq!(parse("a|b").unwrap(), Pattern::char('a') | Pattern::char('b'));
+        assert_eq!(parse("a*").unwrap(), Pattern::char('a').many());
+        assert_eq!(parse("a+").unwrap(), Pattern::char('a').many1());
+        assert_eq!(parse("a?").unwrap(), Pattern::char('a').opt());
+        assert_eq!(parse("(?:a)").unwrap(), Pattern::char('a'));
+        assert_eq!(parse("").unwrap(), Pattern::always());
+        assert_eq!(parse("\\u{3b1}").unwrap(), Pattern::char('α'));
+    }
+
+    #[test]
+    fn parse_classes() {
+        assert_eq!(parse("[a-c]").unwrap(), chars('a', 'c'));
+        assert_eq!(parse("[a-cb-d]").unwrap(), chars('a', 'd'));
+        assert_eq!(parse("[a-]").unwrap(), Pattern::Or(vec![chars('-', '-'), chars('a', 'a')]));
+        assert_eq!(parse("[]]").unwrap(), chars(']', ']'));
+        assert_eq!(parse("[α-ω]").unwrap(), chars('α', 'ω'));
+        let not_newline = Pattern::Or(vec![chars('\0', '\t'), chars('\u{b}', char::MAX)]);
+        assert_eq!(parse("[^\\n]").unwrap(), not_newline);
+        assert_eq!(parse(".").unwrap(), not_newline);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(parse("(a"), Err(ParseError::UnexpectedEnd));
+        assert_eq!(parse("a)"), Err(ParseError::UnexpectedChar { character: ')', position: 1 }));
+        assert_eq!(parse("*a"), Err(ParseError::NothingToRepeat { position: 0 }));
+        assert_eq!(parse("a|+"), Err(ParseError::NothingToRepeat { position: 2 }));
+        assert_eq!(parse("[z-a]"), Err(ParseError::InvalidRange { position: 1 }));
+        assert_eq!(parse("[\\d-z]"), Err(ParseError::InvalidRange { position: 1 }));
+        assert_eq!(parse("a{3,1}"), Err(ParseError::InvalidRepetition { position: 1 }));
+        assert_eq!(parse("a{x}"), Err(ParseError::InvalidRepetition { position: 1 }));
+        assert_eq!(parse("\\q"), Err(ParseError::InvalidEscape { position: 0 }));
+        assert_eq!(parse("\\u{110000}"), Err(ParseError::InvalidEscape { position: 0 }));
+    }
+
+    #[test]
+    fn matching() {
+        assert_matches("abc", &["abc"], &["", "ab", "abcd", "abd"]);
+        assert_matches("a|bc|", &["a", "bc", ""], &["b", "abc"]);
+        assert_matches("(ab)*c", &["c", "abc", "ababc"], &["ac", "abab"]);
+        assert_matches("a{2}", &["aa"], &["a", "aaa"]);
+        assert_matches("a{2,}", &["aa", "aaaaa"], &["a"]);
+        assert_matches("a{1,3}b", &["ab", "aaab"], &["b", "aaaab"]);
+        assert_matches("[^\"\\\\]+", &["abc", "ąę"], &["", "a\"b", "\\"]);
+        assert_matches("\\d+(\\.\\d+)?", &["1", "12.5"], &["1.", ".5", "a"]);
+        assert_matches("[\\u{391}-\\u{3A9}]\\w*", &["Ωmega_1", "Σ"], &["σ", "Σ-"]);
+        assert_matches(".\\s.", &["a b", "ą\tę", "a\nb"], &["\n b", "ab"]);
+    }
+
+    #[test]
+    fn longest_match() {
+        let regex = Regex::new("[a-z]+[0-9]*").unwrap();
+        assert_eq!(regex.longest_match("abc12 rest"), Some(5));
+        assert_eq!(regex.longest_match("ąbc"), None);
+        assert_eq!(regex.longest_match("zażółć"), Some(2));
+        assert_eq!(Regex::new("a*").unwrap().longest_match("b"), Some(0));
+    }
+
+    #[test]
+    fn minimal_automaton() {
+        // Both expressions describe the language of words of `a` and `b` ending with `ab`.
+        let regex = Regex::new("(a|b)*ab").unwrap();
+        assert_eq!(regex.dfa.links.rows, 3);
+        let regex = Regex::new("(a|b)*ab|(a|b)*ab(ab)*").unwrap();
+        assert_eq!(regex.dfa.links.rows, 3);
+        let regex = Regex::new("[a-z]+|[a-z]*").unwrap();
+        assert_eq!(regex.dfa.links.rows, 1);
+    }
+
+    #[test]
+    fn minimization_preserves_language() {
+        let regexes = ["(a|b)*abb", "a*b*|c", "(ab|a)(bc|c)?", "a{1,3}(b|c){2}", "[ab]*c[^a]?"];
+        let alphabet = ['a', 'b', 'c', 'd'];
+        let mut inputs = vec![String::new()];
+        for len in 1..=5 {
+            let shorter =
+                inputs.iter().filter(|input| input.len() == len - 1).cloned().collect_vec();
+            for input in shorter {
+                inputs.extend(alphabet.iter().map(|char| format!("{input}{char}")));
+            }
+        }
+        for regex in regexes {
+            let mut nfa = Nfa::new();
+            let end = nfa.new_pattern(nfa.start, parse(regex).unwrap());
+            let dfa = Dfa::from(&nfa);
+            let minimal = Regex::new(regex).unwrap();
+            assert!(minimal.dfa.links.rows <= dfa.links.rows);
+            for input in &inputs {
+                let state = input.chars().fold(Dfa::START_STATE, |state, char| {
+                    dfa.next_state(state, &Symbol::from(char))
+                });
+                let expected = !state.is_invalid() && dfa.sources[state.id()].contains(&end);
+                assert_eq!(minimal.is_match(input), expected, "{regex:?} on {input:?}.");
+            }
+        }
+    }
+
+    #[test]
+    fn rust_code() {
+        let code = Regex::new("a+").unwrap().as_rust_code("matcher");
+        assert!(code.contains("pub mod matcher {"));
+        assert!(code.contains("pub const DIVISIONS: [u64; 3] = [0, 97, 98];"));
+        assert!(code.contains("pub const TRANSITIONS: [[u32; 3]; 2] = ["));
+        assert!(code.contains("        [INVALID, 1, INVALID],\n        [INVALID, 1, INVALID],\n"));
+        assert!(code.contains("pub const ACCEPTING: [bool; 2] = [false, true];"));
+    }
+}
@@ -4,18 +4,18 @@
 //! order and a predecessor and a successor function. Follow the link to learn more:
 //! https://web.engr.oregonstate.edu/~erwig/diet.
 //!
-//! # WARNING
-//! This implementation is not finished. It is provided as one of alternative solutions to the
-//! problem of efficient attribute memory management in EnsoGL. Read the docs of
-//! [`ensogl::AttributeScopeData`] to learn more. This implementation has the following
-//! shortcomings:
+//! The tree is a B-tree of disjoint, non-adjacent intervals. It is provided as one of alternative
+//! solutions to the problem of efficient attribute memory management in EnsoGL. Read the docs of
+//! [`ensogl::AttributeScopeData`] to learn more. The tree supports:
 //!
-//! 1. No implementation of merging of intervals in case they are not in the same layer. For
-//!    example, assuming a tree with values `1` and `7` in one layer, and value `3` in child-layer,
-//!    inserting the value `2` would not merge `1`,`2`, and `3` into a single [`Interval`].
+//! 1. Inserting and removing single values and whole intervals. Stored intervals adjacent to the
+//!    inserted values are merged, regardless of the layers of the tree they are stored in. Stored
+//!    intervals partially covered by the removed values are shrunk or split. Removal rebalances
+//!    the tree as described here: https://en.wikipedia.org/wiki/B-tree#Deletion.
 //!
-//! 2. No implementation of removing elements. This should be straightforward. The algorithm is
-//!    described here: https://en.wikipedia.org/wiki/B-tree#Algorithms.
+//! 2. Membership queries of single values and whole intervals.
+//!
+//! 3. Iteration over the stored intervals and over the gaps between them within given bounds.
 //!
 //! # Benchmarks
 //! This module contains a lot of benchmarks in order to compare different techniques of managing
@@ -28,6 +28,7 @@
 use crate::prelude::*;
 
 use std::mem::MaybeUninit;
+use std::ops::RangeInclusive;
 
 
 
@@ -49,6 +50,19 @@ pub fn Interval(start: usize, end: usize) -> Interval {
     Interval { start, end }
 }
 
+impl Interval {
+    /// Check whether the interval contains no values, which is the case when `start` is greater
+    /// than `end`.
+    pub fn is_empty(&self) -> bool {
+        self.start > self.end
+    }
+
+    /// Check whether the value is contained in the interval.
+    pub fn contains(&self, t: usize) -> bool {
+        self.start <= t && t <= self.end
+    }
+}
+
 impl Debug for Interval {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "Interval({:?},{:?})", self.start, self.end)
@@ -67,6 +81,12 @@ impl From<(usize, usize)> for Interval {
     }
 }
 
+impl From<RangeInclusive<usize>> for Interval {
+    fn from(range: RangeInclusive<usize>) -> Self {
+        Interval(*range.start(), *range.end())
+    }
+}
+
 
 
 // ============
@@ -120,6 +140,8 @@ pub mod $modname {
 // === Constants ===
 
 const DATA_SIZE : usize = $num;
+/// The minimal number of intervals stored in every node except the root.
+const MIN_DATA_COUNT : usize = DATA_SIZE / 2;
 type DataArray           = [Interval;$num];
 // FIXME: Potential performance gain. We could initialize only the needed elements in this array
 //        by changing it to `[MaybeUninit<$name>;inc!{$num}]`.
@@ -180,15 +202,15 @@ impl $name {
     }
 
     /// Perform linear search of the data layer for the provided value. Returns [`Ok`] containing
-    /// the index of the value or [`Err`] if the value was not found. In the later case, the result
-    /// will contain the index where the value should be inserted in order to keep the right
-    /// ordering.
+    /// the index of the interval containing the value or [`Err`] if the value was not found. In the
+    /// later case, the result will contain the index where the value should be inserted in order to
+    /// keep the right ordering. It is also the index of the child which may contain the value.
     fn search_data(&self, t:usize) -> Result<usize,usize> {
         let mut out = Err(self.data_count);
         for i in 0..self.data_count {
             let interval = &self.data[i];
-            if      t + 1 <  interval.start   { out = Err(i) ; break }
-            else if t     <= interval.end + 1 { out = Ok(i)  ; break }
+            if      t <  interval.start { out = Err(i) ; break }
+            else if t <= interval.end   { out = Ok(i)  ; break }
         }
         out
     }
@@ -244,9 +266,136 @@ impl $name {
         (p_left,p_right)
     }
 
-    /// Insert a new value into this tree.
+    /// Check whether the tree contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.data_count == 0
+    }
+
+    /// Check whether the value is contained in the tree.
+    pub fn contains(&self, t:usize) -> bool {
+        self.interval_containing(t).is_some()
+    }
+
+    /// Check whether all values of the interval are contained in the tree. An empty interval (with
+    /// `start` greater than `end`) is always contained.
+    pub fn contains_interval(&self, interval:impl Into<Interval>) -> bool {
+        let interval = interval.into();
+        interval.is_empty() || self.interval_containing(interval.start).map_or(false, |found| {
+            found.end >= interval.end
+        })
+    }
+
+    /// The stored (maximal) interval containing the value, if any.
+    pub fn interval_containing(&self, t:usize) -> Option<Interval> {
+        match self.search_data(t) {
+            Ok(pos)  => Some(self.data[pos]),
+            Err(pos) => self.children.as_ref().and_then(|children| {
+                children[pos].interval_containing(t)
+            }),
+        }
+    }
+
+    /// Mutable reference to the stored interval containing the value, if any.
+    fn interval_containing_mut(&mut self, t:usize) -> Option<&mut Interval> {
+        match self.search_data(t) {
+            Ok(pos)  => Some(&mut self.data[pos]),
+            Err(pos) => self.children.as_mut().and_then(|children| {
+                children[pos].interval_containing_mut(t)
+            }),
+        }
+    }
+
+    /// The lowest stored interval having at least one common value with the provided interval.
+    fn first_overlapping(&self, interval:Interval) -> Option<Interval> {
+        for i in 0..self.data_count {
+            let data = self.data[i];
+            if data.end >= interval.start {
+                let in_child = self.children.as_ref().and_then(|children| {
+                    children[i].first_overlapping(interval)
+                });
+                return in_child.or_else(|| (data.start <= interval.end).as_some(data));
+            }
+        }
+        self.children.as_ref().and_then(|children| {
+            children[self.data_count].first_overlapping(interval)
+        })
+    }
+
+    /// Insert a new value into this tree. If the value is adjacent to stored intervals, they are
+    /// merged, regardless of the tree layers they are stored in.
     pub fn insert(&mut self, t:usize) {
-        if let Some((median,left,right)) = self.insert_internal(t) {
+        self.insert_interval(Interval(t,t))
+    }
+
+    /// Insert all values of the interval into this tree. All stored intervals overlapping with or
+    /// adjacent to the inserted one are merged with it. Inserting an empty interval (with `start`
+    /// greater than `end`) does nothing.
+    pub fn insert_interval(&mut self, interval:impl Into<Interval>) {
+        let interval = interval.into();
+        if interval.is_empty() { return }
+        let with_neighbors_start = interval.start.saturating_sub(1);
+        let with_neighbors_end   = interval.end.saturating_add(1);
+        let with_neighbors       = Interval(with_neighbors_start,with_neighbors_end);
+        match self.first_overlapping(with_neighbors) {
+            None         => self.insert_disjoint(interval),
+            Some(anchor) => {
+                // The anchor is extended in place, so the tree structure is preserved in the most
+                // common case of growing a single interval. Other intervals covered by (or adjacent
+                // to) the extended one can only follow the anchor, and are removed from the tree.
+                let start      = anchor.start.min(interval.start);
+                let mut end    = anchor.end.max(interval.end);
+                let mut search = anchor.end.checked_add(1);
+                while let Some(search_start) = search {
+                    let search_range = Interval(search_start,end.saturating_add(1));
+                    match self.first_overlapping(search_range) {
+                        None        => search = None,
+                        Some(found) => {
+                            self.remove_containing(found.start);
+                            end    = end.max(found.end);
+                            search = found.end.checked_add(1);
+                        }
+                    }
+                }
+                if let Some(stored) = self.interval_containing_mut(anchor.start) {
+                    *stored = Interval(start,end);
+                }
+            }
+        }
+    }
+
+    /// Remove the value from this tree. Does nothing if the value was not present.
+    pub fn remove(&mut self, t:usize) {
+        self.remove_interval(Interval(t,t))
+    }
+
+    /// Remove all values of the interval from this tree. Stored intervals partially overlapping
+    /// the removed one are shrunk or split. Removing an empty interval (with `start` greater than
+    /// `end`) does nothing.
+    pub fn remove_interval(&mut self, interval:impl Into<Interval>) {
+        let interval = interval.into();
+        if interval.is_empty() { return }
+        while let Some(found) = self.first_overlapping(interval) {
+            let keep_left  = found.start < interval.start;
+            let keep_right = found.end   > interval.end;
+            if !keep_left && !keep_right {
+                self.remove_containing(found.start);
+            } else if let Some(stored) = self.interval_containing_mut(found.start) {
+                if keep_left {
+                    stored.end = interval.start - 1;
+                } else {
+                    stored.start = interval.end + 1;
+                }
+                if keep_left && keep_right {
+                    self.insert_disjoint(Interval(interval.end + 1, found.end));
+                }
+            }
+        }
+    }
+
+    /// Insert the interval, assuming that it is neither overlapping with nor adjacent to any of
+    /// the stored intervals.
+    fn insert_disjoint(&mut self, interval:Interval) {
+        if let Some((median,left,right)) = self.insert_internal(interval) {
             let mut new_root = $name::default();
             new_root.data_count   = 1;
             new_root.data[0]      = median;
@@ -257,39 +406,39 @@ impl $name {
         }
     }
 
-    /// Internal helper for the `insert` function.
-    fn insert_internal(&mut self, t:usize) -> Option<(Interval,$name,$name)> {
-        match self.search_data(t) {
+    /// Internal helper for the `insert_disjoint` function.
+    fn insert_internal(&mut self, interval:Interval) -> Option<(Interval,$name,$name)> {
+        match self.search_data(interval.start) {
             Err(pos) => {
                 match &mut self.children {
                     None => {
                         if self.data_count < DATA_SIZE {
                             // Insert Case (1)
                             self.data[pos..].rotate_right(1);
-                            self.data[pos] = Interval(t,t);
+                            self.data[pos] = interval;
                             self.data_count += 1;
                             None
                         } else {
                             let median_ix = DATA_SIZE / 2;
                             let (median,(left,right)) = if pos == median_ix {
                                 // Insert Case (2)
-                                (Interval(t,t),self.split_leaf(median_ix,median_ix))
+                                (interval,self.split_leaf(median_ix,median_ix))
                             } else if pos < median_ix {
                                 // Insert Case (3)
                                 let (mut left,right) = self.split_leaf(median_ix-1, median_ix);
-                                left.insert_internal(t);
+                                left.insert_internal(interval);
                                 (self.data[median_ix-1],(left,right))
                             } else {
                                 // Insert Case (4)
                                 let (left, mut right) = self.split_leaf(median_ix, median_ix+1);
-                                right.insert_internal(t);
+                                right.insert_internal(interval);
                                 (self.data[median_ix],(left,right))
                             };
                             Some((median,left,right))
                         }
                     }
                     Some(children) => {
-                        if let Some((median,left,right)) = children[pos].insert_internal(t) {
+                        if let Some((median,left,right)) = children[pos].insert_internal(interval) {
                             if self.data_count < DATA_SIZE {
                                 // Insert Case (1-4)
                                 self.data[pos..].rotate_right(1);
@@ -363,26 +512,172 @@ impl $name {
                 }
             },
 
-            Ok(pos) => {
-                let interval = &mut self.data[pos];
-                if t < interval.start {
-                    interval.start = t;
+            // Not possible, as the inserted interval does not overlap with the stored ones.
+            Ok(_) => None
+        }
+    }
+
+    /// Remove the stored interval containing the value. Does nothing if the value was not present.
+    fn remove_containing(&mut self, t:usize) {
+        self.remove_internal(t);
+        if self.data_count == 0 {
+            if let Some(mut children) = self.children.take() {
+                *self = mem::take(&mut children[0]);
+            }
+        }
+    }
+
+    /// Internal helper for the `remove_containing` function. Returns the removed interval. This
+    /// node may be left underfull; it is the responsibility of the caller to fix it.
+    fn remove_internal(&mut self, t:usize) -> Option<Interval> {
+        let search = self.search_data(t);
+        match &mut self.children {
+            None => search.ok().map(|pos| self.remove_data(pos)),
+            Some(children) => match search {
+                Ok(pos) => {
+                    let removed = self.data[pos];
+                    // The removed interval is replaced with its predecessor, which is always
+                    // stored in a leaf.
+                    self.data[pos] = children[pos].remove_last();
+                    self.fix_underflow(pos);
+                    Some(removed)
                 }
-                else if t > interval.end {
-                    interval.end = t;
-                    let next_pos = pos + 1;
-                    if next_pos < self.data_count {
-                        let next_interval = self.data[next_pos];
-                        if next_interval.start == t + 1 {
-                            // Merging intervals.
-                            let interval = &mut self.data[pos];
-                            interval.end = next_interval.end;
-                            self.data[next_pos..].rotate_left(1);
-                            self.data_count -= 1;
-                        }
+                Err(pos) => {
+                    let removed = children[pos].remove_internal(t);
+                    if removed.is_some() {
+                        self.fix_underflow(pos);
                     }
+                    removed
+                }
+            },
+        }
+    }
+
+    /// Remove and return the last interval of this non-empty tree. This node may be left
+    /// underfull; it is the responsibility of the caller to fix it.
+    fn remove_last(&mut self) -> Interval {
+        let last = self.data_count;
+        match &mut self.children {
+            None => self.remove_data(last - 1),
+            Some(children) => {
+                let removed = children[last].remove_last();
+                self.fix_underflow(last);
+                removed
+            }
+        }
+    }
+
+    /// Remove the interval at the given index from the data layer of a leaf node.
+    fn remove_data(&mut self, pos:usize) -> Interval {
+        let removed = self.data[pos];
+        self.data[pos..].rotate_left(1);
+        self.data_count -= 1;
+        removed
+    }
+
+    /// Restore the minimal data count of the child at the given index, if needed, by moving an
+    /// interval from one of its siblings or by merging it with one of them.
+    fn fix_underflow(&mut self, pos:usize) {
+        let data_count = self.data_count;
+        let data       = &mut self.data;
+        let children   = self.children.as_mut().unwrap();
+        if children[pos].data_count >= MIN_DATA_COUNT { return }
+        if pos > 0 && children[pos-1].data_count > MIN_DATA_COUNT {
+            let (left,right) = children.split_at_mut(pos);
+            let left         = &mut left[pos-1];
+            let child        = &mut right[0];
+            let left_count   = left.data_count;
+            child.data[..].rotate_right(1);
+            child.data[0]    = data[pos-1];
+            data[pos-1]      = left.data[left_count-1];
+            child.data_count += 1;
+            left.data_count  -= 1;
+            if let (Some(left_children),Some(child_children)) =
+                (&mut left.children,&mut child.children) {
+                child_children[..].rotate_right(1);
+                child_children[0] = mem::take(&mut left_children[left_count]);
+            }
+        } else if pos < data_count && children[pos+1].data_count > MIN_DATA_COUNT {
+            let (left,right) = children.split_at_mut(pos+1);
+            let child        = &mut left[pos];
+            let right        = &mut right[0];
+            let child_count  = child.data_count;
+            child.data[child_count] = data[pos];
+            data[pos]               = right.data[0];
+            right.data[..].rotate_left(1);
+            child.data_count += 1;
+            right.data_count -= 1;
+            if let (Some(child_children),Some(right_children)) =
+                (&mut child.children,&mut right.children) {
+                child_children[child_count+1] = mem::take(&mut right_children[0]);
+                right_children[..].rotate_left(1);
+            }
+        } else {
+            let left_ix = if pos > 0 { pos - 1 } else { pos };
+            let right   = mem::take(&mut children[left_ix+1]);
+            let left    = &mut children[left_ix];
+            let offset  = left.data_count + 1;
+            left.data[offset-1] = data[left_ix];
+            let right_data = &right.data[0..right.data_count];
+            left.data[offset..offset+right.data_count].copy_from_slice(right_data);
+            if let (Some(left_children),Some(mut right_children)) =
+                (&mut left.children,right.children) {
+                for i in 0..=right.data_count {
+                    left_children[offset+i] = mem::take(&mut right_children[i]);
                 }
-                None
+            }
+            left.data_count += right.data_count + 1;
+            data[left_ix..].rotate_left(1);
+            children[left_ix+1..].rotate_left(1);
+            self.data_count -= 1;
+        }
+    }
+
+    /// Iterator over the stored (maximal) intervals in ascending order.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter::new(self,0)
+    }
+
+    /// Iterator over the maximal intervals of values which are within the `bounds`, but are not
+    /// contained in the tree, in ascending order.
+    pub fn gaps(&self, bounds:impl Into<Interval>) -> Gaps<'_> {
+        let bounds     = bounds.into();
+        let intervals  = Iter::new(self,bounds.start);
+        let next_start = (!bounds.is_empty()).as_some(bounds.start);
+        let end        = bounds.end;
+        Gaps {intervals,next_start,end}
+    }
+
+    /// Check the structural invariants of the tree. Panics if any of them does not hold. Returns
+    /// the depth of the tree.
+    #[cfg(test)]
+    pub (crate) fn check_invariants(&self) -> usize {
+        let intervals = self.to_vec();
+        for interval in &intervals {
+            assert!(!interval.is_empty(), "Empty interval stored: {interval:?}.");
+        }
+        for pair in intervals.windows(2) {
+            assert!(pair[0].end + 1 < pair[1].start, "Unmerged intervals: {pair:?}.");
+        }
+        self.check_node_invariants(true)
+    }
+
+    #[cfg(test)]
+    fn check_node_invariants(&self, is_root:bool) -> usize {
+        assert!(self.data_count <= DATA_SIZE);
+        if !is_root {
+            assert!(self.data_count >= MIN_DATA_COUNT, "Underfull node: {self:?}.");
+        }
+        match &self.children {
+            None => 1,
+            Some(children) => {
+                assert!(self.data_count > 0, "Internal node without data: {self:?}.");
+                let depths = children[0..=self.data_count].iter().map(|child| {
+                    child.check_node_invariants(false)
+                });
+                let depths = depths.collect::<Vec<_>>();
+                assert!(depths.windows(2).all(|p| p[0] == p[1]), "Unbalanced tree: {self:?}.");
+                depths[0] + 1
             }
         }
     }
@@ -456,6 +751,114 @@ impl Debug for $name {
     }
 }
 
+impl<'a> IntoIterator for &'a $name {
+    type Item     = Interval;
+    type IntoIter = Iter<'a>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl FromIterator<usize> for $name {
+    fn from_iter<I:IntoIterator<Item=usize>>(iter:I) -> Self {
+        let mut tree = Self::default();
+        for t in iter { tree.insert(t) }
+        tree
+    }
+}
+
+impl FromIterator<Interval> for $name {
+    fn from_iter<I:IntoIterator<Item=Interval>>(iter:I) -> Self {
+        let mut tree = Self::default();
+        for interval in iter { tree.insert_interval(interval) }
+        tree
+    }
+}
+
+
+// === Iter ===
+
+/// Iterator over the intervals stored in the tree. See [`$name::iter`] to learn more.
+#[derive(Clone,Debug)]
+pub struct Iter<'a> {
+    /// The path from the root to the currently visited node. Each node is accompanied by the
+    /// index of the next interval to be returned from it.
+    stack : Vec<(&'a $name,usize)>,
+}
+
+impl<'a> Iter<'a> {
+    /// Constructor. The iterator skips all intervals ending before the value `from`.
+    fn new(tree:&'a $name, from:usize) -> Self {
+        let mut stack = vec![];
+        let mut node  = tree;
+        loop {
+            let pos = (0..node.data_count).find(|&i| node.data[i].end >= from);
+            let pos = pos.unwrap_or(node.data_count);
+            stack.push((node,pos));
+            match &node.children {
+                Some(children) => node = &children[pos],
+                None           => break,
+            }
+        }
+        Self {stack}
+    }
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = Interval;
+    fn next(&mut self) -> Option<Interval> {
+        loop {
+            let (node,pos) = self.stack.last_mut()?;
+            let node       = *node;
+            if *pos < node.data_count {
+                let interval = node.data[*pos];
+                *pos += 1;
+                let mut child = node.children.as_ref().map(|children| &children[*pos]);
+                while let Some(node) = child {
+                    self.stack.push((node,0));
+                    child = node.children.as_ref().map(|children| &children[0]);
+                }
+                return Some(interval)
+            }
+            self.stack.pop();
+        }
+    }
+}
+
+
+// === Gaps ===
+
+/// Iterator over the gaps between the intervals stored in the tree. See [`$name::gaps`] to learn
+/// more.
+#[derive(Clone,Debug)]
+pub struct Gaps<'a> {
+    intervals  : Iter<'a>,
+    /// The start of the next gap candidate, or [`None`] if the iteration is finished.
+    next_start : Option<usize>,
+    end        : usize,
+}
+
+impl<'a> Iterator for Gaps<'a> {
+    type Item = Interval;
+    fn next(&mut self) -> Option<Interval> {
+        loop {
+            let start = self.next_start?;
+            match self.intervals.next() {
+                Some(interval) if interval.start <= self.end => {
+                    self.next_start = interval.end.checked_add(1).filter(|&t| t <= self.end);
+                    if interval.start > start {
+                        return Some(Interval(start,interval.start - 1))
+                    }
+                }
+                _ => {
+                    self.next_start = None;
+                    return Some(Interval(start,self.end))
+                }
+            }
+        }
+    }
+}
+
 })*};}
 
 define_trees! {
@@ -477,6 +880,8 @@ define_trees! {
 mod tests {
     use super::*;
 
+    use std::collections::BTreeSet;
+
 
     // === Tree4 Testing Utilities ===
 
@@ -915,6 +1320,195 @@ mod tests {
             )
         )
     }
+
+    #[test]
+    fn cross_layer_merging() {
+        let mut v = t!(t!(10, 12), 14, t!(16, 18));
+        v.insert(13);
+        check(&v, &[(10, 10), (12, 14), (16, 16), (18, 18)]);
+        v.check_invariants();
+        v.insert(15);
+        check(&v, &[(10, 10), (12, 16), (18, 18)]);
+        v.check_invariants();
+        v.insert(17);
+        v.insert(11);
+        check(&v, &[(10, 18)]);
+        v.check_invariants();
+    }
+
+    #[test]
+    fn interval_insertion() {
+        let mut v = t!(t!(10, 20), 30, t!(40, 50));
+        v.insert_interval((21, 39));
+        check(&v, &[(10, 10), (20, 40), (50, 50)]);
+        v.check_invariants();
+        v.insert_interval(0..=8);
+        check(&v, &[(0, 8), (10, 10), (20, 40), (50, 50)]);
+        v.insert_interval((9, 9));
+        check(&v, &[(0, 10), (20, 40), (50, 50)]);
+        v.insert_interval((5, 2));
+        check(&v, &[(0, 10), (20, 40), (50, 50)]);
+        v.insert_interval((0, 100));
+        check(&v, &[(0, 100)]);
+        v.check_invariants();
+    }
+
+    #[test]
+    fn removal() {
+        let mut v = Tree4::default();
+        v.insert_interval((0, 10));
+        v.remove(0);
+        check(&v, &[(1, 10)]);
+        v.remove(10);
+        check(&v, &[(1, 9)]);
+        v.remove(5);
+        check(&v, &[(1, 4), (6, 9)]);
+        v.remove(5);
+        check(&v, &[(1, 4), (6, 9)]);
+        v.remove_interval((3, 7));
+        check(&v, &[(1, 2), (8, 9)]);
+        v.remove_interval((0, 100));
+        check(&v, &[]);
+        assert!(v.is_empty());
+    }
+
+    #[test]
+    fn deep_removal() {
+        let mut v = t!(t!(10, 12), 20, t!(30, 32), 40, t!(50, 52));
+        v.remove(40);
+        assert_eq!(v, t!(t!(10, 12, 20, 30), 32, t!(50, 52)));
+        v.remove(50);
+        assert_eq!(v, t!(t!(10, 12, 20), 30, t!(32, 52)));
+        v.remove(12);
+        assert_eq!(v, t!(t!(10, 20), 30, t!(32, 52)));
+        v.remove(52);
+        assert_eq!(v, t!(10, 20, 30, 32));
+        v.check_invariants();
+    }
+
+    #[test]
+    fn membership() {
+        let v = t!(t!((0, 4), (10, 10)), (20, 24), t!((30, 30), (40, 49)));
+        assert!(v.contains(0));
+        assert!(v.contains(4));
+        assert!(!v.contains(5));
+        assert!(v.contains(22));
+        assert!(v.contains(45));
+        assert!(!v.contains(50));
+        assert!(v.contains_interval((41, 49)));
+        assert!(!v.contains_interval((41, 50)));
+        assert!(!v.contains_interval((4, 10)));
+        assert!(v.contains_interval((9, 8)));
+        assert_eq!(v.interval_containing(42), Some(Interval(40, 49)));
+        assert_eq!(v.interval_containing(25), None);
+    }
+
+    #[test]
+    fn gaps() {
+        let v = t!(t!((0, 4), (10, 10)), (20, 24), t!((30, 30), (40, 49)));
+        let gaps = |bounds: (usize, usize)| v.gaps(bounds).collect::<Vec<_>>();
+        assert_eq!(gaps((0, 60)), intervals(&[(5, 9), (11, 19), (25, 29), (31, 39), (50, 60)]));
+        assert_eq!(gaps((3, 25)), intervals(&[(5, 9), (11, 19), (25, 25)]));
+        assert_eq!(gaps((21, 23)), intervals(&[]));
+        assert_eq!(gaps((31, 35)), intervals(&[(31, 35)]));
+        assert_eq!(gaps((7, 6)), intervals(&[]));
+        assert_eq!(Tree4::default().gaps((1, 2)).collect::<Vec<_>>(), intervals(&[(1, 2)]));
+    }
+
+    #[test]
+    fn iteration() {
+        let v = t!(t!((0, 4), (10, 10)), (20, 24), t!((30, 30), (40, 49)));
+        assert_eq!(v.iter().collect::<Vec<_>>(), v.to_vec());
+        assert_eq!((&v).into_iter().count(), 5);
+        assert_eq!(Tree4::default().iter().count(), 0);
+    }
+
+
+    // === Property Tests ===
+
+    /// A minimal deterministic pseudo-random generator, so the randomized tests are reproducible.
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn next(&mut self, bound: usize) -> usize {
+            self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            ((self.0 >> 33) as usize) % bound.max(1)
+        }
+
+        fn interval(&mut self, max: usize) -> Interval {
+            let start = self.next(max);
+            Interval(start, start + self.next(max / 10))
+        }
+    }
+
+    /// Maximal intervals of the values in the model set.
+    fn model_intervals(model: &BTreeSet<usize>) -> Vec<Interval> {
+        let mut out: Vec<Interval> = vec![];
+        for &t in model {
+            match out.last_mut() {
+                Some(last) if last.end + 1 == t => last.end = t,
+                _ => out.push(Interval(t, t)),
+            }
+        }
+        out
+    }
+
+    /// Maximal intervals of the values within the `bounds` which are missing in the model set.
+    fn model_gaps(model: &BTreeSet<usize>, bounds: Interval) -> Vec<Interval> {
+        let missing = (bounds.start..=bounds.end).filter(|t| !model.contains(t));
+        model_intervals(&missing.collect())
+    }
+
+    /// Perform random operations on the tree and on a [`BTreeSet`] model, checking that they
+    /// always contain the same values.
+    macro_rules! model_test {
+        ($name:ident, $tree:ty) => {
+            #[test]
+            fn $name() {
+                const MAX: usize = 300;
+                let mut random = Lcg(2022);
+                for _ in 0..20 {
+                    let mut tree = <$tree>::default();
+                    let mut model = BTreeSet::<usize>::new();
+                    for _ in 0..300 {
+                        match random.next(4) {
+                            0 => {
+                                let t = random.next(MAX);
+                                tree.insert(t);
+                                model.insert(t);
+                            }
+                            1 => {
+                                let t = random.next(MAX);
+                                tree.remove(t);
+                                model.remove(&t);
+                            }
+                            2 => {
+                                let interval = random.interval(MAX);
+                                tree.insert_interval(interval);
+                                model.extend(interval.start..=interval.end);
+                            }
+                            _ => {
+                                let interval = random.interval(MAX);
+                                tree.remove_interval(interval);
+                                model.retain(|t| !interval.contains(*t));
+                            }
+                        }
+                        tree.check_invariants();
+                        assert_eq!(tree.to_vec(), model_intervals(&model));
+                        let t = random.next(MAX);
+                        assert_eq!(tree.contains(t), model.contains(&t));
+                        let bounds = random.interval(MAX);
+                        let gaps = tree.gaps(bounds).collect::<Vec<_>>();
+                        assert_eq!(gaps, model_gaps(&model, bounds));
+                    }
+                }
+            }
+        };
+    }
+
+    model_test!(tree2_matches_model, Tree2);
+    model_test!(tree4_matches_model, Tree4);
+    model_test!(tree16_matches_model, Tree16);
 }
 
 
@@ -1175,6 +1769,90 @@ mod benches {
         });
     }
 
+    /// Inserting values filling the gaps between intervals, which merges intervals stored in
+    /// different layers of the tree.
+    #[bench]
+    fn bench_insert_merging(b: &mut Bencher) {
+        let max = test::black_box(1000);
+        let v = (0..max).map(|i| i * 2).collect::<Tree16>();
+        b.iter(|| {
+            let mut v = v.clone();
+            for i in 0..max {
+                v.insert(i * 2 + 1);
+            }
+        });
+    }
+
+    /// Compare with [`bench_remove_ascending_std_usize`].
+    #[bench]
+    fn bench_remove_ascending(b: &mut Bencher) {
+        let max = test::black_box(1000);
+        let v = (0..max).map(|i| i * 2).collect::<Tree16>();
+        b.iter(|| {
+            let mut v = v.clone();
+            for i in 0..max {
+                v.remove(i * 2);
+            }
+        });
+    }
+
+    #[bench]
+    fn bench_remove_ascending_std_usize(b: &mut Bencher) {
+        let max = test::black_box(1000);
+        let v = (0..max).map(|i| i * 2).collect::<std::collections::BTreeSet<usize>>();
+        b.iter(|| {
+            let mut v = v.clone();
+            for i in 0..max {
+                v.remove(&(i * 2));
+            }
+        });
+    }
+
+    /// Removing single values from the middle of intervals, which splits them.
+    #[bench]
+    fn bench_remove_splitting(b: &mut Bencher) {
+        let max = test::black_box(1000);
+        let mut v = Tree16::default();
+        v.insert_interval((0, max * 2));
+        b.iter(|| {
+            let mut v = v.clone();
+            for i in 0..max {
+                v.remove(i * 2 + 1);
+            }
+        });
+    }
+
+    /// Compare with [`bench_contains_std_usize`].
+    #[bench]
+    fn bench_contains(b: &mut Bencher) {
+        let max = test::black_box(1000);
+        let v = (0..max).map(|i| i * 2).collect::<Tree16>();
+        b.iter(|| (0..max * 2).filter(|t| v.contains(*t)).count());
+    }
+
+    #[bench]
+    fn bench_contains_std_usize(b: &mut Bencher) {
+        let max = test::black_box(1000);
+        let v = (0..max).map(|i| i * 2).collect::<std::collections::BTreeSet<usize>>();
+        b.iter(|| (0..max * 2).filter(|t| v.contains(t)).count());
+    }
+
+    /// Compare with [`bench_gaps_std_usize`].
+    #[bench]
+    fn bench_gaps(b: &mut Bencher) {
+        let max = test::black_box(1000);
+        let v = (0..max).map(|i| i * 3).collect::<Tree16>();
+        b.iter(|| v.gaps((0, max * 3)).count());
+    }
+
+    /// Computing gaps using a set of values requires visiting every value in the bounds.
+    #[bench]
+    fn bench_gaps_std_usize(b: &mut Bencher) {
+        let max = test::black_box(1000);
+        let v = (0..max).map(|i| i * 3).collect::<std::collections::BTreeSet<usize>>();
+        b.iter(|| (0..=max * 3).filter(|t| !v.contains(t)).count());
+    }
+
     // /// Benchmarks of the `lz_diet-0.1.6` crate. Disabled in order not to include it in the final
     // /// binary.
     // /// # Results (ms)
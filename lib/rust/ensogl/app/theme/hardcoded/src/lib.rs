@@ -19,6 +19,7 @@ use enso_prelude::*;
 use ensogl_core::prelude::*;
 
 use enso_shapely::before_main;
+use ensogl_core::display::style::file;
 use ensogl_text::font::DEFAULT_CODE_FONT;
 use ensogl_text::font::DEFAULT_FONT;
 
@@ -122,11 +123,16 @@ macro_rules! _define_theme_wrapper_and_literals {
             use ensogl_core::data::color::Rgba;
             use ensogl_core::display::style::theme;
 
-            /// Registers the theme in the application.
-            pub fn register(theme_manager:impl AsRef<theme::Manager>) {
+            /// Creates the theme.
+            pub fn theme() -> theme::Theme {
                 let $name = theme::Theme::new();
                 _define_theme_literals!{$id $name [] $($t)*}
-                theme_manager.as_ref().register(stringify!($name),$name);
+                $name
+            }
+
+            /// Registers the theme in the application.
+            pub fn register(theme_manager:impl AsRef<theme::Manager>) {
+                theme_manager.as_ref().register(stringify!($name),theme());
             }
 
             /// Enables the current theme.
@@ -809,6 +815,18 @@ define_themes! { [light:0, dark:1]
 }
 
 
+// ====================
+// === Theme Schema ===
+// ====================
+
+/// The schema of theme files, declaring the paths and value types defined by the builtin themes.
+/// Theme files can define only the values defined by the builtin themes.
+pub fn schema() -> file::Schema {
+    file::Schema::from_theme(&builtin::light::theme())
+}
+
+
+
 // ==========================
 // === Theme registration ===
 // ==========================
@@ -11,51 +11,9 @@ use ensogl_core::system::web::JsCast;
 use ensogl_core::system::web::JsValue;
 use ensogl_core::system::web::Map;
 use ensogl_text::font;
-use ensogl_text::font::Font;
-
-
-
-// =================
-// === Constants ===
-// =================
-
-/// The printable characters in the ASCII subset of Unicode. This is the same as the set of keys
-/// on a US-ANSI keyboard.
-const ASCII_PRINTABLE_CHARS: &str = concat!(
-    " !\"#$%&'()*+,-./0123456789:;<=>?@ABCDEFGHIJKLMNOPQRSTUVWXYZ",
-    "[\\]^_`abcdefghijklmnopqrstuvwxyz{|}~"
-);
-
-/// The glyphs to include in the pre-built atlas loaded at application startup.
-const PRELOAD_GLYPHS: &[&str] = &[ASCII_PRINTABLE_CHARS];
-
-/// The variations to be pre-built for each glyph, for each typeface.
-const PRELOAD_VARIATIONS: &[font::NonVariableFaceHeader] = &[
-    font::NonVariableFaceHeader::new(
-        font::Width::Normal,
-        font::Weight::Normal,
-        font::Style::Normal,
-    ),
-    font::NonVariableFaceHeader::new(
-        font::Width::Normal,
-        font::Weight::Medium,
-        font::Style::Normal,
-    ),
-    font::NonVariableFaceHeader::new(font::Width::Normal, font::Weight::Bold, font::Style::Normal),
-    font::NonVariableFaceHeader::new(
-        font::Width::Normal,
-        font::Weight::ExtraBold,
-        font::Style::Normal,
-    ),
-];
-
-/// The typefaces for which atlases should be pre-built.
-const PRELOAD_TYPEFACES: &[&str] = &[font::DEFAULT_CODE_FONT, font::DEFAULT_FONT];
-
-/// Path within the asset directory to store the glyph atlas image.
-const ATLAS_FILE: &str = "atlas.ppm";
-/// Path within the asset directory to store the glyph metadata.
-const METADATA_FILE: &str = "metadata.json";
+use ensogl_text::font::prebuilt;
+use ensogl_text::font::prebuilt::ATLAS_FILE;
+use ensogl_text::font::prebuilt::METADATA_FILE;
 
 
 
@@ -66,7 +24,7 @@ const METADATA_FILE: &str = "metadata.json";
 /// Build atlas sources, and return as JavaScript data.
 pub fn build_atlases() -> JsValue {
     let fonts = Map::new();
-    for font_name in PRELOAD_TYPEFACES {
+    for font_name in prebuilt::PRELOAD_TYPEFACES {
         match build_atlas(font_name) {
             Ok(font) => {
                 fonts.set(&font_name.to_string().into(), &font.into());
@@ -132,23 +90,7 @@ impl TryFrom<JsValue> for Atlas {
 
 /// Generate MSDF data for a font.
 fn build_atlas(name: &str) -> anyhow::Result<Atlas> {
-    let fonts = font::Embedded::default();
-    let font = fonts.load_font(name.into()).ok_or_else(|| anyhow!("Failed to load font."))?;
-    let font = match font {
-        Font::NonVariable(font) => font,
-        Font::Variable(_) =>
-            return Err(anyhow!("Atlas cache pre-seeding for variable fonts is not supported.",)),
-    };
-    for variation in PRELOAD_VARIATIONS {
-        for glyphs in PRELOAD_GLYPHS {
-            font.prepare_glyphs_for_text(variation, glyphs).unwrap_or_else(|e| {
-                warn!("Failed to load specified variation for font `{name}`: {e}")
-            });
-        }
-        let unknown_glyph = font::GlyphId::default();
-        font.prepare_glyph_by_id(variation, unknown_glyph);
-    }
-    let cache = font.cache_snapshot();
+    let cache = prebuilt::build_atlas(name)?;
     let atlas = cache.atlas.encode_ppm();
     let atlas = js_sys::Uint8Array::from(&atlas[..]).buffer();
     let metadata = cache.glyphs;
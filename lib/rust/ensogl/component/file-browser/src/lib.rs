@@ -1,5 +1,7 @@
-//! In this module the File Browser component will be implemented in the future. Currently it
-//! contains only an API description.
+//! The File Browser component. It displays the content of a folder as a tree, with subfolders
+//! loaded lazily when expanded, and allows choosing a file with the mouse or the keyboard. The
+//! content of the folders is provided by [`model::FolderContent`] implementations, and the tree
+//! logic, independent of the rendering, is implemented in the [`tree`] module.
 
 #![recursion_limit = "512"]
 // === Features ===
@@ -25,6 +27,7 @@
 // ==============
 
 pub mod model;
+pub mod tree;
 
 
 
@@ -36,21 +39,56 @@ pub mod prelude {
 use crate::prelude::*;
 
 use crate::model::AnyFolderContent;
+use crate::model::Entry;
+use crate::tree::ExtensionFilter;
+use crate::tree::FolderId;
+use crate::tree::FolderState;
+use crate::tree::LoadRequest;
+use crate::tree::Row;
+use crate::tree::RowKind;
+use crate::tree::Tree;
 
+use enso_frp as frp;
+use ensogl_core::application;
+use ensogl_core::application::shortcut;
+use ensogl_core::application::Application;
 use ensogl_core::display;
+use ensogl_list_view as list_view;
 use std::path::PathBuf;
 
 
+
+// =================
+// === Constants ===
+// =================
+
+/// The size of the component before the first [`Frp::resize`] call.
+const DEFAULT_SIZE: Vector2 = Vector2(300.0, 400.0);
+
+/// The indentation of each nesting level of the displayed entries.
+const INDENT: &str = "    ";
+
+
+
 // ===========
 // === FRP ===
 // ===========
 
 ensogl_core::define_endpoints! {
     Input {
-        set_content      (AnyFolderContent),
-        move_focus_left  (),
-        move_focus_right (),
-        move_focus_by    (isize),
+        set_content          (AnyFolderContent),
+        /// Display only the files with the given extensions. Folders are always displayed.
+        set_extension_filter (ExtensionFilter),
+        resize               (Vector2),
+        move_focus_up        (),
+        move_focus_down      (),
+        /// Collapse the focused folder, or focus the parent folder.
+        move_focus_left      (),
+        /// Expand the focused folder, or focus its first entry if it is already expanded.
+        move_focus_right     (),
+        move_focus_by        (isize),
+        /// Choose the focused file, or expand or collapse the focused folder.
+        choose_focused       (),
 
         copy_focused       (),
         cut_focused        (),
@@ -60,6 +98,8 @@ ensogl_core::define_endpoints! {
     Output {
         entry_selected (PathBuf),
         entry_chosen   (PathBuf),
+        /// Loading the content of a folder failed with the given message.
+        error_occurred (ImString),
 
         copy       (PathBuf),
         cut        (PathBuf),
@@ -69,6 +109,115 @@ ensogl_core::define_endpoints! {
 
 
 
+// =============
+// === Model ===
+// =============
+
+/// A type of Entry used in the FileBrowser's ListView.
+pub type ListEntry = list_view::entry::Label;
+
+/// The text displayed for the row.
+fn row_label(row: &Row) -> String {
+    let indent = INDENT.repeat(row.depth);
+    let (marker, suffix) = match &row.kind {
+        RowKind::File => ("  ", ""),
+        RowKind::Folder(FolderState::Collapsed) => ("+ ", ""),
+        RowKind::Folder(FolderState::Expanded) => ("- ", ""),
+        RowKind::Folder(FolderState::Loading) => ("- ", " (loading...)"),
+        RowKind::Folder(FolderState::Failed(_)) => ("- ", " (failed to load)"),
+    };
+    format!("{indent}{marker}{}{suffix}", row.name)
+}
+
+#[derive(Debug, display::Object)]
+struct Model {
+    display_object: display::object::Instance,
+    list:           list_view::ListView<ListEntry>,
+    tree:           RefCell<Tree>,
+    displayed_rows: RefCell<Vec<Row>>,
+    /// The networks passing the results of the folder content requests to the model. They are
+    /// dropped when the browsed folder changes, so the results of outdated requests are ignored.
+    requests:       RefCell<Vec<frp::Network>>,
+}
+
+impl Model {
+    fn new(app: &Application) -> Self {
+        let display_object = display::object::Instance::new();
+        let list = list_view::ListView::new(app);
+        display_object.add_child(&list);
+        list.disable_selecting_entries_with_mouse();
+        let tree = default();
+        let displayed_rows = default();
+        let requests = default();
+        Self { display_object, list, tree, displayed_rows, requests }
+    }
+
+    /// Run the operation on the tree and update the displayed list accordingly. The tree is not
+    /// borrowed when the list is updated, so the list's events may safely call back the model.
+    fn with_tree<R>(&self, f: impl FnOnce(&mut Tree) -> R) -> R {
+        let result = f(&mut self.tree.borrow_mut());
+        self.update_list();
+        result
+    }
+
+    fn update_list(&self) {
+        let (rows, focused_index) = {
+            let tree = self.tree.borrow();
+            (tree.rows().to_vec(), tree.focused_index())
+        };
+        if *self.displayed_rows.borrow() != rows {
+            let labels = rows.iter().map(row_label).collect_vec();
+            self.displayed_rows.replace(rows);
+            self.list.set_entries(list_view::entry::AnyModelProvider::new(labels));
+        }
+        self.list.select_entry(focused_index);
+    }
+
+    fn set_content(&self, content: &AnyFolderContent) -> LoadRequest {
+        self.requests.borrow_mut().clear();
+        self.with_tree(|tree| tree.set_content(content.clone()))
+    }
+
+    fn set_folder_entries(&self, folder: &FolderId, entries: Result<Rc<Vec<Entry>>, ImString>) {
+        self.with_tree(|tree| tree.set_folder_entries(folder.as_deref(), entries))
+    }
+
+    fn focused_path(&self) -> Option<PathBuf> {
+        self.tree.borrow().focused_path().map(|path| path.to_owned())
+    }
+
+    fn focused_file(&self) -> Option<PathBuf> {
+        let tree = self.tree.borrow();
+        tree.focused_row().filter(|row| row.kind == RowKind::File).map(|row| row.path.clone())
+    }
+
+    /// Request the content of a folder. The results are emitted by `loaded` or `failed`, together
+    /// with the id of the folder.
+    fn request_entries(
+        &self,
+        request: &LoadRequest,
+        loaded: &frp::Any<(FolderId, Rc<Vec<Entry>>)>,
+        failed: &frp::Any<(FolderId, ImString)>,
+    ) {
+        let network = frp::Network::new("file_browser::folder_request");
+        let loaded_folder = request.folder.clone();
+        let failed_folder = request.folder.clone();
+        let on_loaded =
+            move |entries: &Rc<Vec<Entry>>| (loaded_folder.clone(), entries.clone_ref());
+        let on_failed = move |error: &ImString| (failed_folder.clone(), error.clone_ref());
+        frp::extend! { network
+            entries_loaded <- any_mut::<Rc<Vec<Entry>>>();
+            error_occurred <- any_mut::<ImString>();
+            loaded <+ entries_loaded.map(on_loaded);
+            failed <+ error_occurred.map(on_failed);
+        }
+        self.requests.borrow_mut().push(network);
+        request.content.request_entries(entries_loaded, error_occurred);
+    }
+}
+
+
+
 // ===================
 // === FileBrowser ===
 // ===================
@@ -78,21 +227,192 @@ ensogl_core::define_endpoints! {
 #[derive(Clone, CloneRef, Debug, Deref, display::Object)]
 pub struct FileBrowser {
     #[deref]
-    frp:            Frp,
-    display_object: display::object::Instance,
+    frp:   Frp,
+    #[display_object]
+    model: Rc<Model>,
 }
 
 impl FileBrowser {
-    /// Constructore
-    pub fn new() -> Self {
+    /// Constructor.
+    pub fn new(app: &Application) -> Self {
         let frp = Frp::new();
-        let display_object = display::object::Instance::new();
-        Self { frp, display_object }
+        let model = Rc::new(Model::new(app));
+        Self { frp, model }.init()
+    }
+
+    fn init(self) -> Self {
+        let network = &self.frp.network;
+        let frp = &self.frp;
+        let model = &self.model;
+        let list = &model.list;
+        frp::extend! { network
+
+            // === Loading Folders ===
+
+            folder_loaded <- any_mut::<(FolderId, Rc<Vec<Entry>>)>();
+            folder_failed <- any_mut::<(FolderId, ImString)>();
+            loaded <- folder_loaded.map(f!(((folder, entries))
+                model.set_folder_entries(folder, Ok(entries.clone_ref()))
+            ));
+            failed <- folder_failed.map(f!(((folder, error))
+                model.set_folder_entries(folder, Err(error.clone_ref()))
+            ));
+            frp.source.error_occurred <+ folder_failed._1();
+            content_request <- frp.set_content.map(
+                f!([model](content) Some(model.set_content(content)))
+            );
+
+
+            // === Navigation ===
+
+            filtered <- frp.set_extension_filter.map(f!((filter)
+                model.with_tree(|t| t.set_filter(filter.clone()))
+            ));
+            focus_delta <- any(
+                frp.move_focus_by,
+                frp.move_focus_up.constant(-1),
+                frp.move_focus_down.constant(1)
+            );
+            moved <- focus_delta.map(f!((delta) model.with_tree(|t| t.move_focus_by(*delta))));
+            moved_left <- frp.move_focus_left.map(f_!(model.with_tree(Tree::move_focus_left)));
+            right_request <- frp.move_focus_right.map(f_!(model.with_tree(Tree::move_focus_right)));
+            clicked_row <- list.chosen_entry.filter_map(|id| *id);
+            clicked <- clicked_row.map(f!((index) model.with_tree(|t| t.focus_index(*index))));
+            choose <- any(frp.choose_focused, clicked);
+            frp.source.entry_chosen <+ choose.filter_map(f_!(model.focused_file()));
+            toggle_request <- choose.map(f_!(model.with_tree(Tree::toggle_focused_folder)));
+
+
+            // === Requests and Selection ===
+
+            request <- any(content_request, right_request, toggle_request);
+            eval request ([model, folder_loaded, folder_failed](request)
+                if let Some(request) = request {
+                    model.request_entries(request, &folder_loaded, &folder_failed);
+                }
+            );
+            folder_updated <- any(loaded, failed);
+            navigated <- any(filtered, moved, moved_left);
+            tree_changed <- any(folder_updated, navigated, request.constant(()));
+            focused_path <- tree_changed.map(f_!(model.focused_path())).on_change();
+            frp.source.entry_selected <+ focused_path.filter_map(|path| path.clone());
+
+
+            // === Clipboard ===
+
+            frp.source.copy <+ frp.copy_focused.filter_map(f_!(model.focused_path()));
+            frp.source.cut <+ frp.cut_focused.filter_map(f_!(model.focused_path()));
+            frp.source.paste_into <+ frp.paste_into_focused.filter_map(f_!(model.focused_path()));
+
+
+            // === Layout ===
+
+            list.resize <+ frp.resize;
+        }
+        frp.resize(DEFAULT_SIZE);
+        self
+    }
+
+    /// The entries currently displayed by the component, in the display order.
+    pub fn rows(&self) -> Vec<Row> {
+        self.model.tree.borrow().rows().to_vec()
     }
 }
 
-impl Default for FileBrowser {
-    fn default() -> Self {
-        Self::new()
+impl FrpNetworkProvider for FileBrowser {
+    fn network(&self) -> &frp::Network {
+        &self.frp.network
+    }
+}
+
+impl application::View for FileBrowser {
+    fn label() -> &'static str {
+        "FileBrowser"
+    }
+
+    fn new(app: &Application) -> Self {
+        FileBrowser::new(app)
+    }
+
+    fn global_shortcuts() -> Vec<shortcut::Shortcut> {
+        use shortcut::ActionType::*;
+        [
+            (PressAndRepeat, "up", "move_focus_up"),
+            (PressAndRepeat, "down", "move_focus_down"),
+            (PressAndRepeat, "left", "move_focus_left"),
+            (PressAndRepeat, "right", "move_focus_right"),
+            (Press, "enter", "choose_focused"),
+            (Press, "cmd c", "copy_focused"),
+            (Press, "cmd x", "cut_focused"),
+            (Press, "cmd v", "paste_into_focused"),
+        ]
+        .iter()
+        .map(|(a, b, c)| Self::self_shortcut_when(*a, *b, *c, "focused"))
+        .collect()
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::model::EntryType;
+    use crate::model::FolderContent;
+    use crate::model::FolderType;
+
+    /// A folder content with a fixed list of entries, provided immediately on request.
+    #[derive(Debug)]
+    struct StaticFolderContent(Rc<Vec<Entry>>);
+
+    impl FolderContent for StaticFolderContent {
+        fn request_entries(
+            &self,
+            entries_loaded: frp::Any<Rc<Vec<Entry>>>,
+            _error_occurred: frp::Any<ImString>,
+        ) {
+            entries_loaded.emit(self.0.clone_ref());
+        }
+    }
+
+    fn entry(path: &str, type_: EntryType) -> Entry {
+        let path = PathBuf::from(path);
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        Entry { type_, name, path }
+    }
+
+    #[test]
+    fn choosing_files_with_keyboard() {
+        let app = Application::new("root");
+        let file_browser = FileBrowser::new(&app);
+        let file = |path: &str| entry(path, EntryType::File);
+        let folder_content = StaticFolderContent(Rc::new(vec![file("/a/b.csv"), file("/a/c.txt")]));
+        let content = folder_content.into();
+        let folder = entry("/a", EntryType::Folder { type_: FolderType::Standard, content });
+        let root_content = StaticFolderContent(Rc::new(vec![folder, file("/d.csv")]));
+        file_browser.set_content(AnyFolderContent::from(root_content));
+        file_browser.set_extension_filter(ExtensionFilter::new(["csv"]));
+        let names = || file_browser.rows().into_iter().map(|row| row.name).collect_vec();
+        assert_eq!(names(), ["a", "d.csv"]);
+
+        file_browser.move_focus_down();
+        assert_eq!(file_browser.entry_selected.value(), PathBuf::from("/a"));
+        file_browser.move_focus_right();
+        assert_eq!(names(), ["a", "b.csv", "d.csv"]);
+        file_browser.move_focus_right();
+        assert_eq!(file_browser.entry_selected.value(), PathBuf::from("/a/b.csv"));
+        file_browser.choose_focused();
+        assert_eq!(file_browser.entry_chosen.value(), PathBuf::from("/a/b.csv"));
+        assert_eq!(file_browser.model.list.selected_entry.value(), Some(1));
+
+        file_browser.move_focus_left();
+        file_browser.choose_focused();
+        assert_eq!(names(), ["a", "d.csv"]);
+        assert_eq!(file_browser.entry_chosen.value(), PathBuf::from("/a/b.csv"));
     }
 }
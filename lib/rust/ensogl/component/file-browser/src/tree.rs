@@ -0,0 +1,536 @@
+//! The tree model of the [`crate::FileBrowser`]. It keeps the hierarchy of the browsed folders,
+//! which are loaded lazily when expanded, flattens it into the list of displayed rows, and
+//! implements the keyboard navigation over these rows. It does not depend on the rendering, so it
+//! can be tested without a scene.
+
+use crate::prelude::*;
+
+use crate::model::AnyFolderContent;
+use crate::model::Entry;
+use crate::model::EntryType;
+
+use std::path::Path;
+use std::path::PathBuf;
+
+
+
+// =======================
+// === ExtensionFilter ===
+// =======================
+
+/// Filter of the displayed files by their extensions. Folders are always displayed, so the files
+/// inside them can be reached. The default filter accepts all files.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ExtensionFilter {
+    extensions: Vec<String>,
+}
+
+impl ExtensionFilter {
+    /// Accept only the files with one of the given extensions. The extensions may be given with or
+    /// without the leading dot, and are matched case-insensitively. An empty list of extensions
+    /// accepts all files.
+    pub fn new<S: AsRef<str>>(extensions: impl IntoIterator<Item = S>) -> Self {
+        let extensions = extensions.into_iter();
+        let extensions = extensions.map(|ext| ext.as_ref().trim_start_matches('.').to_lowercase());
+        Self { extensions: extensions.collect() }
+    }
+
+    /// Check if the entry should be displayed.
+    pub fn accepts(&self, entry: &Entry) -> bool {
+        match entry.type_ {
+            EntryType::Folder { .. } => true,
+            EntryType::File => self.extensions.is_empty() || self.accepts_file_name(&entry.name),
+        }
+    }
+
+    fn accepts_file_name(&self, name: &str) -> bool {
+        let extension = Path::new(name).extension().and_then(|ext| ext.to_str());
+        let matches = |ext: &str| self.extensions.iter().any(|e| e.eq_ignore_ascii_case(ext));
+        extension.map_or(false, matches)
+    }
+}
+
+
+
+// ===========
+// === Row ===
+// ===========
+
+/// The state of a displayed folder.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FolderState {
+    /// The folder's content is not displayed.
+    Collapsed,
+    /// The folder is expanded, but its content is still being loaded.
+    Loading,
+    /// The folder is expanded and its content is displayed below it.
+    Expanded,
+    /// The folder is expanded, but loading its content failed with the given message.
+    Failed(ImString),
+}
+
+/// The kind of a displayed entry.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RowKind {
+    /// A file.
+    File,
+    /// A folder, which can be expanded.
+    Folder(FolderState),
+}
+
+/// A single displayed entry of the tree.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Row {
+    /// The entry's name.
+    pub name:  String,
+    /// The entry's global path in the file system.
+    pub path:  PathBuf,
+    /// The nesting level of the entry. The entries of the browsed folder have depth 0.
+    pub depth: usize,
+    /// The entry's kind.
+    pub kind:  RowKind,
+}
+
+impl Row {
+    /// Check if the row is a folder which is not collapsed.
+    pub fn is_expanded_folder(&self) -> bool {
+        matches!(&self.kind, RowKind::Folder(state) if *state != FolderState::Collapsed)
+    }
+}
+
+
+
+// ===================
+// === LoadRequest ===
+// ===================
+
+/// The identifier of a folder in the [`Tree`]: `None` is the browsed folder itself, and
+/// `Some(path)` is the subfolder with the given path.
+pub type FolderId = Option<PathBuf>;
+
+/// The request to load the entries of a folder, returned by the [`Tree`] operations which expand
+/// a folder not loaded yet. The result should be passed to [`Tree::set_folder_entries`].
+#[derive(Clone, Debug)]
+#[allow(missing_docs)]
+pub struct LoadRequest {
+    pub folder:  FolderId,
+    pub content: AnyFolderContent,
+}
+
+
+
+// ============
+// === Tree ===
+// ============
+
+/// The entries of a folder, in one of the loading stages.
+#[derive(Clone, Debug, Default)]
+enum Children {
+    #[default]
+    NotLoaded,
+    Loading,
+    Loaded(Vec<Node>),
+    Failed(ImString),
+}
+
+#[derive(Clone, Debug)]
+struct Node {
+    entry:    Entry,
+    expanded: bool,
+    children: Children,
+}
+
+impl Node {
+    fn new(entry: Entry) -> Self {
+        Self { entry, expanded: false, children: default() }
+    }
+
+    fn content(&self) -> Option<&AnyFolderContent> {
+        match &self.entry.type_ {
+            EntryType::Folder { content, .. } => Some(content),
+            EntryType::File => None,
+        }
+    }
+
+    fn find_mut<'a>(nodes: &'a mut [Node], path: &Path) -> Option<&'a mut Node> {
+        nodes.iter_mut().find_map(|node| {
+            if node.entry.path == path {
+                Some(node)
+            } else if let Children::Loaded(children) = &mut node.children {
+                Self::find_mut(children, path)
+            } else {
+                None
+            }
+        })
+    }
+}
+
+/// The lazily loaded tree of the browsed folder, together with the focused entry.
+///
+/// The operations which need folder entries not loaded yet return [`LoadRequest`]s. The tree does
+/// not request the entries itself, so the caller decides how the results are delivered back.
+#[derive(Clone, Debug, Default)]
+pub struct Tree {
+    content: AnyFolderContent,
+    root:    Children,
+    filter:  ExtensionFilter,
+    focus:   Option<PathBuf>,
+    rows:    Vec<Row>,
+}
+
+impl Tree {
+    /// Browse the new folder. The whole tree is discarded, and the returned request loads the
+    /// entries of the new folder.
+    pub fn set_content(&mut self, content: AnyFolderContent) -> LoadRequest {
+        self.content = content.clone();
+        self.root = Children::Loading;
+        self.focus = None;
+        self.update_rows();
+        LoadRequest { folder: None, content }
+    }
+
+    /// Set the filter of displayed files. The focus is moved to the closest displayed ancestor
+    /// if the focused file gets hidden.
+    pub fn set_filter(&mut self, filter: ExtensionFilter) {
+        self.filter = filter;
+        self.update_rows();
+    }
+
+    /// Set the result of a [`LoadRequest`]. Results for folders which are not being loaded
+    /// (because, for example, the browsed folder has changed in the meantime) are ignored.
+    pub fn set_folder_entries(
+        &mut self,
+        folder: Option<&Path>,
+        entries: Result<Rc<Vec<Entry>>, ImString>,
+    ) {
+        let children = match folder {
+            None => Some(&mut self.root),
+            Some(path) => self.node_mut(path).map(|node| &mut node.children),
+        };
+        if let Some(children) = children.filter(|children| matches!(children, Children::Loading)) {
+            *children = match entries {
+                Ok(entries) => {
+                    let nodes = entries.iter().sorted().cloned().map(Node::new);
+                    Children::Loaded(nodes.collect())
+                }
+                Err(error) => Children::Failed(error),
+            };
+            self.update_rows();
+        }
+    }
+
+    /// The displayed entries, in the display order: each expanded folder is followed by its
+    /// content.
+    pub fn rows(&self) -> &[Row] {
+        &self.rows
+    }
+
+    /// The state of the browsed folder. It is never [`FolderState::Collapsed`].
+    pub fn root_state(&self) -> FolderState {
+        match &self.root {
+            Children::NotLoaded | Children::Loading => FolderState::Loading,
+            Children::Loaded(_) => FolderState::Expanded,
+            Children::Failed(error) => FolderState::Failed(error.clone_ref()),
+        }
+    }
+
+    /// The index of the focused row.
+    pub fn focused_index(&self) -> Option<usize> {
+        let focus = self.focus.as_ref()?;
+        self.rows.iter().position(|row| &row.path == focus)
+    }
+
+    /// The focused row.
+    pub fn focused_row(&self) -> Option<&Row> {
+        self.rows.get(self.focused_index()?)
+    }
+
+    /// The path of the focused entry.
+    pub fn focused_path(&self) -> Option<&Path> {
+        self.focus.as_deref()
+    }
+
+    /// Focus the row with the given index. Out of bounds indices remove the focus.
+    pub fn focus_index(&mut self, index: usize) {
+        self.focus = self.rows.get(index).map(|row| row.path.clone());
+    }
+
+    /// Move the focus by the given number of rows, stopping at the first and the last one. When
+    /// nothing is focused, the first or the last row is focused, depending on the direction.
+    pub fn move_focus_by(&mut self, delta: isize) {
+        if let Some(last) = self.rows.len().checked_sub(1) {
+            let index = match self.focused_index() {
+                Some(index) => (index as isize + delta).clamp(0, last as isize) as usize,
+                None if delta >= 0 => 0,
+                None => last,
+            };
+            self.focus_index(index);
+        }
+    }
+
+    /// Collapse the focused folder, or focus the parent folder if the focused entry is a file or
+    /// a collapsed folder.
+    pub fn move_focus_left(&mut self) {
+        if let Some(index) = self.focused_index() {
+            let row = &self.rows[index];
+            if row.is_expanded_folder() {
+                let path = row.path.clone();
+                self.set_expanded(&path, false);
+            } else if let Some(parent_depth) = row.depth.checked_sub(1) {
+                let preceding = self.rows[..index].iter().rposition(|r| r.depth == parent_depth);
+                if let Some(parent) = preceding {
+                    self.focus_index(parent);
+                }
+            }
+        }
+    }
+
+    /// Expand the focused folder, or focus its first entry if it is already expanded.
+    pub fn move_focus_right(&mut self) -> Option<LoadRequest> {
+        let index = self.focused_index()?;
+        let row = &self.rows[index];
+        match &row.kind {
+            RowKind::Folder(FolderState::Collapsed) => {
+                let path = row.path.clone();
+                self.set_expanded(&path, true)
+            }
+            RowKind::Folder(_) => {
+                let depth = row.depth;
+                if self.rows.get(index + 1).map_or(false, |next| next.depth > depth) {
+                    self.focus_index(index + 1);
+                }
+                None
+            }
+            RowKind::File => None,
+        }
+    }
+
+    /// Expand the focused folder if it is collapsed, and collapse it otherwise.
+    pub fn toggle_focused_folder(&mut self) -> Option<LoadRequest> {
+        let row = self.focused_row()?;
+        let expand = !row.is_expanded_folder();
+        let path = row.path.clone();
+        self.set_expanded(&path, expand)
+    }
+
+    /// Expand or collapse the folder with the given path. Expanding a folder whose entries were
+    /// not loaded yet, or failed to load, returns the request to load them. The entries of
+    /// collapsed folders are kept, so expanding them again shows them immediately.
+    pub fn set_expanded(&mut self, path: &Path, expanded: bool) -> Option<LoadRequest> {
+        let node = self.node_mut(path)?;
+        let content = node.content()?.clone();
+        node.expanded = expanded;
+        let needs_loading = matches!(node.children, Children::NotLoaded | Children::Failed(_));
+        let request = (expanded && needs_loading).then(|| {
+            node.children = Children::Loading;
+            LoadRequest { folder: Some(path.to_owned()), content }
+        });
+        self.update_rows();
+        request
+    }
+
+    fn node_mut(&mut self, path: &Path) -> Option<&mut Node> {
+        match &mut self.root {
+            Children::Loaded(nodes) => Node::find_mut(nodes, path),
+            _ => None,
+        }
+    }
+
+    fn update_rows(&mut self) {
+        let mut rows = default();
+        if let Children::Loaded(nodes) = &self.root {
+            self.collect_rows(nodes, 0, &mut rows);
+        }
+        self.rows = rows;
+        if let Some(focus) = self.focus.take() {
+            let is_displayed = |path: &Path| self.rows.iter().any(|row| row.path == path);
+            self.focus = focus.ancestors().find(|path| is_displayed(path)).map(Path::to_owned);
+        }
+    }
+
+    fn collect_rows(&self, nodes: &[Node], depth: usize, rows: &mut Vec<Row>) {
+        for node in nodes.iter().filter(|node| self.filter.accepts(&node.entry)) {
+            let kind = match (&node.entry.type_, node.expanded, &node.children) {
+                (EntryType::File, _, _) => RowKind::File,
+                (_, false, _) => RowKind::Folder(FolderState::Collapsed),
+                (_, true, Children::Loaded(_)) => RowKind::Folder(FolderState::Expanded),
+                (_, true, Children::Failed(error)) =>
+                    RowKind::Folder(FolderState::Failed(error.clone_ref())),
+                (_, true, _) => RowKind::Folder(FolderState::Loading),
+            };
+            let name = node.entry.name.clone();
+            let path = node.entry.path.clone();
+            rows.push(Row { name, path, depth, kind });
+            if let (true, Children::Loaded(children)) = (node.expanded, &node.children) {
+                self.collect_rows(children, depth + 1, rows);
+            }
+        }
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::model::EmptyFolderContent;
+    use crate::model::FolderType;
+
+    fn file(path: &str) -> Entry {
+        let path = PathBuf::from(path);
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        Entry { type_: EntryType::File, name, path }
+    }
+
+    fn folder(path: &str) -> Entry {
+        let content = EmptyFolderContent.into();
+        let type_ = EntryType::Folder { type_: FolderType::Standard, content };
+        Entry { type_, ..file(path) }
+    }
+
+    fn entries(entries: impl IntoIterator<Item = Entry>) -> Result<Rc<Vec<Entry>>, ImString> {
+        Ok(Rc::new(entries.into_iter().collect()))
+    }
+
+    fn row_names(tree: &Tree) -> Vec<String> {
+        tree.rows().iter().map(|row| format!("{}{}", " ".repeat(row.depth), row.name)).collect()
+    }
+
+    fn focused_name(tree: &Tree) -> Option<&str> {
+        tree.focused_row().map(|row| row.name.as_str())
+    }
+
+    /// A tree of `/data.csv`, `/docs/` and `/src/` with the `/src/` folder loaded and expanded.
+    fn loaded_tree() -> Tree {
+        let mut tree = Tree::default();
+        let request = tree.set_content(EmptyFolderContent.into());
+        assert_eq!(request.folder, None);
+        let root = entries([file("/data.csv"), folder("/src"), folder("/docs")]);
+        tree.set_folder_entries(None, root);
+        let request = tree.set_expanded(Path::new("/src"), true).unwrap();
+        assert_eq!(request.folder.as_deref(), Some(Path::new("/src")));
+        let src = entries([file("/src/main.enso"), file("/src/table.xlsx"), folder("/src/lib")]);
+        tree.set_folder_entries(Some(Path::new("/src")), src);
+        tree
+    }
+
+    #[test]
+    fn folders_are_loaded_lazily() {
+        let mut tree = Tree::default();
+        tree.set_content(EmptyFolderContent.into());
+        assert_eq!(tree.root_state(), FolderState::Loading);
+        assert!(tree.rows().is_empty());
+        tree.set_folder_entries(None, entries([file("/b.txt"), folder("/a")]));
+        assert_eq!(tree.root_state(), FolderState::Expanded);
+        // Folders go before files.
+        assert_eq!(row_names(&tree), ["a", "b.txt"]);
+        assert_eq!(tree.rows()[0].kind, RowKind::Folder(FolderState::Collapsed));
+
+        let request = tree.set_expanded(Path::new("/a"), true);
+        assert!(request.is_some());
+        assert_eq!(tree.rows()[0].kind, RowKind::Folder(FolderState::Loading));
+        tree.set_folder_entries(Some(Path::new("/a")), entries([file("/a/c.txt")]));
+        assert_eq!(row_names(&tree), ["a", " c.txt", "b.txt"]);
+
+        // The loaded entries are kept when the folder is collapsed.
+        assert!(tree.set_expanded(Path::new("/a"), false).is_none());
+        assert_eq!(row_names(&tree), ["a", "b.txt"]);
+        assert!(tree.set_expanded(Path::new("/a"), true).is_none());
+        assert_eq!(row_names(&tree), ["a", " c.txt", "b.txt"]);
+    }
+
+    #[test]
+    fn failed_folders_are_reloaded() {
+        let mut tree = Tree::default();
+        tree.set_content(EmptyFolderContent.into());
+        tree.set_folder_entries(None, entries([folder("/a")]));
+        tree.set_expanded(Path::new("/a"), true);
+        tree.set_folder_entries(Some(Path::new("/a")), Err("Access denied.".into()));
+        let failed = RowKind::Folder(FolderState::Failed("Access denied.".into()));
+        assert_eq!(tree.rows()[0].kind, failed);
+        tree.set_expanded(Path::new("/a"), false);
+        assert!(tree.set_expanded(Path::new("/a"), true).is_some());
+    }
+
+    #[test]
+    fn stale_results_are_ignored() {
+        let mut tree = loaded_tree();
+        tree.set_content(EmptyFolderContent.into());
+        tree.set_folder_entries(Some(Path::new("/src")), entries([file("/src/x.txt")]));
+        assert!(tree.rows().is_empty());
+        tree.set_folder_entries(None, entries([file("/y.txt")]));
+        tree.set_folder_entries(None, entries([file("/z.txt")]));
+        assert_eq!(row_names(&tree), ["y.txt"]);
+    }
+
+    #[test]
+    fn navigating_with_keyboard() {
+        let mut tree = loaded_tree();
+        assert_eq!(row_names(&tree), [
+            "docs",
+            "src",
+            " lib",
+            " main.enso",
+            " table.xlsx",
+            "data.csv"
+        ]);
+        assert_eq!(focused_name(&tree), None);
+        tree.move_focus_by(1);
+        assert_eq!(focused_name(&tree), Some("docs"));
+        tree.move_focus_by(-1);
+        assert_eq!(focused_name(&tree), Some("docs"));
+        tree.move_focus_by(100);
+        assert_eq!(focused_name(&tree), Some("data.csv"));
+        tree.move_focus_by(-2);
+        assert_eq!(focused_name(&tree), Some("main.enso"));
+
+        // Going left from a file focuses its parent folder, and then collapses it.
+        tree.move_focus_left();
+        assert_eq!(focused_name(&tree), Some("src"));
+        tree.move_focus_left();
+        assert_eq!(focused_name(&tree), Some("src"));
+        assert_eq!(row_names(&tree), ["docs", "src", "data.csv"]);
+        tree.move_focus_left();
+        assert_eq!(focused_name(&tree), Some("src"));
+
+        // Going right expands the folder, and then enters it.
+        assert!(tree.move_focus_right().is_none());
+        assert_eq!(focused_name(&tree), Some("src"));
+        tree.move_focus_right();
+        assert_eq!(focused_name(&tree), Some("lib"));
+        let request = tree.move_focus_right().unwrap();
+        assert_eq!(request.folder.as_deref(), Some(Path::new("/src/lib")));
+        // The folder being loaded has no entries to enter yet.
+        tree.move_focus_right();
+        assert_eq!(focused_name(&tree), Some("lib"));
+
+        assert!(tree.toggle_focused_folder().is_none());
+        assert_eq!(tree.rows()[2].kind, RowKind::Folder(FolderState::Collapsed));
+    }
+
+    #[test]
+    fn focus_follows_collapsed_folders() {
+        let mut tree = loaded_tree();
+        tree.focus_index(4);
+        assert_eq!(focused_name(&tree), Some("table.xlsx"));
+        tree.set_expanded(Path::new("/src"), false);
+        assert_eq!(focused_name(&tree), Some("src"));
+    }
+
+    #[test]
+    fn filtering_by_extension() {
+        let mut tree = loaded_tree();
+        tree.focus_index(4);
+        assert_eq!(focused_name(&tree), Some("table.xlsx"));
+        tree.set_filter(ExtensionFilter::new([".CSV", "enso"]));
+        assert_eq!(row_names(&tree), ["docs", "src", " lib", " main.enso", "data.csv"]);
+        assert_eq!(focused_name(&tree), Some("src"));
+        tree.set_filter(default());
+        assert_eq!(tree.rows().len(), 6);
+    }
+}
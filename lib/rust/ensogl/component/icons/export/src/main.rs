@@ -0,0 +1,141 @@
+//! Export the icons of the Component Browser to PNG or SVG files, rasterizing them on CPU.
+//!
+//! ```text
+//! ensogl-icons-export [--format <png|svg>] [--pixel-ratio <RATIO>] [--theme <light|dark>]
+//!     <OUTPUT_DIRECTORY>
+//! ```
+//!
+//! The files are named after the icons, and their styles are taken from the builtin theme chosen
+//! with the `--theme` option (`light` by default). The SVG files embed the rasterized PNG images,
+//! as the icons are defined by signed distance functions, which have no vector representation.
+
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+
+use ensogl_core::prelude::*;
+
+use clap::Parser;
+use clap::ValueEnum;
+use ensogl_core::display::shape::primitive::cpu::Rasterizer;
+use ensogl_core::display::shape::StyleWatch;
+use ensogl_core::display::style;
+use ensogl_core::display::style::theme;
+use ensogl_hardcoded_theme::builtin;
+use ensogl_icons::component_icons::Id;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+
+
+// ============
+// === Args ===
+// ============
+
+/// The format of the exported files.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Format {
+    Png,
+    Svg,
+}
+
+/// The builtin theme providing the styles of the icons.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Theme {
+    Light,
+    Dark,
+}
+
+/// Export the icons of the Component Browser to PNG or SVG files.
+#[derive(Debug, Parser)]
+struct Args {
+    /// The format of the exported files.
+    #[clap(long, value_enum, default_value = "png")]
+    format:           Format,
+    /// The number of image pixels per an icon's pixel.
+    #[clap(long, default_value = "1.0")]
+    pixel_ratio:      f32,
+    /// The builtin theme providing the styles of the icons.
+    #[clap(long, value_enum, default_value = "light")]
+    theme:            Theme,
+    /// The directory where the files are written.
+    output_directory: PathBuf,
+}
+
+
+
+// ==============
+// === Export ===
+// ==============
+
+/// The style watch of a style sheet with the given builtin theme enabled.
+fn style_watch(builtin_theme: Theme) -> StyleWatch {
+    let sheet = style::Sheet::new();
+    let themes = theme::Manager::from(&sheet);
+    match builtin_theme {
+        Theme::Light => {
+            builtin::light::register(&themes);
+            builtin::light::enable(&themes);
+        }
+        Theme::Dark => {
+            builtin::dark::register(&themes);
+            builtin::dark::enable(&themes);
+        }
+    }
+    StyleWatch::new(&sheet)
+}
+
+/// An SVG file of the given size, displaying the PNG image.
+fn svg(size: Vector2, png: &[u8]) -> String {
+    let (width, height) = (size.x, size.y);
+    let encoded = base64::encode(png);
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+         viewBox=\"0 0 {width} {height}\"><image width=\"{width}\" height=\"{height}\" \
+         href=\"data:image/png;base64,{encoded}\"/></svg>\n"
+    )
+}
+
+fn export(icon: Id, style: &StyleWatch, args: &Args) -> FallibleResult {
+    let size = icon.size();
+    let rasterizer = Rasterizer::new(size).with_pixel_ratio(args.pixel_ratio);
+    let png = rasterizer.rasterize(&icon.shape_def(style))?.to_png()?;
+    let (extension, contents) = match args.format {
+        Format::Png => ("png", png),
+        Format::Svg => ("svg", svg(size, &png).into_bytes()),
+    };
+    let path = args.output_directory.join(icon.as_str()).with_extension(extension);
+    std::fs::write(path, contents)?;
+    Ok(())
+}
+
+
+
+// ============
+// === Main ===
+// ============
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    if let Err(error) = std::fs::create_dir_all(&args.output_directory) {
+        eprintln!("Cannot create the output directory: {error}");
+        return ExitCode::FAILURE;
+    }
+    let style = style_watch(args.theme);
+    let mut icons = Vec::new();
+    Id::for_each(|icon| icons.push(icon));
+    let mut failed = false;
+    for icon in icons {
+        if let Err(error) = export(icon, &style, &args) {
+            eprintln!("Failed to export the icon {}: {error}", icon.as_str());
+            failed = true;
+        }
+    }
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
@@ -1300,3 +1300,53 @@ impl Default for Id {
         Self::DataScience
     }
 }
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ensogl_core::display::shape::primitive::cpu::golden::assert_matches_golden;
+    use ensogl_core::display::shape::primitive::cpu::Rasterizer;
+    use ensogl_core::display::style;
+    use std::path::Path;
+
+    /// Rasterize the icon with the default style and compare it with the golden image named after
+    /// the icon.
+    fn assert_icon_matches_golden(icon: Id) {
+        let style = StyleWatch::new(&style::Sheet::new());
+        let image = Rasterizer::new(icon.size()).rasterize(&icon.shape_def(&style)).unwrap();
+        let file = format!("golden/{}.png", icon.as_str());
+        assert_matches_golden(&image, Path::new(env!("CARGO_MANIFEST_DIR")).join(file), 2);
+    }
+
+    #[test]
+    fn golden_sub_modules() {
+        assert_icon_matches_golden(Id::SubModules);
+    }
+
+    #[test]
+    fn golden_star() {
+        assert_icon_matches_golden(Id::Star);
+    }
+
+    #[test]
+    fn golden_command_key() {
+        assert_icon_matches_golden(Id::CommandKey);
+    }
+
+    #[test]
+    fn golden_function() {
+        assert_icon_matches_golden(Id::Function);
+    }
+
+    #[test]
+    fn golden_module() {
+        assert_icon_matches_golden(Id::Module);
+    }
+}
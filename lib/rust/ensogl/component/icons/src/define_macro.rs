@@ -99,6 +99,26 @@ macro_rules! define_icons {
                 view
             }
 
+            /// The definition of the icon's shape. It can be rasterized without GPU by the
+            /// [CPU evaluator](ensogl_core::display::shape::primitive::cpu).
+            pub fn shape_def(
+                &self,
+                style: &ensogl_core::display::shape::StyleWatch
+            ) -> ensogl_core::display::shape::AnyShape {
+                use ensogl_core::display::shape::system::Shape as _;
+                match self {$(
+                    Self::$variant => $name::Shape::shape_def(style),
+                )*}
+            }
+
+            /// The declared size of the icon's shape.
+            pub fn size(&self) -> Vector2 {
+                use ensogl_core::display::shape::CachedShape;
+                match self {$(
+                    Self::$variant => Vector2($name::Shape::WIDTH, $name::Shape::HEIGHT),
+                )*}
+            }
+
             /// Call `f` for each possible icon id.
             pub fn for_each<F: FnMut(Self)>(mut f: F) {
                 $(f(Self::$variant);)*
@@ -1,6 +1,7 @@
 use ensogl_core::prelude::*;
 
 use crate::placeholder::StrongPlaceholder;
+use crate::placement::Layout;
 
 use ensogl_core::display;
 use ensogl_core::Animation;
@@ -9,11 +10,15 @@ use ensogl_core::Animation;
 
 ensogl_core::define_endpoints_2! {
     Input {
-        set_margin_left(f32),
+        /// Set the margin separating the item from its neighbour. Depending on the layout, it is
+        /// kept before or after the item along the primary axis. Defaults to zero.
+        set_margin(f32),
         skip_margin_anim(),
+        /// Set the layout of the list containing the item. Defaults to [`Layout::Horizontal`].
+        set_layout(Layout),
     }
     Output {
-        margin_left(f32),
+        margin(f32),
     }
 }
 
@@ -38,27 +43,38 @@ impl<T: display::Object> Item<T> {
         let network = frp.network();
         let elem_obj = elem.display_object();
         placeholder.replace_children(&[&elem_obj]);
-        let margin_left = Animation::<f32>::new_with_init(network, 0.0);
+        let margin = Animation::<f32>::new_with_init(network, 0.0);
         let elem_offset = Animation::<Vector2>::new_with_init(network, elem.position().xy());
-        margin_left.simulator.update_spring(|s| s * crate::DEBUG_ANIMATION_SPRING_FACTOR);
+        margin.simulator.update_spring(|s| s * crate::DEBUG_ANIMATION_SPRING_FACTOR);
         elem_offset.simulator.update_spring(|s| s * crate::DEBUG_ANIMATION_SPRING_FACTOR);
         let debug = Rc::new(Cell::new(false));
 
         frp::extend! { network
-            margin_left.skip <+ frp.skip_margin_anim;
-            margin_left.target <+ frp.set_margin_left;
-            frp.private.output.margin_left <+ margin_left.value;
-            target_size <- all_with(&elem_obj.on_resized, &frp.set_margin_left, |w, m| w.x + m);
+            margin.skip <+ frp.skip_margin_anim;
+            margin.target <+ frp.set_margin;
+            frp.private.output.margin <+ margin.value;
+            placeholder.frp.set_primary_axis <+ frp.set_layout.map(|layout| layout.primary_axis());
+            target_size <- all_with3(&elem_obj.on_resized, &frp.set_margin, &frp.set_layout,
+                |size, margin, layout| size.get_dim(layout.primary_axis()) + margin
+            );
             placeholder.frp.set_target_size <+ target_size;
-            _eval <- all_with(&margin_left.value, &elem_offset.value, f!((margin, offset) {
-                elem_obj.set_xy(*offset + Vector2(*margin, 0.0));
-            }));
+            _eval <- all_with3(&margin.value, &elem_offset.value, &frp.set_layout,
+                f!((margin, offset, layout) {
+                    elem_obj.set_xy(*offset + layout.margin_offset(*margin));
+                })
+            );
         }
+        frp.set_margin.emit(0.0);
+        frp.set_layout.emit(Layout::Horizontal);
         elem_offset.target.emit(Vector2(0.0, 0.0));
         Self { frp, elem, placeholder, debug }
     }
 
-    pub fn set_margin_left(&self, margin: f32) {
-        self.frp.set_margin_left.emit(margin)
+    pub fn set_margin(&self, margin: f32) {
+        self.frp.set_margin.emit(margin)
+    }
+
+    pub fn set_layout(&self, layout: Layout) {
+        self.frp.set_layout.emit(layout)
     }
 }
@@ -1,9 +1,8 @@
 //! List Editor is a list component allowing adding, removing and reordering its items.
 //!
 //! # Primary and secondary axis
-//! The implementation currently only supports horizontal layout, but it's designed to also support
-//! vertical layout. It uses "primary" and "secondary" to describe the list's axis. The primary axis
-//! is where the items are laid out, while the secondary axis is perpendicular to it.
+//! The list uses "primary" and "secondary" to describe its axis. The primary axis is where the
+//! items are laid out, while the secondary axis is perpendicular to it.
 //!
 //! ```text
 //!           ▲                          
@@ -14,6 +13,19 @@
 //! ```
 //!
 //!
+//! # Layouts
+//! The items can be placed in one of the following [`Layout`]s:
+//! - [`Layout::Horizontal`], a single row of items placed from left to right. This is the default.
+//! - [`Layout::Vertical`], a single column of items placed from top to bottom. The primary axis is
+//! vertical, so the items are detached by dragging them horizontally.
+//! - [`Layout::Wrapped`], rows of items placed from left to right, wrapping to a new row below when
+//! the next item does not fit in the maximum width. The insertion point of a dragged item is
+//! computed in the row closest to the mouse along the secondary axis.
+//!
+//! All the layouts support the same adding, removing and reordering of items. See the
+//! [`placement`] module to learn how the items are placed.
+//!
+//!
 //! # Reordering items
 //! To initialize item dragging, it must first be dragged along the secondary axis to detach it from
 //! its current position. Then, it can be freely dragged and placed back in the list by dropping it
@@ -89,6 +101,8 @@ use item::Item;
 use placeholder::Placeholder;
 use placeholder::StrongPlaceholder;
 use placeholder::WeakPlaceholder;
+use placement::Placement;
+use placement::Slot;
 
 
 // ==============
@@ -97,6 +111,9 @@ use placeholder::WeakPlaceholder;
 
 pub mod item;
 pub mod placeholder;
+pub mod placement;
+
+pub use placement::Layout;
 
 
 
@@ -205,13 +222,14 @@ impl<T: display::Object> ItemOrPlaceholder<T> {
     }
 
     /// Replace the item element with a new one. Returns old element if it was replaced.
-    pub fn replace_element(&mut self, element: T) -> Option<T> {
+    pub fn replace_element(&mut self, element: T, layout: Layout) -> Option<T> {
         match self {
             ItemOrPlaceholder::Item(t) =>
                 if t.elem.display_object() == element.display_object() {
                     Some(mem::replace(&mut t.elem, element))
                 } else {
                     let new_item = Item::new_from_placeholder(element, t.placeholder.clone_ref());
+                    new_item.set_layout(layout);
                     Some(mem::replace(t, new_item).elem)
                 },
             _ => None,
@@ -232,15 +250,32 @@ impl<T: display::Object> ItemOrPlaceholder<T> {
     pub fn target_size2(&self) -> f32 {
         match self {
             ItemOrPlaceholder::Placeholder(t) => t.target_size(),
-            ItemOrPlaceholder::Item(t) =>
-                t.placeholder.target_size.value() - t.frp.margin_left.value(),
+            ItemOrPlaceholder::Item(t) => t.placeholder.target_size.value() - t.frp.margin.value(),
         }
     }
 
-    pub fn margin_left(&self) -> f32 {
+    pub fn margin(&self) -> f32 {
         match self {
             ItemOrPlaceholder::Placeholder(_) => 0.0,
-            ItemOrPlaceholder::Item(t) => t.frp.margin_left.value(),
+            ItemOrPlaceholder::Item(t) => t.frp.margin.value(),
+        }
+    }
+
+    /// The space taken by this item or placeholder in the list with the given primary axis.
+    pub fn slot(&self, primary_axis: Axis2) -> Slot {
+        let secondary_axis = primary_axis.orthogonal();
+        let size = self.display_object().map(|t| t.computed_size().get_dim(secondary_axis));
+        Slot::new(self.margin(), self.target_size2(), size.unwrap_or_default())
+    }
+
+    /// Update the item or placeholder to be placed in the given layout.
+    pub fn set_layout(&self, layout: Layout) {
+        match self {
+            ItemOrPlaceholder::Item(t) => t.set_layout(layout),
+            ItemOrPlaceholder::Placeholder(t) =>
+                if let Some(placeholder) = t.upgrade() {
+                    placeholder.set_primary_axis(layout.primary_axis());
+                },
         }
     }
 
@@ -293,6 +328,9 @@ ensogl_core::define_endpoints_2! { <T: ('static + Debug)>
         /// Set the spacing between elements.
         gap(f32),
 
+        /// Set the layout of elements. See docs of this module to learn more.
+        layout(Layout),
+
         /// The distance the user needs to drag the element along secondary axis to start dragging
         /// the element. See docs of this module to learn more.
         secondary_axis_drag_threshold(f32),
@@ -350,11 +388,12 @@ pub struct ListEditor<T: 'static + Debug> {
 
 #[derive(Debug)]
 pub struct Model<T> {
-    cursor: Cursor,
-    items:  VecIndexedBy<ItemOrPlaceholder<T>, ItemOrPlaceholderIndex>,
-    root:   display::object::Instance,
-    layout: display::object::Instance,
-    gap:    f32,
+    cursor:      Cursor,
+    items:       VecIndexedBy<ItemOrPlaceholder<T>, ItemOrPlaceholderIndex>,
+    root:        display::object::Instance,
+    layout:      display::object::Instance,
+    layout_mode: Layout,
+    gap:         f32,
 }
 
 impl<T> Model<T> {
@@ -364,10 +403,11 @@ impl<T> Model<T> {
         let items = default();
         let root = display::object::Instance::new_named("ListEditor");
         let layout = display::object::Instance::new_named("layout");
+        let layout_mode = default();
         let gap = default();
         layout.use_auto_layout().allow_grow_y();
         root.add_child(&layout);
-        Self { cursor, items, root, layout, gap }
+        Self { cursor, items, root, layout, layout_mode, gap }
     }
 }
 
@@ -427,6 +467,7 @@ impl<T: display::Object + CloneRef + Debug> ListEditor<T> {
             pos_diff <- any3(&pos_diff_on_move, &pos_diff_on_down, &pos_diff_on_up);
 
             eval frp.gap((t) model.borrow_mut().set_gap(*t));
+            eval frp.layout((t) model.borrow_mut().set_layout(*t));
 
             // When an item is being dragged, we are connecting to it's `on_resized` endpoint to
             // watch for size changes while dragging. We want to disconnect from it as soon as the
@@ -495,7 +536,8 @@ impl<T: display::Object + CloneRef + Debug> ListEditor<T> {
 
         frp::extend! { network
             init <- source_();
-            gaps <- all(&model_borrowed.layout.on_resized, &init)._0().map(f_!(model.gaps()));
+            placement <- all(&model_borrowed.layout.on_resized, &init)._0();
+            placement <- placement.map(f_!(model.placement()));
 
             // We are debouncing the `is_dragging` stream to avoid double-borrow of list editor, as
             // this event is fired immediately after list-editor instructs cursor to stop dragging.
@@ -503,21 +545,24 @@ impl<T: display::Object + CloneRef + Debug> ListEditor<T> {
 
             opt_index <- all_with7(
                 &frp.gap,
-                &gaps,
+                &placement,
                 &pos_on_move,
                 &model_borrowed.layout.on_resized,
                 &is_dragging,
                 &frp.enable_all_insertion_points,
                 &frp.enable_last_insertion_point,
-                f!([model] (gap, gaps, pos, size, is_dragging, enable_all, enable_last) {
-                    let is_close_x = pos.x > -gap && pos.x < size.x + gap;
-                    let is_close_y = pos.y > 0.0 && pos.y < size.y;
-                    let is_close = is_close_x && is_close_y;
+                f!([model] (gap, placement, pos, size, is_dragging, enable_all, enable_last) {
+                    let layout = placement.layout();
+                    let pos = layout.flow_position(*pos, *size);
+                    let size = layout.flow_size(*size);
+                    let is_close_primary = pos.x > -gap && pos.x < size.x + gap;
+                    let is_close_secondary = pos.y > 0.0 && pos.y < size.y;
+                    let is_close = is_close_primary && is_close_secondary;
                     let enabled = is_close && !is_dragging && (*enable_all || *enable_last);
                     enabled
-                        .and_option_from(|| gaps.find(pos.x))
-                        .filter(|gap| *enable_all || (*enable_last && **gap == gaps.len() - 1))
-                        .and_then(|gap| model.item_or_placeholder_index_to_index(gap))
+                        .and_option_from(|| placement.gap_index(pos))
+                        .filter(|gap| *enable_all || (*enable_last && *gap == placement.len()))
+                        .and_then(|gap| model.item_or_placeholder_index_to_index(gap.into()))
                 })
             ).on_change();
             index <= opt_index;
@@ -586,8 +631,12 @@ impl<T: display::Object + CloneRef + Debug> ListEditor<T> {
             decay.target <+ on_down.constant(0.0);
             no_drag_threshold <- all_with(&decay.value, init_no_drag_threshold, |d, t| d * t);
 
-            init_no_drag <- all_with(&pos_diff, &no_drag_threshold, |p, t| p.x.abs() > *t).on_true();
-            init_drag <- all_with(&pos_diff, init_drag_threshold, |p, t| p.y.abs() > *t).on_true();
+            init_no_drag <- all_with3(&pos_diff, &no_drag_threshold, &frp.layout,
+                |p, t, layout| p.get_dim(layout.primary_axis()).abs() > *t
+            ).on_true();
+            init_drag <- all_with3(&pos_diff, init_drag_threshold, &frp.layout,
+                |p, t, layout| p.get_dim(layout.primary_axis().orthogonal()).abs() > *t
+            ).on_true();
             init_any <- any(init_no_drag, init_drag);
             click_was_handled <- bool(&on_up_cleaning_phase, &init_any).on_change();
             drag_disabled <- bool(&on_up, &init_no_drag).on_change();
@@ -637,9 +686,9 @@ impl<T: display::Object + CloneRef + Debug> ListEditor<T> {
 
         frp::extend! { network
             on_far <- is_close.on_false();
-            center_points <- model_borrowed.layout.on_resized.map(f_!(model.center_points()));
+            placement <- model_borrowed.layout.on_resized.map(f_!(model.placement()));
             pos_close <- pos_on_move.sampled_gate(&is_close);
-            insert_index <- pos_close.map2(&center_points, f!((p, c) model.insert_index(p.x, c)));
+            insert_index <- pos_close.map2(&placement, f!((p, c) model.insert_index(*p, c)));
             insert_index <- insert_index.on_change();
             insert_index <- insert_index.sampled_gate(&is_close);
 
@@ -660,6 +709,7 @@ impl<T: display::Object + CloneRef + Debug> ListEditor<T> {
     /// Initializes default FRP values. See docs of this crate to learn more.
     fn init_frp_values(self) -> Self {
         self.frp.gap(10.0);
+        self.frp.layout(Layout::default());
         self.frp.secondary_axis_drag_threshold(4.0);
         self.frp.primary_axis_no_drag_threshold(4.0);
         self.frp.primary_axis_no_drag_threshold_decay_time(1000.0);
@@ -689,8 +739,9 @@ impl<T: display::Object + CloneRef + Debug> ListEditor<T> {
     pub fn replace_item_no_reposition(&self, index: Index, new_item: T) -> Option<T> {
         let mut model = self.model.borrow_mut();
         let index = model.index_to_item_or_placeholder_index(index)?;
+        let layout = model.layout_mode;
         let item = model.items.get_mut(index)?;
-        item.replace_element(new_item)
+        item.replace_element(new_item, layout)
     }
 
     pub fn trash_item_at(&self, index: Index) -> Option<T> {
@@ -756,16 +807,12 @@ impl<T: display::Object + CloneRef + 'static> SharedModel<T> {
         item.map(|item| self.insert(index, item))
     }
 
-    fn insert_index(&self, x: f32, center_points: &[f32]) -> ItemOrPlaceholderIndex {
-        self.borrow().insert_index(x, center_points)
-    }
-
-    fn gaps(&self) -> Gaps {
-        self.borrow().gaps()
+    fn insert_index(&self, position: Vector2, placement: &Placement) -> ItemOrPlaceholderIndex {
+        self.borrow().insert_index(position, placement)
     }
 
-    fn center_points(&self) -> Vec<f32> {
-        self.borrow().center_points()
+    fn placement(&self) -> Placement {
+        self.borrow().placement()
     }
 
     fn item_or_placeholder_index_to_index(&self, ix: ItemOrPlaceholderIndex) -> Option<Index> {
@@ -773,17 +820,6 @@ impl<T: display::Object + CloneRef + 'static> SharedModel<T> {
     }
 }
 
-#[derive(Clone, Debug, Default, Deref)]
-pub struct Gaps {
-    gaps: Vec<RangeInclusive<f32>>,
-}
-
-impl Gaps {
-    pub fn find(&self, x: f32) -> Option<ItemOrPlaceholderIndex> {
-        self.gaps.iter().position(|gap| gap.contains(&x)).map(|t| t.into())
-    }
-}
-
 impl<T: display::Object + CloneRef + 'static> Model<T> {
     // FIXME: refactor and generalize
     fn screen_to_object_space(&self, screen_pos: Vector2) -> Vector2 {
@@ -816,9 +852,59 @@ impl<T: display::Object + CloneRef + 'static> Model<T> {
 
     fn set_gap(&mut self, gap: f32) {
         self.gap = gap;
+        self.update_auto_layout();
+        self.recompute_margins();
+    }
+
+    fn set_layout(&mut self, layout: Layout) {
+        self.layout_mode = layout;
+        self.update_auto_layout();
+        for item in &self.items {
+            item.set_layout(layout);
+        }
         self.recompute_margins();
     }
 
+    /// Configure the auto-layout of the list to place items according to the current layout. The
+    /// spacing between items is kept by item margins, so only the spacing between the lines of the
+    /// wrapped layout is set.
+    fn update_auto_layout(&self) {
+        let layout = &self.layout;
+        match self.layout_mode {
+            Layout::Horizontal =>
+                layout.set_row_flow().set_grid_packing().set_rows_reversed(false).unset_max_size_x(),
+            Layout::Vertical => layout
+                .set_column_flow()
+                .set_grid_packing()
+                .set_rows_reversed(true)
+                .unset_max_size_x(),
+            Layout::Wrapped { max_width } => layout
+                .set_row_flow()
+                .set_wrap_packing()
+                .set_rows_reversed(true)
+                .set_max_size_x(max_width),
+        };
+        layout.set_gap((0.0, self.layout_mode.line_gap(self.gap)));
+    }
+
+    fn primary_axis(&self) -> Axis2 {
+        self.layout_mode.primary_axis()
+    }
+
+    /// Wrap the element in an [`Item`] placed according to the current layout.
+    fn new_item(&self, elem: T) -> Item<T> {
+        let item = Item::new(elem);
+        item.set_layout(self.layout_mode);
+        item
+    }
+
+    /// A placeholder sized along the primary axis of the current layout.
+    fn new_placeholder(&self) -> StrongPlaceholder {
+        let placeholder = StrongPlaceholder::new();
+        placeholder.set_primary_axis(self.primary_axis());
+        placeholder
+    }
+
     /// Find an element by the provided display object reference.
     fn item_index_of(
         &self,
@@ -859,7 +945,7 @@ impl<T: display::Object + CloneRef + 'static> Model<T> {
 
     fn push_no_reposition(&mut self, item: T) -> Index {
         let index = self.len();
-        let item = Item::new(item);
+        let item = self.new_item(item);
         self.items.push(item.into());
         index
     }
@@ -873,7 +959,7 @@ impl<T: display::Object + CloneRef + 'static> Model<T> {
 
     fn insert_no_reposition(&mut self, index: Index, item: T) -> Index {
         if let Some(index2) = self.index_to_item_or_placeholder_index(index) {
-            let item = Item::new(item);
+            let item = self.new_item(item);
             self.items.insert(index2, item.into());
             index
         } else {
@@ -891,19 +977,25 @@ impl<T: display::Object + CloneRef + 'static> Model<T> {
     }
 
     /// Recompute margins of all elements. The first element that is not a weak placeholder does not
-    /// have a left margin. Every other element has. Weak placeholders are used for animation of the
+    /// have a margin. Every other element has. Weak placeholders are used for animation of the
     /// space leftover after removing an element, so they will collapse over time to zero-space.
+    ///
+    /// If the layout uses trailing margins, the elements are visited in the reversed order, so the
+    /// last element does not have a margin instead.
     fn recompute_margins(&self) {
         let mut first_elem = true;
-        for item in &self.items {
-            match item {
-                ItemOrPlaceholder::Placeholder(Placeholder::Weak(_)) => {}
-                ItemOrPlaceholder::Placeholder(Placeholder::Strong(_)) => first_elem = false,
-                ItemOrPlaceholder::Item(t) => {
-                    t.set_margin_left(if first_elem { 0.0 } else { self.gap });
-                    first_elem = false;
-                }
+        let mut update_margin = |item: &ItemOrPlaceholder<T>| match item {
+            ItemOrPlaceholder::Placeholder(Placeholder::Weak(_)) => {}
+            ItemOrPlaceholder::Placeholder(Placeholder::Strong(_)) => first_elem = false,
+            ItemOrPlaceholder::Item(t) => {
+                t.set_margin(if first_elem { 0.0 } else { self.gap });
+                first_elem = false;
             }
+        };
+        if self.layout_mode.has_trailing_margins() {
+            self.items.iter().rev().for_each(&mut update_margin);
+        } else {
+            self.items.iter().for_each(&mut update_margin);
         }
     }
 
@@ -912,10 +1004,20 @@ impl<T: display::Object + CloneRef + 'static> Model<T> {
         self.items.iter().enumerate().find(|(_, t)| t.is_item()).map(|(i, _)| i.into())
     }
 
+    /// The index of the last element. In case there are no elements, [`None`] is returned.
+    fn last_item_index(&self) -> Option<ItemOrPlaceholderIndex> {
+        self.items.iter().enumerate().rfind(|(_, t)| t.is_item()).map(|(i, _)| i.into())
+    }
+
     /// Get the margin at the given insertion point. If the insertion point is before the first
-    /// item, the margin will be 0.
+    /// item, the margin will be 0. If the layout uses trailing margins, the margin will be 0 after
+    /// the last item instead.
     fn margin_at(&self, index: ItemOrPlaceholderIndex) -> f32 {
-        self.first_item_index().map_or(0.0, |i| if index <= i { 0.0 } else { self.gap })
+        if self.layout_mode.has_trailing_margins() {
+            self.last_item_index().map_or(0.0, |i| if index > i { 0.0 } else { self.gap })
+        } else {
+            self.first_item_index().map_or(0.0, |i| if index <= i { 0.0 } else { self.gap })
+        }
     }
 
     /// Retain only items and placeholders that did not collapse yet (both strong and weak ones).
@@ -968,7 +1070,8 @@ impl<T: display::Object + CloneRef + 'static> Model<T> {
             if let Some((placeholder2_index, placeholder2)) = placeholder_to_merge {
                 placeholder2.drop_self_ref();
                 self.items.remove(placeholder2_index);
-                placeholder.update_size(|t| t + placeholder2.computed_size().x);
+                let placeholder2_size = placeholder2.computed_size().get_dim(self.primary_axis());
+                placeholder.update_size(|t| t + placeholder2_size);
             }
             self.items[placeholder_index] = placeholder.clone().into();
             (placeholder_index, placeholder)
@@ -1044,12 +1147,14 @@ impl<T: display::Object + CloneRef + 'static> Model<T> {
         match self.items.remove(index) {
             ItemOrPlaceholder::Item(item) => {
                 self.collapse_all_placeholders_no_margin_update();
-                let item_size = item.computed_size().x;
+                let item_size = item.computed_size().get_dim(self.primary_axis());
                 if let Some(placeholder) = self.get_merged_placeholder_at(index) {
                     placeholder.update_size(|t| t + item_size);
                     placeholder.set_target_size(item_size);
                 } else {
-                    self.items.insert(index, Placeholder::new_with_size(item_size).into())
+                    let placeholder = StrongPlaceholder::new_with_size(item_size);
+                    placeholder.set_primary_axis(self.primary_axis());
+                    self.items.insert(index, placeholder.into())
                 }
                 self.reposition_items();
                 Some(item.elem)
@@ -1069,9 +1174,10 @@ impl<T: display::Object + CloneRef + 'static> Model<T> {
             self.cursor.with_dragged_item_if_is::<T, _>(|t| t.display_object().clone())
         {
             self.collapse_all_placeholders_no_margin_update();
-            let item_size = item.computed_size().x + self.margin_at(index);
+            let item_size = item.computed_size().get_dim(self.primary_axis());
+            let item_size = item_size + self.margin_at(index);
             let placeholder = self.get_merged_placeholder_at(index).unwrap_or_else(|| {
-                let placeholder = StrongPlaceholder::new();
+                let placeholder = self.new_placeholder();
                 if index >= ItemOrPlaceholderIndex::from(self.items.len()) {
                     self.items.push(placeholder.clone().into());
                 } else {
@@ -1094,16 +1200,19 @@ impl<T: display::Object + CloneRef + 'static> Model<T> {
             let actual_index = if let Some((index, placeholder)) =
                 self.get_indexed_merged_placeholder_at(index)
             {
-                placeholder.set_target_size(placeholder.computed_size().x);
+                let placeholder_size = placeholder.computed_size().get_dim(self.primary_axis());
+                placeholder.set_target_size(placeholder_size);
                 item.update_xy(|t| t - placeholder.global_position().xy());
-                self.items[index] = Item::new_from_placeholder(item, placeholder).into();
+                let item = Item::new_from_placeholder(item, placeholder);
+                item.set_layout(self.layout_mode);
+                self.items[index] = item.into();
                 index
             } else {
                 // This branch should never be reached, as when dragging an item we always create
                 // a placeholder for it (see the [`Self::add_insertion_point_if_type_match`]
                 // function). However, in case something breaks, we want it to still
                 // provide the user with the correct outcome.
-                self.items.insert(index, Item::new(item).into());
+                self.items.insert(index, self.new_item(item).into());
                 warn!("An element was inserted without a placeholder. This should not happen.");
                 index
             };
@@ -1154,52 +1263,18 @@ impl<T: display::Object + CloneRef + 'static> Model<T> {
         }
     }
 
-    /// Get the center points of items and placeholders. This is used to determine the index of the
-    /// insertion point when a new item is being dragged.
-    ///
-    /// ```text                                                    
-    ///   ┬      ┬         ┬         ┬
-    /// ╭─┼─╮ ╭──┼──╮ ╭╌╌╌╌┼╌╌╌╌╮ ╭──┼──╮  
-    /// │ ┆ │ │  ┆  │ ┆    ┆    ┆ │  ┆  │  
-    /// ╰─┼─╯ ╰──┼──╯ ╰╌╌╌╌┼╌╌╌╌╯ ╰──┼──╯
-    ///   ┴      ┴         ┴         ┴
-    /// ```       
-    fn center_points(&self) -> Vec<f32> {
-        let mut centers = Vec::new();
-        let mut current = 0.0;
-        for item in &self.items {
-            let size2 = item.target_size2() / 2.0;
-            current += item.margin_left() + size2;
-            centers.push(current);
-            current += size2;
-        }
-        centers
-    }
-
-    fn gaps(&self) -> Gaps {
-        if self.items.is_empty() {
-            return Gaps { gaps: vec![f32::NEG_INFINITY..=f32::INFINITY] };
-        }
-
-        let mut gaps = Vec::new();
-        gaps.push(f32::NEG_INFINITY..=0.0);
-        let mut fist_gap = true;
-        let mut current = 0.0;
-        for item in &self.items {
-            let start = current;
-            current += item.margin_left();
-            if !fist_gap {
-                gaps.push(start..=current);
-            }
-            fist_gap = false;
-            current += item.target_size2();
-        }
-        gaps.push(current..=f32::INFINITY);
-        Gaps { gaps }
+    /// The placement of items and placeholders, used to determine the insertion points and the
+    /// index of the dropped item. See the [`placement`] module to learn more.
+    fn placement(&self) -> Placement {
+        let axis = self.primary_axis();
+        let slots = self.items.iter().map(|item| item.slot(axis)).collect();
+        Placement::new(self.layout_mode, self.gap, slots)
     }
 
-    /// The insertion point of the given vertical offset.
-    fn insert_index(&self, x: f32, center_points: &[f32]) -> ItemOrPlaceholderIndex {
-        center_points.iter().position(|t| x < *t).unwrap_or(self.items.len()).into()
+    /// The insertion point of the given position in the list object space.
+    fn insert_index(&self, position: Vector2, placement: &Placement) -> ItemOrPlaceholderIndex {
+        let size = self.layout.computed_size();
+        let position = placement.layout().flow_position(position, size);
+        placement.insert_index(position).into()
     }
 }
@@ -15,6 +15,9 @@ use ensogl_core::Animation;
 ensogl_core::define_endpoints_2! {
     Input {
         set_target_size (f32),
+        /// The axis along which the size of the placeholder is animated. The size along the other
+        /// axis hugs the placeholder children. Defaults to [`Axis2::X`].
+        set_primary_axis (Axis2),
         skip_animation(),
     }
     Output {
@@ -151,13 +154,18 @@ impl StrongPlaceholder {
             size.skip <+ model.frp.private.input.skip_animation;
             model.frp.private.output.target_size <+ model.frp.private.input.set_target_size;
 
-            eval size.value ((t) display_object.set_size_x(*t).void());
+            size_along_axis <- all(&size.value, &model.frp.private.input.set_primary_axis);
+            eval size_along_axis ([display_object] ((size, axis)) match axis {
+                Axis2::X => display_object.set_size_x_hug(*size).void(),
+                Axis2::Y => display_object.set_size_hug_y(*size).void(),
+            });
             eval_ size.on_end ([collapsing, self_ref] {
                 if collapsing.get() {
                     self_ref.borrow_mut().take();
                 }
             });
         }
+        model.frp.set_primary_axis(Axis2::X);
         Self { model }
     }
 
@@ -0,0 +1,423 @@
+//! The placement of the list items. It describes the supported layouts of the list and computes
+//! where the items are placed along the primary and secondary axes of the list, which is used to
+//! find the insertion points and the index of the dropped item.
+//!
+//! # Flow space
+//! All computations are performed in the "flow space", a coordinate system in which the first
+//! coordinate is the offset along the primary axis in the direction items are placed, and the
+//! second one is the offset along the secondary axis in the direction new lines are placed. The
+//! origin of the flow space is placed where the first item starts. For example, in the wrapped
+//! layout, the flow space origin is the top left corner of the list, with the secondary axis
+//! pointing down.
+//!
+//! ```text
+//!    ┼─────────────────────────────▶ primary axis
+//!    │ ╭─────╮ ╭─────╮ ╭─────────╮
+//!    │ │  0  │ │  1  │ │    2    │
+//!    │ ╰─────╯ ╰─────╯ ╰─────────╯
+//!    │ ╭─────────╮ ╭─────╮
+//!    │ │    3    │ │  4  │
+//!    │ ╰─────────╯ ╰─────╯
+//!    ▼
+//! secondary axis
+//! ```
+
+use ensogl_core::prelude::*;
+
+
+
+// ==============
+// === Layout ===
+// ==============
+
+/// The layout of the list items.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Layout {
+    /// Items are placed in a single row, from left to right.
+    #[default]
+    Horizontal,
+    /// Items are placed in a single column, from top to bottom.
+    Vertical,
+    /// Items are placed in rows from left to right. When an item does not fit in the given width,
+    /// it is placed in a new row below.
+    Wrapped { max_width: f32 },
+}
+
+impl Layout {
+    /// The axis along which the items are placed.
+    pub fn primary_axis(self) -> Axis2 {
+        match self {
+            Self::Vertical => Axis2::Y,
+            _ => Axis2::X,
+        }
+    }
+
+    /// Check whether the gap between items is kept after every item but the last one. Otherwise,
+    /// the gap is kept before every item but the first one. The wrapped layout uses the trailing
+    /// gaps, so the items starting a new row are not preceded by a gap.
+    pub fn has_trailing_margins(self) -> bool {
+        matches!(self, Self::Wrapped { .. })
+    }
+
+    /// The spacing between lines of items, given the spacing between items. Only the wrapped layout
+    /// places items in multiple lines.
+    pub fn line_gap(self, gap: f32) -> f32 {
+        if matches!(self, Self::Wrapped { .. }) {
+            gap
+        } else {
+            0.0
+        }
+    }
+
+    /// The offset of the item element within its slot, caused by the margin kept before it.
+    pub fn margin_offset(self, margin: f32) -> Vector2 {
+        match self {
+            Self::Horizontal => Vector2(margin, 0.0),
+            // In the vertical layout, the margin is placed above the element, which is the end of
+            // the slot along the Y axis. In the wrapped layout, the margin follows the element.
+            Self::Vertical | Self::Wrapped { .. } => Vector2(0.0, 0.0),
+        }
+    }
+
+    /// Convert the position in the list object space to the flow space. See the docs of this
+    /// module to learn more.
+    pub fn flow_position(self, position: Vector2, list_size: Vector2) -> Vector2 {
+        match self {
+            Self::Vertical => Vector2(list_size.y - position.y, position.x),
+            _ => Vector2(position.x, list_size.y - position.y),
+        }
+    }
+
+    /// Convert the size in the list object space to the flow space.
+    pub fn flow_size(self, size: Vector2) -> Vector2 {
+        match self {
+            Self::Vertical => Vector2(size.y, size.x),
+            _ => size,
+        }
+    }
+}
+
+
+
+// ============
+// === Slot ===
+// ============
+
+/// The space taken by an item or a placeholder in the list.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Slot {
+    /// The margin kept before or after the slot, depending on the layout. See
+    /// [`Layout::has_trailing_margins`].
+    pub margin:         f32,
+    /// The size along the primary axis, excluding the margin.
+    pub size:           f32,
+    /// The size along the secondary axis.
+    pub secondary_size: f32,
+}
+
+impl Slot {
+    /// Constructor.
+    pub fn new(margin: f32, size: f32, secondary_size: f32) -> Self {
+        Self { margin, size, secondary_size }
+    }
+
+    /// The total size along the primary axis, including the margin.
+    pub fn total_size(&self) -> f32 {
+        self.margin + self.size
+    }
+}
+
+
+
+// ============
+// === Line ===
+// ============
+
+/// A line of slots placed along the primary axis. The horizontal and vertical layouts always have a
+/// single line, while the wrapped layout has a line for every row.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Line {
+    /// The indexes of slots placed in this line.
+    pub slots: Range<usize>,
+    /// The offset of the line along the secondary axis.
+    pub start: f32,
+    /// The size of the line along the secondary axis.
+    pub size:  f32,
+}
+
+
+
+// =================
+// === Placement ===
+// =================
+
+/// The placement of slots in the list. Slots are divided into lines in the same way the
+/// auto-layout of the list object does it. See the docs of this module to learn more.
+#[derive(Clone, Debug, Default)]
+pub struct Placement {
+    layout:   Layout,
+    line_gap: f32,
+    slots:    Vec<Slot>,
+    /// The offsets of slots along the primary axis, relative to the start of their lines.
+    starts:   Vec<f32>,
+    lines:    Vec<Line>,
+}
+
+impl Placement {
+    /// Constructor. The `gap` is the spacing between items, used also as a spacing between lines
+    /// of the wrapped layout.
+    pub fn new(layout: Layout, gap: f32, slots: Vec<Slot>) -> Self {
+        let max_line_size = match layout {
+            Layout::Wrapped { max_width } => max_width,
+            _ => f32::INFINITY,
+        };
+        let line_gap = layout.line_gap(gap);
+        let mut starts = Vec::with_capacity(slots.len());
+        let mut lines: Vec<Line> = Vec::new();
+        let mut line = Line::default();
+        let mut line_size = 0.0;
+        for (index, slot) in slots.iter().enumerate() {
+            let line_full = line_size + slot.total_size() > max_line_size;
+            if !line.slots.is_empty() && line_full {
+                let start = line.start + line.size + line_gap;
+                lines.push(mem::replace(&mut line, Line { slots: index..index, start, size: 0.0 }));
+                line_size = 0.0;
+            }
+            starts.push(line_size);
+            line_size += slot.total_size();
+            line.slots.end = index + 1;
+            line.size = line.size.max(slot.secondary_size);
+        }
+        lines.push(line);
+        Self { layout, line_gap, slots, starts, lines }
+    }
+
+    /// The layout used to place the slots.
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    /// The number of slots.
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Check whether there are no slots.
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// The lines of slots. There is always at least one line, even if there are no slots.
+    pub fn lines(&self) -> &[Line] {
+        &self.lines
+    }
+
+    /// The offset along the primary axis where the element of the given slot starts, excluding its
+    /// leading margin.
+    fn elem_start(&self, index: usize) -> f32 {
+        let margin =
+            if self.layout.has_trailing_margins() { 0.0 } else { self.slots[index].margin };
+        self.starts[index] + margin
+    }
+
+    /// The offset along the primary axis where the element of the given slot ends, excluding its
+    /// trailing margin.
+    fn elem_end(&self, index: usize) -> f32 {
+        self.elem_start(index) + self.slots[index].size
+    }
+
+    /// The line at the given offset along the secondary axis. The space between lines is split in
+    /// half between them. Offsets before the first line and after the last one belong to these
+    /// lines, respectively.
+    pub fn line_at(&self, secondary: f32) -> &Line {
+        let half_gap = self.line_gap / 2.0;
+        let line = self.lines.iter().find(|line| secondary < line.start + line.size + half_gap);
+        line.unwrap_or_else(|| self.lines.last().unwrap())
+    }
+
+    /// The center points of slots along the primary axis, relative to the start of their lines.
+    /// They are used to determine the index of the insertion point when a new item is being
+    /// dragged.
+    ///
+    /// ```text
+    ///   ┬      ┬         ┬         ┬
+    /// ╭─┼─╮ ╭──┼──╮ ╭╌╌╌╌┼╌╌╌╌╮ ╭──┼──╮
+    /// │ ┆ │ │  ┆  │ ┆    ┆    ┆ │  ┆  │
+    /// ╰─┼─╯ ╰──┼──╯ ╰╌╌╌╌┼╌╌╌╌╯ ╰──┼──╯
+    ///   ┴      ┴         ┴         ┴
+    /// ```
+    pub fn center_points(&self) -> Vec<f32> {
+        (0..self.len()).map(|index| self.elem_start(index) + self.slots[index].size / 2.0).collect()
+    }
+
+    /// The index at which an item dropped at the given flow space position should be inserted.
+    /// The line is chosen by the secondary axis offset, and then the item is inserted before the
+    /// first slot of the line whose center point is after the primary axis offset.
+    pub fn insert_index(&self, position: Vector2) -> usize {
+        let line = self.line_at(position.y);
+        let center = |index: usize| self.elem_start(index) + self.slots[index].size / 2.0;
+        line.slots.clone().find(|index| position.x < center(*index)).unwrap_or(line.slots.end)
+    }
+
+    /// The index of the insertion point at the given flow space position, if the position is over
+    /// a gap between items or before the first or after the last item of a line. Positions over
+    /// items do not point to any insertion point.
+    ///
+    /// ```text
+    ///  0   ╭─────╮ 1 ╭─────╮ 2 ╭─────╮   3
+    /// ◀──▶ │     │◀─▶│     │◀─▶│     │ ◀───▶
+    ///      ╰─────╯   ╰─────╯   ╰─────╯
+    /// ```
+    pub fn gap_index(&self, position: Vector2) -> Option<usize> {
+        if self.is_empty() {
+            return Some(0);
+        }
+        let line = self.line_at(position.y);
+        let x = position.x;
+        let first = line.slots.start;
+        let last = line.slots.end - 1;
+        if x <= 0.0 {
+            Some(first)
+        } else if x >= self.elem_end(last) {
+            Some(line.slots.end)
+        } else {
+            let gap = |index: usize| self.elem_end(index - 1)..=self.elem_start(index);
+            (first + 1..=last).find(|index| gap(*index).contains(&x))
+        }
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GAP: f32 = 10.0;
+
+    /// Slots of the given sizes, separated with [`GAP`], as set by the list editor.
+    fn slots(layout: Layout, sizes: &[(f32, f32)]) -> Vec<Slot> {
+        let last = sizes.len().saturating_sub(1);
+        let first_or_last = if layout.has_trailing_margins() { last } else { 0 };
+        let margin = |index: usize| if index == first_or_last { 0.0 } else { GAP };
+        let slots = sizes.iter().enumerate();
+        slots
+            .map(|(index, (size, secondary))| Slot::new(margin(index), *size, *secondary))
+            .collect()
+    }
+
+    fn line_slots(placement: &Placement) -> Vec<Range<usize>> {
+        placement.lines().iter().map(|line| line.slots.clone()).collect()
+    }
+
+    #[test]
+    fn converting_to_flow_space() {
+        let size = Vector2(100.0, 50.0);
+        let position = Vector2(30.0, 10.0);
+        assert_eq!(Layout::Horizontal.flow_position(position, size), Vector2(30.0, 40.0));
+        assert_eq!(Layout::Vertical.flow_position(position, size), Vector2(40.0, 30.0));
+        let wrapped = Layout::Wrapped { max_width: 100.0 };
+        assert_eq!(wrapped.flow_position(position, size), Vector2(30.0, 40.0));
+        assert_eq!(Layout::Vertical.flow_size(size), Vector2(50.0, 100.0));
+        assert_eq!(Layout::Vertical.primary_axis(), Axis2::Y);
+        assert_eq!(wrapped.primary_axis(), Axis2::X);
+    }
+
+    #[test]
+    fn single_line_placement() {
+        let layout = Layout::Horizontal;
+        let placement = Placement::new(layout, GAP, slots(layout, &[(20.0, 5.0); 3]));
+        assert_eq!(line_slots(&placement), vec![0..3]);
+        assert_eq!(placement.center_points(), [10.0, 40.0, 70.0]);
+
+        let secondary = 2.0;
+        let insert_index = |x: f32| placement.insert_index(Vector2(x, secondary));
+        assert_eq!(insert_index(-5.0), 0);
+        assert_eq!(insert_index(15.0), 1);
+        assert_eq!(insert_index(45.0), 2);
+        assert_eq!(insert_index(200.0), 3);
+
+        let gap_index = |x: f32| placement.gap_index(Vector2(x, secondary));
+        assert_eq!(gap_index(-5.0), Some(0));
+        assert_eq!(gap_index(10.0), None);
+        assert_eq!(gap_index(25.0), Some(1));
+        assert_eq!(gap_index(40.0), None);
+        assert_eq!(gap_index(55.0), Some(2));
+        assert_eq!(gap_index(85.0), Some(3));
+        // The secondary axis offset does not matter when there is a single line.
+        assert_eq!(placement.insert_index(Vector2(15.0, 100.0)), 1);
+        assert_eq!(placement.gap_index(Vector2(25.0, -100.0)), Some(1));
+    }
+
+    #[test]
+    fn vertical_placement() {
+        // Three items of height 20 placed from top to bottom. The list is 80 high and 30 wide.
+        let layout = Layout::Vertical;
+        let placement = Placement::new(layout, GAP, slots(layout, &[(20.0, 30.0); 3]));
+        let size = Vector2(30.0, 80.0);
+        let insert_index =
+            |y: f32| placement.insert_index(layout.flow_position(Vector2(5.0, y), size));
+        assert_eq!(insert_index(90.0), 0);
+        assert_eq!(insert_index(75.0), 0);
+        assert_eq!(insert_index(65.0), 1);
+        assert_eq!(insert_index(35.0), 2);
+        assert_eq!(insert_index(5.0), 3);
+        let gap_index = |y: f32| placement.gap_index(layout.flow_position(Vector2(5.0, y), size));
+        assert_eq!(gap_index(85.0), Some(0));
+        assert_eq!(gap_index(55.0), Some(1));
+        assert_eq!(gap_index(45.0), None);
+        assert_eq!(gap_index(25.0), Some(2));
+        assert_eq!(gap_index(-5.0), Some(3));
+    }
+
+    #[test]
+    fn wrapped_placement() {
+        // Items with their trailing gaps:
+        // ╭ 0: 30 ─────╮╭ 1: 30 ─────╮
+        // ╰────────────╯╰────────────╯
+        // ╭ 2: 50 ─────────────╮╭ 3 ╮
+        // ╰────────────────────╯╰───╯
+        // ╭ 4: 80 ──────────────────────╮
+        // ╰─────────────────────────────╯
+        let layout = Layout::Wrapped { max_width: 85.0 };
+        let sizes = [(30.0, 10.0), (30.0, 10.0), (50.0, 20.0), (5.0, 10.0), (80.0, 10.0)];
+        let placement = Placement::new(layout, GAP, slots(layout, &sizes));
+        assert_eq!(line_slots(&placement), [0..2, 2..4, 4..5]);
+        let line_starts = placement.lines().iter().map(|line| line.start).collect_vec();
+        assert_eq!(line_starts, [0.0, 20.0, 50.0]);
+        assert_eq!(placement.center_points(), [15.0, 55.0, 25.0, 62.5, 40.0]);
+
+        // The secondary axis offset selects the line, including the half of the gap around it.
+        let insert_index = |x: f32, y: f32| placement.insert_index(Vector2(x, y));
+        assert_eq!(insert_index(0.0, -10.0), 0);
+        assert_eq!(insert_index(20.0, 5.0), 1);
+        assert_eq!(insert_index(80.0, 14.0), 2);
+        assert_eq!(insert_index(0.0, 16.0), 2);
+        assert_eq!(insert_index(30.0, 30.0), 3);
+        assert_eq!(insert_index(80.0, 44.0), 4);
+        assert_eq!(insert_index(0.0, 46.0), 4);
+        assert_eq!(insert_index(50.0, 100.0), 5);
+
+        let gap_index = |x: f32, y: f32| placement.gap_index(Vector2(x, y));
+        assert_eq!(gap_index(35.0, 5.0), Some(1));
+        assert_eq!(gap_index(75.0, 5.0), Some(2));
+        assert_eq!(gap_index(-5.0, 30.0), Some(2));
+        assert_eq!(gap_index(20.0, 30.0), None);
+        assert_eq!(gap_index(55.0, 30.0), Some(3));
+        assert_eq!(gap_index(70.0, 30.0), Some(4));
+        assert_eq!(gap_index(90.0, 55.0), Some(5));
+    }
+
+    #[test]
+    fn empty_placement() {
+        let layout = Layout::Wrapped { max_width: 100.0 };
+        let placement = Placement::new(layout, GAP, vec![]);
+        assert_eq!(line_slots(&placement), vec![0..0]);
+        assert_eq!(placement.insert_index(Vector2(10.0, 10.0)), 0);
+        assert_eq!(placement.gap_index(Vector2(10.0, 10.0)), Some(0));
+    }
+}
@@ -6,6 +6,7 @@ use crate::prelude::*;
 use enso_text::unit::*;
 
 use crate::buffer::formatting::Formatting;
+use crate::buffer::layout::LineLayout;
 use crate::buffer::rope::formatted::FormattedRope;
 
 use enso_font::NonVariableFaceHeader;
@@ -20,6 +21,7 @@ use enso_text::text::BoundsError;
 
 pub mod formatting;
 pub mod index;
+pub mod layout;
 pub mod movement;
 pub mod rope;
 pub mod selection;
@@ -298,6 +300,8 @@ pub struct BufferModelData {
     /// The line that corresponds to `ViewLine(0)`.
     first_view_line:   Cell<Line>,
     view_line_count:   Cell<Option<usize>>,
+    /// Layouts of the wrapped lines. Lines not present here are displayed as a single visual line.
+    line_layouts:      RefCell<BTreeMap<Line, LineLayout>>,
 }
 
 impl BufferModel {
@@ -628,6 +632,7 @@ impl BufferModel {
         let selected_line_count = redraw_end_line - redraw_start_line + Line(1);
         let inserted_line_count = local_byte_selection.end.line - redraw_start_line + Line(1);
         let line_diff = inserted_line_count - selected_line_count;
+        self.update_line_layouts_after_change(redraw_start_line..=redraw_end_line, line_diff);
 
         let loc_selection =
             Selection::<Location>::from_in_context_snapped(self, new_byte_selection);
@@ -724,6 +729,39 @@ impl BufferModel {
 }
 
 
+// === Layout ===
+
+impl BufferModel {
+    /// Layout of the provided line. See the [`layout`] module docs to learn more.
+    pub fn line_layout(&self, line: Line) -> LineLayout {
+        self.line_layouts.borrow().get(&line).cloned().unwrap_or_default()
+    }
+
+    /// Set the layout of the provided line. It should be called by the view after laying out the
+    /// line, so the cursor movement follows the visual lines.
+    pub fn set_line_layout(&self, line: Line, layout: LineLayout) {
+        let mut line_layouts = self.line_layouts.borrow_mut();
+        if layout.is_wrapped() {
+            line_layouts.insert(line, layout);
+        } else {
+            line_layouts.remove(&line);
+        }
+    }
+
+    /// Forget the layouts of the changed lines, as they need to be laid out again, and move the
+    /// layouts of lines placed after the change.
+    fn update_line_layouts_after_change(&self, changed: RangeInclusive<Line>, line_diff: LineDiff) {
+        let mut line_layouts = self.line_layouts.borrow_mut();
+        line_layouts.retain(|line, _| !changed.contains(line));
+        if line_diff != LineDiff(0) {
+            let to_update = line_layouts.drain_filter(|line, _| line > changed.end());
+            let updated = to_update.map(|(line, layout)| (line + line_diff, layout)).collect_vec();
+            line_layouts.extend(updated);
+        }
+    }
+}
+
+
 // === Undo / Redo ===
 
 impl BufferModel {
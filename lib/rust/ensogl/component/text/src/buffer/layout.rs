@@ -0,0 +1,175 @@
+//! Layout of buffer lines in the text view. A buffer line can be displayed as several visual lines
+//! when it is soft-wrapped to fit the view width. Wrapping does not modify the buffer, the buffer
+//! line is still a single line, with a single range of columns.
+//!
+//! Lines are wrapped at the Unicode line break opportunities (see
+//! https://www.unicode.org/reports/tr14), so words are not split unless a single word does not fit
+//! the width. Whitespace at the end of a visual line is allowed to overflow the width, as it is not
+//! visible anyway.
+
+use crate::prelude::*;
+use enso_text::unit::*;
+
+use enso_text::index::Byte;
+use enso_text::Rope;
+
+
+
+// ==================
+// === LineLayout ===
+// ==================
+
+/// Layout of a single buffer line. Describes the columns at which the visual lines start. A
+/// location at the boundary of two visual lines is displayed at the beginning of the second one.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LineLayout {
+    /// The first columns of visual lines, skipping the first visual line starting at column 0.
+    starts: Vec<Column>,
+}
+
+impl LineLayout {
+    /// Wrap the line to visual lines not wider than `width`. The `divs` are the x-axis positions of
+    /// the caret at every column of the line, as described in [`crate::component::line::View`].
+    pub fn wrap(text: &Rope, divs: &[f32], width: f32) -> Self {
+        let content = text.to_string();
+        let mut break_offsets =
+            unicode_linebreak::linebreaks(&content).map(|(offset, _)| Byte(offset)).peekable();
+        let mut starts = vec![];
+        let mut start = Column(0);
+        let mut last_break = None;
+        let mut offset = Byte(0);
+        for (index, right) in divs.iter().skip(1).enumerate() {
+            let column = Column(index);
+            let Some(next_offset) = text.next_grapheme_offset(offset) else { break };
+            let grapheme = &content[offset.value..next_offset.value];
+            let is_whitespace = grapheme.chars().all(char::is_whitespace);
+            while !is_whitespace && column > start && right - divs[start.value] > width {
+                start = last_break.take().unwrap_or(column);
+                starts.push(start);
+            }
+            while break_offsets.next_if(|break_offset| *break_offset < next_offset).is_some() {}
+            if break_offsets.peek() == Some(&next_offset) {
+                last_break = Some(column + Column(1));
+            }
+            offset = next_offset;
+        }
+        Self { starts }
+    }
+
+    /// Check whether the line is displayed as more than one visual line.
+    pub fn is_wrapped(&self) -> bool {
+        !self.starts.is_empty()
+    }
+
+    /// The number of visual lines.
+    pub fn visual_line_count(&self) -> usize {
+        self.starts.len() + 1
+    }
+
+    /// Index of the visual line displaying the provided column.
+    pub fn visual_line_of(&self, column: Column) -> usize {
+        self.starts.partition_point(|start| *start <= column)
+    }
+
+    /// The first column of the visual line. The last visual line is used if the index is too big.
+    pub fn visual_line_start(&self, visual_line: usize) -> Column {
+        let index = visual_line.min(self.starts.len());
+        if index == 0 {
+            Column(0)
+        } else {
+            self.starts[index - 1]
+        }
+    }
+
+    /// The last column of the visual line at which the caret can be placed. Returns [`None`] for
+    /// the last visual line, as it ends with the buffer line.
+    pub fn visual_line_last_column(&self, visual_line: usize) -> Option<Column> {
+        self.starts.get(visual_line).map(|next_start| *next_start - Column(1))
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Wrap the text displayed with a monospace font, every grapheme cluster being 1.0 wide.
+    /// Returns the visual lines.
+    fn wrap_monospace(text: &str, width: f32) -> Vec<String> {
+        let rope = Rope::from(text);
+        let column_count = text.chars().count();
+        let divs = (0..=column_count).map(|column| column as f32).collect_vec();
+        let layout = LineLayout::wrap(&rope, &divs, width);
+        let chars = text.chars().collect_vec();
+        (0..layout.visual_line_count())
+            .map(|visual_line| {
+                let start = layout.visual_line_start(visual_line).value;
+                let end = layout.starts.get(visual_line).map_or(column_count, |end| end.value);
+                chars[start..end].iter().collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn line_fitting_the_width_is_not_wrapped() {
+        assert_eq!(wrap_monospace("foo bar", 7.0), vec!["foo bar"]);
+        assert_eq!(wrap_monospace("", 7.0), vec![""]);
+        assert!(!LineLayout::wrap(&"foo".into(), &[0.0, 1.0, 2.0, 3.0], 3.0).is_wrapped());
+    }
+
+    #[test]
+    fn lines_are_wrapped_between_words() {
+        let lines = wrap_monospace("lorem ipsum dolor sit amet", 12.0);
+        assert_eq!(lines, vec!["lorem ipsum ", "dolor sit ", "amet"]);
+        let lines = wrap_monospace("a-very-long identifier", 8.0);
+        assert_eq!(lines, vec!["a-very-", "long ", "identifi", "er"]);
+    }
+
+    #[test]
+    fn trailing_whitespace_overflows_the_width() {
+        let lines = wrap_monospace("lorem    ipsum", 5.0);
+        assert_eq!(lines, vec!["lorem    ", "ipsum"]);
+    }
+
+    #[test]
+    fn words_longer_than_the_width_are_split() {
+        let lines = wrap_monospace("abcdefghij kl", 4.0);
+        assert_eq!(lines, vec!["abcd", "efgh", "ij ", "kl"]);
+        // There is always at least one grapheme cluster in a visual line.
+        let lines = wrap_monospace("abc", 0.5);
+        assert_eq!(lines, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn ideographs_can_be_wrapped_anywhere() {
+        let lines = wrap_monospace("日本語のテキスト", 3.0);
+        assert_eq!(lines, vec!["日本語", "のテキ", "スト"]);
+    }
+
+    #[test]
+    fn grapheme_clusters_are_not_split() {
+        // The `e` with a combining accent is a single grapheme cluster occupying a single column.
+        let text = "e\u{301}e\u{301} e\u{301}";
+        let layout = LineLayout::wrap(&text.into(), &[0.0, 1.0, 2.0, 3.0, 4.0], 2.0);
+        assert_eq!(layout.starts, vec![Column(3)]);
+    }
+
+    #[test]
+    fn visual_lines_of_columns() {
+        let layout = LineLayout { starts: vec![Column(4), Column(8)] };
+        assert_eq!(layout.visual_line_count(), 3);
+        assert_eq!(layout.visual_line_of(Column(0)), 0);
+        assert_eq!(layout.visual_line_of(Column(3)), 0);
+        assert_eq!(layout.visual_line_of(Column(4)), 1);
+        assert_eq!(layout.visual_line_of(Column(20)), 2);
+        assert_eq!(layout.visual_line_start(1), Column(4));
+        assert_eq!(layout.visual_line_start(5), Column(8));
+        assert_eq!(layout.visual_line_last_column(0), Some(Column(3)));
+        assert_eq!(layout.visual_line_last_column(2), None);
+    }
+}
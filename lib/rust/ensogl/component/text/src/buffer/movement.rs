@@ -34,13 +34,13 @@ pub enum Transform {
     Word,
     /// Select the line at every cursor.
     Line,
-    /// Move to left end of visible line.
+    /// Move to left end of visual line.
     LeftOfLine,
-    /// Move to right end of visible line.
+    /// Move to right end of visual line.
     RightOfLine,
-    /// Move up one visible line.
+    /// Move up one visual line.
     Up,
-    /// Move down one visible line.
+    /// Move down one visual line.
     Down,
     /// Move to the start of the document.
     StartOfDocument,
@@ -72,7 +72,8 @@ impl BufferModel {
         }
     }
 
-    /// Compute movement based on vertical motion by the given number of lines.
+    /// Compute movement based on vertical motion by the given number of visual lines. The column
+    /// offset from the beginning of the visual line is preserved if possible.
     fn vertical_motion(
         &self,
         selection: Selection,
@@ -81,17 +82,51 @@ impl BufferModel {
     ) -> selection::Shape {
         let move_up = line_diff < LineDiff(0);
         let location = self.vertical_motion_selection_to_location(selection, move_up, modify);
+        let tgt_location = self.moved_by_visual_lines(location, line_diff.value);
+        selection::Shape(selection.start, tgt_location)
+    }
+
+    /// Move the location by the given number of visual lines. See the [`layout`] module docs to
+    /// learn more about visual lines.
+    ///
+    /// [`layout`]: crate::buffer::layout
+    fn moved_by_visual_lines(&self, location: Location, diff: i32) -> Location {
         let first_line = Line(0);
         let last_line = self.last_line_index();
-        let desired_line = location.line.to_diff() + line_diff;
-        let tgt_location = if desired_line < first_line.to_diff() {
-            Location { line: first_line, offset: Column(0) }
-        } else if desired_line > last_line.to_diff() {
-            Location { line: last_line, offset: self.last_line_last_column() }
-        } else {
-            location.with_line(desired_line.to_line())
+        let layout = self.line_layout(location.line);
+        let visual_line = layout.visual_line_of(location.offset);
+        let offset = location.offset - layout.visual_line_start(visual_line);
+        let mut line = location.line;
+        let mut visual_line = visual_line as i32 + diff;
+        while visual_line < 0 {
+            if line == first_line {
+                return Location { line: first_line, offset: Column(0) };
+            }
+            line = Line(line.value - 1);
+            visual_line += self.line_layout(line).visual_line_count() as i32;
+        }
+        loop {
+            let visual_line_count = self.line_layout(line).visual_line_count() as i32;
+            if visual_line < visual_line_count {
+                break;
+            }
+            if line == last_line {
+                return Location { line: last_line, offset: self.last_line_last_column() };
+            }
+            visual_line -= visual_line_count;
+            line += Line(1);
+        }
+        let visual_line = visual_line as usize;
+        let layout = self.line_layout(line);
+        // The column can be bigger than the last line column, if the cursor moved from a longer
+        // line. It is kept, so the column is preserved when the cursor moves back to the longer
+        // line. It is not possible inside of a wrapped line.
+        let column = layout.visual_line_start(visual_line) + offset;
+        let column = match layout.visual_line_last_column(visual_line) {
+            Some(last_column) => column.min(last_column),
+            None => column,
         };
-        selection::Shape(selection.start, tgt_location)
+        Location { line, offset: column }
     }
 
     /// Apply the movement to each region in the selection, and returns the union of the results.
@@ -152,23 +187,32 @@ impl BufferModel {
             Transform::RightSelectionBorder => shape(selection.start, selection.max()),
 
             Transform::LeftOfLine => {
-                let end = Location(selection.end.line, Column(0));
+                let layout = self.line_layout(selection.end.line);
+                let visual_line = layout.visual_line_of(selection.end.offset);
+                let end = Location(selection.end.line, layout.visual_line_start(visual_line));
                 shape(selection.start, end)
             }
 
             Transform::RightOfLine => {
                 let line = selection.end.line;
-                let text_byte_size = text.last_byte_index();
-                let is_last_line = line == self.last_line_index();
-                let next_line_offset_opt = self.line_offset(line + Line(1));
-                let next_line_offset =
-                    next_line_offset_opt.unwrap_or_else(|_| text.last_byte_index());
-                let offset = if is_last_line {
-                    text_byte_size
-                } else {
-                    text.prev_grapheme_offset(next_line_offset).unwrap_or(text_byte_size)
+                let layout = self.line_layout(line);
+                let visual_line = layout.visual_line_of(selection.end.offset);
+                let end = match layout.visual_line_last_column(visual_line) {
+                    Some(last_column) => Location(line, last_column),
+                    None => {
+                        let text_byte_size = text.last_byte_index();
+                        let is_last_line = line == self.last_line_index();
+                        let next_line_offset_opt = self.line_offset(line + Line(1));
+                        let next_line_offset =
+                            next_line_offset_opt.unwrap_or_else(|_| text.last_byte_index());
+                        let offset = if is_last_line {
+                            text_byte_size
+                        } else {
+                            text.prev_grapheme_offset(next_line_offset).unwrap_or(text_byte_size)
+                        };
+                        Location::from_in_context_snapped(self, offset)
+                    }
                 };
-                let end = Location::from_in_context_snapped(self, offset);
                 shape(selection.start, end)
             }
 
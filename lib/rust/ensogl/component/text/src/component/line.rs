@@ -5,6 +5,7 @@ use enso_text::unit::*;
 use ensogl_core::display::shape::*;
 
 use crate::buffer::formatting;
+use crate::buffer::layout::LineLayout;
 use crate::font::glyph::Glyph;
 
 use ensogl_core::data::color;
@@ -240,13 +241,18 @@ pub struct View {
     #[deref]
     pub frp:            Frp,
     pub display_object: display::object::Instance,
-    pub glyphs:         VecIndexedBy<Glyph, Column>,
-    /// Division points between glyphs. There is always the beginning division point (0.0). If
-    /// there are any glyphs, this also contains the last division point, which is the glyph
-    /// right hand side + `x_advance`, where `x_advance` is the space to the next glyph place.
+    pub glyphs:         Vec<Glyph>,
+    /// Division points between grapheme clusters, indexed by column. There is always the beginning
+    /// division point (0.0). If there are any glyphs, this also contains the last division point,
+    /// which is the glyph right hand side + `x_advance`, where `x_advance` is the space to the
+    /// next glyph place. A glyph representing several grapheme clusters (a ligature) has
+    /// division points inside it. The division points are computed as if the line was not wrapped.
     pub divs:           NonEmptyVec<f32>,
     /// Centers between division points. Used for glyph selection with mouse cursor.
     pub centers:        Vec<f32>,
+    /// Visual lines the line is wrapped to. The glyphs of the subsequent visual lines are placed
+    /// below the line baseline.
+    pub layout:         LineLayout,
     pub truncation:     Truncation,
     baseline_anim:      Animation<f32>,
 }
@@ -261,6 +267,7 @@ impl View {
         let glyphs = default();
         let divs = default();
         let centers = default();
+        let layout = default();
         let truncation: Truncation = default();
         let frame_time = frame_time.clone_ref();
         baseline_anim.simulator.update_spring(|s| s * crate::DEBUG_ANIMATION_SPRING_FACTOR);
@@ -287,16 +294,36 @@ impl View {
             eval start_time ((t) truncation.set_animation_start_time(*t));
         }
 
-        Self { frp, display_object, glyphs, divs, centers, truncation, baseline_anim }
+        Self { frp, display_object, glyphs, divs, centers, layout, truncation, baseline_anim }
     }
 
-    /// Get glyph for the provided column or create a new one if it does not exist.
-    pub fn get_or_create(&mut self, column: Column, cons: impl Fn() -> Glyph) -> &Glyph {
-        let missing = column.value as i32 - self.glyphs.len() as i32;
+    /// Get glyph for the provided index or create a new one if it does not exist. The existing
+    /// glyph is replaced with a new one if it can't be reused, for example, if it uses a different
+    /// font.
+    pub fn get_or_create(
+        &mut self,
+        index: usize,
+        can_reuse: impl Fn(&Glyph) -> bool,
+        cons: impl Fn() -> Glyph,
+    ) -> &Glyph {
+        let missing = index as i32 - self.glyphs.len() as i32;
         for _ in 0..=missing {
             self.push_glyph(cons());
         }
-        &self.glyphs[column]
+        if !can_reuse(&self.glyphs[index]) {
+            let glyph = cons();
+            self.add_child(&glyph);
+            self.glyphs[index] = glyph;
+        }
+        &self.glyphs[index]
+    }
+
+    /// Glyphs whose first grapheme cluster is placed in the provided column range.
+    pub fn glyphs_in_columns(
+        &self,
+        range: impl RangeBounds<Column>,
+    ) -> impl Iterator<Item = &Glyph> {
+        self.glyphs.iter().filter(move |glyph| range.contains(&glyph.line_column.get()))
     }
 
     /// Set the truncation of the line to the specified size.
@@ -348,19 +375,74 @@ impl View {
         self.divs = divs;
     }
 
-    /// Finds the div close to the given x-axis position.
-    pub fn div_index_close_to(&self, x_pos: f32) -> usize {
-        self.centers.binary_search_by(|t| t.partial_cmp(&x_pos).unwrap()).unwrap_both()
+    /// Set the layout of the line and move the glyphs to their visual lines. The glyphs should be
+    /// placed as if the line was not wrapped.
+    pub fn set_layout(&mut self, layout: LineLayout) {
+        self.layout = layout;
+        if self.layout.is_wrapped() {
+            for glyph in &self.glyphs {
+                let column = glyph.line_column.get();
+                let visual_line = self.layout.visual_line_of(column);
+                let start_x = self.visual_line_start_x(visual_line);
+                let y = self.visual_line_y(visual_line);
+                glyph.update_xy(|position| Vector2(position.x - start_x, y));
+            }
+        }
+    }
+
+    /// The distance between baselines of the visual lines.
+    pub fn visual_line_height(&self) -> f32 {
+        let metrics = self.metrics();
+        metrics.ascender - metrics.descender + metrics.gap
+    }
+
+    /// The height of the visual lines placed below the first one.
+    pub fn wrapped_height(&self) -> f32 {
+        (self.layout.visual_line_count() - 1) as f32 * self.visual_line_height()
+    }
+
+    /// The x-axis position of the visual line beginning in the line as if it was not wrapped.
+    fn visual_line_start_x(&self, visual_line: usize) -> f32 {
+        let start = self.layout.visual_line_start(visual_line);
+        self.divs.as_slice().get(start.value).copied().unwrap_or_else(|| *self.divs.last())
+    }
+
+    /// The y-axis position of the visual line baseline, relative to the line baseline.
+    pub fn visual_line_y(&self, visual_line: usize) -> f32 {
+        -(visual_line as f32 * self.visual_line_height())
+    }
+
+    /// The y-axis position of the baseline of the visual line displaying the column, relative to
+    /// the line baseline.
+    pub fn visual_line_y_by_column(&self, column: Column) -> f32 {
+        self.visual_line_y(self.layout.visual_line_of(column))
+    }
+
+    /// Finds the div close to the given position, relative to the line baseline.
+    pub fn div_index_close_to(&self, position: Vector2) -> usize {
+        let metrics = self.metrics();
+        let visual_line = (metrics.ascender - position.y) / self.visual_line_height();
+        let last_visual_line = self.layout.visual_line_count() - 1;
+        let visual_line = (visual_line.max(0.0) as usize).min(last_visual_line);
+        let end = self.layout.visual_line_last_column(visual_line).map(|column| column.value);
+        let end = end.unwrap_or(self.centers.len()).min(self.centers.len());
+        let start = self.layout.visual_line_start(visual_line).value.min(end);
+        let centers = &self.centers[start..end];
+        let x_pos = position.x + self.visual_line_start_x(visual_line);
+        let index = centers.binary_search_by(|t| t.partial_cmp(&x_pos).unwrap()).unwrap_both();
+        start + index
     }
 
-    /// Get the division by column.
+    /// Get the division by column. It is the x-axis position within the visual line displaying the
+    /// column.
     pub fn div_by_column(&self, column: Column) -> f32 {
+        let start = self.visual_line_start_x(self.layout.visual_line_of(column));
         if column.value < self.divs.len() {
-            self.divs[column.value]
+            self.divs[column.value] - start
         } else {
             // Requested column is bigger then glyph amount. This can happen for example when text
             // is truncated and the cursor is in the truncated area.
-            *self.divs.last()
+            *self.divs.last() - start
         }
     }
 
@@ -10,6 +10,7 @@ use ensogl_core::control::io::keyboard::event::*;
 use crate::buffer;
 use crate::buffer::formatting;
 use crate::buffer::formatting::Formatting;
+use crate::buffer::layout::LineLayout;
 use crate::buffer::FromInContextSnapped;
 use crate::buffer::Transform;
 use crate::buffer::TryFromInContext;
@@ -19,7 +20,9 @@ use crate::component::Selection;
 use crate::font;
 use crate::font::glyph;
 use crate::font::glyph::Glyph;
+use crate::font::shaping;
 use crate::font::Font;
+use crate::font::FontWithGpuData;
 use crate::font::GlyphId;
 use crate::font::GlyphRenderInfo;
 
@@ -130,20 +133,20 @@ impl Lines {
                 "Trying to compute coordinates for multi-line location. This is not supported yet."
             );
         }
-        let get_pos_x = |location: ViewLocation| {
+        let get_pos = |location: ViewLocation| {
             let lines = self.borrow();
-            if location.line > self.last_line_index() {
-                *lines.last().divs.last()
+            let (line, column) = if location.line > self.last_line_index() {
+                let line = lines.last();
+                (line, Column(line.divs.len()))
             } else {
-                lines[location.line].div_by_column(location.offset)
-            }
+                (&lines[location.line], location.offset)
+            };
+            let y = line.baseline() + line.visual_line_y_by_column(column);
+            Vector2(line.div_by_column(column), y)
         };
 
-        let start_x = get_pos_x(start_location);
-        let end_x = get_pos_x(end_location);
-        let y = self.borrow()[start_location.line].baseline();
-        let start_pos = Vector2(start_x, y);
-        let end_pos = Vector2(end_x, y);
+        let start_pos = get_pos(start_location);
+        let end_pos = get_pos(end_location);
         (start_pos, end_pos)
     }
 }
@@ -335,6 +338,12 @@ ensogl_core::define_endpoints_2! {
         /// Please note that you have to set the view width as well.
         set_long_text_truncation_mode(bool),
 
+        /// Wrap lines not fitting the view to several visual lines. Lines are broken at Unicode
+        /// line break opportunities, and the buffer lines are not modified. Cursor movement
+        /// up, down, and to the line ends follows the visual lines. Please note that you have to
+        /// set the view width as well. Takes precedence over the truncation mode.
+        set_long_text_wrap_mode(bool),
+
         // === NOT FINISHED YET ===
         // The following endpoints control the view area of the text area. They are not finished
         // yet and using them will probably cause panics and rendering issues.
@@ -353,6 +362,7 @@ ensogl_core::define_endpoints_2! {
         single_line_mode(bool),
         view_width(Option<f32>),
         long_text_truncation_mode(bool),
+        long_text_wrap_mode(bool),
         glyph_system    (Option<glyph::System>),
 
         // === Internal API ===
@@ -681,6 +691,9 @@ impl Text {
 
             out.long_text_truncation_mode <+ self.frp.set_long_text_truncation_mode;
             eval_ self.frp.set_long_text_truncation_mode (m.redraw());
+
+            out.long_text_wrap_mode <+ self.frp.set_long_text_wrap_mode;
+            eval_ self.frp.set_long_text_wrap_mode (m.redraw());
         }
     }
 
@@ -842,14 +855,16 @@ impl TextModel {
         for line in &*lines {
             // We are adding half of the gap here, so if someone clicks between the lines, the line
             // closer to the mouse pointer will be selected.
-            let height = line.baseline() + line.metrics().descender + line.metrics().gap / 2.0;
+            let bottom = line.baseline() - line.wrapped_height() + line.metrics().descender;
+            let height = bottom + line.metrics().gap / 2.0;
             if height < object_space.y {
                 break;
             }
             view_line += ViewLine(1);
         }
         let view_line = std::cmp::min(view_line, self.lines.last_line_index());
-        let div_index = self.lines.borrow()[view_line].div_index_close_to(object_space.x);
+        let line = &lines[view_line];
+        let div_index = line.div_index_close_to(object_space - Vector2(0.0, line.baseline()));
         let line = Line::from_in_context_snapped(self, view_line);
         let column = Column(div_index);
         let out = Location(line, column);
@@ -891,6 +906,8 @@ impl ShapedLine {
 #[allow(missing_docs)]
 #[derive(Debug)]
 pub struct ShapedGlyphSet {
+    /// The font used to shape the glyphs. It is the glyph system's font or one of its fallbacks.
+    pub font:                    FontWithGpuData,
     pub units_per_em:            u16,
     pub ascender:                i16,
     pub descender:               i16,
@@ -953,15 +970,16 @@ impl TextModel {
         }
     }
 
-    /// Recompute the shape of the provided byte range.
+    /// Recompute the shape of the provided byte range. Glyphs missing in the glyph system's font
+    /// are shaped with its fallback fonts, see the [`shaping`] module docs to learn more.
     fn shape_range(&self, range: Range<Byte>) -> Vec<ShapedGlyphSet> {
         let line_style = self.buffer.sub_style(range.clone());
         let rope = self.buffer.rope.sub(range);
         let content = rope.to_string();
         let glyph_system = self.glyph_system.borrow();
         let font = &glyph_system.font;
+        let fonts = glyph_system.font_chain().collect_vec();
         let mut glyph_sets = vec![];
-        let mut prev_chunk_cluster_byte_offset = 0;
         let mut grapheme_byte_offset = Byte(0);
         for (range, requested_non_variable_variations) in
             Self::chunks_per_font_face(font, &line_style, &rope)
@@ -975,63 +993,81 @@ impl TextModel {
                     requested_non_variable_variations, non_variable_variations
                 );
             }
-            font.with_borrowed_face(non_variable_variations, |face| {
-                let ttf_face = face.ttf.as_face_ref();
-                let units_per_em = ttf_face.units_per_em();
-                let ascender = ttf_face.ascender();
-                let descender = ttf_face.descender();
-                let line_gap = ttf_face.line_gap();
-                // This is safe. Unwrap should be removed after rustybuzz is fixed:
-                // https://github.com/RazrFalcon/rustybuzz/issues/52
-                let buzz_face = rustybuzz::Face::from_face(ttf_face.clone()).unwrap();
-                let mut buffer = rustybuzz::UnicodeBuffer::new();
-                buffer.push_str(&content[range.start.value..range.end.value]);
-                let features = font.feature_settings();
-                let shaped = rustybuzz::shape(&buzz_face, features, buffer);
-                let variable_variations = default();
-                let glyphs = shaped
-                    .glyph_positions()
-                    .iter()
-                    .zip(shaped.glyph_infos())
-                    .filter_map(|(&position, &info)| {
-                        let mut info = info;
-                        // TODO: Add support for variable fonts here.
-                        // let variable_variations = glyph.variations.borrow();
-                        let glyph_id = GlyphId(info.glyph_id as u16);
-                        let render_info = font.glyph_info_of_known_face(
-                            non_variable_variations,
-                            &variable_variations,
-                            glyph_id,
-                            face,
-                        );
-                        info.cluster += prev_chunk_cluster_byte_offset;
-                        if Byte(info.cluster as usize) < grapheme_byte_offset {
-                            // This glyph is part of the previous grapheme cluster. This is caused
-                            // by font not supporting displaying this grapheme cluster. We will not
-                            // display it.
-                            None
-                        } else {
-                            match rope.next_grapheme_offset(grapheme_byte_offset) {
-                                None => error!("Misaligned grapheme cluster boundary."),
-                                Some(next_grapheme_byte_offset) => {
-                                    grapheme_byte_offset = next_grapheme_byte_offset;
+            // Fallback fonts do not have to define the same variations as the main font.
+            let variations = fonts
+                .iter()
+                .map(|font| {
+                    let variations_match =
+                        font.closest_non_variable_variations(requested_non_variable_variations);
+                    variations_match.map_or(non_variable_variations, |t| t.variations)
+                })
+                .collect_vec();
+            let chunk = &content[range.start.value..range.end.value];
+            let runs = shaping::shape_with_fallbacks(chunk, fonts.len(), |index, text| {
+                let font = fonts[index];
+                let glyphs = font.with_borrowed_face(variations[index], |face| {
+                    // This is safe. Unwrap should be removed after rustybuzz is fixed:
+                    // https://github.com/RazrFalcon/rustybuzz/issues/52
+                    let buzz_face =
+                        rustybuzz::Face::from_face(face.ttf.as_face_ref().clone()).unwrap();
+                    shaping::shape(&buzz_face, font.feature_settings(), text)
+                });
+                glyphs.unwrap_or_default()
+            });
+            for run in runs {
+                let font = fonts[run.font];
+                let non_variable_variations = variations[run.font];
+                font.with_borrowed_face(non_variable_variations, |face| {
+                    let ttf_face = face.ttf.as_face_ref();
+                    let units_per_em = ttf_face.units_per_em();
+                    let ascender = ttf_face.ascender();
+                    let descender = ttf_face.descender();
+                    let line_gap = ttf_face.line_gap();
+                    let variable_variations = default();
+                    let glyphs = run
+                        .glyphs
+                        .into_iter()
+                        .filter_map(|glyph| {
+                            let shaping::Glyph { mut info, position } = glyph;
+                            // TODO: Add support for variable fonts here.
+                            // let variable_variations = glyph.variations.borrow();
+                            info.cluster += range.start.value as u32;
+                            let glyph_byte_offset = Byte(info.cluster as usize);
+                            if glyph_byte_offset < grapheme_byte_offset {
+                                // This glyph is part of the previous grapheme cluster. This is
+                                // caused by font not supporting displaying this grapheme cluster.
+                                // We will not display it.
+                                None
+                            } else {
+                                match rope.next_grapheme_offset(glyph_byte_offset) {
+                                    None => error!("Misaligned grapheme cluster boundary."),
+                                    Some(next_grapheme_byte_offset) => {
+                                        grapheme_byte_offset = next_grapheme_byte_offset;
+                                    }
                                 }
+                                let glyph_id = GlyphId(info.glyph_id as u16);
+                                let render_info = font.glyph_info_of_known_face(
+                                    non_variable_variations,
+                                    &variable_variations,
+                                    glyph_id,
+                                    face,
+                                );
+                                Some(ShapedGlyph { position, info, render_info })
                             }
-                            Some(ShapedGlyph { position, info, render_info })
-                        }
-                    })
-                    .collect();
-                let shaped_glyph_set = ShapedGlyphSet {
-                    units_per_em,
-                    ascender,
-                    descender,
-                    line_gap,
-                    non_variable_variations,
-                    glyphs,
-                };
-                glyph_sets.push(shaped_glyph_set);
-            });
-            prev_chunk_cluster_byte_offset = range.end.value as u32;
+                        })
+                        .collect();
+                    let shaped_glyph_set = ShapedGlyphSet {
+                        font: font.clone_ref(),
+                        units_per_em,
+                        ascender,
+                        descender,
+                        line_gap,
+                        non_variable_variations,
+                        glyphs,
+                    };
+                    glyph_sets.push(shaped_glyph_set);
+                });
+            }
         }
         glyph_sets
     }
@@ -1303,6 +1339,7 @@ impl TextModel {
     ) {
         self.resize_lines();
         self.width_dirty.set(true);
+        self.height_dirty.set(true);
         let sorted_line_ranges = sorted_line_ranges.inspect(|range| {
             for line in range.clone() {
                 self.redraw_line(line);
@@ -1316,20 +1353,23 @@ impl TextModel {
     fn redraw_line(&self, view_line: ViewLine) {
         let line = &mut self.lines.borrow_mut()[view_line];
         let default_divs = || NonEmptyVec::singleton(0.0);
-        let mut divs = default_divs();
-        let mut column = Column(0);
+        let mut glyph_count = 0;
+        // Cluster byte offsets and advances of the line glyphs, used to compute the columns of the
+        // glyphs and the division points between grapheme clusters.
+        let mut glyph_advances = vec![];
         let mut to_be_truncated = 0;
-        let mut truncated = false;
+        let mut truncated_at = None;
         let default_size = self.buffer.formatting.font_size().default;
         let line_index = Line::from_in_context_snapped(self, view_line);
+        let line_range = self.buffer.byte_range_of_view_line_index_snapped(view_line);
+        let view_width = self.frp.output.view_width.value();
+        let wrap_width = view_width.filter(|_| self.frp.output.long_text_wrap_mode.value());
         self.with_shaped_line(line_index, |shaped_line| {
             match shaped_line {
                 ShapedLine::NonEmpty { glyph_sets } => {
                     let glyph_system = self.glyph_system.borrow();
-                    let view_width = self.frp.output.view_width.value();
                     let long_text_truncation_mode =
-                        self.frp.output.long_text_truncation_mode.value();
-                    let line_range = self.buffer.byte_range_of_view_line_index_snapped(view_line);
+                        self.frp.output.long_text_truncation_mode.value() && wrap_width.is_none();
                     let line_style = self.buffer.sub_style(line_range.start..line_range.end);
                     let mut line_style_iter = line_style.iter_bytes();
                     let mut glyph_offset_x = 0.0;
@@ -1338,7 +1378,7 @@ impl TextModel {
                     let ellipsis_width = truncation_size.width_with_text_offset();
                     let mut line_metrics = None;
                     for shaped_glyph_set in glyph_sets {
-                        if truncated {
+                        if truncated_at.is_some() {
                             break;
                         }
                         // FIXME[WD]: This is a workaround for a bug in the MSDFgen binding. It
@@ -1367,7 +1407,7 @@ impl TextModel {
                             if long_text_truncation_mode {
                                 if let Some(view_width) = view_width {
                                     if glyph_rhs > view_width {
-                                        truncated = true;
+                                        truncated_at = Some(glyph_byte_start);
                                         break;
                                     } else if glyph_rhs > view_width - ellipsis_width {
                                         to_be_truncated += 1;
@@ -1375,7 +1415,10 @@ impl TextModel {
                                 };
                             }
 
-                            let glyph = &line.get_or_create(column, || glyph_system.new_glyph());
+                            let font = &shaped_glyph_set.font;
+                            let uses_font = |glyph: &Glyph| glyph.font().name() == font.name();
+                            let new_glyph = || glyph_system.new_glyph_with_font(font);
+                            let glyph = line.get_or_create(glyph_count, uses_font, new_glyph);
                             glyph.line_byte_offset.set(glyph_byte_start);
 
                             let glyph_line_metrics = line::Metrics { ascender, descender, gap };
@@ -1396,8 +1439,8 @@ impl TextModel {
                             glyph.set_xy(Vector2(glyph_offset_x, 0.0));
 
                             glyph_offset_x += x_advance;
-                            divs.push(glyph_offset_x);
-                            column += Column(1);
+                            glyph_advances.push((glyph_byte_start, x_advance));
+                            glyph_count += 1;
                         }
                     }
                     if let Some(line_metrics) = line_metrics {
@@ -1422,16 +1465,35 @@ impl TextModel {
             }
         });
 
-        if truncated {
-            let divs = (divs[0..divs.len() - to_be_truncated]).to_vec();
-            let divs = NonEmptyVec::try_from(divs).unwrap_or_else(|_| default_divs());
-            line.set_divs(divs);
-            line.glyphs.truncate(column.value - to_be_truncated);
+        let line_rope = self.buffer.rope.sub(line_range);
+        let clusters = glyph_advances.iter().map(|(cluster, _)| *cluster);
+        let columns = shaping::cluster_columns(&line_rope, clusters);
+        for (glyph, column) in line.glyphs.iter().zip(columns) {
+            glyph.line_column.set(column);
+        }
+        let visible_glyph_count =
+            if truncated_at.is_some() { glyph_count - to_be_truncated } else { glyph_count };
+        // The text behind the visible glyphs is not displayed and has no division points.
+        let visible_end = glyph_advances.get(visible_glyph_count).map(|(cluster, _)| *cluster);
+        let visible_rope = match visible_end.or(truncated_at) {
+            Some(visible_end) => line_rope.sub(Byte(0)..visible_end),
+            None => line_rope,
+        };
+        let visible_glyph_advances = glyph_advances[..visible_glyph_count].iter().copied();
+        let divs = shaping::caret_offsets(&visible_rope, visible_glyph_advances);
+        let divs = NonEmptyVec::try_from(divs).unwrap_or_else(|_| default_divs());
+        let layout = match wrap_width {
+            Some(width) => LineLayout::wrap(&visible_rope, divs.as_slice(), width),
+            None => default(),
+        };
+        line.set_divs(divs);
+        line.glyphs.truncate(visible_glyph_count);
+        line.set_layout(layout.clone());
+        self.buffer.set_line_layout(line_index, layout);
+        if truncated_at.is_some() {
             line.set_truncated(Some(default_size));
             line.update_truncation_color();
         } else {
-            line.set_divs(divs);
-            line.glyphs.truncate(column.value);
             line.set_truncated(None);
         }
     }
@@ -1474,29 +1536,36 @@ impl TextModel {
         let mut attached_glyphs = vec![];
         let mut last_cursor: Option<Selection> = None;
         let mut last_cursor_target_x = default();
+        let mut last_cursor_target_y = default();
 
-        let mut column = Column(0);
+        let mut next_column = Column(0);
         for glyph in line {
-            cursor_map.get(&column).for_each(|id| {
-                if let Some(cursor) = self.selection_map.borrow().id_map.get(id) {
-                    if cursor.edit_mode().get() {
-                        if let Some(last_cursor) = &last_cursor {
-                            let attached_glyphs = Rc::new(mem::take(&mut attached_glyphs));
-                            last_cursor.set_attached_glyphs(attached_glyphs);
+            // Cursors placed inside the previous glyph (e.g. inside a ligature) are followed by
+            // this glyph too.
+            let glyph_column = glyph.line_column.get();
+            for column in (next_column.value..=glyph_column.value).map(Column) {
+                cursor_map.get(&column).for_each(|id| {
+                    if let Some(cursor) = self.selection_map.borrow().id_map.get(id) {
+                        if cursor.edit_mode().get() {
+                            if let Some(last_cursor) = &last_cursor {
+                                let attached_glyphs = Rc::new(mem::take(&mut attached_glyphs));
+                                last_cursor.set_attached_glyphs(attached_glyphs);
+                            }
+                            last_cursor = Some(cursor.clone_ref());
+                            last_cursor_target_x = line.div_by_column(column);
+                            last_cursor_target_y = line.visual_line_y_by_column(column);
                         }
-                        last_cursor = Some(cursor.clone_ref());
-                        last_cursor_target_x = glyph.x();
                     }
-                }
-            });
+                });
+            }
+            next_column = glyph_column + Column(1);
 
             if let Some(cursor) = &last_cursor {
                 cursor.right_side().add_child(glyph);
                 glyph.attached_to_cursor.set(true);
-                glyph.update_x(|p| p - last_cursor_target_x);
+                glyph.update_xy(|p| p - Vector2(last_cursor_target_x, last_cursor_target_y));
                 attached_glyphs.push(glyph.downgrade());
             }
-            column += Column(1);
         }
         if let Some(last_cursor) = &last_cursor {
             last_cursor.set_attached_glyphs(Rc::new(mem::take(&mut attached_glyphs)));
@@ -1514,9 +1583,12 @@ impl TextModel {
                 let selection = selection_map.id_map.get(cursor_id).unwrap();
                 for glyph in &*selection.set_attached_glyphs.value() {
                     if let Some(glyph) = glyph.upgrade() {
-                        self.lines.borrow_mut()[line].add_child(&glyph);
+                        let lines = self.lines.borrow();
+                        let line = &lines[line];
+                        line.add_child(&glyph);
                         let pos_x = selection.position_target.value().x;
-                        glyph.update_xy(|pos| Vector2(pos.x + pos_x, 0.0));
+                        let pos_y = line.visual_line_y_by_column(glyph.line_column.get());
+                        glyph.update_xy(|pos| Vector2(pos.x + pos_x, pos_y));
                         glyph.attached_to_cursor.set(false);
                     }
                 }
@@ -1688,39 +1760,26 @@ impl TextModel {
         let span_ranges = formatting.span_ranges_of_default_values(property.tag());
         for span_range in span_ranges {
             let range = buffer::Range::<Location>::from_in_context_snapped(self, span_range);
-            let mut lines = self.lines.borrow_mut();
+            let lines = self.lines.borrow();
             if range.single_line() {
                 let view_line = ViewLine::from_in_context_snapped(self, range.start.line);
-                let line = &mut lines[view_line];
-                if let Some(last_index) = line.glyphs.last_valid_index() {
-                    let start = std::cmp::min(last_index, range.start.offset);
-                    let end = std::cmp::min(last_index + Column(1), range.end.offset);
-                    for glyph in &mut line.glyphs[start..end] {
-                        glyph.set_property(property);
-                    }
+                let columns = range.start.offset..range.end.offset;
+                for glyph in lines[view_line].glyphs_in_columns(columns) {
+                    glyph.set_property(property);
                 }
             } else {
                 let view_line = ViewLine::from_in_context_snapped(self, range.start.line);
-                let first_line = &mut lines[view_line];
-                if let Some(last_index) = first_line.glyphs.last_valid_index() {
-                    let start = std::cmp::min(last_index, range.start.offset);
-                    for glyph in &mut first_line.glyphs[start..] {
-                        glyph.set_property(property);
-                    }
+                for glyph in lines[view_line].glyphs_in_columns(range.start.offset..) {
+                    glyph.set_property(property);
                 }
 
                 let view_line = ViewLine::from_in_context_snapped(self, range.end.line);
-                let last_line = &mut lines[view_line];
-                if let Some(last_index) = last_line.glyphs.last_valid_index() {
-                    let end = std::cmp::min(last_index + Column(1), range.end.offset);
-                    for glyph in &mut last_line.glyphs[..end] {
-                        glyph.set_property(property);
-                    }
+                for glyph in lines[view_line].glyphs_in_columns(..range.end.offset) {
+                    glyph.set_property(property);
                 }
                 for line_index in range.start.line.value + 1..range.end.line.value {
                     let view_line = ViewLine::from_in_context_snapped(self, Line(line_index));
-                    let line = &mut lines[view_line];
-                    for glyph in &mut line.glyphs[..] {
+                    for glyph in &lines[view_line] {
                         glyph.set_property(property);
                     }
                 }
@@ -1760,7 +1819,8 @@ impl TextModel {
             } else {
                 let prev_line_index = ViewLine(line_index.value - 1);
                 let prev_line = &lines[prev_line_index];
-                let offset = prev_line.metrics().descender + ascender - line.metrics().gap;
+                let prev_descender = prev_line.metrics().descender - prev_line.wrapped_height();
+                let offset = prev_descender + ascender - line.metrics().gap;
                 prev_line.baseline() + offset
             };
             let new_baseline = new_baseline.round();
@@ -1822,8 +1882,10 @@ impl TextModel {
                         max_width = width;
                     }
                 } else {
-                    let last_glyph = line.glyphs.iter().rev().find(|g| !g.attached_to_cursor.get());
-                    let width = last_glyph.map(|g| g.x() + g.x_advance.get()).unwrap_or_default();
+                    // The widest visual line does not have to be the last one if the line is
+                    // wrapped.
+                    let glyphs = line.glyphs.iter().filter(|g| !g.attached_to_cursor.get());
+                    let width = glyphs.map(|g| g.x() + g.x_advance.get()).fold(0.0, f32::max);
                     if width > max_width {
                         max_width = width;
                     }
@@ -1843,7 +1905,11 @@ impl TextModel {
     fn compute_height_if_dirty(&self) -> Option<f32> {
         self.height_dirty.get().then(|| {
             self.height_dirty.set(false);
-            let mut max_height = -self.lines.borrow().last().descent.value();
+            let last_line_height = {
+                let lines = self.lines.borrow();
+                -lines.last().descent.value() + lines.last().wrapped_height()
+            };
+            let mut max_height = last_line_height;
             let selection_map = self.selection_map.borrow();
 
             for (view_line, map) in &selection_map.location_map {
@@ -19,11 +19,14 @@ use ttf::AsFaceRef;
 
 pub mod glyph;
 pub mod glyph_render_info;
+pub mod shaping;
 
 pub use enso_font as family;
+pub use ensogl_text_atlas::prebuilt;
 pub use family::Name;
 pub use family::NonVariableFaceHeader;
 pub use glyph_render_info::GlyphRenderInfo;
+pub use prebuilt::CacheSnapshot;
 pub use ttf::GlyphId;
 pub use ttf::Style;
 pub use ttf::Tag;
@@ -736,7 +739,7 @@ impl<F: Family> FontTemplate<F> {
             glyph_id:   glyph_id.0,
         });
         self.family.update_msdfgen_variations(variations);
-        let render_info = GlyphRenderInfo::load(&face.msdf, glyph_id, &self.atlas);
+        let render_info = glyph_render_info::load(face, glyph_id, &self.atlas);
         if !self.cache.borrow().contains_key(variations) {
             self.cache.borrow_mut().insert(variations.clone(), default());
         }
@@ -785,54 +788,27 @@ thread_local! {
     pub static PREBUILT_ATLASES: RefCell<HashMap<Name, Rc<CacheSnapshot>>> = default();
 }
 
-/// Cached rendering information for a font.
-#[derive(Debug)]
-pub struct CacheSnapshot {
-    /// The MSDF atlas pixel data.
-    pub atlas:  enso_bitmap::Image,
-    /// Index of glyphs found in [`atlas`].
-    pub glyphs: String,
-}
-
 impl FontTemplate<NonVariableFamily> {
     /// Return the current glyph cache data.
     pub fn cache_snapshot(&self) -> CacheSnapshot {
         let atlas = self.atlas.to_image();
-        let cache: HashMap<String, _> = self
-            .cache
-            .borrow()
-            .iter()
-            .map(|(variation, info)| {
-                let glyphs: HashMap<String, GlyphRenderInfo> =
-                    info.glyphs.iter().map(|(id, data)| (id.0.to_string(), *data)).collect();
-                (serialize_variation(variation), glyphs)
-            })
-            .collect();
-        let glyphs = serde_json::to_string(&cache);
-        // Serialization can only fail if the types are not serializable to JSON, so this will
-        // either succeed consistently or fail consistently. [`unwrap`] it so if it gets broken,
-        // we'll catch it.
-        let glyphs = glyphs.unwrap();
-        CacheSnapshot { atlas, glyphs }
+        let cache = self.cache.borrow();
+        let glyphs = cache.iter().map(|(variation, info)| (*variation, info.glyphs.clone()));
+        CacheSnapshot::new(atlas, &glyphs.collect())
     }
 
     /// Populate the cache with the given data.
     #[profile(Debug)]
     pub fn load_cache(&self, snapshot: &CacheSnapshot) -> anyhow::Result<()> {
+        let cache = snapshot.glyph_cache()?;
         self.atlas.set_data(snapshot.atlas.clone());
-        let cache: HashMap<String, HashMap<String, GlyphRenderInfo>> =
-            serde_json::from_str(&snapshot.glyphs)?;
         *self.cache.borrow_mut() = cache
             .into_iter()
-            .map(|(variation, info)| {
+            .map(|(variation, glyphs)| {
                 let kerning = default();
-                let glyphs = info
-                    .into_iter()
-                    .map(|(id, data)| Ok((GlyphId(id.parse()?), data)))
-                    .collect::<anyhow::Result<_>>()?;
-                Ok((deserialize_variation(&variation)?, FontDataCache { kerning, glyphs }))
+                (variation, FontDataCache { kerning, glyphs })
             })
-            .collect::<anyhow::Result<_>>()?;
+            .collect();
         Ok(())
     }
 
@@ -869,55 +845,6 @@ impl FontTemplate<NonVariableFamily> {
 }
 
 
-// === Serialization Helpers, Because `ttf_parser` Doesn't `derive` Them ===
-
-fn serialize_variation(variation: &NonVariableFaceHeader) -> String {
-    let width = match variation.width {
-        Width::UltraCondensed => "UltraCondensed",
-        Width::ExtraCondensed => "ExtraCondensed",
-        Width::Condensed => "Condensed",
-        Width::SemiCondensed => "SemiCondensed",
-        Width::Normal => "Normal",
-        Width::SemiExpanded => "SemiExpanded",
-        Width::Expanded => "Expanded",
-        Width::ExtraExpanded => "ExtraExpanded",
-        Width::UltraExpanded => "UltraExpanded",
-    };
-    let weight = variation.weight.to_number().to_string();
-    let style = match variation.style {
-        Style::Normal => "Normal",
-        Style::Italic => "Italic",
-        Style::Oblique => "Oblique",
-    };
-    format!("{width}-{weight}-{style}")
-}
-
-fn deserialize_variation(variation: &str) -> anyhow::Result<NonVariableFaceHeader> {
-    let mut parts = variation.splitn(3, '-');
-    let bad_variation = || anyhow!("Malformed variation specifier: {variation}");
-    let width = match parts.next().ok_or_else(bad_variation)? {
-        "UltraCondensed" => Width::UltraCondensed,
-        "ExtraCondensed" => Width::ExtraCondensed,
-        "Condensed" => Width::Condensed,
-        "SemiCondensed" => Width::SemiCondensed,
-        "Normal" => Width::Normal,
-        "SemiExpanded" => Width::SemiExpanded,
-        "Expanded" => Width::Expanded,
-        "ExtraExpanded" => Width::ExtraExpanded,
-        "UltraExpanded" => Width::UltraExpanded,
-        width => anyhow::bail!("Unexpected font width: `{width}`."),
-    };
-    let weight = Weight::from(parts.next().ok_or_else(bad_variation)?.parse::<u16>()?);
-    let style = match parts.next().ok_or_else(bad_variation)? {
-        "Normal" => Style::Normal,
-        "Italic" => Style::Italic,
-        "Oblique" => Style::Oblique,
-        style => anyhow::bail!("Unexpected font style: `{style}`."),
-    };
-    Ok(NonVariableFaceHeader { width, weight, style })
-}
-
-
 // === Cache Logging ===
 
 /// A glyph that was not found in the MSDF data cache.
@@ -1008,6 +935,7 @@ impl FontWithGpuData {
 pub struct Registry {
     network:            frp::Network,
     fonts:              Rc<HashMap<Name, FontWithGpuData>>,
+    fallbacks:          Rc<HashMap<Name, Vec<Name>>>,
     set_context_handle: ensogl_core::display::world::ContextHandler,
 }
 
@@ -1032,9 +960,19 @@ impl Registry {
         self.fonts.get(&name).cloned()
     }
 
+    /// Load the fallback fonts of the font with the provided name, in the order of preference.
+    /// They are used to render glyphs missing in the font. Fallback fonts which are not found are
+    /// skipped.
+    pub fn load_fallbacks(&self, name: impl Into<Name>) -> Vec<FontWithGpuData> {
+        let name = name.into();
+        let fallbacks = self.fallbacks.get(&name).map(|names| names.as_slice()).unwrap_or_default();
+        fallbacks.iter().filter_map(|fallback| self.try_load(fallback)).collect()
+    }
+
     fn new(
         scene: &ensogl_core::display::Scene,
         fonts: impl IntoIterator<Item = (Name, Font)>,
+        fallbacks: HashMap<Name, Vec<Name>>,
     ) -> Self {
         let context = scene.context.borrow();
         let context = context.as_ref();
@@ -1049,6 +987,7 @@ impl Registry {
             })
             .collect();
         let fonts = Rc::new(fonts);
+        let fallbacks = Rc::new(fallbacks);
         let fonts_ = Rc::clone(&fonts);
         let set_context_handle = scene.on_set_context(move |context| {
             for font in fonts_.values() {
@@ -1060,7 +999,7 @@ impl Registry {
         frp::extend! { network
             eval_ on_before_rendering([fonts] Self::update(&fonts));
         }
-        Self { network, fonts, set_context_handle }
+        Self { network, fonts, fallbacks, set_context_handle }
     }
 
     fn update(fonts: impl AsRef<HashMap<Name, FontWithGpuData>>) {
@@ -1072,8 +1011,9 @@ impl Registry {
 
 impl scene::Extension for Registry {
     fn init(scene: &scene::Scene) -> Self {
-        let fonts = Embedded::default().into_fonts();
-        Self::new(scene, fonts)
+        let embedded = Embedded::default();
+        let fallbacks = embedded.fallbacks.clone();
+        Self::new(scene, embedded.into_fonts(), fallbacks)
     }
 }
 
@@ -1125,6 +1065,7 @@ pub struct Embedded {
     definitions: HashMap<Name, family::FontFamily>,
     data:        EmbeddedData,
     features:    HashMap<Name, Vec<rustybuzz::Feature>>,
+    fallbacks:   HashMap<Name, Vec<Name>>,
 }
 
 impl Embedded {
@@ -1135,9 +1076,14 @@ impl Embedded {
         definition.map(|def| self.data.load_font(name.clone(), def, features))
     }
 
+    /// Names of the fallback families of the font, in the order of preference.
+    pub fn fallbacks(&self, name: &Name) -> &[Name] {
+        self.fallbacks.get(name).map(|names| names.as_slice()).unwrap_or_default()
+    }
+
     /// Load and return all fonts from the registry.
     pub fn into_fonts(self) -> impl Iterator<Item = (Name, Font)> {
-        let Self { definitions, data, mut features } = self;
+        let Self { definitions, data, mut features, fallbacks: _ } = self;
         definitions.into_iter().map(move |(name, definition)| {
             let features = features.remove(&name).unwrap_or_default();
             (name.clone(), data.load_font(name, &definition, features))
@@ -1147,8 +1093,9 @@ impl Embedded {
 
 impl Default for Embedded {
     fn default() -> Self {
-        let ensogl_text_embedded_fonts::Embedded { definitions, data, features } = default();
-        Self { definitions, data: EmbeddedData { data }, features }
+        let ensogl_text_embedded_fonts::Embedded { definitions, data, features, fallbacks } =
+            default();
+        Self { definitions, data: EmbeddedData { data }, features, fallbacks }
     }
 }
 
@@ -1213,22 +1160,53 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_enso_font_ligatures_disabled() {
+    fn test_enso_font_ligatures_enabled() {
         let registry: HashMap<_, _> = Embedded::default().into_fonts().collect();
         let font = registry.get(&"enso".into()).unwrap();
         font.with_borrowed_face(NonVariableFaceHeader::default(), |face| {
             let face = face.ttf.as_face_ref().clone();
             let face = rustybuzz::Face::from_face(face).unwrap();
             let features = font.feature_settings();
-            // If the font's ligatures are used, these two characters will correspond to one
-            // glyph. `ensogl_text` doesn't support ligatures, so its shaped length must be the
-            // same as its character count.
+            // The font's ligatures are used, so these two characters correspond to one glyph.
             let test_str = "fi";
             let mut buffer = rustybuzz::UnicodeBuffer::new();
             buffer.push_str(test_str);
             let shaped = rustybuzz::shape(&face, features, buffer);
-            assert_eq!(shaped.len(), test_str.chars().count());
+            assert_eq!(shaped.len(), 1);
         })
         .unwrap();
     }
+
+    #[test]
+    fn prebuilt_atlas_is_loaded_into_font() {
+        let name = DEFAULT_FONT;
+        let cache = prebuilt::build_atlas(name).unwrap();
+        let font = Embedded::default().load_font(name.into()).unwrap();
+        match font {
+            Font::NonVariable(font) => {
+                font.load_cache(&cache).unwrap();
+                assert_eq!(font.atlas.rows(), cache.atlas.height);
+                let variation = NonVariableFaceHeader::default();
+                let info = font.glyph_info(&variation, GlyphId::default()).unwrap();
+                let glyph_count = font.atlas.glyphs();
+                assert!(info.msdf_texture_glyph_id < glyph_count);
+                assert!(info.advance > 0.0);
+                // The glyph was found in the loaded cache, so no new MSDF was generated.
+                assert_eq!(font.atlas.glyphs(), glyph_count);
+            }
+            Font::Variable(_) => panic!("The default font is expected to be non-variable."),
+        }
+    }
+
+    #[test]
+    fn test_enso_font_fallbacks() {
+        let embedded = Embedded::default();
+        let names = |names: &[&str]| names.iter().copied().map(Name::from).collect_vec();
+        let enso_fallbacks = names(&["mplus1p", "notosanssymbols2", "notoemoji"]);
+        assert_eq!(embedded.fallbacks(&"enso".into()), enso_fallbacks);
+        let mplus_fallbacks = names(&["notosanssymbols2", "notoemoji"]);
+        assert_eq!(embedded.fallbacks(&"mplus1p".into()), mplus_fallbacks);
+        assert!(embedded.fallbacks(&"notosanssymbols2".into()).is_empty());
+        assert!(embedded.fallbacks(&"notoemoji".into()).is_empty());
+    }
 }
@@ -0,0 +1,113 @@
+//! Glyph atlases of the embedded fonts, built without the GUI.
+//!
+//! The crate contains the pure-Rust MSDF generator, the layout of the MSDF atlas textures and the
+//! builder of the pre-built atlases. It does not depend on the WASM build of msdfgen, nor on the
+//! rendering engine, so it can be used both by the application and by the build tools.
+
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+// === Non-Standard Linter Configuration ===
+#![allow(clippy::option_map_unit_fn)]
+#![allow(clippy::precedence)]
+#![allow(dead_code)]
+#![deny(unconditional_recursion)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+#![warn(unused_qualifications)]
+
+use crate::prelude::*;
+
+
+// ==============
+// === Export ===
+// ==============
+
+pub mod glyph_render_info;
+pub mod msdf;
+pub mod prebuilt;
+
+pub use glyph_render_info::GlyphRenderInfo;
+
+/// Common types.
+pub mod prelude {
+    pub use enso_prelude::*;
+    pub use enso_profiler as profiler;
+    pub use enso_profiler::prelude::*;
+    pub use enso_types::*;
+}
+
+
+
+// ======================
+// === MsdfParameters ===
+// ======================
+
+/// Parameters of MSDF generation.
+///
+/// The structure gathering MSDF generation parameters meant to be same for all rendered glyphs.
+#[derive(Clone, Copy, Debug)]
+#[allow(missing_docs)]
+pub struct MsdfParameters {
+    pub width: usize,
+    pub height: usize,
+    pub edge_coloring_angle_threshold: f64,
+    pub range: f64,
+    pub max_scale: f64,
+    pub edge_threshold: f64,
+    pub overlap_support: bool,
+}
+
+
+
+// ====================
+// === Atlas Layout ===
+// ====================
+
+/// Number of used color channels in the MSDF texture.
+pub const CHANNELS_COUNT: usize = 3;
+
+/// Width of single MSDF in cells.
+pub const GLYPH_WIDTH: usize = 32;
+
+/// Height of single MSDF in cells.
+pub const GLYPH_HEIGHT: usize = 32;
+
+/// Size of the atlas row.
+pub const ROW_SIZE: usize = CHANNELS_COUNT * GLYPH_WIDTH;
+
+/// Size of single MSDF in the atlas.
+pub const GLYPH_SIZE: usize = ROW_SIZE * GLYPH_HEIGHT;
+
+/// Convert the MSDF value to the 8-bit atlas cell value.
+pub fn cell_from_msdf_value(value: f32) -> u8 {
+    const UNSIGNED_BYTE_MIN: f32 = 0.0;
+    const UNSIGNED_BYTE_MAX: f32 = 255.0;
+    let scaled_to_byte = value * UNSIGNED_BYTE_MAX;
+    let clamped_to_byte = scaled_to_byte.clamp(UNSIGNED_BYTE_MIN, UNSIGNED_BYTE_MAX);
+    clamped_to_byte as u8
+}
+
+/// Converts x dimension distance obtained from the MSDF generator to vertex-space values
+///
+/// The values obtained from the generator are expressed in MSDF cells. This function convert them
+/// to normalized coordinates, where (0.0, 0.0) is initial pen position for an character, and
+/// `y` = 1.0 is _ascender_.
+pub fn x_distance_from_msdf_value(msdf_value: f64) -> f32 {
+    msdf_value as f32 / GLYPH_WIDTH as f32
+}
+
+/// Converts y dimension distance obtained from the MSDF generator to vertex-space values
+///
+/// The values obtained from the generator are expressed in MSDF cells. This function convert them
+/// to normalized coordinates, where (0.0, 0.0) is initial pen position for an character, and
+/// `y` = 1.0 is _ascender_.
+pub fn y_distance_from_msdf_value(msdf_value: f64) -> f32 {
+    msdf_value as f32 / GLYPH_HEIGHT as f32
+}
@@ -0,0 +1,186 @@
+//! Pure-Rust generator of Multichannel Signed Distance Fields (MSDF) for glyphs.
+//!
+//! This is a port of the parts of the [msdfgen](https://github.com/Chlumsky/msdfgen) library used
+//! through its WASM bindings in `ensogl-text-msdf`: outline loading, "simple" edge coloring,
+//! distance field generation with overlapping contours support, and the legacy error correction.
+//! The results have the same layout as the ones of the bindings, and are interchangeable with them
+//! up to floating point differences (see [`reference`]). It allows generating glyph atlases
+//! natively, without running the application in a browser.
+
+use crate::prelude::*;
+
+use crate::MsdfParameters;
+
+use owned_ttf_parser as ttf;
+
+
+// ==============
+// === Export ===
+// ==============
+
+pub mod field;
+pub mod reference;
+pub mod segment;
+pub mod shape;
+
+pub use shape::Shape;
+
+
+
+// ============
+// === Msdf ===
+// ============
+
+/// MSDF of a single glyph, generated natively. The fields have the same meaning as the ones of the
+/// msdfgen bindings' `Msdf`, and `data` has the same layout as its memory view.
+#[derive(Clone, Debug, PartialEq)]
+#[allow(missing_docs)]
+pub struct Msdf {
+    pub advance:     f64,
+    pub translation: Vector2<f64>,
+    pub scale:       Vector2<f64>,
+    pub data:        Vec<f32>,
+}
+
+impl Msdf {
+    /// Number of used color channels in the MSDF texture.
+    pub const CHANNELS_COUNT: usize = crate::CHANNELS_COUNT;
+
+    /// Generate MSDF for the glyph of the given character. Characters not present in the font are
+    /// rendered as the "missing glyph".
+    pub fn generate(face: &ttf::Face, unicode: u32, params: &MsdfParameters) -> Msdf {
+        let glyph = char::from_u32(unicode).and_then(|c| face.glyph_index(c));
+        Self::generate_by_index(face, glyph.unwrap_or_default().0 as usize, params)
+    }
+
+    /// Generate MSDF for the glyph by its index in the font.
+    ///
+    /// Variable fonts are rendered with their default variation axes values.
+    #[profile(Debug)]
+    pub fn generate_by_index(face: &ttf::Face, index: usize, params: &MsdfParameters) -> Msdf {
+        let (mut shape, advance) = Shape::load(face, ttf::GlyphId(index as u16));
+        shape.normalize();
+        shape.color_edges(params.edge_coloring_angle_threshold);
+        let projection = auto_frame(&shape, params);
+        let data = field::generate(&shape, projection, params);
+        let translation = projection.translation;
+        let scale = projection.scale;
+        Msdf { advance, translation, scale, data }
+    }
+}
+
+/// Fit the shape, expanded by half of the distance range on each side, into the MSDF frame,
+/// centering it along the axis where it is too small to fill the frame. The scale is then limited
+/// to `params.max_scale`, keeping the translation, so small glyphs stay in the frame's bottom-left
+/// corner.
+fn auto_frame(shape: &Shape, params: &MsdfParameters) -> field::Projection {
+    let margin = Vector2(params.range, params.range) / 2.0;
+    let bounds = shape.bounds().map(|(min, max)| (min - margin, max + margin));
+    let is_valid = |(min, max): &(Vector2<f64>, Vector2<f64>)| min.x < max.x && min.y < max.y;
+    let default_bounds = (Vector2(0.0, 0.0), Vector2(1.0, 1.0));
+    let (min, max) = bounds.filter(is_valid).unwrap_or(default_bounds);
+    let frame = Vector2(params.width as f64, params.height as f64);
+    let dims = max - min;
+    let (translation, scale) = if dims.x * frame.y < dims.y * frame.x {
+        let x = 0.5 * (frame.x / frame.y * dims.y - dims.x) - min.x;
+        (Vector2(x, -min.y), frame.y / dims.y)
+    } else {
+        let y = 0.5 * (frame.y / frame.x * dims.x - dims.y) - min.y;
+        (Vector2(-min.x, y), frame.x / dims.x)
+    };
+    let scale = scale.min(params.max_scale);
+    field::Projection { scale: Vector2(scale, scale), translation }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ensogl_text_embedded_fonts::Embedded;
+
+    const TEST_PARAMETERS: MsdfParameters = reference::PARAMETERS;
+
+    fn with_enso_font(f: impl FnOnce(&ttf::Face)) {
+        let font_base = Embedded::default();
+        let data = font_base.data.get(reference::FONT).unwrap();
+        let face = ttf::Face::from_slice(data, 0).unwrap();
+        f(&face)
+    }
+
+    /// The values checked by the tests of the WASM bindings for the same glyph.
+    #[test]
+    fn generate_msdf_for_capital_a() {
+        with_enso_font(|face| {
+            let msdf = Msdf::generate(face, 'A' as u32, &TEST_PARAMETERS);
+            assert_eq!(msdf.data.len(), 32 * 32 * 3);
+            assert_eq!(msdf.advance, 10.921875);
+            assert_eq!(msdf.scale, Vector2(2.0, 2.0));
+            assert_approx_eq!(msdf.translation.x, 1.2421875, 1e-6);
+            assert_approx_eq!(msdf.translation.y, 1.0, 1e-6);
+            assert_approx_eq!(msdf.data[0], 0.053712357, 1e-3);
+            assert_approx_eq!(msdf.data[10], 0.125, 1e-3);
+            assert_approx_eq!(msdf.data[msdf.data.len() - 1], -3.9244988, 1e-3);
+            assert_eq!(msdf, Msdf::generate_by_index(face, 2, &TEST_PARAMETERS));
+        })
+    }
+
+    #[test]
+    fn generated_msdf_is_equivalent_to_msdfgen_output() {
+        with_enso_font(|face| {
+            let glyphs = reference::glyphs();
+            assert!(!glyphs.is_empty());
+            for glyph in glyphs {
+                let msdf = Msdf::generate(face, glyph.character as u32, &TEST_PARAMETERS);
+                let character = glyph.character;
+                assert_eq!(msdf.advance, glyph.advance, "Advance of {character:?}.");
+                assert_eq!(msdf.scale, glyph.scale(), "Scale of {character:?}.");
+                let translation_difference = (msdf.translation - glyph.translation()).norm();
+                assert!(translation_difference < 1e-6, "Translation of {character:?}.");
+                let differing = glyph.differing_values(&msdf.data);
+                let differing_fraction = differing as f32 / glyph.values.len() as f32;
+                assert!(
+                    differing_fraction <= reference::MAX_DIFFERING_VALUES_FRACTION,
+                    "{differing} MSDF values of {character:?} differ from the ones of msdfgen."
+                );
+            }
+        })
+    }
+
+    #[test]
+    fn glyph_without_outline() {
+        with_enso_font(|face| {
+            let msdf = Msdf::generate(face, ' ' as u32, &TEST_PARAMETERS);
+            assert!(msdf.advance > 0.0);
+            assert!(msdf.data.iter().all(|&value| value < 0.0));
+        })
+    }
+
+    #[test]
+    fn fields_are_inside_positive() {
+        with_enso_font(|face| {
+            let msdf = Msdf::generate(face, 'O' as u32, &TEST_PARAMETERS);
+            let median = |x: usize, y: usize| {
+                let index = (y * TEST_PARAMETERS.width + x) * 3;
+                let [r, g, b] = [0, 1, 2].map(|i| msdf.data[index + i]);
+                r.min(g).max(r.max(g).min(b))
+            };
+            // The frame corner is outside of the glyph, as it is in the distance range margin.
+            assert!(median(TEST_PARAMETERS.width - 1, TEST_PARAMETERS.height - 1) < 0.5);
+            // Rows in the middle of the glyph cross the ring twice, entering and leaving the
+            // glyph on each side of the hole.
+            let crossings = |y: usize| {
+                let row = (0..TEST_PARAMETERS.width).map(|x| median(x, y) > 0.5).collect_vec();
+                row.iter().tuple_windows().filter(|(a, b)| a != b).count()
+            };
+            let row_crossings = (0..TEST_PARAMETERS.height).map(crossings).collect_vec();
+            assert!(row_crossings.contains(&4));
+            assert!(row_crossings.iter().all(|&count| count <= 4));
+        })
+    }
+}
@@ -0,0 +1,456 @@
+//! Computation of the multi-channel signed distance field of a colored [`Shape`].
+
+use crate::prelude::*;
+
+use crate::msdf::segment::cross;
+use crate::msdf::segment::normalize_allow_zero;
+use crate::msdf::segment::EdgeColor;
+use crate::msdf::segment::Segment;
+use crate::msdf::segment::SignedDistance;
+use crate::msdf::shape::Contour;
+use crate::msdf::shape::Edge;
+use crate::msdf::shape::Shape;
+use crate::msdf::Msdf;
+
+
+
+// ==================
+// === Projection ===
+// ==================
+
+/// The transformation from the field's pixel coordinates to the shape coordinates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[allow(missing_docs)]
+pub struct Projection {
+    pub scale:       Vector2<f64>,
+    pub translation: Vector2<f64>,
+}
+
+impl Projection {
+    /// The shape-space position of the center of the given pixel.
+    pub fn unproject(&self, x: usize, y: usize) -> Vector2<f64> {
+        let pixel_center = Vector2(x as f64 + 0.5, y as f64 + 0.5);
+        pixel_center.component_div(&self.scale) - self.translation
+    }
+}
+
+
+
+// =======================
+// === ChannelSelector ===
+// =======================
+
+/// Tracks the closest edge of a single channel, together with the closest pseudo-distances
+/// measured in the edges' extension domains.
+#[derive(Clone, Copy, Debug)]
+struct ChannelSelector {
+    min_true_distance:            SignedDistance,
+    min_negative_pseudo_distance: f64,
+    min_positive_pseudo_distance: f64,
+    near_edge:                    Option<(Segment, f64)>,
+}
+
+impl Default for ChannelSelector {
+    fn default() -> Self {
+        Self {
+            min_true_distance:            default(),
+            min_negative_pseudo_distance: -f64::MAX,
+            min_positive_pseudo_distance: f64::MAX,
+            near_edge:                    None,
+        }
+    }
+}
+
+impl ChannelSelector {
+    fn add_edge_true_distance(&mut self, segment: &Segment, distance: SignedDistance, param: f64) {
+        if distance.is_closer_than(&self.min_true_distance) {
+            self.min_true_distance = distance;
+            self.near_edge = Some((*segment, param));
+        }
+    }
+
+    fn add_edge_pseudo_distance(&mut self, distance: f64) {
+        let min_distance = if distance < 0.0 {
+            &mut self.min_negative_pseudo_distance
+        } else {
+            &mut self.min_positive_pseudo_distance
+        };
+        if distance.abs() < min_distance.abs() {
+            *min_distance = distance;
+        }
+    }
+
+    fn merge(&mut self, other: &Self) {
+        if other.min_true_distance.is_closer_than(&self.min_true_distance) {
+            self.min_true_distance = other.min_true_distance;
+            self.near_edge = other.near_edge;
+        }
+        if other.min_negative_pseudo_distance > self.min_negative_pseudo_distance {
+            self.min_negative_pseudo_distance = other.min_negative_pseudo_distance;
+        }
+        if other.min_positive_pseudo_distance < self.min_positive_pseudo_distance {
+            self.min_positive_pseudo_distance = other.min_positive_pseudo_distance;
+        }
+    }
+
+    fn distance(&self, point: Vector2<f64>) -> f64 {
+        let mut min_distance = if self.min_true_distance.distance < 0.0 {
+            self.min_negative_pseudo_distance
+        } else {
+            self.min_positive_pseudo_distance
+        };
+        if let Some((segment, param)) = &self.near_edge {
+            let mut distance = self.min_true_distance;
+            segment.distance_to_pseudo_distance(&mut distance, point, *param);
+            if distance.distance.abs() < min_distance.abs() {
+                min_distance = distance.distance;
+            }
+        }
+        min_distance
+    }
+}
+
+
+
+// =====================
+// === MultiSelector ===
+// =====================
+
+/// Distances of the three MSDF channels.
+type MultiDistance = [f64; 3];
+
+/// Selects the edges determining each of the three channels of a single pixel.
+#[derive(Clone, Copy, Debug)]
+struct MultiSelector {
+    point:    Vector2<f64>,
+    channels: [ChannelSelector; 3],
+}
+
+impl MultiSelector {
+    fn new(point: Vector2<f64>) -> Self {
+        let channels = default();
+        Self { point, channels }
+    }
+
+    fn add_edge(&mut self, prev: &Segment, edge: &Edge, next: &Segment) {
+        let point = self.point;
+        let segment = &edge.segment;
+        let (distance, param) = segment.signed_distance(point);
+        let is_in_channel = EdgeColor::CHANNELS.map(|channel| edge.color.contains(channel));
+        let channels = self.channels.iter_mut().zip(is_in_channel);
+        let mut channels = channels.filter_map(|(channel, is_in)| is_in.then_some(channel));
+        let mut channels = [channels.next(), channels.next(), channels.next()];
+        for channel in channels.iter_mut().flatten() {
+            channel.add_edge_true_distance(segment, distance, param);
+        }
+        let ap = point - segment.start();
+        let bp = point - segment.end();
+        let a_dir = normalize_allow_zero(segment.direction(0.0));
+        let b_dir = normalize_allow_zero(segment.direction(1.0));
+        let prev_dir = normalize_allow_zero(prev.direction(1.0));
+        let next_dir = normalize_allow_zero(next.direction(0.0));
+        let add = ap.dot(&normalize_allow_zero(prev_dir + a_dir));
+        let bdd = -bp.dot(&normalize_allow_zero(b_dir + next_dir));
+        if add > 0.0 {
+            let mut pseudo_distance = distance.distance;
+            if pseudo_distance_to_ray(&mut pseudo_distance, ap, -a_dir) {
+                pseudo_distance = -pseudo_distance;
+            }
+            for channel in channels.iter_mut().flatten() {
+                channel.add_edge_pseudo_distance(pseudo_distance);
+            }
+        }
+        if bdd > 0.0 {
+            let mut pseudo_distance = distance.distance;
+            pseudo_distance_to_ray(&mut pseudo_distance, bp, b_dir);
+            for channel in channels.iter_mut().flatten() {
+                channel.add_edge_pseudo_distance(pseudo_distance);
+            }
+        }
+    }
+
+    fn add_contour(&mut self, contour: &Contour) {
+        let edges = &contour.edges;
+        if let Some(last) = edges.last() {
+            let mut prev = if edges.len() >= 2 { edges[edges.len() - 2] } else { edges[0] };
+            let mut current = *last;
+            for next in edges {
+                self.add_edge(&prev.segment, &current, &next.segment);
+                prev = current;
+                current = *next;
+            }
+        }
+    }
+
+    fn merge(&mut self, other: &Self) {
+        for (channel, other) in self.channels.iter_mut().zip(&other.channels) {
+            channel.merge(other);
+        }
+    }
+
+    fn distance(&self) -> MultiDistance {
+        self.channels.map(|channel| channel.distance(self.point))
+    }
+}
+
+/// If the point lies in front of the ray, replace the `distance` with the distance to the ray's
+/// line, provided it is smaller. Returns `true` if the distance was replaced.
+fn pseudo_distance_to_ray(
+    distance: &mut f64,
+    to_point: Vector2<f64>,
+    direction: Vector2<f64>,
+) -> bool {
+    let ts = to_point.dot(&direction);
+    if ts > 0.0 {
+        let pseudo_distance = cross(to_point, direction);
+        if pseudo_distance.abs() < distance.abs() {
+            *distance = pseudo_distance;
+            return true;
+        }
+    }
+    false
+}
+
+fn median(distance: MultiDistance) -> f64 {
+    let [a, b, c] = distance;
+    a.min(b).max(a.max(b).min(c))
+}
+
+
+
+// ================
+// === Combiner ===
+// ================
+
+/// The distance at the given point, resolving overlapping contours: contours of the same winding
+/// may overlap, and the edges hidden inside other contours are not considered.
+fn overlapping_distance(shape: &Shape, windings: &[i32], point: Vector2<f64>) -> MultiDistance {
+    let selectors = shape
+        .contours
+        .iter()
+        .map(|contour| {
+            let mut selector = MultiSelector::new(point);
+            selector.add_contour(contour);
+            selector
+        })
+        .collect_vec();
+    let contour_distances = selectors.iter().map(|s| s.distance()).zip(windings).collect_vec();
+    let mut shape_selector = MultiSelector::new(point);
+    let mut inner_selector = MultiSelector::new(point);
+    let mut outer_selector = MultiSelector::new(point);
+    for (selector, (distance, &winding)) in selectors.iter().zip(&contour_distances) {
+        let distance = median(*distance);
+        shape_selector.merge(selector);
+        if winding > 0 && distance >= 0.0 {
+            inner_selector.merge(selector);
+        }
+        if winding < 0 && distance <= 0.0 {
+            outer_selector.merge(selector);
+        }
+    }
+    let shape_distance = shape_selector.distance();
+    let inner_distance = inner_selector.distance();
+    let outer_distance = outer_selector.distance();
+    let inner_scalar_distance = median(inner_distance);
+    let outer_scalar_distance = median(outer_distance);
+    let (mut distance, winding) = if inner_scalar_distance >= 0.0
+        && inner_scalar_distance.abs() <= outer_scalar_distance.abs()
+    {
+        let mut distance = inner_distance;
+        for (contour_distance, &winding) in &contour_distances {
+            let scalar = median(*contour_distance);
+            if winding > 0
+                && scalar.abs() < outer_scalar_distance.abs()
+                && scalar > median(distance)
+            {
+                distance = *contour_distance;
+            }
+        }
+        (distance, 1)
+    } else if outer_scalar_distance <= 0.0
+        && outer_scalar_distance.abs() < inner_scalar_distance.abs()
+    {
+        let mut distance = outer_distance;
+        for (contour_distance, &winding) in &contour_distances {
+            let scalar = median(*contour_distance);
+            if winding < 0
+                && scalar.abs() < inner_scalar_distance.abs()
+                && scalar < median(distance)
+            {
+                distance = *contour_distance;
+            }
+        }
+        (distance, -1)
+    } else {
+        return shape_distance;
+    };
+    for (contour_distance, &contour_winding) in &contour_distances {
+        if contour_winding != winding {
+            let scalar = median(*contour_distance);
+            let current = median(distance);
+            if scalar * current >= 0.0 && scalar.abs() < current.abs() {
+                distance = *contour_distance;
+            }
+        }
+    }
+    if median(distance) == median(shape_distance) {
+        distance = shape_distance;
+    }
+    distance
+}
+
+/// The distance at the given point, treating all contours as a single one.
+fn simple_distance(shape: &Shape, point: Vector2<f64>) -> MultiDistance {
+    let mut selector = MultiSelector::new(point);
+    for contour in &shape.contours {
+        selector.add_contour(contour);
+    }
+    selector.distance()
+}
+
+
+
+// ==================
+// === Generation ===
+// ==================
+
+/// Generate the MSDF of the shape, whose edges must already be colored. The result contains
+/// `width * height` pixels of [`Msdf::CHANNELS_COUNT`] values each, with rows ordered from the
+/// bottom, as produced by msdfgen. The distance values are expressed in `range` units, and offset,
+/// so `0.5` lies on the shape's edge.
+pub fn generate(shape: &Shape, projection: Projection, params: &crate::MsdfParameters) -> Vec<f32> {
+    let (width, height) = (params.width, params.height);
+    let range = params.range;
+    let windings = shape.contours.iter().map(|contour| contour.winding()).collect_vec();
+    let mut data = Vec::with_capacity(width * height * Msdf::CHANNELS_COUNT);
+    for y in 0..height {
+        for x in 0..width {
+            let point = projection.unproject(x, y);
+            let distance = if params.overlap_support {
+                overlapping_distance(shape, &windings, point)
+            } else {
+                simple_distance(shape, point)
+            };
+            data.extend(distance.map(|channel| (channel / range + 0.5) as f32));
+        }
+    }
+    if params.edge_threshold > 0.0 {
+        let threshold = Vector2(params.edge_threshold, params.edge_threshold)
+            .component_div(&(projection.scale * range));
+        correct_errors(&mut data, width, height, threshold);
+    }
+    data
+}
+
+
+
+// ========================
+// === Error Correction ===
+// ========================
+
+type Pixel = [f32; 3];
+
+/// Find pixels whose channels change inconsistently with their neighbours, which would produce
+/// artifacts when interpolated, and replace their channels with the median value.
+fn correct_errors(data: &mut [f32], width: usize, height: usize, threshold: Vector2<f64>) {
+    let pixel = |data: &[f32], x: usize, y: usize| -> Pixel {
+        let index = (y * width + x) * Msdf::CHANNELS_COUNT;
+        [data[index], data[index + 1], data[index + 2]]
+    };
+    let straight =
+        [(-1, 0, threshold.x), (1, 0, threshold.x), (0, -1, threshold.y), (0, 1, threshold.y)];
+    let diagonal_threshold = threshold.x + threshold.y;
+    let diagonal =
+        [(-1, -1), (1, -1), (-1, 1), (1, 1)].map(|(dx, dy)| (dx, dy, diagonal_threshold));
+    for neighbours in [straight, diagonal] {
+        let mut clashes = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                let current = pixel(data, x, y);
+                let is_clash = neighbours.iter().any(|&(dx, dy, threshold)| {
+                    let nx = x as isize + dx;
+                    let ny = y as isize + dy;
+                    let in_bounds =
+                        nx >= 0 && ny >= 0 && (nx as usize) < width && (ny as usize) < height;
+                    in_bounds
+                        && is_pixel_clash(current, pixel(data, nx as usize, ny as usize), threshold)
+                });
+                if is_clash {
+                    clashes.push((x, y));
+                }
+            }
+        }
+        for (x, y) in clashes {
+            let index = (y * width + x) * Msdf::CHANNELS_COUNT;
+            let [r, g, b] = pixel(data, x, y);
+            let median = r.min(g).max(r.max(g).min(b));
+            data[index..index + 3].fill(median);
+        }
+    }
+}
+
+/// Check if the pair of neighbouring pixels clash, and the first one is the one which should be
+/// corrected.
+fn is_pixel_clash(a: Pixel, b: Pixel, threshold: f64) -> bool {
+    let inside = |p: Pixel| p.iter().filter(|&&c| c > 0.5).count() >= 2;
+    // Only consider pairs where both are on the inside or both are on the outside.
+    if inside(a) != inside(b) {
+        return false;
+    }
+    // If the change is 0 <-> 1 or 2 <-> 3 channels and not 1 <-> 1 or 2 <-> 2, it is not a clash.
+    let is_uniform = |p: Pixel| p.iter().all(|&c| c > 0.5) || p.iter().all(|&c| c < 0.5);
+    if is_uniform(a) || is_uniform(b) {
+        return false;
+    }
+    let flips = |i: usize| (a[i] > 0.5) != (b[i] > 0.5) && (a[i] < 0.5) != (b[i] < 0.5);
+    let (first, second, remaining) = if flips(0) {
+        if flips(1) {
+            (0, 1, 2)
+        } else if flips(2) {
+            (0, 2, 1)
+        } else {
+            return false;
+        }
+    } else if flips(1) && flips(2) {
+        (1, 2, 0)
+    } else {
+        return false;
+    };
+    let threshold = threshold as f32;
+    // Find if the channels are in fact discontinuous. Out of the pair, only flag the pixel farther
+    // from a shape edge.
+    (a[first] - b[first]).abs() >= threshold
+        && (a[second] - b[second]).abs() >= threshold
+        && (a[remaining] - 0.5).abs() >= (b[remaining] - 0.5).abs()
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clashing_pixels_are_detected() {
+        let threshold = 0.25;
+        let a = [0.9, 0.1, 0.6];
+        let b = [0.1, 0.9, 0.55];
+        assert!(is_pixel_clash(a, b, threshold));
+        assert!(!is_pixel_clash(b, a, threshold));
+        let continuous = [0.8, 0.2, 0.6];
+        assert!(!is_pixel_clash(a, continuous, threshold));
+        let uniform = [0.9, 0.9, 0.9];
+        assert!(!is_pixel_clash(a, uniform, threshold));
+    }
+
+    #[test]
+    fn median_of_channels() {
+        assert_eq!(median([1.0, 3.0, 2.0]), 2.0);
+        assert_eq!(median([3.0, 1.0, 2.0]), 2.0);
+        assert_eq!(median([2.0, 2.0, -1.0]), 2.0);
+    }
+}
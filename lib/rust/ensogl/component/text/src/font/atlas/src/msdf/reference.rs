@@ -0,0 +1,88 @@
+//! Reference output of the msdfgen library, used to check that the native generator is equivalent
+//! to it.
+//!
+//! The output is checked in as [`MSDFGEN_OUTPUT`]. It is verified against msdfgen by the tests of
+//! its WASM bindings in `ensogl-text-msdf`, which print the library's output for a glyph when it
+//! differs from the reference. New glyphs and values can be added the same way.
+
+use crate::prelude::*;
+
+use crate::MsdfParameters;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The embedded font file the reference glyphs are taken from.
+pub const FONT: &str = "Enso-Regular.ttf";
+
+/// The parameters the reference MSDFs were generated with.
+pub const PARAMETERS: MsdfParameters = MsdfParameters {
+    width: 32,
+    height: 32,
+    edge_coloring_angle_threshold: 3.0,
+    range: 2.0,
+    max_scale: 2.0,
+    edge_threshold: 1.001,
+    overlap_support: true,
+};
+
+/// The msdfgen output for the glyphs of [`FONT`], generated with [`PARAMETERS`].
+pub const MSDFGEN_OUTPUT: &str = include_str!("msdfgen_reference.json");
+
+/// The maximum difference between MSDF values which are considered equal. The values are
+/// expressed in the distance `range` units, and the texture stores them with the precision of
+/// 1/255.
+pub const MAX_VALUE_DIFFERENCE: f32 = 2.0 / 255.0;
+
+/// The maximum fraction of MSDF values which may differ. The error correction of both generators
+/// may resolve the rounding differences near clashing channels differently.
+pub const MAX_DIFFERING_VALUES_FRACTION: f32 = 0.02;
+
+
+
+// =============
+// === Glyph ===
+// =============
+
+/// The msdfgen output for a single glyph.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[allow(missing_docs)]
+pub struct Glyph {
+    pub character:   char,
+    pub advance:     f64,
+    pub translation: [f64; 2],
+    pub scale:       [f64; 2],
+    /// Pairs of an index in the MSDF data and the value at this index.
+    pub values:      Vec<(usize, f32)>,
+}
+
+impl Glyph {
+    /// The translation of the glyph's MSDF frame.
+    pub fn translation(&self) -> Vector2<f64> {
+        Vector2(self.translation[0], self.translation[1])
+    }
+
+    /// The scale of the glyph's MSDF frame.
+    pub fn scale(&self) -> Vector2<f64> {
+        Vector2(self.scale[0], self.scale[1])
+    }
+
+    /// The number of reference values differing from the ones in `data` by more than
+    /// [`MAX_VALUE_DIFFERENCE`]. Values missing in `data` are counted as differing.
+    pub fn differing_values(&self, data: &[f32]) -> usize {
+        let is_different = |&&(index, value): &&(usize, f32)| match data.get(index) {
+            Some(actual) => (actual - value).abs() > MAX_VALUE_DIFFERENCE,
+            None => true,
+        };
+        self.values.iter().filter(is_different).count()
+    }
+}
+
+/// The reference glyphs.
+pub fn glyphs() -> Vec<Glyph> {
+    // The reference is a compile-time constant, so it either parses consistently or not at all.
+    serde_json::from_str(MSDFGEN_OUTPUT).unwrap()
+}
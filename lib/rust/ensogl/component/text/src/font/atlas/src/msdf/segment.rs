@@ -0,0 +1,573 @@
+//! Edge segments of a glyph outline and the distance computations performed on them. The
+//! implementation follows the one of the msdfgen library closely, so that the generated fields are
+//! interchangeable with the ones computed by the WASM build of msdfgen.
+
+use crate::prelude::*;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The number of starting points of the iterative search for the closest point of a cubic curve.
+const CUBIC_SEARCH_STARTS: usize = 4;
+/// The number of refining steps for each starting point of the cubic curve search.
+const CUBIC_SEARCH_STEPS: usize = 4;
+
+
+
+// =================
+// === EdgeColor ===
+// =================
+
+/// A set of MSDF channels an edge contributes to. Encoded as bits: red is `1`, green is `2`, and
+/// blue is `4`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EdgeColor(u8);
+
+#[allow(missing_docs)]
+impl EdgeColor {
+    pub const BLACK: Self = Self(0);
+    pub const RED: Self = Self(1);
+    pub const GREEN: Self = Self(2);
+    pub const YELLOW: Self = Self(3);
+    pub const BLUE: Self = Self(4);
+    pub const MAGENTA: Self = Self(5);
+    pub const CYAN: Self = Self(6);
+    pub const WHITE: Self = Self(7);
+
+    /// The colors of single channels, in the order of the MSDF texture channels.
+    pub const CHANNELS: [Self; 3] = [Self::RED, Self::GREEN, Self::BLUE];
+
+    /// Check if all channels of `other` are included in this color.
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// The raw bits of the color.
+    pub fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Create a color from raw bits. Bits other than the three lowest ones are ignored.
+    pub fn from_bits(bits: u8) -> Self {
+        Self(bits & Self::WHITE.0)
+    }
+}
+
+
+
+// ======================
+// === SignedDistance ===
+// ======================
+
+/// A signed distance to an edge, together with a measure of how orthogonal the edge is to the
+/// direction to the point. The latter is used to choose between edges equally distant from the
+/// point, which happens near corners.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SignedDistance {
+    /// The distance, positive on the right side of the edge.
+    pub distance: f64,
+    /// The absolute value of the cosine of the angle between the edge and the direction from its
+    /// closest point to the measured point. Zero, if the closest point is not an endpoint.
+    pub dot:      f64,
+}
+
+impl SignedDistance {
+    /// Constructor.
+    pub fn new(distance: f64, dot: f64) -> Self {
+        Self { distance, dot }
+    }
+
+    /// Check if this distance is closer than the `other` one.
+    pub fn is_closer_than(&self, other: &Self) -> bool {
+        let this = self.distance.abs();
+        let other_distance = other.distance.abs();
+        this < other_distance || (this == other_distance && self.dot < other.dot)
+    }
+}
+
+impl Default for SignedDistance {
+    /// The farthest possible distance.
+    fn default() -> Self {
+        Self::new(-f64::MAX, 1.0)
+    }
+}
+
+
+
+// ===============
+// === Segment ===
+// ===============
+
+/// A single segment of a glyph contour.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[allow(missing_docs)]
+pub enum Segment {
+    Linear([Vector2<f64>; 2]),
+    Quadratic([Vector2<f64>; 3]),
+    Cubic([Vector2<f64>; 4]),
+}
+
+impl Segment {
+    /// The point of the segment at the given parameter, where `0.0` is the start and `1.0` is the
+    /// end of the segment.
+    pub fn point(&self, t: f64) -> Vector2<f64> {
+        match self {
+            Self::Linear([p0, p1]) => mix(*p0, *p1, t),
+            Self::Quadratic([p0, p1, p2]) => mix(mix(*p0, *p1, t), mix(*p1, *p2, t), t),
+            Self::Cubic([p0, p1, p2, p3]) => {
+                let p12 = mix(*p1, *p2, t);
+                mix(mix(mix(*p0, *p1, t), p12, t), mix(p12, mix(*p2, *p3, t), t), t)
+            }
+        }
+    }
+
+    /// The direction of the segment at the given parameter. Not normalized.
+    pub fn direction(&self, t: f64) -> Vector2<f64> {
+        match self {
+            Self::Linear([p0, p1]) => p1 - p0,
+            Self::Quadratic([p0, p1, p2]) => {
+                let tangent = mix(p1 - p0, p2 - p1, t);
+                if is_zero(tangent) {
+                    p2 - p0
+                } else {
+                    tangent
+                }
+            }
+            Self::Cubic([p0, p1, p2, p3]) => {
+                let tangent = mix(mix(p1 - p0, p2 - p1, t), mix(p2 - p1, p3 - p2, t), t);
+                if is_zero(tangent) && t == 0.0 {
+                    p2 - p0
+                } else if is_zero(tangent) && t == 1.0 {
+                    p3 - p1
+                } else {
+                    tangent
+                }
+            }
+        }
+    }
+
+    /// The first point of the segment.
+    pub fn start(&self) -> Vector2<f64> {
+        self.point(0.0)
+    }
+
+    /// The last point of the segment.
+    pub fn end(&self) -> Vector2<f64> {
+        match self {
+            Self::Linear(p) => p[1],
+            Self::Quadratic(p) => p[2],
+            Self::Cubic(p) => p[3],
+        }
+    }
+
+    /// Extend the `(min, max)` bounding box so it contains the whole segment.
+    pub fn bound(&self, bounds: &mut (Vector2<f64>, Vector2<f64>)) {
+        let mut add = |p: Vector2<f64>| {
+            bounds.0 = bounds.0.inf(&p);
+            bounds.1 = bounds.1.sup(&p);
+        };
+        add(self.start());
+        add(self.end());
+        let extrema: Vec<f64> = match self {
+            Self::Linear(_) => default(),
+            Self::Quadratic([p0, p1, p2]) => {
+                let bot = (p1 - p0) - (p2 - p1);
+                let x = (bot.x != 0.0).then(|| (p1.x - p0.x) / bot.x);
+                let y = (bot.y != 0.0).then(|| (p1.y - p0.y) / bot.y);
+                x.into_iter().chain(y).collect()
+            }
+            Self::Cubic([p0, p1, p2, p3]) => {
+                let a0 = p1 - p0;
+                let a1 = 2.0 * (p2 - p1 - a0);
+                let a2 = p3 - 3.0 * p2 + 3.0 * p1 - p0;
+                let x = solve_quadratic(a2.x, a1.x, a0.x);
+                let y = solve_quadratic(a2.y, a1.y, a0.y);
+                x.into_iter().chain(y).collect()
+            }
+        };
+        for t in extrema {
+            if t > 0.0 && t < 1.0 {
+                add(self.point(t));
+            }
+        }
+    }
+
+    /// Split the segment into three parts of equal parameter span.
+    pub fn split_in_thirds(&self) -> [Segment; 3] {
+        let third = 1.0 / 3.0;
+        let two_thirds = 2.0 / 3.0;
+        let p_1 = self.point(third);
+        let p_2 = self.point(two_thirds);
+        match *self {
+            Self::Linear([p0, p1]) =>
+                [Self::Linear([p0, p_1]), Self::Linear([p_1, p_2]), Self::Linear([p_2, p1])],
+            Self::Quadratic([p0, p1, p2]) => {
+                let c0 = mix(p0, p1, third);
+                let c1 = mix(mix(p0, p1, 5.0 / 9.0), mix(p1, p2, 4.0 / 9.0), 0.5);
+                let c2 = mix(p1, p2, two_thirds);
+                [
+                    Self::Quadratic([p0, c0, p_1]),
+                    Self::Quadratic([p_1, c1, p_2]),
+                    Self::Quadratic([p_2, c2, p2]),
+                ]
+            }
+            Self::Cubic([p0, p1, p2, p3]) => {
+                let at = |t: f64| {
+                    let a = mix(mix(p0, p1, t), mix(p1, p2, t), t);
+                    let b = mix(mix(p1, p2, t), mix(p2, p3, t), t);
+                    (a, b)
+                };
+                let (a_1, b_1) = at(third);
+                let (a_2, b_2) = at(two_thirds);
+                let first_c0 = if p0 == p1 { p0 } else { mix(p0, p1, third) };
+                let last_c1 = if p2 == p3 { p3 } else { mix(p2, p3, two_thirds) };
+                [
+                    Self::Cubic([p0, first_c0, a_1, p_1]),
+                    Self::Cubic([p_1, mix(a_1, b_1, two_thirds), mix(a_2, b_2, third), p_2]),
+                    Self::Cubic([p_2, b_2, last_c1, p3]),
+                ]
+            }
+        }
+    }
+
+    /// The signed distance from the `origin` to the segment, together with the parameter of the
+    /// segment's closest point. The parameter may lie outside of the `0.0..=1.0` range if the
+    /// closest point is an endpoint; it then tells on which extension of the segment the origin
+    /// lies.
+    pub fn signed_distance(&self, origin: Vector2<f64>) -> (SignedDistance, f64) {
+        match *self {
+            Self::Linear([p0, p1]) => {
+                let aq = origin - p0;
+                let ab = p1 - p0;
+                let param = aq.dot(&ab) / ab.dot(&ab);
+                let eq = if param > 0.5 { p1 } else { p0 } - origin;
+                let endpoint_distance = eq.norm();
+                if param > 0.0 && param < 1.0 {
+                    let ortho_distance = orthonormal(ab).dot(&aq);
+                    if ortho_distance.abs() < endpoint_distance {
+                        return (SignedDistance::new(ortho_distance, 0.0), param);
+                    }
+                }
+                let distance = non_zero_sign(cross(aq, ab)) * endpoint_distance;
+                let dot = normalize(ab).dot(&normalize(eq)).abs();
+                (SignedDistance::new(distance, dot), param)
+            }
+            Self::Quadratic([p0, p1, p2]) => {
+                let qa = p0 - origin;
+                let ab = p1 - p0;
+                let br = p2 - p1 - ab;
+                let a = br.dot(&br);
+                let b = 3.0 * ab.dot(&br);
+                let c = 2.0 * ab.dot(&ab) + qa.dot(&br);
+                let d = qa.dot(&ab);
+                let ep_dir = self.direction(0.0);
+                let mut min_distance = non_zero_sign(cross(ep_dir, qa)) * qa.norm();
+                let mut param = -qa.dot(&ep_dir) / ep_dir.dot(&ep_dir);
+                let ep_dir = self.direction(1.0);
+                let distance = (p2 - origin).norm();
+                if distance < min_distance.abs() {
+                    min_distance = non_zero_sign(cross(ep_dir, p2 - origin)) * distance;
+                    param = (origin - p1).dot(&ep_dir) / ep_dir.dot(&ep_dir);
+                }
+                for t in solve_cubic(a, b, c, d) {
+                    if t > 0.0 && t < 1.0 {
+                        let qe = qa + 2.0 * t * ab + t * t * br;
+                        let distance = qe.norm();
+                        if distance <= min_distance.abs() {
+                            min_distance = non_zero_sign(cross(ab + t * br, qe)) * distance;
+                            param = t;
+                        }
+                    }
+                }
+                self.finish_curve_distance(origin, min_distance, param)
+            }
+            Self::Cubic([p0, p1, p2, p3]) => {
+                let qa = p0 - origin;
+                let ab = p1 - p0;
+                let br = p2 - p1 - ab;
+                let as_ = (p3 - p2) - (p2 - p1) - br;
+                let ep_dir = self.direction(0.0);
+                let mut min_distance = non_zero_sign(cross(ep_dir, qa)) * qa.norm();
+                let mut param = -qa.dot(&ep_dir) / ep_dir.dot(&ep_dir);
+                let ep_dir = self.direction(1.0);
+                let distance = (p3 - origin).norm();
+                if distance < min_distance.abs() {
+                    min_distance = non_zero_sign(cross(ep_dir, p3 - origin)) * distance;
+                    param = (ep_dir - (p3 - origin)).dot(&ep_dir) / ep_dir.dot(&ep_dir);
+                }
+                for i in 0..=CUBIC_SEARCH_STARTS {
+                    let mut t = i as f64 / CUBIC_SEARCH_STARTS as f64;
+                    let point_at = |t: f64| qa + 3.0 * t * ab + 3.0 * t * t * br + t * t * t * as_;
+                    let mut qe = point_at(t);
+                    for _ in 0..CUBIC_SEARCH_STEPS {
+                        let d1 = 3.0 * ab + 6.0 * t * br + 3.0 * t * t * as_;
+                        let d2 = 6.0 * br + 6.0 * t * as_;
+                        t -= qe.dot(&d1) / (d1.dot(&d1) + qe.dot(&d2));
+                        if t <= 0.0 || t >= 1.0 {
+                            break;
+                        }
+                        qe = point_at(t);
+                        let distance = qe.norm();
+                        if distance < min_distance.abs() {
+                            min_distance = non_zero_sign(cross(d1, qe)) * distance;
+                            param = t;
+                        }
+                    }
+                }
+                self.finish_curve_distance(origin, min_distance, param)
+            }
+        }
+    }
+
+    fn finish_curve_distance(
+        &self,
+        origin: Vector2<f64>,
+        distance: f64,
+        param: f64,
+    ) -> (SignedDistance, f64) {
+        let dot = if (0.0..=1.0).contains(&param) {
+            0.0
+        } else if param < 0.5 {
+            normalize(self.direction(0.0)).dot(&normalize(self.start() - origin)).abs()
+        } else {
+            normalize(self.direction(1.0)).dot(&normalize(self.end() - origin)).abs()
+        };
+        (SignedDistance::new(distance, dot), param)
+    }
+
+    /// Convert the true distance to the pseudo-distance: if the closest point of the segment is
+    /// its endpoint, the distance to the segment's tangent line at that endpoint is used instead,
+    /// provided it is closer.
+    pub fn distance_to_pseudo_distance(
+        &self,
+        distance: &mut SignedDistance,
+        origin: Vector2<f64>,
+        param: f64,
+    ) {
+        let endpoint = if param < 0.0 {
+            Some((normalize(self.direction(0.0)), origin - self.start(), -1.0))
+        } else if param > 1.0 {
+            Some((normalize(self.direction(1.0)), origin - self.end(), 1.0))
+        } else {
+            None
+        };
+        if let Some((dir, to_origin, side)) = endpoint {
+            let ts = to_origin.dot(&dir);
+            if ts * side > 0.0 {
+                let pseudo_distance = cross(to_origin, dir);
+                if pseudo_distance.abs() <= distance.distance.abs() {
+                    distance.distance = pseudo_distance;
+                    distance.dot = 0.0;
+                }
+            }
+        }
+    }
+}
+
+
+
+// ============
+// === Math ===
+// ============
+
+/// Linear interpolation between `a` and `b`.
+pub fn mix(a: Vector2<f64>, b: Vector2<f64>, t: f64) -> Vector2<f64> {
+    (1.0 - t) * a + t * b
+}
+
+/// The z-component of the cross product of two 2D vectors.
+pub fn cross(a: Vector2<f64>, b: Vector2<f64>) -> f64 {
+    a.x * b.y - a.y * b.x
+}
+
+/// Normalize the vector. A zero vector is normalized to `(0, 1)`, as in msdfgen.
+pub fn normalize(v: Vector2<f64>) -> Vector2<f64> {
+    let length = v.norm();
+    if length == 0.0 {
+        Vector2(0.0, 1.0)
+    } else {
+        v / length
+    }
+}
+
+/// Normalize the vector, leaving the zero vector unchanged.
+pub fn normalize_allow_zero(v: Vector2<f64>) -> Vector2<f64> {
+    let length = v.norm();
+    if length == 0.0 {
+        Vector2(0.0, 0.0)
+    } else {
+        v / length
+    }
+}
+
+/// The unit vector orthogonal to `v`, pointing to its right side.
+fn orthonormal(v: Vector2<f64>) -> Vector2<f64> {
+    let length = v.norm();
+    if length == 0.0 {
+        Vector2(0.0, -1.0)
+    } else {
+        Vector2(v.y / length, -v.x / length)
+    }
+}
+
+/// `1.0` for positive numbers, `-1.0` otherwise.
+pub fn non_zero_sign(x: f64) -> f64 {
+    if x > 0.0 {
+        1.0
+    } else {
+        -1.0
+    }
+}
+
+fn is_zero(v: Vector2<f64>) -> bool {
+    v.x == 0.0 && v.y == 0.0
+}
+
+/// Real solutions of `a*x^2 + b*x + c = 0`. An equation satisfied by every `x` has no reported
+/// solutions.
+pub fn solve_quadratic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    if a == 0.0 || b.abs() > 1e12 * a.abs() {
+        if b == 0.0 {
+            return default();
+        }
+        return vec![-c / b];
+    }
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant > 0.0 {
+        let discriminant = discriminant.sqrt();
+        vec![(-b + discriminant) / (2.0 * a), (-b - discriminant) / (2.0 * a)]
+    } else if discriminant == 0.0 {
+        vec![-b / (2.0 * a)]
+    } else {
+        default()
+    }
+}
+
+/// Real solutions of `x^3 + a*x^2 + b*x + c = 0`.
+fn solve_cubic_normed(a: f64, b: f64, c: f64) -> Vec<f64> {
+    let a2 = a * a;
+    let q = (a2 - 3.0 * b) / 9.0;
+    let r = (a * (2.0 * a2 - 9.0 * b) + 27.0 * c) / 54.0;
+    let r2 = r * r;
+    let q3 = q * q * q;
+    let a = a / 3.0;
+    if r2 < q3 {
+        let t = (r / q3.sqrt()).clamp(-1.0, 1.0).acos();
+        let q = -2.0 * q.sqrt();
+        let tau = 2.0 * std::f64::consts::PI;
+        vec![
+            q * (t / 3.0).cos() - a,
+            q * ((t + tau) / 3.0).cos() - a,
+            q * ((t - tau) / 3.0).cos() - a,
+        ]
+    } else {
+        let u = if r < 0.0 { 1.0 } else { -1.0 } * (r.abs() + (r2 - q3).sqrt()).powf(1.0 / 3.0);
+        let v = if u == 0.0 { 0.0 } else { q / u };
+        let first = (u + v) - a;
+        if u == v || (u - v).abs() < 1e-12 * (u + v).abs() {
+            vec![first, -0.5 * (u + v) - a]
+        } else {
+            vec![first]
+        }
+    }
+}
+
+/// Real solutions of `a*x^3 + b*x^2 + c*x + d = 0`.
+pub fn solve_cubic(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    if a != 0.0 {
+        let bn = b / a;
+        // Above this ratio, the numerical error gets larger than if we treated `a` as zero.
+        if bn.abs() < 1e6 {
+            return solve_cubic_normed(bn, c / a, d / a);
+        }
+    }
+    solve_quadratic(b, c, d)
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn linear_distance_is_positive_on_the_right_side() {
+        let segment = Segment::Linear([Vector2(0.0, 0.0), Vector2(0.0, 2.0)]);
+        let (distance, param) = segment.signed_distance(Vector2(1.0, 1.0));
+        assert_eq!(distance, SignedDistance::new(1.0, 0.0));
+        assert_eq!(param, 0.5);
+        let (distance, _) = segment.signed_distance(Vector2(-0.5, 1.0));
+        assert_eq!(distance.distance, -0.5);
+    }
+
+    #[test]
+    fn pseudo_distance_extends_the_segment() {
+        let segment = Segment::Linear([Vector2(0.0, 0.0), Vector2(0.0, 2.0)]);
+        let origin = Vector2(1.0, 3.0);
+        let (mut distance, param) = segment.signed_distance(origin);
+        assert_approx_eq!(distance.distance, 2.0_f64.sqrt());
+        assert!(param > 1.0);
+        segment.distance_to_pseudo_distance(&mut distance, origin, param);
+        assert_eq!(distance.distance, 1.0);
+    }
+
+    #[test]
+    fn curve_distances() {
+        let quadratic =
+            Segment::Quadratic([Vector2(-1.0, 0.0), Vector2(0.0, 2.0), Vector2(1.0, 0.0)]);
+        let (distance, _) = quadratic.signed_distance(Vector2(0.0, 0.0));
+        assert_approx_eq!(distance.distance, 3.0_f64.sqrt() / 2.0, 1e-9);
+        let cubic = Segment::Cubic([
+            Vector2(0.0, 0.0),
+            Vector2(1.0, 0.0),
+            Vector2(2.0, 0.0),
+            Vector2(3.0, 0.0),
+        ]);
+        let (distance, param) = cubic.signed_distance(Vector2(1.5, -2.0));
+        assert_approx_eq!(distance.distance, 2.0, 1e-9);
+        assert_approx_eq!(param, 0.5, 1e-9);
+    }
+
+    #[test]
+    fn split_in_thirds_preserves_the_curve() {
+        let segments = [
+            Segment::Linear([Vector2(0.0, 0.0), Vector2(3.0, 1.0)]),
+            Segment::Quadratic([Vector2(0.0, 0.0), Vector2(1.0, 3.0), Vector2(3.0, 0.0)]),
+            Segment::Cubic([
+                Vector2(0.0, 0.0),
+                Vector2(1.0, 3.0),
+                Vector2(2.0, -3.0),
+                Vector2(3.0, 0.0),
+            ]),
+        ];
+        for segment in segments {
+            let parts = segment.split_in_thirds();
+            for (i, part) in parts.iter().enumerate() {
+                for step in 0..=4 {
+                    let t = step as f64 / 4.0;
+                    let expected = segment.point((i as f64 + t) / 3.0);
+                    assert_approx_eq!((part.point(t) - expected).norm(), 0.0, 1e-9);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn equation_solutions() {
+        assert_eq!(solve_quadratic(1.0, -3.0, 2.0), vec![2.0, 1.0]);
+        assert_eq!(solve_quadratic(0.0, 2.0, -1.0), vec![0.5]);
+        assert!(solve_quadratic(0.0, 0.0, 0.0).is_empty());
+        let mut roots = solve_cubic(1.0, -6.0, 11.0, -6.0);
+        roots.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        for (root, expected) in roots.iter().zip([1.0, 2.0, 3.0]) {
+            assert_approx_eq!(*root, expected, 1e-9);
+        }
+    }
+}
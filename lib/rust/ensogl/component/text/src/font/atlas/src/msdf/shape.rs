@@ -0,0 +1,363 @@
+//! Glyph outlines, loaded from a font face and prepared for the MSDF generation.
+
+use crate::prelude::*;
+
+use crate::msdf::segment::cross;
+use crate::msdf::segment::normalize;
+use crate::msdf::segment::EdgeColor;
+use crate::msdf::segment::Segment;
+
+use owned_ttf_parser as ttf;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The font coordinates are divided by this factor, as msdfgen reads outlines through FreeType in
+/// the 26.6 fixed-point format without scaling them.
+pub const FONT_UNITS_DIVISOR: f64 = 64.0;
+
+
+
+// ============
+// === Edge ===
+// ============
+
+/// A colored segment of a contour.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[allow(missing_docs)]
+pub struct Edge {
+    pub segment: Segment,
+    pub color:   EdgeColor,
+}
+
+impl From<Segment> for Edge {
+    fn from(segment: Segment) -> Self {
+        let color = EdgeColor::WHITE;
+        Self { segment, color }
+    }
+}
+
+
+
+// ===============
+// === Contour ===
+// ===============
+
+/// A closed sequence of edges.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[allow(missing_docs)]
+pub struct Contour {
+    pub edges: Vec<Edge>,
+}
+
+impl Contour {
+    /// The orientation of the contour: `1` for clockwise ones (the orientation of filled TrueType
+    /// contours), `-1` for counter-clockwise ones, and `0` for degenerate ones.
+    pub fn winding(&self) -> i32 {
+        let shoelace = |a: Vector2<f64>, b: Vector2<f64>| (b.x - a.x) * (a.y + b.y);
+        let points: Vec<Vector2<f64>> = match self.edges.as_slice() {
+            [] => return 0,
+            [edge] => {
+                let s = &edge.segment;
+                vec![s.point(0.0), s.point(1.0 / 3.0), s.point(2.0 / 3.0)]
+            }
+            [first, second] => {
+                let (a, b) = (&first.segment, &second.segment);
+                vec![a.point(0.0), a.point(0.5), b.point(0.0), b.point(0.5)]
+            }
+            edges => edges.iter().map(|edge| edge.segment.start()).collect(),
+        };
+        let last = points[points.len() - 1];
+        let mut total = 0.0;
+        let mut prev = last;
+        for point in points {
+            total += shoelace(prev, point);
+            prev = point;
+        }
+        if total > 0.0 {
+            1
+        } else if total < 0.0 {
+            -1
+        } else {
+            0
+        }
+    }
+}
+
+
+
+// =============
+// === Shape ===
+// =============
+
+/// A glyph outline, in font units divided by [`FONT_UNITS_DIVISOR`].
+#[derive(Clone, Debug, Default, PartialEq)]
+#[allow(missing_docs)]
+pub struct Shape {
+    pub contours: Vec<Contour>,
+}
+
+impl Shape {
+    /// Load the outline of the glyph. Returns the shape and the horizontal advance of the glyph. A
+    /// glyph without an outline (like space) results in an empty shape.
+    pub fn load(face: &ttf::Face, glyph_id: ttf::GlyphId) -> (Self, f64) {
+        let mut builder = ShapeBuilder::default();
+        face.outline_glyph(glyph_id, &mut builder);
+        let advance = face.glyph_hor_advance(glyph_id).unwrap_or_default();
+        (builder.finish(), advance as f64 / FONT_UNITS_DIVISOR)
+    }
+
+    /// Split contours consisting of a single edge, so every contour has at least three edges to
+    /// be colored with.
+    pub fn normalize(&mut self) {
+        for contour in &mut self.contours {
+            if let [edge] = contour.edges.as_slice() {
+                let color = edge.color;
+                let parts = edge.segment.split_in_thirds();
+                contour.edges = parts.into_iter().map(|segment| Edge { segment, color }).collect();
+            }
+        }
+    }
+
+    /// The `(min, max)` corners of the bounding box of the shape, or [`None`] if the shape is
+    /// empty.
+    pub fn bounds(&self) -> Option<(Vector2<f64>, Vector2<f64>)> {
+        let large = f64::MAX;
+        let mut bounds = (Vector2(large, large), Vector2(-large, -large));
+        let edges = self.contours.iter().flat_map(|contour| &contour.edges);
+        let mut is_empty = true;
+        for edge in edges {
+            edge.segment.bound(&mut bounds);
+            is_empty = false;
+        }
+        (!is_empty).then_some(bounds)
+    }
+
+    /// Assign colors to edges, so that the two edges meeting at every sharp corner share exactly
+    /// one channel. The `angle_threshold` is the maximum angle (in radians) between edges meeting
+    /// at a corner to be considered sharp. This is the "simple" coloring strategy of msdfgen.
+    pub fn color_edges(&mut self, angle_threshold: f64) {
+        let cross_threshold = angle_threshold.sin();
+        let mut seed = 0;
+        for contour in &mut self.contours {
+            let corners = find_corners(contour, cross_threshold);
+            match corners.as_slice() {
+                [] =>
+                    for edge in &mut contour.edges {
+                        edge.color = EdgeColor::WHITE;
+                    },
+                [corner] => color_teardrop(contour, *corner, &mut seed),
+                corners => {
+                    let corner_count = corners.len();
+                    let start = corners[0];
+                    let edge_count = contour.edges.len();
+                    let mut spline = 0;
+                    let mut color = EdgeColor::WHITE;
+                    switch_color(&mut color, &mut seed, EdgeColor::BLACK);
+                    let initial_color = color;
+                    for i in 0..edge_count {
+                        let index = (start + i) % edge_count;
+                        if spline + 1 < corner_count && corners[spline + 1] == index {
+                            spline += 1;
+                            let is_last = spline == corner_count - 1;
+                            let banned = if is_last { initial_color } else { EdgeColor::BLACK };
+                            switch_color(&mut color, &mut seed, banned);
+                        }
+                        contour.edges[index].color = color;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Indices of edges which start at a sharp corner.
+fn find_corners(contour: &Contour, cross_threshold: f64) -> Vec<usize> {
+    let is_corner =
+        |a: Vector2<f64>, b: Vector2<f64>| a.dot(&b) <= 0.0 || cross(a, b).abs() > cross_threshold;
+    let mut corners = Vec::new();
+    if let Some(last) = contour.edges.last() {
+        let mut prev_direction = last.segment.direction(1.0);
+        for (index, edge) in contour.edges.iter().enumerate() {
+            let direction = edge.segment.direction(0.0);
+            if is_corner(normalize(prev_direction), normalize(direction)) {
+                corners.push(index);
+            }
+            prev_direction = edge.segment.direction(1.0);
+        }
+    }
+    corners
+}
+
+/// Color a contour with a single corner. The color changes twice along the contour, so contours of
+/// fewer than three edges have their edges split.
+fn color_teardrop(contour: &mut Contour, corner: usize, seed: &mut u64) {
+    let mut colors = [EdgeColor::WHITE, EdgeColor::WHITE, EdgeColor::BLACK];
+    switch_color(&mut colors[0], seed, EdgeColor::BLACK);
+    colors[2] = colors[0];
+    switch_color(&mut colors[2], seed, EdgeColor::BLACK);
+    let edge_count = contour.edges.len();
+    if edge_count >= 3 {
+        for i in 0..edge_count {
+            let color_index = (3.0 + 2.875 * i as f64 / (edge_count - 1) as f64 - 1.4375 + 0.5)
+                .floor() as usize
+                - 2;
+            contour.edges[(corner + i) % edge_count].color = colors[color_index];
+        }
+    } else if edge_count >= 1 {
+        let mut parts: Vec<Option<Segment>> = vec![None; 6];
+        for (i, part) in contour.edges[0].segment.split_in_thirds().into_iter().enumerate() {
+            parts[i + 3 * corner] = Some(part);
+        }
+        let part_colors: &[EdgeColor] = if edge_count >= 2 {
+            for (i, part) in contour.edges[1].segment.split_in_thirds().into_iter().enumerate() {
+                parts[i + 3 - 3 * corner] = Some(part);
+            }
+            &[colors[0], colors[0], colors[1], colors[1], colors[2], colors[2]]
+        } else {
+            &colors
+        };
+        let parts = parts.into_iter().flatten();
+        let edges = parts.zip(part_colors).map(|(segment, &color)| Edge { segment, color });
+        contour.edges = edges.collect();
+    }
+}
+
+/// Switch to the next two-channel color, avoiding the `banned` one.
+fn switch_color(color: &mut EdgeColor, seed: &mut u64, banned: EdgeColor) {
+    let combined = EdgeColor::from_bits(color.bits() & banned.bits());
+    if EdgeColor::CHANNELS.contains(&combined) {
+        *color = EdgeColor::from_bits(combined.bits() ^ EdgeColor::WHITE.bits());
+    } else if *color == EdgeColor::BLACK || *color == EdgeColor::WHITE {
+        let start = [EdgeColor::CYAN, EdgeColor::MAGENTA, EdgeColor::YELLOW];
+        *color = start[(*seed % 3) as usize];
+        *seed /= 3;
+    } else {
+        let shifted = color.bits() << (1 + (*seed & 1));
+        *color = EdgeColor::from_bits(shifted | shifted >> 3);
+        *seed >>= 1;
+    }
+}
+
+
+
+// ====================
+// === ShapeBuilder ===
+// ====================
+
+/// Collects the glyph outline, mimicking how msdfgen decomposes FreeType outlines.
+#[derive(Debug, Default)]
+struct ShapeBuilder {
+    shape:    Shape,
+    start:    Vector2<f64>,
+    position: Vector2<f64>,
+}
+
+impl ShapeBuilder {
+    fn point(x: f32, y: f32) -> Vector2<f64> {
+        Vector2(x as f64, y as f64) / FONT_UNITS_DIVISOR
+    }
+
+    fn add_segment(&mut self, segment: Segment) {
+        if let Some(contour) = self.shape.contours.last_mut() {
+            contour.edges.push(segment.into());
+        }
+        self.position = segment.end();
+    }
+
+    fn finish(mut self) -> Shape {
+        self.shape.contours.retain(|contour| !contour.edges.is_empty());
+        self.shape
+    }
+}
+
+impl ttf::OutlineBuilder for ShapeBuilder {
+    fn move_to(&mut self, x: f32, y: f32) {
+        let is_current_empty = self.shape.contours.last().map_or(false, |c| c.edges.is_empty());
+        if !is_current_empty {
+            self.shape.contours.push(default());
+        }
+        self.start = Self::point(x, y);
+        self.position = self.start;
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        let end = Self::point(x, y);
+        if end != self.position {
+            self.add_segment(Segment::Linear([self.position, end]));
+        }
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let control = Self::point(x1, y1);
+        self.add_segment(Segment::Quadratic([self.position, control, Self::point(x, y)]));
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let control1 = Self::point(x1, y1);
+        let control2 = Self::point(x2, y2);
+        let end = Self::point(x, y);
+        self.add_segment(Segment::Cubic([self.position, control1, control2, end]));
+    }
+
+    fn close(&mut self) {
+        // FreeType closes every contour with a line segment, which is skipped if degenerate.
+        if self.start != self.position {
+            self.add_segment(Segment::Linear([self.position, self.start]));
+        }
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn polygon(points: &[(f64, f64)]) -> Contour {
+        let points = points.iter().map(|&(x, y)| Vector2(x, y)).collect_vec();
+        let next = points.iter().cycle().skip(1);
+        let edges = points.iter().zip(next).map(|(&a, &b)| Segment::Linear([a, b]).into());
+        Contour { edges: edges.collect() }
+    }
+
+    #[test]
+    fn winding_of_polygons() {
+        let clockwise = polygon(&[(0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (1.0, 0.0)]);
+        let counter_clockwise = polygon(&[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]);
+        assert_eq!(clockwise.winding(), 1);
+        assert_eq!(counter_clockwise.winding(), -1);
+    }
+
+    #[test]
+    fn corners_of_a_square_get_distinct_colors() {
+        let mut shape =
+            Shape { contours: vec![polygon(&[(0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (1.0, 0.0)])] };
+        shape.color_edges(3.0);
+        let edges = &shape.contours[0].edges;
+        for (edge, next) in edges.iter().zip(edges.iter().cycle().skip(1)) {
+            let shared = edge.color.bits() & next.color.bits();
+            assert!(EdgeColor::CHANNELS.contains(&EdgeColor::from_bits(shared)));
+        }
+    }
+
+    #[test]
+    fn single_edge_contours_are_split() {
+        let quadratic =
+            Segment::Quadratic([Vector2(0.0, 0.0), Vector2(1.0, 1.0), Vector2(0.0, 0.0)]);
+        let mut shape = Shape { contours: vec![Contour { edges: vec![quadratic.into()] }] };
+        shape.normalize();
+        assert_eq!(shape.contours[0].edges.len(), 3);
+        let (min, max) = shape.bounds().unwrap();
+        assert_eq!(min, Vector2(0.0, 0.0));
+        assert_approx_eq!(max.x, 0.5, 1e-9);
+        assert_approx_eq!(max.y, 0.5, 1e-9);
+    }
+}
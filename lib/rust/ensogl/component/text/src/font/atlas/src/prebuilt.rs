@@ -0,0 +1,296 @@
+//! Pre-built glyph atlases of the embedded fonts.
+//!
+//! Generating MSDF data for glyphs is expensive, so the atlases of the most commonly used glyphs
+//! are built ahead of time and loaded at application startup. The atlases are built from the
+//! embedded font data with the native MSDF generator (see [`crate::msdf`]), so they can be built
+//! both by the running application, and by the build tools. Both ways produce the same files,
+//! described by [`ATLAS_FILE`] and [`METADATA_FILE`].
+
+use crate::prelude::*;
+
+use crate::msdf::Msdf;
+use crate::GlyphRenderInfo;
+
+use enso_font::FontFamily;
+use enso_font::NonVariableFaceHeader;
+use enso_font::Style;
+use enso_font::Weight;
+use enso_font::Width;
+use ensogl_text_embedded_fonts::Embedded;
+use owned_ttf_parser as ttf;
+use owned_ttf_parser::GlyphId;
+use std::collections::hash_map::Entry;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The printable characters in the ASCII subset of Unicode. This is the same as the set of keys
+/// on a US-ANSI keyboard.
+const ASCII_PRINTABLE_CHARS: &str = concat!(
+    " !\"#$%&'()*+,-./0123456789:;<=>?@ABCDEFGHIJKLMNOPQRSTUVWXYZ",
+    "[\\]^_`abcdefghijklmnopqrstuvwxyz{|}~"
+);
+
+/// The glyphs to include in the pre-built atlas loaded at application startup.
+const PRELOAD_GLYPHS: &[&str] = &[ASCII_PRINTABLE_CHARS];
+
+/// The variations to be pre-built for each glyph, for each typeface.
+const PRELOAD_VARIATIONS: &[NonVariableFaceHeader] = &[
+    NonVariableFaceHeader::new(Width::Normal, Weight::Normal, Style::Normal),
+    NonVariableFaceHeader::new(Width::Normal, Weight::Medium, Style::Normal),
+    NonVariableFaceHeader::new(Width::Normal, Weight::Bold, Style::Normal),
+    NonVariableFaceHeader::new(Width::Normal, Weight::ExtraBold, Style::Normal),
+];
+
+/// The typefaces for which atlases should be pre-built: the default code and text fonts.
+pub const PRELOAD_TYPEFACES: &[&str] = &["enso", "mplus1p"];
+
+/// Path within the asset directory to store the glyph atlas image.
+pub const ATLAS_FILE: &str = "atlas.ppm";
+/// Path within the asset directory to store the glyph metadata.
+pub const METADATA_FILE: &str = "metadata.json";
+
+/// TTF files can contain multiple face definitions. We support only the first defined, just as
+/// most web browsers (you cannot define `@font-face` in CSS for multiple faces of the same file).
+const TTF_FONT_FACE_INDEX: u32 = 0;
+
+
+
+// =====================
+// === CacheSnapshot ===
+// =====================
+
+/// Render information of the glyphs of a non-variable font, by the face variation.
+pub type GlyphCache = HashMap<NonVariableFaceHeader, HashMap<GlyphId, GlyphRenderInfo>>;
+
+/// Cached rendering information for a font.
+#[derive(Debug)]
+pub struct CacheSnapshot {
+    /// The MSDF atlas pixel data.
+    pub atlas:  enso_bitmap::Image,
+    /// Index of glyphs found in [`atlas`], serialized as the [`METADATA_FILE`].
+    pub glyphs: String,
+}
+
+impl CacheSnapshot {
+    /// Constructor.
+    pub fn new(atlas: enso_bitmap::Image, glyphs: &GlyphCache) -> Self {
+        let cache: HashMap<String, _> = glyphs
+            .iter()
+            .map(|(variation, info)| {
+                let glyphs: HashMap<String, GlyphRenderInfo> =
+                    info.iter().map(|(id, data)| (id.0.to_string(), *data)).collect();
+                (serialize_variation(variation), glyphs)
+            })
+            .collect();
+        let glyphs = serde_json::to_string(&cache);
+        // Serialization can only fail if the types are not serializable to JSON, so this will
+        // either succeed consistently or fail consistently. [`unwrap`] it so if it gets broken,
+        // we'll catch it.
+        let glyphs = glyphs.unwrap();
+        Self { atlas, glyphs }
+    }
+
+    /// Deserialize the index of glyphs found in the atlas.
+    pub fn glyph_cache(&self) -> anyhow::Result<GlyphCache> {
+        let cache: HashMap<String, HashMap<String, GlyphRenderInfo>> =
+            serde_json::from_str(&self.glyphs)?;
+        cache
+            .into_iter()
+            .map(|(variation, info)| {
+                let glyphs = info
+                    .into_iter()
+                    .map(|(id, data)| Ok((GlyphId(id.parse()?), data)))
+                    .collect::<anyhow::Result<_>>()?;
+                Ok((deserialize_variation(&variation)?, glyphs))
+            })
+            .collect()
+    }
+}
+
+
+// === Serialization Helpers, Because `ttf_parser` Doesn't `derive` Them ===
+
+fn serialize_variation(variation: &NonVariableFaceHeader) -> String {
+    let width = match variation.width {
+        Width::UltraCondensed => "UltraCondensed",
+        Width::ExtraCondensed => "ExtraCondensed",
+        Width::Condensed => "Condensed",
+        Width::SemiCondensed => "SemiCondensed",
+        Width::Normal => "Normal",
+        Width::SemiExpanded => "SemiExpanded",
+        Width::Expanded => "Expanded",
+        Width::ExtraExpanded => "ExtraExpanded",
+        Width::UltraExpanded => "UltraExpanded",
+    };
+    let weight = variation.weight.to_number().to_string();
+    let style = match variation.style {
+        Style::Normal => "Normal",
+        Style::Italic => "Italic",
+        Style::Oblique => "Oblique",
+    };
+    format!("{width}-{weight}-{style}")
+}
+
+fn deserialize_variation(variation: &str) -> anyhow::Result<NonVariableFaceHeader> {
+    let mut parts = variation.splitn(3, '-');
+    let bad_variation = || anyhow!("Malformed variation specifier: {variation}");
+    let width = match parts.next().ok_or_else(bad_variation)? {
+        "UltraCondensed" => Width::UltraCondensed,
+        "ExtraCondensed" => Width::ExtraCondensed,
+        "Condensed" => Width::Condensed,
+        "SemiCondensed" => Width::SemiCondensed,
+        "Normal" => Width::Normal,
+        "SemiExpanded" => Width::SemiExpanded,
+        "Expanded" => Width::Expanded,
+        "ExtraExpanded" => Width::ExtraExpanded,
+        "UltraExpanded" => Width::UltraExpanded,
+        width => anyhow::bail!("Unexpected font width: `{width}`."),
+    };
+    let weight = Weight::from(parts.next().ok_or_else(bad_variation)?.parse::<u16>()?);
+    let style = match parts.next().ok_or_else(bad_variation)? {
+        "Normal" => Style::Normal,
+        "Italic" => Style::Italic,
+        "Oblique" => Style::Oblique,
+        style => anyhow::bail!("Unexpected font style: `{style}`."),
+    };
+    Ok(NonVariableFaceHeader { width, weight, style })
+}
+
+
+
+// ====================
+// === AtlasBuilder ===
+// ====================
+
+/// An atlas being filled with the MSDFs of glyphs, in the layout of the application's MSDF
+/// texture.
+#[derive(Debug, Default)]
+struct AtlasBuilder {
+    data:   Vec<u8>,
+    glyphs: GlyphCache,
+}
+
+impl AtlasBuilder {
+    /// Add the glyph of the face variation to the atlas, unless it is already there.
+    fn add_glyph(&mut self, variation: NonVariableFaceHeader, face: &ttf::Face, id: GlyphId) {
+        let glyphs = self.glyphs.entry(variation).or_default();
+        if let Entry::Vacant(entry) = glyphs.entry(id) {
+            let params = GlyphRenderInfo::MSDF_PARAMS;
+            let msdf = Msdf::generate_by_index(face, id.0 as usize, &params);
+            let index = (self.data.len() / crate::GLYPH_SIZE) as u32;
+            self.data.extend(msdf.data.iter().copied().map(crate::cell_from_msdf_value));
+            let info = GlyphRenderInfo::new(index, msdf.advance, msdf.translation, msdf.scale);
+            entry.insert(info);
+        }
+    }
+
+    fn into_snapshot(self) -> CacheSnapshot {
+        let width = crate::GLYPH_WIDTH;
+        let height = self.data.len() / crate::ROW_SIZE;
+        let atlas = enso_bitmap::Image { width, height, data: self.data };
+        CacheSnapshot::new(atlas, &self.glyphs)
+    }
+}
+
+
+
+// ================
+// === Building ===
+// ================
+
+/// Generate MSDF data for the commonly used glyphs of an embedded font.
+pub fn build_atlas(name: &str) -> anyhow::Result<CacheSnapshot> {
+    build_atlas_of_glyphs(name, PRELOAD_VARIATIONS, PRELOAD_GLYPHS)
+}
+
+#[profile(Debug)]
+fn build_atlas_of_glyphs(
+    name: &str,
+    variations: &[NonVariableFaceHeader],
+    glyphs: &[&str],
+) -> anyhow::Result<CacheSnapshot> {
+    let fonts = Embedded::default();
+    let definition = fonts.definitions.get(&name.into());
+    let definition = match definition.ok_or_else(|| anyhow!("Failed to load font."))? {
+        FontFamily::NonVariable(definition) => definition,
+        FontFamily::Variable(_) =>
+            return Err(anyhow!("Atlas cache pre-seeding for variable fonts is not supported.",)),
+    };
+    let mut atlas = AtlasBuilder::default();
+    for &variation in variations {
+        let Some(face) = definition.get(variation) else {
+            warn!("Failed to load specified variation for font `{name}`: {variation:?}.");
+            continue;
+        };
+        let data = fonts.data.get(face.file);
+        let data = data.ok_or_else(|| anyhow!("Font '{}' not found", face.file))?;
+        let ttf_face = ttf::Face::from_slice(data, TTF_FONT_FACE_INDEX)?;
+        // This is safe. Unwrap should be removed after rustybuzz is fixed:
+        // https://github.com/RazrFalcon/rustybuzz/issues/52
+        let buzz_face = rustybuzz::Face::from_face(ttf_face.clone()).unwrap();
+        for glyphs in glyphs {
+            let mut buffer = rustybuzz::UnicodeBuffer::new();
+            buffer.push_str(glyphs);
+            let shaped = rustybuzz::shape(&buzz_face, &[], buffer);
+            for info in shaped.glyph_infos() {
+                atlas.add_glyph(variation, &ttf_face, GlyphId(info.glyph_id as u16));
+            }
+        }
+        let unknown_glyph = GlyphId::default();
+        atlas.add_glyph(variation, &ttf_face, unknown_glyph);
+    }
+    Ok(atlas.into_snapshot())
+}
+
+/// Generate the asset files of the pre-built atlas of an embedded font: the [`ATLAS_FILE`] and
+/// the [`METADATA_FILE`].
+pub fn build_atlas_files(name: &str) -> anyhow::Result<HashMap<&'static str, Vec<u8>>> {
+    let cache = build_atlas(name)?;
+    let atlas = cache.atlas.encode_ppm();
+    let metadata = cache.glyphs.into_bytes();
+    Ok([(ATLAS_FILE, atlas), (METADATA_FILE, metadata)].into_iter().collect())
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn atlases_are_built_natively() {
+        let name = "mplus1p";
+        let variations = &PRELOAD_VARIATIONS[..1];
+        let glyphs = "Ab";
+        let cache = build_atlas_of_glyphs(name, variations, &[glyphs]).unwrap();
+        // The requested glyphs and the unknown glyph.
+        let glyph_count = glyphs.len() + 1;
+        assert_eq!(cache.atlas.width, crate::GLYPH_WIDTH);
+        assert_eq!(cache.atlas.height, glyph_count * crate::GLYPH_HEIGHT);
+        // The glyph edges have values around the middle of the distance range.
+        assert!(cache.atlas.data.iter().any(|&value| value > 0 && value < 255));
+        let glyph_cache = cache.glyph_cache().unwrap();
+        let variation_glyphs = &glyph_cache[&PRELOAD_VARIATIONS[0]];
+        assert_eq!(variation_glyphs.len(), glyph_count);
+        let info = variation_glyphs[&GlyphId::default()];
+        assert_eq!(info.msdf_texture_glyph_id, glyphs.len() as u32);
+        assert!(info.advance > 0.0);
+    }
+
+    #[test]
+    fn all_preloaded_typefaces_are_built() {
+        for name in PRELOAD_TYPEFACES {
+            let cache = build_atlas_of_glyphs(name, PRELOAD_VARIATIONS, &["a"]).unwrap();
+            let glyph_cache = cache.glyph_cache().unwrap();
+            assert!(!glyph_cache.is_empty(), "No glyphs built for `{name}`.");
+        }
+    }
+}
@@ -22,6 +22,16 @@ use std::fmt::Write as FmtWrite;
 /// The name of the Rust source file that will be generated from the downloaded font data.
 const GENERATED_SOURCE_FILE_NAME: &str = "embedded_fonts_data.rs";
 
+/// The default font of the text. It also renders the CJK glyphs missing in the Enso Font.
+const MPLUS_FONT: &str = "mplus1p";
+
+/// The font rendering symbols missing in other fonts, like chess pieces or Braille patterns.
+const SYMBOLS_FONT: &str = "notosanssymbols2";
+
+/// The font rendering emoji. It is a monochrome font, as the glyphs are rendered from their
+/// outlines; color emoji fonts store glyphs as bitmaps or color layers instead.
+const EMOJI_FONT: &str = "notoemoji";
+
 
 
 // =============
@@ -47,6 +57,7 @@ pub struct CodeGenerator {
     embeds:      String,
     definitions: String,
     features:    String,
+    fallbacks:   String,
 }
 
 impl CodeGenerator {
@@ -85,6 +96,11 @@ impl CodeGenerator {
         ln!(1, &mut self.features, "features.insert(\"{family}\".into(), {features:?}.to_vec());");
     }
 
+    fn add_font_fallbacks(&mut self, family: &str, fallbacks: &[&str]) {
+        let fallbacks = format!("{fallbacks:?}.to_vec()");
+        ln!(1, &mut self.fallbacks, "fallbacks.insert(\"{family}\".into(), {fallbacks});");
+    }
+
     fn body(&self) -> String {
         let mut body = String::new();
         ln!(0, body, "/// Mapping between file name and embedded fonts data.");
@@ -108,6 +124,13 @@ impl CodeGenerator {
         write!(body, "{}", self.features).ok();
         ln!(1, body, "features");
         ln!(0, body, "}}");
+        ln!(0, body, "/// Fallback families used to render glyphs missing in embedded families.");
+        ln!(0, body, "pub fn embedded_family_fallbacks()");
+        ln!(0, body, "-> HashMap<family::Name, Vec<&'static str>> {{");
+        ln!(1, body, "let mut fallbacks = HashMap::new();");
+        write!(body, "{}", self.fallbacks).ok();
+        ln!(1, body, "fallbacks");
+        ln!(0, body, "}}");
         body
     }
 }
@@ -129,9 +152,10 @@ pub async fn load_enso_font(out_dir: impl AsRef<Path>, code_gen: &mut CodeGenera
     for file in font_family.files() {
         code_gen.add_font_data(file);
     }
-    // The Enso Font has ligatures, but `ensogl_text` does not support them.
-    let disable_ligatures = format!("-{}", enso_enso_font::feature::LIGATURES);
-    code_gen.add_font_features(family_name, &[&disable_ligatures]);
+    code_gen.add_font_features(family_name, &[enso_enso_font::feature::LIGATURES]);
+    // The Enso Font does not define CJK glyphs, they are rendered with the M+ 1p font. Neither of
+    // them defines all symbols and emoji.
+    code_gen.add_font_fallbacks(family_name, &[MPLUS_FONT, SYMBOLS_FONT, EMOJI_FONT]);
     Ok(())
 }
 
@@ -231,7 +255,10 @@ async fn main() -> Result {
     let out_dir = ide_ci::programs::cargo::build_env::OUT_DIR.get()?;
     let mut code_gen = CodeGenerator::default();
 
-    google_fonts::load(&out_dir, &mut code_gen, "mplus1p").await?;
+    google_fonts::load(&out_dir, &mut code_gen, MPLUS_FONT).await?;
+    code_gen.add_font_fallbacks(MPLUS_FONT, &[SYMBOLS_FONT, EMOJI_FONT]);
+    google_fonts::load(&out_dir, &mut code_gen, SYMBOLS_FONT).await?;
+    google_fonts::load(&out_dir, &mut code_gen, EMOJI_FONT).await?;
 
     load_enso_font(&out_dir, &mut code_gen).await?;
 
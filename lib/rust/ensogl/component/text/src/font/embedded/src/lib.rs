@@ -48,6 +48,7 @@ pub struct Embedded {
     pub definitions: HashMap<family::Name, family::FontFamily>,
     pub data:        HashMap<&'static str, &'static [u8]>,
     pub features:    HashMap<family::Name, Vec<rustybuzz::Feature>>,
+    pub fallbacks:   HashMap<family::Name, Vec<family::Name>>,
 }
 
 impl Default for Embedded {
@@ -61,7 +62,11 @@ impl Default for Embedded {
                 (family, feats.into_iter().map(|feat| feat.parse().unwrap()).collect())
             })
             .collect();
-        Self { data, definitions, features }
+        let fallbacks = embedded_family_fallbacks()
+            .into_iter()
+            .map(|(family, fallbacks)| (family, fallbacks.into_iter().map(Into::into).collect()))
+            .collect();
+        Self { data, definitions, features, fallbacks }
     }
 }
 
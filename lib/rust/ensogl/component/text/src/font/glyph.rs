@@ -1,5 +1,6 @@
-//! This module defines glyphs and glyphs systems. All glyphs in a glyph system share the same font,
-//! but can differ in all other aspects.
+//! This module defines glyphs and glyphs systems. All glyphs in a glyph system share the same font
+//! (or one of its fallback fonts, used for characters the font does not define), but can differ in
+//! all other aspects.
 
 use crate::prelude::*;
 
@@ -12,6 +13,7 @@ use crate::SdfWeight;
 use crate::Size;
 
 use enso_text::Byte;
+use enso_text::Column;
 use ensogl_core::data::color;
 use ensogl_core::data::color::Rgba;
 use ensogl_core::display;
@@ -162,6 +164,9 @@ pub struct Glyph {
 pub struct GlyphData {
     pub view:               glyph_shape::View,
     pub line_byte_offset:   Cell<Byte>,
+    /// The column of the first grapheme cluster represented by this glyph. A glyph can represent
+    /// several grapheme clusters, like ligatures do.
+    pub line_column:        Cell<Column>,
     pub x_advance:          Cell<f32>,
     /// Indicates whether this glyph is attached to cursor. Needed for text width computation.
     /// Attached glyphs should not be considered part of the line during animation because they
@@ -334,6 +339,11 @@ impl Glyph {
 // === Glyph Modification ===
 
 impl Glyph {
+    /// The font used to render this glyph.
+    pub fn font(&self) -> FontWithGpuData {
+        self.view.data.borrow().font.clone_ref()
+    }
+
     /// Change the displayed character.
     pub fn set_glyph_id(&self, glyph_id: GlyphId) {
         self.glyph_id.set(glyph_id);
@@ -398,7 +408,9 @@ impl WeakGlyph {
 #[derive(Clone, CloneRef, Debug)]
 #[allow(missing_docs)]
 pub struct System {
-    pub font: FontWithGpuData,
+    pub font:      FontWithGpuData,
+    /// Fonts used to display glyphs missing in [`Self::font`], in the order of preference.
+    pub fallbacks: Rc<Vec<FontWithGpuData>>,
 }
 
 impl System {
@@ -408,17 +420,30 @@ impl System {
         let scene = scene.as_ref();
         let fonts = scene.extension::<font::Registry>();
         let font = fonts.load(font_name);
-        Self { font }
+        let fallbacks = Rc::new(fonts.load_fallbacks(font.name()));
+        Self { font, fallbacks }
+    }
+
+    /// The font and its fallback fonts, in the order of preference.
+    pub fn font_chain(&self) -> impl Iterator<Item = &FontWithGpuData> {
+        iter::once(&self.font).chain(self.fallbacks.iter())
     }
 
     /// Create new glyph. In the returned glyph the further parameters (position,size,character)
     /// may be set.
-    #[profile(Debug)]
     pub fn new_glyph(&self) -> Glyph {
+        self.new_glyph_with_font(&self.font)
+    }
+
+    /// Create new glyph rendered with the provided font, which should be the system's font or one
+    /// of its fallback fonts.
+    #[profile(Debug)]
+    pub fn new_glyph_with_font(&self, font: &FontWithGpuData) -> Glyph {
         let display_object = display::object::Instance::new_no_debug();
-        let font = self.font.clone_ref();
+        let font = font.clone_ref();
         let glyph_id = default();
         let line_byte_offset = default();
+        let line_column = default();
         let properties = default();
         let variations = default();
         let x_advance = default();
@@ -433,6 +458,7 @@ impl System {
                 display_object,
                 glyph_id,
                 line_byte_offset,
+                line_column,
                 properties,
                 variations,
                 x_advance,
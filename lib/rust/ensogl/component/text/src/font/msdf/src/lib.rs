@@ -44,6 +44,8 @@ mod binding;
 pub mod emscripten_data;
 pub mod texture;
 
+pub use ensogl_text_atlas::msdf as native;
+pub use ensogl_text_atlas::MsdfParameters;
 pub use texture::*;
 
 /// Common types.
@@ -181,27 +183,6 @@ impl OwnedFace {
 
 
 
-// ======================
-// === MsdfParameters ===
-// ======================
-
-/// Parameters of MSDF generation.
-///
-/// The structure gathering MSDF generation parameters meant to be same for all rendered glyphs.
-#[derive(Clone, Copy, Debug)]
-#[allow(missing_docs)]
-pub struct MsdfParameters {
-    pub width: usize,
-    pub height: usize,
-    pub edge_coloring_angle_threshold: f64,
-    pub range: f64,
-    pub max_scale: f64,
-    pub edge_threshold: f64,
-    pub overlap_support: bool,
-}
-
-
-
 // ============
 // === Msdf ===
 // ============
@@ -225,7 +206,7 @@ impl Drop for Msdf {
 
 impl Msdf {
     /// Number of used color channels in the MSDF texture.
-    pub const CHANNELS_COUNT: usize = 3;
+    pub const CHANNELS_COUNT: usize = ensogl_text_atlas::CHANNELS_COUNT;
 
     /// Generate Mutlichannel Signed Distance Field (MSDF) for one glyph.
     ///
@@ -316,6 +297,7 @@ impl Msdf {
 mod tests {
     use super::*;
 
+    use ensogl_text_atlas::msdf::reference;
     use ensogl_text_embedded_fonts::Embedded;
     use nalgebra::Vector2;
     use wasm_bindgen_test::wasm_bindgen_test;
@@ -323,15 +305,7 @@ mod tests {
 
     wasm_bindgen_test_configure!(run_in_browser);
 
-    const TEST_PARAMETERS: MsdfParameters = MsdfParameters {
-        width: 32,
-        height: 32,
-        edge_coloring_angle_threshold: 3.0,
-        range: 2.0,
-        max_scale: 2.0,
-        edge_threshold: 1.001,
-        overlap_support: true,
-    };
+    const TEST_PARAMETERS: MsdfParameters = reference::PARAMETERS;
 
     #[derive(Debug, PartialEq)]
     struct OutputValueChecks {
@@ -393,4 +367,62 @@ mod tests {
         let data = msdf.data.iter().collect_vec();
         check_outputs(&msdf, &data);
     }
+
+    /// The checked-in reference output the native generator is tested against must be the output
+    /// of msdfgen. On mismatch, the current output of msdfgen is printed, so the reference can be
+    /// updated.
+    #[wasm_bindgen_test(async)]
+    async fn reference_output_is_produced_by_msdfgen() {
+        initialized().await;
+        let font_base = Embedded::default();
+        let font_data = font_base.data.get(reference::FONT).unwrap();
+        let font = OwnedFace::load_from_memory(font_data).unwrap();
+        for glyph in reference::glyphs() {
+            let msdf = Msdf::generate(&font, glyph.character as u32, &TEST_PARAMETERS);
+            let data = msdf.data.iter().collect_vec();
+            let output = reference::Glyph {
+                character:   glyph.character,
+                advance:     msdf.advance,
+                translation: [msdf.translation.x, msdf.translation.y],
+                scale:       [msdf.scale.x, msdf.scale.y],
+                values:      data.iter().copied().enumerate().collect(),
+            };
+            let sampled_output = reference::Glyph {
+                values: glyph.values.iter().map(|&(index, _)| (index, data[index])).collect(),
+                ..output.clone()
+            };
+            if sampled_output != glyph {
+                let output = serde_json::to_string(&output).unwrap();
+                panic!("The reference differs from the msdfgen output: {output}");
+            }
+        }
+    }
+
+    #[wasm_bindgen_test(async)]
+    async fn native_generator_is_equivalent_to_msdfgen() {
+        initialized().await;
+        let font_base = Embedded::default();
+        let font_data = font_base.data.get(reference::FONT).unwrap();
+        let font = OwnedFace::load_from_memory(font_data).unwrap();
+        let face = owned_ttf_parser::Face::from_slice(font_data, 0).unwrap();
+        let params = TEST_PARAMETERS;
+        for character in "Aag8@&%Q{}~ ".chars() {
+            let msdf = Msdf::generate(&font, character as u32, &params);
+            let native = native::Msdf::generate(&face, character as u32, &params);
+            assert_eq!(msdf.advance, native.advance);
+            assert_eq!(msdf.scale, native.scale);
+            assert!((msdf.translation - native.translation).norm() < 1e-6);
+            let data = msdf.data.iter().collect_vec();
+            assert_eq!(data.len(), native.data.len());
+            let values = data.iter().zip(&native.data);
+            let is_different =
+                |(a, b): &(&f32, &f32)| (**a - **b).abs() > reference::MAX_VALUE_DIFFERENCE;
+            let differing = values.filter(is_different).count();
+            let differing_fraction = differing as f32 / data.len() as f32;
+            assert!(
+                differing_fraction <= reference::MAX_DIFFERING_VALUES_FRACTION,
+                "{differing} MSDF values of {character:?} differ from the ones of msdfgen."
+            );
+        }
+    }
 }
@@ -26,16 +26,16 @@ impl Texture {
     pub const CHANNELS_COUNT: usize = Msdf::CHANNELS_COUNT;
 
     /// Width of single MSDF in cells.
-    pub const WIDTH: usize = 32;
+    pub const WIDTH: usize = ensogl_text_atlas::GLYPH_WIDTH;
 
     /// Size of the texture row.
-    pub const ROW_SIZE: usize = Self::CHANNELS_COUNT * Self::WIDTH;
+    pub const ROW_SIZE: usize = ensogl_text_atlas::ROW_SIZE;
 
     /// Height of single MSDF in cells.
-    pub const ONE_GLYPH_HEIGHT: usize = 32;
+    pub const ONE_GLYPH_HEIGHT: usize = ensogl_text_atlas::GLYPH_HEIGHT;
 
     /// Size of single MSDF.
-    pub const ONE_GLYPH_SIZE: usize = Self::ROW_SIZE * Self::ONE_GLYPH_HEIGHT;
+    pub const ONE_GLYPH_SIZE: usize = ensogl_text_atlas::GLYPH_SIZE;
 
     /// Number of rows in texture
     pub fn rows(&self) -> usize {
@@ -66,18 +66,10 @@ impl Texture {
     #[profile(Debug)]
     pub fn extend_with_raw_data<T: IntoIterator<Item = f32>>(&self, iter: T) {
         let f32_iterator = iter.into_iter();
-        let converted_iterator = f32_iterator.map(Self::f32_to_cell);
+        let converted_iterator = f32_iterator.map(ensogl_text_atlas::cell_from_msdf_value);
         self.data.borrow_mut().extend(converted_iterator);
     }
 
-    fn f32_to_cell(value: f32) -> u8 {
-        const UNSIGNED_BYTE_MIN: f32 = 0.0;
-        const UNSIGNED_BYTE_MAX: f32 = 255.0;
-        let scaled_to_byte = value * UNSIGNED_BYTE_MAX;
-        let clamped_to_byte = scaled_to_byte.clamp(UNSIGNED_BYTE_MIN, UNSIGNED_BYTE_MAX);
-        clamped_to_byte as u8
-    }
-
     /// Get the raw pixel data.
     pub fn to_image(&self) -> enso_bitmap::Image {
         let width = Self::WIDTH;
@@ -106,23 +98,8 @@ impl Texture {
 // === Msdf-sys Values Converting ===
 // ==================================
 
-/// Converts x dimension distance obtained from msdf-sys to vertex-space values
-///
-/// The values obtained from `msdf-sys` are expressed in MSDF cells. This function convert them to
-/// normalized coordinates, where (0.0, 0.0) is initial pen position for an character, and
-/// `y` = 1.0 is _ascender_.
-pub fn x_distance_from_msdf_value(msdf_value: f64) -> f32 {
-    msdf_value as f32 / Texture::WIDTH as f32
-}
-
-/// Converts y dimension distance obtained from msdf-sys to vertex-space values
-///
-/// The values obtained from `msdf-sys` are expressed in MSDF cells. This function convert them to
-/// normalized coordinates, where (0.0, 0.0) is initial pen position for an character, and
-/// `y` = 1.0 is _ascender_.
-pub fn y_distance_from_msdf_value(msdf_value: f64) -> f32 {
-    msdf_value as f32 / Texture::ONE_GLYPH_HEIGHT as f32
-}
+pub use ensogl_text_atlas::x_distance_from_msdf_value;
+pub use ensogl_text_atlas::y_distance_from_msdf_value;
 
 /// Converts translation obtained from msdf-sys to vertex-space values
 ///
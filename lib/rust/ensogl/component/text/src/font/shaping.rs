@@ -0,0 +1,337 @@
+//! Text shaping utilities independent of rendering: shaping text with a chain of fallback fonts,
+//! and mapping shaped glyph clusters back to the caret positions in the text.
+//!
+//! # Fallback fonts
+//! A font does not have to define glyphs for all characters. For example, the Enso font does not
+//! define CJK glyphs. The text is first shaped with the primary font, and every run of clusters
+//! containing missing glyphs is shaped again with the next font of the fallback chain.
+//!
+//! # Clusters
+//! Shaping can produce a single glyph for several characters (ligatures, like `->` in the Enso
+//! font), or several glyphs for a single character (e.g. a base glyph and a combining mark). The
+//! glyphs produced for the same piece of text form a cluster, and all of them have the byte offset
+//! of the piece's beginning assigned. Clusters are assumed to be ordered left to right, as only
+//! left-to-right text is supported.
+
+use crate::prelude::*;
+
+use enso_text::index::*;
+use enso_text::unit::*;
+use enso_text::Rope;
+
+
+
+// =============
+// === Glyph ===
+// =============
+
+/// A glyph produced by shaping. See the [`rustybuzz`] library to learn more about the data stored
+/// in this struct.
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug)]
+pub struct Glyph {
+    pub info:     rustybuzz::GlyphInfo,
+    pub position: rustybuzz::GlyphPosition,
+}
+
+impl Glyph {
+    /// The byte offset of the cluster this glyph belongs to.
+    pub fn cluster(&self) -> Byte {
+        Byte(self.info.cluster as usize)
+    }
+
+    /// Check whether the glyph is the font's "missing glyph" (`.notdef`), used by fonts to display
+    /// characters they do not define.
+    pub fn is_missing(&self) -> bool {
+        self.info.glyph_id == 0
+    }
+}
+
+/// Shape the text with the provided [`rustybuzz`] face.
+pub fn shape(face: &rustybuzz::Face, features: &[rustybuzz::Feature], text: &str) -> Vec<Glyph> {
+    let mut buffer = rustybuzz::UnicodeBuffer::new();
+    buffer.push_str(text);
+    let shaped = rustybuzz::shape(face, features, buffer);
+    let positions = shaped.glyph_positions().iter();
+    let infos = shaped.glyph_infos().iter();
+    positions.zip(infos).map(|(&position, &info)| Glyph { info, position }).collect()
+}
+
+
+
+// ===========
+// === Run ===
+// ===========
+
+/// Glyphs shaped with a single font of a fallback chain.
+#[derive(Clone, Debug)]
+pub struct Run {
+    /// Index of the font in the fallback chain.
+    pub font:   usize,
+    /// Shaped glyphs. Their clusters are byte offsets in the whole shaped text.
+    pub glyphs: Vec<Glyph>,
+}
+
+/// Shape the text with a chain of `font_count` fonts. The `shape` function is used to shape the
+/// text with the font of the given index in the chain. Clusters containing glyphs missing in a font
+/// are shaped again with the next font in the chain. If the glyphs are missing in all fonts, the
+/// missing glyphs of the last font are used. Returns runs of glyphs in the text order.
+pub fn shape_with_fallbacks(
+    text: &str,
+    font_count: usize,
+    mut shape: impl FnMut(usize, &str) -> Vec<Glyph>,
+) -> Vec<Run> {
+    let mut runs = vec![];
+    if font_count > 0 {
+        shape_with_font(text, Byte(0), 0, font_count, &mut shape, &mut runs);
+    }
+    runs
+}
+
+fn shape_with_font(
+    text: &str,
+    offset: Byte,
+    font: usize,
+    font_count: usize,
+    shape: &mut impl FnMut(usize, &str) -> Vec<Glyph>,
+    runs: &mut Vec<Run>,
+) {
+    let is_last_font = font + 1 == font_count;
+    let glyphs = shape(font, text);
+    let mut run = vec![];
+    let mut missing: Option<Range<Byte>> = None;
+    let mut shape_missing = |missing: Range<Byte>, runs: &mut Vec<Run>| {
+        let missing_text = &text[missing.start.value..missing.end.value];
+        let missing_offset = Byte(offset.value + missing.start.value);
+        shape_with_font(missing_text, missing_offset, font + 1, font_count, &mut *shape, runs);
+    };
+    for (range, cluster) in clusters(&glyphs, Byte(text.len())) {
+        if !is_last_font && cluster.iter().any(Glyph::is_missing) {
+            if !run.is_empty() {
+                runs.push(Run { font, glyphs: mem::take(&mut run) });
+            }
+            missing = Some(missing.map_or(range.clone(), |missing| missing.start..range.end));
+        } else {
+            if let Some(missing) = missing.take() {
+                shape_missing(missing, runs);
+            }
+            run.extend(cluster.iter().map(|glyph| {
+                let mut glyph = *glyph;
+                glyph.info.cluster += offset.value as u32;
+                glyph
+            }));
+        }
+    }
+    if !run.is_empty() {
+        runs.push(Run { font, glyphs: run });
+    }
+    if let Some(missing) = missing {
+        shape_missing(missing, runs);
+    }
+}
+
+/// Split the glyphs into clusters. Returns the byte range of the text covered by every cluster,
+/// and the cluster's glyphs. The `end` is the byte offset of the end of the shaped text.
+fn clusters(glyphs: &[Glyph], end: Byte) -> impl Iterator<Item = (Range<Byte>, &[Glyph])> {
+    let mut rest = glyphs;
+    iter::from_fn(move || {
+        let first = rest.first()?;
+        let size = rest.iter().take_while(|glyph| glyph.cluster() == first.cluster()).count();
+        let (cluster, tail) = rest.split_at(size);
+        rest = tail;
+        let cluster_end = rest.first().map_or(end, |glyph| glyph.cluster());
+        Some((first.cluster()..cluster_end, cluster))
+    })
+}
+
+
+
+// ==============
+// === Carets ===
+// ==============
+
+/// Compute the x-axis offsets of the caret positions at every grapheme cluster boundary of the
+/// text. The text is described by its shaped glyphs, provided as pairs of the glyph's cluster byte
+/// offset and its advance, in the text order. The first offset is always `0.0`, and then, there is
+/// one offset after every grapheme cluster.
+///
+/// A glyph can represent several grapheme clusters, as ligatures do. In such a case, its advance
+/// is divided evenly between them, so the caret can be placed (and the selection can end) inside
+/// the ligature, as in most text editors.
+pub fn caret_offsets(text: &Rope, glyphs: impl IntoIterator<Item = (Byte, f32)>) -> Vec<f32> {
+    let mut offsets = vec![0.0];
+    let mut x = 0.0;
+    let mut glyphs = glyphs.into_iter().peekable();
+    while let Some((cluster, mut advance)) = glyphs.next() {
+        while let Some((_, next_advance)) = glyphs.next_if(|(next, _)| *next == cluster) {
+            advance += next_advance;
+        }
+        let cluster_end = glyphs.peek().map_or_else(|| text.last_byte_index(), |(next, _)| *next);
+        let grapheme_count = grapheme_count(text, cluster..cluster_end);
+        for grapheme in 1..=grapheme_count {
+            offsets.push(x + advance * grapheme as f32 / grapheme_count as f32);
+        }
+        x += advance;
+    }
+    offsets
+}
+
+/// Compute the columns of the glyphs, provided as their cluster byte offsets in the text order. The
+/// column of a glyph is the index of the first grapheme cluster it represents.
+pub fn cluster_columns(text: &Rope, clusters: impl IntoIterator<Item = Byte>) -> Vec<Column> {
+    let mut column = Column(0);
+    let mut offset = Byte(0);
+    let mut columns = vec![];
+    for cluster in clusters {
+        column += grapheme_count(text, offset..cluster);
+        offset = offset.max(cluster);
+        columns.push(column);
+    }
+    columns
+}
+
+/// The number of grapheme clusters starting in the provided byte range.
+fn grapheme_count(text: &Rope, range: Range<Byte>) -> usize {
+    let mut count = 0;
+    let mut offset = range.start;
+    while offset < range.end {
+        count += 1;
+        match text.next_grapheme_offset(offset) {
+            Some(next) => offset = next,
+            None => break,
+        }
+    }
+    count
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::font::Embedded;
+    use crate::font::Font;
+    use crate::font::Name;
+    use crate::font::NonVariableFaceHeader;
+    use owned_ttf_parser::AsFaceRef;
+
+    /// Shape the text with the embedded font.
+    fn shape_with_embedded(font: &Font, text: &str) -> Vec<Glyph> {
+        font.with_borrowed_face(NonVariableFaceHeader::default(), |face| {
+            let face = rustybuzz::Face::from_face(face.ttf.as_face_ref().clone()).unwrap();
+            shape(&face, font.feature_settings(), text)
+        })
+        .unwrap()
+    }
+
+    /// The embedded font followed by its fallback fonts.
+    fn fallback_chain(font: &str) -> Vec<String> {
+        let fallbacks = Embedded::default().fallbacks(&font.into()).to_vec();
+        iter::once(font.to_owned())
+            .chain(fallbacks.into_iter().map(|name| name.normalized))
+            .collect()
+    }
+
+    /// Shape the text with the chain of embedded fonts. Returns the font name and the text of every
+    /// run.
+    fn shape_runs(fonts: &[impl AsRef<str>], text: &str) -> Vec<(String, String)> {
+        let embedded: HashMap<_, _> = Embedded::default().into_fonts().collect();
+        let fonts = fonts.iter().map(|name| &embedded[&Name::from(name.as_ref())]).collect_vec();
+        let runs = shape_with_fallbacks(text, fonts.len(), |index, text| {
+            shape_with_embedded(fonts[index], text)
+        });
+        let mut result = vec![];
+        let mut iter = runs.iter().peekable();
+        while let Some(run) = iter.next() {
+            let start = run.glyphs[0].cluster().value;
+            let end = iter.peek().map_or(text.len(), |next| next.glyphs[0].cluster().value);
+            result.push((fonts[run.font].name().normalized.clone(), text[start..end].to_owned()));
+        }
+        result
+    }
+
+    fn runs(runs: &[(&str, &str)]) -> Vec<(String, String)> {
+        runs.iter().map(|(font, text)| (font.to_string(), text.to_string())).collect()
+    }
+
+    #[test]
+    fn fallback_font_is_used_for_missing_glyphs() {
+        let shaped = shape_runs(&["enso", "mplus1p"], "a = '日本' + b");
+        assert_eq!(shaped, runs(&[("enso", "a = '"), ("mplus1p", "日本"), ("enso", "' + b")]));
+    }
+
+    #[test]
+    fn symbol_glyph_is_resolved_with_fallback_font() {
+        // The neutral chess king.
+        let symbol = "\u{1FA00}";
+        let shaped = shape_runs(&fallback_chain("enso"), &format!("a {symbol} b"));
+        assert_eq!(shaped, runs(&[("enso", "a "), ("notosanssymbols2", symbol), ("enso", " b")]));
+        let shaped = shape_runs(&fallback_chain("mplus1p"), symbol);
+        assert_eq!(shaped, runs(&[("notosanssymbols2", symbol)]));
+    }
+
+    #[test]
+    fn emoji_glyph_is_resolved_with_fallback_font() {
+        // The grinning face.
+        let emoji = "\u{1F600}";
+        let shaped = shape_runs(&fallback_chain("enso"), &format!("a {emoji} b"));
+        assert_eq!(shaped, runs(&[("enso", "a "), ("notoemoji", emoji), ("enso", " b")]));
+        let shaped = shape_runs(&fallback_chain("mplus1p"), emoji);
+        assert_eq!(shaped, runs(&[("notoemoji", emoji)]));
+        // The emoji font is the last in the chain, so its glyphs are used even if missing.
+        let embedded: HashMap<_, _> = Embedded::default().into_fonts().collect();
+        let glyphs = shape_with_embedded(&embedded[&Name::from("notoemoji")], emoji);
+        assert!(!glyphs.is_empty());
+        assert!(!glyphs.iter().any(Glyph::is_missing));
+    }
+
+    #[test]
+    fn missing_glyphs_without_fallbacks() {
+        let shaped = shape_runs(&["enso"], "a日b");
+        assert_eq!(shaped, runs(&[("enso", "a日b")]));
+    }
+
+    #[test]
+    fn caret_offsets_of_ligatures() {
+        // A ligature of three characters followed by a regular glyph.
+        let text = Rope::from("->>a");
+        let offsets = caret_offsets(&text, [(Byte(0), 30.0), (Byte(3), 10.0)]);
+        assert_eq!(offsets, vec![0.0, 10.0, 20.0, 30.0, 40.0]);
+    }
+
+    #[test]
+    fn caret_offsets_of_multi_glyph_clusters() {
+        // A base glyph with a combining mark rendered as a separate glyph.
+        let text = Rope::from("e\u{301}b");
+        let offsets = caret_offsets(&text, [(Byte(0), 10.0), (Byte(0), 0.0), (Byte(3), 8.0)]);
+        assert_eq!(offsets, vec![0.0, 10.0, 18.0]);
+    }
+
+    #[test]
+    fn columns_of_clusters() {
+        let text = Rope::from("->>e\u{301}b");
+        let columns = cluster_columns(&text, [Byte(0), Byte(3), Byte(3), Byte(6)]);
+        assert_eq!(columns, vec![Column(0), Column(3), Column(3), Column(4)]);
+    }
+
+    #[test]
+    fn caret_offsets_of_shaped_ligatures() {
+        let embedded: HashMap<_, _> = Embedded::default().into_fonts().collect();
+        let font = &embedded[&Name::from("enso")];
+        let text = "a -> b";
+        let glyphs = shape_with_embedded(font, text);
+        let advances =
+            glyphs.iter().map(|glyph| (glyph.cluster(), glyph.position.x_advance as f32));
+        let offsets = caret_offsets(&Rope::from(text), advances);
+        // Every character has its caret position, even if the arrow is rendered as one glyph.
+        assert_eq!(offsets.len(), text.chars().count() + 1);
+        assert!(offsets.is_sorted());
+        let total_advance: i32 = glyphs.iter().map(|glyph| glyph.position.x_advance).sum();
+        assert_eq!(*offsets.last().unwrap(), total_advance as f32);
+    }
+}
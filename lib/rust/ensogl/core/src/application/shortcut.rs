@@ -9,6 +9,8 @@ use crate::frp::io::mouse::Mouse_DEPRECATED;
 use super::command;
 
 use enso_shortcuts as shortcuts;
+use enso_shortcuts::analysis;
+use enso_shortcuts::analysis::Binding;
 
 
 // ==============
@@ -128,6 +130,51 @@ impl Condition {
     }
 }
 
+impl Condition {
+    /// Maximum number of distinct variables for which [`Self::is_satisfiable`] checks all the
+    /// assignments. Conditions with more variables are assumed to be satisfiable.
+    const MAX_CHECKED_VARIABLES: usize = 16;
+
+    /// Check whether there is an assignment of the variables for which the condition holds.
+    pub fn is_satisfiable(&self) -> bool {
+        let mut variables = vec![];
+        self.collect_variables(&mut variables);
+        let variables = variables.into_iter().sorted().dedup().collect_vec();
+        if variables.len() > Self::MAX_CHECKED_VARIABLES {
+            return true;
+        }
+        (0..1_u32 << variables.len()).any(|assignment| {
+            self.evaluate(&|name| {
+                let index = variables.iter().position(|variable| *variable == name);
+                index.map_or(false, |index| assignment & (1 << index) != 0)
+            })
+        })
+    }
+
+    fn collect_variables<'a>(&'a self, out: &mut Vec<&'a str>) {
+        match self {
+            Self::Always | Self::Never => {}
+            Self::When(name) => out.push(name),
+            Self::Not(a) => a.collect_variables(out),
+            Self::Or(a, b) | Self::And(a, b) => {
+                a.collect_variables(out);
+                b.collect_variables(out);
+            }
+        }
+    }
+
+    fn evaluate(&self, value: &impl Fn(&str) -> bool) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            Self::When(name) => value(name),
+            Self::Not(a) => !a.evaluate(value),
+            Self::Or(a, b) => a.evaluate(value) || b.evaluate(value),
+            Self::And(a, b) => a.evaluate(value) && b.evaluate(value),
+        }
+    }
+}
+
 impl From<&str> for Condition {
     fn from(s: &str) -> Self {
         Self::parse(s)
@@ -206,6 +253,75 @@ impl Shortcut {
         let rule = rule.into();
         Self { action, rule }
     }
+
+    /// Check whether both shortcuts may be enabled at the same time. Conditions are evaluated in
+    /// the context of the target, so conditions of different targets are independent.
+    pub fn may_coexist_with(&self, other: &Self) -> bool {
+        if self.target == other.target {
+            Condition::and(self.condition.clone(), other.condition.clone()).is_satisfiable()
+        } else {
+            self.condition.is_satisfiable() && other.condition.is_satisfiable()
+        }
+    }
+}
+
+impl Display for Shortcut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Rule { tp, pattern } = &self.rule;
+        write!(f, "{}::{} ({tp:?} \"{pattern}\")", self.target, self.command.name)
+    }
+}
+
+
+
+// ================
+// === Conflict ===
+// ================
+
+/// A problem with a set of shortcuts, found by [`conflicts`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[allow(missing_docs)]
+pub enum Conflict {
+    /// Both shortcuts trigger on the same input.
+    Overlap { first: Shortcut, second: Shortcut, input: String },
+    /// The `prefix` shortcut triggers on the first strokes of the `sequence` chord sequence, and
+    /// the strokes following it are handled as continuations of the sequence.
+    Shadowed { prefix: Shortcut, sequence: Shortcut },
+    /// The pattern of the shortcut cannot be parsed.
+    Invalid { shortcut: Shortcut, error: shortcuts::PatternError },
+}
+
+impl Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Overlap { first, second, input } =>
+                write!(f, "Shortcuts {first} and {second} both trigger on \"{input}\"."),
+            Self::Shadowed { prefix, sequence } =>
+                write!(f, "Shortcut {prefix} triggers on the beginning of {sequence}."),
+            Self::Invalid { shortcut, error } =>
+                write!(f, "Shortcut {shortcut} is invalid. {error}"),
+        }
+    }
+}
+
+/// Find conflicting shortcuts. Shortcuts whose conditions can never hold at the same time are not
+/// considered conflicting.
+pub fn conflicts(shortcuts: &[Shortcut]) -> Vec<Conflict> {
+    let bindings = shortcuts.iter().map(|s| Binding::new(s.rule.tp, &s.rule.pattern)).collect_vec();
+    let may_coexist =
+        |first: usize, second: usize| shortcuts[first].may_coexist_with(&shortcuts[second]);
+    let shortcut = |index: usize| shortcuts[index].clone();
+    let conflicts = analysis::analyze(&bindings, may_coexist).into_iter();
+    conflicts
+        .map(|conflict| match conflict {
+            analysis::Conflict::Overlap { first, second, input } =>
+                Conflict::Overlap { first: shortcut(first), second: shortcut(second), input },
+            analysis::Conflict::Shadowed { prefix, sequence } =>
+                Conflict::Shadowed { prefix: shortcut(prefix), sequence: shortcut(sequence) },
+            analysis::Conflict::Invalid { binding, error } =>
+                Conflict::Invalid { shortcut: shortcut(binding), error },
+        })
+        .collect()
 }
 
 
@@ -231,6 +347,8 @@ pub struct Registry {
     /// An FRP node that contains the name of the command currently being executed.
     /// `None` means that no command is being executed.
     pub currently_handled: frp::Source<Option<ImString>>,
+    /// All the shortcuts added to this registry, and the shortcuts declared for views' instances.
+    declared:              Rc<RefCell<Vec<Shortcut>>>,
 }
 
 /// Internal representation of `Registry`.
@@ -257,7 +375,8 @@ impl Registry {
         }
         let model = RegistryModel::new(mouse, cmd_registry, currently_handled.clone_ref(), None);
         Self::extend_network(&network, &model, keyboard_target, global_keyboard_target);
-        Self { model, network, currently_handled }
+        let declared = default();
+        Self { model, network, currently_handled, declared }
     }
 
     /// Create a shortcut registry inheriting global parameters, bound to the given instance, and
@@ -277,6 +396,24 @@ impl Registry {
         model
     }
 
+    /// Declare a shortcut that is not added to this registry, but to the registries bound to the
+    /// instances of a view, so that it is included in [`Self::conflicts`].
+    pub fn declare(&self, shortcut: Shortcut) {
+        self.declared.borrow_mut().push(shortcut);
+    }
+
+    /// Find conflicts between the added and declared shortcuts. See [`conflicts`] to learn more.
+    pub fn conflicts(&self) -> Vec<Conflict> {
+        conflicts(&self.declared.borrow())
+    }
+
+    /// Emit a warning for each conflict between the added and declared shortcuts.
+    pub fn report_conflicts(&self) {
+        for conflict in self.conflicts() {
+            warn!("{conflict}");
+        }
+    }
+
     /// Connect the model to the given network and keyboard target.
     fn extend_network(
         network: &frp::Network,
@@ -387,3 +524,70 @@ impl Add<Shortcut> for &RegistryModel {
         self.shortcuts_registry.add(shortcut.rule.tp, &shortcut.rule.pattern, shortcut.clone());
     }
 }
+
+impl Add<Shortcut> for &Registry {
+    type Output = ();
+    fn add(self, shortcut: Shortcut) {
+        self.declared.borrow_mut().push(shortcut.clone());
+        &self.model + shortcut
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn condition_satisfiability() {
+        assert!(Condition::from("").is_satisfiable());
+        assert!(Condition::from("a & !b").is_satisfiable());
+        assert!(Condition::from("a & !a | b & !b | c").is_satisfiable());
+        assert!(!Condition::from("a & !a").is_satisfiable());
+        assert!(!Condition::from("a & !a | b & !b").is_satisfiable());
+        assert!(!Condition::Never.is_satisfiable());
+    }
+
+    #[test]
+    fn conflicting_shortcuts() {
+        let shortcut = |target, pattern, command, condition| {
+            Shortcut::new_when(Rule::new(ActionType::Press, pattern), target, command, condition)
+        };
+        let shortcuts = [
+            shortcut("Editor", "ctrl s", "save", "focused"),
+            shortcut("Editor", "ctrl+s", "save_all", "!focused"),
+            shortcut("Graph", "ctrl s", "save_graph", "focused"),
+            shortcut("Editor", "ctrl+k ctrl+s", "save_without_formatting", ""),
+            shortcut("Graph", "ctrl k", "collapse", "focused"),
+            shortcut("Graph", "ctrl+k ctrl", "expand", ""),
+        ];
+        assert_eq!(conflicts(&shortcuts), vec![
+            Conflict::Invalid {
+                shortcut: shortcuts[5].clone(),
+                error:    shortcuts::PatternError::ModifierOnlyStroke {
+                    pattern: "ctrl+k ctrl".into(),
+                    stroke:  "ctrl".into(),
+                },
+            },
+            Conflict::Overlap {
+                first:  shortcuts[0].clone(),
+                second: shortcuts[2].clone(),
+                input:  "ctrl s".into(),
+            },
+            Conflict::Overlap {
+                first:  shortcuts[1].clone(),
+                second: shortcuts[2].clone(),
+                input:  "ctrl s".into(),
+            },
+            Conflict::Shadowed { prefix: shortcuts[4].clone(), sequence: shortcuts[3].clone() },
+        ]);
+        let expected = "Shortcuts Editor::save (Press \"ctrl s\") and Graph::save_graph (Press \
+            \"ctrl s\") both trigger on \"ctrl s\".";
+        assert_eq!(conflicts(&shortcuts)[1].to_string(), expected);
+    }
+}
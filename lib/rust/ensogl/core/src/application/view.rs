@@ -52,6 +52,9 @@ impl Registry {
         for shortcut in V::global_shortcuts() {
             self.shortcut_registry.add(shortcut)
         }
+        for shortcut in V::focused_shortcuts() {
+            self.shortcut_registry.declare(shortcut)
+        }
         self.definitions.borrow_mut().insert(label);
         self.command_registry.register::<V>();
     }
@@ -51,7 +51,7 @@ macro_rules! define_color_spaces {
                         stringify!($name)   => Ok(AnyFormat::$name($name::from_slice(&args))),
                         stringify!($a_name) => Ok(AnyFormat::$a_name($a_name::from_slice(&args))),
                     )*
-                    _ => panic!("Impossible.")
+                    _ => Err(ParseError::new(format!("Unknown color space '{head}'.")))
                 }
             }
         }
@@ -921,7 +921,7 @@ fn generic_parse(s: &str) -> Result<(String, Vec<f32>), ParseError> {
                 } else {
                     let rest = &rest[..rest.len() - 1];
                     let args: Result<Vec<f32>, std::num::ParseFloatError> =
-                        rest.split(',').map(|t| t.parse::<f32>()).collect();
+                        rest.split(',').map(|t| t.trim().parse::<f32>()).collect();
                     let args = args?;
                     Ok((head, args))
                 }
@@ -898,30 +898,138 @@
 //! root.use_auto_layout().justify_content_space_evenly_x();
 //! ```
 //!
-//! ## Future Grid layout extensions.
+//! ## Spanning multiple columns and rows.
+//! An item can occupy several adjacent columns and rows, set with the [`set_column_span`] and
+//! [`set_row_span`] methods. Items are placed in the first free cells following the previously
+//! placed item, in the order defined by the grid flow. If the spanned columns are too small for the
+//! item, the missing space is evenly distributed among the spanned columns that hug their content.
+//! The item inherits the grow and shrink factors of all the columns it spans, and it is aligned
+//! within the whole spanned area, including the gaps between the columns. The following code
+//! places a header spanning both columns above two other items:
 //!
-//! ### Placing items across multiple rows/columns.
-//! The CSS Grid allows placing items across multiple rows/columns. This mode is not supported by
-//! the Grid layout yet. See the `grid-template-areas` property to learn more about it:
-//! https://css-tricks.com/snippets/css/complete-guide-grid/#aa-grid-template-areas.
+//! ```
+//! // ╔ root ═════════ ▶ ◀ ════════════╗
+//! // ║ ╭───── ▶ ◀ ─────┬──── ▶ ◀ ────╮ ║
+//! // ║ │  ╭ node2 ╮    ┆  ╭ node3 ╮  │ ║
+//! // ║ │  │       │    ┆  │       │  ▼ ║
+//! // ║ │  ╰───────╯    ┆  ╰───────╯  ▲ ▼
+//! // ║ ├╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌┴╌╌╌╌╌╌╌╌╌╌╌╌╌┤ ▲
+//! // ║ │  ╭ node1 ────────────────╮  ▼ ║
+//! // ║ │  ╰────────────────────────╯  ▲ ║
+//! // ║ ╰─────────────────────────────╯ ║
+//! // ╚═════════════════════════════════╝
+//!
+//! # use ensogl_core::prelude::*;
+//! # use ensogl_core::display;
+//! let root = display::object::Instance::new();
+//! let node1 = root.new_child();
+//! let node2 = root.new_child();
+//! let node3 = root.new_child();
+//! root.use_auto_layout().set_column_count(2);
+//! node1.set_size((5.0, 1.0)).set_column_span(2);
+//! node2.set_size((2.0, 2.0));
+//! node3.set_size((1.0, 2.0));
+//! ```
+//!
+//!
+//! ## Named grid areas.
+//! Similarly to the CSS `grid-template-areas` property, the grid can define named areas, and items
+//! can be placed in them by name. The template is defined by rows of whitespace-separated cell
+//! names, listed from the top row to the bottom one. Cells with the same name form an area, which
+//! has to be rectangular, while cells named `.` do not belong to any area. The grid has at least as
+//! many columns and rows as the template. Items without an area, or with an area not defined by the
+//! template, are placed automatically in the free cells. The following code defines a dashboard
+//! with a header above a sidebar and the main content:
 //!
-//! ### The masonry layout.
+//! ```
+//! // ╔ root ══════════ ▶ ◀ ════════════╗
+//! // ║ ╭──── ▶ ◀ ────┬────── ▶ ◀ ────╮ ║
+//! // ║ │  ╭ header ───────────────┬▷ ▼ ║
+//! // ║ │  ╰─────────────────────────╯ ▲ ▼
+//! // ║ ├╌╌╌╌╌╌╌╌╌╌╌╌╌┬╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌┤ ▲
+//! // ║ │  ╭ side ╮   ┆  ╭ main ─╮    │ ║
+//! // ║ │  │      │   ┆  │       │    ▼ ║
+//! // ║ │  ╰──────╯   ┆  ╰───────╯    ▲ ║
+//! // ║ ╰─────────────┴───────────────╯ ║
+//! // ╚═════════════════════════════════╝
+//!
+//! # use ensogl_core::prelude::*;
+//! # use ensogl_core::display;
+//! # use ensogl_core::display::object::layout::GridTemplateAreas;
+//! let root = display::object::Instance::new();
+//! let header = root.new_child();
+//! let side = root.new_child();
+//! let main = root.new_child();
+//! let template = GridTemplateAreas::new(["header header", "side main"]).unwrap();
+//! root.use_auto_layout().set_grid_template_areas(template);
+//! header.set_grid_area("header").set_size((2.0, 1.0)).allow_grow_x();
+//! side.set_grid_area("side").set_size((1.0, 2.0));
+//! main.set_grid_area("main").set_size((3.0, 3.0));
+//! ```
+//!
+//!
+//! ## The masonry layout.
 //! The masonry layout is a layout where rows or columns are not aligned and can be shifted in one
 //! direction. This mode is useful when creating "image walls" or lists of tags, where items have
-//! different lengths. For example, it will allow to place items in the following layout:
+//! different lengths. In the masonry layout, every row contains the number of items set by the
+//! [`set_column_count`] method, and it is laid out independently of other rows, just like a grid
+//! with a single row. The rows are still resized to fit their items. In the 'column' flow, every
+//! column contains the number of items set by the [`set_row_count`] method instead. Please note,
+//! that the item spans and the named grid areas are not used in this layout. The following code
+//! places six items in two rows, where the second row is placed above the first one:
+//!
+//! ```
+//! // ╔ root ══════════════════════ ▶ ◀ ══════════════════════╗
+//! // ║ ╭────── ▶ ◀ ──────┬───── ▶ ◀ ─────┬────── ▶ ◀ ──────╮ ║
+//! // ║ │  ╭ node4 ╮      ┆ ╭ node5 ────╮ ┆ ╭ node6 ──╮      ▼ ║
+//! // ║ │  ╰───────╯      ┆ ╰───────────╯ ┆ ╰─────────╯      ▲ ▼
+//! // ║ ├╌╌╌╌╌╌╌╌╌╌╌╌┬╌╌╌╌┴╌╌╌╌╌╌╌┬╌╌╌╌╌╌╌┴╌╌╌╌┬╌╌╌╌╌╌╌╌╌╌╌╌╌╯ ▲
+//! // ║ │  ╭ node1 ─────╮ ┆ ╭ node2 ╮ ┆ ╭ node3 ─╮ ▼            ║
+//! // ║ │  ╰────────────╯ ┆ ╰───────╯ ┆ ╰────────╯ ▲            ║
+//! // ║ ╰───── ▶ ◀ ───────┴─── ▶ ◀ ───┴─── ▶ ◀ ────╯            ║
+//! // ╚═══════════════════════════════════════════════════════╝
+//!
+//! # use ensogl_core::prelude::*;
+//! # use ensogl_core::display;
+//! let root = display::object::Instance::new();
+//! let nodes = (0..6).map(|_| root.new_child()).collect_vec();
+//! for (node, width) in nodes.iter().zip([4.0, 2.0, 3.0, 2.0, 4.0, 3.0]) {
+//!     node.set_size((width, 1.0));
+//! }
+//! root.use_auto_layout().set_masonry_packing().set_column_count(3);
+//! ```
 //!
-//! ╔════════════════════════════════════════════════════════════════════════╗
-//! ║ ╭───────────── ▶ ◀ ────────────┬─── ▶ ◀ ───┬─────────── ▶ ◀ ─────────╮ ║
-//! ║ │  ╭────────────────────────╮  ┆ ╭───────╮ ┆ ╭─────────────────────╮ ▼ ║
-//! ║ │  ╰────────────────────────╯  ┆ ╰───────╯ ┆ ╰─────────────────────╯ ▲ ▼
-//! ║ ├╌╌╌╌╌╌╌╌╌╌╌╌┬╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌┴╌╌╌╌┬╌╌╌╌╌╌┴╌╌╌╌╌╌╌┬╌╌╌╌╌╌╌╌┬╌╌╌╌╌╌╌╌╯ ▲
-//! ║ │  ╭──────╮  ┆ ╭──────────────────╮ ┆ ╭──────────╮ ┆ ╭────╮ ▼          ║
-//! ║ │  ╰──────╯  ┆ ╰──────────────────╯ ┆ ╰──────────╯ ┆ ╰────╯ ▲          ║
-//! ║ ╰─── ▶ ◀ ────┴───────── ▶ ◀ ────────┴──── ▶ ◀ ─────┴─ ▶ ◀ ──╯          ║
-//! ╚════════════════════════════════════════════════════════════════════════╝
 //!
-//! To learn more about the masonry layout, see the following article:
-//! https://css-tricks.com/snippets/css/complete-guide-grid/#aa-masonry.
+//! ## The wrapping layout.
+//! The wrapping layout is similar to the masonry layout, but the number of items in a row is not
+//! fixed. Instead, items are placed in a row as long as they fit in the size of the container, and
+//! then they wrap to the next row. The size of the container is resolved first, so it can be set
+//! to a fixed value, a percentage of the parent size, or a fraction of the free space. If the
+//! container hugs its content, the rows are limited by its maximum size only. In the 'column' flow,
+//! items wrap to the next column when they do not fit in the height of the container. The
+//! following code places five items in a container of width 10:
+//!
+//! ```
+//! // ╔ root ═════════════════════════════════════════╗
+//! // ║ ╭───── ▶ ◀ ─────┬─────────── ▶ ◀ ─────────╮   ║
+//! // ║ │  ╭ node4 ──╮  ┆ ╭ node5 ──────────────╮ ▼   ║
+//! // ║ │  ╰─────────╯  ┆ ╰─────────────────────╯ ▲   ▼
+//! // ║ ├╌╌╌╌╌╌╌╌╌╌╌╌┬╌╌┴╌╌╌╌╌╌╌┬╌╌╌╌╌╌╌╌╌╌╌╌┬╌╌╌╌╌╯ ▲ ▲
+//! // ║ │  ╭ node1 ─────╮ ┆ ╭ node2 ╮ ┆ ╭ node3 ─╮ ▼ ║
+//! // ║ │  ╰────────────╯ ┆ ╰───────╯ ┆ ╰────────╯ ▲ ║
+//! // ║ ╰───── ▶ ◀ ───────┴─── ▶ ◀ ───┴─── ▶ ◀ ────╯ ║
+//! // ╚═══════════════════════════════════════════════╝
+//! //                         10
+//!
+//! # use ensogl_core::prelude::*;
+//! # use ensogl_core::display;
+//! let root = display::object::Instance::new();
+//! let nodes = (0..5).map(|_| root.new_child()).collect_vec();
+//! for (node, width) in nodes.iter().zip([4.0, 2.0, 3.0, 3.0, 6.0]) {
+//!     node.set_size((width, 1.0));
+//! }
+//! root.use_auto_layout().set_wrap_packing().set_size_x(10.0);
+//! ```
 //!
 //!
 //!
@@ -2831,6 +2939,12 @@ pub struct LayoutModel {
     grow_factor:   Cell<Vector2<f32>>,
     shrink_factor: Cell<Vector2<f32>>,
     computed_size: Cell<Vector2<f32>>,
+    #[derivative(Default(value = "Cell::new(Vector2(1, 1))"))]
+    grid_span:     Cell<Vector2<usize>>,
+    grid_area:     RefCell<Option<ImString>>,
+    /// Children ranges of the lines of the 'wrap' packing. They are computed in the horizontal
+    /// layout pass and reused in the vertical one.
+    wrapped_lines: RefCell<Vec<Range<usize>>>,
 }
 
 impl Model {
@@ -3156,6 +3270,59 @@ pub trait LayoutOps: Object {
         self
     }
 
+    /// The number of columns and rows the object spans when placed in the grid layout. See the docs
+    /// of this module to learn more.
+    fn grid_span(&self) -> Vector2<usize> {
+        self.display_object().layout.grid_span.get()
+    }
+
+    /// Modify the number of columns and rows the object spans when placed in the grid layout.
+    /// Spans are never smaller than one column/row.
+    #[enso_shapely::gen(update, set(trait = "IntoVector2<usize>", fn = "into_vector()"))]
+    fn modify_grid_span(&self, f: impl FnOnce(&mut Vector2<usize>)) -> &Self {
+        self.display_object().modify_layout(|l| {
+            l.grid_span.modify_(|span| {
+                f(span);
+                *span = span.map(|t| t.max(1));
+            });
+        });
+        self
+    }
+
+    /// Set the number of columns the object spans when placed in the grid layout.
+    fn set_column_span(&self, span: usize) -> &Self {
+        self.set_grid_span_x(span)
+    }
+
+    /// Set the number of rows the object spans when placed in the grid layout.
+    fn set_row_span(&self, span: usize) -> &Self {
+        self.set_grid_span_y(span)
+    }
+
+    /// The name of the grid template area the object is placed in. See the docs of this module to
+    /// learn more.
+    fn grid_area(&self) -> Option<ImString> {
+        self.display_object().layout.grid_area.borrow().clone()
+    }
+
+    /// Place the object in the named area of the parent's grid template. If the parent's template
+    /// has no such area, the object is placed automatically, like objects without an area.
+    fn set_grid_area(&self, name: impl Into<ImString>) -> &Self {
+        let name = name.into();
+        self.display_object().modify_layout(|l| {
+            l.grid_area.replace(Some(name));
+        });
+        self
+    }
+
+    /// Place the object automatically in the parent's grid, regardless of the parent's template.
+    fn unset_grid_area(&self) -> &Self {
+        self.display_object().modify_layout(|l| {
+            l.grid_area.take();
+        });
+        self
+    }
+
     /// Set padding of all sides of the object. Padding is the free space inside the object.
     fn set_padding_all(&self, value: impl Into<Unit>) -> &Self {
         let padding = SideSpacing::from(value.into());
@@ -3298,6 +3465,30 @@ pub enum AutoLayoutFlow {
 
 
 
+// =========================
+// === AutoLayoutPacking ===
+// =========================
+
+/// The packing defines how the items are arranged in lines, which are rows in the 'row' flow and
+/// columns in the 'column' flow. The default packing is 'grid', which means that the columns and
+/// rows are aligned across all lines.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum AutoLayoutPacking {
+    /// Items are placed in the grid cells, and the columns and rows are shared by all lines.
+    #[default]
+    Grid,
+    /// Every line contains the number of items set by the [`AutoLayoutOps::set_column_count`]
+    /// method (or by the [`AutoLayoutOps::set_row_count`] method in the 'column' flow). Items of
+    /// different lines are not aligned, as every line is laid out independently.
+    Masonry,
+    /// Items are placed in a line as long as they fit in the size of the object, and then they
+    /// wrap to the next line. Just like in the masonry packing, lines are laid out
+    /// independently.
+    Wrap,
+}
+
+
+
 // ==================
 // === AutoLayout ===
 // ==================
@@ -3312,6 +3503,8 @@ pub struct AutoLayout {
     pub enabled: bool,
     /// Controls in which direction and order the items are placed in the grid.
     pub flow: AutoLayoutFlow,
+    /// Controls whether the items are placed in the grid cells or in independent lines.
+    pub packing: AutoLayoutPacking,
     /// The default item alignment in grid cells. This can be overriden per-child.
     pub children_alignment: alignment::Dim2,
     /// The spacing between columns/rows.
@@ -3324,6 +3517,8 @@ pub struct AutoLayout {
     /// The number of columns and rows in the grid. If it's set to [`None`], the columns and rows
     /// will grow on demand.
     pub columns_and_rows_count: Vector2<Option<usize>>,
+    /// The named areas children can be placed in.
+    pub grid_template_areas: Option<GridTemplateAreas>,
 }
 
 /// A trait alias for accessing columns and rows.
@@ -3341,6 +3536,15 @@ impl AutoLayout {
     where Dim: ColumnOrRowAccessor {
         &mut self.columns_and_rows.get_dim_mut(dim)[index]
     }
+
+    /// The number of items in a line, which is the number of columns in the 'row' flow, and the
+    /// number of rows in the 'column' flow.
+    fn primary_count(&self) -> Option<usize> {
+        match self.flow {
+            AutoLayoutFlow::Row => self.columns_and_rows_count.x,
+            AutoLayoutFlow::Column => self.columns_and_rows_count.y,
+        }
+    }
 }
 
 #[allow(missing_docs)]
@@ -3505,6 +3709,43 @@ pub trait AutoLayoutOps: Object {
         self.display_object().modify_auto_layout(|l| l.flow = AutoLayoutFlow::Column);
         self
     }
+
+    /// Sets the auto layout packing to 'grid'. Items will be placed in the grid cells, aligned with
+    /// items of other rows and columns.
+    fn set_grid_packing(&self) -> &Self {
+        self.display_object().modify_auto_layout(|l| l.packing = AutoLayoutPacking::Grid);
+        self
+    }
+
+    /// Sets the auto layout packing to 'masonry'. Every row (or column in the 'column' flow) will
+    /// contain the number of items set by [`set_column_count`] (or [`set_row_count`]), and will be
+    /// laid out independently of other rows.
+    fn set_masonry_packing(&self) -> &Self {
+        self.display_object().modify_auto_layout(|l| l.packing = AutoLayoutPacking::Masonry);
+        self
+    }
+
+    /// Sets the auto layout packing to 'wrap'. Items will fill a row (or column in the 'column'
+    /// flow) as long as they fit in the size of this object, and then they will wrap to the next
+    /// one. Rows are laid out independently of each other.
+    fn set_wrap_packing(&self) -> &Self {
+        self.display_object().modify_auto_layout(|l| l.packing = AutoLayoutPacking::Wrap);
+        self
+    }
+
+    /// Set the named areas of the grid, which children can be placed in with the
+    /// [`LayoutOps::set_grid_area`] method. The grid has at least as many columns and rows as the
+    /// template.
+    fn set_grid_template_areas(&self, template: GridTemplateAreas) -> &Self {
+        self.display_object().modify_auto_layout(|l| l.grid_template_areas = Some(template));
+        self
+    }
+
+    /// Remove the named areas of the grid. All children will be placed automatically.
+    fn unset_grid_template_areas(&self) -> &Self {
+        self.display_object().modify_auto_layout(|l| l.grid_template_areas = None);
+        self
+    }
 }
 
 
@@ -3625,6 +3866,7 @@ where
     Vector2<SideSpacing>: DimSetter<Self>,
     Vector2<bool>: DimSetter<Self>,
     Vector2<f32>: DimSetter<Self>,
+    Vector2<usize>: DimSetter<Self>,
     Vector2<Unit>: DimSetter<Self>,
     Vector3<f32>: DimSetter<Self>,
     (NonEmptyVec<ColumnOrRow>, NonEmptyVec<ColumnOrRow>):
@@ -3688,7 +3930,7 @@ impl Model {
     where Dim: ResolutionDim {
         // let old_size = self.layout.computed_size.get();
         if let Some(layout) = &*self.layout.auto_layout.borrow() && layout.enabled {
-            self.refresh_grid_layout(x, layout);
+            self.refresh_grid_layout(x, layout, pass_cfg);
         } else {
             self.refresh_manual_layout(x, pass_cfg);
         }
@@ -3813,12 +4055,31 @@ impl Model {
 }
 
 
+#[derive(Debug, Deref)]
+struct GridChild {
+    #[deref]
+    instance:      Instance,
+    /// The index of the first column occupied by the child.
+    start:         usize,
+    /// The number of columns occupied by the child.
+    span:          usize,
+    /// The computed size of the child before the layout refresh.
+    previous_size: f32,
+}
+
+impl GridChild {
+    fn covers(&self, column_index: usize) -> bool {
+        (self.start..self.start + self.span).contains(&column_index)
+    }
+}
+
 #[derive(Debug, Deref, DerefMut)]
 struct UnresolvedColumn {
     #[deref]
     #[deref_mut]
     axis:     ColumnOrRow,
-    children: Vec<Instance>,
+    /// Children occupying only this column.
+    children: Vec<GridChild>,
 }
 
 #[derive(Debug, Deref, DerefMut)]
@@ -3826,7 +4087,15 @@ struct ResolvedColumn {
     #[deref]
     #[deref_mut]
     axis:     ResolvedAxis,
-    children: Vec<Instance>,
+    /// Children occupying only this column.
+    children: Vec<GridChild>,
+}
+
+/// Columns of the grid, along with the children spanning multiple columns.
+#[derive(Debug)]
+struct GridColumns<T> {
+    columns:           Vec<T>,
+    spanning_children: Vec<GridChild>,
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
@@ -3840,6 +4109,77 @@ struct ResolvedAxis {
     max_child_fr_size: Fraction,
 }
 
+/// The placement of children in the grid.
+#[derive(Debug)]
+struct GridPlacement {
+    /// The number of columns and rows of the grid.
+    size:  Vector2<usize>,
+    /// The areas occupied by the children, in the children order.
+    areas: Vec<GridArea>,
+}
+
+impl GridPlacement {
+    /// Place the children in the grid. Children placed in the named areas of the grid template are
+    /// placed first. Then, the remaining children are placed one by one in the first free cells
+    /// following the previously placed child, in the order defined by the grid flow.
+    fn new(opts: &AutoLayout, children: &[Instance]) -> Self {
+        // The placement is computed in the flow-relative coordinates: the primary axis is the one
+        // filled by the flow before moving to the next column/row.
+        let flow = opts.flow;
+        let to_flow = |v: Vector2<usize>| match flow {
+            AutoLayoutFlow::Row => v,
+            AutoLayoutFlow::Column => Vector2(v.y, v.x),
+        };
+        let from_flow = to_flow;
+        let template = opts.grid_template_areas.as_ref();
+        let template_size = to_flow(template.map_or(Vector2(0, 0), |template| template.size()));
+        let template_area = |child: &Instance| {
+            let name = child.layout.grid_area.borrow();
+            template.and_then(|template| template.area(name.as_ref()?))
+        };
+        let explicit_areas = children.iter().map(template_area).collect_vec();
+        let spans =
+            children.iter().map(|child| to_flow(child.layout.grid_span.get())).collect_vec();
+        let auto_spans = spans.iter().zip(&explicit_areas).filter(|(_, area)| area.is_none());
+        let auto_primary_span: usize = auto_spans.map(|(span, _)| span.x).sum();
+        let default_primary_count =
+            if template.is_some() { template_size.x } else { auto_primary_span };
+        let primary_count = opts.primary_count().unwrap_or(default_primary_count).max(1);
+
+        let mut occupied = HashSet::<Vector2<usize>>::new();
+        for area in explicit_areas.iter().flatten() {
+            occupied.extend(area.cells().map(to_flow));
+        }
+        let mut cursor = Vector2(0, 0);
+        let sup = |a: Vector2<usize>, b: Vector2<usize>| Vector2(a.x.max(b.x), a.y.max(b.y));
+        let mut size = sup(Vector2(primary_count, 0), template_size);
+        let mut areas = Vec::with_capacity(children.len());
+        for (explicit_area, span) in explicit_areas.into_iter().zip(spans) {
+            let area =
+                explicit_area.map(|area| GridArea::new(to_flow(area.start), to_flow(area.span)));
+            let area = area.unwrap_or_else(|| {
+                let span = Vector2(span.x.min(primary_count), span.y);
+                loop {
+                    if cursor.x + span.x > primary_count {
+                        cursor = Vector2(0, cursor.y + 1);
+                    }
+                    let area = GridArea::new(cursor, span);
+                    if area.cells().all(|cell| !occupied.contains(&cell)) {
+                        occupied.extend(area.cells());
+                        cursor.x += span.x;
+                        break area;
+                    }
+                    cursor.x += 1;
+                }
+            });
+            size = sup(size, area.end());
+            areas.push(GridArea::new(from_flow(area.start), from_flow(area.span)));
+        }
+        let size = from_flow(size);
+        Self { size, areas }
+    }
+}
+
 impl Model {
     /// # Meaning of the function parameters.
     /// In order to make the code easy to understand, all variables in layout functions were named
@@ -3850,38 +4190,51 @@ impl Model {
         x: Dim,
         opts: &AutoLayout,
         children: &[Instance],
-    ) -> Vec<UnresolvedColumn>
+        previous_sizes: &[f32],
+    ) -> GridColumns<UnresolvedColumn>
     where
         Dim: ResolutionDim,
     {
+        let placement = GridPlacement::new(opts, children);
         let columns_defs = opts.columns_and_rows.get_dim(x);
-        let prim_axis_item_count = match opts.flow {
-            AutoLayoutFlow::Row => opts.columns_and_rows_count.get_dim(X),
-            AutoLayoutFlow::Column => opts.columns_and_rows_count.get_dim(Y),
-        };
-        let prim_axis_item_count = prim_axis_item_count.unwrap_or(children.len());
-        if x.matches_flow_direction(opts.flow) {
-            let column_defs = columns_defs.iter().cycle().enumerate().take(prim_axis_item_count);
-            column_defs
-                .map(|(i, axis)| {
-                    let children_iter = children.iter().skip(i).step_by(prim_axis_item_count);
-                    let children = children_iter.cloned().collect_vec();
-                    let axis = *axis;
-                    UnresolvedColumn { axis, children }
-                })
-                .collect_vec()
-        } else {
-            let column_count = children.len().div_ceil(prim_axis_item_count);
-            let column_axes = columns_defs.iter().cycle().enumerate().take(column_count);
-            column_axes
-                .map(|(i, axis)| {
-                    let skip_count = i * prim_axis_item_count;
-                    let children_iter = children.iter().skip(skip_count).take(prim_axis_item_count);
-                    let children = children_iter.cloned().collect_vec();
-                    let axis = *axis;
-                    UnresolvedColumn { axis, children }
-                })
-                .collect_vec()
+        let column_count = placement.size.get_dim(x);
+        let column_defs = columns_defs.iter().cycle().take(column_count);
+        let mut columns = column_defs
+            .map(|axis| UnresolvedColumn { axis: *axis, children: default() })
+            .collect_vec();
+        let mut spanning_children = Vec::new();
+        let children = children.iter().zip(previous_sizes);
+        for ((child, previous_size), area) in children.zip(placement.areas) {
+            let instance = child.clone_ref();
+            let start = area.start.get_dim(x);
+            let span = area.span.get_dim(x);
+            let previous_size = *previous_size;
+            let child = GridChild { instance, start, span, previous_size };
+            if span > 1 {
+                spanning_children.push(child);
+            } else {
+                columns[start].children.push(child);
+            }
+        }
+        GridColumns { columns, spanning_children }
+    }
+
+    /// Reset the child size to the values that can be computed before resolving the columns. If
+    /// the child size is set to hug, the child layout is refreshed.
+    fn reset_grid_child_size<Dim>(&self, x: Dim, parent_const_size: f32)
+    where Dim: ResolutionDim {
+        let grow_factor = self.layout.grow_factor.get_dim(x);
+        let shrink_factor = self.layout.shrink_factor.get_dim(x);
+        match self.layout.size.get_dim(x) {
+            Size::Hug => {
+                let can_grow_or_shrink = grow_factor > 0.0 || shrink_factor > 0.0;
+                let refresh = can_grow_or_shrink || self.should_propagate_parent_layout_refresh(x);
+                if refresh {
+                    self.reset_size_to_static_values(x, parent_const_size);
+                    self.refresh_layout_internal(x, PassConfig::Default);
+                }
+            }
+            Size::Fixed(_) => self.reset_size_to_static_values(x, parent_const_size),
         }
     }
 
@@ -3893,15 +4246,16 @@ impl Model {
         &self,
         x: Dim,
         opts: &AutoLayout,
-        unresolved_columns: Vec<UnresolvedColumn>,
-    ) -> Vec<ResolvedColumn>
+        unresolved: GridColumns<UnresolvedColumn>,
+    ) -> GridColumns<ResolvedColumn>
     where
         Dim: ResolutionDim,
     {
-        let self_const_size = self.layout.size.get_dim(x).resolve_pixels_or_default();
-        let columns = unresolved_columns
+        let GridColumns { columns: unresolved_columns, mut spanning_children } = unresolved;
+        let mut columns = unresolved_columns
             .into_iter()
-            .map(|column| {
+            .enumerate()
+            .map(|(index, column)| {
                 let children = column.children;
                 let mut avg_child_grow = 0.0;
                 let mut avg_child_shrink = 0.0;
@@ -3910,40 +4264,30 @@ impl Model {
                 let mut max_child_size = 0.0;
                 let mut max_child_fr = Fraction::default();
                 for child in &children {
-                    let child_grow_factor = child.layout.grow_factor.get_dim(x);
-                    let child_shrink_factor = child.layout.shrink_factor.get_dim(x);
-
-                    match child.layout.size.get_dim(x) {
-                        Size::Hug => {
-                            let child_can_grow_or_shrink =
-                                child_grow_factor > 0.0 || child_shrink_factor > 0.0;
-                            let refresh_child = child_can_grow_or_shrink
-                                || child.should_propagate_parent_layout_refresh(x);
-                            if refresh_child {
-                                child.reset_size_to_static_values(x, self_const_size);
-                                child.refresh_layout_internal(x, PassConfig::Default);
-                            }
-                        }
-                        Size::Fixed(unit) => {
-                            max_child_fr = max(max_child_fr, unit.as_fraction_or_default());
-                            child.reset_size_to_static_values(x, self_const_size);
-                        }
-                    };
-
+                    let child_fr = child.layout.size.get_dim(x).as_fraction().unwrap_or_default();
+                    max_child_fr = max(max_child_fr, child_fr);
                     let child_margin = child.layout.margin.get_dim(x).resolve_pixels_or_default();
                     let child_size = child.layout.computed_size.get_dim(x) + child_margin.total();
                     let child_min_size =
                         child.layout.min_size.get_dim(x).resolve_pixels_or_default();
                     let child_max_size = child.layout.max_size.get_dim(x).resolve_pixels();
                     let child_max_size = child_max_size.unwrap_or(f32::INFINITY);
-                    avg_child_grow += child_grow_factor;
-                    avg_child_shrink += child_shrink_factor;
+                    avg_child_grow += child.layout.grow_factor.get_dim(x);
+                    avg_child_shrink += child.layout.shrink_factor.get_dim(x);
                     max_child_min_size = f32::max(max_child_min_size, child_min_size);
                     min_child_max_size = f32::min(min_child_max_size, child_max_size);
                     max_child_size = f32::max(max_child_size, child_size);
                 }
 
-                let child_count = children.len() as f32;
+                // Children spanning multiple columns contribute to the grow and shrink factors of
+                // all the columns they occupy. Their sizes are distributed among the columns below.
+                let mut child_count = children.len();
+                for child in spanning_children.iter().filter(|child| child.covers(index)) {
+                    avg_child_grow += child.layout.grow_factor.get_dim(x);
+                    avg_child_shrink += child.layout.shrink_factor.get_dim(x);
+                    child_count += 1;
+                }
+                let child_count = child_count.max(1) as f32;
                 let avg_child_grow = avg_child_grow / child_count;
                 let avg_child_shrink = avg_child_shrink / child_count;
 
@@ -3971,11 +4315,37 @@ impl Model {
             })
             .collect_vec();
 
+        // If the spanned columns are too small for a spanning child, the missing space is evenly
+        // distributed among the spanned columns that hug their content. Children spanning fewer
+        // columns are resolved first, like in the CSS Grid.
+        let gap = opts.gap.get_dim(x).resolve_pixels_or_default();
+        spanning_children.sort_by_key(|child| child.span);
+        for child in &spanning_children {
+            let child_margin = child.layout.margin.get_dim(x).resolve_pixels_or_default();
+            let child_size = child.layout.computed_size.get_dim(x) + child_margin.total();
+            let spanned_columns = &mut columns[child.start..child.start + child.span];
+            let spanned_gaps = (child.span - 1) as f32 * gap;
+            let spanned_size: f32 =
+                spanned_columns.iter().map(|column| column.computed_size).sum::<f32>();
+            let missing_size = child_size - spanned_size - spanned_gaps;
+            let hug_columns = spanned_columns.iter_mut().filter(|column| column.size.is_hug());
+            let hug_columns = hug_columns.collect_vec();
+            if missing_size > 0.0 && !hug_columns.is_empty() {
+                let column_extra_size = missing_size / hug_columns.len() as f32;
+                for column in hug_columns {
+                    column.computed_size += column_extra_size;
+                }
+            }
+        }
+
         if opts.reversed_columns_and_rows.get_dim(x) {
-            columns.reversed()
-        } else {
-            columns
+            let column_count = columns.len();
+            for child in &mut spanning_children {
+                child.start = column_count - child.start - child.span;
+            }
+            columns.reverse();
         }
+        GridColumns { columns, spanning_children }
     }
 
     fn shrink_or_grow_coeff(f: impl Fn(f32, f32) -> f32, total_factor: f32, space: f32) -> f32 {
@@ -3998,17 +4368,56 @@ impl Model {
     /// In order to make the code easy to understand, all variables in layout functions were named
     /// as if the code was updating horizontal layout only. In reality, the variable [`x`] can be
     /// set to either [`X`] or [`Y`] to update horizontal and vertical axis, respectively.
-    fn refresh_grid_layout<Dim>(&self, x: Dim, opts: &AutoLayout)
+    fn refresh_grid_layout<Dim>(&self, x: Dim, opts: &AutoLayout, pass_cfg: PassConfig)
     where Dim: ResolutionDim {
         let children = self.children();
         if children.is_empty() {
             return;
         }
-        let old_child_computed_sizes: Vec<f32> =
-            children.iter().map(|child| child.layout.computed_size.get_dim(x)).collect();
+        let previous_sizes =
+            children.iter().map(|child| child.layout.computed_size.get_dim(x)).collect_vec();
+        let self_const_size = self.layout.size.get_dim(x).resolve_pixels_or_default();
+        for child in &children {
+            child.reset_grid_child_size(x, self_const_size);
+        }
+        match opts.packing {
+            AutoLayoutPacking::Grid => {
+                let unresolved_columns =
+                    self.divide_children_to_columns(x, opts, &children, &previous_sizes);
+                let resolved_columns = self.resolve_columns(x, opts, unresolved_columns);
+                self.refresh_grid_columns(x, opts, resolved_columns);
+            }
+            AutoLayoutPacking::Masonry | AutoLayoutPacking::Wrap =>
+                self.refresh_lines_layout(x, opts, &children, &previous_sizes, pass_cfg),
+        }
+    }
+
+    /// The size of the grid, not including the space that will be added by growing the columns and
+    /// resolving the fraction units.
+    fn grid_static_size<Dim>(&self, x: Dim, opts: &AutoLayout, columns: &[ResolvedColumn]) -> f32
+    where Dim: ResolutionDim {
+        let gap_count = columns.len().saturating_sub(1) as f32;
+        let static_padding = self.layout.padding.get_dim(x).resolve_pixels_or_default().total();
+        let static_gap = gap_count * opts.gap.get_dim(x).resolve_pixels_or_default();
+        let columns_size: f32 = columns.iter().map(|column| column.computed_size).sum();
+        static_padding + static_gap + columns_size
+    }
 
-        let unresolved_columns = self.divide_children_to_columns(x, opts, &children);
-        let mut columns = self.resolve_columns(x, opts, unresolved_columns);
+    /// Resize the columns to fit the size of this object and place the children in them.
+    ///
+    /// # Meaning of the function parameters.
+    /// In order to make the code easy to understand, all variables in layout functions were named
+    /// as if the code was updating horizontal layout only. In reality, the variable [`x`] can be
+    /// set to either [`X`] or [`Y`] to update horizontal and vertical axis, respectively.
+    fn refresh_grid_columns<Dim>(
+        &self,
+        x: Dim,
+        opts: &AutoLayout,
+        resolved_columns: GridColumns<ResolvedColumn>,
+    ) where
+        Dim: ResolutionDim,
+    {
+        let GridColumns { mut columns, spanning_children } = resolved_columns;
 
 
         // === Compute the static size (no grow, shrink, nor fraction yet) ===
@@ -4016,15 +4425,12 @@ impl Model {
         let gap_def = opts.gap.get_dim(x);
         let padding_def = self.layout.padding.get_dim(x);
         let gap_count = (columns.len() - 1) as f32;
-        let static_padding = padding_def.resolve_pixels_or_default().total();
-        let static_gap = gap_count * gap_def.resolve_pixels_or_default();
         let self_size = self.layout.computed_size.get_dim(x);
-        let mut space_left = self_size - static_padding - static_gap;
+        let mut space_left = self_size - self.grid_static_size(x, opts, &columns);
         let mut total_grow_factor = 0.0;
         let mut total_shrink_factor = 0.0;
         let mut total_fr = Fraction::default();
         for column in &columns {
-            space_left -= column.computed_size;
             total_grow_factor += column.grow_factor;
             total_shrink_factor += column.shrink_factor;
             total_fr += column.max_child_fr_size;
@@ -4066,6 +4472,7 @@ impl Model {
         let padding = padding_def.resolve(self_size, space_left, total_fr);
         let gap = gap_def.resolve(self_size, space_left, total_fr);
         let mut pos_x = padding.start;
+        let mut column_spans = Vec::with_capacity(columns.len());
         for column in &columns {
             let fr_diff = if total_fr > Fraction::from(0.0) {
                 space_left * column.max_child_fr_size.unchecked_raw() / total_fr.unchecked_raw()
@@ -4075,65 +4482,280 @@ impl Model {
             let column_size = column.computed_size + fr_diff;
             let column_size = f32::max(column.min_size, column_size);
             let column_size = f32::min(column.max_size, column_size);
-            for (child, previous_size) in column.children.iter().zip(&old_child_computed_sizes) {
-                let child_base_size = child.layout.computed_size.get_dim(x);
-                let child_unused_space = f32::max(0.0, column_size - child_base_size);
-                let unresolved_margin = child.layout.margin.get_dim(x);
-                let margin_fr = unresolved_margin.as_fraction_or_default().total();
-                let margin = unresolved_margin.resolve(self_size, child_unused_space, margin_fr);
-                let column_size_minus_margin = column_size - margin.start - margin.end;
-
-                let child_can_grow = child.layout.grow_factor.get_dim(x) > 0.0;
-                let child_can_shrink = child.layout.shrink_factor.get_dim(x) > 0.0;
-                if child_can_grow && child_base_size < column_size_minus_margin {
-                    let size = f32::min(
-                        column_size_minus_margin,
-                        child.layout.max_size.get_dim(x).resolve_pixels_or_default(),
-                    );
-                    child.layout.computed_size.set_dim(x, size);
-                }
-                if let Some(fr) = child.layout.size.get_dim(x).as_fraction() {
-                    if fr > Fraction::from(0.0) {
-                        let size = f32::min(
-                            column_size_minus_margin,
-                            child.layout.max_size.get_dim(x).resolve_pixels_or_default(),
-                        );
-                        child.layout.computed_size.set_dim(x, size);
-                    }
-                }
-                if child_can_shrink && child_base_size > column_size_minus_margin {
-                    let size = f32::max(
-                        column_size_minus_margin,
-                        child.layout.min_size.get_dim(x).resolve_pixels_or_default(),
-                    );
-                    child.layout.computed_size.set_dim(x, size);
-                }
+            for child in &column.children {
+                self.place_grid_child(x, opts, child, pos_x, column_size, self_size);
+            }
+            column_spans.push(pos_x..pos_x + column_size);
+            pos_x += column_size + gap;
+        }
+        for child in &spanning_children {
+            let first_column = &column_spans[child.start];
+            let last_column = &column_spans[child.start + child.span - 1];
+            let area_size = last_column.end - first_column.start;
+            self.place_grid_child(x, opts, child, first_column.start, area_size, self_size);
+        }
+    }
 
-                let child_size_changed = child_base_size != child.layout.computed_size.get_dim(x);
-                let child_not_computed =
-                    child.layout.size.get_dim(x).is_fixed() && child.should_refresh_layout();
-                if child_size_changed || child_not_computed {
-                    // Child size changed. There is one case when this might be a second call to
-                    // refresh layout of the same child. If the child size is set to hug, the
-                    // child can grow, and the column size is greater than earlier computed hugged
-                    // child size, we need to refresh the child layout again.
-                    child.refresh_layout_internal(x, PassConfig::DoNotHugDirectChildren);
-                }
+    /// Resize the child to fit the grid area it occupies, if the child can grow or shrink, and
+    /// align it within the area.
+    ///
+    /// # Meaning of the function parameters.
+    /// In order to make the code easy to understand, all variables in layout functions were named
+    /// as if the code was updating horizontal layout only. In reality, the variable [`x`] can be
+    /// set to either [`X`] or [`Y`] to update horizontal and vertical axis, respectively.
+    fn place_grid_child<Dim>(
+        &self,
+        x: Dim,
+        opts: &AutoLayout,
+        child: &GridChild,
+        pos_x: f32,
+        column_size: f32,
+        self_size: f32,
+    ) where
+        Dim: ResolutionDim,
+    {
+        let child_base_size = child.layout.computed_size.get_dim(x);
+        let child_unused_space = f32::max(0.0, column_size - child_base_size);
+        let unresolved_margin = child.layout.margin.get_dim(x);
+        let margin_fr = unresolved_margin.as_fraction_or_default().total();
+        let margin = unresolved_margin.resolve(self_size, child_unused_space, margin_fr);
+        let column_size_minus_margin = column_size - margin.start - margin.end;
+
+        let child_can_grow = child.layout.grow_factor.get_dim(x) > 0.0;
+        let child_can_shrink = child.layout.shrink_factor.get_dim(x) > 0.0;
+        if child_can_grow && child_base_size < column_size_minus_margin {
+            let size = f32::min(
+                column_size_minus_margin,
+                child.layout.max_size.get_dim(x).resolve_pixels_or_default(),
+            );
+            child.layout.computed_size.set_dim(x, size);
+        }
+        if let Some(fr) = child.layout.size.get_dim(x).as_fraction() {
+            if fr > Fraction::from(0.0) {
+                let size = f32::min(
+                    column_size_minus_margin,
+                    child.layout.max_size.get_dim(x).resolve_pixels_or_default(),
+                );
+                child.layout.computed_size.set_dim(x, size);
+            }
+        }
+        if child_can_shrink && child_base_size > column_size_minus_margin {
+            let size = f32::max(
+                column_size_minus_margin,
+                child.layout.min_size.get_dim(x).resolve_pixels_or_default(),
+            );
+            child.layout.computed_size.set_dim(x, size);
+        }
 
-                let child_width = child.layout.computed_size.get_dim(x);
-                let child_unused_space = f32::max(0.0, column_size_minus_margin - child_width);
-                let def_alignment = opts.children_alignment.get_dim(x);
-                let alignment = child.layout.alignment.get().get_dim(x).unwrap_or(def_alignment);
-                let child_offset = child_unused_space * alignment.normalized();
-                let child_left = pos_x + child_offset + margin.start;
-                child.set_position_dim(x, child_left);
-                if *previous_size != child_width {
-                    child.dirty.computed_size.set();
+        let child_size_changed = child_base_size != child.layout.computed_size.get_dim(x);
+        let child_not_computed =
+            child.layout.size.get_dim(x).is_fixed() && child.should_refresh_layout();
+        if child_size_changed || child_not_computed {
+            // Child size changed. There is one case when this might be a second call to
+            // refresh layout of the same child. If the child size is set to hug, the
+            // child can grow, and the column size is greater than earlier computed hugged
+            // child size, we need to refresh the child layout again.
+            child.refresh_layout_internal(x, PassConfig::DoNotHugDirectChildren);
+        }
+
+        let child_width = child.layout.computed_size.get_dim(x);
+        let child_unused_space = f32::max(0.0, column_size_minus_margin - child_width);
+        let def_alignment = opts.children_alignment.get_dim(x);
+        let alignment = child.layout.alignment.get().get_dim(x).unwrap_or(def_alignment);
+        let child_offset = child_unused_space * alignment.normalized();
+        let child_left = pos_x + child_offset + margin.start;
+        child.set_position_dim(x, child_left);
+        if child.previous_size != child_width {
+            child.dirty.computed_size.set();
+        }
+    }
+
+    /// Refresh the layout of the 'masonry' and 'wrap' packing. Along the primary axis of the flow,
+    /// every line is laid out independently, like a grid with a single row. Along the other axis,
+    /// lines are laid out like the rows of a grid.
+    ///
+    /// # Meaning of the function parameters.
+    /// In order to make the code easy to understand, all variables in layout functions were named
+    /// as if the code was updating horizontal layout only. In reality, the variable [`x`] can be
+    /// set to either [`X`] or [`Y`] to update horizontal and vertical axis, respectively.
+    fn refresh_lines_layout<Dim>(
+        &self,
+        x: Dim,
+        opts: &AutoLayout,
+        children: &[Instance],
+        previous_sizes: &[f32],
+        pass_cfg: PassConfig,
+    ) where
+        Dim: ResolutionDim,
+    {
+        let lines = self.divide_children_to_lines(x, opts, children, pass_cfg);
+        let column_defs = opts.columns_and_rows.get_dim(x);
+        let grid_child = |index: usize, start: usize| {
+            let instance = children[index].clone_ref();
+            let previous_size = previous_sizes[index];
+            GridChild { instance, start, span: 1, previous_size }
+        };
+        if x.matches_flow_direction(opts.flow) {
+            let lines = lines
+                .into_iter()
+                .map(|line| {
+                    let columns = line
+                        .zip(column_defs.iter().cycle())
+                        .enumerate()
+                        .map(|(column_index, (child_index, axis))| {
+                            let children = vec![grid_child(child_index, column_index)];
+                            UnresolvedColumn { axis: *axis, children }
+                        })
+                        .collect_vec();
+                    let spanning_children = default();
+                    self.resolve_columns(x, opts, GridColumns { columns, spanning_children })
+                })
+                .collect_vec();
+            let hug = pass_cfg != PassConfig::DoNotHugDirectChildren;
+            if hug && self.layout.size.get_dim(x).is_hug() {
+                let line_sizes =
+                    lines.iter().map(|line| self.grid_static_size(x, opts, &line.columns));
+                let max_line_size = line_sizes.fold(0.0, f32::max);
+                self.layout.computed_size.update_dim(x, |t| t.max(max_line_size));
+            }
+            for line in lines {
+                self.refresh_grid_columns(x, opts, line);
+            }
+        } else {
+            let columns = lines
+                .into_iter()
+                .zip(column_defs.iter().cycle())
+                .enumerate()
+                .map(|(column_index, (line, axis))| {
+                    let children = line.map(|index| grid_child(index, column_index)).collect();
+                    UnresolvedColumn { axis: *axis, children }
+                })
+                .collect_vec();
+            let spanning_children = default();
+            let unresolved_columns = GridColumns { columns, spanning_children };
+            let resolved_columns = self.resolve_columns(x, opts, unresolved_columns);
+            self.refresh_grid_columns(x, opts, resolved_columns);
+        }
+    }
+
+    /// Divide the children into ranges forming the lines of the 'masonry' and 'wrap' packing. The
+    /// lines of the 'wrap' packing are computed in the horizontal pass and reused in the vertical
+    /// one, so both passes use the same lines.
+    fn divide_children_to_lines<Dim>(
+        &self,
+        x: Dim,
+        opts: &AutoLayout,
+        children: &[Instance],
+        pass_cfg: PassConfig,
+    ) -> Vec<Range<usize>>
+    where
+        Dim: ResolutionDim,
+    {
+        let child_count = children.len();
+        match opts.packing {
+            AutoLayoutPacking::Wrap => {
+                let stored_lines =
+                    x.last_pass().then(|| self.layout.wrapped_lines.borrow().clone());
+                let is_valid = |lines: &Vec<Range<usize>>| {
+                    lines.last().map(|line| line.end) == Some(child_count)
+                };
+                stored_lines.filter(is_valid).unwrap_or_else(|| {
+                    let lines = self.wrap_children_to_lines(opts, children, pass_cfg);
+                    *self.layout.wrapped_lines.borrow_mut() = lines.clone();
+                    lines
+                })
+            }
+            _ => {
+                let line_length = opts.primary_count().unwrap_or(child_count).max(1);
+                let starts = (0..child_count).step_by(line_length);
+                starts.map(|start| start..child_count.min(start + line_length)).collect()
+            }
+        }
+    }
+
+    /// Divide the children into lines of the 'wrap' packing, based on their sizes along the primary
+    /// axis of the flow. If this object hugs its content, the lines are limited by its maximum
+    /// size only.
+    ///
+    /// In the 'column' flow, the lines are computed in the horizontal pass, before the heights are
+    /// resolved. Thus, the heights of children are refreshed first, and if the height of this
+    /// object is not set in pixels, nor hugs the content, the height resolved in the previous
+    /// layout refresh is used.
+    fn wrap_children_to_lines(
+        &self,
+        opts: &AutoLayout,
+        children: &[Instance],
+        pass_cfg: PassConfig,
+    ) -> Vec<Range<usize>> {
+        let max_size = self.layout.max_size.get();
+        match opts.flow {
+            AutoLayoutFlow::Row => {
+                let hug = pass_cfg != PassConfig::DoNotHugDirectChildren;
+                let hug = hug && self.layout.size.get_dim(X).is_hug();
+                let self_size = if hug {
+                    max_size.x.resolve_pixels().unwrap_or(f32::INFINITY)
+                } else {
+                    self.layout.computed_size.get_dim(X)
+                };
+                self.wrap_children_to_lines_along(X, opts, children, self_size)
+            }
+            AutoLayoutFlow::Column => {
+                let size = self.layout.size.get_dim(Y);
+                let self_const_size = size.resolve_pixels_or_default();
+                for child in children {
+                    let previous_size = child.layout.computed_size.get_dim(Y);
+                    child.reset_grid_child_size(Y, self_const_size);
+                    if child.layout.computed_size.get_dim(Y) != previous_size {
+                        child.dirty.computed_size.set();
+                    }
                 }
+                let self_size = match size {
+                    Size::Hug => max_size.y.resolve_pixels().unwrap_or(f32::INFINITY),
+                    Size::Fixed(_) =>
+                        size.as_pixels().unwrap_or_else(|| self.layout.computed_size.get_dim(Y)),
+                };
+                self.wrap_children_to_lines_along(Y, opts, children, self_size)
             }
-            pos_x += column_size + gap;
         }
     }
+
+    /// Place children in a line as long as they fit in the given size of this object, and then
+    /// start the next line. Every line contains at least one child.
+    fn wrap_children_to_lines_along<Dim>(
+        &self,
+        x: Dim,
+        opts: &AutoLayout,
+        children: &[Instance],
+        self_size: f32,
+    ) -> Vec<Range<usize>>
+    where
+        Dim: ResolutionDim,
+    {
+        let padding = self.layout.padding.get_dim(x).resolve_pixels_or_default().total();
+        let gap = opts.gap.get_dim(x).resolve_pixels_or_default();
+        let max_line_length = opts.primary_count().unwrap_or(usize::MAX).max(1);
+        let space = self_size - padding;
+        let mut lines = Vec::new();
+        let mut line_start = 0;
+        let mut line_size = 0.0;
+        for (index, child) in children.iter().enumerate() {
+            let child_margin = child.layout.margin.get_dim(x).resolve_pixels_or_default();
+            let child_size = child.layout.computed_size.get_dim(x) + child_margin.total();
+            let line_length = index - line_start;
+            let new_line_size =
+                if line_length == 0 { child_size } else { line_size + gap + child_size };
+            let line_full = line_length >= max_line_length || new_line_size > space;
+            if line_length > 0 && line_full {
+                lines.push(line_start..index);
+                line_start = index;
+                line_size = child_size;
+            } else {
+                line_size = new_line_size;
+            }
+        }
+        lines.push(line_start..children.len());
+        lines
+    }
 }
 
 
@@ -5522,6 +6144,7 @@ mod layout_tests {
     gen_test_flat_children!(1[1]);
     gen_test_flat_children!(2 [1,2]);
     gen_test_flat_children!(3 [1,2,3]);
+    gen_test_flat_children!(5 [1,2,3,4,5]);
 
 
     // === Tests ===
@@ -6757,4 +7380,389 @@ mod layout_tests {
         root.update(&world.default_scene);
         assert_eq!(root.computed_size(), Vector2(10.0, 10.0));
     }
+
+    /// ```text
+    /// ╔ root ═════════ ▶ ◀ ════════════╗
+    /// ║ ╭───── ▶ ◀ ─────┬──── ▶ ◀ ────╮ ║
+    /// ║ │  ╭ node2 ╮    ┆  ╭ node3 ╮  │ ║
+    /// ║ │  │       │    ┆  │       │  ▼ ║
+    /// ║ │  ╰───────╯    ┆  ╰───────╯  ▲ ▼
+    /// ║ ├╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌┴╌╌╌╌╌╌╌╌╌╌╌╌╌┤ ▲
+    /// ║ │  ╭ node1 ────────────────╮  ▼ ║
+    /// ║ │  ╰────────────────────────╯  ▲ ║
+    /// ║ ╰─────────────────────────────╯ ║
+    /// ╚═════════════════════════════════╝
+    /// ```
+    #[test]
+    fn test_grid_layout_with_column_span() {
+        let test = TestFlatChildren3::new();
+        test.root.use_auto_layout().set_column_count(2);
+        test.node1.set_size((5.0, 1.0)).set_column_span(2);
+        test.node2.set_size((2.0, 2.0));
+        test.node3.set_size((1.0, 3.0));
+        test.run(|| {
+            // The missing space is distributed evenly among the spanned columns: 2 + 1 -> 3 + 2.
+            test.assert_root_computed_size(5.0, 4.0)
+                .assert_node1_computed_size(5.0, 1.0)
+                .assert_node2_computed_size(2.0, 2.0)
+                .assert_node3_computed_size(1.0, 3.0)
+                .assert_root_position(0.0, 0.0)
+                .assert_node1_position(0.0, 0.0)
+                .assert_node2_position(0.0, 1.0)
+                .assert_node3_position(3.0, 1.0);
+        });
+    }
+
+    /// ```text
+    /// ╔ root ════════════════════════════════╗
+    /// ║ ╭──────── ▶ ◀ ─────┬▷ ───── ▶ ◀ ─────┬▷ ║
+    /// ║ │  ╭ node2 ╮       ┆  ╭ node3 ╮     │ ║
+    /// ║ │  ╰───────╯       ┆  ╰───────╯     ▼ ▼
+    /// ║ ├╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌┴╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌┤ ▲
+    /// ║ │  ╭ node1 ─────────────────────┬▷  │ ║
+    /// ║ │  ╰──────────────────────────────╯  │ ║
+    /// ║ ╰───────────────────────────────────╯ ║
+    /// ╚══════════════════════════════════════╝
+    ///                    10
+    /// ```
+    #[test]
+    fn test_grid_layout_with_column_span_that_grows() {
+        let test = TestFlatChildren3::new();
+        test.root.use_auto_layout().set_column_count(2).set_size_x(10.0);
+        test.node1.set_size((2.0, 1.0)).set_column_span(2).allow_grow_x();
+        test.node2.set_size((2.0, 2.0));
+        test.node3.set_size((3.0, 2.0));
+        test.run(|| {
+            // Both columns inherit the grow factor of 0.5 and grow by 2.5.
+            test.assert_root_computed_size(10.0, 3.0)
+                .assert_node1_computed_size(10.0, 1.0)
+                .assert_node2_computed_size(2.0, 2.0)
+                .assert_node3_computed_size(3.0, 2.0)
+                .assert_root_position(0.0, 0.0)
+                .assert_node1_position(0.0, 0.0)
+                .assert_node2_position(0.0, 1.0)
+                .assert_node3_position(4.5, 1.0);
+        });
+    }
+
+    /// ```text
+    /// ╔ root ═════════════╗
+    /// ║ ╭───────┬───────╮ ║
+    /// ║ │ node2 ┆ node3 │ ║
+    /// ║ ├╌╌╌╌╌╌╌┴╌╌╌╌╌╌╌┤ ║
+    /// ║ │ ╭ node1 ◁┬──╮ │ ║
+    /// ║ │ ╰─────────────╯ │
+    /// ║ ╰───────────────╯ ║
+    /// ╚═══════════════════╝
+    ///           4
+    /// ```
+    #[test]
+    fn test_grid_layout_with_column_span_that_shrinks() {
+        let test = TestFlatChildren3::new();
+        test.root.use_auto_layout().set_column_count(2).set_size_x(4.0);
+        test.node1.set_size((6.0, 1.0)).set_column_span(2).allow_shrink_x();
+        test.node2.set_size((2.0, 1.0));
+        test.node3.set_size((2.0, 1.0));
+        test.run(|| {
+            test.assert_root_computed_size(4.0, 2.0)
+                .assert_node1_computed_size(4.0, 1.0)
+                .assert_node2_computed_size(2.0, 1.0)
+                .assert_node3_computed_size(2.0, 1.0)
+                .assert_root_position(0.0, 0.0)
+                .assert_node1_position(0.0, 0.0)
+                .assert_node2_position(0.0, 1.0)
+                .assert_node3_position(2.0, 1.0);
+        });
+    }
+
+    /// ```text
+    /// ╔ root ═══════════════════════════════════╗
+    /// ║ ╭──────── 1fr ───────┬─────── 2fr ─────╮ ║
+    /// ║ │ ╭ node2 ╮          ┆ ╭ node3 ╮       │ ║
+    /// ║ │ ╰───────╯          ┆ ╰───────╯       │ ║
+    /// ║ ├╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌┴╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌┤ ║
+    /// ║ │           ╭ node1 ──────╮            │ ║
+    /// ║ │           ╰─────────────╯            │ ║
+    /// ║ ╰──────────────────────────────────────╯ ║
+    /// ╚══════════════════════════════════════════╝
+    ///                      12
+    /// ```
+    #[test]
+    fn test_grid_layout_with_column_span_in_fractional_columns() {
+        let test = TestFlatChildren3::new();
+        test.root.use_auto_layout().set_column_count(2).set_size_x(12.0);
+        test.root.first_column().set_size(1.fr());
+        test.root.add_column().set_size(2.fr());
+        test.node1.set_size((4.0, 1.0)).set_column_span(2).set_alignment_center();
+        test.node2.set_size((1.0, 1.0));
+        test.node3.set_size((1.0, 1.0));
+        test.run(|| {
+            test.assert_root_computed_size(12.0, 2.0)
+                .assert_node1_computed_size(4.0, 1.0)
+                .assert_node2_computed_size(1.0, 1.0)
+                .assert_node3_computed_size(1.0, 1.0)
+                .assert_root_position(0.0, 0.0)
+                .assert_node1_position(4.0, 0.0)
+                .assert_node2_position(0.0, 1.0)
+                .assert_node3_position(4.0, 1.0);
+        });
+    }
+
+    /// ```text
+    /// ╔ root ══════════ ▶ ◀ ════════════╗
+    /// ║ ╭──── ▶ ◀ ────┬────── ▶ ◀ ────╮ ║
+    /// ║ │  ╭ node2 ───────────────┬▷ ▼ ║
+    /// ║ │  ╰─────────────────────────╯ ▲ ▼
+    /// ║ ├╌╌╌╌╌╌╌╌╌╌╌╌╌┬╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌┤ ▲
+    /// ║ │  ╭ node3 ╮  ┆  ╭ node1 ╮    │ ║
+    /// ║ │  │       │  ┆  │       │    ▼ ║
+    /// ║ │  ╰───────╯  ┆  ╰───────╯    ▲ ║
+    /// ║ ╰─────────────┴───────────────╯ ║
+    /// ╚═════════════════════════════════╝
+    /// ```
+    #[test]
+    fn test_grid_layout_with_named_areas() {
+        let test = TestFlatChildren3::new();
+        let template = GridTemplateAreas::new(["header header", "side main"]).unwrap();
+        test.root.use_auto_layout().set_grid_template_areas(template);
+        test.node1.set_grid_area("main").set_size((3.0, 3.0));
+        test.node2.set_grid_area("header").set_size((2.0, 1.0)).allow_grow_x();
+        test.node3.set_grid_area("side").set_size((1.0, 2.0));
+        test.run(|| {
+            test.assert_root_computed_size(4.0, 4.0)
+                .assert_node1_computed_size(3.0, 3.0)
+                .assert_node2_computed_size(4.0, 1.0)
+                .assert_node3_computed_size(1.0, 2.0)
+                .assert_root_position(0.0, 0.0)
+                .assert_node1_position(1.0, 0.0)
+                .assert_node2_position(0.0, 3.0)
+                .assert_node3_position(0.0, 0.0);
+        });
+    }
+
+    /// ```text
+    /// ╔ root ══ ▶ ◀ ═════════╗
+    /// ║ ╭─ ▶ ◀ ─┬── ▶ ◀ ──╮  ║
+    /// ║ │ ╭ n1 ╮┆ ╭ node3 ╮ ▼ ║
+    /// ║ │ │    │┆ ╰───────╯ ▲ ▼
+    /// ║ ├╌│╌╌╌╌│┼╌╌╌╌╌╌╌╌╌╌┤  ▲
+    /// ║ │ │    │┆ ╭ node2 ╮ ▼ ║
+    /// ║ │ ╰────╯┆ ╰───────╯ ▲ ║
+    /// ║ ╰───────┴──────────╯  ║
+    /// ╚═══════════════════════╝
+    /// ```
+    #[test]
+    fn test_grid_layout_with_row_span_and_automatic_placement() {
+        let test = TestFlatChildren3::new();
+        let template = GridTemplateAreas::new(["a .", "a ."]).unwrap();
+        test.root.use_auto_layout().set_grid_template_areas(template);
+        test.node1.set_grid_area("a").set_size((1.0, 5.0));
+        test.node2.set_size((2.0, 2.0));
+        test.node3.set_size((2.0, 2.0));
+        test.run(|| {
+            // The missing space is distributed evenly among the spanned rows: 2 + 2 -> 2.5 + 2.5.
+            test.assert_root_computed_size(3.0, 5.0)
+                .assert_node1_computed_size(1.0, 5.0)
+                .assert_node2_computed_size(2.0, 2.0)
+                .assert_node3_computed_size(2.0, 2.0)
+                .assert_root_position(0.0, 0.0)
+                .assert_node1_position(0.0, 0.0)
+                .assert_node2_position(1.0, 0.0)
+                .assert_node3_position(1.0, 2.5);
+        });
+    }
+
+    /// ```text
+    /// ╔ root ════════════ ▶ ◀ ════════════════╗
+    /// ║ ╭─── ▶ ◀ ───┬─── ▶ ◀ ───┬─── ▶ ◀ ───╮ ║
+    /// ║ │ ╭ node1 ╮ ┆           ┆ ╭ node3 ╮ ▼ ║
+    /// ║ │ │       │ ┆           ┆ │       │ ▲ ▼
+    /// ║ ├╌│╌╌╌╌╌╌╌│╌┼╌╌╌╌╌╌╌╌╌╌╌┼╌│╌╌╌╌╌╌╌│╌┤ ▲
+    /// ║ │ │       │ ┆ ╭ node2 ╮ ┆ │       │ ▼ ║
+    /// ║ │ ╰───────╯ ┆ ╰───────╯ ┆ ╰───────╯ ▲ ║
+    /// ║ ╰───────────┴───────────┴───────────╯ ║
+    /// ╚═══════════════════════════════════════╝
+    /// ```
+    #[test]
+    fn test_grid_layout_with_row_span_and_column_flow() {
+        let test = TestFlatChildren3::new();
+        test.root.use_auto_layout().set_row_count(2).set_column_flow();
+        test.node1.set_size((2.0, 3.0)).set_row_span(2);
+        test.node2.set_size((2.0, 1.0));
+        test.node3.set_size((2.0, 3.0)).set_row_span(2);
+        test.run(|| {
+            // The third node does not fit in the second column, below the second node.
+            test.assert_root_computed_size(6.0, 3.0)
+                .assert_node1_computed_size(2.0, 3.0)
+                .assert_node2_computed_size(2.0, 1.0)
+                .assert_node3_computed_size(2.0, 3.0)
+                .assert_root_position(0.0, 0.0)
+                .assert_node1_position(0.0, 0.0)
+                .assert_node2_position(2.0, 0.0)
+                .assert_node3_position(4.0, 0.0);
+        });
+    }
+
+    #[test]
+    fn test_grid_template_areas() {
+        let template = GridTemplateAreas::new(["a a b", "c . b"]).unwrap();
+        assert_eq!(template.size(), Vector2(3, 2));
+        assert_eq!(template.area("a"), Some(GridArea::new(Vector2(0, 1), Vector2(2, 1))));
+        assert_eq!(template.area("b"), Some(GridArea::new(Vector2(2, 0), Vector2(1, 2))));
+        assert_eq!(template.area("c"), Some(GridArea::new(Vector2(0, 0), Vector2(1, 1))));
+        assert_eq!(template.area("."), None);
+
+        let empty = GridTemplateAreas::new([]);
+        assert_eq!(empty, Err(GridTemplateError::Empty));
+        let mismatched = GridTemplateAreas::new(["a a", "b"]);
+        let expected =
+            GridTemplateError::RowLengthMismatch { row: 1, expected: 2, found: 1 };
+        assert_eq!(mismatched, Err(expected));
+        let not_rectangular = GridTemplateAreas::new(["a a", "a b"]);
+        assert_eq!(not_rectangular, Err(GridTemplateError::NotRectangular("a".into())));
+    }
+
+    /// ```text
+    /// ╔ root ══════════════ ▶ ◀ ═══════════════╗
+    /// ║ ╭─── ▶ ◀ ───┬────── ▶ ◀ ──────╮        ║
+    /// ║ │ ╭ node4 ╮ ┆ ╭ node5 ──────╮ ▼        ▼
+    /// ║ │ ╰───────╯ ┆ ╰─────────────╯ ▲        ▲
+    /// ║ ├╌╌╌╌╌╌╌╌╌╌╌┴╌╌╌╌╌┬╌╌╌╌╌╌╌╌╌┼╌╌╌╌╌╌╌╌╌╌┤ ▼
+    /// ║ │ ╭ node1 ──────╮ ┆ ╭ node2 ╮ ┆ ╭ node3 ╮ ▲
+    /// ║ │ │             │ ┆ ╰───────╯ ┆ │       │ ▼
+    /// ║ │ ╰─────────────╯ ┆           ┆ ╰───────╯ ▲
+    /// ║ ╰────── ▶ ◀ ──────┴─── ▶ ◀ ───┴─── ▶ ◀ ───╯ ║
+    /// ╚════════════════════════════════════════════╝
+    /// ```
+    #[test]
+    fn test_masonry_layout() {
+        let test = TestFlatChildren5::new();
+        test.root.use_auto_layout().set_masonry_packing().set_column_count(3);
+        test.node1.set_size((4.0, 2.0));
+        test.node2.set_size((2.0, 1.0));
+        test.node3.set_size((3.0, 2.0));
+        test.node4.set_size((2.0, 1.0));
+        test.node5.set_size((4.0, 1.0));
+        test.run(|| {
+            test.assert_root_computed_size(9.0, 3.0)
+                .assert_node1_position(0.0, 0.0)
+                .assert_node2_position(4.0, 0.0)
+                .assert_node3_position(6.0, 0.0)
+                .assert_node4_position(0.0, 2.0)
+                .assert_node5_position(2.0, 2.0);
+        });
+
+        test.root.reverse_columns().reverse_rows();
+        test.run(|| {
+            test.assert_root_computed_size(9.0, 3.0)
+                .assert_node1_position(5.0, 1.0)
+                .assert_node2_position(3.0, 1.0)
+                .assert_node3_position(0.0, 1.0)
+                .assert_node4_position(4.0, 0.0)
+                .assert_node5_position(0.0, 0.0);
+        });
+    }
+
+    /// ```text
+    /// ╔ root ═════════════════════════════════════════╗
+    /// ║ ╭─── ▶ ◀ ───┬──────── ▶ ◀ ────────╮           ║
+    /// ║ │ ╭ node4 ╮ ┆ ╭ node5 ──────────╮ ▼           ▼
+    /// ║ │ ╰───────╯ ┆ ╰────────────────╯ ▲           ▲
+    /// ║ ├╌╌╌╌╌╌╌╌╌╌╌┴╌╌╌╌┬╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌┴┬╌╌╌╌╌╌╌╌╌╌╌┤
+    /// ║ │ ╭ node1 ─────╮ ┆ ╭ node2 ───┬▷ ╮ ┆ ╭ node3 ╮ ▼
+    /// ║ │ ╰────────────╯ ┆ ╰────────────╯ ┆ ╰───────╯ ▲
+    /// ║ ╰───── ▶ ◀ ──────┴────── ▶ ◀ ─────┴─── ▶ ◀ ───╯ ║
+    /// ╚═══════════════════════════════════════════════╝
+    ///                         10
+    /// ```
+    #[test]
+    fn test_wrap_layout() {
+        let test = TestFlatChildren5::new();
+        test.root.use_auto_layout().set_wrap_packing().set_size_x(10.0);
+        test.node1.set_size((4.0, 1.0));
+        test.node2.set_size((2.0, 1.0)).allow_grow_x();
+        test.node3.set_size((3.0, 1.0));
+        test.node4.set_size((3.0, 1.0));
+        test.node5.set_size((6.0, 1.0));
+        test.run(|| {
+            // The second node is sized to 2 when wrapping, and then it grows to fill the row.
+            test.assert_root_computed_size(10.0, 2.0)
+                .assert_node2_computed_size(3.0, 1.0)
+                .assert_node1_position(0.0, 0.0)
+                .assert_node2_position(4.0, 0.0)
+                .assert_node3_position(7.0, 0.0)
+                .assert_node4_position(0.0, 1.0)
+                .assert_node5_position(3.0, 1.0);
+        });
+
+        test.node2.set_size_x(2.0).set_grow_factor_x(0.0);
+        test.root.set_gap((1.0, 1.0));
+        test.run(|| {
+            test.assert_root_computed_size(10.0, 5.0)
+                .assert_node2_computed_size(2.0, 1.0)
+                .assert_node1_position(0.0, 0.0)
+                .assert_node2_position(5.0, 0.0)
+                .assert_node3_position(0.0, 2.0)
+                .assert_node4_position(4.0, 2.0)
+                .assert_node5_position(0.0, 4.0);
+        });
+    }
+
+    #[test]
+    fn test_wrap_layout_hug() {
+        let test = TestFlatChildren5::new();
+        test.root.use_auto_layout().set_wrap_packing();
+        test.node1.set_size((4.0, 1.0));
+        test.node2.set_size((2.0, 1.0));
+        test.node3.set_size((3.0, 1.0));
+        test.node4.set_size((3.0, 1.0));
+        test.node5.set_size((6.0, 1.0));
+        test.run(|| {
+            test.assert_root_computed_size(18.0, 1.0)
+                .assert_node4_position(9.0, 0.0)
+                .assert_node5_position(12.0, 0.0);
+        });
+
+        // The object hugging its children wraps them only when its maximum size is exceeded.
+        test.root.set_max_size_x(8.0);
+        test.run(|| {
+            test.assert_root_computed_size(6.0, 3.0)
+                .assert_node1_position(0.0, 0.0)
+                .assert_node2_position(4.0, 0.0)
+                .assert_node3_position(0.0, 1.0)
+                .assert_node4_position(3.0, 1.0)
+                .assert_node5_position(0.0, 2.0);
+        });
+    }
+
+    /// ```text
+    /// ╔ root ══════════════════════════════════╗
+    /// ║ ╭──── ▶ ◀ ────┬── ▶ ◀ ──┬─── ▶ ◀ ───╮ ║
+    /// ║ │ ╭ node2 ─╮  ┆ ╭ n4 ╮  ┆           │ ║
+    /// ║ │ ╰────────╯  ┆ │    │  ┆           │ ║
+    /// ║ │ ╭ n1 ╮      ┆ │    │  ┆           │ ║ 3
+    /// ║ │ │    │      ┆ ╰────╯  ┆           │ ║
+    /// ║ │ │    │      ┆ ╭ n3 ╮  ┆ ╭ node5 ╮ │ ║
+    /// ║ │ ╰────╯      ┆ ╰────╯  ┆ ╰───────╯ │ ║
+    /// ║ ╰─────────────┴─────────┴───────────╯ ║
+    /// ╚═══════════════════════════════════════╝
+    /// ```
+    #[test]
+    fn test_wrap_layout_with_column_flow() {
+        let test = TestFlatChildren5::new();
+        test.root.use_auto_layout().set_wrap_packing().set_column_flow().set_size_y(3.0);
+        test.node1.set_size((1.0, 2.0));
+        test.node2.set_size((2.0, 1.0));
+        test.node3.set_size((1.0, 1.0));
+        test.node4.set_size((1.0, 2.0));
+        test.node5.set_size((3.0, 1.0));
+        test.run(|| {
+            test.assert_root_computed_size(6.0, 3.0)
+                .assert_node1_position(0.0, 0.0)
+                .assert_node2_position(0.0, 2.0)
+                .assert_node3_position(2.0, 0.0)
+                .assert_node4_position(2.0, 1.0)
+                .assert_node5_position(3.0, 0.0);
+        });
+    }
 }
@@ -254,3 +254,133 @@ macro_rules! with_display_object_side_spacing_matrix {
         $f! { $([$($args)*])? [[x left start] [x right end] [y bottom start] [y top end]] }
     }
 }
+
+
+
+// ================
+// === GridArea ===
+// ================
+
+/// A rectangular area of the grid: the first column and row it occupies and the number of columns
+/// and rows it spans. The rows are counted from the bottom, like all rows of the grid layout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub struct GridArea {
+    pub start: Vector2<usize>,
+    pub span:  Vector2<usize>,
+}
+
+impl GridArea {
+    /// Constructor.
+    pub fn new(start: Vector2<usize>, span: Vector2<usize>) -> Self {
+        Self { start, span }
+    }
+
+    /// The index of the column/row after the last column/row of the area.
+    pub fn end(self) -> Vector2<usize> {
+        self.start + self.span
+    }
+
+    /// All the cells of the area, as column and row indices.
+    pub fn cells(self) -> impl Iterator<Item = Vector2<usize>> {
+        let end = self.end();
+        (self.start.y..end.y).flat_map(move |y| (self.start.x..end.x).map(move |x| Vector2(x, y)))
+    }
+}
+
+
+
+// =========================
+// === GridTemplateAreas ===
+// =========================
+
+/// Named areas of the grid layout, similar to the CSS `grid-template-areas` property. The template
+/// is defined by rows of whitespace-separated cell names, listed from the top row to the bottom
+/// one. Cells with the same name form an area, which has to be rectangular. Cells named `.` do not
+/// belong to any area. For example, the following template defines a header spanning two columns,
+/// and a sidebar next to the main content below it:
+///
+/// ```text
+/// header header
+/// side   main
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GridTemplateAreas {
+    size:  Vector2<usize>,
+    areas: HashMap<ImString, GridArea>,
+}
+
+impl GridTemplateAreas {
+    /// Parse the template rows, listed from the top row to the bottom one.
+    pub fn new<'a>(rows: impl IntoIterator<Item = &'a str>) -> Result<Self, GridTemplateError> {
+        let rows = rows.into_iter().map(|row| row.split_whitespace().collect_vec()).collect_vec();
+        let column_count = rows.first().map_or(0, |row| row.len());
+        if column_count == 0 {
+            return Err(GridTemplateError::Empty);
+        }
+        let row_count = rows.len();
+        let mut cells: HashMap<&str, Vec<Vector2<usize>>> = default();
+        for (index_from_top, row) in rows.iter().enumerate() {
+            if row.len() != column_count {
+                let found = row.len();
+                let row = index_from_top;
+                let err =
+                    GridTemplateError::RowLengthMismatch { row, expected: column_count, found };
+                return Err(err);
+            }
+            let y = row_count - 1 - index_from_top;
+            for (x, name) in row.iter().enumerate() {
+                if *name != "." {
+                    cells.entry(*name).or_default().push(Vector2(x, y));
+                }
+            }
+        }
+        let mut areas = HashMap::new();
+        for (name, cells) in cells {
+            let min_x = cells.iter().map(|cell| cell.x).min().unwrap_or_default();
+            let max_x = cells.iter().map(|cell| cell.x).max().unwrap_or_default();
+            let min_y = cells.iter().map(|cell| cell.y).min().unwrap_or_default();
+            let max_y = cells.iter().map(|cell| cell.y).max().unwrap_or_default();
+            let area =
+                GridArea::new(Vector2(min_x, min_y), Vector2(max_x - min_x + 1, max_y - min_y + 1));
+            if area.span.x * area.span.y != cells.len() {
+                return Err(GridTemplateError::NotRectangular(name.into()));
+            }
+            areas.insert(ImString::from(name), area);
+        }
+        Ok(Self { size: Vector2(column_count, row_count), areas })
+    }
+
+    /// The number of columns and rows of the template.
+    pub fn size(&self) -> Vector2<usize> {
+        self.size
+    }
+
+    /// The area of the given name.
+    pub fn area(&self, name: &str) -> Option<GridArea> {
+        self.areas.get(name).copied()
+    }
+}
+
+/// An error of parsing the [`GridTemplateAreas`].
+#[derive(Clone, Debug, Fail, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum GridTemplateError {
+    Empty,
+    RowLengthMismatch { row: usize, expected: usize, found: usize },
+    NotRectangular(ImString),
+}
+
+impl Display for GridTemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "The grid template has no cells."),
+            Self::RowLengthMismatch { row, expected, found } => write!(
+                f,
+                "The grid template row {row} has {found} cells, while {expected} were expected."
+            ),
+            Self::NotRectangular(name) =>
+                write!(f, "The grid template area '{name}' is not rectangular."),
+        }
+    }
+}
@@ -5,6 +5,7 @@
 // === Export ===
 // ==============
 
+pub mod cpu;
 pub mod def;
 pub mod glsl;
 pub mod shader;
@@ -0,0 +1,73 @@
+//! CPU interpreter of shape definitions. Shapes built from [primitives](super::def::primitive) and
+//! [modifiers](super::def::modifier) are normally compiled to GLSL and rendered on GPU. This module
+//! evaluates the same definitions in Rust, so shapes can be rasterized to images without a GPU,
+//! for example in golden-image tests running on headless CI machines, or to export icons to PNG.
+//!
+//! The interpreter mirrors the GLSL code in the [`glsl`](super::glsl) module, including the way
+//! colors are blended and anti-aliased, so the rasterized images are the same as the ones rendered
+//! in the [`DISPLAY_MODE_NORMAL`](super::glsl::codes::DisplayModes) with zoom of `1.0`.
+//!
+//! # Dynamic Variables
+//! Shape parameters can be [`Var::Dynamic`](super::def::var::Var) values, which are GLSL
+//! expressions referring to shader inputs, like `input_size` or `input_<parameter name>` for
+//! parameters of shapes defined with the [`shape!`](crate::shape) macro. These expressions are
+//! evaluated by the [`expr`] module, with the values of inputs provided in the [`expr::Env`]. Only
+//! the subset of GLSL generated by [`Var`](super::def::var::Var) operations is supported.
+//!
+//! # Features
+//! Encoding the images as PNG files requires the `png` feature. The utilities of golden-image tests
+//! in the `golden` module require the `golden-tests` feature, and are always available in tests of
+//! this crate.
+//!
+//! # Limitations
+//! * [Cached shapes](super::system::cached) are not supported, as they read the GPU texture.
+//! * Bounding boxes of shapes are not computed, as they do not affect the rendered image.
+
+use crate::prelude::*;
+
+
+// ==============
+// === Export ===
+// ==============
+
+pub mod canvas;
+pub mod expr;
+#[cfg(any(test, feature = "golden-tests"))]
+pub mod golden;
+pub mod image;
+pub mod sdf;
+
+pub use canvas::Canvas;
+pub use canvas::Draw;
+pub use canvas::Shape;
+pub use expr::Env;
+pub use expr::Value;
+pub use image::Image;
+pub use image::Rasterizer;
+
+
+
+// =============
+// === Error ===
+// =============
+
+/// An error preventing a shape from being evaluated on CPU.
+#[derive(Clone, Debug, Fail, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum Error {
+    UnsupportedShape { name: String },
+    InvalidExpression { code: String, reason: String },
+    Png { reason: String },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedShape { name } =>
+                write!(f, "The shape '{name}' cannot be evaluated on CPU."),
+            Self::InvalidExpression { code, reason } =>
+                write!(f, "Cannot evaluate the expression '{code}': {reason}"),
+            Self::Png { reason } => write!(f, "Invalid PNG image: {reason}"),
+        }
+    }
+}
@@ -0,0 +1,385 @@
+//! Canvas evaluating shapes on CPU. It is the counterpart of the GLSL-generating
+//! [`Canvas`](crate::display::shape::primitive::shader::canvas::Canvas), and every method of it
+//! mirrors the GLSL function of the same name defined in the `shape.glsl` file.
+
+use crate::prelude::*;
+use crate::system::gpu::types::*;
+
+use crate::data::color;
+use crate::display::shape::primitive::cpu::expr::glsl_mod;
+use crate::display::shape::primitive::cpu::expr::Env;
+use crate::display::shape::primitive::cpu::expr::FromValue;
+use crate::display::shape::primitive::cpu::Error;
+use crate::display::shape::primitive::def::var::Var;
+
+
+
+// ==============
+// === Sample ===
+// ==============
+
+/// The shape evaluated at a single point. It is the counterpart of the `Shape` GLSL struct.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sample {
+    /// The signed distance from the shape boundary.
+    pub distance: f32,
+    /// The color in the linear sRGB space, premultiplied by its alpha. Unless the color was just
+    /// set, the [`alpha`](Self::alpha) is also applied to all components.
+    pub color:    Vector4<f32>,
+    /// The opacity of the shape, the result of anti-aliased rendering of the signed distance.
+    pub alpha:    f32,
+}
+
+impl Sample {
+    /// Constructor. Applies the opacity of the rendered shape to the premultiplied color.
+    pub fn new(distance: f32, color: Vector4<f32>, pixel_ratio: f32) -> Self {
+        let alpha = render(distance, pixel_ratio);
+        let color = color * alpha;
+        Self { distance, color, alpha }
+    }
+
+    /// Remove the opacity of the rendered shape from the color's alpha channel, so the shape can be
+    /// rendered again with a modified distance. Mirrors the GLSL code, which does not modify the
+    /// other channels.
+    fn without_alpha(mut self) -> Vector4<f32> {
+        if self.alpha > 0.0 {
+            self.color.w /= self.alpha;
+        }
+        self.color
+    }
+}
+
+/// The anti-aliased opacity of a shape at the given signed distance, with the zoom of `1.0`.
+fn render(distance: f32, pixel_ratio: f32) -> f32 {
+    (-distance * pixel_ratio + 0.5).clamp(0.0, 1.0)
+}
+
+/// Blend the premultiplied colors, with the `ratio` of the foreground's alpha.
+fn blend_with_ratio(bg: Vector4<f32>, fg: Vector4<f32>, ratio: f32) -> Vector4<f32> {
+    fg + (1.0 - ratio) * bg
+}
+
+fn blend(bg: Vector4<f32>, fg: Vector4<f32>) -> Vector4<f32> {
+    blend_with_ratio(bg, fg, fg.w)
+}
+
+fn premultiply(color: color::LinearRgba) -> Vector4<f32> {
+    let alpha = color.alpha;
+    Vector4(color.red * alpha, color.green * alpha, color.blue * alpha, alpha)
+}
+
+
+
+// =============
+// === Shape ===
+// =============
+
+/// A shape defined on the [`Canvas`], which can be sampled at any point.
+#[derive(Clone, CloneRef)]
+pub struct Shape {
+    sampler: Rc<dyn Fn(Vector2<f32>) -> Sample>,
+}
+
+impl Shape {
+    /// Constructor.
+    pub fn new(sampler: impl Fn(Vector2<f32>) -> Sample + 'static) -> Self {
+        Self { sampler: Rc::new(sampler) }
+    }
+
+    /// Evaluate the shape at the given position.
+    pub fn sample(&self, position: Vector2<f32>) -> Sample {
+        (self.sampler)(position)
+    }
+}
+
+impl Debug for Shape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Shape")
+    }
+}
+
+
+
+// ==============
+// === Canvas ===
+// ==============
+
+/// Canvas for evaluating shapes on CPU. The dynamic variables used by shapes are evaluated when the
+/// shapes are drawn, using the provided [`Env`].
+#[derive(Debug)]
+pub struct Canvas {
+    env:            Env,
+    pixel_ratio:    f32,
+    defined_shapes: HashMap<usize, Shape>,
+}
+
+impl Canvas {
+    /// Constructor. The `pixel_ratio` is the number of image pixels per a shape's pixel.
+    pub fn new(env: Env, pixel_ratio: f32) -> Self {
+        Self { env, pixel_ratio, defined_shapes: default() }
+    }
+
+    /// The variables available in dynamic expressions.
+    pub fn env(&self) -> &Env {
+        &self.env
+    }
+
+    /// Evaluate the variable.
+    pub fn eval<T: FromValue + Clone>(&self, var: &Var<T>) -> Result<T, Error> {
+        self.env.eval(var)
+    }
+
+    /// Checks if shape with the given id was already defined. If so, the cached shape is returned.
+    /// Otherwise the provided constructor is run and the result is cached.
+    pub fn if_not_defined(
+        &mut self,
+        id: usize,
+        f: impl FnOnce(&mut Self) -> Result<Shape, Error>,
+    ) -> Result<Shape, Error> {
+        match self.defined_shapes.get(&id) {
+            Some(shape) => Ok(shape.clone_ref()),
+            None => {
+                let shape = f(self)?;
+                self.defined_shapes.insert(id, shape.clone_ref());
+                Ok(shape)
+            }
+        }
+    }
+
+    /// Wrap the shape's sampler, mapping the position before and the sample after sampling.
+    fn modify(
+        &mut self,
+        id: usize,
+        s: Shape,
+        position: impl Fn(Vector2<f32>) -> Vector2<f32> + 'static,
+        sample: impl Fn(Sample, f32) -> Sample + 'static,
+    ) -> Result<Shape, Error> {
+        let pixel_ratio = self.pixel_ratio;
+        self.if_not_defined(id, |_| {
+            Ok(Shape::new(move |p| sample(s.sample(position(p)), pixel_ratio)))
+        })
+    }
+
+    /// Combine the samples of two shapes.
+    fn combine(
+        &mut self,
+        id: usize,
+        s1: Shape,
+        s2: Shape,
+        sample: impl Fn(Sample, Sample, f32) -> Sample + 'static,
+    ) -> Result<Shape, Error> {
+        let pixel_ratio = self.pixel_ratio;
+        self.if_not_defined(id, |_| {
+            Ok(Shape::new(move |p| sample(s1.sample(p), s2.sample(p), pixel_ratio)))
+        })
+    }
+}
+
+
+// === Shape Definition ===
+
+impl Canvas {
+    /// Defines a new shape with the given signed distance function. The shape is red, as in the
+    /// `shape` GLSL constructor.
+    pub fn define_shape(
+        &mut self,
+        id: usize,
+        sdf: impl Fn(Vector2<f32>) -> f32 + 'static,
+    ) -> Result<Shape, Error> {
+        let pixel_ratio = self.pixel_ratio;
+        let color = Vector4(1.0, 0.0, 0.0, 1.0);
+        self.if_not_defined(id, |_| {
+            Ok(Shape::new(move |p| Sample::new(sdf(p), color, pixel_ratio)))
+        })
+    }
+}
+
+
+// === Shape Modification ===
+
+impl Canvas {
+    /// Create a union shape from the provided shape components.
+    pub fn union(&mut self, id: usize, s1: Shape, s2: Shape) -> Result<Shape, Error> {
+        self.combine(id, s1, s2, |bg, fg, pixel_ratio| {
+            let distance = bg.distance.min(fg.distance);
+            Sample::new(distance, blend(bg.color, fg.color), pixel_ratio)
+        })
+    }
+
+    /// Create an exclusive union shape from the provided shape components.
+    pub fn union_exclusive(&mut self, id: usize, s1: Shape, s2: Shape) -> Result<Shape, Error> {
+        self.combine(id, s1, s2, |bg, fg, pixel_ratio| {
+            let distance = bg.distance.min(fg.distance);
+            let ratio = render(fg.distance, pixel_ratio);
+            Sample::new(distance, blend_with_ratio(bg.color, fg.color, ratio), pixel_ratio)
+        })
+    }
+
+    /// Create a difference shape from the provided shape components.
+    pub fn difference(&mut self, id: usize, s1: Shape, s2: Shape) -> Result<Shape, Error> {
+        self.combine(id, s1, s2, |s1, s2, pixel_ratio| {
+            Sample::new(s1.distance.max(-s2.distance), s1.color, pixel_ratio)
+        })
+    }
+
+    /// Create an intersection shape from the provided shape components.
+    pub fn intersection(&mut self, id: usize, s1: Shape, s2: Shape) -> Result<Shape, Error> {
+        self.combine(id, s1, s2, |s1, s2, pixel_ratio| {
+            let distance = s1.distance.max(s2.distance);
+            Sample::new(distance, blend(s1.color, s2.color), pixel_ratio)
+        })
+    }
+
+    /// Translate the current canvas origin.
+    pub fn translate(
+        &mut self,
+        id: usize,
+        s1: Shape,
+        v: &Var<Vector2<Pixels>>,
+    ) -> Result<Shape, Error> {
+        let v = self.eval(v)?;
+        let v = Vector2(v.x.value, v.y.value);
+        self.modify(id, s1, move |p| p - v, |sample, _| sample)
+    }
+
+    /// Rotate the current canvas origin.
+    pub fn rotation(&mut self, id: usize, s1: Shape, angle: &Var<Radians>) -> Result<Shape, Error> {
+        let angle = self.eval(angle)?.value;
+        let (sin, cos) = (-angle).sin_cos();
+        let rotate = move |p: Vector2<f32>| p * cos + Vector2(p.y, -p.x) * sin;
+        self.modify(id, s1, rotate, |sample, _| sample)
+    }
+
+    /// Scale the current canvas origin.
+    pub fn scale(&mut self, id: usize, s1: Shape, value: &Var<f32>) -> Result<Shape, Error> {
+        let value = self.eval(value)?;
+        self.modify(
+            id,
+            s1,
+            move |p| p / value,
+            move |sample, pixel_ratio| {
+                Sample::new(sample.distance * value, sample.without_alpha(), pixel_ratio)
+            },
+        )
+    }
+
+    /// Flip the shape upside-down, mirroring it over the X axis.
+    pub fn flip_y(&mut self, id: usize, s1: Shape) -> Result<Shape, Error> {
+        self.modify(id, s1, |p| Vector2(p.x, -p.y), |sample, _| sample)
+    }
+
+    /// Fill the shape with the provided color.
+    pub fn fill(&mut self, id: usize, s: Shape, color: &Var<color::Rgba>) -> Result<Shape, Error> {
+        let color = self.env.eval_color(color)?;
+        self.modify(
+            id,
+            s,
+            |p| p,
+            move |sample, _| {
+                let mut color = color;
+                color.alpha *= sample.alpha;
+                Sample { color: premultiply(color), ..sample }
+            },
+        )
+    }
+
+    /// Change the shape color depending on RGB components. See the docs of the GLSL canvas
+    /// counterpart to learn more.
+    pub fn recolorize(
+        &mut self,
+        id: usize,
+        s: Shape,
+        r: &Var<color::Rgba>,
+        g: &Var<color::Rgba>,
+        b: &Var<color::Rgba>,
+    ) -> Result<Shape, Error> {
+        let r = premultiply(self.env.eval_color(r)?);
+        let g = premultiply(self.env.eval_color(g)?);
+        let b = premultiply(self.env.eval_color(b)?);
+        self.modify(
+            id,
+            s,
+            |p| p,
+            move |sample, _| {
+                let color = r * sample.color.x + g * sample.color.y + b * sample.color.z;
+                Sample { color, ..sample }
+            },
+        )
+    }
+
+    /// Make the borders of the shape crisp. Please note that it removes any form of antialiasing.
+    pub fn pixel_snap(&mut self, id: usize, s: Shape) -> Result<Shape, Error> {
+        self.modify(
+            id,
+            s,
+            |p| p,
+            |sample, pixel_ratio| {
+                Sample::new(sample.distance.floor() + 0.5, sample.without_alpha(), pixel_ratio)
+            },
+        )
+    }
+
+    /// Grow the shape by the given value.
+    pub fn grow(&mut self, id: usize, s: Shape, value: &Var<f32>) -> Result<Shape, Error> {
+        let value = self.eval(value)?;
+        self.modify(
+            id,
+            s,
+            |p| p,
+            move |sample, pixel_ratio| {
+                Sample::new(sample.distance - value, sample.without_alpha(), pixel_ratio)
+            },
+        )
+    }
+
+    /// Shrink the shape by the given value.
+    pub fn shrink(&mut self, id: usize, s: Shape, value: &Var<f32>) -> Result<Shape, Error> {
+        let value = self.eval(value)?;
+        self.grow(id, s, &Var::Static(-value))
+    }
+
+    /// Repeat the shape with the given tile size.
+    pub fn repeat(
+        &mut self,
+        id: usize,
+        s: Shape,
+        tile_size: &Var<Vector2<Pixels>>,
+    ) -> Result<Shape, Error> {
+        let tile_size = self.eval(tile_size)?;
+        let tile_size = Vector2(tile_size.x.value, tile_size.y.value);
+        let repeat = move |p: Vector2<f32>| {
+            let p = p + tile_size / 2.0;
+            let x = glsl_mod(p.x, tile_size.x);
+            let y = glsl_mod(p.y, tile_size.y);
+            Vector2(x, y) - tile_size / 2.0
+        };
+        self.modify(id, s, repeat, |sample, pixel_ratio| {
+            Sample::new(sample.distance, sample.color, pixel_ratio)
+        })
+    }
+
+    /// Create a stroke of given thickness around shape's boundary.
+    pub fn stroke(&mut self, id: usize, s: Shape, thickness: &Var<f32>) -> Result<Shape, Error> {
+        let half_thickness = self.eval(thickness)? * 0.5;
+        self.modify(
+            id,
+            s,
+            |p| p,
+            move |sample, pixel_ratio| {
+                Sample::new(sample.distance.abs() - half_thickness, sample.color, pixel_ratio)
+            },
+        )
+    }
+}
+
+
+
+// ============
+// === Draw ===
+// ============
+
+/// Trait for every object which can be drawn on the CPU canvas.
+pub trait Draw: Debug {
+    /// Draw the element on the canvas.
+    fn draw_cpu(&self, canvas: &mut Canvas) -> Result<Shape, Error>;
+}
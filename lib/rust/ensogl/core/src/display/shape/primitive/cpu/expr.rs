@@ -0,0 +1,785 @@
+//! Evaluation of GLSL expressions of [`Var::Dynamic`] values. The supported subset of GLSL covers
+//! the code generated by [`Var`] operations: numbers, vectors and colors, function calls (like
+//! `add(a,b)`, `min(a,b)` or `srgba(r,g,b,a)`), arithmetic and comparison operators, swizzling, and
+//! variables, which values are provided by the [`Env`].
+
+use crate::prelude::*;
+use crate::system::gpu::types::*;
+
+use crate::data::color;
+use crate::display::shape::primitive::cpu::Error;
+use crate::display::shape::primitive::def::var::Var;
+
+use nalgebra::Scalar;
+
+
+
+// =============
+// === Value ===
+// =============
+
+/// A value of a GLSL expression.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    Number(f32),
+    /// A vector of 2, 3, or 4 components.
+    Vector(Vec<f32>),
+    /// A color in the sRGB space (the `Srgba` GLSL type).
+    Srgba(color::Rgba),
+    /// A color in the linear sRGB space (the `Rgba` GLSL type).
+    Rgba(color::LinearRgba),
+}
+
+impl Value {
+    fn type_name(&self) -> &'static str {
+        match self {
+            Self::Bool(_) => "bool",
+            Self::Number(_) => "float",
+            Self::Vector(_) => "vec",
+            Self::Srgba(_) => "Srgba",
+            Self::Rgba(_) => "Rgba",
+        }
+    }
+
+    /// The value converted to the linear sRGB space if it is a color. Vectors of 4 components are
+    /// interpreted as linear colors, as in the `rgba(vec4)` GLSL function.
+    fn linear_color(&self) -> Option<color::LinearRgba> {
+        match self {
+            Self::Srgba(color) => Some((*color).into()),
+            Self::Rgba(color) => Some(*color),
+            Self::Vector(v) if v.len() == 4 => Some(color::LinearRgba::new(v[0], v[1], v[2], v[3])),
+            _ => None,
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(t: bool) -> Self {
+        Self::Bool(t)
+    }
+}
+
+impl From<f32> for Value {
+    fn from(t: f32) -> Self {
+        Self::Number(t)
+    }
+}
+
+impl From<Vector2<f32>> for Value {
+    fn from(t: Vector2<f32>) -> Self {
+        Self::Vector(t.as_slice().to_vec())
+    }
+}
+
+impl From<Vector3<f32>> for Value {
+    fn from(t: Vector3<f32>) -> Self {
+        Self::Vector(t.as_slice().to_vec())
+    }
+}
+
+impl From<Vector4<f32>> for Value {
+    fn from(t: Vector4<f32>) -> Self {
+        Self::Vector(t.as_slice().to_vec())
+    }
+}
+
+impl From<color::Rgba> for Value {
+    fn from(t: color::Rgba) -> Self {
+        Self::Srgba(t)
+    }
+}
+
+impl From<color::LinearRgba> for Value {
+    fn from(t: color::LinearRgba) -> Self {
+        Self::Rgba(t)
+    }
+}
+
+
+
+// =================
+// === FromValue ===
+// =================
+
+/// Types which can be read from the [`Value`] of an expression.
+#[allow(missing_docs)]
+pub trait FromValue: Sized {
+    fn from_value(value: Value) -> Option<Self>;
+}
+
+impl FromValue for f32 {
+    fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::Number(t) => Some(t),
+            _ => None,
+        }
+    }
+}
+
+impl FromValue for Pixels {
+    fn from_value(value: Value) -> Option<Self> {
+        f32::from_value(value).map(Pixels)
+    }
+}
+
+impl FromValue for Radians {
+    fn from_value(value: Value) -> Option<Self> {
+        f32::from_value(value).map(Radians)
+    }
+}
+
+impl<T: FromValue + Scalar> FromValue for Vector2<T> {
+    fn from_value(value: Value) -> Option<Self> {
+        let [x, y] = vector_components(value)?;
+        Some(Vector2(x, y))
+    }
+}
+
+impl<T: FromValue + Scalar> FromValue for Vector3<T> {
+    fn from_value(value: Value) -> Option<Self> {
+        let [x, y, z] = vector_components(value)?;
+        Some(Vector3(x, y, z))
+    }
+}
+
+impl<T: FromValue + Scalar> FromValue for Vector4<T> {
+    fn from_value(value: Value) -> Option<Self> {
+        let [x, y, z, w] = vector_components(value)?;
+        Some(Vector4(x, y, z, w))
+    }
+}
+
+fn vector_components<T: FromValue, const N: usize>(value: Value) -> Option<[T; N]> {
+    let Value::Vector(components) = value else { return None };
+    let components: [f32; N] = components.try_into().ok()?;
+    let components = components.map(|t| T::from_value(Value::Number(t)));
+    components.into_iter().collect::<Option<Vec<T>>>()?.try_into().ok()
+}
+
+impl FromValue for color::LinearRgba {
+    fn from_value(value: Value) -> Option<Self> {
+        value.linear_color()
+    }
+}
+
+impl FromValue for color::Rgba {
+    fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::Srgba(color) => Some(color),
+            value => value.linear_color().map(|color| color.into()),
+        }
+    }
+}
+
+
+
+// ===========
+// === Env ===
+// ===========
+
+/// The values of variables available in expressions, like `input_size`.
+#[derive(Clone, Debug, Default)]
+pub struct Env {
+    values: HashMap<String, Value>,
+}
+
+impl Env {
+    /// Constructor.
+    pub fn new() -> Self {
+        default()
+    }
+
+    /// Set the value of a variable. The name is the GLSL one, so shape parameters should be
+    /// prefixed with `input_`.
+    pub fn set(&mut self, name: impl Into<String>, value: impl Into<Value>) {
+        self.values.insert(name.into(), value.into());
+    }
+
+    /// The value of a variable.
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.values.get(name)
+    }
+
+    /// Evaluate the variable. Static values are returned as they are, while dynamic ones are
+    /// evaluated as GLSL expressions.
+    pub fn eval<T: FromValue + Clone>(&self, var: &Var<T>) -> Result<T, Error> {
+        match var {
+            Var::Static(t) => Ok(t.clone()),
+            Var::Dynamic(code) => {
+                let code = String::from(code);
+                let value = self.eval_glsl(&code)?;
+                let type_name = value.type_name();
+                T::from_value(value).ok_or_else(|| Error::InvalidExpression {
+                    code,
+                    reason: format!("Unexpected value of type '{type_name}'."),
+                })
+            }
+        }
+    }
+
+    /// Evaluate the color variable, converting it to the linear sRGB space, in which the colors are
+    /// blended.
+    pub fn eval_color(&self, var: &Var<color::Rgba>) -> Result<color::LinearRgba, Error> {
+        match var {
+            Var::Static(t) => Ok((*t).into()),
+            Var::Dynamic(code) => self.eval(&Var::<color::LinearRgba>::Dynamic(code.clone())),
+        }
+    }
+
+    /// Evaluate the GLSL expression.
+    pub fn eval_glsl(&self, code: &str) -> Result<Value, Error> {
+        let error = |reason: String| Error::InvalidExpression { code: code.into(), reason };
+        let tokens = tokenize(code).map_err(error)?;
+        let mut parser = Parser { env: self, tokens: &tokens, next: 0 };
+        let value = parser.expression().map_err(error)?;
+        match parser.peek() {
+            None => Ok(value),
+            Some(token) => Err(error(format!("Unexpected token '{token}'."))),
+        }
+    }
+}
+
+
+
+// ==============
+// === Tokens ===
+// ==============
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f32),
+    Ident(String),
+    Symbol(&'static str),
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Number(t) => write!(f, "{t}"),
+            Self::Ident(t) => write!(f, "{t}"),
+            Self::Symbol(t) => write!(f, "{t}"),
+        }
+    }
+}
+
+/// Symbols of the supported operators. Longer symbols have to be placed before their prefixes.
+const SYMBOLS: &[&str] =
+    &["<=", ">=", "==", "!=", "(", ")", ",", ".", "+", "-", "*", "/", "<", ">"];
+
+fn tokenize(code: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut rest = code.trim_start();
+    while let Some(char) = rest.chars().next() {
+        let token_len = if char.is_ascii_digit() {
+            let len = number_len(rest);
+            let number = rest[..len].parse().map_err(|_| format!("Invalid number '{rest}'."))?;
+            tokens.push(Token::Number(number));
+            len
+        } else if char.is_alphabetic() || char == '_' {
+            let len = rest.find(|c: char| !c.is_alphanumeric() && c != '_').unwrap_or(rest.len());
+            tokens.push(Token::Ident(rest[..len].into()));
+            len
+        } else {
+            let symbol = SYMBOLS.iter().find(|symbol| rest.starts_with(*symbol));
+            let symbol = symbol.ok_or_else(|| format!("Unexpected character '{char}'."))?;
+            tokens.push(Token::Symbol(symbol));
+            symbol.len()
+        };
+        rest = rest[token_len..].trim_start();
+    }
+    Ok(tokens)
+}
+
+/// The length of the number literal at the beginning of the code, like `1`, `0.5`, or `1e-3`.
+fn number_len(code: &str) -> usize {
+    let bytes = code.as_bytes();
+    let digits_len = |from: usize| bytes[from..].iter().take_while(|b| b.is_ascii_digit()).count();
+    let mut len = digits_len(0);
+    if bytes.get(len) == Some(&b'.') {
+        len += 1 + digits_len(len + 1);
+    }
+    if matches!(bytes.get(len), Some(b'e' | b'E')) {
+        let sign_len = matches!(bytes.get(len + 1), Some(b'+' | b'-')) as usize;
+        let exponent_len = digits_len(len + 1 + sign_len);
+        if exponent_len > 0 {
+            len += 1 + sign_len + exponent_len;
+        }
+    }
+    len
+}
+
+
+
+// ==============
+// === Parser ===
+// ==============
+
+/// A recursive descent parser evaluating the expression while parsing it.
+#[derive(Debug)]
+struct Parser<'a> {
+    env:    &'a Env,
+    tokens: &'a [Token],
+    next:   usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.next)
+    }
+
+    fn next(&mut self) -> Result<&'a Token, String> {
+        let token = self.tokens.get(self.next).ok_or("Unexpected end of the expression.")?;
+        self.next += 1;
+        Ok(token)
+    }
+
+    fn consume_symbol(&mut self, symbols: &[&'static str]) -> Option<&'static str> {
+        match self.peek() {
+            Some(Token::Symbol(symbol)) if symbols.contains(symbol) => {
+                self.next += 1;
+                Some(symbol)
+            }
+            _ => None,
+        }
+    }
+
+    fn expect_symbol(&mut self, symbol: &'static str) -> Result<(), String> {
+        match self.next()? {
+            Token::Symbol(t) if *t == symbol => Ok(()),
+            token => Err(format!("Expected '{symbol}', found '{token}'.")),
+        }
+    }
+
+    fn expression(&mut self) -> Result<Value, String> {
+        let lhs = self.sum()?;
+        match self.consume_symbol(&["<", ">", "<=", ">=", "==", "!="]) {
+            Some(operator) => compare(operator, lhs, self.sum()?),
+            None => Ok(lhs),
+        }
+    }
+
+    fn sum(&mut self) -> Result<Value, String> {
+        let mut value = self.product()?;
+        while let Some(operator) = self.consume_symbol(&["+", "-"]) {
+            value = arithmetic(operator, value, self.product()?)?;
+        }
+        Ok(value)
+    }
+
+    fn product(&mut self) -> Result<Value, String> {
+        let mut value = self.unary()?;
+        while let Some(operator) = self.consume_symbol(&["*", "/"]) {
+            value = arithmetic(operator, value, self.unary()?)?;
+        }
+        Ok(value)
+    }
+
+    fn unary(&mut self) -> Result<Value, String> {
+        match self.consume_symbol(&["-", "+"]) {
+            Some("-") => map(self.unary()?, |t| -t),
+            Some(_) => self.unary(),
+            None => self.postfix(),
+        }
+    }
+
+    fn postfix(&mut self) -> Result<Value, String> {
+        let mut value = self.primary()?;
+        while self.consume_symbol(&["."]).is_some() {
+            match self.next()? {
+                Token::Ident(field) => value = access_field(value, field)?,
+                token => return Err(format!("Expected a field name, found '{token}'.")),
+            }
+        }
+        Ok(value)
+    }
+
+    fn primary(&mut self) -> Result<Value, String> {
+        match self.next()? {
+            Token::Number(t) => Ok(Value::Number(*t)),
+            Token::Symbol("(") => {
+                let value = self.expression()?;
+                self.expect_symbol(")")?;
+                Ok(value)
+            }
+            Token::Ident(name) if self.consume_symbol(&["("]).is_some() => {
+                let mut args = vec![];
+                if self.consume_symbol(&[")"]).is_none() {
+                    args.push(self.expression()?);
+                    while self.consume_symbol(&[","]).is_some() {
+                        args.push(self.expression()?);
+                    }
+                    self.expect_symbol(")")?;
+                }
+                call(name, args)
+            }
+            Token::Ident(name) => match name.as_str() {
+                "true" => Ok(Value::Bool(true)),
+                "false" => Ok(Value::Bool(false)),
+                "PI" => Ok(Value::Number(std::f32::consts::PI)),
+                _ => {
+                    let value = self.env.get(name).cloned();
+                    value.ok_or_else(|| format!("Unknown variable '{name}'."))
+                }
+            },
+            token => Err(format!("Unexpected token '{token}'.")),
+        }
+    }
+}
+
+
+
+// =================
+// === Operators ===
+// =================
+
+fn type_error(operation: &str, args: &[&Value]) -> String {
+    let types = args.iter().map(|arg| arg.type_name()).join(", ");
+    format!("The operation '{operation}' is not defined for arguments of types ({types}).")
+}
+
+/// Apply the function to every component of a number or a vector.
+fn map(value: Value, f: impl Fn(f32) -> f32) -> Result<Value, String> {
+    match value {
+        Value::Number(t) => Ok(Value::Number(f(t))),
+        Value::Vector(t) => Ok(Value::Vector(t.into_iter().map(f).collect())),
+        value => Err(format!("Expected a number or a vector, found '{}'.", value.type_name())),
+    }
+}
+
+/// Apply the function to the corresponding components of numbers or vectors. A number is combined
+/// with every component of a vector.
+fn zip_with(a: Value, b: Value, f: impl Fn(f32, f32) -> f32) -> Result<Value, String> {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => Ok(Value::Number(f(a, b))),
+        (Value::Vector(a), Value::Number(b)) =>
+            Ok(Value::Vector(a.into_iter().map(|a| f(a, b)).collect())),
+        (Value::Number(a), Value::Vector(b)) =>
+            Ok(Value::Vector(b.into_iter().map(|b| f(a, b)).collect())),
+        (Value::Vector(a), Value::Vector(b)) if a.len() == b.len() =>
+            Ok(Value::Vector(a.into_iter().zip(b).map(|(a, b)| f(a, b)).collect())),
+        (a, b) => Err(format!(
+            "Expected numbers or vectors of the same size, found '{}' and '{}'.",
+            a.type_name(),
+            b.type_name()
+        )),
+    }
+}
+
+fn arithmetic(operator: &str, a: Value, b: Value) -> Result<Value, String> {
+    match operator {
+        "+" => zip_with(a, b, |a, b| a + b),
+        "-" => zip_with(a, b, |a, b| a - b),
+        "*" => zip_with(a, b, |a, b| a * b),
+        "/" => zip_with(a, b, |a, b| a / b),
+        _ => Err(format!("Unknown operator '{operator}'.")),
+    }
+}
+
+fn compare(operator: &str, a: Value, b: Value) -> Result<Value, String> {
+    let (Value::Number(a), Value::Number(b)) = (&a, &b) else {
+        return Err(type_error(operator, &[&a, &b]));
+    };
+    let result = match operator {
+        "<" => a < b,
+        ">" => a > b,
+        "<=" => a <= b,
+        ">=" => a >= b,
+        "==" => a == b,
+        _ => a != b,
+    };
+    Ok(Value::Bool(result))
+}
+
+/// GLSL `mod` function, which result has the sign of the divisor, unlike the Rust `%` operator.
+pub(crate) fn glsl_mod(a: f32, b: f32) -> f32 {
+    a - b * (a / b).floor()
+}
+
+/// GLSL `sign` function, returning `0.0` for zero, unlike the Rust [`f32::signum`].
+pub(crate) fn glsl_sign(a: f32) -> f32 {
+    if a > 0.0 {
+        1.0
+    } else if a < 0.0 {
+        -1.0
+    } else {
+        0.0
+    }
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn access_field(value: Value, field: &str) -> Result<Value, String> {
+    match (value, field) {
+        (Value::Number(t), "value") => Ok(Value::Number(t)),
+        (Value::Srgba(color), "raw") =>
+            Ok(Value::Vector(vec![color.red, color.green, color.blue, color.alpha])),
+        (Value::Rgba(color), "raw") =>
+            Ok(Value::Vector(vec![color.red, color.green, color.blue, color.alpha])),
+        (Value::Vector(components), _) => {
+            let swizzled = field.chars().map(|name| {
+                let index = "xyzw".find(name).or_else(|| "rgba".find(name));
+                index.and_then(|index| components.get(index).copied())
+            });
+            let swizzled = swizzled.collect::<Option<Vec<f32>>>();
+            match swizzled {
+                Some(swizzled) if swizzled.len() == 1 => Ok(Value::Number(swizzled[0])),
+                Some(swizzled) if swizzled.len() <= 4 => Ok(Value::Vector(swizzled)),
+                _ => Err(format!("Invalid swizzle '{field}' of a vec{}.", components.len())),
+            }
+        }
+        (value, field) => Err(format!("Type '{}' has no field '{field}'.", value.type_name())),
+    }
+}
+
+
+
+// =================
+// === Functions ===
+// =================
+
+/// Flatten the constructor arguments to components, like GLSL `vec4(vec2(1.0,2.0),3.0,4.0)`. A
+/// single number is used for all components.
+fn components(name: &str, args: Vec<Value>, len: usize) -> Result<Vec<f32>, String> {
+    if let [Value::Number(t)] = args[..] {
+        return Ok(vec![t; len]);
+    }
+    let mut components = vec![];
+    for arg in &args {
+        match arg {
+            Value::Number(t) => components.push(*t),
+            Value::Bool(t) => components.push(*t as u32 as f32),
+            Value::Vector(t) => components.extend(t),
+            arg => return Err(type_error(name, &[arg])),
+        }
+    }
+    if components.len() == len {
+        Ok(components)
+    } else {
+        Err(format!("The '{name}' constructor expects {len} components, got {}.", components.len()))
+    }
+}
+
+/// Read the components of a color constructed with `srgba`, `rgba`, `srgb`, or `rgb` GLSL
+/// functions. The colors without the alpha component are opaque.
+fn color_components(name: &str, args: Vec<Value>) -> Result<[f32; 4], String> {
+    if name.ends_with('a') {
+        Ok(components(name, args, 4)?[..].try_into().unwrap())
+    } else {
+        let [r, g, b]: [f32; 3] = components(name, args, 3)?[..].try_into().unwrap();
+        Ok([r, g, b, 1.0])
+    }
+}
+
+fn call(name: &str, mut args: Vec<Value>) -> Result<Value, String> {
+    let arg_count = args.len();
+    let value = match (name, args.len()) {
+        ("add", 2) | ("sub", 2) | ("mul", 2) | ("div", 2) => {
+            let operator = match name {
+                "add" => "+",
+                "sub" => "-",
+                "mul" => "*",
+                _ => "/",
+            };
+            let b = args.pop().unwrap();
+            arithmetic(operator, args.pop().unwrap(), b)?
+        }
+        ("rem", 2) | ("mod", 2) => {
+            let b = args.pop().unwrap();
+            zip_with(args.pop().unwrap(), b, glsl_mod)?
+        }
+        ("neg", 1) => map(args.pop().unwrap(), |t| -t)?,
+        ("abs", 1) => map(args.pop().unwrap(), f32::abs)?,
+        ("sign", 1) => map(args.pop().unwrap(), glsl_sign)?,
+        ("sqrt", 1) => map(args.pop().unwrap(), f32::sqrt)?,
+        ("sin", 1) => map(args.pop().unwrap(), f32::sin)?,
+        ("cos", 1) => map(args.pop().unwrap(), f32::cos)?,
+        ("tan", 1) => map(args.pop().unwrap(), f32::tan)?,
+        ("asin", 1) => map(args.pop().unwrap(), f32::asin)?,
+        ("acos", 1) => map(args.pop().unwrap(), f32::acos)?,
+        ("atan", 1) => map(args.pop().unwrap(), f32::atan)?,
+        ("floor", 1) => map(args.pop().unwrap(), f32::floor)?,
+        ("ceil", 1) => map(args.pop().unwrap(), f32::ceil)?,
+        ("fract", 1) => map(args.pop().unwrap(), |t| t - t.floor())?,
+        ("exp", 1) => map(args.pop().unwrap(), f32::exp)?,
+        ("log", 1) => map(args.pop().unwrap(), f32::ln)?,
+        ("radians", 1) => map(args.pop().unwrap(), f32::to_radians)?,
+        ("degrees", 1) => map(args.pop().unwrap(), f32::to_degrees)?,
+        // Angles are represented by numbers, as `Radians` and `Degrees` GLSL structs just wrap
+        // numbers, which can be read with the `value` function.
+        ("Radians", 1) | ("Degrees", 1) | ("value", 1) => map(args.pop().unwrap(), |t| t)?,
+        ("atan", 2) => {
+            let x = args.pop().unwrap();
+            zip_with(args.pop().unwrap(), x, f32::atan2)?
+        }
+        ("pow", 2) => {
+            let b = args.pop().unwrap();
+            zip_with(args.pop().unwrap(), b, f32::powf)?
+        }
+        ("step", 2) => {
+            let x = args.pop().unwrap();
+            zip_with(args.pop().unwrap(), x, |edge, x| if x < edge { 0.0 } else { 1.0 })?
+        }
+        ("min", 2) => {
+            let b = args.pop().unwrap();
+            zip_with(args.pop().unwrap(), b, f32::min)?
+        }
+        ("max", 2) => {
+            let b = args.pop().unwrap();
+            zip_with(args.pop().unwrap(), b, f32::max)?
+        }
+        // Overloads defined in `math.glsl`, returning the smallest or the biggest component.
+        ("min", 1) | ("max", 1) => match args.pop().unwrap() {
+            Value::Vector(t) if name == "min" =>
+                Value::Number(t.into_iter().fold(f32::MAX, f32::min)),
+            Value::Vector(t) => Value::Number(t.into_iter().fold(f32::MIN, f32::max)),
+            arg => return Err(type_error(name, &[&arg])),
+        },
+        ("clamp", 1) => map(args.pop().unwrap(), |t| t.clamp(0.0, 1.0))?,
+        ("clamp", 3) => {
+            let upper = args.pop().unwrap();
+            let lower = args.pop().unwrap();
+            let value = zip_with(args.pop().unwrap(), lower, f32::max)?;
+            zip_with(value, upper, f32::min)?
+        }
+        ("smoothstep", 3) => {
+            let [edge0, edge1, x] = <[Value; 3]>::try_from(args).unwrap();
+            match (edge0, edge1, x) {
+                (Value::Number(edge0), Value::Number(edge1), x) =>
+                    map(x, |x| smoothstep(edge0, edge1, x))?,
+                (edge0, edge1, x) => return Err(type_error(name, &[&edge0, &edge1, &x])),
+            }
+        }
+        ("mix", 3) => {
+            let [a, b, t] = <[Value; 3]>::try_from(args).unwrap();
+            let Value::Number(t) = t else { return Err(type_error(name, &[&a, &b, &t])) };
+            let mix = |a: f32, b: f32| a * (1.0 - t) + b * t;
+            match (a, b) {
+                (Value::Srgba(a), Value::Srgba(b)) => {
+                    let a = color::LinearRgba::from(a);
+                    let b = color::LinearRgba::from(b);
+                    let mixed = color::LinearRgba::new(
+                        mix(a.red, b.red),
+                        mix(a.green, b.green),
+                        mix(a.blue, b.blue),
+                        mix(a.alpha, b.alpha),
+                    );
+                    Value::Srgba(mixed.into())
+                }
+                (Value::Rgba(a), Value::Rgba(b)) => Value::Rgba(color::LinearRgba::new(
+                    mix(a.red, b.red),
+                    mix(a.green, b.green),
+                    mix(a.blue, b.blue),
+                    mix(a.alpha, b.alpha),
+                )),
+                (a, b) => zip_with(a, b, mix)?,
+            }
+        }
+        ("length", 1) => match args.pop().unwrap() {
+            Value::Number(t) => Value::Number(t.abs()),
+            Value::Vector(t) => Value::Number(t.iter().map(|t| t * t).sum::<f32>().sqrt()),
+            arg => return Err(type_error(name, &[&arg])),
+        },
+        ("dot", 2) => match zip_with(args.pop().unwrap(), args.pop().unwrap(), |a, b| a * b)? {
+            Value::Vector(t) => Value::Number(t.into_iter().sum()),
+            value => value,
+        },
+        ("float", 1) => match args.pop().unwrap() {
+            Value::Bool(t) => Value::Number(t as u32 as f32),
+            Value::Number(t) => Value::Number(t),
+            arg => return Err(type_error(name, &[&arg])),
+        },
+        ("vec2", _) => Value::Vector(components(name, args, 2)?),
+        ("vec3", _) => Value::Vector(components(name, args, 3)?),
+        ("vec4", _) => Value::Vector(components(name, args, 4)?),
+        ("srgba" | "srgb", 1) if matches!(args[0], Value::Srgba(_) | Value::Rgba(_)) =>
+            match args.pop().unwrap() {
+                Value::Rgba(color) => Value::Srgba(color.into()),
+                color => color,
+            },
+        ("rgba" | "rgb", 1) if matches!(args[0], Value::Srgba(_) | Value::Rgba(_)) =>
+            match args.pop().unwrap() {
+                Value::Srgba(color) => Value::Rgba(color.into()),
+                color => color,
+            },
+        ("srgba" | "srgb" | "rgba" | "rgb", _) => {
+            let [r, g, b, a] = color_components(name, args)?;
+            if name.starts_with('s') {
+                Value::Srgba(color::Rgba::new(r, g, b, a))
+            } else {
+                Value::Rgba(color::LinearRgba::new(r, g, b, a))
+            }
+        }
+        _ => return Err(format!("Function '{name}' cannot be called with {arg_count} arguments.")),
+    };
+    Ok(value)
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env() -> Env {
+        let mut env = Env::new();
+        env.set("input_size", Vector2(100.0, 40.0));
+        env.set("input_offset", 2.5);
+        env
+    }
+
+    fn eval(code: &str) -> Value {
+        env().eval_glsl(code).unwrap()
+    }
+
+    #[test]
+    fn arithmetic_of_numbers_and_vectors() {
+        assert_eq!(eval("add(1.0,mul(2.0,3.0))"), Value::Number(7.0));
+        assert_eq!(eval("-(1.0 + 2.0) * 3.0 / 2.0"), Value::Number(-4.5));
+        assert_eq!(eval("rem(-1.0,4.0)"), Value::Number(3.0));
+        assert_eq!(eval("neg(input_offset)"), Value::Number(-2.5));
+        assert_eq!(eval("div(input_size,2.0)"), Value::Vector(vec![50.0, 20.0]));
+        assert_eq!(eval("sub(input_size,vec2(10.0,1e1))"), Value::Vector(vec![90.0, 30.0]));
+        assert_eq!(eval("float(input_offset > 0.0)"), Value::Number(1.0));
+    }
+
+    #[test]
+    fn functions_and_swizzling() {
+        assert_eq!(eval("min(input_size.x,input_size.y)"), Value::Number(40.0));
+        assert_eq!(eval("max(input_size)"), Value::Number(100.0));
+        assert_eq!(eval("input_size.yx"), Value::Vector(vec![40.0, 100.0]));
+        assert_eq!(eval("vec2(min(5.0,input_offset) * 2.0)"), Value::Vector(vec![5.0, 5.0]));
+        assert_eq!(eval("clamp(input_offset,0.0,1.0)"), Value::Number(1.0));
+        assert_eq!(eval("value(Radians(1.5))"), Value::Number(1.5));
+        assert_eq!(eval("vec4(input_size,0.0,1.0).w"), Value::Number(1.0));
+    }
+
+    #[test]
+    fn evaluating_colors() {
+        let env = env();
+        let srgba = Var::<color::Rgba>::from("srgba(1.0,0.5,0.0,input_offset)");
+        assert_eq!(env.eval(&srgba).unwrap(), color::Rgba::new(1.0, 0.5, 0.0, 2.5));
+        let raw = env.eval_glsl("srgba(0.1,0.2,0.3,0.4).raw.z").unwrap();
+        assert_eq!(raw, Value::Number(0.3));
+        let linear = env.eval_color(&"rgba(srgba(1.0,1.0,1.0,0.5))".into()).unwrap();
+        assert_eq!(linear, color::LinearRgba::new(1.0, 1.0, 1.0, 0.5));
+    }
+
+    #[test]
+    fn reporting_errors() {
+        let env = env();
+        assert!(env.eval_glsl("input_unknown").is_err());
+        assert!(env.eval_glsl("unknown_function(1.0)").is_err());
+        assert!(env.eval_glsl("add(1.0,").is_err());
+        assert!(env.eval_glsl("vec2(1.0,2.0,3.0)").is_err());
+        assert!(env.eval_glsl("1.0 1.0").is_err());
+        assert!(env.eval::<f32>(&"input_size".into()).is_err());
+    }
+}
@@ -0,0 +1,62 @@
+//! Utilities for golden-image tests: the images of rasterized shapes are compared with the golden
+//! PNG files stored in the repository. Available in tests or with the `golden-tests` feature.
+
+use crate::prelude::*;
+
+use crate::display::shape::primitive::cpu::Image;
+
+use std::path::Path;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// If this environment variable is set, the [`assert_matches_golden`] function overwrites the
+/// golden images instead of comparing them.
+pub const UPDATE_GOLDEN_IMAGES_VAR: &str = "ENSOGL_UPDATE_GOLDEN_IMAGES";
+
+
+
+// =====================
+// === Golden Images ===
+// =====================
+
+/// Check if the image matches the golden PNG file, allowing the given difference of 8-bit channel
+/// values. If the [`UPDATE_GOLDEN_IMAGES_VAR`] environment variable is set, the golden file is
+/// written instead.
+///
+/// # Panics
+/// Panics if the golden file is missing or the images differ. The rasterized image is then saved
+/// in the temporary directory, so it can be inspected.
+pub fn assert_matches_golden(image: &Image, path: impl AsRef<Path>, tolerance: u8) {
+    let path = path.as_ref();
+    let png = image.to_png().expect("Cannot encode the image.");
+    if std::env::var_os(UPDATE_GOLDEN_IMAGES_VAR).is_some() {
+        std::fs::write(path, png).expect("Cannot write the golden image.");
+        return;
+    }
+    let golden = std::fs::read(path).unwrap_or_else(|_| {
+        panic!(
+            "Missing golden image {}. Run the test with {UPDATE_GOLDEN_IMAGES_VAR}=1 to create it.",
+            path.display()
+        )
+    });
+    let golden = Image::from_png(&golden).expect("Cannot decode the golden image.");
+    let difference = image.max_difference(&golden);
+    if difference.map_or(true, |difference| difference > tolerance) {
+        let file_name = path.file_name().unwrap_or_default();
+        let actual = std::env::temp_dir().join(file_name);
+        let saved = std::fs::write(&actual, png).is_ok();
+        let saved = if saved { format!(" Saved it to {}.", actual.display()) } else { default() };
+        let difference = match difference {
+            Some(difference) => format!("The channels differ by {difference}."),
+            None => format!(
+                "The size {}x{} differs from {}x{}.",
+                image.width, image.height, golden.width, golden.height
+            ),
+        };
+        panic!("The image does not match {}. {difference}{saved}", path.display());
+    }
+}
@@ -0,0 +1,269 @@
+//! Rasterization of shapes to images.
+
+use crate::prelude::*;
+use crate::system::gpu::types::*;
+
+use crate::data::color;
+use crate::display::shape::primitive::cpu::canvas::Canvas;
+use crate::display::shape::primitive::cpu::canvas::Draw;
+use crate::display::shape::primitive::cpu::canvas::Sample;
+use crate::display::shape::primitive::cpu::expr::Env;
+use crate::display::shape::primitive::cpu::expr::Value;
+use crate::display::shape::primitive::cpu::sdf;
+use crate::display::shape::primitive::cpu::Error;
+
+
+
+// =============
+// === Image ===
+// =============
+
+/// A rasterized image. The pixels are stored row by row, starting from the top-left corner.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+    /// The width in pixels.
+    pub width:  usize,
+    /// The height in pixels.
+    pub height: usize,
+    /// The colors of pixels in the sRGB space, not premultiplied by their alpha.
+    pub pixels: Vec<color::Rgba>,
+}
+
+impl Image {
+    /// The color of the pixel at the given position.
+    pub fn pixel(&self, x: usize, y: usize) -> Option<color::Rgba> {
+        (x < self.width).then(|| self.pixels.get(y * self.width + x).copied()).flatten()
+    }
+
+    /// The image encoded as 8-bit RGBA values, not premultiplied by alpha.
+    pub fn to_rgba8(&self) -> Vec<u8> {
+        let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+        let pixel = |c: &color::Rgba| [c.red, c.green, c.blue, c.alpha].map(channel);
+        self.pixels.iter().flat_map(pixel).collect()
+    }
+
+    /// The image encoded as a PNG file.
+    #[cfg(any(test, feature = "png"))]
+    pub fn to_png(&self) -> Result<Vec<u8>, Error> {
+        let mut bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut bytes, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(png_error)?;
+        writer.write_image_data(&self.to_rgba8()).map_err(png_error)?;
+        writer.finish().map_err(png_error)?;
+        Ok(bytes)
+    }
+
+    /// Decode an 8-bit RGB or RGBA PNG image.
+    #[cfg(any(test, feature = "png"))]
+    pub fn from_png(bytes: &[u8]) -> Result<Self, Error> {
+        let mut decoder = png::Decoder::new(bytes);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info().map_err(png_error)?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer).map_err(png_error)?;
+        let channels = match info.color_type {
+            png::ColorType::Rgb => 3,
+            png::ColorType::Rgba => 4,
+            other =>
+                return Err(Error::Png { reason: format!("Unsupported color type {other:?}.") }),
+        };
+        let width = info.width as usize;
+        let height = info.height as usize;
+        let rows = buffer.chunks(info.line_size).take(height);
+        let pixels = rows.flat_map(|row| row[..width * channels].chunks(channels));
+        let channel = |value: u8| value as f32 / 255.0;
+        let alpha = |pixel: &[u8]| pixel.get(3).copied().map_or(1.0, channel);
+        let color =
+            |p: &[u8]| color::Rgba::new(channel(p[0]), channel(p[1]), channel(p[2]), alpha(p));
+        let pixels = pixels.map(color).collect();
+        Ok(Self { width, height, pixels })
+    }
+
+    /// The maximum difference of 8-bit channel values of the images, or [`None`] if their sizes
+    /// differ.
+    pub fn max_difference(&self, other: &Image) -> Option<u8> {
+        let same_size = self.width == other.width && self.height == other.height;
+        same_size.then(|| {
+            let channels = self.to_rgba8().into_iter().zip(other.to_rgba8());
+            channels.map(|(a, b)| a.abs_diff(b)).max().unwrap_or_default()
+        })
+    }
+}
+
+#[cfg(any(test, feature = "png"))]
+fn png_error(error: impl Display) -> Error {
+    Error::Png { reason: error.to_string() }
+}
+
+
+
+// ==================
+// === Rasterizer ===
+// ==================
+
+/// Rasterizes shapes to [`Image`]s of the given size. The shape's origin is placed in the center
+/// of the image, as in the shape system's sprites.
+#[derive(Clone, Debug)]
+pub struct Rasterizer {
+    /// The size of the canvas, in the shape's pixels. It is the value of the `input_size`
+    /// variable.
+    pub size:        Vector2<f32>,
+    /// The number of image pixels per a shape's pixel.
+    pub pixel_ratio: f32,
+    /// The values of variables, for example shape parameters.
+    pub env:         Env,
+}
+
+impl Rasterizer {
+    /// Constructor.
+    pub fn new(size: Vector2<f32>) -> Self {
+        Self { size, pixel_ratio: 1.0, env: default() }
+    }
+
+    /// Set the number of image pixels per a shape's pixel.
+    pub fn with_pixel_ratio(mut self, pixel_ratio: f32) -> Self {
+        self.pixel_ratio = pixel_ratio;
+        self
+    }
+
+    /// Set the value of a variable. See [`Env::set`].
+    pub fn with_var(mut self, name: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.env.set(name, value);
+        self
+    }
+
+    /// Rasterize the shape. Similarly to the shape system's fragment shader, the shape is clipped
+    /// to the canvas size.
+    pub fn rasterize(&self, shape: &impl Draw) -> Result<Image, Error> {
+        let mut env = self.env.clone();
+        env.set("input_size", self.size);
+        env.set("input_pixel_ratio", self.pixel_ratio);
+        let mut canvas = Canvas::new(env, self.pixel_ratio);
+        let shape = shape.draw_cpu(&mut canvas)?;
+        let view_box = sdf::rect(Vector2(Pixels(self.size.x), Pixels(self.size.y)));
+        let width = (self.size.x * self.pixel_ratio).ceil() as usize;
+        let height = (self.size.y * self.pixel_ratio).ceil() as usize;
+        let mut pixels = Vec::with_capacity(width * height);
+        for row in 0..height {
+            for column in 0..width {
+                let x = (column as f32 + 0.5) / self.pixel_ratio - self.size.x / 2.0;
+                let y = self.size.y / 2.0 - (row as f32 + 0.5) / self.pixel_ratio;
+                let position = Vector2(x, y);
+                let sample = shape.sample(position);
+                let distance = sample.distance.max(view_box(position));
+                let sample = Sample::new(distance, sample.color, self.pixel_ratio);
+                pixels.push(unpremultiply(sample.color));
+            }
+        }
+        Ok(Image { width, height, pixels })
+    }
+}
+
+/// Convert the premultiplied linear color to the sRGB space.
+fn unpremultiply(color: Vector4<f32>) -> color::Rgba {
+    let alpha = color.w;
+    let rgb = if alpha > 0.0 { color.xyz() / alpha } else { Vector3(0.0, 0.0, 0.0) };
+    let linear = color::LinearRgba::new(rgb.x, rgb.y, rgb.z, alpha);
+    linear.into()
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::shape::*;
+
+    use crate::display::shape::primitive::cpu::golden::assert_matches_golden;
+    use std::f32::consts::PI;
+    use std::path::Path;
+
+    const TOLERANCE: u8 = 2;
+
+    fn assert_golden(name: &str, size: (f32, f32), shape: &impl Draw) {
+        let image = Rasterizer::new(Vector2(size.0, size.1)).rasterize(shape).unwrap();
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/src/display/shape/primitive/cpu/golden");
+        assert_matches_golden(&image, Path::new(dir).join(format!("{name}.png")), TOLERANCE);
+    }
+
+    #[test]
+    fn png_round_trip() {
+        let pixels = vec![
+            color::Rgba::new(1.0, 0.0, 0.0, 1.0),
+            color::Rgba::new(0.0, 1.0, 0.0, 0.5),
+            color::Rgba::new(0.0, 0.0, 1.0, 0.0),
+            color::Rgba::new(1.0, 1.0, 1.0, 1.0),
+        ];
+        let image = Image { width: 2, height: 2, pixels };
+        let decoded = Image::from_png(&image.to_png().unwrap()).unwrap();
+        assert_eq!(image.max_difference(&decoded), Some(0));
+        assert_eq!(decoded.pixel(1, 1), Some(color::Rgba::new(1.0, 1.0, 1.0, 1.0)));
+        assert_eq!(decoded.pixel(2, 0), None);
+        let wider = Image { width: 4, height: 1, pixels: decoded.pixels.clone() };
+        assert_eq!(image.max_difference(&wider), None);
+    }
+
+    #[test]
+    fn rasterized_circle_is_centered() {
+        let shape = Circle(4.px());
+        let image = Rasterizer::new(Vector2(10.0, 10.0)).rasterize(&shape).unwrap();
+        assert_eq!((image.width, image.height), (10, 10));
+        let center = image.pixel(5, 5).unwrap();
+        assert_approx_eq!(center.red, 1.0);
+        assert_eq!((center.green, center.alpha), (0.0, 1.0));
+        assert_eq!(image.pixel(0, 0).map(|c| c.alpha), Some(0.0));
+        let hidpi = Rasterizer::new(Vector2(10.0, 10.0)).with_pixel_ratio(2.0);
+        let image = hidpi.rasterize(&shape).unwrap();
+        assert_eq!((image.width, image.height), (20, 20));
+    }
+
+    #[test]
+    fn golden_filled_circle() {
+        let shape = Circle(8.px()).fill(color::Rgba::new(0.2, 0.5, 0.8, 1.0));
+        assert_golden("filled_circle", (20.0, 20.0), &shape);
+    }
+
+    #[test]
+    fn golden_rounded_rect_stroke() {
+        let shape = Rect((24.px(), 16.px())).corners_radius(4.px());
+        let shape = shape.stroke(2.px()).fill(color::Rgba::new(0.1, 0.1, 0.1, 1.0));
+        assert_golden("rounded_rect_stroke", (32.0, 24.0), &shape);
+    }
+
+    #[test]
+    fn golden_compound_shape() {
+        let circle =
+            Circle(7.px()).translate_x((-4).px()).fill(color::Rgba::new(0.9, 0.3, 0.1, 1.0));
+        let square = Rect((10.px(), 10.px())).rotate((PI / 4.0).radians());
+        let square = square.translate_x(5.px()).fill(color::Rgba::new(0.1, 0.4, 0.9, 0.6));
+        let hole = Circle(2.px()).translate_x((-4).px());
+        let shape = circle.union(square).difference(hole);
+        assert_golden("compound_shape", (32.0, 24.0), &shape);
+    }
+
+    #[test]
+    fn golden_canvas_sized_shape() {
+        let size = Var::<Vector2<Pixels>>::canvas_size();
+        let shape = Rect(size).corners_radius(6.px()).shrink(2.px());
+        let shape = shape.fill(color::Rgba::new(0.3, 0.7, 0.3, 0.8));
+        assert_golden("canvas_sized_shape", (28.0, 20.0), &shape);
+    }
+
+    #[test]
+    fn dynamic_parameters() {
+        let radius = Var::<Pixels>::from("input_radius");
+        let shape = AnyShape::from(Circle(radius));
+        let rasterizer = Rasterizer::new(Vector2(10.0, 10.0));
+        let error = rasterizer.rasterize(&shape).unwrap_err();
+        assert!(matches!(error, Error::InvalidExpression { .. }));
+        let image = rasterizer.with_var("input_radius", 2.0).rasterize(&shape).unwrap();
+        assert_eq!(image.pixel(5, 5).map(|c| c.alpha), Some(1.0));
+        assert_eq!(image.pixel(5, 1).map(|c| c.alpha), Some(0.0));
+    }
+}
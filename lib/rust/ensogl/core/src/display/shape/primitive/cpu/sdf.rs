@@ -0,0 +1,350 @@
+//! Signed distance functions of [primitive
+//! shapes](crate::display::shape::primitive::def::primitive) evaluated on CPU. Every function is a
+//! port of the GLSL body of the shape of the same name, and it returns the signed distance of the
+//! point from the shape boundary.
+
+use crate::system::gpu::types::*;
+
+use crate::display::shape::primitive::cpu::expr::glsl_mod;
+use crate::display::shape::primitive::cpu::expr::glsl_sign;
+
+use std::f32::consts::PI;
+
+
+
+// ===============
+// === Helpers ===
+// ===============
+
+fn vec2(x: f32, y: f32) -> Vector2<f32> {
+    Vector2(x, y)
+}
+
+fn pixels(v: Vector2<Pixels>) -> Vector2<f32> {
+    vec2(v.x.value, v.y.value)
+}
+
+fn abs(v: Vector2<f32>) -> Vector2<f32> {
+    v.map(f32::abs)
+}
+
+fn clamp(x: f32, min: f32, max: f32) -> f32 {
+    x.max(min).min(max)
+}
+
+
+
+// ================
+// === Infinite ===
+// ================
+
+/// See [`def::EmptyShape`](crate::display::shape::primitive::def::EmptyShape).
+pub fn empty_shape() -> impl Fn(Vector2<f32>) -> f32 {
+    |_| f32::MAX
+}
+
+/// See [`def::Plane`](crate::display::shape::primitive::def::Plane).
+pub fn plane() -> impl Fn(Vector2<f32>) -> f32 {
+    |_| f32::MIN
+}
+
+/// See [`def::HalfPlane`](crate::display::shape::primitive::def::HalfPlane).
+pub fn half_plane() -> impl Fn(Vector2<f32>) -> f32 {
+    |position| -position.y
+}
+
+/// See [`def::BottomHalfPlane`](crate::display::shape::primitive::def::BottomHalfPlane).
+pub fn bottom_half_plane() -> impl Fn(Vector2<f32>) -> f32 {
+    |position| position.y
+}
+
+/// See [`def::PlaneAngle`](crate::display::shape::primitive::def::PlaneAngle).
+pub fn plane_angle(angle: Radians) -> impl Fn(Vector2<f32>) -> f32 {
+    let angle_norm = angle.value / (2.0 * PI);
+    let angle_norm = 1.0 - (glsl_mod(angle_norm, 2.0) - 1.0).abs();
+    let angle_rad = angle_norm * 2.0 * PI;
+    let off = angle_norm - 0.5;
+    move |position| {
+        position.x.abs() * (angle_rad / 2.0).cos() - position.y * (angle_rad / 2.0).sin() - off
+    }
+}
+
+/// See [`def::PlaneAngleFast`](crate::display::shape::primitive::def::PlaneAngleFast).
+pub fn plane_angle_fast(angle: Radians) -> impl Fn(Vector2<f32>) -> f32 {
+    let angle = angle.value;
+    let off = 0.5;
+    move |position| position.x.abs() * (angle / 2.0).cos() - position.y * (angle / 2.0).sin() + off
+}
+
+/// See [`def::Line`](crate::display::shape::primitive::def::Line).
+pub fn line(width: f32) -> impl Fn(Vector2<f32>) -> f32 {
+    move |position| position.y.abs() - width / 2.0
+}
+
+
+
+// ===============
+// === Segment ===
+// ===============
+
+/// See [`def::Segment`](crate::display::shape::primitive::def::Segment).
+pub fn segment(
+    start: Vector2<Pixels>,
+    end: Vector2<Pixels>,
+    width: Pixels,
+) -> impl Fn(Vector2<f32>) -> f32 {
+    let start = pixels(start);
+    let end = pixels(end);
+    let half_width = width.value / 2.0;
+    let delta = end - start;
+    move |position| {
+        let projection = clamp((position - start).dot(&delta) / delta.dot(&delta), 0.0, 1.0);
+        let closest_point = start + projection * delta;
+        (position - closest_point).norm() - half_width
+    }
+}
+
+
+
+// ===============
+// === Ellipse ===
+// ===============
+
+/// See [`def::Circle`](crate::display::shape::primitive::def::Circle).
+pub fn circle(radius: Pixels) -> impl Fn(Vector2<f32>) -> f32 {
+    move |position| position.norm() - radius.value
+}
+
+/// See [`def::Ellipse`](crate::display::shape::primitive::def::Ellipse).
+pub fn ellipse(x_radius: f32, y_radius: f32) -> impl Fn(Vector2<f32>) -> f32 {
+    move |position| {
+        let mut ab = vec2(x_radius, y_radius);
+        let mut position = abs(position);
+        if position.x > position.y {
+            position = vec2(position.y, position.x);
+            ab = vec2(ab.y, ab.x);
+        }
+        let l = ab.y * ab.y - ab.x * ab.x;
+        let m = ab.x * position.x / l;
+        let m2 = m * m;
+        let n = ab.y * position.y / l;
+        let n2 = n * n;
+        let c = (m2 + n2 - 1.0) / 3.0;
+        let c3 = c * c * c;
+        let q = c3 + m2 * n2 * 2.0;
+        let d = c3 + m2 * n2;
+        let g = m + m * n2;
+        let co = if d < 0.0 {
+            let h = (q / c3).acos() / 3.0;
+            let s = h.cos();
+            let t = h.sin() * 3.0_f32.sqrt();
+            let rx = (-c * (s + t + 2.0) + m2).sqrt();
+            let ry = (-c * (s - t + 2.0) + m2).sqrt();
+            (ry + glsl_sign(l) * rx + g.abs() / (rx * ry) - m) / 2.0
+        } else {
+            let h = 2.0 * m * n * d.sqrt();
+            let s = glsl_sign(q + h) * (q + h).abs().powf(1.0 / 3.0);
+            let u = glsl_sign(q - h) * (q - h).abs().powf(1.0 / 3.0);
+            let rx = -s - u - c * 4.0 + 2.0 * m2;
+            let ry = (s - u) * 3.0_f32.sqrt();
+            let rm = (rx * rx + ry * ry).sqrt();
+            (ry / (rm - rx).sqrt() + 2.0 * g / rm - m) / 2.0
+        };
+        let r = ab.component_mul(&vec2(co, (1.0 - co * co).sqrt()));
+        (r - position).norm() * glsl_sign(position.y - r.y)
+    }
+}
+
+
+
+// =================
+// === Rectangle ===
+// =================
+
+/// See [`def::Rect`](crate::display::shape::primitive::def::Rect).
+pub fn rect(size: Vector2<Pixels>) -> impl Fn(Vector2<f32>) -> f32 {
+    let size = pixels(size);
+    move |position| {
+        let dir = abs(position) - size / 2.0;
+        dir.x.min(0.0).max(dir.y.min(0.0)) + dir.map(|t| t.max(0.0)).norm()
+    }
+}
+
+/// See [`def::RoundedRectByCorner`](crate::display::shape::primitive::def::RoundedRectByCorner).
+pub fn rounded_rect_by_corner(
+    size: Vector2<Pixels>,
+    top_left: Pixels,
+    top_right: Pixels,
+    bottom_left: Pixels,
+    bottom_right: Pixels,
+) -> impl Fn(Vector2<f32>) -> f32 {
+    let size = pixels(size);
+    let (top_left, top_right) = (top_left.value, top_right.value);
+    let (bottom_left, bottom_right) = (bottom_left.value, bottom_right.value);
+    let top_weight = clamp(size.x / (top_left + top_right), 0.0, 1.0);
+    let bottom_weight = clamp(size.x / (bottom_left + bottom_right), 0.0, 1.0);
+    let left_weight = clamp(size.y / (top_left + bottom_left), 0.0, 1.0);
+    let right_weight = clamp(size.y / (top_right + bottom_right), 0.0, 1.0);
+    let tl = top_weight.min(left_weight) * top_left;
+    let tr = top_weight.min(right_weight) * top_right;
+    let bl = bottom_weight.min(left_weight) * bottom_left;
+    let br = bottom_weight.min(right_weight) * bottom_right;
+    let size = size / 2.0;
+    move |position| {
+        let is_top_left = position.x < -size.x + tl && position.y > size.y - tl;
+        let is_top_right = position.x > size.x - tr && position.y > size.y - tr;
+        let is_bottom_left = position.x < -size.x + bl && position.y < -size.y + bl;
+        let is_bottom_right = position.x > size.x - br && position.y < -size.y + br;
+        let dir = abs(position) - size;
+        let rect_inner = dir.x.max(dir.y).min(0.0);
+        let dist = if is_top_left {
+            (position - vec2(-size.x + tl, size.y - tl)).norm() - tl
+        } else if is_top_right {
+            (position - vec2(size.x - tr, size.y - tr)).norm() - tr
+        } else if is_bottom_left {
+            (position - vec2(-size.x + bl, -size.y + bl)).norm() - bl
+        } else if is_bottom_right {
+            (position - vec2(size.x - br, -size.y + br)).norm() - br
+        } else {
+            dir.map(|t| t.max(0.0)).norm() + rect_inner
+        };
+        dist.max(rect_inner)
+    }
+}
+
+
+
+// ================
+// === Triangle ===
+// ================
+
+/// See [`def::Triangle`](crate::display::shape::primitive::def::Triangle).
+pub fn triangle(width: f32, height: f32) -> impl Fn(Vector2<f32>) -> f32 {
+    let q = vec2(width * 0.5, height);
+    move |position| {
+        let p = vec2(position.x.abs(), height * 0.5 - position.y);
+        let a = p - q * clamp(p.dot(&q) / q.dot(&q), 0.0, 1.0);
+        let b = p - q.component_mul(&vec2(clamp(p.x / q.x, 0.0, 1.0), 1.0));
+        let s = -glsl_sign(q.y);
+        let d1 = vec2(a.dot(&a), s * (p.x * q.y - p.y * q.x));
+        let d2 = vec2(b.dot(&b), s * (p.y - q.y));
+        let d = vec2(d1.x.min(d2.x), d1.y.min(d2.y));
+        -d.x.sqrt() * glsl_sign(d.y)
+    }
+}
+
+
+
+// ======================
+// === Uneven Capsule ===
+// ======================
+
+/// See [`def::UnevenCapsule`](crate::display::shape::primitive::def::UnevenCapsule).
+pub fn uneven_capsule(
+    radius_top: Pixels,
+    radius_bottom: Pixels,
+    inner_height: Pixels,
+) -> impl Fn(Vector2<f32>) -> f32 {
+    let (radius_top, radius_bottom) = (radius_top.value, radius_bottom.value);
+    let inner_height = inner_height.value;
+    let b = (radius_bottom - radius_top) / inner_height;
+    let a = (1.0 - b * b).sqrt();
+    move |position| {
+        let position = vec2(position.x.abs(), position.y);
+        let k = position.dot(&vec2(-b, a));
+        if k < 0.0 {
+            position.norm() - radius_bottom
+        } else if k > a * inner_height {
+            (position - vec2(0.0, inner_height)).norm() - radius_top
+        } else {
+            position.dot(&vec2(a, b)) - radius_bottom
+        }
+    }
+}
+
+
+
+// =================
+// === Five Star ===
+// =================
+
+/// See [`def::FiveStar`](crate::display::shape::primitive::def::FiveStar).
+pub fn five_star(radius: Pixels, ratio: f32) -> impl Fn(Vector2<f32>) -> f32 {
+    let radius = radius.value;
+    let k1 = vec2(0.809016994375, -0.587785252292);
+    let k2 = vec2(-k1.x, k1.y);
+    move |position| {
+        let mut position = vec2(position.x.abs(), position.y);
+        position -= 2.0 * k1.dot(&position).max(0.0) * k1;
+        position -= 2.0 * k2.dot(&position).max(0.0) * k2;
+        position.x = position.x.abs();
+        position.y -= radius;
+        let ba = ratio * vec2(-k1.y, k1.x) - vec2(0.0, 1.0);
+        let h = clamp(position.dot(&ba) / ba.dot(&ba), 0.0, radius);
+        (position - ba * h).norm() * glsl_sign(position.y * ba.x - position.x * ba.y)
+    }
+}
+
+
+
+// ===========
+// === Arc ===
+// ===========
+
+/// See [`def::RoundedArc`](crate::display::shape::primitive::def::RoundedArc).
+pub fn rounded_arc(radius: Pixels, angle: Radians, width: Pixels) -> impl Fn(Vector2<f32>) -> f32 {
+    let radius = radius.value;
+    let half_arc_angle = angle.value * 0.5;
+    let sc = vec2(half_arc_angle.sin(), half_arc_angle.cos());
+    move |position| {
+        let position = vec2(position.x.abs(), position.y);
+        let k = if sc.y * position.x > sc.x * position.y {
+            (position - sc * radius).norm()
+        } else {
+            (position.norm() - radius).abs()
+        };
+        k - width.value * 0.5
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn distances_of_primitives() {
+        let size = Vector2(Pixels(20.0), Pixels(10.0));
+        let rect = rect(size);
+        assert_eq!(rect(vec2(0.0, 0.0)), -5.0);
+        assert_eq!(rect(vec2(13.0, 0.0)), 3.0);
+        assert_eq!(rect(vec2(13.0, 9.0)), 5.0);
+        let circle = circle(Pixels(5.0));
+        assert_eq!(circle(vec2(3.0, 4.0)), 0.0);
+        let radius = Pixels(5.0);
+        let no_radius = Pixels(0.0);
+        let rounded = rounded_rect_by_corner(size, radius, no_radius, no_radius, no_radius);
+        assert_approx_eq!(rounded(vec2(-10.0, 5.0)), 50.0_f32.sqrt() - 5.0);
+        assert_eq!(rounded(vec2(10.0, 5.0)), 0.0);
+        let start = Vector2(Pixels(0.0), Pixels(0.0));
+        let end = Vector2(Pixels(10.0), Pixels(0.0));
+        let segment = segment(start, end, Pixels(2.0));
+        assert_eq!(segment(vec2(5.0, 3.0)), 2.0);
+        assert_eq!(segment(vec2(13.0, 4.0)), 4.0);
+    }
+
+    #[test]
+    fn ellipse_is_a_scaled_circle() {
+        let ellipse = ellipse(20.0, 10.0);
+        assert_approx_eq!(ellipse(vec2(20.0, 0.0)), 0.0, 1e-4);
+        assert_approx_eq!(ellipse(vec2(0.0, 10.0)), 0.0, 1e-4);
+        assert_approx_eq!(ellipse(vec2(0.0, 15.0)), 5.0, 1e-4);
+        assert!(ellipse(vec2(0.0, 0.0)) < 0.0);
+    }
+}
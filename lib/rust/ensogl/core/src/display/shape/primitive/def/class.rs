@@ -5,6 +5,7 @@ use super::unit::*;
 use crate::prelude::*;
 
 use crate::data::color;
+use crate::display::shape::primitive::cpu;
 use crate::display::shape::primitive::def::var::Var;
 use crate::display::shape::primitive::shader::canvas;
 use crate::display::shape::primitive::shader::canvas::Canvas;
@@ -16,13 +17,18 @@ use crate::display::shape::primitive::shader::canvas::Canvas;
 // =============
 
 /// Type of any shape which we can display on the canvas.
-pub trait Shape = 'static + canvas::Draw;
+pub trait Shape = 'static + canvas::Draw + cpu::Draw;
+
+/// Shape which can be drawn both on the GLSL and the [CPU](cpu) canvas. It is needed, as trait
+/// objects cannot be created for the [`Shape`] trait alias.
+pub trait AnyDraw: canvas::Draw + cpu::Draw {}
+impl<T: canvas::Draw + cpu::Draw> AnyDraw for T {}
 
 /// Generic 2d shape representation. You can convert any specific shape type to this type and use it
 /// as a generic shape type.
 #[derive(Debug, Clone, CloneRef)]
 pub struct AnyShape {
-    rc: Rc<dyn canvas::Draw>,
+    rc: Rc<dyn AnyDraw>,
 }
 
 impl AsOwned for AnyShape {
@@ -42,6 +48,12 @@ impl canvas::Draw for AnyShape {
     }
 }
 
+impl cpu::Draw for AnyShape {
+    fn draw_cpu(&self, canvas: &mut cpu::Canvas) -> Result<cpu::Shape, cpu::Error> {
+        self.rc.draw_cpu(canvas)
+    }
+}
+
 
 
 // ================
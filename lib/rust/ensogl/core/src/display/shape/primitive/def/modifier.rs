@@ -8,7 +8,9 @@
 use crate::data::color::*;
 use crate::prelude::*;
 
+use crate::display::shape::primitive::cpu;
 use crate::display::shape::primitive::def::class::AnyShape;
+use crate::display::shape::primitive::def::class::Shape;
 use crate::display::shape::primitive::def::class::ShapeRef;
 use crate::display::shape::primitive::def::var::Var;
 use crate::display::shape::primitive::shader::canvas;
@@ -27,7 +29,7 @@ use crate::display::shape::primitive::shader::canvas::Canvas;
 /// It defines `canvas::Draw` method for each modifier definition which first draws all children to
 /// the canvas and then runs a canvas method of the same name as the modifier. For example, for
 /// `Union`, it first draws its two children and then calls `canvas.union` with results of the draw
-/// commands.
+/// commands. The `cpu::Draw` implementation is defined the same way, using the CPU canvas.
 macro_rules! define_modifiers {
     ( $($name:ident $lname:ident $shapes:tt $fields:tt)* ) => {
         /// Contains mutable shapes definitions.
@@ -86,13 +88,13 @@ macro_rules! _define_modifier {
             type Owned = $name<$($shape_field),*>;
         }
 
-        impl<$($shape_field:'static+canvas::Draw),*> From<$name<$($shape_field),*>> for AnyShape {
+        impl<$($shape_field:Shape),*> From<$name<$($shape_field),*>> for AnyShape {
             fn from(t:$name<$($shape_field),*>) -> Self {
                 Self::new(t)
             }
         }
 
-        impl<$($shape_field:'static+canvas::Draw),*> From<&$name<$($shape_field),*>> for AnyShape {
+        impl<$($shape_field:Shape),*> From<&$name<$($shape_field),*>> for AnyShape {
             fn from(t:&$name<$($shape_field),*>) -> Self {
                 Self::new(t.clone())
             }
@@ -104,6 +106,13 @@ macro_rules! _define_modifier {
                 canvas.$lname(self.id() $(,$shape_field)* $(,&self.$field)*)
             }
         }
+
+        impl<$($shape_field:cpu::Draw),*> cpu::Draw for $name<$($shape_field),*> {
+            fn draw_cpu(&self, canvas:&mut cpu::Canvas) -> Result<cpu::Shape,cpu::Error> {
+                $(let $shape_field = self.$shape_field.draw_cpu(canvas)?;)*
+                canvas.$lname(self.id() $(,$shape_field)* $(,&self.$field)*)
+            }
+        }
     }
 }
 
@@ -8,6 +8,7 @@
 use crate::prelude::*;
 use crate::system::gpu::shader::glsl::traits::*;
 
+use crate::display::shape::primitive::cpu;
 use crate::display::shape::primitive::def::class::AnyShape;
 use crate::display::shape::primitive::def::class::ShapeRef;
 use crate::display::shape::primitive::shader::canvas;
@@ -93,6 +94,13 @@ macro_rules! _define_sdf_shape_immutable_part {
             }
         }
 
+        impl cpu::Draw for $name {
+            fn draw_cpu(&self, canvas:&mut cpu::Canvas) -> Result<cpu::Shape,cpu::Error> {
+                let sdf = paste! { cpu::sdf::[<$name:snake>] ($(canvas.eval(&self.$field)?),*) };
+                canvas.define_shape(self.id(),sdf)
+            }
+        }
+
         impl GlslShapeDefinition for $name {
             fn glsl_shape_definition() -> String {
                 let name = stringify!($name).to_snake_case();
@@ -117,6 +117,7 @@ use crate::display::shape;
 use crate::display::shape::canvas;
 use crate::display::shape::canvas::Canvas;
 use crate::display::shape::class::ShapeRef;
+use crate::display::shape::primitive::cpu;
 use crate::display::shape::system::cached::arrange_on_texture::arrange_shapes_on_texture;
 use crate::display::shape::system::cached::arrange_on_texture::ShapeWithPosition;
 use crate::display::shape::system::cached::arrange_on_texture::ShapeWithSize;
@@ -309,6 +310,12 @@ impl canvas::Draw for AnyCachedShape {
     }
 }
 
+impl cpu::Draw for AnyCachedShape {
+    fn draw_cpu(&self, _canvas: &mut cpu::Canvas) -> Result<cpu::Shape, cpu::Error> {
+        Err(cpu::Error::UnsupportedShape { name: "AnyCachedShape".into() })
+    }
+}
+
 impl From<AnyCachedShape> for AnyShape {
     fn from(value: AnyCachedShape) -> Self {
         Self::new(value)
@@ -6,6 +6,7 @@
 // ==============
 
 pub mod data;
+pub mod file;
 pub mod javascript;
 pub mod path;
 pub mod sheet;
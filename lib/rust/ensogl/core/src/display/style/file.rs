@@ -0,0 +1,341 @@
+//! Theme files, allowing defining and modifying themes at runtime.
+//!
+//! A theme file is a JSON document describing the values of a theme. Objects define nested paths,
+//! and their keys may contain dots, so `{"graph": {"node": {"radius": 14}}}` and
+//! `{"graph.node.radius": 14}` are equivalent. The values are interpreted according to the types
+//! declared by the [`Schema`] the file is validated against:
+//!  - numbers are written as JSON numbers, like `14` or `0.5`;
+//!  - vectors are written as arrays of two numbers, like `[1.0, 2.0]`;
+//!  - colors are written as strings in any supported color space, like `"rgba(1.0,0.0,0.0,1.0)"` or
+//!    `"lcha(0.5,0.0,0.0,1.0)"`;
+//!  - texts are written as JSON strings, like `"DejaVuSans"`.
+//!
+//! For example, the following file changes the application background and the default text size:
+//!
+//! ```json
+//! {
+//!     "application": {
+//!         "background": "rgba(0.1,0.1,0.1,1.0)",
+//!         "tooltip.show_delay_duration_ms": 300
+//!     },
+//!     "text.size": 14
+//! }
+//! ```
+//!
+//! Paths not declared in the schema and values of mismatched types are reported as [`Error`]s. The
+//! file is parsed completely even if it contains errors, so all of them can be reported at once.
+//! The parsed theme can be registered in the theme [`Manager`] with the [`Manager::load_file`]
+//! method, which is also used to reload the theme when the file changes.
+//!
+//! [`Manager`]: super::theme::Manager
+//! [`Manager::load_file`]: super::theme::Manager::load_file
+
+use crate::prelude::*;
+
+use crate::data::color;
+
+use super::data::Data;
+use super::sheet::Value;
+use super::theme::Theme;
+
+use serde_json::Value as Json;
+
+
+
+// ================
+// === DataType ===
+// ================
+
+/// The type of a [`Data`] value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum DataType {
+    Number,
+    Vector,
+    Color,
+    Text,
+}
+
+impl DataType {
+    /// The type of the provided value. Returns [`None`] for the [`Data::Invalid`] values.
+    pub fn of(data: &Data) -> Option<Self> {
+        match data {
+            Data::Invalid(_) => None,
+            Data::Number(_) => Some(Self::Number),
+            Data::Vector(_) => Some(Self::Vector),
+            Data::Color(_) => Some(Self::Color),
+            Data::Text(_) => Some(Self::Text),
+        }
+    }
+}
+
+impl Display for DataType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Number => write!(f, "a number"),
+            Self::Vector => write!(f, "a vector"),
+            Self::Color => write!(f, "a color"),
+            Self::Text => write!(f, "a text"),
+        }
+    }
+}
+
+
+
+// ==============
+// === Schema ===
+// ==============
+
+/// The paths which can be defined in theme files, along with the types of their values.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Schema {
+    types: BTreeMap<String, DataType>,
+}
+
+impl Schema {
+    /// Constructor.
+    pub fn new() -> Self {
+        default()
+    }
+
+    /// Create a schema declaring all paths of the theme, with the types of their current values.
+    /// Paths defined by expressions are not declared, as their types are not known before the
+    /// expressions are evaluated.
+    pub fn from_theme(theme: &Theme) -> Self {
+        let mut schema = Self::new();
+        for (path, value) in theme.values() {
+            if let Value::Data(data) = value {
+                if let Some(data_type) = DataType::of(&data) {
+                    schema.declare(path, data_type);
+                }
+            }
+        }
+        schema
+    }
+
+    /// Declare a path and the type of its value.
+    pub fn declare(&mut self, path: impl Into<String>, data_type: DataType) {
+        self.types.insert(path.into(), data_type);
+    }
+
+    /// The type of the value declared for the given path.
+    pub fn get(&self, path: &str) -> Option<DataType> {
+        self.types.get(path).copied()
+    }
+}
+
+
+
+// =============
+// === Error ===
+// =============
+
+/// An error found in a theme file.
+#[derive(Clone, Debug, Fail, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum Error {
+    Syntax(String),
+    NotAnObject,
+    UnknownPath { path: String },
+    TypeMismatch { path: String, expected: DataType, found: String },
+    InvalidColor { path: String, value: String, reason: String },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax(reason) => write!(f, "The theme file is not a valid JSON: {reason}."),
+            Self::NotAnObject => write!(f, "The theme file should contain a JSON object."),
+            Self::UnknownPath { path } => write!(f, "The theme does not define '{path}'."),
+            Self::TypeMismatch { path, expected, found } =>
+                write!(f, "The value of '{path}' should be {expected}, but it is {found}."),
+            Self::InvalidColor { path, value, reason } =>
+                write!(f, "The value of '{path}' is not a valid color '{value}': {reason}"),
+        }
+    }
+}
+
+
+
+// ===============
+// === Parsing ===
+// ===============
+
+/// Parse the theme file and validate it against the schema. Returns all errors found in the file.
+pub fn parse(source: &str, schema: &Schema) -> Result<Theme, Vec<Error>> {
+    let json = serde_json::from_str(source).map_err(|err| vec![Error::Syntax(err.to_string())])?;
+    let Json::Object(fields) = json else { return Err(vec![Error::NotAnObject]) };
+    let mut parser = Parser { schema, values: default(), errors: default() };
+    parser.parse_object("", &fields);
+    if parser.errors.is_empty() {
+        let theme = Theme::new();
+        for (path, data) in parser.values {
+            theme.set(path, data);
+        }
+        Ok(theme)
+    } else {
+        Err(parser.errors)
+    }
+}
+
+/// The state of the theme file parser.
+#[derive(Debug)]
+struct Parser<'a> {
+    schema: &'a Schema,
+    values: Vec<(String, Data)>,
+    errors: Vec<Error>,
+}
+
+impl<'a> Parser<'a> {
+    fn parse_object(&mut self, prefix: &str, fields: &serde_json::Map<String, Json>) {
+        for (key, value) in fields {
+            let path = if prefix.is_empty() { key.clone() } else { format!("{prefix}.{key}") };
+            match value {
+                Json::Object(fields) => self.parse_object(&path, fields),
+                value => match self.schema.get(&path) {
+                    Some(expected) => match parse_value(&path, value, expected) {
+                        Ok(data) => self.values.push((path, data)),
+                        Err(error) => self.errors.push(error),
+                    },
+                    None => self.errors.push(Error::UnknownPath { path }),
+                },
+            }
+        }
+    }
+}
+
+fn parse_value(path: &str, value: &Json, expected: DataType) -> Result<Data, Error> {
+    let number = |value: &Json| value.as_f64().map(|number| number as f32);
+    let data = match (expected, value) {
+        (DataType::Number, value) => number(value).map(Data::Number),
+        (DataType::Vector, Json::Array(items)) => match items.as_slice() {
+            [x, y] => number(x).zip(number(y)).map(|(x, y)| Data::Vector(Vector2(x, y))),
+            _ => None,
+        },
+        (DataType::Color, Json::String(value)) => {
+            let color = value.parse::<color::AnyFormat>().map_err(|err| Error::InvalidColor {
+                path:   path.into(),
+                value:  value.clone(),
+                reason: err.reason,
+            })?;
+            Some(Data::Color(color.into()))
+        }
+        (DataType::Text, Json::String(value)) => Some(Data::Text(value.clone())),
+        _ => None,
+    };
+    data.ok_or_else(|| {
+        let found = describe(value);
+        Error::TypeMismatch { path: path.into(), expected, found }
+    })
+}
+
+/// A human-readable description of the JSON value, used in error messages.
+fn describe(value: &Json) -> String {
+    match value {
+        Json::Null => "null".into(),
+        Json::Bool(value) => format!("a boolean '{value}'"),
+        Json::Number(value) => format!("a number '{value}'"),
+        Json::String(value) => format!("a string '{value}'"),
+        Json::Array(items) => format!("an array of {} elements", items.len()),
+        Json::Object(_) => "an object".into(),
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use super::super::data::DataMatch;
+
+    fn schema() -> Schema {
+        let mut schema = Schema::new();
+        schema.declare("application.background", DataType::Color);
+        schema.declare("application.tooltip.delay", DataType::Number);
+        schema.declare("text.font", DataType::Text);
+        schema.declare("graph.node.offset", DataType::Vector);
+        schema
+    }
+
+    fn value(theme: &Theme, path: &str) -> Option<Data> {
+        let values = theme.values().into_iter().find(|(value_path, _)| value_path == path);
+        values.and_then(|(_, value)| match value {
+            Value::Data(data) => Some(data),
+            Value::Expression(_) => None,
+        })
+    }
+
+    #[test]
+    fn parse_valid_file() {
+        let source = r#"{
+            "application": {
+                "background": "rgba(1.0, 0.0, 0.0, 1.0)",
+                "tooltip.delay": 300
+            },
+            "text.font": "DejaVuSans",
+            "graph": { "node": { "offset": [1.0, 2.5] } }
+        }"#;
+        let theme = parse(source, &schema()).unwrap();
+        let red = color::Rgba(1.0, 0.0, 0.0, 1.0);
+        assert_eq!(value(&theme, "application.background").color(), Some(red));
+        assert_eq!(value(&theme, "application.tooltip.delay").number(), Some(300.0));
+        assert_eq!(value(&theme, "text.font").string(), Some("DejaVuSans".into()));
+        assert_eq!(value(&theme, "graph.node.offset").vector(), Some(Vector2(1.0, 2.5)));
+        assert_eq!(theme.values().len(), 4);
+    }
+
+    #[test]
+    fn report_all_errors() {
+        // Fields of JSON objects are visited in the alphabetical order.
+        let source = r#"{
+            "application.background": "purple(1.0)",
+            "application.tooltip.delay": "300",
+            "application.tooltip.color": "rgb(1.0,1.0,1.0)",
+            "graph.node.offset": [1.0, 2.0, 3.0]
+        }"#;
+        let errors = parse(source, &schema()).unwrap_err();
+        let expected = vec![
+            Error::InvalidColor {
+                path:   "application.background".into(),
+                value:  "purple(1.0)".into(),
+                reason: "Unknown color space 'Purple'.".into(),
+            },
+            Error::UnknownPath { path: "application.tooltip.color".into() },
+            Error::TypeMismatch {
+                path:     "application.tooltip.delay".into(),
+                expected: DataType::Number,
+                found:    "a string '300'".into(),
+            },
+            Error::TypeMismatch {
+                path:     "graph.node.offset".into(),
+                expected: DataType::Vector,
+                found:    "an array of 3 elements".into(),
+            },
+        ];
+        assert_eq!(errors, expected);
+    }
+
+    #[test]
+    fn report_malformed_file() {
+        assert!(matches!(parse("{", &schema()).unwrap_err()[..], [Error::Syntax(_)]));
+        assert_eq!(parse("[]", &schema()).unwrap_err(), vec![Error::NotAnObject]);
+    }
+
+    #[test]
+    fn schema_from_theme() {
+        let theme = Theme::new();
+        theme.set("application.background", color::Rgba(1.0, 1.0, 1.0, 1.0));
+        theme.set("text.size", 12.0);
+        theme.set("text.font", "DejaVuSans");
+        let schema = Schema::from_theme(&theme);
+        assert_eq!(schema.get("application.background"), Some(DataType::Color));
+        assert_eq!(schema.get("text.size"), Some(DataType::Number));
+        assert_eq!(schema.get("text.font"), Some(DataType::Text));
+        assert_eq!(schema.get("text"), None);
+    }
+}
@@ -9,6 +9,7 @@ use crate::data::color;
 use crate::data::dirty;
 use crate::data::HashMapTree;
 
+use super::file;
 use super::sheet as style;
 use super::sheet::Change;
 use super::sheet::Path;
@@ -127,6 +128,9 @@ impl PartialSemigroup<&Theme> for Theme {
 pub struct ManagerData {
     all:         HashMap<String, Theme>,
     enabled:     Vec<String>,
+    /// Themes applied over the enabled ones, regardless of which themes are enabled. See
+    /// [`Manager::add_overlay`].
+    overlays:    Vec<String>,
     combined:    Theme,
     style_sheet: style::Sheet,
 }
@@ -142,6 +146,17 @@ impl ManagerData {
         &self.enabled
     }
 
+    /// Return names of all overlay themes. See [`Manager::add_overlay`].
+    pub fn overlays(&self) -> &Vec<String> {
+        &self.overlays
+    }
+
+    /// Check if the theme of the given name is applied, either as an enabled theme or as an
+    /// overlay.
+    pub fn is_applied(&self, name: &str) -> bool {
+        self.enabled.iter().chain(&self.overlays).any(|applied| applied == name)
+    }
+
     /// Return a reference to the theme of the given name.
     pub fn get(&self, name: &str) -> Option<&Theme> {
         self.all.get(name)
@@ -158,7 +173,7 @@ impl ManagerData {
         N: IntoIterator,
         N::Item: ToString, {
         self.enabled = names.into_iter().map(|name| name.to_string()).collect();
-        let combined = self.combine(&self.enabled);
+        let combined = self.combine(self.enabled.iter().chain(&self.overlays));
         let changes = self.combined.diff(&combined);
         self.combined = combined;
         self.style_sheet.apply_changes(changes);
@@ -193,6 +208,15 @@ impl ManagerData {
         self.set_enabled(self.enabled.clone())
     }
 
+    /// Apply the theme over the enabled ones. See [`Manager::add_overlay`].
+    pub fn add_overlay(&mut self, name: impl Str) {
+        let name = name.into();
+        if !self.overlays.contains(&name) {
+            self.overlays.push(name);
+            self.refresh();
+        }
+    }
+
     /// Registers a new theme.
     pub fn register<T: Into<Theme>>(&mut self, name: impl Str, theme: T) {
         let name = name.into();
@@ -251,6 +275,24 @@ impl Manager {
         self.data.borrow().keys()
     }
 
+    /// Return names of all enabled themes.
+    pub fn enabled(&self) -> Vec<String> {
+        self.data.borrow().enabled().clone()
+    }
+
+    /// Return names of all overlay themes. See [`Self::add_overlay`].
+    pub fn overlays(&self) -> Vec<String> {
+        self.data.borrow().overlays().clone()
+    }
+
+    /// Apply the registered theme over the enabled themes, overriding their values. Unlike the
+    /// enabled themes, the overlays stay applied when another set of themes is enabled, so they
+    /// can be used to customize whichever builtin theme is chosen, e.g. by the project's theme
+    /// file.
+    pub fn add_overlay(&self, name: impl Str) {
+        self.data.borrow_mut().add_overlay(name);
+    }
+
     /// Registers a new theme.
     pub fn register<T: Into<Theme>>(&self, name: impl Str, theme: T) {
         self.register_internal(name.into(), theme.into())
@@ -270,6 +312,27 @@ impl Manager {
         self.set_enabled([name]);
     }
 
+    /// Parse the theme file (see the [`file`] module docs to learn about its format) and register
+    /// it with the provided name. If a theme with the same name was registered before, it is
+    /// replaced, and if it is enabled or applied as an overlay, the changes are applied in the next
+    /// update. This allows reloading the theme when the file changes. If the file contains errors,
+    /// the previously registered theme is kept.
+    pub fn load_file(
+        &self,
+        name: impl Str,
+        source: &str,
+        schema: &file::Schema,
+    ) -> Result<(), Vec<file::Error>> {
+        let theme = file::parse(source, schema)?;
+        let name = name.into();
+        let is_applied = self.data.borrow().is_applied(&name);
+        self.register_internal(name, theme);
+        if is_applied {
+            self.current_dirty.set();
+        }
+        Ok(())
+    }
+
     fn register_internal(&self, name: String, theme: Theme) {
         let dirty = self.current_dirty.clone_ref();
         let handle = theme.on_mut(move || dirty.set());
@@ -389,3 +452,49 @@ pub fn test() {
     theme_manager.set_enabled(["theme1".to_string()]);
     theme_manager.set_enabled(["theme1", "theme2"]);
 }
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::display::style::data::DataMatch;
+
+    fn number(sheet: &style::Sheet, path: &str) -> Option<f32> {
+        sheet.value(path).and_then(|data| data.number())
+    }
+
+    #[test]
+    fn overlays_stay_applied_when_switching_themes() {
+        let sheet = style::Sheet::new();
+        let manager = Manager::from(&sheet);
+        let light = Theme::new();
+        light.set("size", 1.0);
+        light.set("margin", 1.0);
+        let dark = Theme::new();
+        dark.set("size", 2.0);
+        dark.set("margin", 2.0);
+        let schema = file::Schema::from_theme(&light);
+        manager.register("light", light);
+        manager.register("dark", dark);
+        manager.load_file("custom", r#"{ "size": 3.0 }"#, &schema).unwrap();
+        manager.set_enabled(["light"]);
+        manager.add_overlay("custom");
+        assert_eq!((number(&sheet, "size"), number(&sheet, "margin")), (Some(3.0), Some(1.0)));
+
+        manager.set_enabled(["dark"]);
+        assert_eq!((number(&sheet, "size"), number(&sheet, "margin")), (Some(3.0), Some(2.0)));
+        assert_eq!(manager.enabled(), vec!["dark".to_string()]);
+        assert_eq!(manager.overlays(), vec!["custom".to_string()]);
+
+        // Reloading the overlay's file applies the new values in the next update.
+        manager.load_file("custom", r#"{ "size": 4.0 }"#, &schema).unwrap();
+        manager.update();
+        assert_eq!(number(&sheet, "size"), Some(4.0));
+    }
+}
@@ -26,6 +26,7 @@ use ensogl_core::prelude::*;
 use enso_frp as frp;
 use ensogl_core::application::Application;
 use ensogl_core::display::navigation::navigator::Navigator;
+use ensogl_list_editor::Layout;
 use ensogl_list_editor::ListEditor;
 use ensogl_slider as slider;
 use ensogl_text_msdf::run_once_initialized;
@@ -95,6 +96,18 @@ fn run() {
     // list_editor2.debug(true);
     mem::forget(list_editor2);
 
+    let list_editor3 = new_list_editor(&app);
+    list_editor3.layout(Layout::Vertical);
+    list_editor3.set_xy((-250.0, 100.0));
+    world.add_child(&list_editor3);
+    mem::forget(list_editor3);
+
+    let list_editor4 = new_list_editor(&app);
+    list_editor4.layout(Layout::Wrapped { max_width: 450.0 });
+    list_editor4.set_y(-100.0);
+    world.add_child(&list_editor4);
+    mem::forget(list_editor4);
+
     world.keep_alive_forever();
     mem::forget(navigator);
 }
@@ -15,6 +15,7 @@ use ide_ci::prelude::*;
 use crate::Paths;
 
 use enso_prelude::anyhow;
+use ensogl_text_atlas::prebuilt;
 use ide_ci::programs::shaderc::Glslc;
 use ide_ci::programs::shaderc::SpirvOpt;
 use ide_ci::programs::spirv_cross::SpirvCross;
@@ -27,6 +28,7 @@ use std::hash::Hasher;
 // =============
 
 /// Bring the dynamic assets up-to-date, for the current asset sources. This consists of:
+/// - On Linux, generate the font atlas sources natively (see [`generate_font_sources`]).
 /// - Scan the asset source directory tree, hashing the input files.
 /// - Update the assets:
 ///   - For each asset-source directory, determine an output directory based on the inputs name and
@@ -36,6 +38,9 @@ use std::hash::Hasher;
 ///   - Generate a manifest, identifying the current assets and paths to their sources.
 pub async fn build(paths: &Paths) -> Result<()> {
     info!("Building dynamic assets.");
+    if cfg!(target_os = "linux") {
+        generate_font_sources(paths).await?;
+    }
     let sources = survey_asset_sources(paths)?;
     let assets = update_assets(paths, &sources).await?;
     let manifest = serde_json::to_string(&assets)?;
@@ -329,6 +334,27 @@ fn gc_assets(paths: &Paths, assets: &AssetManifest) -> Result<()> {
 // === Fonts ===
 // =============
 
+/// Generate the glyph atlases of the embedded fonts with the native MSDF generator, and write them
+/// to the font asset sources directory, replacing the atlases extracted from the application.
+///
+/// The native generator produces the same atlases as the application does, without depending on
+/// the WASM build of msdfgen. This is done on Linux only; on other platforms the atlases extracted
+/// from the application are used.
+async fn generate_font_sources(paths: &Paths) -> Result<()> {
+    let fonts_dir = paths.target.ensogl_pack.dynamic_assets.join(Builder::Font.dir_name());
+    for name in prebuilt::PRELOAD_TYPEFACES {
+        info!("Generating the glyph atlas of the `{name}` font.");
+        let files =
+            tokio::task::spawn_blocking(move || prebuilt::build_atlas_files(name)).await??;
+        let dir = fonts_dir.join(name);
+        ide_ci::fs::tokio::create_dir_if_missing(&dir).await?;
+        for (file_name, contents) in files {
+            ide_ci::fs::tokio::write(dir.join(file_name), contents).await?;
+        }
+    }
+    Ok(())
+}
+
 async fn build_font(input_dir: &Path, input_files: &[String], output_dir: &Path) -> Result<()> {
     for file_name in input_files {
         crate::copy(input_dir.join(file_name), output_dir.join(file_name))?;
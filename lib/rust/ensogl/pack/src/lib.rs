@@ -84,7 +84,9 @@
 //! `pkg.js`, `snippets`, and `runtime-libs.js` into a single bundle.
 //!
 //! 4. The program `target/ensogl-pack/dist/asset-extractor.cjs` is run. It loads
-//! `target/dist/pkg.wasm` and writes asset sources to `target/ensogl-pack/dynamic-assets`.
+//! `target/dist/pkg.wasm` and writes asset sources to `target/ensogl-pack/dynamic-assets`. On
+//! Linux, the font asset sources are then re-generated natively, using the pure-Rust MSDF
+//! generator.
 //!
 //! 5. For each asset, its inputs are hashed and an output directory is determined based on its
 //! name and input hash. If the output directory doesn't already exist, the asset is built, and the
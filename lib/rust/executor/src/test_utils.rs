@@ -5,10 +5,26 @@ use crate::prelude::*;
 use crate::global::set_spawner;
 use crate::global::spawn;
 
+use enso_web::clock::VirtualClock;
 use futures::executor;
+use futures::task::noop_waker_ref;
+use futures::task::ArcWake;
+use std::panic::AssertUnwindSafe;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::Condvar;
+use std::sync::Mutex;
+use std::task::Context;
+use std::task::Poll;
+use std::task::Waker;
+use std::time::Duration;
 
 
 
+// =================================
+// === TestWithLocalPoolExecutor ===
+// =================================
+
 /// A fixture for tests which makes able to run part of tests as asynchronous tasks in
 /// LocalPoolExecutor. All spawned task will be run before dropping this structure - if some
 /// task will be blocked, panic will be raised.
@@ -109,3 +125,593 @@ impl Drop for TestWithLocalPoolExecutor {
         }
     }
 }
+
+
+
+// ============================
+// === TestWithVirtualClock ===
+// ============================
+
+/// The maximum number of timers fired by [`TestWithVirtualClock::run_until_complete`] before
+/// assuming that the awaited future will never complete (e.g. because it waits for a condition
+/// which a repeating timer never fulfills).
+const MAX_TIMERS_FIRED_WHILE_WAITING: usize = 100_000;
+
+/// A fixture for tests of asynchronous code depending on the passage of time, like timeouts and
+/// retries.
+///
+/// Like [`TestWithLocalPoolExecutor`], it sets up a global spawner backed by a `LocalPool`.
+/// Additionally, it installs a [`VirtualClock`] for the current thread, so [`enso_web::sleep`],
+/// the FRP timers and everything built on top of them (like json-rpc request timeouts) are driven
+/// by this fixture: the time passes only when [`Self::advance`] or
+/// [`Self::run_until_complete`] are called, and it passes instantly.
+///
+/// All tasks run on the thread which set up the fixture, so they do not need to be `Send`, like the
+/// controllers and the FRP timers. Both the spawner and the clock are thread-local, so tests using
+/// this fixture may run in parallel on separate threads without affecting each other. To test
+/// `Send` futures on multiple threads, use [`TestWithMultiThreadedVirtualClock`].
+#[derive(Debug)]
+pub struct TestWithVirtualClock {
+    executor: executor::LocalPool,
+    clock:    VirtualClock,
+}
+
+impl TestWithVirtualClock {
+    /// Set up the test fixture.
+    pub fn set_up() -> Self {
+        let executor = executor::LocalPool::new();
+        let clock = VirtualClock::new();
+        set_spawner(executor.spawner());
+        clock.install();
+        Self { executor, clock }
+    }
+
+    /// The virtual clock driving the time in this fixture.
+    pub fn clock(&self) -> &VirtualClock {
+        &self.clock
+    }
+
+    /// The virtual time elapsed since the fixture was set up.
+    pub fn now(&self) -> Duration {
+        self.clock.now()
+    }
+
+    /// Spawn new task in executor.
+    pub fn spawn(&mut self, task: impl Future<Output = ()> + 'static) {
+        spawn(task);
+    }
+
+    /// Runs all tasks in the pool and returns if no more progress can be made on any task without
+    /// advancing the time.
+    pub fn run_until_stalled(&mut self) {
+        self.executor.run_until_stalled();
+    }
+
+    /// Advance the time by `duration`. All timers with deadlines in that period are fired in
+    /// order, and after each of them the tasks are run until stalled, so the tasks may register
+    /// new timers which will be fired in the same call if their deadlines are early enough.
+    pub fn advance(&mut self, duration: Duration) {
+        self.advance_to(self.now() + duration);
+    }
+
+    /// Advance the time to the given point. See [`Self::advance`].
+    pub fn advance_to(&mut self, time: Duration) {
+        let clock = self.clock.clone_ref();
+        advance_to(&clock, time, || self.run_until_stalled());
+    }
+
+    /// Advance the time to the earliest pending timer's deadline, fire it and run tasks until
+    /// stalled. Returns `false` if there were no pending timers.
+    pub fn advance_to_next_timer(&mut self) -> bool {
+        let clock = self.clock.clone_ref();
+        advance_to_next_timer(&clock, || self.run_until_stalled())
+    }
+
+    /// Run tasks and advance the time until the given future completes, returning its output.
+    /// Panics if the future cannot complete, because all tasks are stalled and there are no
+    /// pending timers.
+    pub fn run_until_complete<R>(&mut self, fut: impl Future<Output = R>) -> R {
+        let mut fut = fut.boxed_local();
+        let mut context = Context::from_waker(noop_waker_ref());
+        let clock = self.clock.clone_ref();
+        wait_for(
+            &clock,
+            || self.run_until_stalled(),
+            || match fut.poll_unpin(&mut context) {
+                Poll::Ready(result) => Some(result),
+                Poll::Pending => None,
+            },
+        )
+    }
+
+    /// Run all tasks until stalled and try retrieving value from the future without advancing the
+    /// time. Panics if the future is not ready.
+    pub fn expect_completion<R>(&mut self, fut: impl Future<Output = R>) -> R {
+        self.run_until_stalled();
+        fut.boxed_local().expect_ready()
+    }
+}
+
+impl Drop for TestWithVirtualClock {
+    fn drop(&mut self) {
+        if self.clock.is_installed() {
+            VirtualClock::uninstall();
+        }
+    }
+}
+
+
+
+// === Advancing Time ===
+
+/// Advance the `clock` to the given point, firing the timers in order and running tasks until
+/// stalled after each of them.
+fn advance_to(clock: &VirtualClock, time: Duration, mut run_until_stalled: impl FnMut()) {
+    run_until_stalled();
+    while clock.fire_next_until(time) {
+        run_until_stalled();
+    }
+    clock.advance_to(time);
+}
+
+/// Advance the `clock` to the earliest pending timer's deadline, fire it and run tasks until
+/// stalled. Returns `false` if there were no pending timers.
+fn advance_to_next_timer(clock: &VirtualClock, mut run_until_stalled: impl FnMut()) -> bool {
+    run_until_stalled();
+    match clock.next_deadline() {
+        Some(deadline) => {
+            clock.fire_next_until(deadline);
+            run_until_stalled();
+            true
+        }
+        None => false,
+    }
+}
+
+/// Run tasks and advance the `clock` until `result` returns a value. Panics if all tasks are
+/// stalled and there are no pending timers.
+fn wait_for<R>(
+    clock: &VirtualClock,
+    mut run_until_stalled: impl FnMut(),
+    mut result: impl FnMut() -> Option<R>,
+) -> R {
+    for _ in 0..MAX_TIMERS_FIRED_WHILE_WAITING {
+        run_until_stalled();
+        if let Some(result) = result() {
+            return result;
+        }
+        if !advance_to_next_timer(clock, &mut run_until_stalled) {
+            panic!("The future cannot complete: the executor is stalled and no timer is set.");
+        }
+    }
+    panic!("The future did not complete after firing {MAX_TIMERS_FIRED_WHILE_WAITING} timers.");
+}
+
+
+
+// =========================================
+// === TestWithMultiThreadedVirtualClock ===
+// =========================================
+
+/// The number of worker threads of [`TestWithMultiThreadedVirtualClock`]. Fixed, so the tasks are
+/// run on multiple threads regardless of the number of CPUs.
+const WORKER_THREADS: usize = 4;
+
+/// A multi-threaded version of [`TestWithVirtualClock`], for testing `Send` futures.
+///
+/// The tasks are run by a thread pool, and the same [`VirtualClock`] is installed on its worker
+/// threads and on the thread which set up the fixture, so [`enso_web::sleep`] is driven by this
+/// fixture regardless of where the sleeping future was created and where it is polled. The fixture
+/// tracks when its tasks are woken and polled, so [`Self::run_until_stalled`] blocks until no task
+/// can make progress without advancing the time.
+///
+/// The timers with callbacks, like the FRP timers, are bound to the thread setting them, so they
+/// should not be set by the tasks of this fixture. The global spawner is not set up either, so the
+/// tasks should be spawned using [`Self::spawn`].
+#[derive(Debug)]
+pub struct TestWithMultiThreadedVirtualClock {
+    pool:    executor::ThreadPool,
+    tracker: Arc<TaskTracker>,
+    clock:   VirtualClock,
+}
+
+impl TestWithMultiThreadedVirtualClock {
+    /// Set up the test fixture.
+    pub fn set_up() -> Self {
+        let clock = VirtualClock::new();
+        let worker_clock = clock.clone_ref();
+        let pool = executor::ThreadPool::builder()
+            .pool_size(WORKER_THREADS)
+            .name_prefix("test-worker-")
+            .after_start(move |_| worker_clock.install())
+            .before_stop(|_| VirtualClock::uninstall())
+            .create()
+            .expect("Failed to create the thread pool.");
+        let tracker = default();
+        clock.install();
+        Self { pool, tracker, clock }
+    }
+
+    /// The virtual clock driving the time in this fixture.
+    pub fn clock(&self) -> &VirtualClock {
+        &self.clock
+    }
+
+    /// The virtual time elapsed since the fixture was set up.
+    pub fn now(&self) -> Duration {
+        self.clock.now()
+    }
+
+    /// Spawn new task in the thread pool.
+    pub fn spawn(&mut self, task: impl Future<Output = ()> + Send + 'static) {
+        self.pool.spawn_ok(TrackedTask::new(&self.tracker, task));
+    }
+
+    /// Block until no task can make progress without advancing the time. Panics if any task has
+    /// panicked.
+    pub fn run_until_stalled(&mut self) {
+        self.tracker.wait_until_stalled();
+        if let Some(message) = self.tracker.panics.lock().unwrap().first() {
+            panic!("A task panicked: {message}");
+        }
+    }
+
+    /// Advance the time by `duration`. See [`TestWithVirtualClock::advance`].
+    pub fn advance(&mut self, duration: Duration) {
+        self.advance_to(self.now() + duration);
+    }
+
+    /// Advance the time to the given point. See [`TestWithVirtualClock::advance`].
+    pub fn advance_to(&mut self, time: Duration) {
+        let clock = self.clock.clone_ref();
+        advance_to(&clock, time, || self.run_until_stalled());
+    }
+
+    /// Advance the time to the earliest pending timer's deadline, fire it and run tasks until
+    /// stalled. Returns `false` if there were no pending timers.
+    pub fn advance_to_next_timer(&mut self) -> bool {
+        let clock = self.clock.clone_ref();
+        advance_to_next_timer(&clock, || self.run_until_stalled())
+    }
+
+    /// Spawn the future in the thread pool, then run tasks and advance the time until it completes,
+    /// returning its output. Panics if the future cannot complete, because all tasks are stalled
+    /// and there are no pending timers.
+    pub fn run_until_complete<R: Send + 'static>(
+        &mut self,
+        fut: impl Future<Output = R> + Send + 'static,
+    ) -> R {
+        let result = Arc::new(Mutex::new(None));
+        let task_result = result.clone();
+        self.spawn(async move {
+            let output = fut.await;
+            *task_result.lock().unwrap() = Some(output);
+        });
+        let clock = self.clock.clone_ref();
+        wait_for(&clock, || self.run_until_stalled(), || result.lock().unwrap().take())
+    }
+}
+
+impl Drop for TestWithMultiThreadedVirtualClock {
+    fn drop(&mut self) {
+        if self.clock.is_installed() {
+            VirtualClock::uninstall();
+        }
+    }
+}
+
+
+// === TaskTracker ===
+
+/// Counts the tasks of [`TestWithMultiThreadedVirtualClock`] which are scheduled or being polled.
+/// When the count drops to zero, the tasks are stalled.
+#[derive(Debug, Default)]
+struct TaskTracker {
+    active:  Mutex<usize>,
+    stalled: Condvar,
+    panics:  Mutex<Vec<String>>,
+}
+
+impl TaskTracker {
+    fn increment(&self) {
+        *self.active.lock().unwrap() += 1;
+    }
+
+    fn decrement(&self) {
+        let mut active = self.active.lock().unwrap();
+        *active -= 1;
+        if *active == 0 {
+            self.stalled.notify_all();
+        }
+    }
+
+    fn wait_until_stalled(&self) {
+        let active = self.active.lock().unwrap();
+        drop(self.stalled.wait_while(active, |active| *active > 0).unwrap());
+    }
+}
+
+
+// === TrackedTask ===
+
+/// The scheduling state of a [`TrackedTask`]. The task is counted as active in the
+/// [`TaskTracker`] while it is scheduled or being polled.
+#[derive(Debug, Default)]
+struct TaskState {
+    scheduled: bool,
+    polled:    bool,
+    finished:  bool,
+    /// The waker of the thread pool's task, woken when the tracked task is woken.
+    waker:     Option<Waker>,
+}
+
+/// The waker of a [`TrackedTask`], scheduling it in the [`TaskTracker`] before waking the thread
+/// pool's task.
+#[derive(Debug)]
+struct TaskWaker {
+    tracker: Arc<TaskTracker>,
+    state:   Mutex<TaskState>,
+}
+
+impl ArcWake for TaskWaker {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        let mut state = arc_self.state.lock().unwrap();
+        if !state.finished && !state.scheduled {
+            state.scheduled = true;
+            if !state.polled {
+                arc_self.tracker.increment();
+            }
+            let waker = state.waker.clone();
+            drop(state);
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        }
+    }
+}
+
+/// A task of [`TestWithMultiThreadedVirtualClock`], wrapping the spawned future to track when it
+/// is scheduled and polled, and to catch its panics.
+struct TrackedTask {
+    future: Pin<Box<dyn Future<Output = ()> + Send>>,
+    waker:  Arc<TaskWaker>,
+}
+
+impl TrackedTask {
+    fn new(tracker: &Arc<TaskTracker>, future: impl Future<Output = ()> + Send + 'static) -> Self {
+        let state = Mutex::new(TaskState { scheduled: true, ..default() });
+        tracker.increment();
+        let waker = Arc::new(TaskWaker { tracker: tracker.clone(), state });
+        Self { future: Box::pin(future), waker }
+    }
+}
+
+impl Future for TrackedTask {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let tracker = &self.waker.tracker;
+        {
+            let mut state = self.waker.state.lock().unwrap();
+            if !state.scheduled {
+                tracker.increment();
+            }
+            state.scheduled = false;
+            state.polled = true;
+            state.waker = Some(cx.waker().clone());
+        }
+        let waker = futures::task::waker(self.waker.clone());
+        let mut context = Context::from_waker(&waker);
+        let future = &mut self.future;
+        let result = std::panic::catch_unwind(AssertUnwindSafe(|| future.poll_unpin(&mut context)));
+        let finished = match result {
+            Ok(poll) => poll.is_ready(),
+            Err(payload) => {
+                let message = payload
+                    .downcast_ref::<&str>()
+                    .map(|message| message.to_string())
+                    .or_else(|| payload.downcast_ref::<String>().cloned())
+                    .unwrap_or_default();
+                self.waker.tracker.panics.lock().unwrap().push(message);
+                true
+            }
+        };
+        let mut state = self.waker.state.lock().unwrap();
+        state.polled = false;
+        state.finished = finished;
+        if finished || !state.scheduled {
+            self.waker.tracker.decrement();
+        }
+        if finished {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use enso_web::sleep;
+    use ensogl_core::frp;
+    use futures::future;
+
+    #[test]
+    fn sleep_completes_after_advancing_time() {
+        let mut fixture = TestWithVirtualClock::set_up();
+        let finished = Rc::new(Cell::new(false));
+        let finished_in_task = finished.clone_ref();
+        fixture.spawn(async move {
+            sleep(Duration::from_secs(10)).await;
+            finished_in_task.set(true);
+        });
+        fixture.advance(Duration::from_secs(9));
+        assert!(!finished.get());
+        fixture.advance(Duration::from_secs(1));
+        assert!(finished.get());
+        assert_eq!(fixture.now(), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn timers_fire_in_deadline_order() {
+        let mut fixture = TestWithVirtualClock::set_up();
+        let log = Rc::new(RefCell::new(Vec::new()));
+        for delay in [30, 10, 20] {
+            let log = log.clone_ref();
+            fixture.spawn(async move {
+                sleep(Duration::from_millis(delay)).await;
+                log.borrow_mut().push(delay);
+            });
+        }
+        fixture.advance(Duration::from_millis(25));
+        assert_eq!(*log.borrow(), vec![10, 20]);
+        fixture.advance(Duration::from_millis(5));
+        assert_eq!(*log.borrow(), vec![10, 20, 30]);
+    }
+
+    #[test]
+    fn run_until_complete_advances_time_as_needed() {
+        let mut fixture = TestWithVirtualClock::set_up();
+        let result = fixture.run_until_complete(async {
+            sleep(Duration::from_millis(100)).await;
+            sleep(Duration::from_millis(100)).await;
+            sleep(Duration::from_millis(100)).await;
+            7
+        });
+        assert_eq!(result, 7);
+        assert_eq!(fixture.now(), Duration::from_millis(300));
+    }
+
+    #[test]
+    fn timeout_wins_with_never_completing_future() {
+        let mut fixture = TestWithVirtualClock::set_up();
+        let timeout = sleep(Duration::from_secs(15)).boxed_local();
+        let request = future::pending::<()>();
+        let result = fixture.run_until_complete(future::select(request, timeout));
+        assert!(matches!(result, future::Either::Right(_)));
+        assert_eq!(fixture.now(), Duration::from_secs(15));
+    }
+
+    #[test]
+    fn sleep_deadline_is_set_when_called() {
+        let mut fixture = TestWithVirtualClock::set_up();
+        let sleep = sleep(Duration::from_secs(10));
+        fixture.advance(Duration::from_secs(5));
+        fixture.run_until_complete(sleep);
+        assert_eq!(fixture.now(), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn frp_timers_are_driven_by_virtual_clock() {
+        let mut fixture = TestWithVirtualClock::set_up();
+        let network = frp::Network::new("test");
+        let timeout = frp::io::timer::Timeout::new(&network);
+        let interval = frp::io::timer::Interval::new(&network);
+        frp::extend! { network
+            expired_count <- timeout.on_expired.count();
+            expired <- expired_count.sampler();
+            ticks_count <- interval.on_interval.count();
+            ticks <- ticks_count.sampler();
+        }
+        timeout.restart.emit(50);
+        interval.restart.emit(20);
+        fixture.advance(Duration::from_millis(49));
+        assert_eq!(expired.value(), 0);
+        assert_eq!(ticks.value(), 2);
+        fixture.advance(Duration::from_millis(1));
+        assert_eq!(expired.value(), 1);
+        interval.stop.emit(());
+        fixture.advance(Duration::from_millis(100));
+        assert_eq!(expired.value(), 1);
+        assert_eq!(ticks.value(), 2);
+        assert_eq!(fixture.clock().pending_timers_count(), 0);
+    }
+
+    #[test]
+    fn fixtures_on_separate_threads_are_independent() {
+        let run = |delay: u64| {
+            std::thread::spawn(move || {
+                let mut fixture = TestWithVirtualClock::set_up();
+                fixture.run_until_complete(sleep(Duration::from_secs(delay)));
+                fixture.now()
+            })
+        };
+        let threads = [run(5), run(10)];
+        let times = threads.map(|thread| thread.join().unwrap());
+        assert_eq!(times, [Duration::from_secs(5), Duration::from_secs(10)]);
+        assert!(VirtualClock::current().is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn waiting_for_stalled_future_panics() {
+        let mut fixture = TestWithVirtualClock::set_up();
+        fixture.run_until_complete(future::pending::<()>());
+    }
+
+    #[test]
+    fn multi_threaded_sleeps_are_driven_by_virtual_clock() {
+        let mut fixture = TestWithMultiThreadedVirtualClock::set_up();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let test_thread = std::thread::current().id();
+        for delay in [30, 10, 20] {
+            let log = log.clone();
+            fixture.spawn(async move {
+                sleep(Duration::from_millis(delay)).await;
+                assert_ne!(std::thread::current().id(), test_thread);
+                log.lock().unwrap().push(delay);
+            });
+        }
+        fixture.advance(Duration::from_millis(25));
+        assert_eq!(*log.lock().unwrap(), vec![10, 20]);
+        fixture.advance(Duration::from_millis(5));
+        assert_eq!(*log.lock().unwrap(), vec![10, 20, 30]);
+        assert_eq!(fixture.clock().pending_timers_count(), 0);
+    }
+
+    #[test]
+    fn multi_threaded_tasks_communicate_between_threads() {
+        let mut fixture = TestWithMultiThreadedVirtualClock::set_up();
+        let (sender, receiver) = futures::channel::oneshot::channel();
+        fixture.spawn(async move {
+            sleep(Duration::from_secs(3)).await;
+            sender.send(7).unwrap();
+        });
+        let result = fixture.run_until_complete(async move {
+            let value = receiver.await.unwrap();
+            sleep(Duration::from_secs(2)).await;
+            value
+        });
+        assert_eq!(result, 7);
+        assert_eq!(fixture.now(), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn multi_threaded_timeout_wins_with_never_completing_future() {
+        let mut fixture = TestWithMultiThreadedVirtualClock::set_up();
+        let timeout = sleep(Duration::from_secs(15)).boxed();
+        let request = future::pending::<()>();
+        let result = fixture.run_until_complete(future::select(request, timeout));
+        assert!(matches!(result, future::Either::Right(_)));
+        assert_eq!(fixture.now(), Duration::from_secs(15));
+    }
+
+    #[test]
+    #[should_panic(expected = "A task panicked: Task failure.")]
+    fn multi_threaded_task_panic_is_reported() {
+        let mut fixture = TestWithMultiThreadedVirtualClock::set_up();
+        fixture.spawn(async {
+            sleep(Duration::from_secs(1)).await;
+            panic!("Task failure.");
+        });
+        fixture.advance(Duration::from_secs(1));
+    }
+}
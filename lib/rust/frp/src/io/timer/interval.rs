@@ -4,8 +4,11 @@ use crate::prelude::*;
 
 use crate as frp;
 
+use enso_web::clock::TimerId;
+use enso_web::clock::VirtualClock;
 use enso_web::window;
 use enso_web::Closure;
+use std::time::Duration;
 
 
 // ==============
@@ -36,6 +39,9 @@ type TimerClosure = Closure<dyn FnMut()>;
 /// The timer is based on `setInterval` browser API. That means there is no guarantee about the
 /// exact time the events will be emitted. The true period between events will approach provided
 /// value over time.
+///
+/// If an [`enso_web::clock::VirtualClock`] is installed for the current thread when the timer is
+/// started, the timer is driven by that clock instead.
 #[derive(Clone, CloneRef, Debug)]
 pub struct Interval {
     /// Starts the timer with provided period value, specified in integer milliseconds. If the
@@ -55,8 +61,7 @@ impl Interval {
             on_interval <- any_mut();
         }
 
-        let closure: TimerClosure = Closure::new(f!(on_interval.emit(())));
-        let raw_interval = Rc::new(RawInterval::new(closure));
+        let raw_interval = Rc::new(RawInterval::new(f!(on_interval.emit(()))));
 
         frp::extend! { network
             restart <- any_mut::<i32>();
@@ -74,21 +79,47 @@ impl Interval {
 // === RawInterval ===
 // ===================
 
+/// A handle of a started interval.
 #[derive(Debug)]
+enum IntervalHandle {
+    Web(i32),
+    Virtual(VirtualClock, TimerId),
+}
+
+#[derive(Derivative)]
+#[derivative(Debug)]
 struct RawInterval {
+    #[derivative(Debug = "ignore")]
+    callback:     Rc<dyn Fn()>,
     closure:      TimerClosure,
-    timer_handle: RefCell<Option<i32>>,
+    timer_handle: RefCell<Option<IntervalHandle>>,
 }
 
 impl RawInterval {
-    fn new(closure: TimerClosure) -> Self {
-        Self { closure, timer_handle: default() }
+    fn new(callback: impl Fn() + 'static) -> Self {
+        let callback: Rc<dyn Fn()> = Rc::new(callback);
+        let closure_callback = callback.clone();
+        let closure = Closure::new(move || closure_callback());
+        Self { callback, closure, timer_handle: default() }
     }
 
     fn restart(&self, time: i32) {
-        let js_func = self.closure.as_js_function();
-        let result = window.set_interval_with_callback_and_timeout_and_arguments_0(js_func, time);
-        let handle = result.expect("setInterval should never fail when callback is a function.");
+        let handle = match VirtualClock::current() {
+            Some(clock) => {
+                let callback = self.callback.clone();
+                let period = Duration::from_millis(time.max(0) as u64);
+                let id = clock.set_interval(period, move || callback());
+                IntervalHandle::Virtual(clock, id)
+            }
+            None => {
+                let js_func = self.closure.as_js_function();
+                let result =
+                    window.set_interval_with_callback_and_timeout_and_arguments_0(js_func, time);
+                let handle =
+                    result.expect("setInterval should never fail when callback is a function.");
+                IntervalHandle::Web(handle)
+            }
+        };
         self.set_timer_handle(Some(handle));
     }
 
@@ -96,9 +127,11 @@ impl RawInterval {
         self.set_timer_handle(None);
     }
 
-    fn set_timer_handle(&self, handle: Option<i32>) {
-        if let Some(old_handle) = self.timer_handle.replace(handle) {
-            window.clear_interval_with_handle(old_handle);
+    fn set_timer_handle(&self, handle: Option<IntervalHandle>) {
+        match self.timer_handle.replace(handle) {
+            Some(IntervalHandle::Web(old_handle)) => window.clear_interval_with_handle(old_handle),
+            Some(IntervalHandle::Virtual(clock, id)) => clock.clear(id),
+            None => {}
         }
     }
 }
@@ -4,8 +4,11 @@ use crate::prelude::*;
 
 use crate as frp;
 
+use enso_web::clock::TimerId;
+use enso_web::clock::VirtualClock;
 use enso_web::window;
 use enso_web::Closure;
+use std::time::Duration;
 
 
 // ==============
@@ -39,6 +42,9 @@ type TimerClosure = Closure<dyn FnMut()>;
 /// exact time the event will be emitted. It might be delayed if the browser event loop is busy. If
 /// you need to fire events periodically, prefer [`Interval`] timer in order to avoid unpredictable
 /// event trigger rate.
+///
+/// If an [`enso_web::clock::VirtualClock`] is installed for the current thread when the timer is
+/// started, the timer is driven by that clock instead.
 #[derive(Clone, CloneRef, Debug)]
 pub struct Timeout {
     /// Starts the timer immediately with provided timeout value, specified in integer
@@ -61,8 +67,7 @@ impl Timeout {
             on_expired <- any_mut();
         }
 
-        let closure: TimerClosure = Closure::new(f!(on_expired.emit(())));
-        let raw_timeout = Rc::new(RawTimeout::new(closure));
+        let raw_timeout = Rc::new(RawTimeout::new(f!(on_expired.emit(()))));
 
         frp::extend! { network
             restart <- any_mut::<i32>();
@@ -84,21 +89,47 @@ impl Timeout {
 // === RawTimeout ===
 // ==================
 
+/// A handle of a started timeout.
 #[derive(Debug)]
+enum TimeoutHandle {
+    Web(i32),
+    Virtual(VirtualClock, TimerId),
+}
+
+#[derive(Derivative)]
+#[derivative(Debug)]
 struct RawTimeout {
+    #[derivative(Debug = "ignore")]
+    callback:     Rc<dyn Fn()>,
     closure:      TimerClosure,
-    timer_handle: RefCell<Option<i32>>,
+    timer_handle: RefCell<Option<TimeoutHandle>>,
 }
 
 impl RawTimeout {
-    fn new(closure: TimerClosure) -> Self {
-        Self { closure, timer_handle: default() }
+    fn new(callback: impl Fn() + 'static) -> Self {
+        let callback: Rc<dyn Fn()> = Rc::new(callback);
+        let closure_callback = callback.clone();
+        let closure = Closure::new(move || closure_callback());
+        Self { callback, closure, timer_handle: default() }
     }
 
     fn restart(&self, time: i32) {
-        let js_func = self.closure.as_js_function();
-        let result = window.set_timeout_with_callback_and_timeout_and_arguments_0(js_func, time);
-        let handle = result.expect("setTimeout should never fail when callback is a function.");
+        let handle = match VirtualClock::current() {
+            Some(clock) => {
+                let callback = self.callback.clone();
+                let delay = Duration::from_millis(time.max(0) as u64);
+                let id = clock.set_timeout(delay, move || callback());
+                TimeoutHandle::Virtual(clock, id)
+            }
+            None => {
+                let js_func = self.closure.as_js_function();
+                let result =
+                    window.set_timeout_with_callback_and_timeout_and_arguments_0(js_func, time);
+                let handle =
+                    result.expect("setTimeout should never fail when callback is a function.");
+                TimeoutHandle::Web(handle)
+            }
+        };
         self.set_timer_handle(Some(handle));
     }
 
@@ -106,9 +137,11 @@ impl RawTimeout {
         self.set_timer_handle(None);
     }
 
-    fn set_timer_handle(&self, handle: Option<i32>) {
-        if let Some(old_handle) = self.timer_handle.replace(handle) {
-            window.clear_timeout_with_handle(old_handle);
+    fn set_timer_handle(&self, handle: Option<TimeoutHandle>) {
+        match self.timer_handle.replace(handle) {
+            Some(TimeoutHandle::Web(old_handle)) => window.clear_timeout_with_handle(old_handle),
+            Some(TimeoutHandle::Virtual(clock, id)) => clock.clear(id),
+            None => {}
         }
     }
 }
@@ -0,0 +1,545 @@
+//! An index of a fixed set of targets, for finding the best matches of a pattern among all of them.
+//!
+//! Searching every target on every keystroke repeats a lot of work: most targets cannot match a
+//! pattern because they lack some of its characters, and a target lacking a character of a pattern
+//! also lacks it for every longer pattern. The [`Index`] records the characters of each target when
+//! it is built, and remembers which targets were not ruled out by the last search; when the next
+//! pattern extends the last one, only those targets are examined.
+
+use crate::*;
+
+use std::cmp::Ordering;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt::Debug;
+
+
+
+// =====================
+// === Character Set ===
+// =====================
+
+/// An over-approximation of a set of characters. Each character is assigned one of 64 bits: ASCII
+/// letters (case-insensitively) and digits have a bit each, and other characters share the rest.
+/// Delimiters are not included, as a space in a pattern matches other characters.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct CharSet(u64);
+
+impl CharSet {
+    fn new(chars: impl IntoIterator<Item = char>) -> Self {
+        let chars = chars.into_iter().filter(|c| !matches!(c, '_' | '.' | ' '));
+        Self(chars.fold(0, |bits, c| bits | 1 << Self::bit(c)))
+    }
+
+    fn bit(c: char) -> u32 {
+        match c.to_ascii_lowercase() {
+            c @ 'a'..='z' => c as u32 - 'a' as u32,
+            c @ '0'..='9' => 26 + c as u32 - '0' as u32,
+            c => 36 + c as u32 % 28,
+        }
+    }
+
+    /// A lower bound on the number of characters in `self` that are not in `other`.
+    fn count_missing_from(self, other: Self) -> u32 {
+        (self.0 & !other.0).count_ones()
+    }
+}
+
+
+
+// =============
+// === Index ===
+// =============
+
+/// A fixed set of targets, prepared for searching with many patterns. See the [module
+/// documentation](self).
+#[derive(Derivative)]
+#[derivative(Debug(bound = "SB: Debug, SB::SubmatchScore: Debug"))]
+pub struct Index<SB: ScoreBuilder> {
+    targets:    Vec<Target>,
+    matcher:    Matcher<SB>,
+    /// The most recent search. Its candidates are reused if the next pattern extends its pattern.
+    last_query: Option<Query>,
+}
+
+#[derive(Debug)]
+struct Target {
+    text:  String,
+    chars: CharSet,
+}
+
+#[derive(Debug)]
+struct Query {
+    /// The normalized pattern.
+    pattern:     String,
+    /// The number of characters of the pattern a target may lack and still be a candidate.
+    max_missing: u32,
+    /// Indexes of the targets that were not ruled out by the pattern.
+    candidates:  Vec<usize>,
+}
+
+impl<SB: ScoreBuilder> Index<SB> {
+    /// Build an index of the given targets. Search results identify targets by their position in
+    /// this sequence.
+    pub fn new<T: Into<String>>(targets: impl IntoIterator<Item = T>) -> Self {
+        let targets = targets.into_iter().map(|text| {
+            let text = text.into();
+            let chars = CharSet::new(text.chars());
+            Target { text, chars }
+        });
+        Self { targets: targets.collect(), matcher: default(), last_query: default() }
+    }
+
+    /// The number of targets in the index.
+    pub fn len(&self) -> usize {
+        self.targets.len()
+    }
+
+    /// Return true if the index has no targets.
+    pub fn is_empty(&self) -> bool {
+        self.targets.is_empty()
+    }
+
+    /// The target at the given position.
+    pub fn target(&self, index: usize) -> &str {
+        &self.targets[index].text
+    }
+
+    /// Find the targets matching the pattern, as by [`Matcher::search`]. Returns at most `limit`
+    /// results, best first; equally-good matches are ordered by position in the index.
+    pub fn search(&mut self, pattern: &str, limit: usize) -> Vec<Hit<SB::SubmatchScore>> {
+        let query = self.query(pattern, 0);
+        let mut best = Best::new(limit);
+        for &target in &query.candidates {
+            let text = &self.targets[target].text;
+            if let Some(r#match) = self.matcher.search(&query.pattern, text) {
+                best.push(Hit { target, r#match });
+            }
+        }
+        self.last_query = Some(query);
+        best.into_vec()
+    }
+
+    /// Select the candidates for a search: the targets lacking at most `max_missing` of the
+    /// characters of the pattern. If the pattern extends the pattern of the last search with the
+    /// same `max_missing`, only the candidates of that search are considered.
+    fn query(&mut self, pattern: &str, max_missing: u32) -> Query {
+        let pattern: String = normalize_pattern(pattern).collect();
+        let chars = CharSet::new(pattern.chars());
+        let last_query = self.last_query.take().filter(|query| {
+            query.max_missing == max_missing && pattern.starts_with(&query.pattern)
+        });
+        let mut candidates = match last_query {
+            Some(query) => query.candidates,
+            None => (0..self.targets.len()).collect(),
+        };
+        let targets = &self.targets;
+        candidates.retain(|&i| chars.count_missing_from(targets[i].chars) <= max_missing);
+        Query { pattern, max_missing, candidates }
+    }
+}
+
+impl<SB: ScoreBuilder> Index<SB>
+where SB::SubmatchScore: TypoScore
+{
+    /// Find the targets matching the pattern, as by [`Matcher::search_tolerant`]. Returns at most
+    /// `limit` results, best first; equally-good matches are ordered by position in the index.
+    pub fn search_tolerant(
+        &mut self,
+        pattern: &str,
+        limit: usize,
+        tolerance: TypoTolerance,
+    ) -> Vec<Hit<SB::SubmatchScore>> {
+        // Each edit can replace at most one character missing from a target, so the candidates
+        // must be selected with the greatest number of edits any extension of the pattern allows.
+        let query = self.query(pattern, tolerance.max_edits);
+        let chars = CharSet::new(query.pattern.chars());
+        let mut best = Best::new(limit);
+        let mut inexact = Vec::new();
+        for &target in &query.candidates {
+            let Target { text, chars: target_chars } = &self.targets[target];
+            let missing = chars.count_missing_from(*target_chars);
+            let exact = (missing == 0).then(|| self.matcher.search(&query.pattern, text)).flatten();
+            match exact {
+                Some(r#match) => best.push(Hit { target, r#match }),
+                None => inexact.push((target, missing)),
+            }
+        }
+        // Any corrected match scores lower than any exact match.
+        let max_edits = tolerance.edits_allowed(&query.pattern);
+        if best.len() < limit {
+            for (target, missing) in inexact {
+                if missing <= max_edits {
+                    let text = &self.targets[target].text;
+                    if let Some(r#match) =
+                        self.matcher.search_corrected(&query.pattern, text, max_edits)
+                    {
+                        best.push(Hit { target, r#match });
+                    }
+                }
+            }
+        }
+        self.last_query = Some(query);
+        best.into_vec()
+    }
+}
+
+impl<SB: ScoreBuilder, T: Into<String>> FromIterator<T> for Index<SB> {
+    fn from_iter<I: IntoIterator<Item = T>>(targets: I) -> Self {
+        Self::new(targets)
+    }
+}
+
+fn default<T: Default>() -> T {
+    Default::default()
+}
+
+
+// === Best ===
+
+/// The best hits found so far, up to a limit.
+#[derive(Debug)]
+struct Best<Score> {
+    hits:  BinaryHeap<Reverse<Hit<Score>>>,
+    limit: usize,
+}
+
+impl<Score: Ord> Best<Score> {
+    fn new(limit: usize) -> Self {
+        Self { hits: BinaryHeap::with_capacity(limit + 1), limit }
+    }
+
+    fn len(&self) -> usize {
+        self.hits.len()
+    }
+
+    fn push(&mut self, hit: Hit<Score>) {
+        self.hits.push(Reverse(hit));
+        if self.hits.len() > self.limit {
+            self.hits.pop();
+        }
+    }
+
+    /// The hits, best first.
+    fn into_vec(self) -> Vec<Hit<Score>> {
+        self.hits.into_sorted_vec().into_iter().map(|Reverse(hit)| hit).collect()
+    }
+}
+
+
+// === Hit ===
+
+/// A target matched by a search of an [`Index`]. Better matches compare greater.
+#[derive(Debug, Clone)]
+pub struct Hit<Score> {
+    /// The position of the target in the index.
+    pub target:  usize,
+    /// The result of matching the pattern against the target.
+    pub r#match: Match<Score>,
+}
+
+impl<Score: Ord> PartialEq for Hit<Score> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<Score: Ord> Eq for Hit<Score> {}
+
+impl<Score: Ord> PartialOrd for Hit<Score> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<Score: Ord> Ord for Hit<Score> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let by_score = Ord::cmp(&self.r#match, &other.r#match);
+        by_score.then_with(|| Ord::cmp(&other.target, &self.target))
+    }
+}
+
+
+
+// ===============
+// === Corpora ===
+// ===============
+
+/// Generate a corpus resembling the entries of a suggestion database: method names of one to three
+/// words, mostly qualified by a type name.
+#[cfg(test)]
+fn suggestion_corpus(len: usize) -> Vec<String> {
+    use rand::seq::SliceRandom;
+    use rand::Rng;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    const TYPES: &[&str] = &[
+        "Table",
+        "Column",
+        "Text",
+        "Vector",
+        "Integer",
+        "Decimal",
+        "Date",
+        "Date_Time",
+        "File",
+        "Json",
+        "Map",
+        "Range",
+        "Boolean",
+        "Regex",
+        "Connection",
+        "Data_Formatter",
+        "Aggregate",
+    ];
+    const WORDS: &[&str] = &[
+        "filter",
+        "by",
+        "read",
+        "write",
+        "file",
+        "to",
+        "from",
+        "text",
+        "join",
+        "sort",
+        "order",
+        "group",
+        "aggregate",
+        "map",
+        "at",
+        "first",
+        "last",
+        "take",
+        "drop",
+        "select",
+        "columns",
+        "rows",
+        "is",
+        "empty",
+        "nothing",
+        "parse",
+        "format",
+        "split",
+        "replace",
+        "contains",
+        "starts",
+        "with",
+        "ends",
+        "length",
+        "count",
+        "sum",
+        "mean",
+        "median",
+        "distinct",
+        "set",
+        "remove",
+        "rename",
+        "cast",
+        "expression",
+        "value",
+        "type",
+        "index",
+        "of",
+        "trim",
+        "pad",
+    ];
+    let mut rng = ChaCha8Rng::seed_from_u64(0);
+    let mut corpus = Vec::with_capacity(len);
+    while corpus.len() < len {
+        let words = rng.gen_range(1..=3);
+        let name: Vec<_> = (0..words).map(|_| *WORDS.choose(&mut rng).unwrap()).collect();
+        let name = name.join("_");
+        match rng.gen_range(0..8) {
+            0 => corpus.push(name),
+            _ => corpus.push(format!("{}.{}", TYPES.choose(&mut rng).unwrap(), name)),
+        }
+    }
+    corpus
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    fn targets<S>(index: &Index<PenaltyBuilder>, hits: &[Hit<S>]) -> Vec<String> {
+        hits.iter().map(|hit| index.target(hit.target).to_owned()).collect()
+    }
+
+    /// Find the best matches by searching every target, without an index.
+    fn search_all(corpus: &[String], pattern: &str, limit: usize) -> Vec<(usize, Penalty)> {
+        let mut matcher = Matcher::<PenaltyBuilder>::default();
+        let mut hits: Vec<_> = corpus
+            .iter()
+            .enumerate()
+            .filter_map(|(i, target)| {
+                let r#match = matcher.search_tolerant(pattern, target, TypoTolerance::default())?;
+                Some(Hit { target: i, r#match })
+            })
+            .collect();
+        hits.sort_by(|a, b| b.cmp(a));
+        hits.truncate(limit);
+        hits.into_iter().map(|hit| (hit.target, hit.r#match.score)).collect()
+    }
+
+    #[test]
+    fn test_best_matches_first() {
+        let mut index: Index<PenaltyBuilder> =
+            ["Table.filter_rows", "filter", "Column.filter", "filter", "Table.select"]
+                .into_iter()
+                .collect();
+        assert_eq!(index.len(), 5);
+        let hits = index.search("filter", 3);
+        assert_eq!(targets(&index, &hits), ["filter", "filter", "Column.filter"]);
+        assert_eq!(hits.iter().map(|hit| hit.target).collect::<Vec<_>>(), [1, 3, 2]);
+        let hits = index.search("filter", 10);
+        assert_eq!(hits.len(), 4);
+        assert!(index.search("filter", 0).is_empty());
+        assert!(index.search("xyz", 10).is_empty());
+    }
+
+    #[test]
+    fn test_candidates_narrow_as_pattern_grows() {
+        let corpus = suggestion_corpus(500);
+        let mut index = Index::<PenaltyBuilder>::new(corpus.iter().cloned());
+        let mut candidates = index.len();
+        for pattern in ["f", "fi", "fil", "filt", "filte", "filter", "filter_b", "filter_by"] {
+            let hits = index.search(pattern, 10);
+            let mut fresh = Index::<PenaltyBuilder>::new(corpus.iter().cloned());
+            assert_eq!(hits, fresh.search(pattern, 10), "{pattern}");
+            let query = index.last_query.as_ref().unwrap();
+            assert!(query.candidates.len() <= candidates);
+            assert_eq!(query.candidates.len(), fresh.last_query.unwrap().candidates.len());
+            candidates = query.candidates.len();
+        }
+        assert!(candidates < index.len() / 2);
+        // A pattern that doesn't extend the previous one considers every target again.
+        index.search("x", 10);
+        assert!(index.last_query.unwrap().candidates.len() > candidates);
+    }
+
+    #[test]
+    fn test_search_tolerant() {
+        let mut index: Index<PenaltyBuilder> =
+            ["Table.fill_nothing", "Table.filter", "Table.first", "filter_rows"]
+                .into_iter()
+                .collect();
+        assert!(index.search("fitler", 10).is_empty());
+        let hits = index.search_tolerant("fitler", 10, default());
+        assert_eq!(targets(&index, &hits), ["filter_rows", "Table.filter"]);
+        assert!(hits.iter().all(|hit| hit.r#match.score.typos == 1));
+        // Exact matches rank above corrected ones.
+        let hits = index.search_tolerant("filt", 10, default());
+        assert_eq!(targets(&index, &hits), ["filter_rows", "Table.filter", "Table.fill_nothing"]);
+    }
+
+    /// Check that searching incrementally with an index gives the same results as matching every
+    /// target, for patterns typed one character at a time.
+    #[test]
+    fn test_index_matches_exhaustive_search() {
+        use rand::seq::SliceRandom;
+        use rand::Rng;
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha8Rng;
+        let corpus = suggestion_corpus(300);
+        let mut index = Index::<PenaltyBuilder>::new(corpus.iter().cloned());
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        for _ in 0..12 {
+            let mut typed: Vec<char> = corpus.choose(&mut rng).unwrap().chars().collect();
+            if rng.gen() && typed.len() > 2 {
+                let i = rng.gen_range(1..typed.len());
+                typed.swap(i - 1, i);
+            }
+            let typed: String = typed.into_iter().collect();
+            for end in typed.char_indices().map(|(i, _)| i).skip(1).chain([typed.len()]) {
+                let pattern = &typed[..end];
+                let hits = index.search_tolerant(pattern, 5, default());
+                let hits: Vec<_> = hits.into_iter().map(|h| (h.target, h.r#match.score)).collect();
+                assert_eq!(hits, search_all(&corpus, pattern, 5), "{pattern}");
+            }
+        }
+    }
+}
+
+
+
+// ==================
+// === Benchmarks ===
+// ==================
+
+#[cfg(test)]
+mod bench {
+    extern crate test;
+
+    use super::*;
+    use crate::test_utils::PenaltyBuilder;
+    use test::Bencher;
+
+    /// The size of a typical suggestion database.
+    const CORPUS_LEN: usize = 5_000;
+
+    fn prefixes(pattern: &str) -> impl Iterator<Item = &str> {
+        (1..=pattern.len()).map(|end| &pattern[..end])
+    }
+
+    /// Find the best matches for each keystroke without an index, by matching every target.
+    #[bench]
+    fn bench_typing_without_index(b: &mut Bencher) {
+        let corpus = suggestion_corpus(CORPUS_LEN);
+        let mut matcher = Matcher::<PenaltyBuilder>::default();
+        b.iter(|| {
+            for pattern in prefixes("filter_by") {
+                let mut hits: Vec<_> = corpus
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(target, text)| {
+                        let r#match = matcher.search(pattern, text)?;
+                        Some(Hit { target, r#match })
+                    })
+                    .collect();
+                hits.sort_by(|a, b| b.cmp(a));
+                hits.truncate(10);
+                test::black_box(hits);
+            }
+        });
+    }
+
+    /// Find the best matches for each keystroke using an index.
+    #[bench]
+    fn bench_typing_with_index(b: &mut Bencher) {
+        let mut index = Index::<PenaltyBuilder>::new(suggestion_corpus(CORPUS_LEN));
+        b.iter(|| {
+            for pattern in prefixes("filter_by") {
+                test::black_box(index.search(pattern, 10));
+            }
+        });
+    }
+
+    /// Find the best typo-tolerant matches for each keystroke of a misspelled pattern.
+    #[bench]
+    fn bench_typing_tolerant_with_index(b: &mut Bencher) {
+        let mut index = Index::<PenaltyBuilder>::new(suggestion_corpus(CORPUS_LEN));
+        b.iter(|| {
+            for pattern in prefixes("fitler_by") {
+                test::black_box(index.search_tolerant(pattern, 10, default()));
+            }
+        });
+    }
+
+    /// Search for a single pattern, without any candidates from an earlier search.
+    #[bench]
+    fn bench_single_search_with_index(b: &mut Bencher) {
+        let mut index = Index::<PenaltyBuilder>::new(suggestion_corpus(CORPUS_LEN));
+        b.iter(|| {
+            index.last_query = None;
+            test::black_box(index.search("rd_fl", 10))
+        });
+    }
+}
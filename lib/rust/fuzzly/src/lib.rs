@@ -9,6 +9,13 @@
 //! - The matcher may be reused for multiple matches, as a performance optimization. It retains no
 //!   logical state between searches, but reuses buffers, which is much more efficient when
 //!   performing many matches.
+//!
+//! For typo-tolerant matching, the scorer must also implement [`score::TypoScore`]; then
+//! [`Matcher::search_tolerant`] can be used to match patterns such as `fitler` against `filter`.
+//!
+//! To search a fixed set of targets repeatedly, e.g. on every keystroke while a pattern is typed,
+//! build an [`Index`] of the targets. It returns the best matches, and avoids re-examining targets
+//! that were ruled out by an earlier prefix of the pattern.
 
 #![recursion_limit = "256"]
 // === Features ===
@@ -32,7 +39,12 @@
 // === Export ===
 // ==============
 
+pub mod index;
 pub mod score;
+pub mod typo;
+
+pub use index::Index;
+pub use typo::TypoTolerance;
 
 
 
@@ -170,6 +182,8 @@ pub struct Matcher<SB: ScoreBuilder> {
     /// Empty `Vec` used to reuse storage.
     next_states_buffer: Vec<State<'static, WithMatchIndexes<SB>>>,
     best_score:         Vec<Candidate<SB::SubmatchScore>>,
+    /// Storage used by [`search_tolerant`].
+    typo:               typo::Buffers,
 }
 
 impl<SB: ScoreBuilder> Matcher<SB> {
@@ -630,6 +644,66 @@ mod test_utils {
             self
         }
     }
+
+    /// Simple score builder for testing the ordering of results: prefers fewer typos, and then
+    /// fewer skipped characters.
+    #[derive(Debug, Default, Clone)]
+    pub(crate) struct PenaltyBuilder {
+        skipped: u32,
+    }
+
+    impl ScoreBuilder for PenaltyBuilder {
+        type SubmatchScore = Penalty;
+        fn skip_word_chars(&mut self, count: NonZeroU32) {
+            self.skipped += count.get();
+        }
+        fn match_word_char(&mut self) {}
+        fn match_delimiter(&mut self, _pattern: char, _value: char) {}
+        fn skip_delimiter(&mut self, _pattern: Option<char>, _value: char) {
+            self.skipped += 1;
+        }
+        fn finish(&self) -> Self::SubmatchScore {
+            Penalty { typos: 0, skipped: self.skipped }
+        }
+    }
+
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub(crate) struct Penalty {
+        pub typos:   u32,
+        pub skipped: u32,
+    }
+
+    impl PartialOrd for Penalty {
+        fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for Penalty {
+        fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+            Ord::cmp(&(self.typos, self.skipped), &(other.typos, other.skipped)).reverse()
+        }
+    }
+
+    impl SubmatchScore for Penalty {
+        const ANY_PREFIX_MATCH_BEATS_ANY_INITIALS_MATCH: bool = false;
+        fn with_submatch_by_initials_penalty(self) -> Self {
+            self
+        }
+    }
+
+    impl TypoScore for Penalty {
+        fn with_typo_penalty(self, edits: NonZeroU32) -> Self {
+            Self { typos: self.typos + edits.get(), ..self }
+        }
+    }
+
+    impl Add for Penalty {
+        type Output = Self;
+        fn add(self, rhs: Self) -> Self::Output {
+            Self { typos: self.typos + rhs.typos, skipped: self.skipped + rhs.skipped }
+        }
+    }
 }
 
 
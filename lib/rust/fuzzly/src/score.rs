@@ -69,3 +69,14 @@ pub trait SubmatchScore:
     /// as a prefix).
     fn with_submatch_by_initials_penalty(self) -> Self;
 }
+
+
+// === Typo Score ===
+
+/// Score information supporting typo-tolerant matching, with [`crate::Matcher::search_tolerant`].
+pub trait TypoScore: SubmatchScore {
+    /// Adjust the score as appropriate for a match that was only found after correcting the given
+    /// number of typos in the pattern. A corrected match must score lower than any exact match (of
+    /// any target), so that corrections can be skipped when there are enough exact matches.
+    fn with_typo_penalty(self, edits: core::num::NonZeroU32) -> Self;
+}
@@ -0,0 +1,284 @@
+//! Typo-tolerant matching.
+//!
+//! A pattern that does not match a target may still match it after a small number of edits:
+//! insertions, deletions and substitutions of characters, and transpositions of adjacent characters
+//! (that is, within a bounded [optimal string alignment distance][osa]). When exact matching fails,
+//! the pattern is aligned against spans of the target that start at word boundaries; the closest
+//! span is then matched exactly in place of the pattern, so that the resulting score and match
+//! indexes are computed by the ordinary [`Matcher`] algorithm, and then penalized according to the
+//! number of edits by [`TypoScore::with_typo_penalty`].
+//!
+//! Because the correction replaces the pattern with a contiguous span of the target, a pattern is
+//! either matched exactly (possibly by initials or by word prefixes), or corrected as a whole; e.g.
+//! `fitler` matches `Table.filter`, but `tb.fitler` does not.
+//!
+//! [osa]: https://en.wikipedia.org/wiki/Damerau%E2%80%93Levenshtein_distance
+
+use crate::*;
+
+
+
+// ======================
+// === Typo Tolerance ===
+// ======================
+
+/// Limits on how much a pattern may be corrected in a typo-tolerant search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TypoTolerance {
+    /// The maximum number of edits applied to any pattern.
+    pub max_edits:      u32,
+    /// The number of word characters a pattern must contain for each edit allowed. Short patterns
+    /// are not corrected, because after an edit or two they would match nearly anything.
+    pub chars_per_edit: u32,
+}
+
+impl TypoTolerance {
+    /// The number of edits allowed for the given normalized pattern.
+    pub fn edits_allowed(&self, pattern: &str) -> u32 {
+        let word_chars = pattern.chars().filter(|c| !matches!(c, '_' | ' ' | '.')).count() as u32;
+        let edits = word_chars.checked_div(self.chars_per_edit).unwrap_or_default();
+        core::cmp::min(edits, self.max_edits)
+    }
+}
+
+impl Default for TypoTolerance {
+    fn default() -> Self {
+        Self { max_edits: 2, chars_per_edit: 4 }
+    }
+}
+
+
+
+// ==========================
+// === Tolerant Searching ===
+// ==========================
+
+impl<SB: ScoreBuilder> Matcher<SB>
+where SB::SubmatchScore: TypoScore
+{
+    /// Like [`Matcher::search`], but if the pattern does not match the target exactly, tries to
+    /// match it after correcting up to the number of typos allowed by the given tolerance. The
+    /// score of a corrected match is adjusted with [`TypoScore::with_typo_penalty`].
+    pub fn search_tolerant(
+        &mut self,
+        pattern: &str,
+        target: &str,
+        tolerance: TypoTolerance,
+    ) -> Option<Match<SB::SubmatchScore>> {
+        if let Some(exact) = self.search(pattern, target) {
+            return Some(exact);
+        }
+        let pattern: String = normalize_pattern(pattern).collect();
+        let max_edits = tolerance.edits_allowed(&pattern);
+        self.search_corrected(&pattern, target, max_edits)
+    }
+
+    /// Match the normalized pattern against the target after correcting at least one and at most
+    /// `max_edits` typos in the pattern.
+    pub(crate) fn search_corrected(
+        &mut self,
+        pattern: &str,
+        target: &str,
+        max_edits: u32,
+    ) -> Option<Match<SB::SubmatchScore>> {
+        if max_edits == 0 {
+            return None;
+        }
+        let mut buffers = core::mem::take(&mut self.typo);
+        buffers.find_spans(pattern, target, max_edits);
+        let mut corrected = String::new();
+        let result = buffers.spans.iter().find_map(|span| {
+            let edits = NonZeroU32::new(span.edits)?;
+            corrected.clear();
+            corrected.extend(&buffers.target[span.start..span.end]);
+            let corrected_match = self.search(&corrected, target)?;
+            Some(corrected_match.map_score(|score| score.with_typo_penalty(edits)))
+        });
+        self.typo = buffers;
+        result
+    }
+}
+
+
+
+// ===============
+// === Buffers ===
+// ===============
+
+/// Storage used by [`Matcher::search_tolerant`], persisted in the [`Matcher`] to be reused between
+/// searches.
+#[derive(Debug, Default)]
+pub(crate) struct Buffers {
+    /// The normalized pattern.
+    pattern: Vec<char>,
+    /// The case-normalized target.
+    target:  Vec<char>,
+    /// Distances computed for the current target position and the two positions before it, indexed
+    /// by the length of the pattern prefix.
+    rows:    [Vec<u32>; 3],
+    /// Spans of the target that are close enough to the pattern, best first.
+    spans:   Vec<Span>,
+}
+
+/// A span of the target, in characters, and its distance from the pattern.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Span {
+    edits: u32,
+    start: usize,
+    end:   usize,
+}
+
+impl Buffers {
+    /// Find, for every word in the target, the span starting at the word that is closest to the
+    /// pattern, if it is within the given number of edits. The results are stored in `self.spans`,
+    /// ordered by distance and then by position.
+    fn find_spans(&mut self, pattern: &str, target: &str, max_edits: u32) {
+        self.pattern.clear();
+        self.pattern.extend(pattern.chars());
+        self.target.clear();
+        self.target.extend(target.chars().map(|c| c.to_ascii_lowercase()));
+        self.spans.clear();
+        for start in 0..self.target.len() {
+            let at_word_start = !is_boundary(self.target[start])
+                && (start == 0 || is_boundary(self.target[start - 1]));
+            if at_word_start && let Some(span) = self.closest_span(start, max_edits) {
+                self.spans.push(span);
+            }
+        }
+        self.spans.sort_unstable();
+    }
+
+    /// Find the span starting at the given position that is closest to the pattern, preferring the
+    /// longest of equally-close spans.
+    ///
+    /// This computes the optimal string alignment distance of the pattern from each prefix of the
+    /// target suffix, one target character at a time, stopping when no longer span could be close
+    /// enough.
+    fn closest_span(&mut self, start: usize, max_edits: u32) -> Option<Span> {
+        let pattern = &self.pattern;
+        let target = &self.target[start..];
+        let [before_previous, previous, current] = &mut self.rows;
+        current.clear();
+        current.extend(0..=pattern.len() as u32);
+        let mut best: Option<Span> = None;
+        let max_len = core::cmp::min(target.len(), pattern.len() + max_edits as usize);
+        for len in 1..=max_len {
+            core::mem::swap(before_previous, previous);
+            core::mem::swap(previous, current);
+            current.clear();
+            current.push(len as u32);
+            let t = target[len - 1];
+            for (j, &p) in pattern.iter().enumerate() {
+                let substitution = previous[j] + (!chars_match(p, t)) as u32;
+                let insertion = current[j] + 1;
+                let deletion = previous[j + 1] + 1;
+                let mut distance = substitution.min(insertion).min(deletion);
+                let transposed = j > 0
+                    && len > 1
+                    && chars_match(p, target[len - 2])
+                    && chars_match(pattern[j - 1], t);
+                if transposed {
+                    distance = distance.min(before_previous[j - 1] + 1);
+                }
+                current.push(distance);
+            }
+            let edits = current[pattern.len()];
+            if edits <= max_edits && best.map_or(true, |best| edits <= best.edits) {
+                best = Some(Span { edits, start, end: start + len });
+            }
+            if current.iter().all(|&distance| distance > max_edits) {
+                break;
+            }
+        }
+        best
+    }
+}
+
+/// Whether the character is treated as a word boundary in the target.
+fn is_boundary(c: char) -> bool {
+    matches!(c, '_' | '.' | ' ' | '(' | ')')
+}
+
+/// Whether a normalized pattern character matches a case-normalized target character. As in exact
+/// matching, a space in the pattern matches any word boundary.
+fn chars_match(pattern: char, target: char) -> bool {
+    pattern == target || (pattern == ' ' && is_boundary(target))
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    fn search_tolerant(pattern: &str, target: &str) -> Option<Match<Penalty>> {
+        let mut matcher = Matcher::<PenaltyBuilder>::default();
+        matcher.search_tolerant(pattern, target, TypoTolerance::default())
+    }
+
+    #[test]
+    fn test_edits_allowed() {
+        let tolerance = TypoTolerance::default();
+        assert_eq!(tolerance.edits_allowed("fil"), 0);
+        assert_eq!(tolerance.edits_allowed("filt"), 1);
+        assert_eq!(tolerance.edits_allowed("fi_l_t"), 1);
+        assert_eq!(tolerance.edits_allowed("filter_by"), 2);
+        assert_eq!(tolerance.edits_allowed("filter_by_expression"), 2);
+        let strict = TypoTolerance { max_edits: 1, chars_per_edit: 0 };
+        assert_eq!(strict.edits_allowed("filter"), 0);
+    }
+
+    #[test]
+    fn test_corrects_typos() {
+        let cases = [
+            // === Transposition ===
+            ("fitler", "table.FILTER", 1),
+            ("fitl", "table.FILTer", 1),
+            // === Substitution, insertion, deletion ===
+            ("filtar", "FILTER_rows", 1),
+            ("fillter", "FILTER_rows", 1),
+            ("fiter", "FILTER_rows", 1),
+            // === Delimiters ===
+            ("filter rwos", "table.FILTER_ROWS", 1),
+            ("flter_rwos", "FILTER_ROWS", 2),
+        ];
+        for (pattern, target, edits) in cases {
+            let result = search_tolerant(pattern, target).unwrap();
+            assert_eq!(result.score.typos, edits, "{pattern} ~ {target}");
+            let unmatched = target.to_ascii_lowercase();
+            assert_eq!(fmt_match(result.match_indexes, &unmatched), target, "{pattern}");
+        }
+    }
+
+    #[test]
+    fn test_rejects_distant_patterns() {
+        // Too short to be corrected.
+        assert!(search_tolerant("fli", "filter").is_none());
+        // Too many edits for the length.
+        assert!(search_tolerant("ftiler", "filter").is_none());
+        assert!(search_tolerant("retlif", "filter").is_none());
+        // Corrections must start at a word boundary.
+        assert!(search_tolerant("lter", "filter").is_none());
+    }
+
+    #[test]
+    fn test_exact_matches_are_not_penalized() {
+        let exact = search_tolerant("filter", "filter").unwrap();
+        assert_eq!(exact.score, Penalty::default());
+        let corrected = search_tolerant("fitler", "filter").unwrap();
+        assert!(exact > corrected);
+    }
+
+    #[test]
+    fn test_prefers_closest_word() {
+        let result = search_tolerant("mdeian", "Column.mean_median").unwrap();
+        assert_eq!(result.score.typos, 1);
+        let target = "column.mean_median";
+        assert_eq!(fmt_match(result.match_indexes, target), "column.mean_MEDIAN");
+    }
+}
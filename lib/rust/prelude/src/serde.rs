@@ -1,6 +1,7 @@
 //! Module for utilities related to serialization/deserialization using the `serde` library.
 
 use serde::Deserialize;
+use serde::Serialize;
 
 
 
@@ -47,13 +48,37 @@ where
     Ok(option_value.unwrap_or_default())
 }
 
+/// Serialize a value as `Some` value. In JSON, it is the same as serializing the value itself, but
+/// in non-self-describing formats, like `bincode`, it makes the result readable by
+/// [`deserialize_null_as_default`].
+///
+/// Example usage:
+/// ```
+/// # use serde::Deserialize;
+/// # use serde::Serialize;
+/// # use enso_prelude::deserialize_null_as_default;
+/// # use enso_prelude::serialize_as_some;
+/// #[derive(Debug, Deserialize, PartialEq, Serialize)]
+/// struct Foo {
+///     #[serde(default, deserialize_with = "deserialize_null_as_default")]
+///     #[serde(serialize_with = "serialize_as_some")]
+///     blah: Vec<i32>,
+/// }
+/// let foo = Foo { blah: vec![1, 2, 3] };
+/// assert_eq!(serde_json::to_string(&foo).unwrap(), r#"{"blah":[1,2,3]}"#);
+/// ```
+pub fn serialize_as_some<T, S>(value: &T, s: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize,
+    S: serde::Serializer, {
+    s.serialize_some(value)
+}
+
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    use serde::Serialize;
-
     #[test]
     fn deserialize_or_default_attribute_test() {
         // Two structures - same except for `deserialize_or_default` atribute.
@@ -0,0 +1,207 @@
+//! Static analysis of a set of shortcut bindings, reporting bindings that trigger on the same
+//! input, bindings shadowed by chord sequences, and bindings with invalid patterns.
+
+use crate::*;
+
+use std::collections::BTreeSet;
+
+
+
+// ===============
+// === Binding ===
+// ===============
+
+/// A shortcut binding to analyze.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[allow(missing_docs)]
+pub struct Binding<'a> {
+    pub action_type: ActionType,
+    pub pattern:     &'a str,
+}
+
+impl<'a> Binding<'a> {
+    /// Constructor.
+    pub fn new(action_type: ActionType, pattern: &'a str) -> Self {
+        Self { action_type, pattern }
+    }
+}
+
+
+
+// ================
+// === Conflict ===
+// ================
+
+/// A problem found by [`analyze`]. Bindings are identified by their positions in the analyzed set.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[allow(missing_docs)]
+pub enum Conflict {
+    /// Both bindings trigger on the same input, given as an expression of sorted keys.
+    Overlap { first: usize, second: usize, input: String },
+    /// The `prefix` binding triggers on a stroke which also starts the chord sequence of the
+    /// `sequence` binding. After it triggers, the next stroke is treated as a continuation of the
+    /// chord sequence, instead of triggering the bindings it would trigger otherwise.
+    Shadowed { prefix: usize, sequence: usize },
+    /// The pattern of the binding cannot be parsed. See [`strokes`] to learn more.
+    Invalid { binding: usize, error: PatternError },
+}
+
+/// Find conflicts between the given bindings. Pairs of bindings for which `may_coexist` returns
+/// false, e.g. because they are never enabled at the same time, are not considered conflicting.
+///
+/// A [`Press`] binding overlaps with a [`PressAndRepeat`] binding triggering on the same input,
+/// as both trigger on the first press.
+pub fn analyze(
+    bindings: &[Binding],
+    mut may_coexist: impl FnMut(usize, usize) -> bool,
+) -> Vec<Conflict> {
+    let mut registry = HashSetRegistryModel::<usize>::new();
+    let mut conflicts = vec![];
+    let mut binding_exprs = Vec::with_capacity(bindings.len());
+    for (index, binding) in bindings.iter().enumerate() {
+        let exprs = registry.sequence_exprs(binding.pattern);
+        if let Err(error) = &exprs {
+            conflicts.push(Conflict::Invalid { binding: index, error: error.clone() });
+        }
+        binding_exprs.push(exprs.unwrap_or_default());
+    }
+
+    let mut bindings_by_input = BTreeMap::<_, Vec<usize>>::new();
+    for (index, (binding, exprs)) in bindings.iter().zip(&binding_exprs).enumerate() {
+        let action_type = match binding.action_type {
+            PressAndRepeat => Press,
+            action_type => action_type,
+        };
+        for expr in exprs {
+            let key = (action_order(action_type), expr.as_str());
+            bindings_by_input.entry(key).or_default().push(index);
+        }
+    }
+    let mut overlapping = HashSet::new();
+    for ((_, input), indexes) in bindings_by_input {
+        for (&first, &second) in indexes.iter().tuple_combinations() {
+            if overlapping.insert((first, second)) && may_coexist(first, second) {
+                let input = input.to_owned();
+                conflicts.push(Conflict::Overlap { first, second, input });
+            }
+        }
+    }
+
+    let mut bindings_by_chord_prefix = HashMap::<_, Vec<usize>>::new();
+    for (index, exprs) in binding_exprs.iter().enumerate() {
+        for expr in exprs.iter().filter(|expr| registry.chord_prefixes.contains(*expr)) {
+            bindings_by_chord_prefix.entry(expr.as_str()).or_default().push(index);
+        }
+    }
+    let mut shadowed = BTreeSet::new();
+    for (sequence, exprs) in binding_exprs.iter().enumerate() {
+        for expr in exprs {
+            for (end, _) in expr.match_indices(STROKE_SEPARATOR) {
+                let prefixes = bindings_by_chord_prefix.get(&expr[..end]).into_iter().flatten();
+                shadowed.extend(prefixes.map(|&prefix| (prefix, sequence)));
+            }
+        }
+    }
+    for (prefix, sequence) in shadowed {
+        if may_coexist(prefix, sequence) {
+            conflicts.push(Conflict::Shadowed { prefix, sequence });
+        }
+    }
+    conflicts
+}
+
+/// A fixed order of action types, making the reported conflicts deterministic.
+fn action_order(action_type: ActionType) -> usize {
+    match action_type {
+        Press => 0,
+        PressAndRepeat => 1,
+        Release => 2,
+        DoublePress => 3,
+        DoubleClick => 4,
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn analyze_all(bindings: &[(ActionType, &str)]) -> Vec<Conflict> {
+        let bindings =
+            bindings.iter().map(|(tp, pattern)| Binding::new(*tp, pattern)).collect_vec();
+        analyze(&bindings, |_, _| true)
+    }
+
+    #[test]
+    fn overlapping_bindings() {
+        let conflicts = analyze_all(&[
+            (Press, "ctrl a"),
+            (Press, "a CTRL"),
+            (Release, "ctrl a"),
+            (PressAndRepeat, "ctrl-left a"),
+            (Press, "ctrl b"),
+            (Press, "ctrl+b"),
+        ]);
+        assert_eq!(conflicts, vec![
+            Conflict::Overlap { first: 0, second: 1, input: "a ctrl".into() },
+            Conflict::Overlap { first: 0, second: 3, input: "a ctrl-left".into() },
+            Conflict::Overlap { first: 1, second: 3, input: "a ctrl-left".into() },
+            Conflict::Overlap { first: 4, second: 5, input: "b ctrl".into() },
+        ]);
+    }
+
+    #[test]
+    fn coexistence() {
+        let bindings = [Binding::new(Press, "ctrl a"), Binding::new(Press, "ctrl a")];
+        assert_eq!(analyze(&bindings, |_, _| false), vec![]);
+        assert_eq!(analyze(&bindings, |_, _| true).len(), 1);
+    }
+
+    #[test]
+    fn shadowed_bindings() {
+        let conflicts = analyze_all(&[
+            (Press, "ctrl+k ctrl+c"),
+            (Press, "ctrl k"),
+            (Release, "ctrl+k"),
+            (Press, "ctrl+k ctrl+u"),
+            (Press, "ctrl+x ctrl+k"),
+            (Press, "ctrl+k ctrl+c ctrl+d"),
+        ]);
+        assert_eq!(conflicts, vec![
+            Conflict::Shadowed { prefix: 0, sequence: 5 },
+            Conflict::Shadowed { prefix: 1, sequence: 0 },
+            Conflict::Shadowed { prefix: 1, sequence: 3 },
+            Conflict::Shadowed { prefix: 1, sequence: 5 },
+            Conflict::Shadowed { prefix: 2, sequence: 0 },
+            Conflict::Shadowed { prefix: 2, sequence: 3 },
+            Conflict::Shadowed { prefix: 2, sequence: 5 },
+        ]);
+    }
+
+    #[test]
+    fn invalid_bindings() {
+        let conflicts = analyze_all(&[
+            (Press, "ctrl+shift a"),
+            (Press, "shift+a ctrl"),
+            (Press, "shift+a ctrl+b"),
+            (Press, "ctrl shift"),
+        ]);
+        let invalid = |binding, pattern: &str, stroke: &str| {
+            let pattern = pattern.into();
+            let stroke = stroke.into();
+            Conflict::Invalid {
+                binding,
+                error: PatternError::ModifierOnlyStroke { pattern, stroke },
+            }
+        };
+        assert_eq!(conflicts, vec![
+            invalid(0, "ctrl+shift a", "ctrl shift"),
+            invalid(1, "shift+a ctrl", "ctrl"),
+        ]);
+    }
+}
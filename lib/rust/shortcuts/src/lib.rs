@@ -26,6 +26,13 @@ use enso_prelude::*;
 use enso_web as web;
 
 
+// ==============
+// === Export ===
+// ==============
+
+pub mod analysis;
+
+
 
 // ==============
 // === Traits ===
@@ -55,18 +62,83 @@ pub fn print_matrix(matrix: &data::Matrix<dfa::State>) {
     }
 }
 
-/// List of special keys. Special keys can be grouped together to distinguish action sequences like
-/// `ctrl + a` and `a + ctrl`. Please note, that this is currently not happening.
+/// List of modifier keys. Each of them can be pressed on either side of the keyboard: a pattern
+/// naming the key without a side, like `ctrl`, matches either `ctrl-left` or `ctrl-right`, and
+/// pressing both sides at once counts as pressing the key without a side. A stroke of a chord
+/// sequence is completed by pressing a key that is not a modifier.
 const SIDE_KEYS: &[&str] = &["ctrl", "alt", "alt-graph", "meta", "cmd", "shift"];
 
 lazy_static! {
     static ref SIDE_KEYS_SET: HashSet<&'static str> = SIDE_KEYS.iter().copied().collect();
+    static ref KEY_ALIASES: HashMap<String, String> = key_aliases();
 }
 
 /// The maximum time difference between presses/clicks where they are treated as single
 /// `DoublePress`/`DoubleClick` event.
 pub const DOUBLE_EVENT_TIME_MS: f32 = 300.0;
 
+/// The maximum time difference between the strokes of a chord sequence, like `ctrl+k ctrl+c`.
+pub const CHORD_TIMEOUT_MS: f32 = 2000.0;
+
+/// Separator of the strokes of a chord sequence in the expressions used as registry keys. It cannot
+/// occur in a key name.
+const STROKE_SEPARATOR: &str = " -> ";
+
+/// Split a shortcut pattern into the patterns of its strokes. Keys pressed together can be
+/// separated by spaces, like `ctrl shift a`, or joined with `+`, like `ctrl+shift+a`. In the latter
+/// form, strokes of a chord sequence are separated by spaces, like `ctrl+k ctrl+c`, and the `+` key
+/// cannot be used.
+///
+/// As a stroke of a chord sequence is completed by pressing a key that is not a modifier, a chord
+/// sequence with a stroke consisting of modifiers only is rejected. This catches patterns mixing
+/// both forms, like `ctrl+shift a`, which would otherwise be split into the strokes `ctrl shift`
+/// and `a`.
+pub fn strokes(pattern: &str) -> Result<Vec<String>, PatternError> {
+    let mut tokens = pattern.split_whitespace();
+    let is_chord_syntax = tokens.clone().any(|token| token.len() > 1 && token.contains('+'));
+    if is_chord_syntax {
+        let keys = |token: &str| token.split('+').filter(|key| !key.is_empty()).join(" ");
+        let strokes = tokens.map(keys).collect_vec();
+        let is_modifier_only = |stroke: &&String| stroke.split(' ').all(is_modifier);
+        match strokes.iter().find(is_modifier_only) {
+            Some(stroke) => {
+                let pattern = pattern.to_owned();
+                let stroke = stroke.clone();
+                Err(PatternError::ModifierOnlyStroke { pattern, stroke })
+            }
+            None => Ok(strokes),
+        }
+    } else {
+        Ok(vec![tokens.join(" ")])
+    }
+}
+
+/// Check whether the key is a modifier key, on any side of the keyboard.
+fn is_modifier(key: &str) -> bool {
+    let key = key.to_lowercase();
+    let key = KEY_ALIASES.get(&key).unwrap_or(&key);
+    let base = key.strip_suffix("-left").or_else(|| key.strip_suffix("-right"));
+    SIDE_KEYS_SET.contains(base.unwrap_or(key))
+}
+
+
+
+// ====================
+// === PatternError ===
+// ====================
+
+/// An error of parsing a shortcut pattern. See [`strokes`] to learn more.
+#[derive(Clone, Debug, Eq, Fail, PartialEq)]
+#[allow(missing_docs)]
+pub enum PatternError {
+    #[fail(
+        display = "The stroke \"{}\" of the chord sequence \"{}\" consists of modifier keys only. \
+        Keys pressed together must be joined with `+`, like `ctrl+shift+a`.",
+        stroke, pattern
+    )]
+    ModifierOnlyStroke { pattern: String, stroke: String },
+}
+
 
 
 // ==================
@@ -98,7 +170,8 @@ pub use ActionType::*;
 #[allow(missing_docs)]
 pub trait Registry<T>: Default {
     /// Add a new action mapping. `The expr` needs to be a list of keys separated by space, like
-    /// "ctrl shift a".
+    /// "ctrl shift a", or a chord sequence, like "ctrl+k ctrl+c". See [`strokes`] to learn more.
+    /// The action type applies to the last stroke of a chord sequence.
     fn add(&self, action_type: ActionType, expr: impl AsRef<str>, action: impl Into<T>);
 
     /// Get a list of items registered for the action that just happened. It might include items
@@ -123,15 +196,27 @@ pub trait Registry<T>: Default {
 pub trait HashSetRegistryItem = Clone + Debug + Eq + Hash;
 
 /// Internal model for `HashSetRegistry`.
+///
+/// Chord sequences are registered under expressions joining the expressions of their strokes with
+/// [`STROKE_SEPARATOR`]. When a stroke completes a proper prefix of a registered chord sequence,
+/// the prefix becomes the pending `chord`, and the events of the next stroke are looked up as its
+/// continuations (and only as its continuations) if the stroke happens within
+/// [`CHORD_TIMEOUT_MS`].
 #[derive(Debug)]
 pub struct HashSetRegistryModel<T> {
-    current_expr:  String,
-    actions:       HashMap<ActionType, HashMap<String, Vec<T>>>,
-    pressed:       HashSet<String>,
-    press_times:   HashMap<String, f32>,
-    release_times: HashMap<String, f32>,
-    side_keys:     HashMap<String, Vec<String>>,
-    key_aliases:   HashMap<String, String>,
+    current_expr:   String,
+    actions:        HashMap<ActionType, HashMap<String, Vec<T>>>,
+    pressed:        HashSet<String>,
+    press_times:    HashMap<String, f32>,
+    release_times:  HashMap<String, f32>,
+    side_keys:      HashMap<String, Vec<String>>,
+    key_aliases:    HashMap<String, String>,
+    /// Proper prefixes of the registered chord sequences.
+    chord_prefixes: HashSet<String>,
+    /// The chord sequence prefix waiting for its next stroke, and the time of its last stroke.
+    chord:          Option<(String, f32)>,
+    /// The chord sequence prefix the current stroke continues, if any.
+    qualifier:      String,
 }
 
 impl<T> HashSetRegistryModel<T> {
@@ -144,8 +229,22 @@ impl<T> HashSetRegistryModel<T> {
         let release_times = default();
         let side_keys = default();
         let key_aliases = key_aliases();
-        Self { current_expr, actions, pressed, press_times, release_times, side_keys, key_aliases }
-            .init()
+        let chord_prefixes = default();
+        let chord = default();
+        let qualifier = default();
+        Self {
+            current_expr,
+            actions,
+            pressed,
+            press_times,
+            release_times,
+            side_keys,
+            key_aliases,
+            chord_prefixes,
+            chord,
+            qualifier,
+        }
+        .init()
     }
 
     fn init(mut self) -> Self {
@@ -156,8 +255,43 @@ impl<T> HashSetRegistryModel<T> {
         self
     }
 
+    /// The sorted expression of the pressed keys. A modifier pressed on both sides of the keyboard
+    /// is included without a side.
     fn current_expr(&self) -> String {
-        self.pressed.iter().sorted().join(" ")
+        let keys = self.pressed.iter().map(|key| {
+            let base = key.strip_suffix("-left").or_else(|| key.strip_suffix("-right"));
+            let base = base.filter(|base| SIDE_KEYS_SET.contains(base));
+            let both_sides = base.filter(|base| {
+                self.pressed.contains(&format!("{base}-left"))
+                    && self.pressed.contains(&format!("{base}-right"))
+            });
+            both_sides.unwrap_or(key.as_str())
+        });
+        keys.sorted().dedup().join(" ")
+    }
+
+    /// The expression used to look up actions for the current stroke: the current expression,
+    /// qualified by the chord sequence prefix the stroke continues.
+    fn lookup_expr(&self) -> String {
+        if self.qualifier.is_empty() {
+            self.current_expr.clone()
+        } else {
+            format!("{}{STROKE_SEPARATOR}{}", self.qualifier, self.current_expr)
+        }
+    }
+
+    /// Start a new stroke, after a key other than a modifier was pressed. If there is a pending
+    /// chord sequence prefix, the stroke continues it. If the stroke completes a chord sequence
+    /// prefix, it becomes the pending one.
+    fn begin_stroke(&mut self) {
+        let time = web::time_from_start() as f32;
+        let chord =
+            self.chord.take().filter(|(_, chord_time)| time - chord_time < CHORD_TIMEOUT_MS);
+        self.qualifier = chord.map(|(prefix, _)| prefix).unwrap_or_default();
+        let expr = self.lookup_expr();
+        if self.chord_prefixes.contains(&expr) {
+            self.chord = Some((expr, time));
+        }
     }
 }
 
@@ -166,13 +300,41 @@ impl<T: HashSetRegistryItem> HashSetRegistryModel<T> {
     pub fn add(&mut self, action_type: ActionType, input: impl AsRef<str>, action: impl Into<T>) {
         let input = input.as_ref();
         let action = action.into();
-        let exprs = self.possible_exprs(input);
-        let map = self.actions.entry(action_type).or_default();
-        for expr in exprs {
-            map.entry(expr).or_default().push(action.clone());
+        match self.sequence_exprs(input) {
+            Ok(exprs) => {
+                let map = self.actions.entry(action_type).or_default();
+                for expr in exprs {
+                    map.entry(expr).or_default().push(action.clone());
+                }
+            }
+            Err(error) => warn!("Ignoring the shortcut \"{input}\": {error}"),
         }
     }
 
+    /// Return all possible expressions for a pattern that may be a chord sequence, registering
+    /// the expressions of its proper prefixes in `chord_prefixes`.
+    fn sequence_exprs(&mut self, input: &str) -> Result<Vec<String>, PatternError> {
+        let strokes = strokes(input)?;
+        let mut exprs = vec![String::new()];
+        for (index, stroke) in strokes.iter().enumerate() {
+            exprs = self.continue_exprs(&exprs, stroke);
+            if index + 1 < strokes.len() {
+                self.chord_prefixes.extend(exprs.iter().cloned());
+            }
+        }
+        Ok(exprs)
+    }
+
+    /// Return all possible expressions for the given expressions continued with the given stroke.
+    fn continue_exprs(&self, exprs: &[String], stroke: &str) -> Vec<String> {
+        let stroke_exprs = self.possible_exprs(stroke);
+        let continue_expr = |(prefix, expr): (&String, &String)| match prefix.is_empty() {
+            true => expr.clone(),
+            false => format!("{prefix}{STROKE_SEPARATOR}{expr}"),
+        };
+        exprs.iter().cartesian_product(&stroke_exprs).map(continue_expr).collect()
+    }
+
     #[allow(clippy::collapsible_else_if)]
     fn on_event(&mut self, input: impl AsRef<str>, press: bool) -> Vec<T> {
         let input = input.as_ref().to_lowercase();
@@ -180,12 +342,19 @@ impl<T: HashSetRegistryItem> HashSetRegistryModel<T> {
         let repeat = if press { exists } else { !exists };
         if !repeat {
             let out = self.process_event(Release);
+            let is_stroke = !is_modifier(&input);
             if press {
                 self.pressed.insert(input);
             } else {
                 self.pressed.remove(&input);
+                if is_stroke {
+                    self.qualifier.clear();
+                }
             }
             self.current_expr = self.current_expr();
+            if press && is_stroke {
+                self.begin_stroke();
+            }
             out.extended(self.process_event(Press)).extended(self.process_event(PressAndRepeat))
         } else {
             if press {
@@ -197,7 +366,7 @@ impl<T: HashSetRegistryItem> HashSetRegistryModel<T> {
     }
 
     fn process_event(&mut self, action: ActionType) -> Vec<T> {
-        let expr = &self.current_expr;
+        let expr = &self.lookup_expr();
         let mut out = self
             .actions
             .get(&action)
@@ -245,12 +414,13 @@ impl<T: HashSetRegistryItem> HashSetRegistryModel<T> {
 
     /// Return all possible expressions with sorted keys for a given input expression. For example,
     /// for the input expression "cmd a", it will return ["a cmd", "a cmd-left", "a cmd-right"].
+    /// Keys are matched case-insensitively, and repeated keys are ignored.
     fn possible_exprs(&self, expr: impl AsRef<str>) -> Vec<String> {
         let mut out = Vec::<String>::new();
-        let expr = expr.as_ref();
+        let expr = expr.as_ref().to_lowercase();
         let chunks = expr.split(' ').map(|t| t.trim()).filter(|t| !t.is_empty());
         let keys = chunks.map(|t| self.key_aliases.get(t).map(|t| t.as_ref()).unwrap_or(t));
-        for key in keys.sorted() {
+        for key in keys.sorted().dedup() {
             match self.side_keys.get(key) {
                 Some(alts) =>
                     if out.is_empty() {
@@ -556,6 +726,118 @@ mod tests {
         assert_eq!(registry.on_press("a"), vec![0]);
         registry
     }
+
+
+    // === Modifier Normalization ===
+
+    #[test]
+    fn hash_set_registry_modifier_normalization() {
+        modifier_normalization::<HashSetRegistry<i32>>();
+    }
+    fn modifier_normalization<T: Registry<i32>>() -> T {
+        let nothing = Vec::<i32>::new();
+        let registry: T = default();
+        registry.add(Press, "Shift Control a", 0);
+        registry.add(Press, "ctrl+b", 1);
+        assert_eq!(registry.on_press("shift-right"), nothing);
+        assert_eq!(registry.on_press("ctrl-left"), nothing);
+        assert_eq!(registry.on_press("A"), vec![0]);
+        assert_eq!(registry.on_release("A"), nothing);
+        assert_eq!(registry.on_release("shift-right"), nothing);
+        // A modifier pressed on both sides of the keyboard.
+        assert_eq!(registry.on_press("ctrl-right"), nothing);
+        assert_eq!(registry.on_press("b"), vec![1]);
+        assert_eq!(registry.on_release("b"), nothing);
+        assert_eq!(registry.on_release("ctrl-right"), nothing);
+        assert_eq!(registry.on_release("ctrl-left"), nothing);
+        registry
+    }
+
+
+    // === Chord Sequences ===
+
+    #[test]
+    fn stroke_splitting() {
+        assert_eq!(strokes("ctrl shift a"), Ok(vec!["ctrl shift a".into()]));
+        assert_eq!(strokes("ctrl+shift+a"), Ok(vec!["ctrl shift a".into()]));
+        assert_eq!(strokes("ctrl+k ctrl+c"), Ok(vec!["ctrl k".into(), "ctrl c".into()]));
+        assert_eq!(strokes("ctrl+k  u"), Ok(vec!["ctrl k".into(), "u".into()]));
+        assert_eq!(strokes("shift +"), Ok(vec!["shift +".into()]));
+    }
+
+    #[test]
+    fn mixed_stroke_separators() {
+        let error = |pattern: &str, stroke: &str| {
+            let pattern = pattern.into();
+            let stroke = stroke.into();
+            Err(PatternError::ModifierOnlyStroke { pattern, stroke })
+        };
+        assert_eq!(strokes("ctrl+shift a"), error("ctrl+shift a", "ctrl shift"));
+        assert_eq!(strokes("Control+k Shift a"), error("Control+k Shift a", "Shift"));
+        assert_eq!(strokes("shift+a cmd-left"), error("shift+a cmd-left", "cmd-left"));
+        let registry = HashSetRegistry::<i32>::default();
+        registry.add(Press, "ctrl+shift a", 0);
+        assert_eq!(registry.on_press("ctrl"), vec![]);
+        assert_eq!(registry.on_press("shift"), vec![]);
+        assert_eq!(registry.on_press("a"), vec![]);
+    }
+
+    #[test]
+    fn hash_set_registry_chord_sequence() {
+        chord_sequence::<HashSetRegistry<&'static str>>();
+    }
+    fn chord_sequence<T: Registry<&'static str>>() -> T {
+        let nothing = Vec::<&'static str>::new();
+        let registry: T = default();
+        registry.add(Press, "ctrl+k ctrl+c", "comment");
+        registry.add(Press, "ctrl+k u", "uncomment");
+        registry.add(Press, "ctrl c", "copy");
+        registry.add(Press, "u", "u");
+        // Modifiers may be kept pressed between strokes.
+        assert_eq!(registry.on_press("ctrl-left"), nothing);
+        assert_eq!(registry.on_press("k"), nothing);
+        assert_eq!(registry.on_release("k"), nothing);
+        assert_eq!(registry.on_press("c"), vec!["comment"]);
+        assert_eq!(registry.on_release("c"), nothing);
+        assert_eq!(registry.on_press("c"), vec!["copy"]);
+        assert_eq!(registry.on_release("c"), nothing);
+        assert_eq!(registry.on_press("k"), nothing);
+        assert_eq!(registry.on_release("k"), nothing);
+        assert_eq!(registry.on_release("ctrl-left"), nothing);
+        assert_eq!(registry.on_press("u"), vec!["uncomment"]);
+        assert_eq!(registry.on_release("u"), nothing);
+        assert_eq!(registry.on_press("u"), vec!["u"]);
+        assert_eq!(registry.on_release("u"), nothing);
+        // A stroke not continuing the sequence is swallowed.
+        assert_eq!(registry.on_press("ctrl-right"), nothing);
+        assert_eq!(registry.on_press("k"), nothing);
+        assert_eq!(registry.on_release("k"), nothing);
+        assert_eq!(registry.on_release("ctrl-right"), nothing);
+        assert_eq!(registry.on_press("a"), nothing);
+        assert_eq!(registry.on_release("a"), nothing);
+        assert_eq!(registry.on_press("u"), vec!["u"]);
+        assert_eq!(registry.on_release("u"), nothing);
+        registry
+    }
+
+    #[test]
+    fn hash_set_registry_chord_timeout() {
+        chord_timeout::<HashSetRegistry<&'static str>>();
+    }
+    fn chord_timeout<T: Registry<&'static str>>() -> T {
+        let nothing = Vec::<&'static str>::new();
+        let registry: T = default();
+        registry.add(Press, "ctrl+k u", "uncomment");
+        registry.add(Press, "u", "u");
+        assert_eq!(registry.on_press("ctrl"), nothing);
+        assert_eq!(registry.on_press("k"), nothing);
+        assert_eq!(registry.on_release("k"), nothing);
+        assert_eq!(registry.on_release("ctrl"), nothing);
+        web::simulate_sleep(CHORD_TIMEOUT_MS as f64 + 1.0);
+        assert_eq!(registry.on_press("u"), vec!["u"]);
+        assert_eq!(registry.on_release("u"), nothing);
+        registry
+    }
 }
 
 
@@ -0,0 +1,706 @@
+//! Change sets - text edits which can be composed, inverted and transformed against each other.
+//!
+//! A [`ChangeSet`] describes how to transform a text of a given length (the _base_ text) into a
+//! new text (the _target_ text). It is a sequence of [`Operation`]s covering the whole base text:
+//! every byte of the base is either retained or deleted, and new text may be inserted between them.
+//! Unlike a list of [`Change`]s, the change set is independent of the order in which the changes
+//! were made, which allows implementing the operational transformation (OT) algorithms:
+//!
+//! - **Composition** ([`ChangeSet::compose`]) - merging two consecutive change sets into one.
+//! - **Inversion** ([`ChangeSet::invert`]) - creating a change set reverting the given one.
+//! - **Transformation** ([`ChangeSet::transform`]) - rebasing a change set made concurrently with
+//!   another one, so it can be applied after it. For two concurrent change sets `a` and `b`,
+//!   applying `a` and then `b.transform(a, Side::Right)` gives the same text as applying `b` and
+//!   then `a.transform(b, Side::Left)`, so both sides of a connection converge to the same text
+//!   regardless of the order in which they receive the edits.
+//!
+//! Positions in the text (like spans of AST nodes or selections) can be rebased over a change set
+//! using [`ChangeSet::transform_offset`] and [`ChangeSet::transform_range`].
+
+use crate::index::*;
+use crate::prelude::*;
+use crate::unit::*;
+
+use crate::range::Range;
+use crate::text::Change;
+use crate::text::Rope;
+
+
+
+// =============
+// === Error ===
+// =============
+
+/// Error returned by operations on change sets.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[allow(missing_docs)]
+pub enum ChangeSetError {
+    /// The length of the text (or the target length of the preceding change set) does not match
+    /// the base length of the change set.
+    LengthMismatch { expected: Bytes, found: Bytes },
+    /// The range of a change is reversed or exceeds the base text.
+    InvalidRange { range: Range<Byte>, base_len: Bytes },
+}
+
+
+
+// =================
+// === Operation ===
+// =================
+
+/// A single step of the [`ChangeSet`].
+#[derive(Clone, Debug)]
+pub enum Operation {
+    /// Keep the given number of bytes of the base text.
+    Retain(Bytes),
+    /// Remove the given number of bytes of the base text.
+    Delete(Bytes),
+    /// Insert the text.
+    Insert(Rope),
+}
+
+impl Operation {
+    /// The number of bytes of base text (for [`Operation::Retain`] and [`Operation::Delete`]) or
+    /// of the inserted text (for [`Operation::Insert`]) covered by this operation.
+    pub fn len(&self) -> Bytes {
+        match self {
+            Self::Retain(len) | Self::Delete(len) => *len,
+            Self::Insert(text) => text.len(),
+        }
+    }
+
+    /// Check if the operation is a no-op.
+    pub fn is_empty(&self) -> bool {
+        self.len() == Bytes(0)
+    }
+
+    /// Split the operation into the part covering first `len` bytes and the rest. The rest is
+    /// [`None`] if `len` covers the whole operation.
+    fn split(self, len: Bytes) -> (Self, Option<Self>) {
+        if len >= self.len() {
+            (self, None)
+        } else {
+            let rest_len = self.len() - len;
+            match self {
+                Self::Retain(_) => (Self::Retain(len), Some(Self::Retain(rest_len))),
+                Self::Delete(_) => (Self::Delete(len), Some(Self::Delete(rest_len))),
+                Self::Insert(text) => {
+                    let split_point = len.to_byte();
+                    let prefix = text.sub(Byte(0)..split_point);
+                    let suffix = text.sub(split_point..text.last_byte_index());
+                    (Self::Insert(prefix), Some(Self::Insert(suffix)))
+                }
+            }
+        }
+    }
+}
+
+
+
+// ============
+// === Side ===
+// ============
+
+/// Decides the order of texts inserted at the same position by two concurrent change sets during
+/// [`ChangeSet::transform`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Side {
+    /// The insertions of the transformed change set are placed before the other's insertions.
+    Left,
+    /// The insertions of the transformed change set are placed after the other's insertions.
+    Right,
+}
+
+/// Decides where an offset lands when text is inserted exactly at it. See
+/// [`ChangeSet::transform_offset`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Bias {
+    /// The offset stays before the inserted text.
+    Left,
+    /// The offset is moved after the inserted text.
+    Right,
+}
+
+
+
+// =================
+// === ChangeSet ===
+// =================
+
+/// A sequence of operations transforming a text of `base_len` bytes into a text of `target_len`
+/// bytes. See the module docs to learn more.
+///
+/// The operations are kept normalized: there are no empty operations, no two adjacent operations
+/// of the same kind, and an insertion always precedes an adjacent deletion. Thus, two change sets
+/// doing the same edits have the same operations.
+#[derive(Clone, Debug, Default)]
+pub struct ChangeSet {
+    operations: Vec<Operation>,
+    base_len:   Bytes,
+    target_len: Bytes,
+}
+
+
+// === Constructors ===
+
+impl ChangeSet {
+    /// A change set which does not change the text of length `len`.
+    pub fn identity(len: Bytes) -> Self {
+        let mut change_set = Self::default();
+        change_set.retain(len);
+        change_set
+    }
+
+    /// A change set replacing the `change.range` in a text of length `base_len` with the
+    /// `change.text`.
+    pub fn from_change(
+        base_len: Bytes,
+        change: Change<Byte, impl Into<Rope>>,
+    ) -> Result<Self, ChangeSetError> {
+        let range = change.range;
+        if range.start > range.end || range.end > base_len.to_byte() {
+            return Err(ChangeSetError::InvalidRange { range, base_len });
+        }
+        let mut change_set = Self::default();
+        change_set.retain(Bytes(range.start.value));
+        change_set.delete(Bytes(range.end.value - range.start.value));
+        change_set.insert(change.text.into());
+        change_set.retain(Bytes(base_len.value - range.end.value));
+        Ok(change_set)
+    }
+
+    /// A change set equivalent to applying the `changes` one after another to a text of length
+    /// `base_len`. Each change's range refers to the text with all previous changes applied.
+    pub fn from_changes<S: Into<Rope>>(
+        base_len: Bytes,
+        changes: impl IntoIterator<Item = Change<Byte, S>>,
+    ) -> Result<Self, ChangeSetError> {
+        changes.into_iter().try_fold(Self::identity(base_len), |change_set, change| {
+            let next = Self::from_change(change_set.target_len, change)?;
+            change_set.compose(&next)
+        })
+    }
+
+    /// A change set transforming `old` text into `new` one, replacing everything between their
+    /// common prefix and suffix.
+    pub fn from_diff(old: &Rope, new: &Rope) -> Self {
+        let common = old.common_prefix_and_suffix(new);
+        let prefix = common.prefix.value as usize;
+        let suffix = common.suffix.value as usize;
+        let new_text = new.sub(Byte(prefix)..Byte(new.len().value - suffix));
+        let mut change_set = Self::default();
+        change_set.retain(Bytes(prefix));
+        change_set.delete(Bytes(old.len().value - prefix - suffix));
+        change_set.insert(new_text);
+        change_set.retain(Bytes(suffix));
+        change_set
+    }
+}
+
+
+// === Getters ===
+
+impl ChangeSet {
+    /// The length of the text this change set can be applied to.
+    pub fn base_len(&self) -> Bytes {
+        self.base_len
+    }
+
+    /// The length of the text after applying this change set.
+    pub fn target_len(&self) -> Bytes {
+        self.target_len
+    }
+
+    /// The normalized operations of this change set.
+    pub fn operations(&self) -> &[Operation] {
+        &self.operations
+    }
+
+    /// Check if the change set does not change the text.
+    pub fn is_identity(&self) -> bool {
+        self.operations.iter().all(|op| matches!(op, Operation::Retain(_)))
+    }
+
+    /// The changes done by this change set, ordered by their position. All the ranges refer to the
+    /// base text and do not overlap, so applying them in the reversed order gives the target text.
+    pub fn changes(&self) -> Vec<Change<Byte, Rope>> {
+        let mut changes = Vec::new();
+        let mut offset = Byte(0);
+        let mut current: Option<Change<Byte, Rope>> = None;
+        for op in &self.operations {
+            match op {
+                Operation::Retain(len) => {
+                    changes.extend(current.take());
+                    offset += *len;
+                }
+                Operation::Delete(len) => {
+                    let change = current.get_or_insert_with(|| Change::inserted(offset, default()));
+                    offset += *len;
+                    change.range.end = offset;
+                }
+                Operation::Insert(text) => {
+                    let change = current.get_or_insert_with(|| Change::inserted(offset, default()));
+                    change.text = concat(&change.text, text);
+                }
+            }
+        }
+        changes.extend(current);
+        changes
+    }
+}
+
+
+// === Building ===
+
+impl ChangeSet {
+    fn retain(&mut self, len: Bytes) {
+        if len == Bytes(0) {
+            return;
+        }
+        self.base_len += len;
+        self.target_len += len;
+        match self.operations.last_mut() {
+            Some(Operation::Retain(last)) => *last += len,
+            _ => self.operations.push(Operation::Retain(len)),
+        }
+    }
+
+    fn delete(&mut self, len: Bytes) {
+        if len == Bytes(0) {
+            return;
+        }
+        self.base_len += len;
+        match self.operations.last_mut() {
+            Some(Operation::Delete(last)) => *last += len,
+            _ => self.operations.push(Operation::Delete(len)),
+        }
+    }
+
+    fn insert(&mut self, text: Rope) {
+        if text.is_empty() {
+            return;
+        }
+        self.target_len += text.len();
+        let ops = &mut self.operations;
+        // Insertions are kept before deletions, as their order does not matter for the result.
+        let index = match ops.last() {
+            Some(Operation::Delete(_)) => ops.len() - 1,
+            _ => ops.len(),
+        };
+        match index.checked_sub(1).and_then(|previous| ops.get_mut(previous)) {
+            Some(Operation::Insert(previous)) => *previous = concat(previous, &text),
+            _ => ops.insert(index, Operation::Insert(text)),
+        }
+    }
+
+    fn push(&mut self, op: Operation) {
+        match op {
+            Operation::Retain(len) => self.retain(len),
+            Operation::Delete(len) => self.delete(len),
+            Operation::Insert(text) => self.insert(text),
+        }
+    }
+}
+
+impl Extend<Operation> for ChangeSet {
+    fn extend<T: IntoIterator<Item = Operation>>(&mut self, iter: T) {
+        for op in iter {
+            self.push(op);
+        }
+    }
+}
+
+impl FromIterator<Operation> for ChangeSet {
+    fn from_iter<T: IntoIterator<Item = Operation>>(iter: T) -> Self {
+        let mut change_set = Self::default();
+        change_set.extend(iter);
+        change_set
+    }
+}
+
+
+// === Applying ===
+
+impl ChangeSet {
+    /// Return the `text` with this change set applied.
+    pub fn apply(&self, text: &Rope) -> Result<Rope, ChangeSetError> {
+        check_len(self.base_len, text.len())?;
+        let mut result = text.clone();
+        for change in self.changes().into_iter().rev() {
+            result.replace(change.range, change.text);
+        }
+        Ok(result)
+    }
+}
+
+impl Rope {
+    /// Apply the given change set on the current text.
+    ///
+    /// See also [`Self::apply_change`].
+    pub fn apply_change_set(&mut self, change_set: &ChangeSet) -> Result<(), ChangeSetError> {
+        *self = change_set.apply(self)?;
+        Ok(())
+    }
+}
+
+
+// === Composition and Inversion ===
+
+impl ChangeSet {
+    /// Merge this change set with the `next` one, which is meant to be applied after it. Applying
+    /// the result is equivalent to applying both change sets in order.
+    pub fn compose(&self, next: &ChangeSet) -> Result<ChangeSet, ChangeSetError> {
+        check_len(next.base_len, self.target_len)?;
+        let mut result = ChangeSet::default();
+        let mut first_ops = self.operations.iter().cloned();
+        let mut second_ops = next.operations.iter().cloned();
+        let mut first = first_ops.next();
+        let mut second = second_ops.next();
+        loop {
+            match (first.take(), second.take()) {
+                (None, None) => break,
+                // Deletions of the first change set are not visible to the second one.
+                (Some(Operation::Delete(len)), op) => {
+                    result.delete(len);
+                    first = first_ops.next();
+                    second = op;
+                }
+                // Insertions of the second change set do not consume the first one's output.
+                (op, Some(Operation::Insert(text))) => {
+                    result.insert(text);
+                    first = op;
+                    second = second_ops.next();
+                }
+                (Some(first_op), Some(second_op)) => {
+                    let len = first_op.len().min(second_op.len());
+                    let (first_op, first_rest) = first_op.split(len);
+                    let (second_op, second_rest) = second_op.split(len);
+                    match (first_op, second_op) {
+                        (Operation::Retain(len), Operation::Retain(_)) => result.retain(len),
+                        (Operation::Retain(len), Operation::Delete(_)) => result.delete(len),
+                        (Operation::Insert(text), Operation::Retain(_)) => result.insert(text),
+                        // Text inserted by the first change set and deleted by the second one.
+                        (Operation::Insert(_), Operation::Delete(_)) => {}
+                        _ => unreachable!("Deletions and insertions are handled above."),
+                    }
+                    first = first_rest.or_else(|| first_ops.next());
+                    second = second_rest.or_else(|| second_ops.next());
+                }
+                (None, Some(op)) | (Some(op), None) => {
+                    // Not reachable for valid change sets, as the lengths were checked above.
+                    let found = op.len();
+                    return Err(ChangeSetError::LengthMismatch { expected: Bytes(0), found });
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    /// Create a change set reverting this one. The `base` is the text this change set is applied
+    /// to; it is needed to restore the deleted text.
+    pub fn invert(&self, base: &Rope) -> Result<ChangeSet, ChangeSetError> {
+        check_len(self.base_len, base.len())?;
+        let mut result = ChangeSet::default();
+        let mut offset = Byte(0);
+        for op in &self.operations {
+            match op {
+                Operation::Retain(len) => {
+                    result.retain(*len);
+                    offset += *len;
+                }
+                Operation::Delete(len) => {
+                    let end = offset + *len;
+                    result.insert(base.sub(offset..end));
+                    offset = end;
+                }
+                Operation::Insert(text) => result.delete(text.len()),
+            }
+        }
+        Ok(result)
+    }
+}
+
+
+// === Transformation ===
+
+impl ChangeSet {
+    /// Rebase this change set over the `other` one, made concurrently to the same base text. The
+    /// result can be applied to the text after applying `other`, and has the same intent as this
+    /// change set: text deleted by both change sets is deleted once, and texts inserted at the same
+    /// position are ordered by the `side`.
+    pub fn transform(&self, other: &ChangeSet, side: Side) -> Result<ChangeSet, ChangeSetError> {
+        check_len(other.base_len, self.base_len)?;
+        let mut result = ChangeSet::default();
+        let mut self_ops = self.operations.iter().cloned();
+        let mut other_ops = other.operations.iter().cloned();
+        let mut this = self_ops.next();
+        let mut that = other_ops.next();
+        loop {
+            let this_inserts_first =
+                side == Side::Left || !matches!(that, Some(Operation::Insert(_)));
+            match (this.take(), that.take()) {
+                (None, None) => break,
+                (Some(Operation::Insert(text)), op) if this_inserts_first => {
+                    result.insert(text);
+                    this = self_ops.next();
+                    that = op;
+                }
+                (op, Some(Operation::Insert(text))) => {
+                    result.retain(text.len());
+                    this = op;
+                    that = other_ops.next();
+                }
+                (Some(this_op), Some(that_op)) => {
+                    let len = this_op.len().min(that_op.len());
+                    let (this_op, this_rest) = this_op.split(len);
+                    let (that_op, that_rest) = that_op.split(len);
+                    match (this_op, that_op) {
+                        (Operation::Retain(len), Operation::Retain(_)) => result.retain(len),
+                        (Operation::Delete(len), Operation::Retain(_)) => result.delete(len),
+                        // The text was already deleted by the other change set.
+                        (Operation::Retain(_) | Operation::Delete(_), Operation::Delete(_)) => {}
+                        _ => unreachable!("Insertions are handled above."),
+                    }
+                    this = this_rest.or_else(|| self_ops.next());
+                    that = that_rest.or_else(|| other_ops.next());
+                }
+                (None, Some(op)) | (Some(op), None) => {
+                    // Not reachable for valid change sets, as the lengths were checked above.
+                    let found = op.len();
+                    return Err(ChangeSetError::LengthMismatch { expected: Bytes(0), found });
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    /// Map an offset in the base text to the corresponding offset in the target text. If text is
+    /// inserted exactly at the offset, the `bias` decides whether the offset lands before or after
+    /// it. Offsets inside replaced text are moved to the beginning ([`Bias::Left`]) or the end
+    /// ([`Bias::Right`]) of the replacement. Offsets past the end of the base text are mapped past
+    /// the end of the target text.
+    pub fn transform_offset(&self, offset: Byte, bias: Bias) -> Byte {
+        let mut base = Byte(0);
+        let mut target = Byte(0);
+        let mut inserted_at_base = Bytes(0);
+        for op in &self.operations {
+            match op {
+                Operation::Retain(len) => {
+                    if offset < base + *len {
+                        return target + (offset - base);
+                    }
+                    base += *len;
+                    target += *len;
+                    inserted_at_base = Bytes(0);
+                }
+                Operation::Delete(len) => {
+                    if offset < base + *len {
+                        return match bias {
+                            Bias::Left => Byte(target.value - inserted_at_base.value),
+                            Bias::Right => target,
+                        };
+                    }
+                    base += *len;
+                    inserted_at_base = Bytes(0);
+                }
+                Operation::Insert(text) => {
+                    if offset == base && bias == Bias::Left {
+                        return target;
+                    }
+                    target += text.len();
+                    inserted_at_base += text.len();
+                }
+            }
+        }
+        target + (offset - base)
+    }
+
+    /// Map a range in the base text to the corresponding range in the target text, preserving the
+    /// span it marks: text inserted inside the range extends it, text inserted at its boundaries
+    /// is left outside of it, and deleted parts shrink it. If nothing is left of the range (because
+    /// it was empty or deleted entirely), it collapses to an empty range placed before any text
+    /// inserted at its position.
+    pub fn transform_range(&self, range: Range<Byte>) -> Range<Byte> {
+        let start = self.transform_offset(range.start, Bias::Right);
+        let end = self.transform_offset(range.end, Bias::Left);
+        if end < start {
+            Range::new(end, end)
+        } else {
+            Range::new(start, end)
+        }
+    }
+}
+
+
+// === Utilities ===
+
+fn check_len(expected: Bytes, found: Bytes) -> Result<(), ChangeSetError> {
+    if expected == found {
+        Ok(())
+    } else {
+        Err(ChangeSetError::LengthMismatch { expected, found })
+    }
+}
+
+fn concat(left: &Rope, right: &Rope) -> Rope {
+    let mut result = left.clone();
+    let end = result.last_byte_index();
+    result.replace(end..end, right.clone());
+    result
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(start: usize, end: usize, text: &str) -> Change<Byte, Rope> {
+        Change { range: Range::new(Byte(start), Byte(end)), text: text.into() }
+    }
+
+    fn change_set(base: &str, changes: Vec<Change<Byte, Rope>>) -> ChangeSet {
+        ChangeSet::from_changes(Rope::from(base).len(), changes).unwrap()
+    }
+
+    fn applied(change_set: &ChangeSet, text: &str) -> String {
+        change_set.apply(&text.into()).unwrap().to_string()
+    }
+
+    #[test]
+    fn applying_change_sets() {
+        let text = "hello world";
+        let change_set = change_set(text, vec![change(0, 5, "goodbye"), change(8, 8, "big ")]);
+        assert_eq!(applied(&change_set, text), "goodbye big world");
+        assert_eq!(change_set.base_len(), Bytes(11));
+        assert_eq!(change_set.target_len(), Bytes(17));
+        let changes = change_set.changes();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].range, Range::new(Byte(0), Byte(5)));
+        assert_eq!(changes[0].text.to_string(), "goodbye");
+        assert_eq!(changes[1].range, Range::new(Byte(6), Byte(6)));
+        assert_eq!(changes[1].text.to_string(), "big ");
+        assert!(ChangeSet::from_change(Bytes(3), change(2, 4, "")).is_err());
+        assert!(change_set.apply(&"too short".into()).is_err());
+    }
+
+    #[test]
+    fn composing_change_sets() {
+        let text = "abcdef";
+        let first = change_set(text, vec![change(1, 3, "XYZ")]);
+        let second = change_set("aXYZdef", vec![change(2, 5, ""), change(0, 0, "<")]);
+        let composed = first.compose(&second).unwrap();
+        assert_eq!(applied(&composed, text), "<aXef");
+        assert!(second.compose(&second).is_err());
+    }
+
+    #[test]
+    fn inverting_change_sets() {
+        let text = "fn main() { body }";
+        let change_set = change_set(text, vec![change(3, 7, "start"), change(13, 17, "new")]);
+        let target = change_set.apply(&text.into()).unwrap();
+        let inverted = change_set.invert(&text.into()).unwrap();
+        assert_eq!(applied(&inverted, &target.to_string()), text);
+        assert_eq!(applied(&change_set.compose(&inverted).unwrap(), text), text);
+        assert!(ChangeSet::identity(Bytes(4)).is_identity());
+    }
+
+    #[test]
+    fn transforming_concurrent_inserts_at_the_same_position() {
+        let text = "ab";
+        let left = change_set(text, vec![change(1, 1, "L")]);
+        let right = change_set(text, vec![change(1, 1, "R")]);
+        let left_then_right = left.compose(&right.transform(&left, Side::Right).unwrap()).unwrap();
+        let right_then_left = right.compose(&left.transform(&right, Side::Left).unwrap()).unwrap();
+        assert_eq!(applied(&left_then_right, text), "aLRb");
+        assert_eq!(applied(&right_then_left, text), "aLRb");
+    }
+
+    #[test]
+    fn transforming_overlapping_deletions() {
+        let text = "0123456789";
+        let local = change_set(text, vec![change(2, 6, "x")]);
+        let remote = change_set(text, vec![change(4, 8, "y")]);
+        let local_first = local.compose(&remote.transform(&local, Side::Right).unwrap()).unwrap();
+        let remote_first = remote.compose(&local.transform(&remote, Side::Left).unwrap()).unwrap();
+        assert_eq!(applied(&local_first, text), "01xy89");
+        assert_eq!(applied(&remote_first, text), "01xy89");
+    }
+
+    #[test]
+    fn transforming_ranges() {
+        let text = "let x = foo bar";
+        // Replace `let` with `const`, and insert text inside and after the `foo bar` span.
+        let changes = vec![change(0, 3, "const"), change(13, 13, " baz"), change(21, 21, "!")];
+        let change_set = change_set(text, changes);
+        assert_eq!(applied(&change_set, text), "const x = foo baz bar!");
+        let span = Range::new(Byte(8), Byte(15));
+        assert_eq!(change_set.transform_range(span), Range::new(Byte(10), Byte(21)));
+        // A span inside replaced text collapses to the beginning of the replacement.
+        let deleted_span = Range::new(Byte(1), Byte(2));
+        assert_eq!(change_set.transform_range(deleted_span), Range::new(Byte(0), Byte(0)));
+        assert_eq!(change_set.transform_offset(Byte(1), Bias::Right), Byte(5));
+        // Text inserted at the position of an empty span is left outside of it.
+        let empty_span = Range::new(Byte(11), Byte(11));
+        assert_eq!(change_set.transform_range(empty_span), Range::new(Byte(13), Byte(13)));
+        assert_eq!(change_set.transform_offset(Byte(11), Bias::Right), Byte(17));
+    }
+
+    #[test]
+    fn change_set_from_diff() {
+        let old = Rope::from("main =\n    foo = 1\n    bar = foo");
+        let new = Rope::from("main =\n    foo = 12\n    bar = foo");
+        let change_set = ChangeSet::from_diff(&old, &new);
+        assert_eq!(change_set.apply(&old).unwrap().to_string(), new.to_string());
+        assert_eq!(change_set.changes().len(), 1);
+    }
+
+    /// A minimal deterministic pseudo-random generator, so the randomized test is reproducible.
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn next(&mut self, bound: usize) -> usize {
+            self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            ((self.0 >> 33) as usize) % bound.max(1)
+        }
+
+        fn change_set(&mut self, text: &str) -> ChangeSet {
+            let mut current = text.to_owned();
+            let mut changes = vec![];
+            for _ in 0..self.next(4) {
+                let a = self.next(current.len() + 1);
+                let b = self.next(current.len() + 1);
+                let inserted = ["", "a", "zz", "ąę", "\n"][self.next(5)];
+                let range = Range::new(Byte(a.min(b)), Byte(a.max(b)));
+                let on_boundaries = current.is_char_boundary(range.start.value)
+                    && current.is_char_boundary(range.end.value);
+                if on_boundaries {
+                    let change = Change { range, text: inserted };
+                    current = change.applied(&current).unwrap();
+                    changes.push(change);
+                }
+            }
+            ChangeSet::from_changes(Rope::from(text).len(), changes).unwrap()
+        }
+    }
+
+    #[test]
+    fn randomized_transformation_convergence() {
+        let mut random = Lcg(2023);
+        let text = "abc ąę\ndef";
+        for _ in 0..500 {
+            let a = random.change_set(text);
+            let b = random.change_set(text);
+            let a_then_b = a.compose(&b.transform(&a, Side::Right).unwrap()).unwrap();
+            let b_then_a = b.compose(&a.transform(&b, Side::Left).unwrap()).unwrap();
+            assert_eq!(applied(&a_then_b, text), applied(&b_then_a, text));
+            let a_target = a.apply(&text.into()).unwrap();
+            let inverted = a.invert(&text.into()).unwrap();
+            assert_eq!(applied(&inverted, &a_target.to_string()), text);
+        }
+    }
+}
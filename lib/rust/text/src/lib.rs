@@ -7,6 +7,7 @@
 //!   chars, or in bytes? Or maybe in _grapheme clusters_)?
 //! * An alternative [`Range`] with text-related trait implementations + copyable.
 //! * Interval tree structure [`Spans`] useful for text rich decorations.
+//! * The [`ChangeSet`] structure allowing composing, inverting and transforming concurrent edits.
 //!
 //! To properly understand the implementation and its assumptions, you have to know a lot about
 //! text encoding in different formats and text rendering. Especially, these links are very useful:
@@ -100,6 +101,7 @@
 // === Export ===
 // ==============
 
+pub mod change_set;
 pub mod index;
 pub mod range;
 pub mod rope;
@@ -107,6 +109,7 @@ pub mod spans;
 pub mod text;
 pub mod unit;
 
+pub use change_set::ChangeSet;
 pub use index::*;
 pub use range::Range;
 pub use range::RangeBounds;
@@ -0,0 +1,338 @@
+//! A virtual clock allowing deterministic control over the passage of time.
+//!
+//! By default, [`crate::sleep`] and timer-based utilities (like the FRP `Timeout` and `Interval`)
+//! use the real time of the platform they run on. When a [`VirtualClock`] is installed for the
+//! current thread using [`VirtualClock::install`], they are driven by the clock instead: a sleep
+//! completes and a timer fires only when the clock is explicitly advanced past its deadline. This
+//! allows testing timeout and retry logic on native targets without waiting real time.
+//!
+//! The clock is installed per thread, so tests running in parallel on separate threads do not
+//! interfere with each other. The same clock may be installed on several threads, like the worker
+//! threads of a thread pool, and the futures returned by [`VirtualClock::sleep`] may be moved
+//! between threads. The callbacks of timers set with [`VirtualClock::set_timeout`] and
+//! [`VirtualClock::set_interval`] do not need to be `Send`, so they are bound to the thread
+//! setting the timer, and the timer must be fired on that thread.
+
+use crate::prelude::*;
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::task::Context;
+use std::task::Poll;
+use std::task::Waker;
+use std::thread::ThreadId;
+use std::time::Duration;
+
+
+
+// ===============
+// === TimerId ===
+// ===============
+
+/// An identifier of a timer registered in the [`VirtualClock`]. The identifiers are unique across
+/// all clocks, and increase in the order of registration.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct TimerId(u64);
+
+static NEXT_TIMER_ID: AtomicU64 = AtomicU64::new(0);
+
+impl TimerId {
+    fn next() -> Self {
+        Self(NEXT_TIMER_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+
+
+// =============
+// === Timer ===
+// =============
+
+/// The action performed when a timer reaches its deadline.
+#[derive(Debug)]
+enum Timer {
+    /// The task is woken and the timer is removed.
+    Wake(Waker),
+    /// The timer's callback, stored in [`CALLBACKS`] of the given thread, is called.
+    Callback(ThreadId),
+}
+
+/// A callback of a timer, stored on the thread which set the timer.
+enum Callback {
+    /// The callback is called once and the timer is removed.
+    Once(Box<dyn FnOnce()>),
+    /// The callback is called and the timer is rescheduled after the given period.
+    Repeat { period: Duration, callback: Rc<dyn Fn()> },
+}
+
+
+
+// ====================
+// === VirtualClock ===
+// ====================
+
+thread_local! {
+    /// The clock driving [`crate::sleep`] and timers on the current thread, if any.
+    static CURRENT: RefCell<Option<VirtualClock>> = default();
+    /// The callbacks of the timers set on the current thread.
+    static CALLBACKS: RefCell<HashMap<TimerId, Callback>> = default();
+}
+
+/// The minimal period of repeating timers. Mirrors the browsers' behavior of clamping interval
+/// periods, and prevents an infinite loop when advancing the clock with a zero-period timer.
+const MIN_INTERVAL_PERIOD: Duration = Duration::from_millis(1);
+
+/// A clock whose time moves only when explicitly advanced. See the module docs to learn more.
+#[derive(Clone, Debug, Default)]
+pub struct VirtualClock {
+    data: Arc<Mutex<ClockData>>,
+}
+
+#[derive(Debug, Default)]
+struct ClockData {
+    now:       Duration,
+    /// Pending timers ordered by their deadlines. Timers with equal deadlines are ordered by
+    /// their registration order.
+    timers:    BTreeMap<(Duration, TimerId), Timer>,
+    deadlines: HashMap<TimerId, Duration>,
+}
+
+impl ClockData {
+    fn insert(&mut self, deadline: Duration, id: TimerId, timer: Timer) {
+        self.timers.insert((deadline, id), timer);
+        self.deadlines.insert(id, deadline);
+    }
+
+    fn remove(&mut self, id: TimerId) -> Option<Timer> {
+        let deadline = self.deadlines.remove(&id)?;
+        self.timers.remove(&(deadline, id))
+    }
+}
+
+impl CloneRef for VirtualClock {
+    fn clone_ref(&self) -> Self {
+        self.clone()
+    }
+}
+
+impl VirtualClock {
+    /// Constructor. The clock starts at zero and has no pending timers.
+    pub fn new() -> Self {
+        default()
+    }
+
+    /// The clock installed for the current thread, if any.
+    pub fn current() -> Option<Self> {
+        CURRENT.with(|current| current.borrow().as_ref().map(|clock| clock.clone_ref()))
+    }
+
+    /// Install this clock for the current thread. From now on, [`crate::sleep`] and timers created
+    /// on this thread will be driven by this clock. Replaces the previously installed clock.
+    pub fn install(&self) {
+        CURRENT.with(|current| *current.borrow_mut() = Some(self.clone_ref()));
+    }
+
+    /// Uninstall the clock installed for the current thread, restoring the real-time behavior.
+    pub fn uninstall() {
+        CURRENT.with(|current| *current.borrow_mut() = None);
+    }
+
+    /// Check if this clock is the one installed for the current thread.
+    pub fn is_installed(&self) -> bool {
+        CURRENT.with(|current| {
+            current.borrow().as_ref().map_or(false, |clock| Arc::ptr_eq(&clock.data, &self.data))
+        })
+    }
+
+    /// The time elapsed since the clock creation.
+    pub fn now(&self) -> Duration {
+        self.data().now
+    }
+
+    /// Number of timers which have not fired yet (repeating timers are always pending until
+    /// cleared).
+    pub fn pending_timers_count(&self) -> usize {
+        self.data().timers.len()
+    }
+
+    /// The deadline of the earliest pending timer.
+    pub fn next_deadline(&self) -> Option<Duration> {
+        self.data().timers.keys().next().map(|(deadline, _)| *deadline)
+    }
+
+    /// Call the `callback` once, after the clock is advanced by `delay`. The timer must be fired
+    /// on the current thread.
+    pub fn set_timeout(&self, delay: Duration, callback: impl FnOnce() + 'static) -> TimerId {
+        self.set_callback(delay, Callback::Once(Box::new(callback)))
+    }
+
+    /// Call the `callback` every `period`, until the timer is cleared. The timer must be fired on
+    /// the current thread.
+    pub fn set_interval(&self, period: Duration, callback: impl Fn() + 'static) -> TimerId {
+        let period = period.max(MIN_INTERVAL_PERIOD);
+        self.set_callback(period, Callback::Repeat { period, callback: Rc::new(callback) })
+    }
+
+    /// Remove the timer. Does nothing if the timer has already fired or was cleared.
+    pub fn clear(&self, id: TimerId) {
+        let timer = self.data().remove(id);
+        if let Some(Timer::Callback(thread)) = timer {
+            if thread == std::thread::current().id() {
+                // The callback is dropped after the callbacks are released, as dropping it may
+                // clear other timers.
+                let callback = CALLBACKS.with(|callbacks| callbacks.borrow_mut().remove(&id));
+                drop(callback);
+            }
+        }
+    }
+
+    /// Create a future which completes once the clock is advanced by `duration`, counting from
+    /// now, not from the first poll of the future.
+    pub fn sleep(&self, duration: Duration) -> Sleep {
+        let clock = self.clone_ref();
+        let deadline = self.now() + duration;
+        Sleep { clock, deadline, timer: None }
+    }
+
+    /// Fire the earliest pending timer, if its deadline is not later than `limit`. The clock is
+    /// moved to the timer's deadline before calling its callback. Returns `true` if a timer was
+    /// fired.
+    ///
+    /// Only a single timer is fired, so the caller can make progress on tasks woken by it before
+    /// firing the next one.
+    ///
+    /// # Panics
+    /// Panics if the timer has a callback set on another thread.
+    pub fn fire_next_until(&self, limit: Duration) -> bool {
+        let next = {
+            let mut data = self.data();
+            let key = data.timers.keys().next().copied().filter(|(deadline, _)| *deadline <= limit);
+            let next = key.and_then(|key| data.timers.remove_entry(&key));
+            if let Some(((deadline, id), _)) = &next {
+                data.deadlines.remove(id);
+                data.now = data.now.max(*deadline);
+            }
+            next
+        };
+        // The clock data and the callbacks are not borrowed here, so the callback may set or clear
+        // timers.
+        match next {
+            Some(((_, _), Timer::Wake(waker))) => {
+                waker.wake();
+                true
+            }
+            Some(((deadline, id), Timer::Callback(thread))) => {
+                let current_thread = std::thread::current().id();
+                assert_eq!(thread, current_thread, "A timer fired on another thread than set it.");
+                self.call(deadline, id);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Advance the clock by `duration`, firing all timers with deadlines in that period, in the
+    /// order of their deadlines.
+    pub fn advance(&self, duration: Duration) {
+        self.advance_to(self.now() + duration)
+    }
+
+    /// Advance the clock to the `time`, firing all timers with deadlines up to that time. Does
+    /// nothing if the clock is already past the `time`.
+    pub fn advance_to(&self, time: Duration) {
+        while self.fire_next_until(time) {}
+        let mut data = self.data();
+        data.now = data.now.max(time);
+    }
+
+    fn data(&self) -> MutexGuard<'_, ClockData> {
+        // A panic while holding the lock cannot leave the data inconsistent.
+        self.data.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn set_callback(&self, delay: Duration, callback: Callback) -> TimerId {
+        let id = TimerId::next();
+        CALLBACKS.with(|callbacks| callbacks.borrow_mut().insert(id, callback));
+        let mut data = self.data();
+        let deadline = data.now + delay;
+        data.insert(deadline, id, Timer::Callback(std::thread::current().id()));
+        id
+    }
+
+    /// Call the callback of the fired timer set on the current thread, rescheduling it if it is
+    /// repeating.
+    fn call(&self, deadline: Duration, id: TimerId) {
+        let callback = CALLBACKS.with(|callbacks| {
+            let mut callbacks = callbacks.borrow_mut();
+            match callbacks.get(&id) {
+                Some(Callback::Repeat { period, callback }) => {
+                    let next_deadline = deadline + *period;
+                    let timer = Timer::Callback(std::thread::current().id());
+                    self.data().insert(next_deadline, id, timer);
+                    Some(Callback::Repeat { period: *period, callback: callback.clone() })
+                }
+                _ => callbacks.remove(&id),
+            }
+        });
+        match callback {
+            Some(Callback::Once(callback)) => callback(),
+            Some(Callback::Repeat { callback, .. }) => callback(),
+            None => {}
+        }
+    }
+
+    /// Wake the task at the `deadline`, unless the deadline has already passed. Returns the
+    /// registered timer.
+    fn wake_at(&self, deadline: Duration, waker: &Waker) -> Option<TimerId> {
+        let mut data = self.data();
+        (data.now < deadline).then(|| {
+            let id = TimerId::next();
+            data.insert(deadline, id, Timer::Wake(waker.clone()));
+            id
+        })
+    }
+}
+
+
+
+// =============
+// === Sleep ===
+// =============
+
+/// A future returned by [`VirtualClock::sleep`].
+#[derive(Debug)]
+pub struct Sleep {
+    clock:    VirtualClock,
+    deadline: Duration,
+    timer:    Option<TimerId>,
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if let Some(timer) = self.timer.take() {
+            self.clock.clear(timer);
+        }
+        self.timer = self.clock.wake_at(self.deadline, cx.waker());
+        match self.timer {
+            Some(_) => Poll::Pending,
+            None => Poll::Ready(()),
+        }
+    }
+}
+
+impl Drop for Sleep {
+    fn drop(&mut self) {
+        if let Some(timer) = self.timer.take() {
+            self.clock.clear(timer);
+        }
+    }
+}
@@ -37,6 +37,7 @@ use wasm_bindgen::prelude::wasm_bindgen;
 
 pub mod binding;
 pub mod clipboard;
+pub mod clock;
 pub mod closure;
 pub mod event;
 pub mod platform;
@@ -1035,19 +1036,37 @@ fn report_panic(info: &std::panic::PanicInfo) {
 // === Sleep ===
 // =============
 
-#[cfg(target_arch = "wasm32")]
 /// Sleeps for the specified amount of time.
 ///
 /// This function might sleep for slightly longer than the specified duration but never less. This
 /// function is an async version of std::thread::sleep, its timer starts just after the function
 /// call.
-pub async fn sleep(duration: Duration) {
-    use gloo_timers::future::TimeoutFuture;
-    TimeoutFuture::new(duration.as_millis() as u32).await
+///
+/// If a [`clock::VirtualClock`] is installed for the current thread, the sleep completes once that
+/// clock is advanced by the `duration` instead, counting from the clock time of the function call.
+pub fn sleep(duration: Duration) -> impl std::future::Future<Output = ()> {
+    let virtual_sleep = clock::VirtualClock::current().map(|clock| clock.sleep(duration));
+    let real_time_sleep = virtual_sleep.is_none().then(|| real_time_sleep(duration));
+    async move {
+        if let Some(sleep) = virtual_sleep {
+            sleep.await
+        }
+        if let Some(sleep) = real_time_sleep {
+            sleep.await
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn real_time_sleep(duration: Duration) -> impl std::future::Future<Output = ()> {
+    gloo_timers::future::TimeoutFuture::new(duration.as_millis() as u32)
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-pub use async_std::task::sleep;
+fn real_time_sleep(duration: Duration) -> impl std::future::Future<Output = ()> {
+    let deadline = Instant::now() + duration;
+    async move { async_std::task::sleep(deadline.saturating_duration_since(Instant::now())).await }
+}
 
 
 